@@ -0,0 +1,103 @@
+use beacon_chain::events::{EventHandler, EventKind, EventTopic, NullEventHandler};
+use beacon_chain::test_utils::{
+    AttestationStrategy, BeaconChainHarness, BlockStrategy, HarnessType,
+};
+use criterion::{black_box, criterion_group, criterion_main, Benchmark, Criterion};
+use std::sync::Arc;
+use types::{
+    test_utils::{generate_deterministic_keypairs, SeedableRng, TestRandom, XorShiftRng},
+    Hash256, MainnetEthSpec, MinimalEthSpec, SignedBeaconBlock,
+};
+
+type E = MainnetEthSpec;
+
+/// The number of validators in the chain used by the head benchmarks.
+const HEAD_VALIDATOR_COUNT: usize = 1_024;
+
+fn all_benches(c: &mut Criterion) {
+    let mut rng = XorShiftRng::from_seed([42; 16]);
+    let block = SignedBeaconBlock::<E>::random_for_test(&mut rng);
+    let handler = NullEventHandler::<E>::default();
+
+    // Emulates the block import events prior to `EventHandler::wants`, where the block is always
+    // cloned for the event.
+    let inner_block = block.clone();
+    let inner_handler = NullEventHandler::<E>::default();
+    c.bench(
+        "block_imported_event",
+        Benchmark::new("unconditional", move |b| {
+            b.iter(|| {
+                let _ = inner_handler.register(EventKind::BeaconBlockImported {
+                    block_root: Hash256::zero(),
+                    block: Box::new(black_box(&inner_block).clone()),
+                });
+            })
+        }),
+    );
+
+    c.bench(
+        "block_imported_event",
+        Benchmark::new("declined_by_handler", move |b| {
+            b.iter(|| {
+                if handler.wants(EventTopic::Block) {
+                    let _ = handler.register(EventKind::BeaconBlockImported {
+                        block_root: Hash256::zero(),
+                        block: Box::new(black_box(&block).clone()),
+                    });
+                }
+            })
+        }),
+    );
+}
+
+fn head_benches(c: &mut Criterion) {
+    let harness: BeaconChainHarness<HarnessType<MinimalEthSpec>> = BeaconChainHarness::new(
+        MinimalEthSpec,
+        generate_deterministic_keypairs(HEAD_VALIDATOR_COUNT),
+    );
+    harness.advance_slot();
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let harness = Arc::new(harness);
+
+    // Emulates `BeaconChain::head` prior to the head being stored as a shared snapshot, where the
+    // block and state were cloned for every caller.
+    let inner_harness = harness.clone();
+    c.bench(
+        "head",
+        Benchmark::new("deep_clone", move |b| {
+            b.iter(|| {
+                black_box(
+                    inner_harness
+                        .chain
+                        .head()
+                        .expect("should get head")
+                        .as_ref()
+                        .clone(),
+                )
+            })
+        })
+        .sample_size(10),
+    );
+
+    let inner_harness = harness.clone();
+    c.bench(
+        "head",
+        Benchmark::new("shared_snapshot", move |b| {
+            b.iter(|| black_box(inner_harness.chain.head().expect("should get head")))
+        }),
+    );
+
+    c.bench(
+        "head",
+        Benchmark::new("head_info", move |b| {
+            b.iter(|| black_box(harness.chain.head_info().expect("should get head info")))
+        }),
+    );
+}
+
+criterion_group!(benches, all_benches, head_benches);
+criterion_main!(benches);
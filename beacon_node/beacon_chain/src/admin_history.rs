@@ -0,0 +1,151 @@
+use parking_lot::Mutex;
+use serde_derive::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use store::{DBColumn, Error as StoreError, SimpleStoreItem, Store};
+use types::{EthSpec, Hash256};
+
+/// The version of this crate, recorded alongside each event.
+pub const SOFTWARE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A record of an administrative override (e.g., a checkpoint start or a snapshot import) applied
+/// to the chain by the user.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct AdminEvent {
+    /// The unix timestamp (seconds) at which the override was applied.
+    pub timestamp: u64,
+    /// A short, stable name for the override (e.g., `import_snapshot`).
+    pub action: String,
+    /// A human-readable description of the parameters supplied to the override.
+    pub parameters: String,
+    /// The version of the software which applied the override.
+    pub software_version: String,
+}
+
+impl AdminEvent {
+    /// Returns a new event, timestamped with the present system time.
+    pub fn new(action: String, parameters: String) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_else(|_| 0),
+            action,
+            parameters,
+            software_version: SOFTWARE_VERSION.to_string(),
+        }
+    }
+}
+
+impl SimpleStoreItem for AdminEvent {
+    fn db_column() -> DBColumn {
+        DBColumn::ChainEvents
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("admin event should serialize")
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        serde_json::from_slice(bytes).map_err(|e| StoreError::DBError {
+            message: format!("Unable to decode admin event: {:?}", e),
+        })
+    }
+}
+
+/// The number of `AdminEvent`s in the database.
+struct AdminHistoryLength(u64);
+
+impl SimpleStoreItem for AdminHistoryLength {
+    fn db_column() -> DBColumn {
+        DBColumn::ChainEvents
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        let mut length = [0; 8];
+
+        if bytes.len() != length.len() {
+            return Err(StoreError::DBError {
+                message: format!("Invalid admin history length: {} bytes", bytes.len()),
+            });
+        }
+
+        length.copy_from_slice(bytes);
+
+        Ok(AdminHistoryLength(u64::from_le_bytes(length)))
+    }
+}
+
+/// The key of the `AdminHistoryLength`. Events are keyed by their index plus one.
+fn length_key() -> Hash256 {
+    Hash256::zero()
+}
+
+fn event_key(index: u64) -> Hash256 {
+    Hash256::from_low_u64_be(index + 1)
+}
+
+/// An append-only log of `AdminEvent`s, persisted in the `ChainEvents` column of the database.
+///
+/// Each event is written before the length is incremented, so a crash mid-way through an append
+/// leaves the existing history intact.
+pub struct AdminHistory {
+    length: Mutex<u64>,
+}
+
+impl AdminHistory {
+    /// Reads the number of events from `store`, returning an empty history if there is none.
+    pub fn load<E: EthSpec, S: Store<E>>(store: &S) -> Result<Self, StoreError> {
+        let length = store
+            .get::<AdminHistoryLength>(&length_key())?
+            .map_or(0, |length| length.0);
+
+        Ok(Self {
+            length: Mutex::new(length),
+        })
+    }
+
+    /// Returns the total number of events that have been recorded.
+    pub fn num_events(&self) -> u64 {
+        *self.length.lock()
+    }
+
+    /// Writes `event` to `store`, after all previously recorded events.
+    pub fn append<E: EthSpec, S: Store<E>>(
+        &self,
+        store: &S,
+        event: &AdminEvent,
+    ) -> Result<(), StoreError> {
+        let mut length = self.length.lock();
+
+        store.put(&event_key(*length), event)?;
+        store.put(&length_key(), &AdminHistoryLength(*length + 1))?;
+
+        *length += 1;
+
+        Ok(())
+    }
+
+    /// Returns the most recent `count` events, in the order they were recorded.
+    pub fn latest<E: EthSpec, S: Store<E>>(
+        &self,
+        store: &S,
+        count: usize,
+    ) -> Result<Vec<AdminEvent>, StoreError> {
+        let length = self.num_events();
+        let start = length.saturating_sub(count as u64);
+
+        (start..length)
+            .map(|index| {
+                store
+                    .get::<AdminEvent>(&event_key(index))?
+                    .ok_or_else(|| StoreError::DBError {
+                        message: format!("Missing admin event {}", index),
+                    })
+            })
+            .collect()
+    }
+}
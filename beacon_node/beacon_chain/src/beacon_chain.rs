@@ -1,33 +1,61 @@
+use crate::admin_history::{AdminEvent, AdminHistory};
+use crate::block_verification::{GossipBlockError, GossipVerifiedBlock, IntoBlockForImport};
+use crate::chain_snapshot::SnapshotRecord;
+use crate::chain_stats::{ChainStats, ChainStatsSnapshot};
 use crate::checkpoint::CheckPoint;
-use crate::errors::{BeaconChainError as Error, BlockProductionError};
+use crate::errors::{BeaconChainError as Error, BlockProductionError, OperationImportError};
 use crate::eth1_chain::{Eth1Chain, Eth1ChainBackend};
-use crate::events::{EventHandler, EventKind};
+use crate::events::{EventHandler, EventKind, EventTopic};
+use crate::finalization_info_cache::FinalizationInfoCache;
 use crate::fork_choice::{Error as ForkChoiceError, ForkChoice};
 use crate::head_tracker::HeadTracker;
+use crate::invalid_block_dump::InvalidBlockDump;
+use crate::latest_messages::{LatestMessage, LatestMessagesIter};
+use crate::local_attestations::{LocalAttestationCorrectness, LocalAttestations};
 use crate::metrics;
+use crate::observed_attestations::ObservedAttestations;
+use crate::observed_attester_votes::ObservedAttesterVotes;
+use crate::observed_block_proposers::ObservedBlockProposers;
+use crate::op_pool_persistence::OpPoolPersistence;
 use crate::persisted_beacon_chain::PersistedBeaconChain;
+use crate::produced_block_cache::ProducedBlockCache;
+use crate::readiness::{DegradedReason, Readiness, ReadinessConfig, SyncStatus};
 use crate::shuffling_cache::ShufflingCache;
-use crate::timeout_rw_lock::TimeoutRwLock;
+use crate::state_skip_cache::StateSkipCache;
+use crate::timeout_rw_lock::{LockTimeouts, TimeoutRwLock};
+use crate::validator_monitor::ValidatorMonitor;
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
-use operation_pool::{OperationPool, PersistedOperationPool};
+use futures::Stream;
+use operation_pool::{AttestationObservation, OperationPool};
+use parking_lot::{Mutex, RwLock};
+use proto_array_fork_choice::ForkChoiceNode;
 use slog::{debug, error, info, trace, warn, Logger};
 use slot_clock::SlotClock;
-use ssz::Encode;
+use ssz::{Decode, Encode};
 use state_processing::per_block_processing::errors::{
     AttestationValidationError, AttesterSlashingValidationError, ExitValidationError,
     ProposerSlashingValidationError,
 };
 use state_processing::{
-    common::get_indexed_attestation, per_block_processing, per_slot_processing,
-    signature_sets::indexed_attestation_signature_set_from_pubkeys, BlockProcessingError,
-    BlockSignatureStrategy,
+    common::{get_base_reward, get_indexed_attestation},
+    per_block_processing,
+    per_block_processing::{
+        verify_attester_slashing, verify_exit_time_independent_only, verify_proposer_slashing,
+    },
+    per_epoch_processing::ValidatorStatuses,
+    per_slot_processing,
+    signature_sets::{
+        attestation_signing_root, block_proposal_signature_set, block_signing_root,
+        indexed_attestation_signature_set_from_pubkeys, randao_signature_set,
+    },
+    BlockProcessingError, BlockSignatureStrategy, VerifySignatures,
 };
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::fs;
+use std::collections::HashSet;
 use std::io::prelude::*;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use store::iter::{
     BlockRootsIterator, ReverseBlockRootIterator, ReverseStateRootIterator, StateRootsIterator,
 };
@@ -41,31 +69,22 @@ use types::*;
 //                          |-------must be this long------|
 pub const GRAFFITI: &str = "sigp/lighthouse-0.1.1-prerelease";
 
-/// If true, everytime a block is processed the pre-state, post-state and block are written to SSZ
-/// files in the temp directory.
-///
-/// Only useful for testing.
-const WRITE_BLOCK_PROCESSING_SSZ: bool = cfg!(feature = "write_ssz_files");
-
 /// Maximum block slot number. Block with slots bigger than this constant will NOT be processed.
 const MAXIMUM_BLOCK_SLOT_NUMBER: u64 = 4_294_967_296; // 2^32
 
-/// The time-out before failure during an operation to take a read/write RwLock on the canonical
-/// head.
-const HEAD_LOCK_TIMEOUT: Duration = Duration::from_secs(1);
-
-/// The time-out before failure during an operation to take a read/write RwLock on the
-/// attestation cache.
-const ATTESTATION_CACHE_LOCK_TIMEOUT: Duration = Duration::from_secs(1);
-
-/// The time-out before failure during an operation to take a read/write RwLock on the
-/// validator pubkey cache.
-const VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT: Duration = Duration::from_secs(1);
-
 pub const BEACON_CHAIN_DB_KEY: [u8; 32] = [0; 32];
 pub const OP_POOL_DB_KEY: [u8; 32] = [0; 32];
 pub const ETH1_CACHE_DB_KEY: [u8; 32] = [0; 32];
 pub const FORK_CHOICE_DB_KEY: [u8; 32] = [0; 32];
+pub const ANCHOR_DB_KEY: [u8; 32] = [0; 32];
+
+/// The default maximum number of slots that may be covered by a single block range query (see
+/// `BeaconChain::block_roots_in_range`).
+pub const DEFAULT_MAX_BLOCK_RANGE: u64 = 1024;
+
+/// The maximum number of epochs that may be covered by a single call to
+/// `BeaconChain::validator_balance_history`, each of which requires a state to be loaded.
+pub const MAX_BALANCE_HISTORY_EPOCHS: u64 = 256;
 
 #[derive(Debug, PartialEq)]
 pub enum BlockProcessingOutcome {
@@ -76,7 +95,8 @@ pub enum BlockProcessingOutcome {
         parent: Hash256,
         reference_location: &'static str,
     },
-    /// The block slot is greater than the present slot.
+    /// The block slot is greater than the present slot, even when allowing for the
+    /// `max_future_slot_tolerance`.
     FutureSlot {
         present_slot: Slot,
         block_slot: Slot,
@@ -101,6 +121,9 @@ pub enum BlockProcessingOutcome {
 #[derive(Debug, PartialEq)]
 pub enum AttestationProcessingOutcome {
     Processed,
+    /// An attestation with the same data and a superset of the aggregation bits has already been
+    /// processed this epoch.
+    AlreadyKnown,
     EmptyAggregationBitfield,
     UnknownHeadBlock {
         beacon_block_root: Hash256,
@@ -134,7 +157,25 @@ pub enum AttestationProcessingOutcome {
     Invalid(AttestationValidationError),
 }
 
+/// The outcome of processing a `SignedAggregateAndProof`.
+#[derive(Debug, PartialEq)]
+pub enum AggregateProcessingOutcome {
+    /// The aggregate was imported and the aggregator was entitled to publish it.
+    Processed,
+    /// The aggregate was not imported, for the given reason.
+    InvalidAggregate(AttestationProcessingOutcome),
+    /// The aggregator is not a member of the committee that produced the aggregate.
+    AggregatorNotInCommittee { aggregator_index: u64 },
+    /// The selection proof is not a signature of the aggregate's slot by the aggregator.
+    InvalidSelectionProof { aggregator_index: u64 },
+    /// The selection proof does not select the aggregator to aggregate for its committee.
+    NotAggregator { aggregator_index: u64 },
+    /// The `AggregateAndProof` was not signed by the aggregator.
+    InvalidSignature { aggregator_index: u64 },
+}
+
 /// Defines how a `BeaconState` should be "skipped" through skip-slots.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StateSkipConfig {
     /// Calculate the state root during each skip slot, producing a fully-valid `BeaconState`.
     WithStateRoots,
@@ -146,6 +187,35 @@ pub enum StateSkipConfig {
     WithoutStateRoots,
 }
 
+/// Defines whether a `BeaconChain` takes part in block production or only follows the chain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BeaconChainMode {
+    /// Imports blocks and operations and produces blocks (normal case).
+    Full,
+    /// Imports blocks and serves data, but never produces blocks. Operations (e.g., attestations
+    /// and exits) are processed but not queued in the op pool.
+    Follower,
+}
+
+impl Default for BeaconChainMode {
+    fn default() -> Self {
+        BeaconChainMode::Full
+    }
+}
+
+/// A block that has passed all of the checks required for import, along with the state that
+/// results from applying it to its parent.
+struct ValidatedBlock<E: EthSpec> {
+    block_root: Hash256,
+    state: BeaconState<E>,
+    state_root: Hash256,
+    /// The slot of the block's parent.
+    parent_slot: Slot,
+    /// The states of any skipped slots between the parent and the block.
+    intermediate_states: StateBatch<E>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct HeadInfo {
     pub slot: Slot,
     pub block_root: Hash256,
@@ -155,6 +225,105 @@ pub struct HeadInfo {
     pub fork: Fork,
 }
 
+impl HeadInfo {
+    pub(crate) fn from_checkpoint<E: EthSpec>(head: &CheckPoint<E>) -> Self {
+        Self {
+            slot: head.beacon_block.slot(),
+            block_root: head.beacon_block_root,
+            state_root: head.beacon_state_root,
+            current_justified_checkpoint: head.beacon_state.current_justified_checkpoint.clone(),
+            finalized_checkpoint: head.beacon_state.finalized_checkpoint.clone(),
+            fork: head.beacon_state.fork.clone(),
+        }
+    }
+}
+
+/// Describes one of the heads of the `BeaconChain`, including the checkpoints of its state.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HeadDetail {
+    pub block_root: Hash256,
+    pub slot: Slot,
+    pub state_root: Hash256,
+    pub current_justified_checkpoint: types::Checkpoint,
+    pub finalized_checkpoint: types::Checkpoint,
+}
+
+/// Describes when a block became finalized (see `BeaconChain::finalization_info_for_block`).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FinalizationInfo {
+    /// The slot of the block.
+    pub block_slot: Slot,
+    /// The earliest checkpoint finalized by the canonical chain which has the block as its root or
+    /// as an ancestor of its root.
+    pub finalized_checkpoint: types::Checkpoint,
+    /// The first epoch of the canonical chain at the start of which `finalized_checkpoint` was
+    /// finalized.
+    pub finalized_at_epoch: Epoch,
+}
+
+/// The canonical blocks within a range of slots (see `BeaconChain::block_roots_in_range`).
+#[derive(Debug, PartialEq, Clone)]
+pub struct BlockRange<T> {
+    /// An item for each block in the range, in ascending order of slot.
+    pub blocks: Vec<T>,
+    /// `true` if the range begins prior to the oldest block available to the chain (i.e., the
+    /// anchor of a chain started from a checkpoint), in which case there are no items for the
+    /// slots prior to it.
+    pub precedes_oldest_available: bool,
+}
+
+/// The block proposers for each slot of an epoch.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProposerDuties {
+    pub epoch: Epoch,
+    /// The root of the block at the last slot prior to `epoch` (or the genesis block root), on
+    /// the chain from which the proposers were computed. See
+    /// `BeaconChain::proposer_dependent_root`.
+    ///
+    /// The proposers depend on this block, so they must be re-computed if it is re-orged out.
+    pub dependent_root: Hash256,
+    /// The proposer index for each slot of `epoch`, in order of slot.
+    pub proposers: Vec<usize>,
+}
+
+/// The attestation duties of some validators during an epoch.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AttesterDuties {
+    pub epoch: Epoch,
+    /// The root of the block upon which the attester shuffling for `epoch` depends. See
+    /// `BeaconChain::shuffling_dependent_root`.
+    ///
+    /// The duties must be re-computed if this block is re-orged out.
+    pub dependent_root: Hash256,
+    /// The duties of each requested validator, or `None` if the validator is unknown or inactive.
+    pub duties: Vec<Option<AttestationDuty>>,
+}
+
+/// An estimate of the rewards for a timely attestation, see
+/// `BeaconChain::estimate_attestation_reward`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AttestationRewardEstimate {
+    pub validator_index: usize,
+    pub epoch: Epoch,
+    /// The base reward of the validator, from which each of the following rewards is derived.
+    pub base_reward: u64,
+    /// The reward for voting for the correct FFG source.
+    pub source_reward: u64,
+    /// The reward for voting for the correct FFG target.
+    pub target_reward: u64,
+    /// The reward for voting for the correct head.
+    pub head_reward: u64,
+    /// The reward for being included in the slot after the attestation (the minimum delay).
+    pub inclusion_delay_reward: u64,
+}
+
+impl AttestationRewardEstimate {
+    /// The sum of all rewards for the attestation.
+    pub fn total(&self) -> u64 {
+        self.source_reward + self.target_reward + self.head_reward + self.inclusion_delay_reward
+    }
+}
+
 pub trait BeaconChainTypes: Send + Sync + 'static {
     type Store: store::Store<Self::EthSpec>;
     type StoreMigrator: store::Migrate<Self::Store, Self::EthSpec>;
@@ -177,12 +346,26 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     /// Stores all operations (e.g., `Attestation`, `Deposit`, etc) that are candidates for
     /// inclusion in a block.
     pub op_pool: OperationPool<T::EthSpec>,
+    /// Tracks the snapshot and deltas used to persist `self.op_pool`.
+    pub(crate) op_pool_persistence: OpPoolPersistence,
     /// Provides information from the Ethereum 1 (PoW) chain.
     pub eth1_chain: Option<Eth1Chain<T::Eth1Chain, T::EthSpec, T::Store>>,
+    /// Whether this chain produces blocks or only follows the chain.
+    pub(crate) mode: BeaconChainMode,
     /// Stores a "snapshot" of the chain at the time the head-of-the-chain block was received.
-    pub(crate) canonical_head: TimeoutRwLock<CheckPoint<T::EthSpec>>,
+    ///
+    /// The snapshot is never modified, it is replaced with a new `Arc` when the head changes.
+    pub(crate) canonical_head: TimeoutRwLock<Arc<CheckPoint<T::EthSpec>>>,
     /// The root of the genesis block.
+    ///
+    /// If the chain was started from a trusted checkpoint, this is the root of the anchor block.
     pub genesis_block_root: Hash256,
+    /// The slot of the block from which the chain was started (zero if started from genesis).
+    pub(crate) anchor_slot: Slot,
+    /// The genesis time of the chain, which never changes.
+    pub(crate) genesis_time: u64,
+    /// The root of the validators in the genesis state, which never changes.
+    pub(crate) genesis_validators_root: Hash256,
     /// A state-machine that is updated with information from the network and chooses a canonical
     /// head block.
     pub fork_choice: ForkChoice<T>,
@@ -194,6 +377,46 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub(crate) shuffling_cache: TimeoutRwLock<ShufflingCache>,
     /// Caches a map of `validator_index -> validator_pubkey`.
     pub(crate) validator_pubkey_cache: TimeoutRwLock<ValidatorPubkeyCache>,
+    /// The time-outs for taking locks on the head and caches.
+    pub(crate) lock_timeouts: LockTimeouts,
+    /// The thresholds used to determine whether the node is ready to serve validator duties.
+    pub(crate) readiness_config: ReadinessConfig,
+    /// The maximum number of slots that may be skipped when advancing a state, if any.
+    pub(crate) max_skip_slots: Option<u64>,
+    /// The maximum number of slots that may be covered by a block range query.
+    pub(crate) max_block_range: u64,
+    /// How far before the start of its slot a block may be processed.
+    pub(crate) max_future_slot_tolerance: Duration,
+    /// Whether to cache the balances required by fork choice whenever a block is imported.
+    pub(crate) warm_balances_cache_on_import: bool,
+    /// Counts the operations performed since this `BeaconChain` was started.
+    pub(crate) chain_stats: ChainStats,
+    /// A durable log of the administrative overrides applied to the chain.
+    pub(crate) admin_history: AdminHistory,
+    /// Tracks whether the attestations produced by this node agreed with the canonical chain.
+    pub(crate) local_attestations: LocalAttestations,
+    /// The post-state of the most recent block produced by this node, used to import that block
+    /// without re-processing it.
+    pub(crate) produced_block_cache: ProducedBlockCache<T::EthSpec>,
+    /// The progress of the most recent state skip that ran out of time, used to resume it.
+    pub(crate) state_skip_cache: StateSkipCache<T::EthSpec>,
+    /// Writes blocks that fail processing to disk for debugging, if configured.
+    pub(crate) invalid_block_dump: Option<InvalidBlockDump>,
+    /// The `FinalizationInfo` of recently queried finalized blocks.
+    pub(crate) finalization_info_cache: Mutex<FinalizationInfoCache>,
+    /// The attestations processed during the current epoch, used to drop duplicates cheaply.
+    pub(crate) observed_attestations: ObservedAttestations<T::EthSpec>,
+    /// The recent votes of each validator, used to detect attester slashings. Slashing detection
+    /// is disabled if `None`.
+    pub(crate) observed_attester_votes: Option<ObservedAttesterVotes<T::EthSpec>>,
+    /// The first block header signed by each proposer at each recent slot, used to detect
+    /// proposer slashings. Slashing detection is disabled if `None`.
+    pub(crate) observed_block_proposers: Option<ObservedBlockProposers>,
+    /// Reports the block proposals and attestation inclusions of validators chosen by the
+    /// operator.
+    pub validator_monitor: ValidatorMonitor,
+    /// A summary of the head, refreshed after each head change by fork choice.
+    pub(crate) cached_head_info: RwLock<HeadInfo>,
     /// Logging to CLI, etc.
     pub(crate) log: Logger,
 }
@@ -214,7 +437,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     pub fn persist_head_and_fork_choice(&self) -> Result<(), Error> {
         let canonical_head_block_root = self
             .canonical_head
-            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .try_read_for(self.lock_timeouts.head)
             .ok_or_else(|| Error::CanonicalHeadLockTimeout)?
             .beacon_block_root;
 
@@ -226,10 +449,13 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         let fork_choice_timer = metrics::start_timer(&metrics::PERSIST_FORK_CHOICE);
 
-        self.store.put(
-            &Hash256::from_slice(&FORK_CHOICE_DB_KEY),
-            &self.fork_choice.as_ssz_container(),
-        )?;
+        let fork_choice = self.fork_choice.as_ssz_container();
+        metrics::set_gauge(
+            &metrics::FORK_CHOICE_PERSISTED_BYTES,
+            fork_choice.ssz_bytes_len() as i64,
+        );
+        self.store
+            .put(&Hash256::from_slice(&FORK_CHOICE_DB_KEY), &fork_choice)?;
 
         metrics::stop_timer(fork_choice_timer);
         let head_timer = metrics::start_timer(&metrics::PERSIST_HEAD);
@@ -242,19 +468,36 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         Ok(())
     }
 
-    /// Persists `self.op_pool` to disk.
+    /// Persists the changes to `self.op_pool` since it was last persisted to disk.
+    ///
+    /// ## Notes
+    ///
+    /// Only the operations that changed are written, except for the first persist and every
+    /// `OP_POOL_DELTAS_PER_SNAPSHOT` persists thereafter, which write the entire pool (see
+    /// `Self::persist_op_pool_snapshot`).
+    pub fn persist_op_pool(&self) -> Result<(), Error> {
+        let timer = metrics::start_timer(&metrics::PERSIST_OP_POOL);
+
+        self.op_pool_persistence
+            .persist(&*self.store, &self.op_pool)?;
+
+        metrics::stop_timer(timer);
+
+        Ok(())
+    }
+
+    /// Persists the entire `self.op_pool` to disk, replacing the changes persisted by
+    /// `Self::persist_op_pool`.
     ///
     /// ## Notes
     ///
     /// This operation is typically slow and causes a lot of allocations. It should be used
     /// sparingly.
-    pub fn persist_op_pool(&self) -> Result<(), Error> {
+    pub fn persist_op_pool_snapshot(&self) -> Result<(), Error> {
         let timer = metrics::start_timer(&metrics::PERSIST_OP_POOL);
 
-        self.store.put(
-            &Hash256::from_slice(&OP_POOL_DB_KEY),
-            &PersistedOperationPool::from_operation_pool(&self.op_pool),
-        )?;
+        self.op_pool_persistence
+            .persist_snapshot(&*self.store, &self.op_pool)?;
 
         metrics::stop_timer(timer);
 
@@ -277,6 +520,19 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         Ok(())
     }
 
+    /// Persists the head, fork choice, operation pool and eth1 cache to disk.
+    ///
+    /// Each component is persisted even if persisting another fails, in which case the first error
+    /// is returned. Useful for saving the chain at a controlled point (e.g., prior to shutdown)
+    /// and learning whether it succeeded, rather than relying upon `Drop`.
+    pub fn persist_all(&self) -> Result<(), Error> {
+        let head_and_fork_choice = self.persist_head_and_fork_choice();
+        let op_pool = self.persist_op_pool();
+        let eth1_cache = self.persist_eth1_cache();
+
+        head_and_fork_choice.and(op_pool).and(eth1_cache)
+    }
+
     /// Returns the slot _right now_ according to `self.slot_clock`. Returns `Err` if the slot is
     /// unavailable.
     ///
@@ -286,6 +542,31 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         self.slot_clock.now().ok_or_else(|| Error::UnableToReadSlot)
     }
 
+    /// Returns the latest slot from which a block may be processed _right now_, allowing for the
+    /// blocks of slots which start within `max_future_slot_tolerance`.
+    pub fn latest_permissible_slot(&self) -> Result<Slot, Error> {
+        let present_slot = self.slot()?;
+        let duration_to_next_slot = self
+            .slot_clock
+            .duration_to_next_slot()
+            .ok_or_else(|| Error::UnableToReadSlot)?;
+
+        let tolerated_slots = self
+            .max_future_slot_tolerance
+            .checked_sub(duration_to_next_slot)
+            .map_or(0, |remaining| {
+                let slot_millis = self.slot_clock.slot_duration().as_millis();
+                let further_slots = if slot_millis == 0 {
+                    0
+                } else {
+                    (remaining.as_millis() / slot_millis) as u64
+                };
+                1 + further_slots
+            });
+
+        Ok(present_slot + tolerated_slots)
+    }
+
     /// Returns the epoch _right now_ according to `self.slot_clock`. Returns `Err` if the epoch is
     /// unavailable.
     ///
@@ -304,7 +585,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// `slot` always decreases by `1`.
     /// - Skipped slots contain the root of the closest prior
     ///     non-skipped slot (identical to the way they are stored in `state.block_roots`) .
-    /// - Iterator returns `(Hash256, Slot)`.
+    /// - Iterator returns `Result<(Hash256, Slot), store::Error>`, ending after the first error.
     /// - As this iterator starts at the `head` of the chain (viz., the best block), the first slot
     ///     returned may be earlier than the wall-clock slot.
     pub fn rev_iter_block_roots(
@@ -312,7 +593,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     ) -> Result<ReverseBlockRootIterator<T::EthSpec, T::Store>, Error> {
         let head = self.head()?;
 
-        let iter = BlockRootsIterator::owned(self.store.clone(), head.beacon_state);
+        let iter = BlockRootsIterator::owned(
+            self.store.clone(),
+            head.beacon_state.clone_with_only_committee_caches(),
+        );
 
         Ok(ReverseBlockRootIterator::new(
             (head.beacon_block_root, head.beacon_block.slot()),
@@ -329,10 +613,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         Ok(T::Store::forwards_block_roots_iterator(
             self.store.clone(),
             start_slot,
-            local_head.beacon_state,
+            local_head.beacon_state.clone_with_only_committee_caches(),
             local_head.beacon_block_root,
             &self.spec,
-        ))
+        )?)
     }
 
     /// Traverse backwards from `block_root` to find the block roots of its ancestors.
@@ -342,7 +626,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// `slot` always decreases by `1`.
     /// - Skipped slots contain the root of the closest prior
     ///     non-skipped slot (identical to the way they are stored in `state.block_roots`) .
-    /// - Iterator returns `(Hash256, Slot)`.
+    /// - Iterator returns `Result<(Hash256, Slot), store::Error>`, ending after the first error.
     /// - The provided `block_root` is included as the first item in the iterator.
     pub fn rev_iter_block_roots_from(
         &self,
@@ -362,15 +646,18 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     }
 
     /// Traverse backwards from `block_root` to find the root of the ancestor block at `slot`.
+    ///
+    /// Returns `None` if `slot` is prior to the anchor of the chain.
     pub fn get_ancestor_block_root(
         &self,
         block_root: Hash256,
         slot: Slot,
     ) -> Result<Option<Hash256>, Error> {
-        Ok(self
-            .rev_iter_block_roots_from(block_root)?
-            .find(|(_, ancestor_slot)| *ancestor_slot == slot)
-            .map(|(ancestor_block_root, _)| ancestor_block_root))
+        if slot < self.anchor_slot {
+            return Ok(None);
+        }
+
+        find_root_at_slot(self.rev_iter_block_roots_from(block_root)?, slot)
     }
 
     /// Iterates across all `(state_root, slot)` pairs from the head of the chain (inclusive) to
@@ -379,7 +666,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// ## Notes
     ///
     /// `slot` always decreases by `1`.
-    /// - Iterator returns `(Hash256, Slot)`.
+    /// - Iterator returns `Result<(Hash256, Slot), store::Error>`, ending after the first error.
     /// - As this iterator starts at the `head` of the chain (viz., the best block), the first slot
     ///     returned may be earlier than the wall-clock slot.
     pub fn rev_iter_state_roots(
@@ -388,7 +675,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         let head = self.head()?;
         let slot = head.beacon_state.slot;
 
-        let iter = StateRootsIterator::owned(self.store.clone(), head.beacon_state);
+        let iter = StateRootsIterator::owned(
+            self.store.clone(),
+            head.beacon_state.clone_with_only_committee_caches(),
+        );
 
         Ok(ReverseStateRootIterator::new(
             (head.beacon_state_root, slot),
@@ -405,18 +695,117 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         &self,
         slot: Slot,
     ) -> Result<Option<SignedBeaconBlock<T::EthSpec>>, Error> {
-        let root = self
-            .rev_iter_block_roots()?
-            .find(|(_, this_slot)| *this_slot == slot)
-            .map(|(root, _)| root);
-
-        if let Some(block_root) = root {
+        if let Some(block_root) = self.root_at_slot(slot)? {
             Ok(self.store.get(&block_root)?)
         } else {
             Ok(None)
         }
     }
 
+    /// Returns the `(block_root, slot)` of each block in the canonical chain with a slot in the
+    /// range `start_slot..start_slot + count`, in ascending order of slot.
+    ///
+    /// ## Notes
+    ///
+    /// - `count` is limited to the configured `max_block_range`.
+    /// - The range is truncated at the head, so it may contain fewer than `count` slots.
+    /// - Blocks prior to the anchor of a chain started from a checkpoint are unavailable, which is
+    ///     indicated by `BlockRange::precedes_oldest_available`.
+    pub fn block_roots_in_range(
+        &self,
+        start_slot: Slot,
+        count: u64,
+    ) -> Result<BlockRange<(Hash256, Slot)>, Error> {
+        if count > self.max_block_range {
+            debug!(
+                self.log,
+                "Clamped block range";
+                "start_slot" => start_slot,
+                "requested" => count,
+                "max_block_range" => self.max_block_range,
+            );
+        }
+
+        let end_slot = start_slot + std::cmp::min(count, self.max_block_range);
+        let precedes_oldest_available = start_slot < self.anchor_slot;
+        let start_slot = std::cmp::max(start_slot, self.anchor_slot);
+
+        if start_slot >= end_slot || start_slot > self.head_info()?.slot {
+            return Ok(BlockRange {
+                blocks: vec![],
+                precedes_oldest_available,
+            });
+        }
+
+        // Skipped slots repeat the root of the prior block, so iteration starts at the slot prior
+        // to the range in order to exclude a block from before the range.
+        let iter_start_slot = if start_slot > self.anchor_slot {
+            start_slot - 1
+        } else {
+            start_slot
+        };
+
+        let mut prev_root = None;
+        let mut blocks = vec![];
+        for result in self
+            .forwards_iter_block_roots(iter_start_slot)?
+            .take_while(|result| match result {
+                Ok((_, slot)) => *slot < end_slot,
+                Err(_) => true,
+            })
+        {
+            let (root, slot) = result?;
+
+            if slot >= start_slot && prev_root != Some(root) {
+                blocks.push((root, slot));
+            }
+            prev_root = Some(root);
+        }
+
+        Ok(BlockRange {
+            blocks,
+            precedes_oldest_available,
+        })
+    }
+
+    /// As per `Self::block_roots_in_range`, but returns each block along with its root.
+    pub fn blocks_in_range(
+        &self,
+        start_slot: Slot,
+        count: u64,
+    ) -> Result<BlockRange<(Hash256, SignedBeaconBlock<T::EthSpec>)>, Error> {
+        let range = self.block_roots_in_range(start_slot, count)?;
+        let roots = range
+            .blocks
+            .into_iter()
+            .map(|(root, _slot)| root)
+            .collect::<Vec<_>>();
+        let blocks = self.get_blocks(&roots)?;
+
+        Ok(BlockRange {
+            blocks: roots.into_iter().zip(blocks).collect(),
+            precedes_oldest_available: range.precedes_oldest_available,
+        })
+    }
+
+    /// Returns the block at each of the given roots, in the same order, reading them from the
+    /// store as a single batch.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `Error::MissingBeaconBlock` if any of the blocks is unknown, or a database error.
+    pub fn get_blocks(
+        &self,
+        block_roots: &[Hash256],
+    ) -> Result<Vec<SignedBeaconBlock<T::EthSpec>>, Error> {
+        self.store
+            .get_blocks(block_roots)?
+            .into_iter()
+            .zip(block_roots)
+            .map(|(block, root)| block.ok_or_else(|| Error::MissingBeaconBlock(*root)))
+            .collect()
+    }
+
     /// Returns the block at the given root, if any.
     ///
     /// ## Errors
@@ -463,197 +852,1331 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         )?)
     }
 
+    /// Returns the genesis time of the chain.
+    ///
+    /// Does not take a lock on the head.
+    pub fn genesis_time(&self) -> u64 {
+        self.genesis_time
+    }
+
+    /// Returns the tree hash root of the validators in the genesis state, for use in signing
+    /// domains.
+    ///
+    /// Does not take a lock on the head.
+    pub fn genesis_validators_root(&self) -> Hash256 {
+        self.genesis_validators_root
+    }
+
+    /// Returns the slot of the block from which the chain was started, which is zero unless the
+    /// chain was started from a trusted checkpoint. No block at or prior to this slot is imported.
+    pub fn anchor_slot(&self) -> Slot {
+        self.anchor_slot
+    }
+
     /// Returns a `Checkpoint` representing the head block and state. Contains the "best block";
     /// the head of the canonical `BeaconChain`.
     ///
     /// It is important to note that the `beacon_state` returned may not match the present slot. It
     /// is the state as it was when the head block was received, which could be some slots prior to
     /// now.
-    pub fn head(&self) -> Result<CheckPoint<T::EthSpec>, Error> {
+    ///
+    /// The returned snapshot is shared with the `BeaconChain` (it is not cloned), so it is cheap to
+    /// obtain. Use `BeaconState::clone_with_only_committee_caches` when an owned state is required.
+    pub fn head(&self) -> Result<Arc<CheckPoint<T::EthSpec>>, Error> {
         self.canonical_head
-            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .try_read_for(self.lock_timeouts.head)
             .ok_or_else(|| Error::CanonicalHeadLockTimeout)
-            .map(|v| v.clone_with_only_committee_caches())
+            .map(|head| head.clone())
     }
 
     /// Returns info representing the head block and state.
     ///
     /// A summarized version of `Self::head` that involves less cloning.
     pub fn head_info(&self) -> Result<HeadInfo, Error> {
-        let head = self
-            .canonical_head
-            .try_read_for(HEAD_LOCK_TIMEOUT)
-            .ok_or_else(|| Error::CanonicalHeadLockTimeout)?;
+        self.canonical_head
+            .try_read_for(self.lock_timeouts.head)
+            .ok_or_else(|| Error::CanonicalHeadLockTimeout)
+            .map(|head| HeadInfo::from_checkpoint(&head))
+    }
 
-        Ok(HeadInfo {
-            slot: head.beacon_block.slot(),
-            block_root: head.beacon_block_root,
-            state_root: head.beacon_state_root,
-            current_justified_checkpoint: head.beacon_state.current_justified_checkpoint.clone(),
-            finalized_checkpoint: head.beacon_state.finalized_checkpoint.clone(),
-            fork: head.beacon_state.fork.clone(),
-        })
+    /// Returns the `(justified, finalized)` checkpoints that fork choice uses to find the head.
+    ///
+    /// These are usually those of the head state (i.e., `Self::head_info`), however they may
+    /// differ whilst a block which updated the fork choice checkpoints is not yet the head, or
+    /// whilst fork choice is delaying an update to the justified checkpoint.
+    pub fn fork_choice_checkpoints(&self) -> (Checkpoint, Checkpoint) {
+        self.fork_choice.checkpoints()
     }
 
-    /// Returns the current heads of the `BeaconChain`. For the canonical head, see `Self::head`.
+    /// As per `Self::head_info`, but returns `None` immediately (rather than waiting) if the head
+    /// lock is held by a writer (e.g., during a head update).
     ///
-    /// Returns `(block_root, block_slot)`.
-    pub fn heads(&self) -> Vec<(Hash256, Slot)> {
-        self.head_tracker.heads()
+    /// Useful for latency-sensitive callers that can fall back to `Self::head_info_cached`.
+    pub fn try_head_info_now(&self) -> Option<HeadInfo> {
+        self.canonical_head
+            .try_read()
+            .map(|head| HeadInfo::from_checkpoint(&head))
     }
 
-    /// Returns the `BeaconState` at the given slot.
+    /// Returns the head as it was after the most recent head change by fork choice (or when the
+    /// `BeaconChain` was built), without touching the head lock.
     ///
-    /// Returns `None` when the state is not found in the database or there is an error skipping
-    /// to a future state.
-    pub fn state_at_slot(
-        &self,
-        slot: Slot,
-        config: StateSkipConfig,
-    ) -> Result<BeaconState<T::EthSpec>, Error> {
-        let head_state = self.head()?.beacon_state;
+    /// The snapshot is updated immediately after the head, so it may briefly trail `Self::head_info`.
+    pub fn head_info_cached(&self) -> HeadInfo {
+        self.cached_head_info.read().clone()
+    }
 
-        match slot.cmp(&head_state.slot) {
-            Ordering::Equal => Ok(head_state),
-            Ordering::Greater => {
-                if slot > head_state.slot + T::EthSpec::slots_per_epoch() {
-                    warn!(
-                        self.log,
-                        "Skipping more than an epoch";
-                        "head_slot" => head_state.slot,
-                        "request_slot" => slot
-                    )
-                }
+    /// Returns the number of times that the lock on the canonical head has been acquired since the
+    /// `BeaconChain` was built.
+    ///
+    /// Useful for diagnosing contention on the head lock.
+    pub fn head_lock_acquisitions(&self) -> u64 {
+        self.canonical_head.acquisitions()
+    }
 
-                let start_slot = head_state.slot;
-                let task_start = Instant::now();
-                let max_task_runtime = Duration::from_millis(self.spec.milliseconds_per_slot);
+    /// Returns the number of slots by which the canonical head is ahead of the slot clock, or `0`
+    /// if it is not ahead.
+    ///
+    /// A non-zero value indicates that blocks were imported whilst the system clock was fast. Until
+    /// the clock catches up, blocks and attestations will not be produced (however, blocks and
+    /// attestations will still be imported).
+    pub fn head_in_future_slots(&self) -> Result<u64, Error> {
+        let head_slot = self.head_info()?.slot;
+        let current_slot = self.slot()?;
+
+        Ok(head_slot.as_u64().saturating_sub(current_slot.as_u64()))
+    }
 
-                let head_state_slot = head_state.slot;
-                let mut state = head_state;
+    /// Returns `Err(Error::HeadInFuture)` if the head at `head_slot` is ahead of the slot clock.
+    fn check_head_not_in_future(&self, head_slot: Slot) -> Result<(), Error> {
+        let current_slot = self.slot()?;
 
-                let skip_state_root = match config {
-                    StateSkipConfig::WithStateRoots => None,
-                    StateSkipConfig::WithoutStateRoots => Some(Hash256::zero()),
-                };
+        if head_slot > current_slot {
+            metrics::set_gauge(
+                &metrics::HEAD_IN_FUTURE_SLOTS,
+                (head_slot - current_slot).as_u64() as i64,
+            );
 
-                while state.slot < slot {
-                    // Do not allow and forward state skip that takes longer than the maximum task duration.
-                    //
-                    // This is a protection against nodes doing too much work when they're not synced
-                    // to a chain.
-                    if task_start + max_task_runtime < Instant::now() {
-                        return Err(Error::StateSkipTooLarge {
-                            start_slot,
-                            requested_slot: slot,
-                            max_task_runtime,
-                        });
-                    }
+            Err(Error::HeadInFuture {
+                head_slot,
+                current_slot,
+            })
+        } else {
+            Ok(())
+        }
+    }
 
-                    // Note: supplying some `state_root` when it is known would be a cheap and easy
-                    // optimization.
-                    match per_slot_processing(&mut state, skip_state_root, &self.spec) {
-                        Ok(()) => (),
-                        Err(e) => {
-                            warn!(
-                                self.log,
-                                "Unable to load state at slot";
-                                "error" => format!("{:?}", e),
-                                "head_slot" => head_state_slot,
-                                "requested_slot" => slot
-                            );
-                            return Err(Error::NoStateForSlot(slot));
-                        }
-                    };
-                }
-                Ok(state)
-            }
-            Ordering::Less => {
-                let state_root = self
-                    .rev_iter_state_roots()?
-                    .take_while(|(_root, current_slot)| *current_slot >= slot)
-                    .find(|(_root, current_slot)| *current_slot == slot)
-                    .map(|(root, _slot)| root)
-                    .ok_or_else(|| Error::NoStateForSlot(slot))?;
+    /// Returns whether this node is ready to serve validator duties, according to the
+    /// `ReadinessConfig` supplied to the builder.
+    ///
+    /// Considers the distance between the head and the slot clock, the age of the latest block in
+    /// the eth1 cache and (optionally) whether the caches required for attestation verification are
+    /// warm. Prior to genesis the node is not considered to be syncing.
+    pub fn readiness(&self) -> Result<Readiness, Error> {
+        let (head_slot, shuffling_key) = {
+            let head = self
+                .canonical_head
+                .try_read_for(self.lock_timeouts.head)
+                .ok_or_else(|| Error::CanonicalHeadLockTimeout)?;
+
+            let head_slot = head.beacon_block.slot();
+            let epoch = head_slot.epoch(T::EthSpec::slots_per_epoch());
+            let epoch_start_slot = epoch.start_slot(T::EthSpec::slots_per_epoch());
+            let target_root = if head_slot == epoch_start_slot {
+                head.beacon_block_root
+            } else {
+                *head.beacon_state.get_block_root(epoch_start_slot)?
+            };
 
-                Ok(self
-                    .get_state(&state_root, Some(slot))?
-                    .ok_or_else(|| Error::NoStateForSlot(slot))?)
-            }
+            (head_slot, (epoch, target_root))
+        };
+
+        let sync_distance = SyncStatus::new(
+            head_slot,
+            self.slot_clock.now(),
+            self.readiness_config.max_sync_distance,
+        )
+        .sync_distance();
+
+        let eth1_block_age = self
+            .eth1_chain
+            .as_ref()
+            .and_then(|eth1_chain| eth1_chain.latest_block_timestamp())
+            .map(|timestamp| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_else(|_| Duration::from_secs(0))
+                    .checked_sub(Duration::from_secs(timestamp))
+                    .unwrap_or_else(|| Duration::from_secs(0))
+            });
+
+        let mut degraded_reasons = vec![];
+
+        let (epoch, target_root) = shuffling_key;
+        if !self
+            .shuffling_cache
+            .try_read_for(self.lock_timeouts.attestation_cache)
+            .ok_or_else(|| Error::AttestationCacheLockTimeout)?
+            .contains(epoch, target_root)
+        {
+            degraded_reasons.push(DegradedReason::ShufflingNotCached { epoch });
         }
+
+        Ok(Readiness::evaluate(
+            &self.readiness_config,
+            sync_distance,
+            eth1_block_age,
+            degraded_reasons,
+        ))
     }
 
-    /// Returns the `BeaconState` the current slot (viz., `self.slot()`).
+    /// Returns the progress of the head towards the slot clock.
     ///
-    ///  - A reference to the head state (note: this keeps a read lock on the head, try to use
-    ///  sparingly).
-    ///  - The head state, but with skipped slots (for states later than the head).
-    ///
-    ///  Returns `None` when there is an error skipping to a future state or the slot clock cannot
-    ///  be read.
-    pub fn wall_clock_state(&self) -> Result<BeaconState<T::EthSpec>, Error> {
-        self.state_at_slot(self.slot()?, StateSkipConfig::WithStateRoots)
+    /// The node is considered synced whilst the head is no more than
+    /// `ReadinessConfig::max_sync_distance` slots behind. Prior to genesis a
+    /// `SyncStatus::PreGenesis` is returned, rather than an error.
+    pub fn sync_status(&self) -> Result<SyncStatus, Error> {
+        Ok(SyncStatus::new(
+            self.head_info()?.slot,
+            self.slot_clock.now(),
+            self.readiness_config.max_sync_distance,
+        ))
     }
 
-    /// Returns the slot of the highest block in the canonical chain.
-    pub fn best_slot(&self) -> Result<Slot, Error> {
-        self.canonical_head
-            .try_read_for(HEAD_LOCK_TIMEOUT)
-            .map(|head| head.beacon_block.slot())
-            .ok_or_else(|| Error::CanonicalHeadLockTimeout)
+    /// Returns a snapshot of the operations performed since this `BeaconChain` was started.
+    pub fn stats(&self) -> ChainStatsSnapshot {
+        self.chain_stats.snapshot(self.admin_history.num_events())
     }
 
-    /// Returns the validator index (if any) for the given public key.
+    /// Durably records that an administrative override (e.g., a snapshot import) has been applied
+    /// to the chain, so that it can be accounted for when debugging.
     ///
-    /// Information is retrieved from the present `beacon_state.validators`.
-    pub fn validator_index(&self, pubkey: &PublicKeyBytes) -> Result<Option<usize>, Error> {
-        for (i, validator) in self.head()?.beacon_state.validators.iter().enumerate() {
-            if validator.pubkey == *pubkey {
-                return Ok(Some(i));
-            }
-        }
-        Ok(None)
+    /// The `action` should be a short, stable name and `parameters` a human-readable description
+    /// of the inputs to the override.
+    pub fn record_admin_event(&self, action: &str, parameters: String) -> Result<(), Error> {
+        let event = AdminEvent::new(action.to_string(), parameters);
+
+        self.admin_history
+            .append::<T::EthSpec, _>(&*self.store, &event)?;
+
+        info!(
+            self.log,
+            "Recorded administrative override";
+            "action" => action,
+            "parameters" => &event.parameters,
+        );
+
+        Ok(())
     }
 
-    /// Returns the block canonical root of the current canonical chain at a given slot.
-    ///
-    /// Returns None if a block doesn't exist at the slot.
-    pub fn root_at_slot(&self, target_slot: Slot) -> Result<Option<Hash256>, Error> {
+    /// Returns the most recent `count` administrative overrides applied to the chain (including
+    /// those from previous runs), in the order they were applied.
+    pub fn admin_history(&self, count: usize) -> Result<Vec<AdminEvent>, Error> {
         Ok(self
-            .rev_iter_block_roots()?
-            .find(|(_root, slot)| *slot == target_slot)
-            .map(|(root, _slot)| root))
+            .admin_history
+            .latest::<T::EthSpec, _>(&*self.store, count)?)
     }
 
-    /// Returns the block proposer for a given slot.
+    /// Returns the target and head vote correctness of the attestations produced by this node,
+    /// for each recently settled epoch (oldest first).
     ///
-    /// Information is read from the present `beacon_state` shuffling, only information from the
-    /// present epoch is available.
-    pub fn block_proposer(&self, slot: Slot) -> Result<usize, Error> {
-        let epoch = |slot: Slot| slot.epoch(T::EthSpec::slots_per_epoch());
-        let head_state = &self.head()?.beacon_state;
+    /// An epoch is settled (and evaluated) once the canonical head reaches the end of the following
+    /// epoch.
+    pub fn local_attestation_correctness(&self) -> Vec<LocalAttestationCorrectness> {
+        self.local_attestations.evaluated()
+    }
 
-        let mut state = if epoch(slot) == epoch(head_state.slot) {
-            self.head()?.beacon_state
-        } else {
-            // The block proposer shuffling is not affected by the state roots, so we don't need to
+    /// Returns the fork choice latest messages of the validators with indices in
+    /// `start_index..start_index + count`.
+    ///
+    /// Validators that have never had an attestation processed by fork choice are omitted.
+    pub fn latest_messages(&self, start_index: usize, count: usize) -> Vec<LatestMessage> {
+        self.fork_choice
+            .latest_messages(start_index, count)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Returns the fork choice latest message of the validator with `validator_index`, if any.
+    pub fn latest_message(&self, validator_index: usize) -> Option<LatestMessage> {
+        self.fork_choice
+            .latest_message(validator_index)
+            .map(|(root, epoch)| (validator_index, root, epoch).into())
+    }
+
+    /// Returns the weight that fork choice assigned to the block with `block_root` during its
+    /// last run, or `None` if the block is unknown to fork choice.
+    pub fn block_weight(&self, block_root: &Hash256) -> Option<u64> {
+        self.fork_choice.block_weight(block_root)
+    }
+
+    /// Returns a summary of every block known to fork choice, with parents before children.
+    pub fn fork_choice_nodes(&self) -> Vec<ForkChoiceNode> {
+        self.fork_choice.fork_choice_nodes()
+    }
+
+    /// Returns an iterator over the fork choice latest messages of all validators, reading
+    /// `chunk_size` validators at a time.
+    pub fn latest_messages_iter(&self, chunk_size: usize) -> LatestMessagesIter<T> {
+        LatestMessagesIter::new(self, chunk_size)
+    }
+
+    /// Returns a stream of all future events that belong to one of the given `topics`.
+    ///
+    /// Returns `None` if the event handler does not support subscriptions (see
+    /// `events::ChannelEventHandler`). Events are dropped for a stream that is not being polled
+    /// fast enough, rather than delaying block processing, in which case the stream receives an
+    /// `EventKind::Lagged` notice.
+    pub fn event_stream(
+        &self,
+        topics: HashSet<EventTopic>,
+    ) -> Option<impl Stream<Item = EventKind<T::EthSpec>, Error = ()>> {
+        let receiver = self.event_handler.subscribe()?;
+
+        Some(receiver.filter(move |event| event.matches_any(&topics)))
+    }
+
+    /// Returns the current heads of the `BeaconChain`. For the canonical head, see `Self::head`.
+    ///
+    /// Returns `(block_root, block_slot)`.
+    pub fn heads(&self) -> Vec<(Hash256, Slot)> {
+        self.head_tracker.heads()
+    }
+
+    /// Returns the current heads of the `BeaconChain` along with the justified and finalized
+    /// checkpoints of each head state.
+    ///
+    /// The state of each head is loaded from the database in turn. Heads for which the block or
+    /// state cannot be loaded are omitted after logging a warning.
+    pub fn heads_detailed(&self) -> Vec<HeadDetail> {
+        self.heads()
+            .into_iter()
+            .filter_map(
+                |(block_root, slot)| match self.head_detail(block_root, slot) {
+                    Ok(Some(detail)) => Some(detail),
+                    Ok(None) => {
+                        warn!(
+                            self.log,
+                            "Unable to load head";
+                            "block_root" => format!("{:?}", block_root),
+                            "slot" => slot,
+                            "reason" => "missing block or state",
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        warn!(
+                            self.log,
+                            "Unable to load head";
+                            "block_root" => format!("{:?}", block_root),
+                            "slot" => slot,
+                            "error" => format!("{:?}", e),
+                        );
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Loads the state of the head with the given `block_root` to build a `HeadDetail`.
+    ///
+    /// Returns `Ok(None)` if the block or its state is not in the database.
+    fn head_detail(&self, block_root: Hash256, slot: Slot) -> Result<Option<HeadDetail>, Error> {
+        let state_root = match self.get_block(&block_root)? {
+            Some(block) => block.state_root(),
+            None => return Ok(None),
+        };
+
+        Ok(self
+            .get_state_caching_only_with_committee_caches(&state_root, Some(slot))?
+            .map(|state| HeadDetail {
+                block_root,
+                slot,
+                state_root,
+                current_justified_checkpoint: state.current_justified_checkpoint,
+                finalized_checkpoint: state.finalized_checkpoint,
+            }))
+    }
+
+    /// Returns the `BeaconState` at the given slot.
+    ///
+    /// Returns `None` when the state is not found in the database or there is an error skipping
+    /// to a future state.
+    ///
+    /// Skipping to a future state may take no longer than the duration of a slot (see
+    /// `Self::state_at_slot_with_deadline`).
+    pub fn state_at_slot(
+        &self,
+        slot: Slot,
+        config: StateSkipConfig,
+    ) -> Result<BeaconState<T::EthSpec>, Error> {
+        self.state_at_slot_with_deadline(slot, config, self.default_state_skip_deadline())
+    }
+
+    /// As per `Self::state_at_slot`, but skipping to a future state may continue until
+    /// `deadline`.
+    ///
+    /// If the `deadline` passes before the state reaches
+    /// `slot`, a `StateSkipTooLarge` error is returned and the partially-advanced state is
+    /// cached, so that a subsequent call for the same head may resume from it. Therefore, a state
+    /// that is too far ahead of the head to be reached before one deadline may be reached by
+    /// repeated calls.
+    pub fn state_at_slot_with_deadline(
+        &self,
+        slot: Slot,
+        config: StateSkipConfig,
+        deadline: Instant,
+    ) -> Result<BeaconState<T::EthSpec>, Error> {
+        self.state_at_slot_from_head(&self.head()?, slot, config, deadline)
+    }
+
+    /// As per `Self::state_at_slot_with_deadline`, but the state is read from the chain of the
+    /// given snapshot of the head, rather than the current head.
+    fn state_at_slot_from_head(
+        &self,
+        head: &CheckPoint<T::EthSpec>,
+        slot: Slot,
+        config: StateSkipConfig,
+        deadline: Instant,
+    ) -> Result<BeaconState<T::EthSpec>, Error> {
+        let head_state = &head.beacon_state;
+
+        match slot.cmp(&head_state.slot) {
+            Ordering::Equal => Ok(head_state.clone_with_only_committee_caches()),
+            Ordering::Greater => {
+                self.check_skip_slots(head_state.slot, slot)?;
+
+                if slot > head_state.slot + T::EthSpec::slots_per_epoch() {
+                    warn!(
+                        self.log,
+                        "Skipping more than an epoch";
+                        "head_slot" => head_state.slot,
+                        "request_slot" => slot
+                    )
+                }
+
+                let start_root = head.beacon_state_root;
+                let (mut state, state_root) =
+                    match self.state_skip_cache.take(start_root, config, slot) {
+                        Some(state) => (state, None),
+                        None => (
+                            head_state.clone_with_only_committee_caches(),
+                            Some(start_root),
+                        ),
+                    };
+
+                match self.advance_state_to_slot(&mut state, state_root, slot, config, deadline) {
+                    Ok(()) => Ok(state),
+                    Err(e @ Error::StateSkipTooLarge { .. }) => {
+                        debug!(
+                            self.log,
+                            "Caching partially skipped state";
+                            "head_slot" => head_state.slot,
+                            "reached_slot" => state.slot,
+                            "request_slot" => slot
+                        );
+                        self.state_skip_cache.insert(start_root, config, state);
+                        Err(e)
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            Ordering::Less => {
+                let state_root = match head_state.get_state_root(slot) {
+                    Ok(root) => *root,
+                    // The slot is too old to be in `head_state.state_roots`, so it is finalized and
+                    // may be read from the current canonical chain.
+                    Err(_) => self
+                        .state_root_at_slot(slot)?
+                        .ok_or_else(|| Error::NoStateForSlot(slot))?,
+                };
+
+                Ok(self
+                    .get_state(&state_root, Some(slot))?
+                    .ok_or_else(|| Error::NoStateForSlot(slot))?)
+            }
+        }
+    }
+
+    /// Returns the `BeaconState` at `target_slot` on the chain of the state with
+    /// `anchor_state_root`.
+    ///
+    /// Unlike `Self::state_at_slot`, the state need not have been stored at `target_slot` (e.g.,
+    /// it may be a skip slot). The nearest state at or prior to `target_slot` is loaded from the
+    /// database and slots are processed (including state roots) until `target_slot` is reached.
+    ///
+    /// ## Errors
+    ///
+    /// - The state with `anchor_state_root` is unknown.
+    /// - No state at or prior to `target_slot` is stored on the chain of the anchor state.
+    /// - Processing slots takes longer than one slot (see `Error::StateSkipTooLarge`).
+    pub fn replay_state_to_slot(
+        &self,
+        target_slot: Slot,
+        anchor_state_root: Hash256,
+    ) -> Result<BeaconState<T::EthSpec>, Error> {
+        let anchor_state = self
+            .get_state(&anchor_state_root, None)?
+            .ok_or_else(|| Error::MissingBeaconState(anchor_state_root))?;
+
+        let (mut state, state_root) = if anchor_state.slot <= target_slot {
+            (anchor_state, anchor_state_root)
+        } else if target_slot < self.anchor_slot {
+            // The states prior to the anchor of the chain are unavailable.
+            return Err(Error::NoStateForSlot(target_slot));
+        } else {
+            StateRootsIterator::owned(self.store.clone(), anchor_state)
+                .skip_while(|result| match result {
+                    Ok((_root, slot)) => *slot > target_slot,
+                    Err(_) => false,
+                })
+                .find_map(|result| {
+                    result
+                        .map_err(Error::from)
+                        .and_then(|(root, slot)| {
+                            self.get_state(&root, Some(slot))
+                                .map(|state| state.map(|state| (state, root)))
+                        })
+                        .transpose()
+                })
+                .transpose()?
+                .ok_or_else(|| Error::NoStateForSlot(target_slot))?
+        };
+
+        self.advance_state_to_slot(
+            &mut state,
+            Some(state_root),
+            target_slot,
+            StateSkipConfig::WithStateRoots,
+            self.default_state_skip_deadline(),
+        )?;
+
+        Ok(state)
+    }
+
+    /// Returns an error if advancing a state from `start_slot` to `slot` would skip more than the
+    /// configured `max_skip_slots`.
+    ///
+    /// Should be called before any loop of `per_slot_processing`.
+    pub fn check_skip_slots(&self, start_slot: Slot, slot: Slot) -> Result<(), Error> {
+        match self.max_skip_slots {
+            Some(max) if slot > start_slot + max => Err(Error::TooManySkipSlots {
+                requested: (slot - start_slot).as_u64(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the deadline for a state skip that starts now, unless the caller chooses another.
+    ///
+    /// Skips may take no longer than the duration of a slot by default.
+    fn default_state_skip_deadline(&self) -> Instant {
+        Instant::now() + Duration::from_millis(self.spec.milliseconds_per_slot)
+    }
+
+    /// Applies `per_slot_processing` to `state` until it reaches `slot`.
+    ///
+    /// The `state_root` of `state` is used for the first slot, if known, rather than hashing the
+    /// state.
+    ///
+    /// Returns an error if `deadline` has passed after processing any slot before `slot` is
+    /// reached, leaving `state` at the slot reached so far. This is a protection against nodes
+    /// doing too much work when they're not synced to a chain.
+    fn advance_state_to_slot(
+        &self,
+        state: &mut BeaconState<T::EthSpec>,
+        mut state_root: Option<Hash256>,
+        slot: Slot,
+        config: StateSkipConfig,
+        deadline: Instant,
+    ) -> Result<(), Error> {
+        self.check_skip_slots(state.slot, slot)?;
+
+        let start_slot = state.slot;
+        let task_start = Instant::now();
+
+        let skip_state_root = match config {
+            StateSkipConfig::WithStateRoots => None,
+            StateSkipConfig::WithoutStateRoots => Some(Hash256::zero()),
+        };
+
+        while state.slot < slot {
+            let state_root = match state_root.take().or(skip_state_root) {
+                Some(state_root) => state_root,
+                None => update_tree_hash_cache(state)?,
+            };
+
+            match per_slot_processing(state, Some(state_root), &self.spec) {
+                Ok(()) => (),
+                Err(e) => {
+                    warn!(
+                        self.log,
+                        "Unable to load state at slot";
+                        "error" => format!("{:?}", e),
+                        "start_slot" => start_slot,
+                        "requested_slot" => slot
+                    );
+                    return Err(Error::NoStateForSlot(slot));
+                }
+            };
+
+            // Do not allow any forward state skip to continue past its deadline. An epoch
+            // transition may be slow, so the deadline is checked after every slot.
+            if state.slot < slot && deadline < Instant::now() {
+                return Err(Error::StateSkipTooLarge {
+                    start_slot,
+                    requested_slot: slot,
+                    max_task_runtime: deadline.saturating_duration_since(task_start),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the `BeaconState` the current slot (viz., `self.slot()`).
+    ///
+    ///  - A reference to the head state (note: this keeps a read lock on the head, try to use
+    ///  sparingly).
+    ///  - The head state, but with skipped slots (for states later than the head).
+    ///
+    ///  Returns `None` when there is an error skipping to a future state or the slot clock cannot
+    ///  be read.
+    pub fn wall_clock_state(&self) -> Result<BeaconState<T::EthSpec>, Error> {
+        self.state_at_slot(self.slot()?, StateSkipConfig::WithStateRoots)
+    }
+
+    /// Returns the slot of the highest block in the canonical chain.
+    pub fn best_slot(&self) -> Result<Slot, Error> {
+        self.canonical_head
+            .try_read_for(self.lock_timeouts.head)
+            .map(|head| head.beacon_block.slot())
+            .ok_or_else(|| Error::CanonicalHeadLockTimeout)
+    }
+
+    /// Returns the validator index (if any) for the given public key.
+    ///
+    /// Information is retrieved from the present `beacon_state.validators`.
+    pub fn validator_index(&self, pubkey: &PublicKeyBytes) -> Result<Option<usize>, Error> {
+        for (i, validator) in self.head()?.beacon_state.validators.iter().enumerate() {
+            if validator.pubkey == *pubkey {
+                return Ok(Some(i));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the block canonical root of the current canonical chain at a given slot.
+    ///
+    /// Returns None if a block doesn't exist at the slot, or if the slot is prior to the anchor of
+    /// the chain.
+    pub fn root_at_slot(&self, target_slot: Slot) -> Result<Option<Hash256>, Error> {
+        if target_slot < self.anchor_slot {
+            return Ok(None);
+        }
+
+        find_root_at_slot(self.rev_iter_block_roots()?, target_slot)
+    }
+
+    /// Returns the root of the state at a given slot of the current canonical chain.
+    ///
+    /// Returns None if the slot is after the head, or if it is prior to the anchor of the chain.
+    pub fn state_root_at_slot(&self, target_slot: Slot) -> Result<Option<Hash256>, Error> {
+        if target_slot < self.anchor_slot {
+            return Ok(None);
+        }
+
+        find_root_at_slot(self.rev_iter_state_roots()?, target_slot)
+    }
+
+    /// Returns `true` if the block with `block_root` is the head or an ancestor of the head.
+    ///
+    /// Blocks within `SLOTS_PER_HISTORICAL_ROOT` slots of the head are checked against the block
+    /// roots of the head state, so the chain is only iterated for older blocks. Returns `false`
+    /// if the block is unknown.
+    pub fn is_canonical(&self, block_root: &Hash256) -> Result<bool, Error> {
+        let block_slot = match self.fork_choice.block_slot_and_state_root(block_root) {
+            Some((slot, _state_root)) => slot,
+            None => match self.get_block(block_root)? {
+                Some(block) => block.slot(),
+                None => return Ok(false),
+            },
+        };
+
+        let (head_block_root, head_slot, recent_root) = {
+            let head = self
+                .canonical_head
+                .try_read_for(self.lock_timeouts.head)
+                .ok_or_else(|| Error::CanonicalHeadLockTimeout)?;
+
+            (
+                head.beacon_block_root,
+                head.beacon_block.slot(),
+                // Errors if the slot is not prior to the head or is too old to be in the state.
+                head.beacon_state.get_block_root(block_slot).ok().copied(),
+            )
+        };
+
+        if block_slot >= head_slot {
+            Ok(*block_root == head_block_root)
+        } else if let Some(root) = recent_root {
+            Ok(*block_root == root)
+        } else {
+            Ok(self.get_ancestor_block_root(head_block_root, block_slot)? == Some(*block_root))
+        }
+    }
+
+    /// Returns a description of when the block with `block_root` was finalized by the canonical
+    /// chain, or `None` if it is unknown, not yet finalized or not in the canonical chain.
+    ///
+    /// The finalized checkpoint of the state at the start of each epoch is never older than that
+    /// of the previous epoch, so the epoch at which the block was finalized is found by a binary
+    /// search over the states at the start of each epoch since the block. Results are cached,
+    /// since the history of a finalized block cannot change.
+    pub fn finalization_info_for_block(
+        &self,
+        block_root: Hash256,
+    ) -> Result<Option<FinalizationInfo>, Error> {
+        if let Some(info) = self.finalization_info_cache.lock().get(&block_root) {
+            return Ok(Some(info));
+        }
+
+        let block_slot = match self.get_block(&block_root)? {
+            Some(block) => block.slot(),
+            None => return Ok(None),
+        };
+
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+        let is_finalized_by =
+            |checkpoint: &Checkpoint| checkpoint.epoch.start_slot(slots_per_epoch) >= block_slot;
+
+        let head = self.head()?;
+        let head_finalized_checkpoint = head.beacon_state.finalized_checkpoint;
+        if !is_finalized_by(&head_finalized_checkpoint) {
+            return Ok(None);
+        }
+
+        // The root of the genesis checkpoint is zero, rather than the genesis block root.
+        let finalized_block_root = if head_finalized_checkpoint.root == Hash256::zero() {
+            self.genesis_block_root
+        } else {
+            head_finalized_checkpoint.root
+        };
+        if self.get_ancestor_block_root(finalized_block_root, block_slot)? != Some(block_root) {
+            return Ok(None);
+        }
+
+        // The roots of the states at the start of each epoch since the block, in ascending order
+        // of slot.
+        let mut epoch_start_states = ReverseStateRootIterator::new(
+            (head.beacon_state_root, head.beacon_state.slot),
+            StateRootsIterator::owned(
+                self.store.clone(),
+                head.beacon_state.clone_with_only_committee_caches(),
+            ),
+        )
+        .take_while(|result| match result {
+            Ok((_, slot)) => *slot >= block_slot,
+            Err(_) => true,
+        })
+        .filter(|result| match result {
+            Ok((_, slot)) => slot.as_u64() % slots_per_epoch == 0,
+            Err(_) => true,
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+        epoch_start_states.reverse();
+
+        // The head state finalizes the block, so the state at the start of its epoch (which has
+        // the same finalized checkpoint) does too.
+        let mut info = FinalizationInfo {
+            block_slot,
+            finalized_checkpoint: head_finalized_checkpoint,
+            finalized_at_epoch: head.beacon_state.current_epoch(),
+        };
+        let (mut low, mut high) = (0, epoch_start_states.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            let (state_root, slot) = epoch_start_states[mid];
+            let finalized_checkpoint = self
+                .get_state_caching_only_with_committee_caches(&state_root, Some(slot))?
+                .ok_or_else(|| Error::MissingBeaconState(state_root))?
+                .finalized_checkpoint;
+
+            if is_finalized_by(&finalized_checkpoint) {
+                info.finalized_checkpoint = finalized_checkpoint;
+                info.finalized_at_epoch = slot.epoch(slots_per_epoch);
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        self.finalization_info_cache.lock().insert(block_root, info);
+
+        Ok(Some(info))
+    }
+
+    /// Returns the balance of the validator with `validator_index` in the canonical state at the
+    /// start of each epoch from `start_epoch` to `end_epoch` (inclusive), in ascending order of
+    /// epoch.
+    ///
+    /// Epochs after the head, epochs prior to the anchor of the chain, epochs for which the state
+    /// is not available and epochs in which the validator does not yet exist are omitted, so the
+    /// result may contain gaps.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the range covers more than `MAX_BALANCE_HISTORY_EPOCHS` epochs.
+    pub fn validator_balance_history(
+        &self,
+        validator_index: usize,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+    ) -> Result<Vec<(Epoch, u64)>, Error> {
+        if end_epoch < start_epoch {
+            return Ok(vec![]);
+        }
+        if (end_epoch - start_epoch).as_u64() >= MAX_BALANCE_HISTORY_EPOCHS {
+            return Err(Error::BalanceHistoryRangeTooLarge {
+                start_epoch,
+                end_epoch,
+                max_epochs: MAX_BALANCE_HISTORY_EPOCHS,
+            });
+        }
+
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+        // The states prior to the anchor of the chain are unavailable and cannot be iterated.
+        let start_slot = std::cmp::max(start_epoch.start_slot(slots_per_epoch), self.anchor_slot);
+        let end_slot = end_epoch.start_slot(slots_per_epoch);
+
+        let mut epoch_start_states = self
+            .rev_iter_state_roots()?
+            .skip_while(|result| match result {
+                Ok((_, slot)) => *slot > end_slot,
+                Err(_) => false,
+            })
+            .take_while(|result| match result {
+                Ok((_, slot)) => *slot >= start_slot,
+                Err(_) => true,
+            })
+            .filter(|result| match result {
+                Ok((_, slot)) => slot.as_u64() % slots_per_epoch == 0,
+                Err(_) => true,
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        epoch_start_states.reverse();
+
+        let mut history = Vec::with_capacity(epoch_start_states.len());
+        for (state_root, slot) in epoch_start_states {
+            let epoch = slot.epoch(slots_per_epoch);
+
+            match self.get_state(&state_root, Some(slot))? {
+                Some(state) => {
+                    if let Some(balance) = state.balances.get(validator_index) {
+                        history.push((epoch, *balance));
+                    }
+                }
+                None => debug!(
+                    self.log,
+                    "Omitting epoch from balance history";
+                    "reason" => "state unavailable",
+                    "epoch" => epoch.as_u64(),
+                    "state_root" => format!("{:?}", state_root),
+                ),
+            }
+        }
+
+        Ok(history)
+    }
+
+    /// Returns the FFG target checkpoint for `epoch` on the canonical chain.
+    ///
+    /// The target root is the root of the block at the start slot of `epoch` or, if that slot was
+    /// skipped, the most recent prior block. If the start slot is not prior to the head, the head
+    /// block is the target.
+    pub fn target_checkpoint(&self, epoch: Epoch) -> Result<Checkpoint, Error> {
+        let target_slot = epoch.start_slot(T::EthSpec::slots_per_epoch());
+        let head = self.head()?;
+
+        if target_slot + T::EthSpec::slots_per_historical_root() as u64 > head.beacon_state.slot {
+            Self::target_checkpoint_for_block(epoch, head.beacon_block_root, &head.beacon_state)
+        } else {
+            // The target is too old to be in `head.beacon_state.block_roots`.
+            let root = self
+                .root_at_slot(target_slot)?
+                .ok_or_else(|| Error::UnableToFindTargetRoot(target_slot))?;
+
+            Ok(Checkpoint { epoch, root })
+        }
+    }
+
+    /// Returns the FFG target checkpoint for `epoch` on the chain of `block_root`, where `state`
+    /// is the state of `block_root` (or that state, skipped forward).
+    ///
+    /// As per `Self::target_checkpoint`, except that the target is read from `state`, rather than
+    /// the canonical chain.
+    fn target_checkpoint_for_block(
+        epoch: Epoch,
+        block_root: Hash256,
+        state: &BeaconState<T::EthSpec>,
+    ) -> Result<Checkpoint, Error> {
+        let target_slot = epoch.start_slot(T::EthSpec::slots_per_epoch());
+
+        let root = if target_slot >= state.slot {
+            block_root
+        } else {
+            *state.get_block_root(target_slot)?
+        };
+
+        Ok(Checkpoint { epoch, root })
+    }
+
+    /// Returns the block proposer for a given slot.
+    ///
+    /// Information is read from the present `beacon_state` shuffling, only information from the
+    /// present epoch is available.
+    pub fn block_proposer(&self, slot: Slot) -> Result<usize, Error> {
+        let epoch = |slot: Slot| slot.epoch(T::EthSpec::slots_per_epoch());
+        let head = self.head()?;
+
+        let mut state = if epoch(slot) == epoch(head.beacon_state.slot) {
+            head.beacon_state.clone_with_only_committee_caches()
+        } else {
+            // The block proposer shuffling is not affected by the state roots, so we don't need to
             // calculate them.
             self.state_at_slot(slot, StateSkipConfig::WithoutStateRoots)?
         };
 
-        state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+        state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+
+        if epoch(state.slot) != epoch(slot) {
+            return Err(Error::InvariantViolated(format!(
+                "Epochs in consistent in proposer lookup: state: {}, requested: {}",
+                epoch(state.slot),
+                epoch(slot)
+            )));
+        }
+
+        state
+            .get_beacon_proposer_index(slot, &self.spec)
+            .map_err(Into::into)
+    }
+
+    /// Returns the block proposer for each slot in the given `epoch`, as `(slot, proposer_index)`.
+    ///
+    /// The committee cache is built only once for the entire epoch. Only the present epoch
+    /// (according to the slot clock) or prior epochs may be requested. See
+    /// `Self::proposer_duties` for the next epoch.
+    pub fn block_proposers_for_epoch(&self, epoch: Epoch) -> Result<Vec<(Slot, usize)>, Error> {
+        let current_epoch = self.epoch()?;
+
+        if epoch > current_epoch {
+            return Err(Error::ProposerEpochTooFarAhead {
+                request_epoch: epoch,
+                current_epoch,
+            });
+        }
+
+        let duties = self.proposer_duties(epoch)?;
+
+        Ok(epoch
+            .slot_iter(T::EthSpec::slots_per_epoch())
+            .zip(duties.proposers.into_iter())
+            .collect())
+    }
+
+    /// Returns the block proposers for each slot of `epoch`, along with the root of the block
+    /// that they are dependent upon.
+    ///
+    /// Up to one epoch past the present epoch (according to the slot clock) may be requested. If
+    /// `epoch` is later than the epoch of the head, the proposers are computed by skipping a copy
+    /// of the head state to the start of `epoch`. Such a result is speculative: the randao mix
+    /// it is derived from is not yet final, so it is only valid whilst `dependent_root` (the head
+    /// block root) remains canonical. The skip is bounded in the same way as
+    /// `Self::state_at_slot`, by the `max_skip_slots` config and the duration of a slot.
+    pub fn proposer_duties(&self, epoch: Epoch) -> Result<ProposerDuties, Error> {
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+        let current_epoch = self.epoch()?;
+
+        if epoch > current_epoch + 1 {
+            return Err(Error::ProposerEpochTooFarAhead {
+                request_epoch: epoch,
+                current_epoch,
+            });
+        }
+
+        let head = self.head()?;
+        let head_epoch = head.beacon_state.current_epoch();
+        let start_slot = epoch.start_slot(slots_per_epoch);
+
+        let dependent_root = self.dependent_root_from_head(
+            &head.beacon_state,
+            head.beacon_block_root,
+            Self::last_slot_before_epoch(epoch),
+        )?;
+
+        let mut state = if epoch > head_epoch {
+            let mut state = head.beacon_state.clone_with_only_committee_caches();
+
+            // The block proposer shuffling is not affected by the state roots, so there is no need
+            // to calculate them.
+            self.advance_state_to_slot(
+                &mut state,
+                None,
+                start_slot,
+                StateSkipConfig::WithoutStateRoots,
+                self.default_state_skip_deadline(),
+            )?;
+
+            state
+        } else if epoch == head_epoch {
+            head.beacon_state.clone_with_only_committee_caches()
+        } else {
+            drop(head);
+            self.state_at_slot(start_slot, StateSkipConfig::WithoutStateRoots)?
+        };
+
+        state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+
+        let proposers = epoch
+            .slot_iter(slots_per_epoch)
+            .map(|slot| state.get_beacon_proposer_index(slot, &self.spec))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ProposerDuties {
+            epoch,
+            dependent_root,
+            proposers,
+        })
+    }
+
+    /// Returns the `AttestationDuty` for each of the given `validator_indices` during `epoch`.
+    ///
+    /// Equivalent to `Self::attester_duties`, for validator indices given as `u64`.
+    pub fn attestation_duties(
+        &self,
+        epoch: Epoch,
+        validator_indices: &[u64],
+    ) -> Result<Vec<Option<AttestationDuty>>, Error> {
+        let validator_indices = validator_indices
+            .iter()
+            .map(|validator_index| *validator_index as usize)
+            .collect::<Vec<_>>();
+
+        self.attester_duties(epoch, &validator_indices)
+    }
+
+    /// Returns the `AttestationDuty` for each of the given `validator_indices` during `epoch`.
+    ///
+    /// See `Self::attester_duties_with_dependent_root`.
+    pub fn attester_duties(
+        &self,
+        epoch: Epoch,
+        validator_indices: &[usize],
+    ) -> Result<Vec<Option<AttestationDuty>>, Error> {
+        self.attester_duties_with_dependent_root(epoch, validator_indices)
+            .map(|attester_duties| attester_duties.duties)
+    }
+
+    /// Returns the `AttestationDuty` for each of the given `validator_indices` during `epoch`,
+    /// along with the root of the block upon which they depend.
+    ///
+    /// The duties and the dependent root are read from the same snapshot of the head, so that a
+    /// client is never given duties under the dependent root of another chain. The committee
+    /// cache for `epoch` is built only once, regardless of the number of validators requested.
+    ///
+    /// Only the current and next epoch (according to the slot clock), or any prior epoch, may be
+    /// requested. Requests for any later epoch will return an error, since the shuffling is not
+    /// yet known.
+    ///
+    /// A `None` is returned for any validator that is unknown or not active in `epoch`.
+    pub fn attester_duties_with_dependent_root(
+        &self,
+        epoch: Epoch,
+        validator_indices: &[usize],
+    ) -> Result<AttesterDuties, Error> {
+        let current_epoch = self.epoch()?;
+
+        if epoch > current_epoch + 1 {
+            return Err(Error::AttesterDutiesEpochTooFarAhead {
+                request_epoch: epoch,
+                current_epoch,
+            });
+        }
+
+        let (state, relative_epoch, dependent_root) =
+            self.state_with_committee_cache_for_epoch(epoch)?;
+        let committee_cache = state.committee_cache(relative_epoch)?;
+
+        Ok(AttesterDuties {
+            epoch,
+            dependent_root,
+            duties: validator_indices
+                .iter()
+                .map(|validator_index| committee_cache.get_attestation_duties(*validator_index))
+                .collect(),
+        })
+    }
+
+    /// Returns the indices of the validators in the committee with `index` at `slot`, according
+    /// to the canonical head.
+    ///
+    /// The committee is read from the same shuffling cache as attestation processing (see
+    /// `Self::with_committee_cache`), so subsequent calls for the same epoch are cheap.
+    pub fn get_beacon_committee(
+        &self,
+        slot: Slot,
+        index: CommitteeIndex,
+    ) -> Result<Vec<usize>, Error> {
+        self.with_committee_cache(
+            slot.epoch(T::EthSpec::slots_per_epoch()),
+            |committee_cache| {
+                committee_cache
+                    .get_beacon_committee(slot, index)
+                    .map(|committee| committee.committee.to_vec())
+                    .ok_or_else(|| Error::NoCommitteeForSlotAndIndex { slot, index })
+            },
+        )
+    }
+
+    /// Returns all of the committees at `slot`, according to the canonical head, in order of
+    /// committee index.
+    ///
+    /// All committees are read from a single lookup in the shuffling cache (see
+    /// `Self::with_committee_cache`).
+    pub fn get_beacon_committees_at_slot(
+        &self,
+        slot: Slot,
+    ) -> Result<Vec<OwnedBeaconCommittee>, Error> {
+        self.with_committee_cache(
+            slot.epoch(T::EthSpec::slots_per_epoch()),
+            |committee_cache| {
+                Ok(committee_cache
+                    .get_beacon_committees_at_slot(slot)?
+                    .into_iter()
+                    .map(BeaconCommittee::into_owned)
+                    .collect())
+            },
+        )
+    }
+
+    /// Calls `f` with the committee cache for `epoch` on the canonical chain.
+    ///
+    /// The committee cache is read from the same shuffling cache as attestation processing. Upon
+    /// a miss, it is built from a state (see `Self::state_with_committee_cache_for_epoch`) and
+    /// added to the shuffling cache.
+    fn with_committee_cache<F, R>(&self, epoch: Epoch, f: F) -> Result<R, Error>
+    where
+        F: Fn(&CommitteeCache) -> Result<R, Error>,
+    {
+        let target = self.target_checkpoint(epoch)?;
+
+        if let Some(committee_cache) = self
+            .shuffling_cache
+            .try_write_for(self.lock_timeouts.attestation_cache)
+            .ok_or_else(|| Error::AttestationCacheLockTimeout)?
+            .get(epoch, target.root)
+        {
+            return f(committee_cache);
+        }
+
+        let (state, relative_epoch, _dependent_root) =
+            self.state_with_committee_cache_for_epoch(epoch)?;
+        let committee_cache = state.committee_cache(relative_epoch)?;
+
+        self.shuffling_cache
+            .try_write_for(self.lock_timeouts.attestation_cache)
+            .ok_or_else(|| Error::AttestationCacheLockTimeout)?
+            .insert(epoch, target.root, committee_cache);
+
+        f(committee_cache)
+    }
+
+    /// Returns an estimate of the rewards for an attestation from the validator with
+    /// `validator_index` at `slot`.
+    ///
+    /// This is only an estimate, which assumes that the attestation is correct and included with
+    /// the minimum delay. The base reward is computed from the effective balances of the head
+    /// state, whilst each component is scaled by the participation in the previous epoch of the
+    /// head state, rather than in the epoch of `slot`.
+    ///
+    /// Returns an error if the validator is unknown or is not active in the epoch of `slot`.
+    pub fn estimate_attestation_reward(
+        &self,
+        validator_index: usize,
+        slot: Slot,
+    ) -> Result<AttestationRewardEstimate, Error> {
+        let epoch = slot.epoch(T::EthSpec::slots_per_epoch());
+        let head = self.head()?;
+        let state = &head.beacon_state;
+
+        let is_active = state
+            .validators
+            .get(validator_index)
+            .map_or(false, |validator| validator.is_active_at(epoch));
+        if !is_active {
+            return Err(Error::ValidatorNotActive {
+                validator_index,
+                epoch,
+            });
+        }
+
+        let mut validator_statuses = ValidatorStatuses::new(state, &self.spec)?;
+        validator_statuses.process_attestations(state, &self.spec)?;
+        let total_balances = &validator_statuses.total_balances;
+        let total_balance = total_balances.current_epoch;
+
+        let base_reward = get_base_reward(state, validator_index, total_balance, &self.spec)?;
+        // Computed as `u128`, since the product may overflow with a large validator set.
+        let scaled_reward = |participating_balance: u64| {
+            if total_balance == 0 {
+                0
+            } else {
+                (u128::from(base_reward) * u128::from(participating_balance)
+                    / u128::from(total_balance)) as u64
+            }
+        };
+
+        Ok(AttestationRewardEstimate {
+            validator_index,
+            epoch,
+            base_reward,
+            source_reward: scaled_reward(total_balances.previous_epoch_attesters),
+            target_reward: scaled_reward(total_balances.previous_epoch_target_attesters),
+            head_reward: scaled_reward(total_balances.previous_epoch_head_attesters),
+            inclusion_delay_reward: base_reward - base_reward / self.spec.proposer_reward_quotient,
+        })
+    }
 
-        if epoch(state.slot) != epoch(slot) {
-            return Err(Error::InvariantViolated(format!(
-                "Epochs in consistent in proposer lookup: state: {}, requested: {}",
-                epoch(state.slot),
-                epoch(slot)
-            )));
+    /// Returns the root of the block upon which the attester shuffling for `epoch` depends,
+    /// according to the canonical head.
+    ///
+    /// The shuffling for `epoch` is decided by the randao mix at the end of `epoch - 2`, so this
+    /// is the root of the block at the last slot of `epoch - 2` (or the genesis block root). If
+    /// this block is re-orged out, the attestation duties for `epoch` may change.
+    pub fn shuffling_dependent_root(&self, epoch: Epoch) -> Result<Hash256, Error> {
+        let head = self
+            .canonical_head
+            .try_read_for(self.lock_timeouts.head)
+            .ok_or_else(|| Error::CanonicalHeadLockTimeout)?;
+
+        self.dependent_root_from_head(
+            &head.beacon_state,
+            head.beacon_block_root,
+            Self::last_slot_before_epoch(epoch.saturating_sub(1u64)),
+        )
+    }
+
+    /// Returns the root of the block upon which the block proposers for `epoch` depend, according
+    /// to the canonical head.
+    ///
+    /// This is the root of the block at the last slot of `epoch - 1` (or the genesis block root).
+    pub fn proposer_dependent_root(&self, epoch: Epoch) -> Result<Hash256, Error> {
+        let head = self
+            .canonical_head
+            .try_read_for(self.lock_timeouts.head)
+            .ok_or_else(|| Error::CanonicalHeadLockTimeout)?;
+
+        self.dependent_root_from_head(
+            &head.beacon_state,
+            head.beacon_block_root,
+            Self::last_slot_before_epoch(epoch),
+        )
+    }
+
+    /// Returns the last slot prior to `epoch`, or `None` if `epoch` is the genesis epoch.
+    fn last_slot_before_epoch(epoch: Epoch) -> Option<Slot> {
+        let start_slot = epoch.start_slot(T::EthSpec::slots_per_epoch());
+
+        if start_slot == 0 {
+            None
+        } else {
+            Some(start_slot - 1)
         }
+    }
 
-        state
-            .get_beacon_proposer_index(slot, &self.spec)
-            .map_err(Into::into)
+    /// Returns the root of the block at `decision_slot` on the chain of the head block
+    /// `head_block_root` (with state `head_state`).
+    ///
+    /// Slots later than the head are considered to be skipped, so they resolve to the head block.
+    /// A `decision_slot` of `None` resolves to the genesis block.
+    fn dependent_root_from_head(
+        &self,
+        head_state: &BeaconState<T::EthSpec>,
+        head_block_root: Hash256,
+        decision_slot: Option<Slot>,
+    ) -> Result<Hash256, Error> {
+        match decision_slot {
+            None => Ok(self.genesis_block_root),
+            Some(slot) if slot >= head_state.slot => Ok(head_block_root),
+            Some(slot) => match head_state.get_block_root(slot) {
+                Ok(root) => Ok(*root),
+                // The slot is too old to be in `head_state.block_roots`.
+                Err(_) => self
+                    .get_ancestor_block_root(head_block_root, slot)?
+                    .ok_or_else(|| {
+                        Error::DBInconsistent(format!("Missing ancestor at slot {}", slot))
+                    }),
+            },
+        }
+    }
+
+    /// Returns a state with an initialized committee cache for `epoch`, along with the
+    /// `RelativeEpoch` of that cache and the root of the block upon which the shuffling depends
+    /// (see `Self::shuffling_dependent_root`).
+    ///
+    /// The head state is used if possible, otherwise a state is loaded from the database or skipped
+    /// forward from the head without calculating state roots. Both the state and the dependent
+    /// root are read from a single snapshot of the head, so the dependent root always identifies
+    /// the shuffling of the state, even if the head changes concurrently.
+    fn state_with_committee_cache_for_epoch(
+        &self,
+        epoch: Epoch,
+    ) -> Result<(BeaconState<T::EthSpec>, RelativeEpoch, Hash256), Error> {
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+        let head = self.head()?;
+        let head_epoch = head.beacon_state.current_epoch();
+
+        let dependent_root = self.dependent_root_from_head(
+            &head.beacon_state,
+            head.beacon_block_root,
+            Self::last_slot_before_epoch(epoch.saturating_sub(1u64)),
+        )?;
+
+        let mut state = if RelativeEpoch::from_epoch(head_epoch, epoch).is_ok() {
+            head.beacon_state.clone_with_only_committee_caches()
+        } else if epoch > head_epoch {
+            // Skip to the first slot of the epoch prior to `epoch`, so that the duties can be read
+            // from the `RelativeEpoch::Next` committee cache.
+            //
+            // The shuffling is not affected by the state roots, so we don't need to calculate them.
+            self.state_at_slot_from_head(
+                &head,
+                (epoch - 1).start_slot(slots_per_epoch),
+                StateSkipConfig::WithoutStateRoots,
+                self.default_state_skip_deadline(),
+            )?
+        } else {
+            // Load the state at the last slot of the epoch following `epoch`, so that the duties
+            // can be read from the `RelativeEpoch::Previous` committee cache.
+            self.state_at_slot_from_head(
+                &head,
+                (epoch + 2).start_slot(slots_per_epoch) - 1,
+                StateSkipConfig::WithoutStateRoots,
+                self.default_state_skip_deadline(),
+            )?
+        };
+        drop(head);
+
+        let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch)
+            .map_err(BeaconStateError::from)?;
+
+        state.build_committee_cache(relative_epoch, &self.spec)?;
+
+        Ok((state, relative_epoch, dependent_root))
     }
 
     /// Produce an `Attestation` that is valid for the given `slot` and `index`.
@@ -664,19 +2187,135 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         slot: Slot,
         index: CommitteeIndex,
     ) -> Result<Attestation<T::EthSpec>, Error> {
+        let attestation = self.with_attestation_state_at_slot(
+            slot,
+            |beacon_block_root, state, head_epoch, head_justified_checkpoint| {
+                self.produce_attestation_for_block_with_justified_checkpoint(
+                    slot,
+                    index,
+                    beacon_block_root,
+                    state,
+                    head_epoch,
+                    head_justified_checkpoint,
+                )
+            },
+        )?;
+
+        self.local_attestations
+            .observe_production::<T::EthSpec>(&attestation.data);
+
+        Ok(attestation)
+    }
+
+    /// Returns the root that a validator must sign to attest to `data`, using the fork of the head
+    /// state.
+    ///
+    /// Useful for signers that are external to the validator client. At this spec version the
+    /// domain does not include a `genesis_validators_root`.
+    pub fn attestation_signing_root(&self, data: &AttestationData) -> Result<Hash256, Error> {
+        let fork = self.head_info()?.fork;
+
+        Ok(attestation_signing_root(data, &fork, &self.spec))
+    }
+
+    /// Returns the root that the proposer of `block` must sign, using the fork of the head state
+    /// at the epoch of the block's slot.
+    ///
+    /// Useful for signers that are external to the validator client.
+    pub fn block_signing_root(&self, block: &BeaconBlock<T::EthSpec>) -> Result<Hash256, Error> {
+        let fork = self.head_info()?.fork;
+
+        Ok(block_signing_root(block, None, &fork, &self.spec))
+    }
+
+    /// Produce an `Attestation` for each of the given committee `indices` at `slot`.
+    ///
+    /// Equivalent to calling `Self::produce_attestation` for each index, except that the head is
+    /// only read once and the block root, state, source and target are shared between all
+    /// attestations. An `Err` is only returned if the shared work fails; failures for individual
+    /// indices (e.g., an index without a committee) are returned in the position of that index.
+    ///
+    /// Always attests to the canonical chain.
+    pub fn produce_attestations_for_slot(
+        &self,
+        slot: Slot,
+        indices: &[CommitteeIndex],
+    ) -> Result<Vec<Result<Attestation<T::EthSpec>, Error>>, Error> {
+        let attestations = self.with_attestation_state_at_slot(
+            slot,
+            |beacon_block_root, state, head_epoch, head_justified_checkpoint| {
+                self.produce_attestations_for_block_with_justified_checkpoint(
+                    slot,
+                    indices,
+                    beacon_block_root,
+                    state,
+                    head_epoch,
+                    head_justified_checkpoint,
+                )
+            },
+        )?;
+
+        // All attestations share the same data, aside from the index.
+        if let Some(attestation) = attestations.iter().find_map(|result| result.as_ref().ok()) {
+            self.local_attestations
+                .observe_production::<T::EthSpec>(&attestation.data);
+        }
+
+        Ok(attestations)
+    }
+
+    /// Produce an `Attestation` at `slot` that attests to `head_block_root`, which may be any block
+    /// known to fork choice rather than the canonical head.
+    ///
+    /// Useful for attesting to (or testing) minority forks. Unlike `Self::produce_attestation`,
+    /// the attestation is not recorded as produced by this node.
+    pub fn produce_attestation_at_head(
+        &self,
+        slot: Slot,
+        index: CommitteeIndex,
+        head_block_root: Hash256,
+    ) -> Result<Attestation<T::EthSpec>, Error> {
+        let (_, state_root) = self
+            .fork_choice
+            .block_slot_and_state_root(&head_block_root)
+            .ok_or_else(|| Error::UnknownHeadBlock(head_block_root))?;
+
+        let mut state = self
+            .get_state_caching_only_with_committee_caches(&state_root, None)?
+            .ok_or_else(|| Error::MissingBeaconState(state_root))?;
+
+        state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+        state.build_committee_cache(RelativeEpoch::Next, &self.spec)?;
+
+        self.produce_attestation_for_block(slot, index, head_block_root, Cow::Owned(state))
+    }
+
+    /// Calls `produce` with the canonical block root and state that should be used to attest at
+    /// `slot`, alongside the epoch and justified checkpoint of the head.
+    ///
+    /// The head lock is taken exactly once and may be held whilst `produce` is running.
+    fn with_attestation_state_at_slot<F, R>(&self, slot: Slot, produce: F) -> Result<R, Error>
+    where
+        F: FnOnce(Hash256, Cow<BeaconState<T::EthSpec>>, Epoch, Checkpoint) -> Result<R, Error>,
+    {
         // Note: we're taking a lock on the head. The work involved here should be trivial enough
         // that the lock should not be held for long.
         let head = self
             .canonical_head
-            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .try_read_for(self.lock_timeouts.head)
             .ok_or_else(|| Error::CanonicalHeadLockTimeout)?;
 
+        self.check_head_not_in_future(head.beacon_block.slot())?;
+
+        let head_epoch = head.beacon_state.current_epoch();
+        let head_justified_checkpoint = head.beacon_state.current_justified_checkpoint.clone();
+
         if slot >= head.beacon_block.slot() {
-            self.produce_attestation_for_block(
-                slot,
-                index,
+            produce(
                 head.beacon_block_root,
                 Cow::Borrowed(&head.beacon_state),
+                head_epoch,
+                head_justified_checkpoint,
             )
         } else {
             // Note: this method will fail if `slot` is more than `state.block_roots.len()` slots
@@ -704,7 +2343,12 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
             state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
 
-            self.produce_attestation_for_block(slot, index, beacon_block_root, Cow::Owned(state))
+            produce(
+                beacon_block_root,
+                Cow::Owned(state),
+                head_epoch,
+                head_justified_checkpoint,
+            )
         }
     }
 
@@ -712,18 +2356,118 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     ///
     /// Permits attesting to any arbitrary chain. Generally, the `produce_attestation_data`
     /// function should be used as it attests to the canonical chain.
+    ///
+    /// The source of the attestation is the justified checkpoint of `state`, which should be the
+    /// state of `beacon_block_root`. The canonical head is never consulted, since its justified
+    /// checkpoint need not be an ancestor of `beacon_block_root`.
     pub fn produce_attestation_for_block(
+        &self,
+        slot: Slot,
+        index: CommitteeIndex,
+        beacon_block_root: Hash256,
+        state: Cow<BeaconState<T::EthSpec>>,
+    ) -> Result<Attestation<T::EthSpec>, Error> {
+        let state_epoch = state.current_epoch();
+        let state_justified_checkpoint = state.current_justified_checkpoint.clone();
+
+        self.produce_attestation_for_block_with_justified_checkpoint(
+            slot,
+            index,
+            beacon_block_root,
+            state,
+            state_epoch,
+            state_justified_checkpoint,
+        )
+    }
+
+    /// As per `Self::produce_attestation_for_block`, but the epoch and justified checkpoint of the
+    /// canonical head are provided by the caller (allowing it to be called whilst holding the head
+    /// lock).
+    ///
+    /// When attesting in or after `head_epoch`, if the justified checkpoint of `state` lags behind
+    /// `head_justified_checkpoint` (e.g., when `state` is an old state that has been skipped
+    /// forward), the head's justified checkpoint is used as the source as long as it is an
+    /// ancestor of `beacon_block_root`. Otherwise, an attestation with an outdated source would
+    /// earn no reward.
+    fn produce_attestation_for_block_with_justified_checkpoint(
         &self,
         slot: Slot,
         index: CommitteeIndex,
         beacon_block_root: Hash256,
         mut state: Cow<BeaconState<T::EthSpec>>,
+        head_epoch: Epoch,
+        head_justified_checkpoint: Checkpoint,
     ) -> Result<Attestation<T::EthSpec>, Error> {
+        let data = self.produce_attestation_data_template(
+            slot,
+            beacon_block_root,
+            &mut state,
+            head_epoch,
+            head_justified_checkpoint,
+        )?;
+        let relative_epoch = RelativeEpoch::from_epoch(
+            state.current_epoch(),
+            slot.epoch(T::EthSpec::slots_per_epoch()),
+        )
+        .map_err(BeaconStateError::from)?;
+        let committee_cache = state.committee_cache(relative_epoch)?;
+
+        attestation_for_committee(committee_cache, &data, index)
+    }
+
+    /// As per `Self::produce_attestation_for_block_with_justified_checkpoint`, but produces an
+    /// attestation for each of the given `indices`, sharing all the work except for the committee
+    /// lookup.
+    fn produce_attestations_for_block_with_justified_checkpoint(
+        &self,
+        slot: Slot,
+        indices: &[CommitteeIndex],
+        beacon_block_root: Hash256,
+        mut state: Cow<BeaconState<T::EthSpec>>,
+        head_epoch: Epoch,
+        head_justified_checkpoint: Checkpoint,
+    ) -> Result<Vec<Result<Attestation<T::EthSpec>, Error>>, Error> {
+        let data = self.produce_attestation_data_template(
+            slot,
+            beacon_block_root,
+            &mut state,
+            head_epoch,
+            head_justified_checkpoint,
+        )?;
+        let relative_epoch = RelativeEpoch::from_epoch(
+            state.current_epoch(),
+            slot.epoch(T::EthSpec::slots_per_epoch()),
+        )
+        .map_err(BeaconStateError::from)?;
+        let committee_cache = state.committee_cache(relative_epoch)?;
+
+        Ok(indices
+            .iter()
+            .map(|&index| attestation_for_committee(committee_cache, &data, index))
+            .collect())
+    }
+
+    /// Produces the `AttestationData` shared by all committees at `slot`, with an `index` of `0`.
+    ///
+    /// Advances `state` (if required) so that it has a committee cache for the epoch of `slot`.
+    fn produce_attestation_data_template(
+        &self,
+        slot: Slot,
+        beacon_block_root: Hash256,
+        state: &mut Cow<BeaconState<T::EthSpec>>,
+        head_epoch: Epoch,
+        head_justified_checkpoint: Checkpoint,
+    ) -> Result<AttestationData, Error> {
         let epoch = slot.epoch(T::EthSpec::slots_per_epoch());
 
         if state.slot > slot {
             return Err(Error::CannotAttestToFutureState);
         } else if state.current_epoch() + 1 < epoch {
+            self.check_skip_slots(
+                state.slot,
+                (epoch - 1).start_slot(T::EthSpec::slots_per_epoch()),
+            )?;
+
             let mut_state = state.to_mut();
             while mut_state.current_epoch() + 1 < epoch {
                 // Note: here we provide `Hash256::zero()` as the root of the current state. This
@@ -735,31 +2479,85 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             mut_state.build_committee_cache(RelativeEpoch::Next, &self.spec)?;
         }
 
-        let committee_len = state.get_beacon_committee(slot, index)?.committee.len();
+        let target = Self::target_checkpoint_for_block(epoch, beacon_block_root, state)?;
 
-        let target_slot = epoch.start_slot(T::EthSpec::slots_per_epoch());
-        let target_root = if state.slot <= target_slot {
-            beacon_block_root
+        // Attestations from prior epochs must use the justification of that epoch, so they are
+        // never compared to the head.
+        let source = if epoch >= head_epoch {
+            self.attestation_source(
+                &state.current_justified_checkpoint,
+                head_justified_checkpoint,
+                beacon_block_root,
+            )?
         } else {
-            *state.get_block_root(target_slot)?
+            state.current_justified_checkpoint.clone()
         };
 
-        Ok(Attestation {
-            aggregation_bits: BitList::with_capacity(committee_len)?,
-            data: AttestationData {
-                slot,
-                index,
-                beacon_block_root: beacon_block_root,
-                source: state.current_justified_checkpoint.clone(),
-                target: Checkpoint {
-                    epoch,
-                    root: target_root,
-                },
-            },
-            signature: AggregateSignature::new(),
+        Ok(AttestationData {
+            slot,
+            index: 0,
+            beacon_block_root,
+            source,
+            target,
         })
     }
 
+    /// Returns the checkpoint that should be used as the source of an attestation to
+    /// `beacon_block_root`, given the justified checkpoint of the state used for attestation
+    /// production and that of the canonical head.
+    ///
+    /// The head's justified checkpoint is only used if it is an ancestor of `beacon_block_root`,
+    /// otherwise the justified checkpoint of the state is used.
+    fn attestation_source(
+        &self,
+        state_justified_checkpoint: &Checkpoint,
+        head_justified_checkpoint: Checkpoint,
+        beacon_block_root: Hash256,
+    ) -> Result<Checkpoint, Error> {
+        if head_justified_checkpoint.epoch <= state_justified_checkpoint.epoch {
+            return Ok(state_justified_checkpoint.clone());
+        }
+
+        let justified_slot = head_justified_checkpoint
+            .epoch
+            .start_slot(T::EthSpec::slots_per_epoch());
+
+        // Avoid iterating all the way back to genesis when the block is prior to the justified
+        // checkpoint (in which case it cannot possibly descend from it).
+        let block_is_after_justified_slot = self
+            .fork_choice
+            .block_slot_and_state_root(&beacon_block_root)
+            .map_or(false, |(block_slot, _)| block_slot >= justified_slot);
+
+        let is_ancestor = head_justified_checkpoint.root == beacon_block_root
+            || (block_is_after_justified_slot
+                && self.get_ancestor_block_root(beacon_block_root, justified_slot)?
+                    == Some(head_justified_checkpoint.root));
+
+        if is_ancestor {
+            metrics::inc_counter(&metrics::ATTESTATION_PRODUCTION_SOURCE_FROM_HEAD);
+            debug!(
+                self.log,
+                "Using head justified checkpoint as source";
+                "state_justified_epoch" => state_justified_checkpoint.epoch,
+                "head_justified_epoch" => head_justified_checkpoint.epoch,
+            );
+
+            Ok(head_justified_checkpoint)
+        } else {
+            metrics::inc_counter(&metrics::ATTESTATION_PRODUCTION_SOURCE_MISMATCH);
+            warn!(
+                self.log,
+                "Attestation source lags head justification";
+                "state_justified_epoch" => state_justified_checkpoint.epoch,
+                "head_justified_epoch" => head_justified_checkpoint.epoch,
+                "beacon_block_root" => format!("{}", beacon_block_root),
+            );
+
+            Ok(state_justified_checkpoint.clone())
+        }
+    }
+
     /// Accept a new, potentially invalid attestation from the network.
     ///
     /// If valid, the attestation is added to `self.op_pool` and `self.fork_choice`.
@@ -779,7 +2577,19 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_REQUESTS);
         let timer = metrics::start_timer(&metrics::ATTESTATION_PROCESSING_TIMES);
 
-        let outcome = self.process_attestation_internal(attestation.clone());
+        let target_epoch = attestation.data.target.epoch;
+        let index = attestation.data.index;
+
+        // Only copy the attestation for the event handler if it will be used.
+        let event_attestation = if self.event_handler.wants(EventTopic::Attestation) {
+            Some(attestation.clone())
+        } else {
+            None
+        };
+
+        let outcome = self.process_attestation_internal(attestation);
+
+        self.chain_stats.observe_attestation(&outcome);
 
         match &outcome {
             Ok(outcome) => match outcome {
@@ -788,14 +2598,16 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     trace!(
                         self.log,
                         "Beacon attestation imported";
-                        "target_epoch" => attestation.data.target.epoch,
-                        "index" => attestation.data.index,
+                        "target_epoch" => target_epoch,
+                        "index" => index,
                     );
-                    let _ = self
-                        .event_handler
-                        .register(EventKind::BeaconAttestationImported {
-                            attestation: Box::new(attestation),
-                        });
+                    if let Some(attestation) = event_attestation {
+                        let _ = self
+                            .event_handler
+                            .register(EventKind::BeaconAttestationImported {
+                                attestation: Box::new(attestation),
+                            });
+                    }
                 }
                 other => {
                     trace!(
@@ -803,12 +2615,14 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                         "Beacon attestation rejected";
                         "reason" => format!("{:?}", other),
                     );
-                    let _ = self
-                        .event_handler
-                        .register(EventKind::BeaconAttestationRejected {
-                            reason: format!("Invalid attestation: {:?}", other),
-                            attestation: Box::new(attestation),
-                        });
+                    if let Some(attestation) = event_attestation {
+                        let _ = self
+                            .event_handler
+                            .register(EventKind::BeaconAttestationRejected {
+                                reason: format!("Invalid attestation: {:?}", other),
+                                attestation: Box::new(attestation),
+                            });
+                    }
                 }
             },
             Err(e) => {
@@ -817,12 +2631,14 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     "Beacon attestation processing error";
                     "error" => format!("{:?}", e),
                 );
-                let _ = self
-                    .event_handler
-                    .register(EventKind::BeaconAttestationRejected {
-                        reason: format!("Internal error: {:?}", e),
-                        attestation: Box::new(attestation),
-                    });
+                if let Some(attestation) = event_attestation {
+                    let _ = self
+                        .event_handler
+                        .register(EventKind::BeaconAttestationRejected {
+                            reason: format!("Internal error: {:?}", e),
+                            attestation: Box::new(attestation),
+                        });
+                }
             }
         }
 
@@ -830,38 +2646,32 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         outcome
     }
 
-    pub fn process_attestation_internal(
+    /// Performs the checks upon the epoch and target of an attestation with `data` which do not
+    /// require its committee, returning the slot and state root of its target block.
+    ///
+    /// Returns the outcome with which the attestation should be rejected if a check fails.
+    fn verify_attestation_target(
         &self,
-        attestation: Attestation<T::EthSpec>,
-    ) -> Result<AttestationProcessingOutcome, Error> {
-        let initial_validation_timer =
-            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_INITIAL_VALIDATION_TIMES);
-
-        // There is no point in processing an attestation with an empty bitfield. Reject
-        // it immediately.
-        if attestation.aggregation_bits.num_set_bits() == 0 {
-            return Ok(AttestationProcessingOutcome::EmptyAggregationBitfield);
-        }
-
-        let attestation_epoch = attestation.data.slot.epoch(T::EthSpec::slots_per_epoch());
-        let epoch_now = self.epoch()?;
-        let target = attestation.data.target.clone();
+        data: &AttestationData,
+        epoch_now: Epoch,
+    ) -> Result<(Slot, Hash256), AttestationProcessingOutcome> {
+        let attestation_epoch = data.slot.epoch(T::EthSpec::slots_per_epoch());
 
         // Attestation must be from the current or previous epoch.
         if attestation_epoch > epoch_now {
-            return Ok(AttestationProcessingOutcome::FutureEpoch {
+            return Err(AttestationProcessingOutcome::FutureEpoch {
                 attestation_epoch,
                 current_epoch: epoch_now,
             });
         } else if attestation_epoch + 1 < epoch_now {
-            return Ok(AttestationProcessingOutcome::PastEpoch {
+            return Err(AttestationProcessingOutcome::PastEpoch {
                 attestation_epoch,
                 current_epoch: epoch_now,
             });
         }
 
-        if target.epoch != attestation.data.slot.epoch(T::EthSpec::slots_per_epoch()) {
-            return Ok(AttestationProcessingOutcome::BadTargetEpoch);
+        if data.target.epoch != attestation_epoch {
+            return Err(AttestationProcessingOutcome::BadTargetEpoch);
         }
 
         // Attestation target must be for a known block.
@@ -871,32 +2681,25 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         // processing an attestation that does not include our latest finalized block in its chain.
         //
         // We do not delay consideration for later, we simply drop the attestation.
-        let (target_block_slot, target_block_state_root) = if let Some((slot, state_root)) =
-            self.fork_choice.block_slot_and_state_root(&target.root)
-        {
-            (slot, state_root)
-        } else {
-            return Ok(AttestationProcessingOutcome::UnknownTargetRoot(target.root));
-        };
+        let (target_block_slot, target_block_state_root) = self
+            .fork_choice
+            .block_slot_and_state_root(&data.target.root)
+            .ok_or_else(|| AttestationProcessingOutcome::UnknownTargetRoot(data.target.root))?;
 
-        // Load the slot and state root for `attestation.data.beacon_block_root`.
+        // Load the slot and state root for `data.beacon_block_root`.
         //
-        // This indirectly checks to see if the `attestation.data.beacon_block_root` is in our fork
-        // choice. Any known, non-finalized block should be in fork choice, so this check
-        // immediately filters out attestations that attest to a block that has not been processed.
+        // This indirectly checks to see if the `data.beacon_block_root` is in our fork choice. Any
+        // known, non-finalized block should be in fork choice, so this check immediately filters
+        // out attestations that attest to a block that has not been processed.
         //
         // Attestations must be for a known block. If the block is unknown, we simply drop the
         // attestation and do not delay consideration for later.
-        let block_slot = if let Some((slot, _state_root)) = self
+        let (block_slot, _state_root) = self
             .fork_choice
-            .block_slot_and_state_root(&attestation.data.beacon_block_root)
-        {
-            slot
-        } else {
-            return Ok(AttestationProcessingOutcome::UnknownHeadBlock {
-                beacon_block_root: attestation.data.beacon_block_root,
-            });
-        };
+            .block_slot_and_state_root(&data.beacon_block_root)
+            .ok_or_else(|| AttestationProcessingOutcome::UnknownHeadBlock {
+                beacon_block_root: data.beacon_block_root,
+            })?;
 
         // TODO: currently we do not check the FFG source/target. This is what the spec dictates
         // but it seems wrong.
@@ -909,116 +2712,170 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         // Attestations must not be for blocks in the future. If this is the case, the attestation
         // should not be considered.
-        if block_slot > attestation.data.slot {
-            return Ok(AttestationProcessingOutcome::AttestsToFutureBlock {
+        if block_slot > data.slot {
+            return Err(AttestationProcessingOutcome::AttestsToFutureBlock {
                 block: block_slot,
-                attestation: attestation.data.slot,
+                attestation: data.slot,
             });
         }
 
-        metrics::stop_timer(initial_validation_timer);
+        Ok((target_block_slot, target_block_state_root))
+    }
+
+    /// Calls `f` with the committee of an attestation with `data`, as determined by the shuffling
+    /// of its target.
+    ///
+    /// Upon a shuffling cache miss, the committee cache is built from the state of the target
+    /// block (with the given slot and state root) and added to the shuffling cache.
+    ///
+    /// Returns `Ok(None)` if there is no committee with the index of `data` at its slot.
+    fn with_attestation_committee<F, R>(
+        &self,
+        data: &AttestationData,
+        target_block_slot: Slot,
+        target_block_state_root: Hash256,
+        f: F,
+    ) -> Result<Option<R>, Error>
+    where
+        F: FnOnce(&[usize]) -> Result<R, Error>,
+    {
+        let attestation_epoch = data.slot.epoch(T::EthSpec::slots_per_epoch());
 
         let cache_wait_timer =
             metrics::start_timer(&metrics::ATTESTATION_PROCESSING_SHUFFLING_CACHE_WAIT_TIMES);
 
         let mut shuffling_cache = self
             .shuffling_cache
-            .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
+            .try_write_for(self.lock_timeouts.attestation_cache)
             .ok_or_else(|| Error::AttestationCacheLockTimeout)?;
 
         metrics::stop_timer(cache_wait_timer);
 
-        let indexed_attestation =
-            if let Some(committee_cache) = shuffling_cache.get(attestation_epoch, target.root) {
-                if let Some(committee) = committee_cache
-                    .get_beacon_committee(attestation.data.slot, attestation.data.index)
-                {
-                    let indexed_attestation =
-                        get_indexed_attestation(committee.committee, &attestation)?;
+        if let Some(committee_cache) = shuffling_cache.get(attestation_epoch, data.target.root) {
+            return committee_cache
+                .get_beacon_committee(data.slot, data.index)
+                .map(|committee| f(committee.committee))
+                .transpose();
+        }
 
-                    // Drop the shuffling cache to avoid holding the lock for any longer than
-                    // required.
-                    drop(shuffling_cache);
+        // Drop the shuffling cache to avoid holding the lock for any longer than required.
+        drop(shuffling_cache);
 
-                    indexed_attestation
-                } else {
-                    return Ok(AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
-                        slot: attestation.data.slot,
-                        index: attestation.data.index,
-                    });
-                }
-            } else {
-                // Drop the shuffling cache to avoid holding the lock for any longer than
-                // required.
-                drop(shuffling_cache);
+        debug!(
+            self.log,
+            "Attestation processing cache miss";
+            "attn_epoch" => attestation_epoch.as_u64(),
+            "target_block_epoch" => target_block_slot.epoch(T::EthSpec::slots_per_epoch()).as_u64(),
+        );
 
-                debug!(
-                    self.log,
-                    "Attestation processing cache miss";
-                    "attn_epoch" => attestation_epoch.as_u64(),
-                    "head_block_epoch" => block_slot.epoch(T::EthSpec::slots_per_epoch()).as_u64(),
-                );
+        let state_read_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_STATE_READ_TIMES);
 
-                let state_read_timer =
-                    metrics::start_timer(&metrics::ATTESTATION_PROCESSING_STATE_READ_TIMES);
-
-                let mut state = self
-                    .get_state_caching_only_with_committee_caches(
-                        &target_block_state_root,
-                        Some(target_block_slot),
-                    )?
-                    .ok_or_else(|| Error::MissingBeaconState(target_block_state_root))?;
-
-                metrics::stop_timer(state_read_timer);
-                let state_skip_timer =
-                    metrics::start_timer(&metrics::ATTESTATION_PROCESSING_STATE_SKIP_TIMES);
-
-                while state.current_epoch() + 1 < attestation_epoch {
-                    // Here we tell `per_slot_processing` to skip hashing the state and just
-                    // use the zero hash instead.
-                    //
-                    // The state roots are not useful for the shuffling, so there's no need to
-                    // compute them.
-                    per_slot_processing(&mut state, Some(Hash256::zero()), &self.spec)?
-                }
+        let mut state = self
+            .get_state_caching_only_with_committee_caches(
+                &target_block_state_root,
+                Some(target_block_slot),
+            )?
+            .ok_or_else(|| Error::MissingBeaconState(target_block_state_root))?;
+
+        metrics::stop_timer(state_read_timer);
+        let state_skip_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_STATE_SKIP_TIMES);
+
+        if state.current_epoch() + 1 < attestation_epoch {
+            self.check_skip_slots(
+                state.slot,
+                (attestation_epoch - 1).start_slot(T::EthSpec::slots_per_epoch()),
+            )?;
+        }
+
+        while state.current_epoch() + 1 < attestation_epoch {
+            // Here we tell `per_slot_processing` to skip hashing the state and just use the zero
+            // hash instead.
+            //
+            // The state roots are not useful for the shuffling, so there's no need to compute
+            // them.
+            per_slot_processing(&mut state, Some(Hash256::zero()), &self.spec)?
+        }
+
+        metrics::stop_timer(state_skip_timer);
+        let committee_building_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_COMMITTEE_BUILDING_TIMES);
+
+        let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), attestation_epoch)
+            .map_err(Error::IncorrectStateForAttestation)?;
+
+        state.build_committee_cache(relative_epoch, &self.spec)?;
+
+        let committee_cache = state.committee_cache(relative_epoch)?;
+
+        self.shuffling_cache
+            .try_write_for(self.lock_timeouts.attestation_cache)
+            .ok_or_else(|| Error::AttestationCacheLockTimeout)?
+            .insert(attestation_epoch, data.target.root, committee_cache);
+
+        metrics::stop_timer(committee_building_timer);
+
+        committee_cache
+            .get_beacon_committee(data.slot, data.index)
+            .map(|committee| f(committee.committee))
+            .transpose()
+    }
 
-                metrics::stop_timer(state_skip_timer);
-                let committee_building_timer =
-                    metrics::start_timer(&metrics::ATTESTATION_PROCESSING_COMMITTEE_BUILDING_TIMES);
+    pub fn process_attestation_internal(
+        &self,
+        attestation: Attestation<T::EthSpec>,
+    ) -> Result<AttestationProcessingOutcome, Error> {
+        let initial_validation_timer =
+            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_INITIAL_VALIDATION_TIMES);
 
-                let relative_epoch =
-                    RelativeEpoch::from_epoch(state.current_epoch(), attestation_epoch)
-                        .map_err(Error::IncorrectStateForAttestation)?;
+        // There is no point in processing an attestation with an empty bitfield. Reject
+        // it immediately.
+        if attestation.aggregation_bits.num_set_bits() == 0 {
+            return Ok(AttestationProcessingOutcome::EmptyAggregationBitfield);
+        }
 
-                state.build_committee_cache(relative_epoch, &self.spec)?;
+        let epoch_now = self.epoch()?;
 
-                let committee_cache = state.committee_cache(relative_epoch)?;
+        let (target_block_slot, target_block_state_root) =
+            match self.verify_attestation_target(&attestation.data, epoch_now) {
+                Ok(target_block) => target_block,
+                Err(outcome) => return Ok(outcome),
+            };
 
-                self.shuffling_cache
-                    .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
-                    .ok_or_else(|| Error::AttestationCacheLockTimeout)?
-                    .insert(attestation_epoch, target.root, committee_cache);
+        // Drop the attestation if it tells us nothing that an attestation we have already
+        // processed did not, avoiding the cost of verifying its signature.
+        let data_root = attestation.data.tree_hash_root();
+        if self
+            .observed_attestations
+            .is_known(&attestation, data_root, epoch_now)
+        {
+            return Ok(AttestationProcessingOutcome::AlreadyKnown);
+        }
 
-                metrics::stop_timer(committee_building_timer);
+        metrics::stop_timer(initial_validation_timer);
 
-                if let Some(committee) = committee_cache
-                    .get_beacon_committee(attestation.data.slot, attestation.data.index)
-                {
-                    get_indexed_attestation(committee.committee, &attestation)?
-                } else {
-                    return Ok(AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
-                        slot: attestation.data.slot,
-                        index: attestation.data.index,
-                    });
-                }
-            };
+        let indexed_attestation = match self.with_attestation_committee(
+            &attestation.data,
+            target_block_slot,
+            target_block_state_root,
+            |committee| get_indexed_attestation(committee, &attestation).map_err(Into::into),
+        )? {
+            Some(indexed_attestation) => indexed_attestation,
+            None => {
+                return Ok(AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
+                    slot: attestation.data.slot,
+                    index: attestation.data.index,
+                })
+            }
+        };
 
         let signature_setup_timer =
             metrics::start_timer(&metrics::ATTESTATION_PROCESSING_SIGNATURE_SETUP_TIMES);
 
         let pubkey_cache = self
             .validator_pubkey_cache
-            .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+            .try_read_for(self.lock_timeouts.validator_pubkey_cache)
             .ok_or_else(|| Error::ValidatorPubkeyCacheLockTimeout)?;
 
         let pubkeys = indexed_attestation
@@ -1033,7 +2890,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         let fork = self
             .canonical_head
-            .try_read_for(HEAD_LOCK_TIMEOUT)
+            .try_read_for(self.lock_timeouts.head)
             .ok_or_else(|| Error::CanonicalHeadLockTimeout)
             .map(|head| head.beacon_state.fork.clone())?;
 
@@ -1071,11 +2928,41 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 return Err(e.into());
             }
 
+            self.observed_attestations
+                .observe(&attestation, data_root, epoch_now);
+
+            if let Some(observed_attester_votes) = &self.observed_attester_votes {
+                for attester_slashing in
+                    observed_attester_votes.observe(&indexed_attestation, epoch_now)
+                {
+                    self.import_detected_attester_slashing(attester_slashing);
+                }
+            }
+
             // Provide the valid attestation to op pool, which may choose to retain the
             // attestation for inclusion in a future block.
-            if self.eth1_chain.is_some() {
-                self.op_pool
-                    .insert_attestation(attestation, &fork, &self.spec)?;
+            if self.mode == BeaconChainMode::Full && self.eth1_chain.is_some() {
+                metrics::observe(
+                    &metrics::OP_POOL_ATTESTATIONS_BEFORE_INSERTION,
+                    self.op_pool.num_attestations() as f64,
+                );
+
+                let observation =
+                    self.op_pool
+                        .insert_attestation(attestation, &fork, &self.spec)?;
+
+                metrics::inc_counter_vec(
+                    &metrics::OP_POOL_ATTESTATION_INSERTIONS,
+                    &[match observation {
+                        AttestationObservation::New => "new",
+                        AttestationObservation::Aggregated => "aggregated",
+                        AttestationObservation::AlreadyKnown => "already_known",
+                    }],
+                );
+                metrics::observe(
+                    &metrics::OP_POOL_ATTESTATIONS_AFTER_INSERTION,
+                    self.op_pool.num_attestations() as f64,
+                );
             };
 
             Ok(AttestationProcessingOutcome::Processed)
@@ -1084,18 +2971,250 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Returns the attestation with the most signers that is known for committee `index` at
+    /// `slot`, so that it may be published by an aggregator.
+    pub fn get_aggregated_attestation(
+        &self,
+        slot: Slot,
+        index: CommitteeIndex,
+    ) -> Option<Attestation<T::EthSpec>> {
+        self.op_pool.get_aggregated_attestation(slot, index)
+    }
+
+    /// Returns all of the attestations in the operation pool, without removing them.
+    ///
+    /// The pool aggregates attestations as they are inserted, so these are the aggregated forms
+    /// rather than the attestations that were originally received. There are
+    /// `self.op_pool.num_attestations()` of them.
+    pub fn get_pooled_attestations(&self) -> Vec<Attestation<T::EthSpec>> {
+        self.op_pool.get_all_attestations()
+    }
+
+    /// Provides each attestation in the operation pool to fork choice, restoring the votes that
+    /// fork choice had learned from them (e.g., prior to a restart).
+    ///
+    /// Attestations from before the finalized epoch, for blocks unknown to fork choice or with a
+    /// target which is not on the canonical chain are skipped. Returns the number of attestations
+    /// that were provided to fork choice.
+    ///
+    /// Fork choice only updates a vote which is newer than the latest message of a validator, so
+    /// it is safe to provide attestations which fork choice has already seen.
+    pub fn reprocess_op_pool_attestations_into_fork_choice(&self) -> Result<usize, Error> {
+        let finalized_epoch = self.head_info()?.finalized_checkpoint.epoch;
+        let mut processed = 0;
+
+        for attestation in self.op_pool.get_all_attestations() {
+            let data = &attestation.data;
+
+            if data.target.epoch < finalized_epoch
+                || !self.fork_choice.contains_block(&data.beacon_block_root)
+            {
+                continue;
+            }
+
+            let indexed_attestation = match self.canonical_indexed_attestation(&attestation) {
+                Ok(Some(indexed_attestation)) => indexed_attestation,
+                Ok(None) => continue,
+                Err(e) => {
+                    debug!(
+                        self.log,
+                        "Unable to reprocess pooled attestation";
+                        "slot" => data.slot,
+                        "index" => data.index,
+                        "error" => format!("{:?}", e)
+                    );
+                    continue;
+                }
+            };
+
+            self.fork_choice
+                .process_indexed_attestation(&indexed_attestation)?;
+            processed += 1;
+        }
+
+        Ok(processed)
+    }
+
+    /// Returns the indexed form of `attestation` if its target is on the canonical chain, or
+    /// `None` otherwise. The committees are read from the canonical chain, so they only apply to
+    /// attestations with a canonical target.
+    fn canonical_indexed_attestation(
+        &self,
+        attestation: &Attestation<T::EthSpec>,
+    ) -> Result<Option<IndexedAttestation<T::EthSpec>>, Error> {
+        let data = &attestation.data;
+
+        if self.target_checkpoint(data.target.epoch)? != data.target {
+            return Ok(None);
+        }
+
+        self.with_committee_cache(data.target.epoch, |cache| {
+            let committee = cache
+                .get_beacon_committee(data.slot, data.index)
+                .ok_or_else(|| Error::NoCommitteeForSlotAndIndex {
+                    slot: data.slot,
+                    index: data.index,
+                })?;
+
+            get_indexed_attestation(committee.committee, attestation).map_err(Into::into)
+        })
+        .map(Some)
+    }
+
+    /// Returns the attestations in the operation pool for `slot`, without removing them.
+    pub fn get_pooled_attestations_for_slot(&self, slot: Slot) -> Vec<Attestation<T::EthSpec>> {
+        self.op_pool.get_attestations_for_slot(slot)
+    }
+
+    /// Returns all of the attester slashings in the operation pool, without removing them.
+    pub fn get_pooled_attester_slashings(&self) -> Vec<AttesterSlashing<T::EthSpec>> {
+        self.op_pool.get_all_attester_slashings()
+    }
+
+    /// Returns `true` if an attestation with `data` from `validator_index` would be slashable, as
+    /// either a double vote or a surround vote.
+    ///
+    /// This is a best-effort check, intended as a sanity check for validator clients. The
+    /// attestation is only checked against the recent votes of the validator that this node has
+    /// observed in valid attestations, so a `false` result does not imply that the attestation is
+    /// safe to sign. Returns an error if slashing detection is disabled.
+    pub fn is_slashable_attestation(
+        &self,
+        validator_index: usize,
+        data: &AttestationData,
+    ) -> Result<bool, Error> {
+        self.observed_attester_votes
+            .as_ref()
+            .map(|votes| votes.is_slashable(validator_index as u64, data))
+            .ok_or_else(|| Error::SlashingDetectionDisabled)
+    }
+
+    /// Returns all of the proposer slashings in the operation pool, without removing them.
+    pub fn get_pooled_proposer_slashings(&self) -> Vec<ProposerSlashing> {
+        self.op_pool.get_all_proposer_slashings()
+    }
+
+    /// Returns all of the voluntary exits in the operation pool, without removing them.
+    pub fn get_pooled_voluntary_exits(&self) -> Vec<SignedVoluntaryExit> {
+        self.op_pool.get_all_voluntary_exits()
+    }
+
+    /// Verifies that the aggregator of `signed_aggregate` was selected to aggregate for the
+    /// aggregate's committee and signed it, then imports the aggregate.
+    ///
+    /// The aggregator is verified first, so that an aggregate which may not be published is never
+    /// provided to fork choice or the op pool. Only an outcome of `Processed` indicates that the
+    /// aggregate was imported, whilst an `InvalidAggregate(AlreadyKnown)` outcome indicates that
+    /// the aggregator was verified but the aggregate was known already.
+    pub fn process_aggregate_and_proof(
+        &self,
+        signed_aggregate: &SignedAggregateAndProof<T::EthSpec>,
+    ) -> Result<AggregateProcessingOutcome, Error> {
+        let aggregate_and_proof = &signed_aggregate.message;
+        let aggregate = &aggregate_and_proof.aggregate;
+        let aggregator_index = aggregate_and_proof.aggregator_index;
+
+        // The aggregate's target must be checked before its committee is loaded, so that an
+        // aggregate from a distant epoch cannot trigger a long state skip.
+        let (target_block_slot, target_block_state_root) =
+            match self.verify_attestation_target(&aggregate.data, self.epoch()?) {
+                Ok(target_block) => target_block,
+                Err(outcome) => return Ok(AggregateProcessingOutcome::InvalidAggregate(outcome)),
+            };
+
+        let (in_committee, committee_len) = match self.with_attestation_committee(
+            &aggregate.data,
+            target_block_slot,
+            target_block_state_root,
+            |committee| {
+                Ok((
+                    committee.contains(&(aggregator_index as usize)),
+                    committee.len(),
+                ))
+            },
+        )? {
+            Some(membership) => membership,
+            None => {
+                return Ok(AggregateProcessingOutcome::InvalidAggregate(
+                    AttestationProcessingOutcome::NoCommitteeForSlotAndIndex {
+                        slot: aggregate.data.slot,
+                        index: aggregate.data.index,
+                    },
+                ))
+            }
+        };
+
+        if !in_committee {
+            return Ok(AggregateProcessingOutcome::AggregatorNotInCommittee { aggregator_index });
+        }
+
+        let aggregator_pubkey = self
+            .validator_pubkey_cache
+            .try_read_for(self.lock_timeouts.validator_pubkey_cache)
+            .ok_or_else(|| Error::ValidatorPubkeyCacheLockTimeout)?
+            .get(aggregator_index as usize)
+            .cloned()
+            .ok_or_else(|| Error::ValidatorPubkeyCacheIncomplete(aggregator_index as usize))?;
+        let fork = self.head_info()?.fork;
+
+        if !aggregate_and_proof.is_valid_selection_proof(&aggregator_pubkey, &fork, &self.spec) {
+            return Ok(AggregateProcessingOutcome::InvalidSelectionProof { aggregator_index });
+        }
+
+        if !SelectionProof::from(aggregate_and_proof.selection_proof.clone())
+            .is_aggregator(committee_len, &self.spec)
+        {
+            return Ok(AggregateProcessingOutcome::NotAggregator { aggregator_index });
+        }
+
+        if !signed_aggregate.is_valid_signature(&aggregator_pubkey, &fork, &self.spec) {
+            return Ok(AggregateProcessingOutcome::InvalidSignature { aggregator_index });
+        }
+
+        match self.process_attestation(aggregate.clone())? {
+            AttestationProcessingOutcome::Processed => Ok(AggregateProcessingOutcome::Processed),
+            other => Ok(AggregateProcessingOutcome::InvalidAggregate(other)),
+        }
+    }
+
+    /// Returns the mode of this chain (i.e., whether it produces blocks or only follows the chain).
+    pub fn mode(&self) -> BeaconChainMode {
+        self.mode
+    }
+
+    /// Returns `Ok(())` if this node produces blocks and should therefore queue operations in the
+    /// op pool for inclusion in them.
+    fn queues_operations<E>(&self) -> Result<(), OperationImportError<E>> {
+        if self.mode == BeaconChainMode::Follower {
+            Err(OperationImportError::FollowerMode)
+        } else if self.eth1_chain.is_none() {
+            Err(OperationImportError::Eth1Required)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Accept some exit and queue it for inclusion in an appropriate block.
+    ///
+    /// Returns `OperationImportError::Eth1Required` or `OperationImportError::FollowerMode`
+    /// without validating the exit if this node cannot produce blocks.
     pub fn process_voluntary_exit(
         &self,
         exit: SignedVoluntaryExit,
-    ) -> Result<(), ExitValidationError> {
+    ) -> Result<(), OperationImportError<ExitValidationError>> {
+        self.queues_operations()?;
+
         match self.wall_clock_state() {
             Ok(state) => {
-                if self.eth1_chain.is_some() {
-                    self.op_pool.insert_voluntary_exit(exit, &state, &self.spec)
-                } else {
-                    Ok(())
-                }
+                self.op_pool
+                    .insert_voluntary_exit(exit.clone(), &state, &self.spec)
+                    .map_err(OperationImportError::Invalid)?;
+
+                let _ = self
+                    .event_handler
+                    .register(EventKind::VoluntaryExitReceived { exit });
+
+                Ok(())
             }
             Err(e) => {
                 error!(
@@ -1104,24 +3223,50 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     "error" => format!("{:?}", e),
                     "reason" => "no state"
                 );
-                Ok(())
+                Err(OperationImportError::BeaconChainError(e))
             }
         }
     }
 
+    /// Verifies some exit against the current state without queuing it.
+    ///
+    /// Allows a node which does not produce blocks to check an exit before publishing it, since
+    /// `Self::process_voluntary_exit` refuses such exits without validating them.
+    pub fn verify_voluntary_exit(
+        &self,
+        exit: &SignedVoluntaryExit,
+    ) -> Result<(), OperationImportError<ExitValidationError>> {
+        let state = self
+            .wall_clock_state()
+            .map_err(OperationImportError::BeaconChainError)?;
+
+        verify_exit_time_independent_only(&state, exit, VerifySignatures::True, &self.spec)
+            .map_err(OperationImportError::Invalid)
+    }
+
     /// Accept some proposer slashing and queue it for inclusion in an appropriate block.
+    ///
+    /// Returns `OperationImportError::Eth1Required` or `OperationImportError::FollowerMode`
+    /// without validating the slashing if this node cannot produce blocks.
     pub fn process_proposer_slashing(
         &self,
         proposer_slashing: ProposerSlashing,
-    ) -> Result<(), ProposerSlashingValidationError> {
+    ) -> Result<(), OperationImportError<ProposerSlashingValidationError>> {
+        self.queues_operations()?;
+
         match self.wall_clock_state() {
             Ok(state) => {
-                if self.eth1_chain.is_some() {
-                    self.op_pool
-                        .insert_proposer_slashing(proposer_slashing, &state, &self.spec)
-                } else {
-                    Ok(())
-                }
+                self.op_pool
+                    .insert_proposer_slashing(proposer_slashing.clone(), &state, &self.spec)
+                    .map_err(OperationImportError::Invalid)?;
+
+                let _ = self
+                    .event_handler
+                    .register(EventKind::ProposerSlashingReceived {
+                        proposer_slashing: Box::new(proposer_slashing),
+                    });
+
+                Ok(())
             }
             Err(e) => {
                 error!(
@@ -1130,45 +3275,314 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     "error" => format!("{:?}", e),
                     "reason" => "no state"
                 );
+                Err(OperationImportError::BeaconChainError(e))
+            }
+        }
+    }
+
+    /// Verifies some proposer slashing against the current state without queuing it.
+    ///
+    /// Allows a node which does not produce blocks to check a slashing, since
+    /// `Self::process_proposer_slashing` refuses such slashings without validating them.
+    pub fn verify_proposer_slashing(
+        &self,
+        proposer_slashing: &ProposerSlashing,
+    ) -> Result<(), OperationImportError<ProposerSlashingValidationError>> {
+        let state = self
+            .wall_clock_state()
+            .map_err(OperationImportError::BeaconChainError)?;
+
+        verify_proposer_slashing(
+            proposer_slashing,
+            &state,
+            VerifySignatures::True,
+            &self.spec,
+        )
+        .map_err(OperationImportError::Invalid)
+    }
+
+    /// Queues an attester slashing that was constructed from conflicting attestations observed by
+    /// this node for inclusion in an appropriate block.
+    fn import_detected_attester_slashing(&self, attester_slashing: AttesterSlashing<T::EthSpec>) {
+        metrics::inc_counter(&metrics::ATTESTER_SLASHINGS_DETECTED);
+
+        let target_epochs = (
+            attester_slashing.attestation_1.data.target.epoch,
+            attester_slashing.attestation_2.data.target.epoch,
+        );
+
+        match self.process_attester_slashing(attester_slashing) {
+            Ok(()) => info!(
+                self.log,
+                "Detected attester slashing";
+                "target_epoch_1" => target_epochs.0,
+                "target_epoch_2" => target_epochs.1,
+            ),
+            Err(OperationImportError::Eth1Required) | Err(OperationImportError::FollowerMode) => {
+                debug!(
+                    self.log,
+                    "Detected attester slashing not queued";
+                    "reason" => "block production disabled",
+                    "target_epoch_1" => target_epochs.0,
+                    "target_epoch_2" => target_epochs.1,
+                )
+            }
+            Err(e) => warn!(
+                self.log,
+                "Detected invalid attester slashing";
+                "error" => format!("{:?}", e),
+                "target_epoch_1" => target_epochs.0,
+                "target_epoch_2" => target_epochs.1,
+            ),
+        }
+    }
+
+    /// Records the header of `signed_block`, which must carry a valid signature from
+    /// `proposer_index`, queueing a proposer slashing if the proposer has already signed a
+    /// different block at the same slot.
+    fn observe_block_proposer(
+        &self,
+        signed_block: &SignedBeaconBlock<T::EthSpec>,
+        proposer_index: usize,
+    ) {
+        if let Some(observed_block_proposers) = &self.observed_block_proposers {
+            if let Some(proposer_slashing) = observed_block_proposers
+                .observe(proposer_index as u64, signed_block.signed_block_header())
+            {
+                self.import_detected_proposer_slashing(proposer_slashing);
+            }
+        }
+    }
+
+    /// Queues a proposer slashing that was constructed from conflicting blocks observed by this
+    /// node for inclusion in an appropriate block.
+    fn import_detected_proposer_slashing(&self, proposer_slashing: ProposerSlashing) {
+        metrics::inc_counter(&metrics::PROPOSER_SLASHINGS_DETECTED);
+
+        let proposer_index = proposer_slashing.proposer_index;
+        let slot = proposer_slashing.signed_header_1.message.slot;
+
+        match self.process_proposer_slashing(proposer_slashing) {
+            Ok(()) => info!(
+                self.log,
+                "Detected proposer slashing";
+                "proposer_index" => proposer_index,
+                "slot" => slot,
+            ),
+            Err(OperationImportError::Eth1Required) | Err(OperationImportError::FollowerMode) => {
+                debug!(
+                    self.log,
+                    "Detected proposer slashing not queued";
+                    "reason" => "block production disabled",
+                    "proposer_index" => proposer_index,
+                    "slot" => slot,
+                )
+            }
+            Err(e) => warn!(
+                self.log,
+                "Detected invalid proposer slashing";
+                "error" => format!("{:?}", e),
+                "proposer_index" => proposer_index,
+                "slot" => slot,
+            ),
+        }
+    }
+
+    /// Accept some attester slashing and queue it for inclusion in an appropriate block.
+    ///
+    /// Returns `OperationImportError::Eth1Required` or `OperationImportError::FollowerMode`
+    /// without validating the slashing if this node cannot produce blocks.
+    pub fn process_attester_slashing(
+        &self,
+        attester_slashing: AttesterSlashing<T::EthSpec>,
+    ) -> Result<(), OperationImportError<AttesterSlashingValidationError>> {
+        self.queues_operations()?;
+
+        match self.wall_clock_state() {
+            Ok(state) => {
+                self.op_pool
+                    .insert_attester_slashing(attester_slashing.clone(), &state, &self.spec)
+                    .map_err(OperationImportError::Invalid)?;
+
+                let _ = self
+                    .event_handler
+                    .register(EventKind::AttesterSlashingReceived {
+                        attester_slashing: Box::new(attester_slashing),
+                    });
+
                 Ok(())
             }
+            Err(e) => {
+                error!(
+                    &self.log,
+                    "Unable to process attester slashing";
+                    "error" => format!("{:?}", e),
+                    "reason" => "no state"
+                );
+                Err(OperationImportError::BeaconChainError(e))
+            }
         }
     }
 
-    /// Accept some attester slashing and queue it for inclusion in an appropriate block.
-    pub fn process_attester_slashing(
-        &self,
-        attester_slashing: AttesterSlashing<T::EthSpec>,
-    ) -> Result<(), AttesterSlashingValidationError> {
-        match self.wall_clock_state() {
-            Ok(state) => {
-                if self.eth1_chain.is_some() {
-                    self.op_pool
-                        .insert_attester_slashing(attester_slashing, &state, &self.spec)
-                } else {
-                    Ok(())
-                }
-            }
-            Err(e) => {
-                error!(
-                    &self.log,
-                    "Unable to process attester slashing";
-                    "error" => format!("{:?}", e),
-                    "reason" => "no state"
-                );
-                Ok(())
-            }
+    /// Verifies some attester slashing against the current state without queuing it.
+    ///
+    /// Allows a node which does not produce blocks to check a slashing, since
+    /// `Self::process_attester_slashing` refuses such slashings without validating them.
+    pub fn verify_attester_slashing(
+        &self,
+        attester_slashing: &AttesterSlashing<T::EthSpec>,
+    ) -> Result<(), OperationImportError<AttesterSlashingValidationError>> {
+        let state = self
+            .wall_clock_state()
+            .map_err(OperationImportError::BeaconChainError)?;
+
+        verify_attester_slashing(
+            &state,
+            attester_slashing,
+            true,
+            VerifySignatures::True,
+            &self.spec,
+        )
+        .map_err(OperationImportError::Invalid)
+    }
+
+    /// Checks that `block` is from a recent slot, that its parent is known and that it is signed by
+    /// the expected proposer for its slot, without applying it to its parent state.
+    ///
+    /// These checks are much cheaper than those of `Self::process_block`, making them suitable for
+    /// the first stage of gossip validation. The returned `GossipVerifiedBlock` may be passed to
+    /// `Self::process_block`, which will not verify the proposal signature again.
+    pub fn verify_block_for_gossip(
+        &self,
+        block: SignedBeaconBlock<T::EthSpec>,
+    ) -> Result<GossipVerifiedBlock<T::EthSpec>, GossipBlockError> {
+        metrics::inc_counter(&metrics::BLOCK_GOSSIP_VERIFICATION_REQUESTS);
+        let _timer = metrics::start_timer(&metrics::BLOCK_GOSSIP_VERIFICATION_TIMES);
+
+        let block_slot = block.slot();
+
+        if block_slot == 0 {
+            return Err(GossipBlockError::GenesisBlock);
+        }
+
+        // Allow for a small disparity between our clock and the clock of the proposer by accepting
+        // blocks from a slot which is about to start.
+        let present_slot = self.slot()?;
+
+        if block_slot > self.latest_permissible_slot()? {
+            return Err(GossipBlockError::FutureSlot {
+                present_slot,
+                block_slot,
+            });
+        }
+
+        let finalized_slot = std::cmp::max(
+            self.head_info()?
+                .finalized_checkpoint
+                .epoch
+                .start_slot(T::EthSpec::slots_per_epoch()),
+            self.anchor_slot,
+        );
+
+        if block_slot <= finalized_slot {
+            return Err(GossipBlockError::WouldRevertFinalizedSlot {
+                block_slot,
+                finalized_slot,
+            });
+        }
+
+        let parent_root = block.parent_root();
+
+        if !self.fork_choice.contains_block(&parent_root) {
+            return Err(GossipBlockError::ParentUnknown {
+                parent: parent_root,
+            });
+        }
+
+        let block_root = block.canonical_root();
+
+        if self.fork_choice.contains_block(&block_root) {
+            return Err(GossipBlockError::BlockIsAlreadyKnown);
+        }
+
+        // Load the state of the parent and skip it to the block slot, so that the proposer is
+        // computed from the shuffling of the chain the block builds upon.
+        //
+        // The proposer shuffling is not affected by the state roots, so we don't need to calculate
+        // them.
+        let head = self.head()?;
+        let (mut state, state_root) = if head.beacon_block_root == parent_root {
+            (
+                head.beacon_state.clone_with_only_committee_caches(),
+                head.beacon_state_root,
+            )
+        } else {
+            drop(head);
+            let parent_block = self
+                .get_block(&parent_root)?
+                .ok_or_else(|| Error::MissingBeaconBlock(parent_root))?;
+            let parent_state_root = parent_block.state_root();
+            let parent_state = self
+                .get_state(&parent_state_root, Some(parent_block.slot()))?
+                .ok_or_else(|| Error::MissingBeaconState(parent_state_root))?;
+            (parent_state, parent_state_root)
+        };
+        self.advance_state_to_slot(
+            &mut state,
+            Some(state_root),
+            block_slot,
+            StateSkipConfig::WithoutStateRoots,
+            self.default_state_skip_deadline(),
+        )?;
+
+        state
+            .build_committee_cache(RelativeEpoch::Current, &self.spec)
+            .map_err(Error::from)?;
+
+        let proposer_index = state
+            .get_beacon_proposer_index(block_slot, &self.spec)
+            .map_err(Error::from)?;
+
+        let signature_is_valid =
+            block_proposal_signature_set(&state, &block, Some(block_root), &self.spec)
+                .map_err(Error::SignatureSetError)?
+                .is_valid();
+
+        if !signature_is_valid {
+            return Err(GossipBlockError::ProposalSignatureInvalid { proposer_index });
         }
+
+        metrics::inc_counter(&metrics::BLOCK_GOSSIP_VERIFICATION_SUCCESSES);
+
+        Ok(GossipVerifiedBlock {
+            block,
+            block_root,
+            proposer_index,
+        })
     }
 
     /// Accept some block and attempt to add it to block DAG.
     ///
     /// Will accept blocks from prior slots, however it will reject any block from a future slot.
-    pub fn process_block(
+    ///
+    /// The proposal signature of a `GossipVerifiedBlock` is not verified again.
+    pub fn process_block<B: IntoBlockForImport<T::EthSpec>>(
         &self,
-        block: SignedBeaconBlock<T::EthSpec>,
+        block: B,
     ) -> Result<BlockProcessingOutcome, Error> {
-        let outcome = self.process_block_internal(block.clone());
+        let (block, verified_block_root) = block.into_block_for_import();
+        let block_slot = block.slot();
+
+        // Only copy the block for the event handler if it will be used.
+        let event_block = if self.event_handler.wants(EventTopic::Block) {
+            Some(block.clone())
+        } else {
+            None
+        };
+
+        let outcome = self.process_block_internal(block, verified_block_root);
+
+        self.chain_stats.observe_block(&outcome);
 
         match &outcome {
             Ok(outcome) => match outcome {
@@ -1177,23 +3591,44 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                         self.log,
                         "Beacon block imported";
                         "block_root" => format!("{:?}", block_root),
-                        "block_slot" => format!("{:?}", block.slot().as_u64()),
+                        "block_slot" => format!("{:?}", block_slot.as_u64()),
                     );
-                    let _ = self.event_handler.register(EventKind::BeaconBlockImported {
-                        block_root: *block_root,
-                        block: Box::new(block),
-                    });
+                    if let Some(block) = event_block {
+                        let _ = self.event_handler.register(EventKind::BeaconBlockImported {
+                            block_root: *block_root,
+                            block: Box::new(block),
+                        });
+                    }
                 }
                 other => {
+                    if let BlockProcessingOutcome::FutureSlot {
+                        present_slot,
+                        block_slot,
+                    } = other
+                    {
+                        metrics::inc_counter(&metrics::BLOCK_PROCESSING_FUTURE_SLOT);
+                        if let Some(block) = &event_block {
+                            let event = EventKind::BeaconBlockFromFutureSlot {
+                                block_root: block.canonical_root(),
+                                block_slot: *block_slot,
+                                present_slot: *present_slot,
+                                skew: block_slot.as_u64().saturating_sub(present_slot.as_u64()),
+                            };
+                            let _ = self.event_handler.register(event);
+                        }
+                    }
+
                     trace!(
                         self.log,
                         "Beacon block rejected";
                         "reason" => format!("{:?}", other),
                     );
-                    let _ = self.event_handler.register(EventKind::BeaconBlockRejected {
-                        reason: format!("Invalid block: {:?}", other),
-                        block: Box::new(block),
-                    });
+                    if let Some(block) = event_block {
+                        let _ = self.event_handler.register(EventKind::BeaconBlockRejected {
+                            reason: format!("Invalid block: {:?}", other),
+                            block: Box::new(block),
+                        });
+                    }
                 }
             },
             Err(e) => {
@@ -1202,10 +3637,12 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     "Beacon block processing error";
                     "error" => format!("{:?}", e),
                 );
-                let _ = self.event_handler.register(EventKind::BeaconBlockRejected {
-                    reason: format!("Internal error: {:?}", e),
-                    block: Box::new(block),
-                });
+                if let Some(block) = event_block {
+                    let _ = self.event_handler.register(EventKind::BeaconBlockRejected {
+                        reason: format!("Internal error: {:?}", e),
+                        block: Box::new(block),
+                    });
+                }
             }
         }
 
@@ -1215,34 +3652,183 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// Accept some block and attempt to add it to block DAG.
     ///
     /// Will accept blocks from prior slots, however it will reject any block from a future slot.
+    ///
+    /// If `verified_block_root` is `Some`, it must be the root of `signed_block`, whose proposal
+    /// signature has already been verified.
     fn process_block_internal(
         &self,
         signed_block: SignedBeaconBlock<T::EthSpec>,
+        verified_block_root: Option<Hash256>,
     ) -> Result<BlockProcessingOutcome, Error> {
         metrics::inc_counter(&metrics::BLOCK_PROCESSING_REQUESTS);
         let full_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_TIMES);
 
+        let ValidatedBlock {
+            block_root,
+            state,
+            state_root,
+            parent_slot,
+            intermediate_states,
+        } = match self.check_block(&signed_block, verified_block_root, true)? {
+            Ok(validated) => validated,
+            Err(outcome) => return Ok(outcome),
+        };
+
         let block = &signed_block.message;
 
-        let finalized_slot = self
-            .head_info()?
-            .finalized_checkpoint
-            .epoch
-            .start_slot(T::EthSpec::slots_per_epoch());
+        let fork_choice_register_timer =
+            metrics::start_timer(&metrics::BLOCK_PROCESSING_FORK_CHOICE_REGISTER);
+
+        // If there are new validators in this block, update our pubkey cache.
+        //
+        // We perform this _before_ adding the block to fork choice because the pubkey cache is
+        // used by attestation processing which will only process an attestation if the block is
+        // known to fork choice. This ordering ensure that the pubkey cache is always up-to-date.
+        self.validator_pubkey_cache
+            .try_write_for(self.lock_timeouts.validator_pubkey_cache)
+            .ok_or_else(|| Error::ValidatorPubkeyCacheLockTimeout)?
+            .import_new_pubkeys(&state)?;
+
+        // If the imported block is in the previous or current epochs (according to the
+        // wall-clock), check to see if this is the first block of the epoch. If so, add the
+        // committee to the shuffling cache.
+        if state.current_epoch() + 1 >= self.epoch()?
+            && parent_slot.epoch(T::EthSpec::slots_per_epoch()) != state.current_epoch()
+        {
+            let mut shuffling_cache = self
+                .shuffling_cache
+                .try_write_for(self.lock_timeouts.attestation_cache)
+                .ok_or_else(|| Error::AttestationCacheLockTimeout)?;
+
+            let committee_cache = state.committee_cache(RelativeEpoch::Current)?;
+
+            let epoch_start_slot = state
+                .current_epoch()
+                .start_slot(T::EthSpec::slots_per_epoch());
+            let target_root = if state.slot == epoch_start_slot {
+                block_root
+            } else {
+                *state.get_block_root(epoch_start_slot)?
+            };
+
+            shuffling_cache.insert(state.current_epoch(), target_root, committee_cache);
+        }
+
+        // Register the new block with the fork choice service.
+        if let Err(e) = self
+            .fork_choice
+            .process_block(self, &state, &block, block_root)
+        {
+            error!(
+                self.log,
+                "Add block to fork choice failed";
+                "block_root" =>  format!("{}", block_root),
+                "error" => format!("{:?}", e),
+            )
+        }
+
+        if self.warm_balances_cache_on_import {
+            if let Err(e) = self.fork_choice.warm_balances_cache(block_root, &state) {
+                warn!(
+                    self.log,
+                    "Unable to warm balances cache";
+                    "block_root" =>  format!("{}", block_root),
+                    "error" => format!("{:?}", e),
+                )
+            }
+        }
+
+        metrics::stop_timer(fork_choice_register_timer);
+
+        self.head_tracker.register_block(block_root, &block);
+        self.validator_monitor
+            .process_block(&block, &state, &self.spec);
+        metrics::observe(
+            &metrics::OPERATIONS_PER_BLOCK_ATTESTATION,
+            block.body.attestations.len() as f64,
+        );
+
+        let db_write_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_DB_WRITE);
+
+        // Store all the states between the parent block state and this block's slot before storing
+        // the final state.
+        intermediate_states.commit(&*self.store)?;
+
+        // Store the block and state.
+        // NOTE: we store the block *after* the state to guard against inconsistency in the event of
+        // a crash, as states are usually looked up from blocks, not the other way around. A better
+        // solution would be to use a database transaction (once our choice of database and API
+        // settles down).
+        // See: https://github.com/sigp/lighthouse/issues/692
+        self.store.put_state(&state_root, state)?;
+        self.store.put_block(&block_root, signed_block)?;
+
+        metrics::stop_timer(db_write_timer);
+
+        metrics::inc_counter(&metrics::BLOCK_PROCESSING_SUCCESSES);
+
+        metrics::stop_timer(full_timer);
+
+        Ok(BlockProcessingOutcome::Processed { block_root })
+    }
+
+    /// Checks whether `block` would be imported by `Self::process_block`, without importing it.
+    ///
+    /// Performs all of the checks and the state transition of `Self::process_block`, but does not
+    /// modify the store, fork choice, head tracker or any caches. Returns
+    /// `BlockProcessingOutcome::Processed` if the block is valid.
+    pub fn validate_block(
+        &self,
+        block: &SignedBeaconBlock<T::EthSpec>,
+    ) -> Result<BlockProcessingOutcome, Error> {
+        Ok(match self.check_block(block, None, false)? {
+            Ok(validated) => BlockProcessingOutcome::Processed {
+                block_root: validated.block_root,
+            },
+            Err(outcome) => outcome,
+        })
+    }
+
+    /// Performs all of the checks required to import `signed_block` and applies it to its parent
+    /// state, without persisting anything.
+    ///
+    /// Returns `Err(outcome)` if the block is invalid. If `importing` is `true` and the block was
+    /// produced by this node, the state computed during production is used instead of
+    /// re-processing the block. Blocks which are being imported are also recorded for proposer
+    /// slashing detection once their proposal signature is known to be valid, even if they are
+    /// otherwise invalid.
+    ///
+    /// If `verified_block_root` is `Some`, it must be the root of `signed_block`, whose proposal
+    /// signature has already been verified.
+    fn check_block(
+        &self,
+        signed_block: &SignedBeaconBlock<T::EthSpec>,
+        verified_block_root: Option<Hash256>,
+        importing: bool,
+    ) -> Result<Result<ValidatedBlock<T::EthSpec>, BlockProcessingOutcome>, Error> {
+        let block = &signed_block.message;
+
+        let finalized_slot = std::cmp::max(
+            self.head_info()?
+                .finalized_checkpoint
+                .epoch
+                .start_slot(T::EthSpec::slots_per_epoch()),
+            self.anchor_slot,
+        );
 
         if block.slot == 0 {
-            return Ok(BlockProcessingOutcome::GenesisBlock);
+            return Ok(Err(BlockProcessingOutcome::GenesisBlock));
         }
 
         if block.slot >= MAXIMUM_BLOCK_SLOT_NUMBER {
-            return Ok(BlockProcessingOutcome::BlockSlotLimitReached);
+            return Ok(Err(BlockProcessingOutcome::BlockSlotLimitReached));
         }
 
         if block.slot <= finalized_slot {
-            return Ok(BlockProcessingOutcome::WouldRevertFinalizedSlot {
+            return Ok(Err(BlockProcessingOutcome::WouldRevertFinalizedSlot {
                 block_slot: block.slot,
                 finalized_slot,
-            });
+            }));
         }
 
         // Reject any block if its parent is not known to fork choice.
@@ -1256,225 +3842,277 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         //  choice, so we will not reject any child of the finalized block (this is relevant during
         //  genesis).
         if !self.fork_choice.contains_block(&block.parent_root) {
-            return Ok(BlockProcessingOutcome::ParentUnknown {
+            return Ok(Err(BlockProcessingOutcome::ParentUnknown {
                 parent: block.parent_root,
                 reference_location: "fork_choice",
-            });
+            }));
         }
 
         let block_root_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_BLOCK_ROOT);
 
-        let block_root = block.canonical_root();
+        let block_root = verified_block_root.unwrap_or_else(|| block.canonical_root());
 
         metrics::stop_timer(block_root_timer);
 
         if block_root == self.genesis_block_root {
-            return Ok(BlockProcessingOutcome::GenesisBlock);
+            return Ok(Err(BlockProcessingOutcome::GenesisBlock));
         }
 
         let present_slot = self.slot()?;
 
-        if block.slot > present_slot {
-            return Ok(BlockProcessingOutcome::FutureSlot {
+        if block.slot > self.latest_permissible_slot()? {
+            return Ok(Err(BlockProcessingOutcome::FutureSlot {
                 present_slot,
                 block_slot: block.slot,
-            });
+            }));
         }
 
         // Check if the block is already known. We know it is post-finalization, so it is
         // sufficient to check the fork choice.
         if self.fork_choice.contains_block(&block_root) {
-            return Ok(BlockProcessingOutcome::BlockIsAlreadyKnown);
+            return Ok(Err(BlockProcessingOutcome::BlockIsAlreadyKnown));
         }
 
-        // Records the time taken to load the block and state from the database during block
-        // processing.
-        let db_read_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_DB_READ);
-
-        // Load the blocks parent block from the database, returning invalid if that block is not
-        // found.
-        let parent_block = match self.get_block(&block.parent_root)? {
-            Some(block) => block,
-            None => {
-                return Ok(BlockProcessingOutcome::ParentUnknown {
-                    parent: block.parent_root,
-                    reference_location: "database",
-                });
-            }
+        // If this block was produced by this node, reuse the state computed during production.
+        // Otherwise, load the parent state and apply the block to it.
+        let produced_block_state = if importing {
+            self.take_produced_block_state(signed_block, block_root, present_slot)?
+        } else {
+            None
         };
 
-        // Load the parent blocks state from the database, returning an error if it is not found.
-        // It is an error because if we know the parent block we should also know the parent state.
-        let parent_state_root = parent_block.state_root();
-        let parent_state = self
-            .get_state(&parent_state_root, Some(parent_block.slot()))?
-            .ok_or_else(|| {
-                Error::DBInconsistent(format!("Missing state {:?}", parent_state_root))
-            })?;
-
-        metrics::stop_timer(db_read_timer);
-
-        write_block(&block, block_root, &self.log);
+        let (state, state_root, parent_slot, intermediate_states) =
+            if let Some(state) = produced_block_state {
+                self.chain_stats.observe_produced_block_reuse();
 
-        let catchup_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_CATCHUP_STATE);
-
-        // Keep a batch of any states that were "skipped" (block-less) in between the parent state
-        // slot and the block slot. These will be stored in the database.
-        let mut intermediate_states = StateBatch::new();
+                // The proposal signature was verified by `Self::take_produced_block_state`.
+                if self.observed_block_proposers.is_some() {
+                    let proposer_index = state.get_beacon_proposer_index(block.slot, &self.spec)?;
+                    self.observe_block_proposer(signed_block, proposer_index);
+                }
 
-        // Transition the parent state to the block slot.
-        let mut state: BeaconState<T::EthSpec> = parent_state;
-        let distance = block.slot.as_u64().saturating_sub(state.slot.as_u64());
-        for i in 0..distance {
-            let state_root = if i == 0 {
-                parent_block.state_root()
+                (state, block.state_root, block.slot - 1, StateBatch::new())
             } else {
-                // This is a new state we've reached, so stage it for storage in the DB.
-                // Computing the state root here is time-equivalent to computing it during slot
-                // processing, but we get early access to it.
-                let state_root = state.update_tree_hash_cache()?;
-                intermediate_states.add_state(state_root, &state)?;
-                state_root
-            };
-
-            per_slot_processing(&mut state, Some(state_root), &self.spec)?;
-        }
-
-        metrics::stop_timer(catchup_timer);
-
-        let committee_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_COMMITTEE);
-
-        state.build_committee_cache(RelativeEpoch::Previous, &self.spec)?;
-        state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
-
-        metrics::stop_timer(committee_timer);
-
-        write_state(
-            &format!("state_pre_block_{}", block_root),
-            &state,
-            &self.log,
-        );
-
-        let core_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_CORE);
-
-        // Apply the received block to its parent state (which has been transitioned into this
-        // slot).
-        match per_block_processing(
-            &mut state,
-            &signed_block,
-            Some(block_root),
-            BlockSignatureStrategy::VerifyBulk,
-            &self.spec,
-        ) {
-            Err(BlockProcessingError::BeaconStateError(e)) => {
-                return Err(Error::BeaconStateError(e))
-            }
-            Err(e) => return Ok(BlockProcessingOutcome::PerBlockProcessingError(e)),
-            _ => {}
-        }
-
-        metrics::stop_timer(core_timer);
+                // Records the time taken to load the block and state from the database during block
+                // processing.
+                let db_read_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_DB_READ);
+
+                // Load the blocks parent block from the database, returning invalid if that block
+                // is not found.
+                let parent_block = match self.get_block(&block.parent_root)? {
+                    Some(block) => block,
+                    None => {
+                        return Ok(Err(BlockProcessingOutcome::ParentUnknown {
+                            parent: block.parent_root,
+                            reference_location: "database",
+                        }));
+                    }
+                };
 
-        let state_root_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_STATE_ROOT);
+                // Load the parent blocks state from the database, returning an error if it is not
+                // found. It is an error because if we know the parent block we should also know the
+                // parent state.
+                let parent_state_root = parent_block.state_root();
+                let parent_state = self
+                    .get_state(&parent_state_root, Some(parent_block.slot()))?
+                    .ok_or_else(|| {
+                        Error::DBInconsistent(format!("Missing state {:?}", parent_state_root))
+                    })?;
+
+                metrics::stop_timer(db_read_timer);
+
+                let catchup_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_CATCHUP_STATE);
+
+                // Keep a batch of any states that were "skipped" (block-less) in between the parent
+                // state slot and the block slot. These will be stored in the database.
+                let mut intermediate_states = StateBatch::new();
+
+                // Transition the parent state to the block slot.
+                let mut state: BeaconState<T::EthSpec> = parent_state;
+                self.check_skip_slots(state.slot, block.slot)?;
+                let distance = block.slot.as_u64().saturating_sub(state.slot.as_u64());
+                for i in 0..distance {
+                    let state_root = if i == 0 {
+                        parent_block.state_root()
+                    } else {
+                        // This is a new state we've reached, so stage it for storage in the DB.
+                        // Computing the state root here is time-equivalent to computing it during
+                        // slot processing, but we get early access to it.
+                        let state_root = update_tree_hash_cache(&mut state)?;
+                        intermediate_states.add_state(state_root, &state)?;
+                        state_root
+                    };
 
-        let state_root = state.update_tree_hash_cache()?;
+                    per_slot_processing(&mut state, Some(state_root), &self.spec)?;
+                }
 
-        metrics::stop_timer(state_root_timer);
+                metrics::stop_timer(catchup_timer);
 
-        write_state(
-            &format!("state_post_block_{}", block_root),
-            &state,
-            &self.log,
-        );
+                let committee_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_COMMITTEE);
 
-        if block.state_root != state_root {
-            return Ok(BlockProcessingOutcome::StateRootMismatch {
-                block: block.state_root,
-                local: state_root,
-            });
-        }
+                state.build_committee_cache(RelativeEpoch::Previous, &self.spec)?;
+                state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
 
-        let fork_choice_register_timer =
-            metrics::start_timer(&metrics::BLOCK_PROCESSING_FORK_CHOICE_REGISTER);
+                metrics::stop_timer(committee_timer);
 
-        // If there are new validators in this block, update our pubkey cache.
-        //
-        // We perform this _before_ adding the block to fork choice because the pubkey cache is
-        // used by attestation processing which will only process an attestation if the block is
-        // known to fork choice. This ordering ensure that the pubkey cache is always up-to-date.
-        self.validator_pubkey_cache
-            .try_write_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
-            .ok_or_else(|| Error::ValidatorPubkeyCacheLockTimeout)?
-            .import_new_pubkeys(&state)?;
+                let core_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_CORE);
 
-        // If the imported block is in the previous or current epochs (according to the
-        // wall-clock), check to see if this is the first block of the epoch. If so, add the
-        // committee to the shuffling cache.
-        if state.current_epoch() + 1 >= self.epoch()?
-            && parent_block.slot().epoch(T::EthSpec::slots_per_epoch()) != state.current_epoch()
-        {
-            let mut shuffling_cache = self
-                .shuffling_cache
-                .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
-                .ok_or_else(|| Error::AttestationCacheLockTimeout)?;
+                let signature_strategy = if verified_block_root.is_some() {
+                    BlockSignatureStrategy::VerifyBulkExceptProposal
+                } else {
+                    BlockSignatureStrategy::VerifyBulk
+                };
 
-            let committee_cache = state.committee_cache(RelativeEpoch::Current)?;
+                let proposer_index = if importing && self.observed_block_proposers.is_some() {
+                    Some(state.get_beacon_proposer_index(block.slot, &self.spec)?)
+                } else {
+                    None
+                };
 
-            let epoch_start_slot = state
-                .current_epoch()
-                .start_slot(T::EthSpec::slots_per_epoch());
-            let target_root = if state.slot == epoch_start_slot {
-                block_root
-            } else {
-                *state.get_block_root(epoch_start_slot)?
-            };
+                // Keep a copy of the pre-state for debugging, in case the block is invalid.
+                let pre_state = self
+                    .invalid_block_dump
+                    .as_ref()
+                    .map(|_| state.clone_with_only_committee_caches());
+
+                // Apply the received block to its parent state (which has been transitioned into
+                // this slot).
+                let block_processing_result = per_block_processing(
+                    &mut state,
+                    signed_block,
+                    Some(block_root),
+                    signature_strategy,
+                    &self.spec,
+                );
 
-            shuffling_cache.insert(state.current_epoch(), target_root, committee_cache);
-        }
+                // Record the proposal if it is signed by the proposer, even if the block is
+                // otherwise invalid. Block processing does not modify the proposer's public key or
+                // the fork, so the proposal signature may still be verified against `state`.
+                if let Some(proposer_index) = proposer_index {
+                    let proposal_is_verified = verified_block_root.is_some()
+                        || block_processing_result.is_ok()
+                        || block_proposal_signature_set(
+                            &state,
+                            signed_block,
+                            Some(block_root),
+                            &self.spec,
+                        )
+                        .map_or(false, |signature_set| signature_set.is_valid());
+
+                    if proposal_is_verified {
+                        self.observe_block_proposer(signed_block, proposer_index);
+                    }
+                }
 
-        // Register the new block with the fork choice service.
-        if let Err(e) = self
-            .fork_choice
-            .process_block(self, &state, &block, block_root)
-        {
-            error!(
-                self.log,
-                "Add block to fork choice failed";
-                "block_root" =>  format!("{}", block_root),
-                "error" => format!("{:?}", e),
-            )
-        }
+                match block_processing_result {
+                    Err(BlockProcessingError::BeaconStateError(e)) => {
+                        return Err(Error::BeaconStateError(e))
+                    }
+                    Err(e) => {
+                        self.dump_invalid_block(signed_block, block_root, pre_state.as_ref());
+                        return Ok(Err(BlockProcessingOutcome::PerBlockProcessingError(e)));
+                    }
+                    _ => {}
+                }
 
-        metrics::stop_timer(fork_choice_register_timer);
+                metrics::stop_timer(core_timer);
 
-        self.head_tracker.register_block(block_root, &block);
-        metrics::observe(
-            &metrics::OPERATIONS_PER_BLOCK_ATTESTATION,
-            block.body.attestations.len() as f64,
-        );
+                let state_root_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_STATE_ROOT);
 
-        let db_write_timer = metrics::start_timer(&metrics::BLOCK_PROCESSING_DB_WRITE);
+                let state_root = update_tree_hash_cache(&mut state)?;
 
-        // Store all the states between the parent block state and this block's slot before storing
-        // the final state.
-        intermediate_states.commit(&*self.store)?;
+                metrics::stop_timer(state_root_timer);
 
-        // Store the block and state.
-        // NOTE: we store the block *after* the state to guard against inconsistency in the event of
-        // a crash, as states are usually looked up from blocks, not the other way around. A better
-        // solution would be to use a database transaction (once our choice of database and API
-        // settles down).
-        // See: https://github.com/sigp/lighthouse/issues/692
-        self.store.put_state(&state_root, state)?;
-        self.store.put_block(&block_root, signed_block)?;
+                if block.state_root != state_root {
+                    self.dump_invalid_block(signed_block, block_root, pre_state.as_ref());
+                    return Ok(Err(BlockProcessingOutcome::StateRootMismatch {
+                        block: block.state_root,
+                        local: state_root,
+                    }));
+                }
 
-        metrics::stop_timer(db_write_timer);
+                (state, state_root, parent_block.slot(), intermediate_states)
+            };
 
-        metrics::inc_counter(&metrics::BLOCK_PROCESSING_SUCCESSES);
+        Ok(Ok(ValidatedBlock {
+            block_root,
+            state,
+            state_root,
+            parent_slot,
+            intermediate_states,
+        }))
+    }
 
-        metrics::stop_timer(full_timer);
+    /// Writes `signed_block` and the `pre_state` to which it was applied to the invalid block
+    /// dump directory, if one is configured.
+    fn dump_invalid_block(
+        &self,
+        signed_block: &SignedBeaconBlock<T::EthSpec>,
+        block_root: Hash256,
+        pre_state: Option<&BeaconState<T::EthSpec>>,
+    ) {
+        let (dump, pre_state) = match (&self.invalid_block_dump, pre_state) {
+            (Some(dump), Some(pre_state)) => (dump, pre_state),
+            _ => return,
+        };
 
-        Ok(BlockProcessingOutcome::Processed { block_root })
+        match dump.dump(signed_block, block_root, pre_state) {
+            Ok(Some(files)) => {
+                metrics::inc_counter(&metrics::INVALID_BLOCK_DUMPS);
+                info!(
+                    self.log,
+                    "Dumped invalid block";
+                    "pre_state" => format!("{:?}", files.pre_state),
+                    "block" => format!("{:?}", files.block),
+                    "block_root" => format!("{}", block_root),
+                );
+            }
+            Ok(None) => debug!(
+                self.log,
+                "Invalid block not dumped";
+                "reason" => "already dumped or dump directory is full",
+                "dir" => format!("{:?}", dump.dir()),
+                "block_root" => format!("{}", block_root),
+            ),
+            Err(e) => error!(
+                self.log,
+                "Failed to dump invalid block";
+                "error" => e,
+                "block_root" => format!("{}", block_root),
+            ),
+        }
+    }
+
+    /// Returns the post-state of `signed_block` if it was produced by this node (see
+    /// `Self::produce_block`) and its proposer and randao signatures are valid.
+    ///
+    /// Returns `None` if the block must be imported by applying it to its parent state.
+    fn take_produced_block_state(
+        &self,
+        signed_block: &SignedBeaconBlock<T::EthSpec>,
+        block_root: Hash256,
+        present_slot: Slot,
+    ) -> Result<Option<BeaconState<T::EthSpec>>, Error> {
+        let state = match self.produced_block_cache.take(block_root, present_slot) {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+
+        // The block was produced without a signature, so the signatures are the only part of the
+        // block that has not already been verified.
+        let signatures_are_valid =
+            block_proposal_signature_set(&state, signed_block, Some(block_root), &self.spec)
+                .map_err(Error::SignatureSetError)?
+                .is_valid()
+                && randao_signature_set(&state, &signed_block.message, &self.spec)
+                    .map_err(Error::SignatureSetError)?
+                    .is_valid();
+
+        if signatures_are_valid {
+            Ok(Some(state))
+        } else {
+            Ok(None)
+        }
     }
 
     /// Produce a new block at the given `slot`.
@@ -1486,11 +4124,73 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         randao_reveal: Signature,
         slot: Slot,
     ) -> Result<BeaconBlockAndState<T::EthSpec>, BlockProductionError> {
-        let state = self
-            .state_at_slot(slot - 1, StateSkipConfig::WithStateRoots)
-            .map_err(|_| BlockProductionError::UnableToProduceAtSlot(slot))?;
+        let head = self.head()?;
+        self.check_head_not_in_future(head.beacon_block.slot())?;
+
+        // The root of the head state is known, which saves hashing it when producing a block in the
+        // slot following the head.
+        let (state, state_root) = if head.beacon_state.slot + 1 == slot {
+            (
+                head.beacon_state.clone_with_only_committee_caches(),
+                Some(head.beacon_state_root),
+            )
+        } else {
+            let state = self
+                .state_at_slot(slot - 1, StateSkipConfig::WithStateRoots)
+                .map_err(|_| BlockProductionError::UnableToProduceAtSlot(slot))?;
+            (state, None)
+        };
+        drop(head);
+        let parent_slot = state.latest_block_header.slot;
+
+        let (block, state) = self.produce_block_on_state(state, state_root, slot, randao_reveal)?;
+
+        // Keep the post-state so the block can be imported cheaply once it has been signed.
+        //
+        // Blocks atop skipped slots are excluded, since their import must also store the states
+        // of the skipped slots.
+        if parent_slot + 1 == slot {
+            self.produced_block_cache
+                .insert(block.canonical_root(), slot, state.clone());
+        }
+
+        Ok((block, state))
+    }
+
+    /// As per `Self::produce_block`, but first verifies that `randao_reveal` is a valid signature
+    /// of the epoch of `slot` by the proposer for `slot`.
+    ///
+    /// Returns `BlockProductionError::InvalidRandaoReveal` without producing a block if it is not,
+    /// since such a block would be rejected.
+    pub fn produce_block_checked(
+        &self,
+        randao_reveal: Signature,
+        slot: Slot,
+    ) -> Result<BeaconBlockAndState<T::EthSpec>, BlockProductionError> {
+        let proposer_index = self.block_proposer(slot)?;
+        let epoch = slot.epoch(T::EthSpec::slots_per_epoch());
+        let fork = self.head_info()?.fork;
+
+        let is_valid = {
+            let pubkey_cache = self
+                .validator_pubkey_cache
+                .try_read_for(self.lock_timeouts.validator_pubkey_cache)
+                .ok_or_else(|| BlockProductionError::ValidatorPubkeyCacheLockTimeout)?;
+            let pubkey = pubkey_cache
+                .get(proposer_index)
+                .ok_or_else(|| BlockProductionError::UnknownProposerPubkey(proposer_index))?;
+
+            let domain = self.spec.get_domain(epoch, Domain::Randao, &fork);
+            let message = epoch.signing_root(domain);
 
-        self.produce_block_on_state(state, slot, randao_reveal)
+            randao_reveal.verify(message.as_bytes(), pubkey)
+        };
+
+        if !is_valid {
+            return Err(BlockProductionError::InvalidRandaoReveal { proposer_index });
+        }
+
+        self.produce_block(randao_reveal, slot)
     }
 
     /// Produce a block for some `slot` upon the given `state`.
@@ -1500,31 +4200,44 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// non-current slots.
     ///
     /// The given state will be advanced to the given `produce_at_slot`, then a block will be
-    /// produced at that slot height.
+    /// produced at that slot height. The `state_root` of the given state is used to advance it, if
+    /// known, rather than hashing the state.
     pub fn produce_block_on_state(
         &self,
         mut state: BeaconState<T::EthSpec>,
+        mut state_root: Option<Hash256>,
         produce_at_slot: Slot,
         randao_reveal: Signature,
     ) -> Result<BeaconBlockAndState<T::EthSpec>, BlockProductionError> {
         metrics::inc_counter(&metrics::BLOCK_PRODUCTION_REQUESTS);
         let timer = metrics::start_timer(&metrics::BLOCK_PRODUCTION_TIMES);
 
+        if self.mode == BeaconChainMode::Follower {
+            return Err(BlockProductionError::ProductionDisabledInFollowerMode);
+        }
+
         let eth1_chain = self
             .eth1_chain
             .as_ref()
             .ok_or_else(|| BlockProductionError::NoEth1ChainConnection)?;
 
+        let state_advance_timer = metrics::start_timer(&metrics::BLOCK_PRODUCTION_STATE_ADVANCE);
+
+        self.check_skip_slots(state.slot, produce_at_slot)?;
+
         // If required, transition the new state to the present slot.
-        //
-        // Note: supplying some `state_root` when it it is known would be a cheap and easy
-        // optimization.
         while state.slot < produce_at_slot {
-            per_slot_processing(&mut state, None, &self.spec)?;
+            let state_root = match state_root.take() {
+                Some(state_root) => state_root,
+                None => update_tree_hash_cache(&mut state)?,
+            };
+            per_slot_processing(&mut state, Some(state_root), &self.spec)?;
         }
 
         state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
 
+        metrics::stop_timer(state_advance_timer);
+
         let parent_root = if state.slot > 0 {
             *state
                 .get_block_root(state.slot - 1)
@@ -1536,13 +4249,24 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         let mut graffiti: [u8; 32] = [0; 32];
         graffiti.copy_from_slice(GRAFFITI.as_bytes());
 
+        let slashings_and_exits_timer =
+            metrics::start_timer(&metrics::BLOCK_PRODUCTION_SLASHINGS_AND_EXITS);
         let (proposer_slashings, attester_slashings) =
             self.op_pool.get_slashings(&state, &self.spec);
+        let voluntary_exits = self.op_pool.get_voluntary_exits(&state, &self.spec);
+        metrics::stop_timer(slashings_and_exits_timer);
 
-        let eth1_data = eth1_chain.eth1_data_for_block_production(&state, &self.spec)?;
-        let deposits = eth1_chain
-            .deposits_for_block_inclusion(&state, &eth1_data, &self.spec)?
-            .into();
+        let eth1_data_timer = metrics::start_timer(&metrics::BLOCK_PRODUCTION_ETH1_DATA);
+        let (eth1_data, deposits) = eth1_chain
+            .eth1_data_and_deposits_for_block_production(&state, &self.spec, &self.log)?;
+        metrics::stop_timer(eth1_data_timer);
+
+        let attestations_timer = metrics::start_timer(&metrics::BLOCK_PRODUCTION_ATTESTATIONS);
+        let attestations = self
+            .op_pool
+            .get_attestations(&state, &self.spec)
+            .map_err(BlockProductionError::OpPoolError)?;
+        metrics::stop_timer(attestations_timer);
 
         let mut block = SignedBeaconBlock {
             message: BeaconBlock {
@@ -1555,19 +4279,16 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     graffiti,
                     proposer_slashings: proposer_slashings.into(),
                     attester_slashings: attester_slashings.into(),
-                    attestations: self
-                        .op_pool
-                        .get_attestations(&state, &self.spec)
-                        .map_err(BlockProductionError::OpPoolError)?
-                        .into(),
-                    deposits,
-                    voluntary_exits: self.op_pool.get_voluntary_exits(&state, &self.spec).into(),
+                    attestations: attestations.into(),
+                    deposits: deposits.into(),
+                    voluntary_exits: voluntary_exits.into(),
                 },
             },
             // The block is not signed here, that is the task of a validator client.
             signature: Signature::empty_signature(),
         };
 
+        let core_timer = metrics::start_timer(&metrics::BLOCK_PRODUCTION_CORE);
         per_block_processing(
             &mut state,
             &block,
@@ -1575,8 +4296,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             BlockSignatureStrategy::NoVerification,
             &self.spec,
         )?;
+        metrics::stop_timer(core_timer);
 
-        let state_root = state.update_tree_hash_cache()?;
+        let state_root_timer = metrics::start_timer(&metrics::BLOCK_PRODUCTION_STATE_ROOT);
+        let state_root = update_tree_hash_cache(&mut state)?;
+        metrics::stop_timer(state_root_timer);
 
         block.message.state_root = state_root;
 
@@ -1594,6 +4318,26 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         Ok((block.message, state))
     }
 
+    /// Computes the effective balances that fork choice requires if the first block in the epoch
+    /// of `state` becomes justified, and adds them to the fork choice balances cache.
+    ///
+    /// Does nothing unless the latest block in `state` is the first block in its epoch.
+    ///
+    /// This prevents the first `fork_choice` after the checkpoint is justified from reading a
+    /// state from the database. Computing the root of the latest block in `state` may require
+    /// hashing `state`.
+    pub fn warm_balances_cache(&self, state: &BeaconState<T::EthSpec>) -> Result<(), Error> {
+        let mut latest_block_header = state.latest_block_header.clone();
+        // The state root of the latest block header is only filled in at the next slot.
+        if latest_block_header.state_root == Hash256::zero() {
+            latest_block_header.state_root = state.canonical_root();
+        }
+
+        self.fork_choice
+            .warm_balances_cache(latest_block_header.canonical_root(), state)
+            .map_err(Into::into)
+    }
+
     /// Execute the fork choice algorithm and enthrone the result as the canonical head.
     pub fn fork_choice(&self) -> Result<(), Error> {
         metrics::inc_counter(&metrics::FORK_CHOICE_REQUESTS);
@@ -1604,8 +4348,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         // Determine the root of the block that is the head of the chain.
         let beacon_block_root = self.fork_choice.find_head(&self)?;
 
+        // Read the previous head once, rather than taking the head lock for each of its fields.
+        let previous_head = self.head_info()?;
+
         // If a new head was chosen.
-        let result = if beacon_block_root != self.head_info()?.block_root {
+        let result = if beacon_block_root != previous_head.block_root {
             metrics::inc_counter(&metrics::FORK_CHOICE_CHANGED_HEAD);
 
             let beacon_block = self
@@ -1617,15 +4364,15 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 .get_state(&beacon_state_root, Some(beacon_block.slot()))?
                 .ok_or_else(|| Error::MissingBeaconState(beacon_state_root))?;
 
-            let previous_slot = self.head_info()?.slot;
+            let previous_slot = previous_head.slot;
             let new_slot = beacon_block.slot();
 
             // Note: this will declare a re-org if we skip `SLOTS_PER_HISTORICAL_ROOT` blocks
             // between calls to fork choice without swapping between chains. This seems like an
             // extreme-enough scenario that a warning is fine.
-            let is_reorg = self.head_info()?.block_root
+            let is_reorg = previous_head.block_root
                 != beacon_state
-                    .get_block_root(self.head_info()?.slot)
+                    .get_block_root(previous_slot)
                     .map(|root| *root)
                     .unwrap_or_else(|_| Hash256::random());
 
@@ -1635,7 +4382,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 warn!(
                     self.log,
                     "Beacon chain re-org";
-                    "previous_head" => format!("{}", self.head_info()?.block_root),
+                    "previous_head" => format!("{}", previous_head.block_root),
                     "previous_slot" => previous_slot,
                     "new_head_parent" => format!("{}", beacon_block.parent_root()),
                     "new_head" => format!("{}", beacon_block_root),
@@ -1654,9 +4401,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 );
             };
 
-            let old_finalized_epoch = self.head_info()?.finalized_checkpoint.epoch;
-            let new_finalized_epoch = beacon_state.finalized_checkpoint.epoch;
-            let finalized_root = beacon_state.finalized_checkpoint.root;
+            let old_finalized_epoch = previous_head.finalized_checkpoint.epoch;
+            let new_finalized_checkpoint = beacon_state.finalized_checkpoint.clone();
+            let new_finalized_epoch = new_finalized_checkpoint.epoch;
 
             // Never revert back past a finalized epoch.
             if new_finalized_epoch < old_finalized_epoch {
@@ -1665,11 +4412,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     new_epoch: new_finalized_epoch,
                 })
             } else {
-                let previous_head_beacon_block_root = self
-                    .canonical_head
-                    .try_read_for(HEAD_LOCK_TIMEOUT)
-                    .ok_or_else(|| Error::CanonicalHeadLockTimeout)?
-                    .beacon_block_root;
+                let previous_head_beacon_block_root = previous_head.block_root;
                 let current_head_beacon_block_root = beacon_block_root;
 
                 let mut new_head = CheckPoint {
@@ -1681,24 +4424,52 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
                 new_head.beacon_state.build_all_caches(&self.spec)?;
 
+                let new_head_info = HeadInfo::from_checkpoint(&new_head);
+
                 let timer = metrics::start_timer(&metrics::UPDATE_HEAD_TIMES);
 
                 // Update the checkpoint that stores the head of the chain at the time it received the
                 // block.
-                *self
-                    .canonical_head
-                    .try_write_for(HEAD_LOCK_TIMEOUT)
-                    .ok_or_else(|| Error::CanonicalHeadLockTimeout)? = new_head;
+                //
+                // The new snapshot is fully built before the lock is taken and the previous snapshot
+                // is dropped after it is released, so the write lock is only held for the swap.
+                let replaced_head = std::mem::replace(
+                    &mut *self
+                        .canonical_head
+                        .try_write_for(self.lock_timeouts.head)
+                        .ok_or_else(|| Error::CanonicalHeadLockTimeout)?,
+                    Arc::new(new_head),
+                );
+
+                *self.cached_head_info.write() = new_head_info;
+
+                drop(replaced_head);
 
                 metrics::stop_timer(timer);
 
-                if previous_slot.epoch(T::EthSpec::slots_per_epoch())
-                    < new_slot.epoch(T::EthSpec::slots_per_epoch())
-                    || is_reorg
-                {
+                self.chain_stats.observe_head_update(is_reorg);
+
+                let is_new_epoch = previous_slot.epoch(T::EthSpec::slots_per_epoch())
+                    < new_slot.epoch(T::EthSpec::slots_per_epoch());
+
+                if is_new_epoch || is_reorg {
                     self.persist_head_and_fork_choice()?;
                 }
 
+                if is_new_epoch {
+                    self.evaluate_local_attestations()?;
+
+                    // The head has already changed, so a failure to prune must not prevent the
+                    // event from being registered or finalization from being processed.
+                    if let Err(e) = self.prune_op_pool() {
+                        error!(
+                            self.log,
+                            "Failed to prune op pool";
+                            "error" => format!("{:?}", e)
+                        );
+                    }
+                }
+
                 let _ = self.event_handler.register(EventKind::BeaconHeadChanged {
                     reorg: is_reorg,
                     previous_head_beacon_block_root,
@@ -1706,7 +4477,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 });
 
                 if new_finalized_epoch != old_finalized_epoch {
-                    self.after_finalization(old_finalized_epoch, finalized_root)?;
+                    self.after_finalization(old_finalized_epoch, new_finalized_checkpoint)?;
                 }
 
                 Ok(())
@@ -1725,14 +4496,104 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         result
     }
 
+    /// Removes the operations from the op pool that can no longer be included in a block atop the
+    /// canonical head.
+    ///
+    /// Unlike the pruning in `Self::after_finalization`, this does not depend upon finality, so it
+    /// keeps the pool bounded whilst finality is stalled.
+    fn prune_op_pool(&self) -> Result<(), Error> {
+        let head = self
+            .canonical_head
+            .try_read_for(self.lock_timeouts.head)
+            .ok_or_else(|| Error::CanonicalHeadLockTimeout)?;
+        let current_slot = self.slot().map_or(head.beacon_state.slot, |slot| {
+            slot.max(head.beacon_state.slot)
+        });
+
+        let attestations = self.op_pool.prune_attestations_by_slot(current_slot);
+        let voluntary_exits = self
+            .op_pool
+            .prune_voluntary_exits_for_exited(&head.beacon_state, &self.spec);
+        let slashings = self.op_pool.prune_slashings_for_slashed(&head.beacon_state);
+
+        drop(head);
+
+        metrics::set_gauge(&metrics::OP_POOL_PRUNED_ATTESTATIONS, attestations as i64);
+        metrics::set_gauge(
+            &metrics::OP_POOL_PRUNED_VOLUNTARY_EXITS,
+            voluntary_exits as i64,
+        );
+        metrics::set_gauge(&metrics::OP_POOL_PRUNED_SLASHINGS, slashings as i64);
+
+        debug!(
+            self.log,
+            "Pruned op pool";
+            "slot" => current_slot,
+            "attestations" => attestations,
+            "voluntary_exits" => voluntary_exits,
+            "slashings" => slashings,
+        );
+
+        Ok(())
+    }
+
+    /// Compares the attestations produced by this node against the canonical head, updating the
+    /// metrics for any epochs that have settled.
+    fn evaluate_local_attestations(&self) -> Result<(), Error> {
+        let results = self.local_attestations.evaluate(
+            &self
+                .canonical_head
+                .try_read_for(self.lock_timeouts.head)
+                .ok_or_else(|| Error::CanonicalHeadLockTimeout)?
+                .beacon_state,
+        );
+
+        for result in results {
+            metrics::inc_counter_by(
+                &metrics::LOCAL_ATTESTATIONS_EVALUATED,
+                result.produced as i64,
+            );
+            metrics::inc_counter_by(
+                &metrics::LOCAL_ATTESTATIONS_CORRECT_TARGET,
+                result.correct_target as i64,
+            );
+            metrics::inc_counter_by(
+                &metrics::LOCAL_ATTESTATIONS_CORRECT_HEAD,
+                result.correct_head as i64,
+            );
+
+            debug!(
+                self.log,
+                "Evaluated local attestations";
+                "epoch" => result.epoch,
+                "produced" => result.produced,
+                "correct_target" => result.correct_target,
+                "correct_head" => result.correct_head,
+            );
+        }
+
+        Ok(())
+    }
+
     /// Called after `self` has had a new block finalized.
     ///
     /// Performs pruning and finality-based optimizations.
     fn after_finalization(
         &self,
         old_finalized_epoch: Epoch,
-        finalized_block_root: Hash256,
+        new_finalized_checkpoint: Checkpoint,
     ) -> Result<(), Error> {
+        // A chain started from a trusted checkpoint holds nothing prior to its anchor, so there is
+        // nothing to prune or migrate until finality reaches the anchor.
+        if new_finalized_checkpoint
+            .epoch
+            .start_slot(T::EthSpec::slots_per_epoch())
+            < self.anchor_slot
+        {
+            return Ok(());
+        }
+
+        let finalized_block_root = new_finalized_checkpoint.root;
         let finalized_block = self
             .store
             .get_block(&finalized_block_root)?
@@ -1747,7 +4608,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 new_epoch: new_finalized_epoch,
             })
         } else {
-            self.fork_choice.prune()?;
+            let pruned_fork_choice_nodes = self.fork_choice.prune()?;
+
+            self.head_tracker
+                .prune_below(finalized_block.slot, finalized_block_root);
 
             let finalized_state = self
                 .get_state_caching_only_with_committee_caches(
@@ -1758,6 +4622,20 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
             self.op_pool.prune_all(&finalized_state, &self.spec);
 
+            if let Some(observed_block_proposers) = &self.observed_block_proposers {
+                observed_block_proposers.prune(
+                    new_finalized_checkpoint
+                        .epoch
+                        .start_slot(T::EthSpec::slots_per_epoch()),
+                );
+            }
+
+            if let Some(eth1_chain) = self.eth1_chain.as_ref() {
+                eth1_chain.prune_finalized(&finalized_state, &self.spec);
+            }
+
+            let finalized_state_root = finalized_block.state_root;
+
             // TODO: configurable max finality distance
             let max_finality_distance = 0;
             self.store_migrator.freeze_to_state(
@@ -1766,12 +4644,82 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 max_finality_distance,
             );
 
+            self.chain_stats.observe_finalization();
+
             let _ = self.event_handler.register(EventKind::BeaconFinalization {
                 epoch: new_finalized_epoch,
                 root: finalized_block_root,
+                slot: finalized_block.slot,
+                state_root: finalized_state_root,
+                pruned_fork_choice_nodes,
             });
 
+            let _ = self
+                .event_handler
+                .register(EventKind::BeaconFinalizedCheckpoint {
+                    checkpoint: new_finalized_checkpoint,
+                    state_root: finalized_state_root,
+                    slot: finalized_block.slot,
+                });
+
+            Ok(())
+        }
+    }
+
+    /// Decodes a `SignedBeaconBlock` from `bytes`, which a peer has declared to belong to the fork
+    /// with `fork_version`.
+    ///
+    /// Returns `Error::UnsupportedFork` without attempting to decode `bytes` if `fork_version` is
+    /// neither the current nor the previous version in the fork of the canonical head.
+    pub fn decode_block_for_fork(
+        &self,
+        bytes: &[u8],
+        fork_version: [u8; 4],
+    ) -> Result<SignedBeaconBlock<T::EthSpec>, Error> {
+        self.verify_fork_is_supported(fork_version, || {
+            // The message is variable-length, so it starts with the offset of the block.
+            let offset = u32::from_ssz_bytes(bytes.get(0..4)?).ok()? as usize;
+            Slot::from_ssz_bytes(bytes.get(offset..offset + 8)?).ok()
+        })?;
+
+        SignedBeaconBlock::from_ssz_bytes(bytes).map_err(Error::SszDecodeError)
+    }
+
+    /// Decodes an `Attestation` from `bytes`, which a peer has declared to belong to the fork with
+    /// `fork_version`.
+    ///
+    /// Returns `Error::UnsupportedFork` without attempting to decode `bytes` if `fork_version` is
+    /// neither the current nor the previous version in the fork of the canonical head.
+    pub fn decode_attestation_for_fork(
+        &self,
+        bytes: &[u8],
+        fork_version: [u8; 4],
+    ) -> Result<Attestation<T::EthSpec>, Error> {
+        self.verify_fork_is_supported(fork_version, || {
+            // The `data.slot` immediately follows the offset of the aggregation bits.
+            Slot::from_ssz_bytes(bytes.get(4..12)?).ok()
+        })?;
+
+        Attestation::from_ssz_bytes(bytes).map_err(Error::SszDecodeError)
+    }
+
+    /// Returns `Ok(())` if `fork_version` is in the fork of the canonical head.
+    ///
+    /// Otherwise, `slot_hint` is used to indicate which epoch the unsupported object claims to be
+    /// from.
+    fn verify_fork_is_supported<F>(&self, fork_version: [u8; 4], slot_hint: F) -> Result<(), Error>
+    where
+        F: FnOnce() -> Option<Slot>,
+    {
+        let fork = self.head_info()?.fork;
+
+        if fork_version == fork.current_version || fork_version == fork.previous_version {
             Ok(())
+        } else {
+            Err(Error::UnsupportedFork {
+                fork_version,
+                epoch_hint: slot_hint().map(|slot| slot.epoch(T::EthSpec::slots_per_epoch())),
+            })
         }
     }
 
@@ -1782,66 +4730,297 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             .exists::<SignedBeaconBlock<T::EthSpec>>(beacon_block_root)?)
     }
 
-    /// Dumps the entire canonical chain, from the head to genesis to a vector for analysis.
+    /// Returns an iterator over the canonical chain, from the head towards genesis, which loads
+    /// the block and state of one `CheckPoint` from the store at a time.
+    ///
+    /// Iteration stops at genesis or after `limit` items. A missing state is yielded as an error
+    /// and iteration continues from its block, whilst a missing block is yielded as an error and
+    /// ends iteration (since its parent is unknown). This allows for iterating over a partial
+    /// history, such as that of a chain started from a checkpoint.
+    pub fn rev_iter_checkpoints(
+        &self,
+        limit: Option<usize>,
+    ) -> impl Iterator<Item = Result<CheckPoint<T::EthSpec>, Error>> + '_ {
+        let mut head = Some(
+            self.head()
+                .map(|head| head.clone_with_only_committee_caches()),
+        );
+        // The root of the next block to load, or `None` if iteration has ended.
+        let mut next_block_root = None;
+
+        std::iter::from_fn(move || {
+            if let Some(head) = head.take() {
+                if let Ok(head) = &head {
+                    next_block_root = Some(head.beacon_block.parent_root());
+                }
+                return Some(head);
+            }
+
+            // The parent of the genesis block is the zero hash.
+            let beacon_block_root = next_block_root
+                .take()
+                .filter(|root| *root != Hash256::zero())?;
+
+            let beacon_block = match self.store.get_block(&beacon_block_root) {
+                Ok(Some(block)) => block,
+                Ok(None) => {
+                    return Some(Err(Error::DBInconsistent(format!(
+                        "Missing block {}",
+                        beacon_block_root
+                    ))))
+                }
+                Err(e) => return Some(Err(e.into())),
+            };
+            next_block_root = Some(beacon_block.parent_root());
+
+            let beacon_state_root = beacon_block.state_root();
+            let checkpoint = match self
+                .store
+                .get_state(&beacon_state_root, Some(beacon_block.slot()))
+            {
+                Ok(Some(beacon_state)) => Ok(CheckPoint {
+                    beacon_block,
+                    beacon_block_root,
+                    beacon_state,
+                    beacon_state_root,
+                }),
+                Ok(None) => Err(Error::DBInconsistent(format!(
+                    "Missing state {:?}",
+                    beacon_state_root
+                ))),
+                Err(e) => Err(e.into()),
+            };
+
+            Some(checkpoint)
+        })
+        .take(limit.unwrap_or_else(usize::max_value))
+    }
+
+    /// Dumps the entire canonical chain, from genesis to the head, to a vector for analysis.
     ///
-    /// This could be a very expensive operation and should only be done in testing/analysis
-    /// activities.
+    /// This could be a very expensive operation and should only be done in testing. Prefer
+    /// `Self::rev_iter_checkpoints` for analysis of long chains.
     pub fn chain_dump(&self) -> Result<Vec<CheckPoint<T::EthSpec>>, Error> {
-        let mut dump = vec![];
+        let mut dump = self
+            .rev_iter_checkpoints(None)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        dump.reverse();
+
+        Ok(dump)
+    }
 
-        let mut last_slot = CheckPoint {
-            beacon_block: self.head()?.beacon_block,
-            beacon_block_root: self.head()?.beacon_block_root,
-            beacon_state: self.head()?.beacon_state,
-            beacon_state_root: self.head()?.beacon_state_root,
+    /// Writes the records of a snapshot of the finalized portion of the canonical chain to
+    /// `writer` (see `SnapshotRecord`).
+    ///
+    /// A single state is advanced from genesis by applying each block, so that only one state is
+    /// held in memory and each block is processed once. The blocks have already been verified, so
+    /// their signatures are not checked again.
+    ///
+    /// Like `Self::chain_dump`, this could be a very expensive operation.
+    pub fn export_snapshot<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+        let finalized_checkpoint = self.head_info()?.finalized_checkpoint;
+        let finalized_block_root = if finalized_checkpoint.root == Hash256::zero() {
+            self.genesis_block_root
+        } else {
+            finalized_checkpoint.root
         };
+        let last_checkpoint_slot = finalized_checkpoint.epoch.start_slot(slots_per_epoch);
+
+        let mut block_roots = self
+            .rev_iter_block_roots_from(finalized_block_root)?
+            .map(|result| result.map(|(root, _slot)| root))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Skip slots repeat the root of the prior block.
+        block_roots.dedup();
+        block_roots.retain(|root| *root != self.genesis_block_root);
+        block_roots.reverse();
+
+        let genesis_block = self
+            .get_block(&self.genesis_block_root)?
+            .ok_or_else(|| Error::MissingBeaconBlock(self.genesis_block_root))?;
+        let mut state = self
+            .get_state(&genesis_block.state_root(), Some(genesis_block.slot()))?
+            .ok_or_else(|| Error::MissingBeaconState(genesis_block.state_root()))?;
+        let mut next_checkpoint_slot = genesis_block.slot();
+
+        for block_root in block_roots {
+            let block = self
+                .get_block(&block_root)?
+                .ok_or_else(|| Error::MissingBeaconBlock(block_root))?;
+
+            // Write the states of any checkpoints at skip slots prior to the block.
+            while next_checkpoint_slot < block.slot() {
+                self.advance_snapshot_state(&mut state, next_checkpoint_slot)?;
+                SnapshotRecord::write_state(&state, writer)?;
+                next_checkpoint_slot += slots_per_epoch;
+            }
 
-        dump.push(last_slot.clone());
+            self.advance_snapshot_state(&mut state, block.slot())?;
+            state.build_committee_cache(RelativeEpoch::Previous, &self.spec)?;
+            state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+            per_block_processing(
+                &mut state,
+                &block,
+                Some(block_root),
+                BlockSignatureStrategy::NoVerification,
+                &self.spec,
+            )
+            .map_err(|e| {
+                Error::DBInconsistent(format!("Unable to apply block {}: {:?}", block_root, e))
+            })?;
 
-        loop {
-            let beacon_block_root = last_slot.beacon_block.parent_root();
+            let block_slot = block.slot();
+            SnapshotRecord::Block(block).write(writer)?;
 
-            if beacon_block_root == Hash256::zero() {
-                break; // Genesis has been reached.
+            if block_slot == next_checkpoint_slot {
+                SnapshotRecord::write_state(&state, writer)?;
+                next_checkpoint_slot += slots_per_epoch;
             }
+        }
 
-            let beacon_block = self.store.get_block(&beacon_block_root)?.ok_or_else(|| {
-                Error::DBInconsistent(format!("Missing block {}", beacon_block_root))
-            })?;
-            let beacon_state_root = beacon_block.state_root();
-            let beacon_state = self
-                .store
-                .get_state(&beacon_state_root, Some(beacon_block.slot()))?
-                .ok_or_else(|| {
-                    Error::DBInconsistent(format!("Missing state {:?}", beacon_state_root))
-                })?;
+        while next_checkpoint_slot <= last_checkpoint_slot {
+            self.advance_snapshot_state(&mut state, next_checkpoint_slot)?;
+            SnapshotRecord::write_state(&state, writer)?;
+            next_checkpoint_slot += slots_per_epoch;
+        }
 
-            let slot = CheckPoint {
-                beacon_block,
-                beacon_block_root,
-                beacon_state,
-                beacon_state_root,
-            };
+        writer
+            .flush()
+            .map_err(|e| Error::SnapshotIoError(format!("{:?}", e)))
+    }
+
+    /// Applies `per_slot_processing` (including state roots) to `state` until it reaches `slot`,
+    /// without a deadline.
+    fn advance_snapshot_state(
+        &self,
+        state: &mut BeaconState<T::EthSpec>,
+        slot: Slot,
+    ) -> Result<(), Error> {
+        self.check_skip_slots(state.slot, slot)?;
 
-            dump.push(slot.clone());
-            last_slot = slot;
+        while state.slot < slot {
+            let state_root = update_tree_hash_cache(state)?;
+            per_slot_processing(state, Some(state_root), &self.spec)?;
         }
 
-        dump.reverse();
+        Ok(())
+    }
 
-        Ok(dump)
+    /// Reads the records of a snapshot written by `Self::export_snapshot` from `reader` and
+    /// imports its blocks into the store and fork choice, returning the number of blocks that were
+    /// imported.
+    ///
+    /// The records are read one at a time and each is verified as it is read. Blocks which are
+    /// already known are skipped. Each state of the snapshot is compared against the state
+    /// produced by the blocks imported before it.
+    ///
+    /// ## Errors
+    ///
+    /// - The snapshot was built from a different genesis state.
+    /// - A block of the snapshot is not a child of the block which precedes it.
+    /// - A block or state of the snapshot is invalid.
+    ///
+    /// The blocks prior to an invalid record remain imported, however the head is only updated
+    /// once the whole snapshot has been imported.
+    pub fn import_snapshot<R: Read>(&self, reader: &mut R) -> Result<usize, Error> {
+        let genesis_block = self
+            .get_block(&self.genesis_block_root)?
+            .ok_or_else(|| Error::MissingBeaconBlock(self.genesis_block_root))?;
+
+        let snapshot_genesis_state_root = match SnapshotRecord::read(reader)? {
+            Some(SnapshotRecord::State(state)) => Some(state.canonical_root()),
+            _ => None,
+        };
+        if snapshot_genesis_state_root != Some(genesis_block.state_root()) {
+            return Err(Error::SnapshotGenesisMismatch {
+                snapshot_state_root: snapshot_genesis_state_root,
+                local_state_root: genesis_block.state_root(),
+            });
+        }
+
+        let mut imported = 0;
+        let mut checkpoint_states = 1;
+        let mut parent_root = self.genesis_block_root;
+        let mut parent_slot = genesis_block.slot();
+        let mut parent_state_root = genesis_block.state_root();
+
+        while let Some(record) = SnapshotRecord::read(reader)? {
+            match record {
+                SnapshotRecord::Block(block) => {
+                    let block_root = block.canonical_root();
+                    if block.parent_root() != parent_root {
+                        return Err(Error::SnapshotBlocksNotChained {
+                            block_root,
+                            parent_root: block.parent_root(),
+                            expected_parent_root: parent_root,
+                        });
+                    }
+
+                    parent_root = block_root;
+                    parent_slot = block.slot();
+                    parent_state_root = block.state_root();
+
+                    if !self.is_new_block_root(&block_root)? {
+                        continue;
+                    }
+
+                    match self.process_block(block)? {
+                        BlockProcessingOutcome::Processed { .. } => imported += 1,
+                        outcome => {
+                            return Err(Error::SnapshotBlockRejected {
+                                block_root,
+                                outcome: Box::new(outcome),
+                            })
+                        }
+                    }
+                }
+                SnapshotRecord::State(snapshot_state) => {
+                    let snapshot_state_root = snapshot_state.canonical_root();
+                    let local_state_root = if snapshot_state.slot == parent_slot {
+                        parent_state_root
+                    } else {
+                        self.replay_state_to_slot(snapshot_state.slot, parent_state_root)?
+                            .canonical_root()
+                    };
+                    if snapshot_state_root != local_state_root {
+                        return Err(Error::SnapshotStateMismatch {
+                            slot: snapshot_state.slot,
+                            snapshot_state_root,
+                            local_state_root,
+                        });
+                    }
+
+                    checkpoint_states += 1;
+                }
+            }
+        }
+
+        self.fork_choice()?;
+
+        info!(
+            self.log,
+            "Imported chain snapshot";
+            "imported_blocks" => imported,
+            "checkpoint_states" => checkpoint_states,
+        );
+
+        self.record_admin_event(
+            "import_snapshot",
+            format!(
+                "imported_blocks: {}, checkpoint_states: {}, last_block_root: {:?}",
+                imported, checkpoint_states, parent_root
+            ),
+        )?;
+
+        Ok(imported)
     }
 }
 
 impl<T: BeaconChainTypes> Drop for BeaconChain<T> {
     fn drop(&mut self) {
-        let drop = || -> Result<(), Error> {
-            self.persist_head_and_fork_choice()?;
-            self.persist_op_pool()?;
-            self.persist_eth1_cache()
-        };
-
-        if let Err(e) = drop() {
+        if let Err(e) = self.persist_all() {
             error!(
                 self.log,
                 "Failed to persist on BeaconChain drop";
@@ -1856,47 +5035,66 @@ impl<T: BeaconChainTypes> Drop for BeaconChain<T> {
     }
 }
 
-fn write_state<T: EthSpec>(prefix: &str, state: &BeaconState<T>, log: &Logger) {
-    if WRITE_BLOCK_PROCESSING_SSZ {
-        let root = state.tree_hash_root();
-        let filename = format!("{}_slot_{}_root_{}.ssz", prefix, state.slot, root);
-        let mut path = std::env::temp_dir().join("lighthouse");
-        let _ = fs::create_dir_all(path.clone());
-        path = path.join(filename);
-
-        match fs::File::create(path.clone()) {
-            Ok(mut file) => {
-                let _ = file.write_all(&state.as_ssz_bytes());
-            }
-            Err(e) => error!(
-                log,
-                "Failed to log state";
-                "path" => format!("{:?}", path),
-                "error" => format!("{:?}", e)
-            ),
-        }
-    }
+/// Produces an unsigned `Attestation` for the committee with `index`, using `data` for all fields
+/// except the committee index.
+fn attestation_for_committee<T: EthSpec>(
+    committee_cache: &CommitteeCache,
+    data: &AttestationData,
+    index: CommitteeIndex,
+) -> Result<Attestation<T>, Error> {
+    let committee_len = committee_cache
+        .get_beacon_committee(data.slot, index)
+        .ok_or_else(|| BeaconStateError::NoCommittee {
+            slot: data.slot,
+            index,
+        })?
+        .committee
+        .len();
+
+    Ok(Attestation {
+        aggregation_bits: BitList::with_capacity(committee_len)?,
+        data: AttestationData {
+            index,
+            ..data.clone()
+        },
+        signature: AggregateSignature::new(),
+    })
 }
 
-fn write_block<T: EthSpec>(block: &BeaconBlock<T>, root: Hash256, log: &Logger) {
-    if WRITE_BLOCK_PROCESSING_SSZ {
-        let filename = format!("block_slot_{}_root{}.ssz", block.slot, root);
-        let mut path = std::env::temp_dir().join("lighthouse");
-        let _ = fs::create_dir_all(path.clone());
-        path = path.join(filename);
+/// Returns the root yielded by `iter` (which iterates backwards through `(root, slot)` pairs) at
+/// `slot`, or `None` if `iter` skips or ends prior to `slot`.
+///
+/// Iteration stops at `slot`, or at the first error, which is returned.
+fn find_root_at_slot<I>(mut iter: I, slot: Slot) -> Result<Option<Hash256>, Error>
+where
+    I: Iterator<Item = Result<(Hash256, Slot), DBError>>,
+{
+    Ok(iter
+        .find(|result| match result {
+            Ok((_, this_slot)) => *this_slot <= slot,
+            Err(_) => true,
+        })
+        .transpose()?
+        .filter(|(_, this_slot)| *this_slot == slot)
+        .map(|(root, _)| root))
+}
 
-        match fs::File::create(path.clone()) {
-            Ok(mut file) => {
-                let _ = file.write_all(&block.as_ssz_bytes());
-            }
-            Err(e) => error!(
-                log,
-                "Failed to log block";
-                "path" => format!("{:?}", path),
-                "error" => format!("{:?}", e)
-            ),
-        }
+/// Updates the tree hash cache of `state` and returns its root, recording the time taken and
+/// whether an existing cache was reused.
+pub(crate) fn update_tree_hash_cache<T: EthSpec>(
+    state: &mut BeaconState<T>,
+) -> Result<Hash256, BeaconStateError> {
+    if state.tree_hash_cache.is_some() {
+        metrics::inc_counter(&metrics::TREE_HASH_CACHE_HITS);
+    } else {
+        metrics::inc_counter(&metrics::TREE_HASH_CACHE_MISSES);
     }
+
+    let timer = metrics::start_timer(&metrics::TREE_HASH_CACHE_UPDATE_TIMES);
+    let result = state.update_tree_hash_cache();
+    metrics::stop_timer(timer);
+
+    result
 }
 
 impl From<DBError> for Error {
@@ -0,0 +1,97 @@
+use crate::errors::BeaconChainError;
+use std::time::Duration;
+use types::{EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// The default maximum duration before the start of a slot at which a block from that slot will
+/// be accepted by `BeaconChain::verify_block_for_gossip` and `BeaconChain::process_block`.
+///
+/// This allows for small differences between the clocks of the proposer and this node.
+pub const MAXIMUM_GOSSIP_CLOCK_DISPARITY: Duration = Duration::from_millis(500);
+
+/// The reason a block was rejected by `BeaconChain::verify_block_for_gossip`.
+#[derive(Debug, PartialEq)]
+pub enum GossipBlockError {
+    /// The block slot is greater than the present slot, even when allowing for the
+    /// `max_future_slot_tolerance` of the `BeaconChain`.
+    FutureSlot {
+        present_slot: Slot,
+        block_slot: Slot,
+    },
+    /// The block is not later than the finalized slot.
+    WouldRevertFinalizedSlot {
+        block_slot: Slot,
+        finalized_slot: Slot,
+    },
+    /// The block was a genesis block, these blocks cannot be re-imported.
+    GenesisBlock,
+    /// The parent block is not known to fork choice.
+    ParentUnknown { parent: Hash256 },
+    /// The block is already known to fork choice.
+    BlockIsAlreadyKnown,
+    /// The block is not signed by `proposer_index`, the expected proposer for its slot.
+    ///
+    /// Blocks do not declare their proposer, so a block signed by the wrong validator cannot be
+    /// distinguished from a block with an invalid signature.
+    ProposalSignatureInvalid { proposer_index: usize },
+    /// There was an error whilst verifying the block. It is not known whether the block is valid.
+    BeaconChainError(BeaconChainError),
+}
+
+impl From<BeaconChainError> for GossipBlockError {
+    fn from(e: BeaconChainError) -> Self {
+        GossipBlockError::BeaconChainError(e)
+    }
+}
+
+/// A block that has passed the checks in `BeaconChain::verify_block_for_gossip`, including the
+/// verification of its proposal signature.
+///
+/// It may be passed to `BeaconChain::process_block`, which will not verify the proposal signature
+/// again.
+#[derive(Debug, Clone)]
+pub struct GossipVerifiedBlock<E: EthSpec> {
+    pub(crate) block: SignedBeaconBlock<E>,
+    pub(crate) block_root: Hash256,
+    pub(crate) proposer_index: usize,
+}
+
+impl<E: EthSpec> GossipVerifiedBlock<E> {
+    /// Returns the verified block.
+    pub fn block(&self) -> &SignedBeaconBlock<E> {
+        &self.block
+    }
+
+    /// Returns the root of the verified block.
+    pub fn block_root(&self) -> Hash256 {
+        self.block_root
+    }
+
+    /// Returns the index of the validator that proposed the block.
+    pub fn proposer_index(&self) -> usize {
+        self.proposer_index
+    }
+
+    /// Returns the verified block, discarding the result of the verification.
+    pub fn into_block(self) -> SignedBeaconBlock<E> {
+        self.block
+    }
+}
+
+/// Implemented on the types that may be imported with `BeaconChain::process_block`.
+pub trait IntoBlockForImport<E: EthSpec> {
+    /// Returns the block, along with its root if its proposal signature has already been
+    /// verified.
+    fn into_block_for_import(self) -> (SignedBeaconBlock<E>, Option<Hash256>);
+}
+
+impl<E: EthSpec> IntoBlockForImport<E> for SignedBeaconBlock<E> {
+    fn into_block_for_import(self) -> (SignedBeaconBlock<E>, Option<Hash256>) {
+        (self, None)
+    }
+}
+
+impl<E: EthSpec> IntoBlockForImport<E> for GossipVerifiedBlock<E> {
+    fn into_block_for_import(self) -> (SignedBeaconBlock<E>, Option<Hash256>) {
+        (self.block, Some(self.block_root))
+    }
+}
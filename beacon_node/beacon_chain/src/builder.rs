@@ -1,30 +1,51 @@
+use crate::admin_history::{AdminEvent, AdminHistory};
 use crate::beacon_chain::{
-    BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, FORK_CHOICE_DB_KEY, OP_POOL_DB_KEY,
+    update_tree_hash_cache, BeaconChainMode, HeadInfo, ANCHOR_DB_KEY, BEACON_CHAIN_DB_KEY,
+    DEFAULT_MAX_BLOCK_RANGE, ETH1_CACHE_DB_KEY, FORK_CHOICE_DB_KEY, OP_POOL_DB_KEY,
 };
+use crate::block_verification::MAXIMUM_GOSSIP_CLOCK_DISPARITY;
+use crate::chain_stats::ChainStats;
 use crate::eth1_chain::{CachingEth1Backend, SszEth1};
 use crate::events::NullEventHandler;
+use crate::finalization_info_cache::FinalizationInfoCache;
 use crate::fork_choice::SszForkChoice;
 use crate::head_tracker::HeadTracker;
-use crate::persisted_beacon_chain::PersistedBeaconChain;
+use crate::invalid_block_dump::InvalidBlockDump;
+use crate::local_attestations::LocalAttestations;
+use crate::metrics;
+use crate::observed_attestations::ObservedAttestations;
+use crate::observed_attester_votes::ObservedAttesterVotes;
+use crate::observed_block_proposers::ObservedBlockProposers;
+use crate::op_pool_persistence::{
+    op_pool_delta_key, OpPoolPersistence, OP_POOL_DELTAS_PER_SNAPSHOT,
+};
+use crate::persisted_beacon_chain::{PersistedAnchor, PersistedBeaconChain};
+use crate::produced_block_cache::ProducedBlockCache;
+use crate::readiness::ReadinessConfig;
 use crate::shuffling_cache::ShufflingCache;
-use crate::timeout_rw_lock::TimeoutRwLock;
+use crate::state_skip_cache::StateSkipCache;
+use crate::timeout_rw_lock::{LockTimeouts, TimeoutRwLock};
+use crate::validator_monitor::ValidatorMonitor;
 use crate::validator_pubkey_cache::ValidatorPubkeyCache;
 use crate::{
     BeaconChain, BeaconChainTypes, CheckPoint, Eth1Chain, Eth1ChainBackend, EventHandler,
     ForkChoice,
 };
 use eth1::Config as Eth1Config;
-use operation_pool::{OperationPool, PersistedOperationPool};
+use operation_pool::{OperationPool, PersistedOperationPool, PersistedOperationPoolDelta};
+use parking_lot::{Mutex, RwLock};
 use proto_array_fork_choice::ProtoArrayForkChoice;
-use slog::{info, Logger};
+use slog::{debug, info, warn, Logger};
 use slot_clock::{SlotClock, TestingSlotClock};
 use std::marker::PhantomData;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use store::Store;
+use store::{Store, StoreItem};
+use tree_hash::TreeHash;
 use types::{
-    BeaconBlock, BeaconState, ChainSpec, EthSpec, Hash256, Signature, SignedBeaconBlock, Slot,
+    BeaconBlock, BeaconState, ChainSpec, EthSpec, Hash256, PublicKeyBytes, Signature,
+    SignedBeaconBlock, Slot,
 };
 
 pub const PUBKEY_CACHE_FILENAME: &str = "pubkey_cache.ssz";
@@ -76,6 +97,8 @@ pub struct BeaconChainBuilder<T: BeaconChainTypes> {
     /// checkpoint.
     pub finalized_checkpoint: Option<CheckPoint<T::EthSpec>>,
     genesis_block_root: Option<Hash256>,
+    /// The trusted checkpoint from which the chain was started, if not genesis.
+    anchor: Option<PersistedAnchor>,
     op_pool: Option<OperationPool<T::EthSpec>>,
     fork_choice: Option<ForkChoice<T>>,
     eth1_chain: Option<Eth1Chain<T::Eth1Chain, T::EthSpec, T::Store>>,
@@ -85,6 +108,17 @@ pub struct BeaconChainBuilder<T: BeaconChainTypes> {
     data_dir: Option<PathBuf>,
     pubkey_cache_path: Option<PathBuf>,
     validator_pubkey_cache: Option<ValidatorPubkeyCache>,
+    lock_timeouts: LockTimeouts,
+    readiness_config: ReadinessConfig,
+    max_skip_slots: Option<u64>,
+    max_block_range: u64,
+    invalid_block_dump: Option<InvalidBlockDump>,
+    max_future_slot_tolerance: Duration,
+    warm_balances_cache_on_import: bool,
+    mode: BeaconChainMode,
+    slashing_detection: bool,
+    monitored_validator_indices: Vec<usize>,
+    monitored_validator_pubkeys: Vec<PublicKeyBytes>,
     spec: ChainSpec,
     log: Option<Logger>,
 }
@@ -112,6 +146,7 @@ where
             canonical_head: None,
             finalized_checkpoint: None,
             genesis_block_root: None,
+            anchor: None,
             op_pool: None,
             fork_choice: None,
             eth1_chain: None,
@@ -121,6 +156,17 @@ where
             pubkey_cache_path: None,
             data_dir: None,
             validator_pubkey_cache: None,
+            lock_timeouts: LockTimeouts::default(),
+            readiness_config: ReadinessConfig::default(),
+            max_skip_slots: None,
+            max_block_range: DEFAULT_MAX_BLOCK_RANGE,
+            invalid_block_dump: None,
+            max_future_slot_tolerance: MAXIMUM_GOSSIP_CLOCK_DISPARITY,
+            warm_balances_cache_on_import: false,
+            mode: BeaconChainMode::default(),
+            slashing_detection: true,
+            monitored_validator_indices: vec![],
+            monitored_validator_pubkeys: vec![],
             spec: TEthSpec::default_spec(),
             log: None,
         }
@@ -166,16 +212,102 @@ where
         self
     }
 
+    /// Sets the time-outs used when taking locks on the head and caches.
+    pub fn lock_timeouts(mut self, lock_timeouts: LockTimeouts) -> Self {
+        self.lock_timeouts = lock_timeouts;
+        self
+    }
+
+    /// Sets the thresholds used to determine whether the node is ready to serve validator duties.
+    pub fn readiness_config(mut self, readiness_config: ReadinessConfig) -> Self {
+        self.readiness_config = readiness_config;
+        self
+    }
+
+    /// Sets the maximum number of slots that may be skipped when advancing a state (e.g., to
+    /// produce a block). There is no limit when `None`.
+    pub fn max_skip_slots(mut self, max_skip_slots: Option<u64>) -> Self {
+        self.max_skip_slots = max_skip_slots;
+        self
+    }
+
+    /// Sets the maximum number of slots that may be covered by a single block range query (e.g.,
+    /// from a `BlocksByRange` request or the HTTP API).
+    pub fn max_block_range(mut self, max_block_range: u64) -> Self {
+        self.max_block_range = max_block_range;
+        self
+    }
+
+    /// Sets the directory to which blocks that fail processing are written, along with their
+    /// pre-states, for debugging. No more blocks are written once the directory contains
+    /// `max_files` files. Blocks are not written when `None` (the default).
+    ///
+    /// Note: each block is processed upon a copy of its pre-state whilst enabled.
+    pub fn dump_invalid_blocks(mut self, dir: Option<PathBuf>, max_files: usize) -> Self {
+        self.invalid_block_dump = dir.map(|dir| InvalidBlockDump::new(dir, max_files));
+        self
+    }
+
+    /// Sets how far before the start of its slot a block may be processed, allowing for clock skew
+    /// between the proposer and this node. Blocks from slots which start later than this are
+    /// rejected as `FutureSlot`. Defaults to `MAXIMUM_GOSSIP_CLOCK_DISPARITY`.
+    pub fn max_future_slot_tolerance(mut self, tolerance: Duration) -> Self {
+        self.max_future_slot_tolerance = tolerance;
+        self
+    }
+
+    /// Sets whether the balances required by fork choice are cached whenever a block is imported,
+    /// rather than read from the database when its checkpoint is justified. Defaults to `false`.
+    pub fn warm_balances_cache_on_import(mut self, enabled: bool) -> Self {
+        self.warm_balances_cache_on_import = enabled;
+        self
+    }
+
+    /// Sets whether the chain produces blocks or only follows the chain. Defaults to
+    /// `BeaconChainMode::Full`.
+    pub fn mode(mut self, mode: BeaconChainMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets whether attester and proposer slashings should be detected amongst processed
+    /// attestations and blocks and added to the op pool. Enabled by default.
+    pub fn slashing_detection(mut self, enabled: bool) -> Self {
+        self.slashing_detection = enabled;
+        self
+    }
+
+    /// Sets the validators whose block proposals and attestation inclusions are reported by the
+    /// `ValidatorMonitor`, by validator index or by public key.
+    pub fn monitor_validators(
+        mut self,
+        validator_indices: Vec<usize>,
+        validator_pubkeys: Vec<PublicKeyBytes>,
+    ) -> Self {
+        self.monitored_validator_indices = validator_indices;
+        self.monitored_validator_pubkeys = validator_pubkeys;
+        self
+    }
+
     /// Attempt to load an existing eth1 cache from the builder's `Store`.
+    ///
+    /// A persisted cache that cannot be decoded is discarded, returning `Ok(None)`.
     pub fn get_persisted_eth1_backend(&self) -> Result<Option<SszEth1>, String> {
         let store = self
             .store
             .clone()
             .ok_or_else(|| "get_persisted_eth1_backend requires a store.".to_string())?;
+        let log = self
+            .log
+            .as_ref()
+            .ok_or_else(|| "get_persisted_eth1_backend requires a log".to_string())?;
 
-        store
-            .get::<SszEth1>(&Hash256::from_slice(&ETH1_CACHE_DB_KEY))
-            .map_err(|e| format!("DB error whilst reading eth1 cache: {:?}", e))
+        get_recoverable_container::<SszEth1, _, TEthSpec>(
+            &*store,
+            &Hash256::from_slice(&ETH1_CACHE_DB_KEY),
+            "eth1_cache",
+            log,
+        )
     }
 
     /// Attempt to load an existing chain from the builder's `Store`.
@@ -205,13 +337,16 @@ where
 
         let chain = store
             .get::<PersistedBeaconChain>(&Hash256::from_slice(&BEACON_CHAIN_DB_KEY))
-            .map_err(|e| format!("DB error when reading persisted beacon chain: {:?}", e))?
+            .map_err(|e| required_container_error("beacon chain", e))?
             .ok_or_else(|| {
                 "No persisted beacon chain found in store. Try deleting the .lighthouse/beacon dir."
                     .to_string()
             })?;
 
         self.genesis_block_root = Some(chain.genesis_block_root);
+        self.anchor = store
+            .get::<PersistedAnchor>(&Hash256::from_slice(&ANCHOR_DB_KEY))
+            .map_err(|e| required_container_error("anchor", e))?;
         self.head_tracker = Some(
             HeadTracker::from_ssz_container(&chain.ssz_head_tracker)
                 .map_err(|e| format!("Failed to decode head tracker for database: {:?}", e))?,
@@ -229,14 +364,28 @@ where
             .ok_or_else(|| "Head state not found in store".to_string())?;
 
         self.op_pool = Some(
-            store
-                .get::<PersistedOperationPool<TEthSpec>>(&Hash256::from_slice(&OP_POOL_DB_KEY))
-                .map_err(|e| format!("DB error whilst reading persisted op pool: {:?}", e))?
-                .map(|persisted| persisted.into_operation_pool(&head_state, &self.spec))
-                .unwrap_or_else(|| OperationPool::new()),
+            get_recoverable_container::<PersistedOperationPool<TEthSpec>, _, TEthSpec>(
+                &*store,
+                &Hash256::from_slice(&OP_POOL_DB_KEY),
+                "op_pool",
+                log,
+            )?
+            .map(|persisted| persisted.into_operation_pool(&head_state, &self.spec))
+            .map(|op_pool| apply_op_pool_deltas(&*store, op_pool, log))
+            .transpose()?
+            .unwrap_or_else(|| OperationPool::new()),
         );
 
-        let finalized_block_root = head_state.finalized_checkpoint.root;
+        // The finalized block of a chain started from an anchor may precede the anchor, in which
+        // case it is not in the store and the anchor stands in for it.
+        let finalized_slot = head_state
+            .finalized_checkpoint
+            .epoch
+            .start_slot(TEthSpec::slots_per_epoch());
+        let finalized_block_root = match self.anchor {
+            Some(anchor) if finalized_slot <= anchor.anchor_slot => chain.genesis_block_root,
+            _ => head_state.finalized_checkpoint.root,
+        };
         let finalized_block = store
             .get::<SignedBeaconBlock<TEthSpec>>(&finalized_block_root)
             .map_err(|e| format!("DB error when reading finalized block: {:?}", e))?
@@ -315,6 +464,95 @@ where
         Ok(self.empty_op_pool())
     }
 
+    /// Starts a new chain from a trusted, finalized `anchor_state` and its block, `anchor_block`,
+    /// rather than from genesis (i.e., weak subjectivity sync).
+    ///
+    /// The anchor block takes the place of the genesis block, so blocks are imported forwards
+    /// from the anchor and any block at or prior to the anchor slot is rejected. The anchor must
+    /// lie at the start of an epoch. The `genesis_validators_root` of the chain cannot be derived
+    /// from the anchor, so it must also be supplied.
+    pub fn weak_subjectivity_state(
+        mut self,
+        mut anchor_state: BeaconState<TEthSpec>,
+        anchor_block: SignedBeaconBlock<TEthSpec>,
+        genesis_validators_root: Hash256,
+    ) -> Result<Self, String> {
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| "weak_subjectivity_state requires a store")?;
+
+        let anchor_slot = anchor_block.slot();
+        if anchor_slot == 0 {
+            return Err("Use genesis_state to start from a genesis anchor".to_string());
+        }
+        if anchor_state.slot != anchor_slot {
+            return Err(format!(
+                "Anchor state slot {} does not match anchor block slot {}",
+                anchor_state.slot, anchor_slot
+            ));
+        }
+        if anchor_slot % TEthSpec::slots_per_epoch() != 0 {
+            return Err(format!(
+                "Anchor slot {} is not at the start of an epoch",
+                anchor_slot
+            ));
+        }
+
+        anchor_state
+            .build_all_caches(&self.spec)
+            .map_err(|e| format!("Failed to build anchor state caches: {:?}", e))?;
+
+        let beacon_state_root = anchor_block.state_root();
+        let anchor_state_root = anchor_state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Failed to hash anchor state: {:?}", e))?;
+        if anchor_state_root != beacon_state_root {
+            return Err(format!(
+                "Anchor state root {} does not match anchor block state root {}",
+                anchor_state_root, beacon_state_root
+            ));
+        }
+
+        let beacon_block_root = anchor_block.canonical_root();
+        let anchor = PersistedAnchor {
+            anchor_slot,
+            genesis_validators_root,
+        };
+
+        store
+            .put_state(&beacon_state_root, anchor_state.clone())
+            .map_err(|e| format!("Failed to store anchor state: {:?}", e))?;
+        store
+            .put(&beacon_block_root, &anchor_block)
+            .map_err(|e| format!("Failed to store anchor block: {:?}", e))?;
+        store
+            .put(&Hash256::from_slice(&ANCHOR_DB_KEY), &anchor)
+            .map_err(|e| format!("Failed to store anchor: {:?}", e))?;
+
+        let event = AdminEvent::new(
+            "checkpoint_start".to_string(),
+            format!(
+                "anchor_slot: {}, anchor_block_root: {:?}, anchor_state_root: {:?}",
+                anchor_slot, beacon_block_root, beacon_state_root
+            ),
+        );
+        AdminHistory::load::<TEthSpec, _>(&*store)
+            .and_then(|history| history.append::<TEthSpec, _>(&*store, &event))
+            .map_err(|e| format!("Failed to record checkpoint start: {:?}", e))?;
+
+        self.genesis_block_root = Some(beacon_block_root);
+        self.anchor = Some(anchor);
+        self.finalized_checkpoint = Some(CheckPoint {
+            beacon_block_root,
+            beacon_block: anchor_block,
+            beacon_state_root,
+            beacon_state: anchor_state,
+        });
+
+        Ok(self.empty_op_pool())
+    }
+
     /// Sets the `BeaconChain` eth1 backend.
     pub fn eth1_backend(mut self, backend: Option<TEth1Backend>) -> Self {
         self.eth1_chain = backend.map(Eth1Chain::new);
@@ -380,6 +618,8 @@ where
             return Err("beacon_block.state_root != beacon_state".to_string());
         }
 
+        let head_info = HeadInfo::from_checkpoint(&canonical_head);
+
         let pubkey_cache_path = self
             .pubkey_cache_path
             .ok_or_else(|| "Cannot build without a pubkey cache path".to_string())?;
@@ -392,11 +632,36 @@ where
                     .map_err(|e| format!("Unable to init validator pubkey cache: {:?}", e))
             })?;
 
+        let store = self
+            .store
+            .ok_or_else(|| "Cannot build without store".to_string())?;
+
+        let admin_history = AdminHistory::load::<TEthSpec, _>(&*store)
+            .map_err(|e| format!("Unable to load admin history: {:?}", e))?;
+
+        let genesis_block_root = self
+            .genesis_block_root
+            .ok_or_else(|| "Cannot build without a genesis block root".to_string())?;
+        let genesis_validators_root = match self.anchor {
+            Some(anchor) => anchor.genesis_validators_root,
+            None => genesis_validators_root(&*store, genesis_block_root)?,
+        };
+        let anchor_slot = self
+            .anchor
+            .map_or(Slot::new(0), |anchor| anchor.anchor_slot);
+        let genesis_time = canonical_head.beacon_state.genesis_time;
+
+        let validator_monitor = ValidatorMonitor::new(log.clone());
+        for validator_index in self.monitored_validator_indices {
+            validator_monitor.add_validator_index(validator_index);
+        }
+        for pubkey in self.monitored_validator_pubkeys {
+            validator_monitor.add_validator_pubkey(pubkey);
+        }
+
         let beacon_chain = BeaconChain {
             spec: self.spec,
-            store: self
-                .store
-                .ok_or_else(|| "Cannot build without store".to_string())?,
+            store,
             store_migrator: self
                 .store_migrator
                 .ok_or_else(|| "Cannot build without store migrator".to_string())?,
@@ -406,11 +671,18 @@ where
             op_pool: self
                 .op_pool
                 .ok_or_else(|| "Cannot build without op pool".to_string())?,
+            op_pool_persistence: OpPoolPersistence::default(),
             eth1_chain: self.eth1_chain,
-            canonical_head: TimeoutRwLock::new(canonical_head),
-            genesis_block_root: self
-                .genesis_block_root
-                .ok_or_else(|| "Cannot build without a genesis block root".to_string())?,
+            mode: self.mode,
+            canonical_head: TimeoutRwLock::new(
+                Arc::new(canonical_head),
+                "canonical_head",
+                &metrics::HEAD_LOCK_TIMEOUTS,
+            ),
+            genesis_block_root,
+            anchor_slot,
+            genesis_time,
+            genesis_validators_root,
             fork_choice: self
                 .fork_choice
                 .ok_or_else(|| "Cannot build without a fork choice".to_string())?,
@@ -418,8 +690,42 @@ where
                 .event_handler
                 .ok_or_else(|| "Cannot build without an event handler".to_string())?,
             head_tracker: self.head_tracker.unwrap_or_default(),
-            shuffling_cache: TimeoutRwLock::new(ShufflingCache::new()),
-            validator_pubkey_cache: TimeoutRwLock::new(validator_pubkey_cache),
+            shuffling_cache: TimeoutRwLock::new(
+                ShufflingCache::new(),
+                "shuffling_cache",
+                &metrics::ATTESTATION_CACHE_LOCK_TIMEOUTS,
+            ),
+            validator_pubkey_cache: TimeoutRwLock::new(
+                validator_pubkey_cache,
+                "pubkey_cache",
+                &metrics::VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUTS,
+            ),
+            lock_timeouts: self.lock_timeouts,
+            readiness_config: self.readiness_config,
+            max_skip_slots: self.max_skip_slots,
+            max_block_range: self.max_block_range,
+            max_future_slot_tolerance: self.max_future_slot_tolerance,
+            warm_balances_cache_on_import: self.warm_balances_cache_on_import,
+            chain_stats: ChainStats::default(),
+            admin_history,
+            local_attestations: LocalAttestations::default(),
+            produced_block_cache: ProducedBlockCache::default(),
+            state_skip_cache: StateSkipCache::default(),
+            invalid_block_dump: self.invalid_block_dump,
+            finalization_info_cache: Mutex::new(FinalizationInfoCache::new()),
+            observed_attestations: ObservedAttestations::default(),
+            observed_attester_votes: if self.slashing_detection {
+                Some(ObservedAttesterVotes::default())
+            } else {
+                None
+            },
+            observed_block_proposers: if self.slashing_detection {
+                Some(ObservedBlockProposers::default())
+            } else {
+                None
+            },
+            validator_monitor,
+            cached_head_info: RwLock::new(head_info),
             log: log.clone(),
         };
 
@@ -427,6 +733,22 @@ where
             .head()
             .map_err(|e| format!("Failed to get head: {:?}", e))?;
 
+        // The op pool may have been restored from disk with attestations that fork choice has not
+        // seen (e.g., if fork choice was persisted before them).
+        match beacon_chain.reprocess_op_pool_attestations_into_fork_choice() {
+            Ok(0) => (),
+            Ok(count) => debug!(
+                log,
+                "Restored fork choice votes from op pool";
+                "attestations" => count,
+            ),
+            Err(e) => warn!(
+                log,
+                "Unable to restore fork choice votes from op pool";
+                "error" => format!("{:?}", e),
+            ),
+        }
+
         info!(
             log,
             "Beacon chain initialized";
@@ -435,6 +757,21 @@ where
             "head_slot" => format!("{}", head.beacon_block.slot()),
         );
 
+        // Note: this will be `Err` prior to genesis, when the head cannot be in the future.
+        let head_in_future_slots = beacon_chain.head_in_future_slots().unwrap_or(0);
+        metrics::set_gauge(&metrics::HEAD_IN_FUTURE_SLOTS, head_in_future_slots as i64);
+
+        if head_in_future_slots > 0 {
+            warn!(
+                log,
+                "Head is ahead of the system clock";
+                "info" => "block and attestation production is disabled until the clock catches up",
+                "msg" => "the system clock may have been fast when these blocks were imported",
+                "head_slot" => format!("{}", head.beacon_block.slot()),
+                "slots_ahead" => head_in_future_slots,
+            );
+        }
+
         Ok(beacon_chain)
     }
 }
@@ -451,10 +788,14 @@ where
     TEthSpec: EthSpec + 'static,
     TEventHandler: EventHandler<TEthSpec> + 'static,
 {
-    /// Initializes a fork choice with the `ThreadSafeReducedTree` backend.
+    /// Initializes a fork choice with the `ProtoArrayForkChoice` backend.
     ///
     /// If this builder is being "resumed" from disk, then rebuild the last fork choice stored to
     /// the database. Otherwise, create a new, empty fork choice.
+    ///
+    /// A persisted fork choice which cannot be decoded (e.g., one written by the reduced tree
+    /// backend of an earlier version) is an error, since the blocks it contains cannot be
+    /// recovered. The `.lighthouse/beacon` dir must be deleted to start from genesis again.
     pub fn reduced_tree_fork_choice(mut self) -> Result<Self, String> {
         let store = self
             .store
@@ -463,7 +804,7 @@ where
 
         let persisted_fork_choice = store
             .get::<SszForkChoice>(&Hash256::from_slice(&FORK_CHOICE_DB_KEY))
-            .map_err(|e| format!("DB error when reading persisted fork choice: {:?}", e))?;
+            .map_err(|e| required_container_error("fork choice", e))?;
 
         let fork_choice = if let Some(persisted) = persisted_fork_choice {
             ForkChoice::from_ssz_container(persisted)
@@ -477,23 +818,36 @@ where
                 .genesis_block_root
                 .ok_or_else(|| "fork_choice_backend requires a genesis_block_root")?;
 
-            let backend = ProtoArrayForkChoice::new(
-                finalized_checkpoint.beacon_block.message.slot,
-                finalized_checkpoint.beacon_block.message.state_root,
-                // Note: here we set the `justified_epoch` to be the same as the epoch of the
-                // finalized checkpoint. Whilst this finalized checkpoint may actually point to
-                // a _later_ justified checkpoint, that checkpoint won't yet exist in the fork
-                // choice.
-                finalized_checkpoint.beacon_state.current_epoch(),
-                finalized_checkpoint.beacon_state.current_epoch(),
-                finalized_checkpoint.beacon_block_root,
-            )?;
-
-            ForkChoice::new(
-                backend,
-                genesis_block_root,
-                &finalized_checkpoint.beacon_state,
-            )
+            if self.anchor.is_some() {
+                let anchor_state = &finalized_checkpoint.beacon_state;
+                let backend = ProtoArrayForkChoice::new(
+                    finalized_checkpoint.beacon_block.message.slot,
+                    finalized_checkpoint.beacon_block.message.state_root,
+                    anchor_state.current_justified_checkpoint.epoch,
+                    anchor_state.finalized_checkpoint.epoch,
+                    finalized_checkpoint.beacon_block_root,
+                )?;
+
+                ForkChoice::from_anchor(backend, genesis_block_root, anchor_state)
+            } else {
+                let backend = ProtoArrayForkChoice::new(
+                    finalized_checkpoint.beacon_block.message.slot,
+                    finalized_checkpoint.beacon_block.message.state_root,
+                    // Note: here we set the `justified_epoch` to be the same as the epoch of the
+                    // finalized checkpoint. Whilst this finalized checkpoint may actually point
+                    // to a _later_ justified checkpoint, that checkpoint won't yet exist in the
+                    // fork choice.
+                    finalized_checkpoint.beacon_state.current_epoch(),
+                    finalized_checkpoint.beacon_state.current_epoch(),
+                    finalized_checkpoint.beacon_block_root,
+                )?;
+
+                ForkChoice::new(
+                    backend,
+                    genesis_block_root,
+                    &finalized_checkpoint.beacon_state,
+                )
+            }
         };
 
         self.fork_choice = Some(fork_choice);
@@ -604,6 +958,89 @@ where
     }
 }
 
+/// Reads a persisted container which the node is able to rebuild from scratch (e.g., the op pool).
+///
+/// If the container is present but cannot be decoded it is discarded with a warning and `Ok(None)`
+/// is returned, as if it had never been persisted.
+fn get_recoverable_container<I: StoreItem, S: Store<E>, E: EthSpec>(
+    store: &S,
+    key: &Hash256,
+    container: &str,
+    log: &Logger,
+) -> Result<Option<I>, String> {
+    match store.get::<I>(key) {
+        Ok(item) => Ok(item),
+        Err(store::Error::SszDecodeError(e)) => {
+            metrics::inc_counter_vec(&metrics::PERSISTED_CONTAINER_DECODE_FAILURES, &[container]);
+            warn!(
+                log,
+                "Discarding undecodable persisted container";
+                "container" => container,
+                "error" => format!("{:?}", e),
+                "info" => "a new, empty container will be used instead"
+            );
+            Ok(None)
+        }
+        Err(e) => Err(format!(
+            "DB error whilst reading persisted {}: {:?}",
+            container, e
+        )),
+    }
+}
+
+/// Applies the deltas persisted after the snapshot from which `op_pool` was loaded, in the order in
+/// which they were written.
+///
+/// An undecodable delta is discarded along with any that follow it.
+fn apply_op_pool_deltas<S: Store<E>, E: EthSpec>(
+    store: &S,
+    op_pool: OperationPool<E>,
+    log: &Logger,
+) -> Result<OperationPool<E>, String> {
+    for index in 0..OP_POOL_DELTAS_PER_SNAPSHOT {
+        match get_recoverable_container::<PersistedOperationPoolDelta<E>, _, E>(
+            store,
+            &op_pool_delta_key(index),
+            "op_pool_delta",
+            log,
+        )? {
+            Some(delta) => delta.apply_to_operation_pool(&op_pool),
+            None => break,
+        }
+    }
+
+    Ok(op_pool)
+}
+
+/// Produces an error message for a persisted container which the node cannot start without.
+fn required_container_error(container: &str, e: store::Error) -> String {
+    match e {
+        store::Error::SszDecodeError(e) => format!(
+            "Unable to decode the persisted {} ({:?}). The database may be corrupt or from an \
+             incompatible version. Try deleting the .lighthouse/beacon dir.",
+            container, e
+        ),
+        e => format!("DB error when reading persisted {}: {:?}", container, e),
+    }
+}
+
+/// Reads the genesis state from `store` and returns the tree hash root of its validators.
+fn genesis_validators_root<S: Store<E>, E: EthSpec>(
+    store: &S,
+    genesis_block_root: Hash256,
+) -> Result<Hash256, String> {
+    let genesis_block = store
+        .get_block(&genesis_block_root)
+        .map_err(|e| format!("DB error when reading genesis block: {:?}", e))?
+        .ok_or_else(|| "Genesis block not found in store".to_string())?;
+    let genesis_state = store
+        .get_state(&genesis_block.state_root(), Some(genesis_block.slot()))
+        .map_err(|e| format!("DB error when reading genesis state: {:?}", e))?
+        .ok_or_else(|| "Genesis state not found in store".to_string())?;
+
+    Ok(genesis_state.validators.tree_hash_root())
+}
+
 fn genesis_block<T: EthSpec>(
     genesis_state: &mut BeaconState<T>,
     spec: &ChainSpec,
@@ -614,8 +1051,7 @@ fn genesis_block<T: EthSpec>(
         // block consistent with every other block.
         signature: Signature::empty_signature(),
     };
-    genesis_block.message.state_root = genesis_state
-        .update_tree_hash_cache()
+    genesis_block.message.state_root = update_tree_hash_cache(genesis_state)
         .map_err(|e| format!("Error hashing genesis state: {:?}", e))?;
     Ok(genesis_block)
 }
@@ -624,6 +1060,7 @@ fn genesis_block<T: EthSpec>(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::errors::OperationImportError;
     use eth2_hashing::hash;
     use genesis::{generate_deterministic_keypairs, interop_genesis_state};
     use sloggers::{null::NullLoggerBuilder, Build};
@@ -631,7 +1068,7 @@ mod test {
     use std::time::Duration;
     use store::{migrate::NullMigrator, MemoryStore};
     use tempfile::tempdir;
-    use types::{EthSpec, MinimalEthSpec, Slot};
+    use types::{test_utils::TestingVoluntaryExitBuilder, Epoch, EthSpec, MinimalEthSpec, Slot};
 
     type TestEthSpec = MinimalEthSpec;
 
@@ -656,6 +1093,7 @@ mod test {
             &spec,
         )
         .expect("should create interop genesis state");
+        let genesis_validators_root = genesis_state.validators.tree_hash_root();
 
         let chain = BeaconChainBuilder::new(MinimalEthSpec)
             .logger(log.clone())
@@ -676,8 +1114,8 @@ mod test {
 
         let head = chain.head().expect("should get head");
 
-        let state = head.beacon_state;
-        let block = head.beacon_block;
+        let state = &head.beacon_state;
+        let block = &head.beacon_block;
 
         assert_eq!(state.slot, Slot::new(0), "should start from genesis");
         assert_eq!(
@@ -695,7 +1133,7 @@ mod test {
                 .get_block(&Hash256::zero())
                 .expect("should read db")
                 .expect("should find genesis block"),
-            block,
+            *block,
             "should store genesis block under zero hash alias"
         );
         assert_eq!(
@@ -708,6 +1146,57 @@ mod test {
             block.canonical_root(),
             "should have correct genesis block root"
         );
+        assert_eq!(
+            chain.genesis_time(),
+            genesis_time,
+            "should cache the genesis time"
+        );
+        assert_eq!(
+            chain.genesis_validators_root(),
+            genesis_validators_root,
+            "should cache the genesis validators root"
+        );
+    }
+
+    #[test]
+    fn operations_require_eth1() {
+        let validator_count = 8;
+        let keypairs = generate_deterministic_keypairs(validator_count);
+        let spec = MinimalEthSpec::default_spec();
+        let data_dir = tempdir().expect("should create temporary data_dir");
+
+        let genesis_state = interop_genesis_state(&keypairs, 13_371_337, &spec)
+            .expect("should create interop genesis state");
+
+        let chain = BeaconChainBuilder::new(MinimalEthSpec)
+            .logger(get_logger())
+            .store(Arc::new(MemoryStore::open()))
+            .store_migrator(NullMigrator)
+            .data_dir(data_dir.path().to_path_buf())
+            .genesis_state(genesis_state)
+            .expect("should build state using recent genesis")
+            .no_eth1_backend()
+            .null_event_handler()
+            .testing_slot_clock(Duration::from_secs(1))
+            .expect("should configure testing slot clock")
+            .reduced_tree_fork_choice()
+            .expect("should add fork choice to builder")
+            .build()
+            .expect("should build");
+
+        let fork = chain.head_info().expect("should get head info").fork;
+        let exit =
+            TestingVoluntaryExitBuilder::new(Epoch::new(0), 0).build(&keypairs[0].sk, &fork, &spec);
+
+        assert_eq!(
+            chain.process_voluntary_exit(exit),
+            Err(OperationImportError::Eth1Required),
+            "should refuse to queue an exit without eth1"
+        );
+        assert!(
+            chain.op_pool.get_all_voluntary_exits().is_empty(),
+            "should not add the exit to the op pool"
+        );
     }
 
     #[test]
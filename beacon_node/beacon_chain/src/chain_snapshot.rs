@@ -0,0 +1,94 @@
+use crate::errors::BeaconChainError as Error;
+use ssz::{Decode, Encode};
+use std::io::{ErrorKind, Read, Write};
+use types::{BeaconState, EthSpec, SignedBeaconBlock};
+
+/// The kind byte of a `SnapshotRecord::State`.
+const STATE_RECORD: u8 = 0;
+/// The kind byte of a `SnapshotRecord::Block`.
+const BLOCK_RECORD: u8 = 1;
+
+/// A record of the finalized portion of the canonical chain, as exported by
+/// `BeaconChain::export_snapshot` and imported by `BeaconChain::import_snapshot`.
+///
+/// A snapshot is a stream of records in ascending slot order: the genesis state, then each block
+/// from genesis (exclusive) to the finalized block (inclusive), interleaved with the state at the
+/// first slot of each epoch up to the finalized epoch (inclusive). A state follows the block at
+/// its slot, if any.
+///
+/// Only the finalized portion of the chain is exported, so that the snapshot of a node is not
+/// invalidated by a re-org.
+///
+/// Each record is written as its kind byte, followed by the length of its SSZ encoding (as a
+/// little-endian `u32`) and the encoding itself, so that a snapshot may be read and written one
+/// record at a time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotRecord<E: EthSpec> {
+    State(BeaconState<E>),
+    Block(SignedBeaconBlock<E>),
+}
+
+impl<E: EthSpec> SnapshotRecord<E> {
+    /// Writes `self` to `writer`.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        match self {
+            SnapshotRecord::State(state) => Self::write_state(state, writer),
+            SnapshotRecord::Block(block) => {
+                write_record(writer, BLOCK_RECORD, &block.as_ssz_bytes())
+            }
+        }
+    }
+
+    /// Writes `state` to `writer` as a `SnapshotRecord::State`, without cloning it.
+    pub fn write_state<W: Write>(state: &BeaconState<E>, writer: &mut W) -> Result<(), Error> {
+        write_record(writer, STATE_RECORD, &state.as_ssz_bytes())
+    }
+
+    /// Reads the next record from `reader`, returning `None` if `reader` is at its end.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Option<Self>, Error> {
+        let mut kind = [0; 1];
+        match reader.read_exact(&mut kind) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(Error::SnapshotIoError(format!("{:?}", e))),
+        }
+
+        let mut length = [0; 4];
+        reader
+            .read_exact(&mut length)
+            .map_err(|e| Error::SnapshotIoError(format!("{:?}", e)))?;
+        let length = u32::from_le_bytes(length) as u64;
+
+        let mut bytes = vec![];
+        reader
+            .by_ref()
+            .take(length)
+            .read_to_end(&mut bytes)
+            .map_err(|e| Error::SnapshotIoError(format!("{:?}", e)))?;
+        if (bytes.len() as u64) < length {
+            return Err(Error::SnapshotIoError("truncated record".to_string()));
+        }
+
+        match kind[0] {
+            STATE_RECORD => BeaconState::from_ssz_bytes(&bytes).map(SnapshotRecord::State),
+            BLOCK_RECORD => SignedBeaconBlock::from_ssz_bytes(&bytes).map(SnapshotRecord::Block),
+            other => {
+                return Err(Error::SnapshotIoError(format!(
+                    "unknown record kind {}",
+                    other
+                )))
+            }
+        }
+        .map(Some)
+        .map_err(Error::SszDecodeError)
+    }
+}
+
+/// Writes a record of the given `kind` with the SSZ encoding `bytes` to `writer`.
+fn write_record<W: Write>(writer: &mut W, kind: u8, bytes: &[u8]) -> Result<(), Error> {
+    writer
+        .write_all(&[kind])
+        .and_then(|_| writer.write_all(&(bytes.len() as u32).to_le_bytes()))
+        .and_then(|_| writer.write_all(bytes))
+        .map_err(|e| Error::SnapshotIoError(format!("{:?}", e)))
+}
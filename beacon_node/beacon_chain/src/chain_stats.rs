@@ -0,0 +1,156 @@
+use crate::beacon_chain::{AttestationProcessingOutcome, BlockProcessingOutcome};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Counters of the operations performed by a `BeaconChain` since it was started.
+///
+/// Unlike the Prometheus metrics, these counters are scoped to the `BeaconChain` instance (rather
+/// than the process-wide registry) and are never reset.
+///
+/// All counters are atomics so that they can be incremented on the hot paths without locking or
+/// allocating.
+pub struct ChainStats {
+    start_instant: Instant,
+    start_time: u64,
+    blocks_imported: AtomicU64,
+    blocks_ignored: AtomicU64,
+    blocks_invalid: AtomicU64,
+    block_errors: AtomicU64,
+    produced_blocks_reused: AtomicU64,
+    attestations_processed: AtomicU64,
+    attestations_invalid: AtomicU64,
+    attestation_errors: AtomicU64,
+    head_updates: AtomicU64,
+    reorgs: AtomicU64,
+    finalizations: AtomicU64,
+}
+
+/// A point-in-time copy of the `ChainStats`, suitable for serialization.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ChainStatsSnapshot {
+    /// The unix timestamp (seconds) at which the `BeaconChain` was started.
+    pub start_time: u64,
+    /// The number of seconds since the `BeaconChain` was started.
+    pub uptime_seconds: u64,
+    /// Blocks that were valid and imported.
+    pub blocks_imported: u64,
+    /// Blocks that were not imported, but were not invalid (e.g., already known, unknown parent).
+    pub blocks_ignored: u64,
+    /// Blocks that were found to be invalid.
+    pub blocks_invalid: u64,
+    /// Blocks for which an internal error prevented a determination.
+    pub block_errors: u64,
+    /// Blocks produced by this node that were imported by reusing the state computed during
+    /// production.
+    pub produced_blocks_reused: u64,
+    /// Attestations that were valid and processed.
+    pub attestations_processed: u64,
+    /// Attestations that were rejected.
+    pub attestations_invalid: u64,
+    /// Attestations for which an internal error prevented a determination.
+    pub attestation_errors: u64,
+    /// The number of times the canonical head has changed.
+    pub head_updates: u64,
+    /// The number of head changes that were re-orgs.
+    pub reorgs: u64,
+    /// The number of times the finalized checkpoint has advanced.
+    pub finalizations: u64,
+    /// The number of administrative overrides ever applied to the chain, including those from
+    /// previous runs.
+    pub admin_events: u64,
+}
+
+impl Default for ChainStats {
+    fn default() -> Self {
+        Self {
+            start_instant: Instant::now(),
+            start_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_else(|_| 0),
+            blocks_imported: AtomicU64::new(0),
+            blocks_ignored: AtomicU64::new(0),
+            blocks_invalid: AtomicU64::new(0),
+            block_errors: AtomicU64::new(0),
+            produced_blocks_reused: AtomicU64::new(0),
+            attestations_processed: AtomicU64::new(0),
+            attestations_invalid: AtomicU64::new(0),
+            attestation_errors: AtomicU64::new(0),
+            head_updates: AtomicU64::new(0),
+            reorgs: AtomicU64::new(0),
+            finalizations: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ChainStats {
+    /// Records the result of processing a block.
+    pub fn observe_block<E>(&self, outcome: &Result<BlockProcessingOutcome, E>) {
+        let counter = match outcome {
+            Ok(BlockProcessingOutcome::Processed { .. }) => &self.blocks_imported,
+            Ok(BlockProcessingOutcome::ParentUnknown { .. })
+            | Ok(BlockProcessingOutcome::FutureSlot { .. })
+            | Ok(BlockProcessingOutcome::GenesisBlock)
+            | Ok(BlockProcessingOutcome::WouldRevertFinalizedSlot { .. })
+            | Ok(BlockProcessingOutcome::BlockIsAlreadyKnown) => &self.blocks_ignored,
+            Ok(BlockProcessingOutcome::StateRootMismatch { .. })
+            | Ok(BlockProcessingOutcome::BlockSlotLimitReached)
+            | Ok(BlockProcessingOutcome::PerBlockProcessingError(_)) => &self.blocks_invalid,
+            Err(_) => &self.block_errors,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records that a block produced by this node was imported without being re-processed.
+    pub fn observe_produced_block_reuse(&self) {
+        self.produced_blocks_reused.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the result of processing an attestation.
+    pub fn observe_attestation<E>(&self, outcome: &Result<AttestationProcessingOutcome, E>) {
+        let counter = match outcome {
+            Ok(AttestationProcessingOutcome::Processed) => &self.attestations_processed,
+            Ok(_) => &self.attestations_invalid,
+            Err(_) => &self.attestation_errors,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a change in the canonical head.
+    pub fn observe_head_update(&self, is_reorg: bool) {
+        self.head_updates.fetch_add(1, Ordering::Relaxed);
+
+        if is_reorg {
+            self.reorgs.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Records an advancement of the finalized checkpoint.
+    pub fn observe_finalization(&self) {
+        self.finalizations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns a copy of the present values of all counters, along with the total number of
+    /// `admin_events` recorded in the database.
+    pub fn snapshot(&self, admin_events: u64) -> ChainStatsSnapshot {
+        ChainStatsSnapshot {
+            start_time: self.start_time,
+            uptime_seconds: self.start_instant.elapsed().as_secs(),
+            blocks_imported: self.blocks_imported.load(Ordering::Relaxed),
+            blocks_ignored: self.blocks_ignored.load(Ordering::Relaxed),
+            blocks_invalid: self.blocks_invalid.load(Ordering::Relaxed),
+            block_errors: self.block_errors.load(Ordering::Relaxed),
+            produced_blocks_reused: self.produced_blocks_reused.load(Ordering::Relaxed),
+            attestations_processed: self.attestations_processed.load(Ordering::Relaxed),
+            attestations_invalid: self.attestations_invalid.load(Ordering::Relaxed),
+            attestation_errors: self.attestation_errors.load(Ordering::Relaxed),
+            head_updates: self.head_updates.load(Ordering::Relaxed),
+            reorgs: self.reorgs.load(Ordering::Relaxed),
+            finalizations: self.finalizations.load(Ordering::Relaxed),
+            admin_events,
+        }
+    }
+}
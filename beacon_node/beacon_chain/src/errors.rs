@@ -1,3 +1,4 @@
+use crate::beacon_chain::BlockProcessingOutcome;
 use crate::eth1_chain::Error as Eth1ChainError;
 use crate::fork_choice::Error as ForkChoiceError;
 use operation_pool::OpPoolError;
@@ -6,6 +7,7 @@ use ssz_types::Error as SszTypesError;
 use state_processing::per_block_processing::errors::AttestationValidationError;
 use state_processing::BlockProcessingError;
 use state_processing::SlotProcessingError;
+use std::fmt;
 use std::time::Duration;
 use types::*;
 
@@ -19,6 +21,21 @@ macro_rules! easy_from_to {
     };
 }
 
+/// A coarse classification of an error, allowing a caller (e.g., the HTTP API) to decide how to
+/// respond without matching upon each variant.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ErrorCategory {
+    /// The requested object is not known.
+    NotFound,
+    /// The request can never succeed as it was made (e.g., it is too far in the future).
+    BadRequest,
+    /// The request may succeed if it is repeated later (e.g., a lock is contended or genesis has
+    /// not yet occurred).
+    ServiceUnavailable,
+    /// An unexpected failure, likely indicating a bug or corrupt data.
+    Internal,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum BeaconChainError {
     InsufficientValidators,
@@ -41,13 +58,28 @@ pub enum BeaconChainError {
     NoStateForAttestation {
         beacon_block_root: Hash256,
     },
+    /// An attestation was requested for a head block that is unknown to fork choice.
+    UnknownHeadBlock(Hash256),
     CannotAttestToFutureState,
+    /// The canonical head is at a later slot than the slot clock, so production is refused until
+    /// the clock catches up.
+    HeadInFuture {
+        head_slot: Slot,
+        current_slot: Slot,
+    },
     AttestationValidationError(AttestationValidationError),
+    /// Advancing a state did not finish before its deadline. The partially-advanced state is
+    /// cached, so the request may succeed if it is retried.
     StateSkipTooLarge {
         start_slot: Slot,
         requested_slot: Slot,
         max_task_runtime: Duration,
     },
+    /// Advancing a state would skip more slots than permitted by the `max_skip_slots` config.
+    TooManySkipSlots {
+        requested: u64,
+        max: u64,
+    },
     /// Returned when an internal check fails, indicating corrupt data.
     InvariantViolated(String),
     SszTypesError(SszTypesError),
@@ -59,12 +91,304 @@ pub enum BeaconChainError {
     ValidatorPubkeyCacheIncomplete(usize),
     SignatureSetError(state_processing::signature_sets::Error),
     ValidatorPubkeyCacheFileError(String),
+    /// Attester duties were requested for an epoch for which the shuffling is not yet known.
+    AttesterDutiesEpochTooFarAhead {
+        request_epoch: Epoch,
+        current_epoch: Epoch,
+    },
+    /// Block proposers were requested for an epoch for which the shuffling is not yet known.
+    ProposerEpochTooFarAhead {
+        request_epoch: Epoch,
+        current_epoch: Epoch,
+    },
+    /// An object was tagged with a fork version that is not in the fork schedule of the head.
+    ///
+    /// The `epoch_hint` is read from the undecoded bytes, so it is only indicative.
+    UnsupportedFork {
+        fork_version: [u8; 4],
+        epoch_hint: Option<Epoch>,
+    },
+    SszDecodeError(DecodeError),
+    /// There is no committee with the given index at the given slot.
+    NoCommitteeForSlotAndIndex {
+        slot: Slot,
+        index: CommitteeIndex,
+    },
+    /// A validator is unknown or is not active in the given epoch.
+    ValidatorNotActive {
+        validator_index: usize,
+        epoch: Epoch,
+    },
+    /// A chain snapshot could not be read or written.
+    SnapshotIoError(String),
+    /// The first state of an imported snapshot is not the genesis state of this chain.
+    SnapshotGenesisMismatch {
+        snapshot_state_root: Option<Hash256>,
+        local_state_root: Hash256,
+    },
+    /// A block of an imported snapshot is not a child of the block which precedes it.
+    SnapshotBlocksNotChained {
+        block_root: Hash256,
+        parent_root: Hash256,
+        expected_parent_root: Hash256,
+    },
+    /// A block of an imported snapshot could not be imported.
+    SnapshotBlockRejected {
+        block_root: Hash256,
+        outcome: Box<BlockProcessingOutcome>,
+    },
+    /// A state of an imported snapshot differs from the state produced by its blocks.
+    SnapshotStateMismatch {
+        slot: Slot,
+        snapshot_state_root: Hash256,
+        local_state_root: Hash256,
+    },
+    /// Slashing detection is disabled, so no votes are stored to check against.
+    SlashingDetectionDisabled,
+    /// A balance history was requested for more epochs than may be loaded at once.
+    BalanceHistoryRangeTooLarge {
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+        max_epochs: u64,
+    },
 }
 
 easy_from_to!(SlotProcessingError, BeaconChainError);
 easy_from_to!(AttestationValidationError, BeaconChainError);
 easy_from_to!(SszTypesError, BeaconChainError);
 
+impl BeaconChainError {
+    /// Returns the category of `self`.
+    ///
+    /// The match is exhaustive so that each new variant must be categorised explicitly.
+    pub fn kind(&self) -> ErrorCategory {
+        match self {
+            BeaconChainError::NoStateForSlot(_)
+            | BeaconChainError::UnableToFindTargetRoot(_)
+            | BeaconChainError::MissingBeaconBlock(_)
+            | BeaconChainError::MissingBeaconState(_)
+            | BeaconChainError::NoStateForAttestation { .. }
+            | BeaconChainError::UnknownHeadBlock(_)
+            | BeaconChainError::NoCommitteeForSlotAndIndex { .. } => ErrorCategory::NotFound,
+            BeaconChainError::CannotAttestToFutureState
+            | BeaconChainError::AttestationValidationError(_)
+            | BeaconChainError::TooManySkipSlots { .. }
+            | BeaconChainError::InvalidValidatorPubkeyBytes(_)
+            | BeaconChainError::AttesterDutiesEpochTooFarAhead { .. }
+            | BeaconChainError::ProposerEpochTooFarAhead { .. }
+            | BeaconChainError::UnsupportedFork { .. }
+            | BeaconChainError::SszDecodeError(_)
+            | BeaconChainError::ValidatorNotActive { .. }
+            | BeaconChainError::BalanceHistoryRangeTooLarge { .. } => ErrorCategory::BadRequest,
+            BeaconChainError::UnableToReadSlot
+            | BeaconChainError::SlotClockDidNotStart
+            | BeaconChainError::HeadInFuture { .. }
+            | BeaconChainError::CanonicalHeadLockTimeout
+            | BeaconChainError::AttestationCacheLockTimeout
+            | BeaconChainError::ValidatorPubkeyCacheLockTimeout
+            | BeaconChainError::StateSkipTooLarge { .. }
+            | BeaconChainError::SlashingDetectionDisabled => ErrorCategory::ServiceUnavailable,
+            BeaconChainError::InsufficientValidators
+            | BeaconChainError::RevertedFinalizedEpoch { .. }
+            | BeaconChainError::BeaconStateError(_)
+            | BeaconChainError::DBInconsistent(_)
+            | BeaconChainError::DBError(_)
+            | BeaconChainError::ForkChoiceError(_)
+            | BeaconChainError::SlotProcessingError(_)
+            | BeaconChainError::UnableToAdvanceState(_)
+            | BeaconChainError::InvariantViolated(_)
+            | BeaconChainError::SszTypesError(_)
+            | BeaconChainError::IncorrectStateForAttestation(_)
+            | BeaconChainError::ValidatorPubkeyCacheIncomplete(_)
+            | BeaconChainError::SignatureSetError(_)
+            | BeaconChainError::ValidatorPubkeyCacheFileError(_)
+            | BeaconChainError::SnapshotIoError(_)
+            | BeaconChainError::SnapshotGenesisMismatch { .. }
+            | BeaconChainError::SnapshotBlocksNotChained { .. }
+            | BeaconChainError::SnapshotBlockRejected { .. }
+            | BeaconChainError::SnapshotStateMismatch { .. } => ErrorCategory::Internal,
+        }
+    }
+}
+
+impl fmt::Display for BeaconChainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BeaconChainError::InsufficientValidators => write!(f, "insufficient validators"),
+            BeaconChainError::UnableToReadSlot => write!(
+                f,
+                "unable to read the slot clock, genesis may not have occurred"
+            ),
+            BeaconChainError::RevertedFinalizedEpoch {
+                previous_epoch,
+                new_epoch,
+            } => write!(
+                f,
+                "finalized epoch reverted from {} to {}",
+                previous_epoch, new_epoch
+            ),
+            BeaconChainError::SlotClockDidNotStart => write!(f, "the slot clock has not started"),
+            BeaconChainError::NoStateForSlot(slot) => write!(f, "no state for slot {}", slot),
+            BeaconChainError::UnableToFindTargetRoot(slot) => {
+                write!(f, "unable to find the target root for slot {}", slot)
+            }
+            BeaconChainError::BeaconStateError(e) => write!(f, "beacon state error: {:?}", e),
+            BeaconChainError::DBInconsistent(e) => write!(f, "database inconsistent: {}", e),
+            BeaconChainError::DBError(e) => write!(f, "database error: {:?}", e),
+            BeaconChainError::ForkChoiceError(e) => write!(f, "fork choice error: {:?}", e),
+            BeaconChainError::MissingBeaconBlock(root) => write!(f, "unknown block {:?}", root),
+            BeaconChainError::MissingBeaconState(root) => write!(f, "unknown state {:?}", root),
+            BeaconChainError::SlotProcessingError(e) => write!(f, "slot processing error: {:?}", e),
+            BeaconChainError::UnableToAdvanceState(e) => {
+                write!(f, "unable to advance state: {}", e)
+            }
+            BeaconChainError::NoStateForAttestation { beacon_block_root } => {
+                write!(f, "no state to attest to block {:?}", beacon_block_root)
+            }
+            BeaconChainError::UnknownHeadBlock(root) => {
+                write!(f, "head block {:?} is unknown to fork choice", root)
+            }
+            BeaconChainError::CannotAttestToFutureState => {
+                write!(f, "cannot attest to a future state")
+            }
+            BeaconChainError::HeadInFuture {
+                head_slot,
+                current_slot,
+            } => write!(
+                f,
+                "the head slot {} is later than the current slot {}",
+                head_slot, current_slot
+            ),
+            BeaconChainError::AttestationValidationError(e) => {
+                write!(f, "invalid attestation: {:?}", e)
+            }
+            BeaconChainError::StateSkipTooLarge {
+                start_slot,
+                requested_slot,
+                ..
+            } => write!(
+                f,
+                "skipping from slot {} to slot {} would take too long",
+                start_slot, requested_slot
+            ),
+            BeaconChainError::TooManySkipSlots { requested, max } => write!(
+                f,
+                "{} skip slots were requested, at most {} are permitted",
+                requested, max
+            ),
+            BeaconChainError::InvariantViolated(e) => write!(f, "invariant violated: {}", e),
+            BeaconChainError::SszTypesError(e) => write!(f, "ssz types error: {:?}", e),
+            BeaconChainError::CanonicalHeadLockTimeout => {
+                write!(f, "timed out waiting for the canonical head lock")
+            }
+            BeaconChainError::AttestationCacheLockTimeout => {
+                write!(f, "timed out waiting for the attestation cache lock")
+            }
+            BeaconChainError::ValidatorPubkeyCacheLockTimeout => {
+                write!(f, "timed out waiting for the validator pubkey cache lock")
+            }
+            BeaconChainError::IncorrectStateForAttestation(e) => {
+                write!(f, "incorrect state for attestation: {:?}", e)
+            }
+            BeaconChainError::InvalidValidatorPubkeyBytes(e) => {
+                write!(f, "invalid validator pubkey: {:?}", e)
+            }
+            BeaconChainError::ValidatorPubkeyCacheIncomplete(i) => {
+                write!(f, "validator pubkey cache is missing index {}", i)
+            }
+            BeaconChainError::SignatureSetError(e) => write!(f, "signature set error: {:?}", e),
+            BeaconChainError::ValidatorPubkeyCacheFileError(e) => {
+                write!(f, "validator pubkey cache file error: {}", e)
+            }
+            BeaconChainError::AttesterDutiesEpochTooFarAhead {
+                request_epoch,
+                current_epoch,
+            } => write!(
+                f,
+                "attester duties for epoch {} are not yet known in epoch {}",
+                request_epoch, current_epoch
+            ),
+            BeaconChainError::ProposerEpochTooFarAhead {
+                request_epoch,
+                current_epoch,
+            } => write!(
+                f,
+                "proposers for epoch {} are not yet known in epoch {}",
+                request_epoch, current_epoch
+            ),
+            BeaconChainError::UnsupportedFork {
+                fork_version,
+                epoch_hint,
+            } => write!(
+                f,
+                "unsupported fork version {:?} (epoch hint {:?})",
+                fork_version, epoch_hint
+            ),
+            BeaconChainError::SszDecodeError(e) => write!(f, "ssz decode error: {:?}", e),
+            BeaconChainError::NoCommitteeForSlotAndIndex { slot, index } => {
+                write!(f, "no committee {} at slot {}", index, slot)
+            }
+            BeaconChainError::ValidatorNotActive {
+                validator_index,
+                epoch,
+            } => write!(
+                f,
+                "validator {} is not active in epoch {}",
+                validator_index, epoch
+            ),
+            BeaconChainError::SnapshotIoError(e) => write!(f, "snapshot io error: {}", e),
+            BeaconChainError::SnapshotGenesisMismatch {
+                snapshot_state_root,
+                local_state_root,
+            } => write!(
+                f,
+                "snapshot genesis state {:?} does not match local genesis state {:?}",
+                snapshot_state_root, local_state_root
+            ),
+            BeaconChainError::SnapshotBlocksNotChained {
+                block_root,
+                parent_root,
+                expected_parent_root,
+            } => write!(
+                f,
+                "snapshot block {:?} has parent {:?}, expected {:?}",
+                block_root, parent_root, expected_parent_root
+            ),
+            BeaconChainError::SnapshotBlockRejected {
+                block_root,
+                outcome,
+            } => write!(
+                f,
+                "snapshot block {:?} was rejected: {:?}",
+                block_root, outcome
+            ),
+            BeaconChainError::SnapshotStateMismatch {
+                slot,
+                snapshot_state_root,
+                local_state_root,
+            } => write!(
+                f,
+                "snapshot state {:?} at slot {} does not match local state {:?}",
+                snapshot_state_root, slot, local_state_root
+            ),
+            BeaconChainError::SlashingDetectionDisabled => {
+                write!(f, "slashing detection is disabled")
+            }
+            BeaconChainError::BalanceHistoryRangeTooLarge {
+                start_epoch,
+                end_epoch,
+                max_epochs,
+            } => write!(
+                f,
+                "balance history from epoch {} to {} exceeds the maximum of {} epochs",
+                start_epoch, end_epoch, max_epochs
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BeaconChainError {}
+
 #[derive(Debug, PartialEq)]
 pub enum BlockProductionError {
     UnableToGetBlockRootFromState,
@@ -78,9 +402,102 @@ pub enum BlockProductionError {
     /// The `BeaconChain` was explicitly configured _without_ a connection to eth1, therefore it
     /// cannot produce blocks.
     NoEth1ChainConnection,
+    /// The `BeaconChain` is in `BeaconChainMode::Follower`, therefore it does not produce blocks.
+    ProductionDisabledInFollowerMode,
+    BeaconChainError(BeaconChainError),
+    /// The supplied RANDAO reveal is not a valid signature of the epoch by the proposer.
+    InvalidRandaoReveal {
+        proposer_index: usize,
+    },
+    UnknownProposerPubkey(usize),
+    ValidatorPubkeyCacheLockTimeout,
 }
 
 easy_from_to!(BlockProcessingError, BlockProductionError);
 easy_from_to!(BeaconStateError, BlockProductionError);
 easy_from_to!(SlotProcessingError, BlockProductionError);
 easy_from_to!(Eth1ChainError, BlockProductionError);
+easy_from_to!(BeaconChainError, BlockProductionError);
+
+impl BlockProductionError {
+    /// Returns the category of `self`.
+    pub fn kind(&self) -> ErrorCategory {
+        match self {
+            BlockProductionError::BeaconChainError(e) => e.kind(),
+            BlockProductionError::UnableToProduceAtSlot(_)
+            | BlockProductionError::InvalidRandaoReveal { .. } => ErrorCategory::BadRequest,
+            // The node will not produce blocks in its present configuration, however another
+            // node may be able to.
+            BlockProductionError::NoEth1ChainConnection
+            | BlockProductionError::ProductionDisabledInFollowerMode
+            | BlockProductionError::ValidatorPubkeyCacheLockTimeout => {
+                ErrorCategory::ServiceUnavailable
+            }
+            BlockProductionError::UnableToGetBlockRootFromState
+            | BlockProductionError::UnableToReadSlot
+            | BlockProductionError::SlotProcessingError(_)
+            | BlockProductionError::BlockProcessingError(_)
+            | BlockProductionError::Eth1ChainError(_)
+            | BlockProductionError::BeaconStateError(_)
+            | BlockProductionError::OpPoolError(_)
+            | BlockProductionError::UnknownProposerPubkey(_) => ErrorCategory::Internal,
+        }
+    }
+}
+
+impl fmt::Display for BlockProductionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockProductionError::UnableToGetBlockRootFromState => {
+                write!(f, "unable to read block root from state")
+            }
+            BlockProductionError::UnableToReadSlot => write!(f, "unable to read the slot clock"),
+            BlockProductionError::UnableToProduceAtSlot(slot) => {
+                write!(f, "unable to produce a block at slot {}", slot)
+            }
+            BlockProductionError::SlotProcessingError(e) => {
+                write!(f, "slot processing error: {:?}", e)
+            }
+            BlockProductionError::BlockProcessingError(e) => {
+                write!(f, "block processing error: {:?}", e)
+            }
+            BlockProductionError::Eth1ChainError(e) => write!(f, "eth1 chain error: {:?}", e),
+            BlockProductionError::BeaconStateError(e) => write!(f, "beacon state error: {:?}", e),
+            BlockProductionError::OpPoolError(e) => write!(f, "operation pool error: {:?}", e),
+            BlockProductionError::NoEth1ChainConnection => {
+                write!(f, "the node has no connection to eth1")
+            }
+            BlockProductionError::ProductionDisabledInFollowerMode => {
+                write!(f, "block production is disabled in follower mode")
+            }
+            BlockProductionError::BeaconChainError(e) => write!(f, "{}", e),
+            BlockProductionError::InvalidRandaoReveal { proposer_index } => {
+                write!(f, "invalid randao reveal for proposer {}", proposer_index)
+            }
+            BlockProductionError::UnknownProposerPubkey(i) => {
+                write!(f, "unknown pubkey for proposer {}", i)
+            }
+            BlockProductionError::ValidatorPubkeyCacheLockTimeout => {
+                write!(f, "timed out waiting for the validator pubkey cache lock")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BlockProductionError {}
+
+/// Returned when an operation (e.g., a voluntary exit or a slashing) is not queued in the
+/// operation pool.
+#[derive(Debug, PartialEq)]
+pub enum OperationImportError<T> {
+    /// The operation is not valid against the current state.
+    Invalid(T),
+    /// The `BeaconChain` was explicitly configured _without_ a connection to eth1. It cannot
+    /// produce blocks, so it does not queue operations for inclusion in them.
+    Eth1Required,
+    /// The `BeaconChain` is in `BeaconChainMode::Follower`. It does not produce blocks, so it
+    /// does not queue operations for inclusion in them.
+    FollowerMode,
+    /// No state was available to validate the operation against.
+    BeaconChainError(BeaconChainError),
+}
@@ -1,8 +1,9 @@
 use crate::metrics;
-use eth1::{Config as Eth1Config, Eth1Block, Service as HttpService};
+use eth1::{Config as Eth1Config, Eth1Block, Service as HttpService, SszEth1Cache};
 use eth2_hashing::hash;
 use exit_future::Exit;
 use futures::Future;
+use merkle_proof::verify_merkle_proof;
 use slog::{debug, error, trace, Logger};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
@@ -12,7 +13,9 @@ use std::collections::HashMap;
 use std::iter::DoubleEndedIterator;
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use store::{DBColumn, Error as StoreError, SimpleStoreItem, Store};
+use tree_hash::TreeHash;
 use types::{
     BeaconState, BeaconStateError, ChainSpec, Deposit, Eth1Data, EthSpec, Hash256, Slot, Unsigned,
     DEPOSIT_TREE_DEPTH,
@@ -45,6 +48,13 @@ pub enum Error {
     ///
     /// The eth1 caches are stale, or a junk value was voted into the chain.
     UnknownPreviousEth1BlockHash,
+    /// A block must include `expected` deposits, but `found` deposits were supplied.
+    WrongDepositCount { expected: u64, found: u64 },
+    /// The deposit that should have the given index does not have a valid Merkle proof against
+    /// the deposit root that will be used to process it.
+    ///
+    /// The deposit cache is corrupt, or the eth1 node supplied invalid deposit logs.
+    InvalidDepositProof { deposit_index: u64 },
 }
 
 #[derive(Encode, Decode, Clone)]
@@ -53,6 +63,29 @@ pub struct SszEth1 {
     backend_bytes: Vec<u8>,
 }
 
+impl SszEth1 {
+    /// Returns the encoded caches of the eth1 backend.
+    pub fn backend_bytes(&self) -> &[u8] {
+        &self.backend_bytes
+    }
+
+    /// Re-encodes the caches of the eth1 backend in the current layout, converting them from the
+    /// layout of schema version zero if required.
+    ///
+    /// The dummy backend does not persist any caches, so empty backend bytes are left as-is.
+    pub fn upgrade_from_v0(self) -> Result<Self, String> {
+        if self.backend_bytes.is_empty() {
+            return Ok(self);
+        }
+
+        let cache = SszEth1Cache::from_versioned_bytes(&self.backend_bytes)?;
+        Ok(Self {
+            use_dummy_backend: self.use_dummy_backend,
+            backend_bytes: cache.as_ssz_bytes(),
+        })
+    }
+}
+
 impl SimpleStoreItem for SszEth1 {
     fn db_column() -> DBColumn {
         DBColumn::Eth1Cache
@@ -132,6 +165,68 @@ where
         }
     }
 
+    /// Returns the `Eth1Data` vote and the `Deposits` for a block being produced atop `state`.
+    ///
+    /// Avoids producing an invalid block if the eth1 cache is corrupt: if the deposits for the
+    /// vote of the backend can't be retrieved or verified, the existing `state.eth1_data` is voted
+    /// for instead. This only requires deposits that the chain has already agreed upon, which is
+    /// usually none at all.
+    pub fn eth1_data_and_deposits_for_block_production(
+        &self,
+        state: &BeaconState<E>,
+        spec: &ChainSpec,
+        log: &Logger,
+    ) -> Result<(Eth1Data, Vec<Deposit>), Error> {
+        let eth1_data = self.eth1_data_for_block_production(state, spec)?;
+
+        let deposits = self
+            .deposits_for_block_inclusion(state, &eth1_data, spec)
+            .and_then(|deposits| {
+                verify_deposits_for_inclusion(state, &eth1_data, &deposits, spec)?;
+                Ok(deposits)
+            });
+
+        match deposits {
+            Ok(deposits) => Ok((eth1_data, deposits)),
+            Err(e) => {
+                metrics::inc_counter(&metrics::BLOCK_PRODUCTION_INVALID_DEPOSITS);
+                error!(
+                    log,
+                    "Invalid deposits for block production";
+                    "error" => format!("{:?}", e),
+                    "deposit_root" => format!("{:?}", eth1_data.deposit_root),
+                    "deposit_count" => eth1_data.deposit_count,
+                    "outcome" => "voting for the existing eth1 data",
+                );
+
+                let eth1_data = state.eth1_data.clone();
+                let deposits = self.deposits_for_block_inclusion(state, &eth1_data, spec)?;
+                verify_deposits_for_inclusion(state, &eth1_data, &deposits, spec)?;
+
+                Ok((eth1_data, deposits))
+            }
+        }
+    }
+
+    /// Returns the timestamp of the latest block in the eth1 cache, or `None` if the cache is
+    /// empty.
+    pub fn latest_block_timestamp(&self) -> Option<u64> {
+        if self.use_dummy_backend {
+            let dummy_backend: DummyEth1ChainBackend<E, S> = DummyEth1ChainBackend::default();
+            dummy_backend.latest_block_timestamp()
+        } else {
+            self.backend.latest_block_timestamp()
+        }
+    }
+
+    /// Drops any cached eth1 blocks and deposits that are no longer required once
+    /// `finalized_state` has been finalized.
+    pub fn prune_finalized(&self, finalized_state: &BeaconState<E>, spec: &ChainSpec) {
+        if !self.use_dummy_backend {
+            self.backend.prune_finalized(finalized_state, spec)
+        }
+    }
+
     /// Instantiate `Eth1Chain` from a persisted `SszEth1`.
     ///
     /// The `Eth1Chain` will have the same caches as the persisted `SszEth1`.
@@ -184,6 +279,14 @@ pub trait Eth1ChainBackend<T: EthSpec, S: Store<T>>: Sized + Send + Sync {
         spec: &ChainSpec,
     ) -> Result<Vec<Deposit>, Error>;
 
+    /// Returns the timestamp of the latest eth1 block known to the backend, or `None` if it knows
+    /// of no blocks.
+    fn latest_block_timestamp(&self) -> Option<u64>;
+
+    /// Drops any cached data that is no longer required to serve `Self::eth1_data` and
+    /// `Self::queued_deposits` for descendants of `finalized_state`.
+    fn prune_finalized(&self, finalized_state: &BeaconState<T>, spec: &ChainSpec);
+
     /// Encode the `Eth1ChainBackend` instance to bytes.
     fn as_bytes(&self) -> Vec<u8>;
 
@@ -230,6 +333,17 @@ impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for DummyEth1ChainBackend<T
         Ok(vec![])
     }
 
+    /// The dummy back-end generates eth1 data on demand, so it is never out-of-date.
+    fn latest_block_timestamp(&self) -> Option<u64> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|duration| duration.as_secs())
+    }
+
+    /// The dummy back-end has no caches to prune.
+    fn prune_finalized(&self, _: &BeaconState<T>, _: &ChainSpec) {}
+
     /// Return empty Vec<u8> for dummy backend.
     fn as_bytes(&self) -> Vec<u8> {
         Vec::new()
@@ -295,6 +409,14 @@ impl<T: EthSpec, S: Store<T>> CachingEth1Backend<T, S> {
 }
 
 impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for CachingEth1Backend<T, S> {
+    /// Follows the honest validator algorithm for voting on `Eth1Data`:
+    ///
+    /// - The candidates are the cached eth1 blocks within the follow-distance window prior to the
+    ///   start of the voting period, excluding those with fewer deposits than `state.eth1_data`.
+    /// - If any candidates have been voted for in `state`, vote for the candidate with the most
+    ///   votes, preferring the later block in the case of a tie.
+    /// - Otherwise, vote for the latest candidate.
+    /// - If there are no candidates, vote for `state.eth1_data`.
     fn eth1_data(&self, state: &BeaconState<T>, spec: &ChainSpec) -> Result<Eth1Data, Error> {
         let period = T::SlotsPerEth1VotingPeriod::to_u64();
         let voting_period_start_slot = (state.slot / period) * period;
@@ -306,9 +428,13 @@ impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for CachingEth1Backend<T, S
 
         let blocks = self.core.blocks().read();
 
-        let votes_to_consider =
+        let mut votes_to_consider =
             get_votes_to_consider(blocks.iter(), voting_period_start_seconds, spec);
 
+        // A vote must never reduce the number of deposits known to the beacon chain.
+        votes_to_consider
+            .retain(|eth1_data, _| eth1_data.deposit_count >= state.eth1_data.deposit_count);
+
         trace!(
             self.log,
             "Found eth1 data votes_to_consider";
@@ -317,38 +443,32 @@ impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for CachingEth1Backend<T, S
         let valid_votes = collect_valid_votes(state, &votes_to_consider);
 
         let eth1_data = if let Some(eth1_data) = find_winning_vote(valid_votes) {
+            metrics::inc_counter(&metrics::MAJORITY_ETH1_VOTES);
             eth1_data
+        } else if let Some((eth1_data, _block_number)) = votes_to_consider
+            .iter()
+            .max_by_key(|(_, block_number)| *block_number)
+        {
+            // None of the votes in the state are for candidate blocks, so start voting for the
+            // latest candidate.
+            debug!(
+                self.log,
+                "No valid eth1_data votes";
+                "outcome" => "Casting vote corresponding to last candidate eth1 block",
+            );
+            metrics::inc_counter(&metrics::NEW_CANDIDATE_ETH1_VOTES);
+            eth1_data.clone()
         } else {
-            // In this case, there are no valid votes available.
-            //
-            // Here we choose the eth1_data corresponding to the latest block in our voting window.
-            // If no votes exist, choose `state.eth1_data` as default vote.
-            let default_vote = votes_to_consider
-                .iter()
-                .max_by(|(_, x), (_, y)| x.cmp(y))
-                .map(|vote| {
-                    let vote = vote.0.clone();
-                    debug!(
-                        self.log,
-                        "No valid eth1_data votes";
-                        "outcome" => "Casting vote corresponding to last candidate eth1 block",
-                    );
-                    vote
-                })
-                .unwrap_or_else(|| {
-                    let vote = state.eth1_data.clone();
-                    error!(
-                        self.log,
-                        "No valid eth1_data votes, `votes_to_consider` empty";
-                        "lowest_block_number" => self.core.lowest_block_number(),
-                        "earliest_block_timestamp" => self.core.earliest_block_timestamp(),
-                        "genesis_time" => state.genesis_time,
-                        "outcome" => "casting `state.eth1_data` as eth1 vote"
-                    );
-                    metrics::inc_counter(&metrics::DEFAULT_ETH1_VOTES);
-                    vote
-                });
-            default_vote
+            error!(
+                self.log,
+                "No valid eth1_data votes, `votes_to_consider` empty";
+                "lowest_block_number" => self.core.lowest_block_number(),
+                "earliest_block_timestamp" => self.core.earliest_block_timestamp(),
+                "genesis_time" => state.genesis_time,
+                "outcome" => "casting `state.eth1_data` as eth1 vote"
+            );
+            metrics::inc_counter(&metrics::DEFAULT_ETH1_VOTES);
+            state.eth1_data.clone()
         };
 
         debug!(
@@ -393,6 +513,34 @@ impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for CachingEth1Backend<T, S
         }
     }
 
+    fn latest_block_timestamp(&self) -> Option<u64> {
+        self.core.latest_block_timestamp()
+    }
+
+    /// Drops the eth1 blocks that are prior to both `finalized_state.eth1_data` and the candidate
+    /// blocks of the voting period of `finalized_state`, as well as the logs of the deposits that
+    /// have been included in `finalized_state`.
+    ///
+    /// Descendants of `finalized_state` are in the same or later voting periods and may only
+    /// include later deposits, so they will never require the dropped data.
+    fn prune_finalized(&self, finalized_state: &BeaconState<T>, spec: &ChainSpec) {
+        let period = T::SlotsPerEth1VotingPeriod::to_u64();
+        let voting_period_start_slot = (finalized_state.slot / period) * period;
+        let voting_period_start_seconds = slot_start_seconds::<T>(
+            finalized_state.genesis_time,
+            spec.milliseconds_per_slot,
+            voting_period_start_slot,
+        );
+        let earliest_candidate_timestamp = voting_period_start_seconds
+            .saturating_sub(spec.seconds_per_eth1_block * spec.eth1_follow_distance * 2);
+
+        self.core.prune_finalized(
+            finalized_state.eth1_data.block_hash,
+            finalized_state.eth1_deposit_index,
+            earliest_candidate_timestamp,
+        );
+    }
+
     /// Return encoded byte representation of the block and deposit caches.
     fn as_bytes(&self) -> Vec<u8> {
         self.core.as_bytes()
@@ -415,6 +563,50 @@ impl<T: EthSpec, S: Store<T>> Eth1ChainBackend<T, S> for CachingEth1Backend<T, S
     }
 }
 
+/// Checks that `deposits` may be included in a block that is applied to `state` and votes for
+/// `eth1_data_vote`.
+///
+/// The deposits must be exactly those that the block is required to include, in order, starting
+/// at `state.eth1_deposit_index`. Each deposit must have a valid Merkle proof against the deposit
+/// root of the `Eth1Data` that will be in effect once the vote has been processed.
+pub fn verify_deposits_for_inclusion<T: EthSpec>(
+    state: &BeaconState<T>,
+    eth1_data_vote: &Eth1Data,
+    deposits: &[Deposit],
+    spec: &ChainSpec,
+) -> Result<(), Error> {
+    let eth1_data =
+        get_new_eth1_data(state, eth1_data_vote).unwrap_or_else(|| state.eth1_data.clone());
+
+    let expected = std::cmp::min(
+        eth1_data
+            .deposit_count
+            .saturating_sub(state.eth1_deposit_index),
+        T::MaxDeposits::to_u64(),
+    );
+    let found = deposits.len() as u64;
+    if expected != found {
+        return Err(Error::WrongDepositCount { expected, found });
+    }
+
+    deposits
+        .iter()
+        .zip(state.eth1_deposit_index..)
+        .try_for_each(|(deposit, deposit_index)| {
+            if verify_merkle_proof(
+                deposit.data.tree_hash_root(),
+                &deposit.proof[..],
+                spec.deposit_contract_tree_depth as usize + 1,
+                deposit_index as usize,
+                eth1_data.deposit_root,
+            ) {
+                Ok(())
+            } else {
+                Err(Error::InvalidDepositProof { deposit_index })
+            }
+        })
+}
+
 /// Get all votes from eth1 blocks which are in the list of candidate blocks for the
 /// current eth1 voting period.
 ///
@@ -707,6 +899,110 @@ mod test {
             })
         }
 
+        #[test]
+        fn verify_deposits() {
+            let spec = &E::default_spec();
+            let slots_per_eth1_voting_period = <E as EthSpec>::SlotsPerEth1VotingPeriod::to_usize();
+
+            let eth1_chain = get_eth1_chain();
+            let core = &eth1_chain.backend.core;
+
+            (0..4).for_each(|i| {
+                core.deposits()
+                    .write()
+                    .cache
+                    .insert_log(get_deposit_log(i, spec))
+                    .expect("should insert log")
+            });
+
+            let deposit_root_at = |count: u64| {
+                core.deposits()
+                    .read()
+                    .cache
+                    .get_deposits(count, count, count, DEPOSIT_TREE_DEPTH)
+                    .expect("should get deposit root")
+                    .0
+            };
+
+            let mut state: BeaconState<E> = BeaconState::new(0, get_eth1_data(0), &spec);
+            state.eth1_deposit_index = 1;
+            state.eth1_data.deposit_count = 4;
+            state.eth1_data.deposit_root = deposit_root_at(4);
+            let vote = state.eth1_data.clone();
+
+            let deposits = eth1_chain
+                .deposits_for_block_inclusion(&state, &vote, spec)
+                .expect("should get deposits");
+
+            assert_eq!(
+                verify_deposits_for_inclusion(&state, &vote, &deposits, spec),
+                Ok(()),
+                "should accept the deposits from the cache"
+            );
+
+            let mut corrupt_deposits = deposits.clone();
+            corrupt_deposits[1].proof[0] = Hash256::from_low_u64_be(42);
+            assert_eq!(
+                verify_deposits_for_inclusion(&state, &vote, &corrupt_deposits, spec),
+                Err(Error::InvalidDepositProof { deposit_index: 2 }),
+                "should reject a corrupt proof"
+            );
+
+            let mut reordered_deposits = deposits.clone();
+            reordered_deposits.swap(0, 1);
+            assert_eq!(
+                verify_deposits_for_inclusion(&state, &vote, &reordered_deposits, spec),
+                Err(Error::InvalidDepositProof { deposit_index: 1 }),
+                "should reject deposits that are out of order"
+            );
+
+            assert_eq!(
+                verify_deposits_for_inclusion(&state, &vote, &deposits[1..], spec),
+                Err(Error::WrongDepositCount {
+                    expected: 3,
+                    found: 2
+                }),
+                "should reject a missing deposit"
+            );
+
+            // Simulate an eth1 node that supplies a junk deposit root, which is about to be voted
+            // into the state.
+            state.eth1_data.deposit_count = 1;
+            state.eth1_data.deposit_root = deposit_root_at(1);
+            let junk_vote = Eth1Data {
+                deposit_root: Hash256::from_low_u64_be(42),
+                deposit_count: 4,
+                block_hash: Hash256::from_low_u64_be(42),
+            };
+            for _ in 0..slots_per_eth1_voting_period / 2 {
+                state
+                    .eth1_data_votes
+                    .push(junk_vote.clone())
+                    .expect("should add vote");
+            }
+
+            let deposits = eth1_chain
+                .deposits_for_block_inclusion(&state, &junk_vote, spec)
+                .expect("should get deposits");
+            assert_eq!(
+                verify_deposits_for_inclusion(&state, &junk_vote, &deposits, spec),
+                Err(Error::InvalidDepositProof { deposit_index: 1 }),
+                "should reject deposits that do not match the vote"
+            );
+
+            // Falling back to the existing eth1 data allows a valid block to be produced.
+            let fallback_vote = state.eth1_data.clone();
+            let deposits = eth1_chain
+                .deposits_for_block_inclusion(&state, &fallback_vote, spec)
+                .expect("should get deposits");
+            assert!(deposits.is_empty(), "should not require any deposits");
+            assert_eq!(
+                verify_deposits_for_inclusion(&state, &fallback_vote, &deposits, spec),
+                Ok(()),
+                "should accept the fallback vote"
+            );
+        }
+
         #[test]
         fn eth1_data_empty_cache() {
             let spec = &E::default_spec();
@@ -780,6 +1076,274 @@ mod test {
                 "default vote must correspond to last block in candidate blocks"
             );
         }
+
+        #[test]
+        fn stale_cache_default_vote() {
+            let spec = &E::default_spec();
+            let slots_per_eth1_voting_period = <E as EthSpec>::SlotsPerEth1VotingPeriod::to_u64();
+            let eth1_follow_distance = spec.eth1_follow_distance;
+
+            let eth1_chain = get_eth1_chain();
+
+            let mut state: BeaconState<E> = BeaconState::new(0, get_eth1_data(0), &spec);
+
+            state.slot = Slot::from(slots_per_eth1_voting_period * 10);
+            let follow_distance_seconds = eth1_follow_distance * spec.seconds_per_eth1_block;
+            let voting_period_start = get_voting_period_start_seconds(&state, &spec);
+            let start_eth1_block = voting_period_start - follow_distance_seconds * 2;
+
+            // Populate the blocks cache with blocks that are all too old to be candidates.
+            (start_eth1_block - 10..start_eth1_block).for_each(|i| {
+                eth1_chain
+                    .backend
+                    .core
+                    .blocks()
+                    .write()
+                    .insert_root_or_child(get_eth1_block(i, i))
+                    .expect("should add blocks to cache");
+            });
+
+            let vote = eth1_chain
+                .eth1_data_for_block_production(&state, &spec)
+                .expect("should produce default eth1 data vote");
+
+            assert_eq!(
+                vote, state.eth1_data,
+                "default vote should be state.eth1_data if there are no candidate blocks"
+            );
+        }
+
+        #[test]
+        fn majority_vote() {
+            let spec = &E::default_spec();
+            let slots_per_eth1_voting_period = <E as EthSpec>::SlotsPerEth1VotingPeriod::to_u64();
+            let eth1_follow_distance = spec.eth1_follow_distance;
+
+            let eth1_chain = get_eth1_chain();
+
+            let mut state: BeaconState<E> = BeaconState::new(0, get_eth1_data(0), &spec);
+
+            state.slot = Slot::from(slots_per_eth1_voting_period * 10);
+            let follow_distance_seconds = eth1_follow_distance * spec.seconds_per_eth1_block;
+            let voting_period_start = get_voting_period_start_seconds(&state, &spec);
+            let start_eth1_block = voting_period_start - follow_distance_seconds * 2;
+            let end_eth1_block = voting_period_start - follow_distance_seconds;
+
+            let candidates = (start_eth1_block..end_eth1_block)
+                .map(|i| get_eth1_block(i, i))
+                .collect::<Vec<_>>();
+
+            candidates.iter().for_each(|block| {
+                eth1_chain
+                    .backend
+                    .core
+                    .blocks()
+                    .write()
+                    .insert_root_or_child(block.clone())
+                    .expect("should add blocks to cache");
+            });
+
+            let eth1_data = |block: &Eth1Block| {
+                block
+                    .clone()
+                    .eth1_data()
+                    .expect("should have valid eth1 data")
+            };
+            let earlier = eth1_data(&candidates[0]);
+            let later = eth1_data(&candidates[1]);
+            let latest = eth1_data(candidates.last().expect("should have candidates"));
+
+            let add_votes = |state: &mut BeaconState<E>, vote: &Eth1Data, count: usize| {
+                for _ in 0..count {
+                    state
+                        .eth1_data_votes
+                        .push(vote.clone())
+                        .expect("should add vote");
+                }
+            };
+
+            // Votes for blocks that are not candidates should be ignored.
+            add_votes(&mut state, &get_eth1_data(42), 3);
+            add_votes(&mut state, &earlier, 2);
+            add_votes(&mut state, &later, 1);
+
+            assert_eq!(
+                eth1_chain.eth1_data_for_block_production(&state, &spec),
+                Ok(earlier),
+                "should vote with the majority"
+            );
+
+            add_votes(&mut state, &later, 1);
+
+            assert_eq!(
+                eth1_chain.eth1_data_for_block_production(&state, &spec),
+                Ok(later.clone()),
+                "should prefer the later block when votes are tied"
+            );
+
+            state.eth1_data.deposit_count = later.deposit_count + 1;
+            add_votes(&mut state, &later, 1);
+
+            assert_eq!(
+                eth1_chain.eth1_data_for_block_production(&state, &spec),
+                Ok(latest),
+                "should not vote for blocks with fewer deposits than the state"
+            );
+        }
+
+        #[test]
+        fn pruning_keeps_caches_bounded() {
+            let spec = &E::default_spec();
+            let slots_per_eth1_voting_period = <E as EthSpec>::SlotsPerEth1VotingPeriod::to_u64();
+            let seconds_per_eth1_block = spec.seconds_per_eth1_block;
+            let eth1_follow_distance = spec.eth1_follow_distance;
+
+            let eth1_chain = get_eth1_chain();
+            let core = &eth1_chain.backend.core;
+            let template_log = get_deposit_log(0, spec);
+
+            // Simulate a chain that finalizes every voting period, with one deposit per eth1
+            // block.
+            let mut next_block_number = 0;
+            for period in 0..64 {
+                let mut state: BeaconState<E> = BeaconState::new(0, get_eth1_data(0), &spec);
+                state.slot = Slot::new(period * slots_per_eth1_voting_period);
+                let voting_period_start = get_voting_period_start_seconds(&state, &spec);
+
+                while next_block_number * seconds_per_eth1_block <= voting_period_start {
+                    core.blocks()
+                        .write()
+                        .insert_root_or_child(get_eth1_block(
+                            next_block_number * seconds_per_eth1_block,
+                            next_block_number,
+                        ))
+                        .expect("should add block to cache");
+                    core.deposits()
+                        .write()
+                        .cache
+                        .insert_log(DepositLog {
+                            index: next_block_number,
+                            block_number: next_block_number,
+                            ..template_log.clone()
+                        })
+                        .expect("should add deposit to cache");
+                    next_block_number += 1;
+                }
+
+                // Finalize the newest candidate block of the voting period, along with all of the
+                // deposits prior to it.
+                let finalized_block_number = voting_period_start
+                    .saturating_sub(seconds_per_eth1_block * eth1_follow_distance)
+                    / seconds_per_eth1_block;
+                state.eth1_data = get_eth1_block(
+                    finalized_block_number * seconds_per_eth1_block,
+                    finalized_block_number,
+                )
+                .eth1_data()
+                .expect("should have eth1 data");
+                state.eth1_deposit_index = state.eth1_data.deposit_count;
+
+                eth1_chain.prune_finalized(&state, spec);
+
+                assert!(
+                    core.block_cache_len() as u64 <= 2 * eth1_follow_distance + 2,
+                    "block cache should be bounded in period {}",
+                    period
+                );
+                assert!(
+                    core.deposits().read().cache.retained_logs_len() as u64
+                        <= eth1_follow_distance + 2,
+                    "deposit logs should be bounded in period {}",
+                    period
+                );
+
+                // The finalized deposits and all later deposits should still be available.
+                state.eth1_data.deposit_count = next_block_number;
+                assert_eq!(
+                    eth1_chain
+                        .deposits_for_block_inclusion(&state, &Eth1Data::default(), spec)
+                        .map(|deposits| deposits.len() as u64),
+                    Ok(std::cmp::min(
+                        next_block_number - state.eth1_deposit_index,
+                        <E as EthSpec>::MaxDeposits::to_u64()
+                    )),
+                    "should get deposits after pruning in period {}",
+                    period
+                );
+            }
+
+            assert_eq!(
+                core.deposits().read().cache.len() as u64,
+                next_block_number,
+                "should count pruned deposits"
+            );
+        }
+    }
+
+    mod block_production {
+        use super::*;
+        use store::MemoryStore;
+
+        /// A backend which votes for junk eth1 data, but fails to provide the deposits for any vote
+        /// other than the existing eth1 data of the state.
+        struct FailingEth1Backend;
+
+        impl Eth1ChainBackend<E, MemoryStore<E>> for FailingEth1Backend {
+            fn eth1_data(&self, _: &BeaconState<E>, _: &ChainSpec) -> Result<Eth1Data, Error> {
+                Ok(get_eth1_data(42))
+            }
+
+            fn queued_deposits(
+                &self,
+                state: &BeaconState<E>,
+                eth1_data_vote: &Eth1Data,
+                _: &ChainSpec,
+            ) -> Result<Vec<Deposit>, Error> {
+                if *eth1_data_vote == state.eth1_data {
+                    Ok(vec![])
+                } else {
+                    Err(Error::BackendError("deposit cache unavailable".to_string()))
+                }
+            }
+
+            fn latest_block_timestamp(&self) -> Option<u64> {
+                None
+            }
+
+            fn prune_finalized(&self, _: &BeaconState<E>, _: &ChainSpec) {}
+
+            fn as_bytes(&self) -> Vec<u8> {
+                vec![]
+            }
+
+            fn from_bytes(
+                _: &[u8],
+                _: Eth1Config,
+                _: Arc<MemoryStore<E>>,
+                _: Logger,
+            ) -> Result<Self, String> {
+                Ok(FailingEth1Backend)
+            }
+        }
+
+        #[test]
+        fn falls_back_to_existing_eth1_data_when_deposits_are_unavailable() {
+            let spec = &E::default_spec();
+            let log = null_logger().unwrap();
+            let eth1_chain: Eth1Chain<_, E, MemoryStore<E>> = Eth1Chain::new(FailingEth1Backend);
+            let state: BeaconState<E> = BeaconState::new(0, get_eth1_data(0), spec);
+
+            assert_eq!(
+                eth1_chain.deposits_for_block_inclusion(&state, &get_eth1_data(42), spec),
+                Err(Error::BackendError("deposit cache unavailable".to_string())),
+                "should fail to get deposits for the vote of the backend"
+            );
+            assert_eq!(
+                eth1_chain.eth1_data_and_deposits_for_block_production(&state, spec, &log),
+                Ok((state.eth1_data.clone(), vec![])),
+                "should vote for the existing eth1 data instead of failing"
+            );
+        }
     }
 
     mod eth1_data_sets {
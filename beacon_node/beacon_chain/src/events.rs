@@ -1,14 +1,173 @@
+use crate::metrics;
+use futures::sync::mpsc::{channel, Receiver, Sender};
+use parking_lot::Mutex;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::marker::PhantomData;
-use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock};
+use std::str::FromStr;
+use types::{
+    Attestation, AttesterSlashing, Checkpoint, Epoch, EthSpec, Hash256, ProposerSlashing,
+    SignedBeaconBlock, SignedVoluntaryExit, Slot,
+};
 pub use websocket_server::WebSocketSender;
 
+/// The default number of events that may be queued for a `ChannelEventHandler` subscriber before
+/// further events are dropped for that subscriber.
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 1_024;
+
 pub trait EventHandler<T: EthSpec>: Sized + Send + Sync {
     fn register(&self, kind: EventKind<T>) -> Result<(), String>;
+
+    /// Returns `false` if events of `topic` would be discarded by `Self::register`.
+    ///
+    /// Allows callers to skip building an event (e.g., cloning a block) that nobody will receive.
+    fn wants(&self, _topic: EventTopic) -> bool {
+        true
+    }
+
+    /// Returns a channel which receives all future events, if the handler supports subscription.
+    fn subscribe(&self) -> Option<Receiver<EventKind<T>>> {
+        None
+    }
 }
 
 pub struct NullEventHandler<T: EthSpec>(PhantomData<T>);
 
+/// Broadcasts each event to all subscribers via a bounded channel per subscriber.
+///
+/// Events are never allowed to block the caller of `register` (i.e., block processing). If a
+/// subscriber's channel is full the event is dropped for that subscriber (it "lags") and it is
+/// sent an `EventKind::Lagged` notice once it has caught up. Subscribers that have hung up are
+/// removed.
+pub struct ChannelEventHandler<T: EthSpec> {
+    subscribers: Mutex<Vec<Subscriber<T>>>,
+    capacity: usize,
+}
+
+/// A subscriber to a `ChannelEventHandler`.
+struct Subscriber<T: EthSpec> {
+    sender: Sender<EventKind<T>>,
+    /// The number of events dropped since the subscriber was last sent an event.
+    missed: u64,
+}
+
+impl<T: EthSpec> Subscriber<T> {
+    /// Attempts to send `kind` to the subscriber without blocking, preceded by a notice of any
+    /// events that it has missed.
+    ///
+    /// Returns `false` if the subscriber has hung up.
+    fn try_send(&mut self, kind: EventKind<T>) -> bool {
+        if self.missed > 0 {
+            let notice = EventKind::Lagged {
+                missed: self.missed,
+            };
+            match self.sender.try_send(notice) {
+                Ok(()) => self.missed = 0,
+                Err(e) if e.is_full() => return self.lag(),
+                Err(_) => return false,
+            }
+        }
+
+        match self.sender.try_send(kind) {
+            Ok(()) => true,
+            Err(e) if e.is_full() => self.lag(),
+            Err(_) => false,
+        }
+    }
+
+    /// Records that an event was dropped for this subscriber.
+    fn lag(&mut self) -> bool {
+        metrics::inc_counter(&metrics::EVENT_STREAM_LAGGED);
+        self.missed += 1;
+        true
+    }
+}
+
+impl<T: EthSpec> ChannelEventHandler<T> {
+    /// Instantiates a new handler, where each subscriber may have up to `capacity` queued events.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            subscribers: Mutex::new(vec![]),
+            capacity,
+        }
+    }
+
+    /// Returns the number of subscribers that have not yet hung up.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().len()
+    }
+}
+
+impl<T: EthSpec> Default for ChannelEventHandler<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHANNEL_CAPACITY)
+    }
+}
+
+impl<T: EthSpec> EventHandler<T> for ChannelEventHandler<T> {
+    fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        let mut subscribers = self.subscribers.lock();
+
+        *subscribers = subscribers
+            .drain(..)
+            .filter_map(|mut subscriber| {
+                if subscriber.try_send(kind.clone()) {
+                    Some(subscriber)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Ok(())
+    }
+
+    /// Returns `false` if there are no subscribers.
+    fn wants(&self, _topic: EventTopic) -> bool {
+        !self.subscribers.lock().is_empty()
+    }
+
+    fn subscribe(&self) -> Option<Receiver<EventKind<T>>> {
+        let (sender, receiver) = channel(self.capacity);
+        self.subscribers
+            .lock()
+            .push(Subscriber { sender, missed: 0 });
+        Some(receiver)
+    }
+}
+
+/// Publishes each event to the WebSocket server, as well as to the subscribers of a
+/// `ChannelEventHandler` (e.g., HTTP event streams).
+pub struct TeeEventHandler<T: EthSpec> {
+    websocket: WebSocketSender<T>,
+    channel: ChannelEventHandler<T>,
+}
+
+impl<T: EthSpec> TeeEventHandler<T> {
+    /// Instantiates a new handler that publishes to `websocket` and any subscribers.
+    pub fn new(websocket: WebSocketSender<T>) -> Self {
+        Self {
+            websocket,
+            channel: ChannelEventHandler::default(),
+        }
+    }
+}
+
+impl<T: EthSpec> EventHandler<T> for TeeEventHandler<T> {
+    fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        self.channel.register(kind.clone())?;
+        self.websocket.register(kind)
+    }
+
+    fn wants(&self, topic: EventTopic) -> bool {
+        self.websocket.wants(topic) || self.channel.wants(topic)
+    }
+
+    fn subscribe(&self) -> Option<Receiver<EventKind<T>>> {
+        self.channel.subscribe()
+    }
+}
+
 impl<T: EthSpec> EventHandler<T> for WebSocketSender<T> {
     fn register(&self, kind: EventKind<T>) -> Result<(), String> {
         self.send_string(
@@ -16,12 +175,21 @@ impl<T: EthSpec> EventHandler<T> for WebSocketSender<T> {
                 .map_err(|e| format!("Unable to serialize event: {:?}", e))?,
         )
     }
+
+    /// Returns `false` if the server was never started.
+    fn wants(&self, _topic: EventTopic) -> bool {
+        self.is_active()
+    }
 }
 
 impl<T: EthSpec> EventHandler<T> for NullEventHandler<T> {
     fn register(&self, _kind: EventKind<T>) -> Result<(), String> {
         Ok(())
     }
+
+    fn wants(&self, _topic: EventTopic) -> bool {
+        false
+    }
 }
 
 impl<T: EthSpec> Default for NullEventHandler<T> {
@@ -30,7 +198,7 @@ impl<T: EthSpec> Default for NullEventHandler<T> {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(
     bound = "T: EthSpec",
     rename_all = "snake_case",
@@ -46,6 +214,21 @@ pub enum EventKind<T: EthSpec> {
     BeaconFinalization {
         epoch: Epoch,
         root: Hash256,
+        /// The slot of the finalized block.
+        slot: Slot,
+        /// The state root of the finalized block.
+        state_root: Hash256,
+        /// The number of nodes that were pruned from fork choice as a result of finalization.
+        pruned_fork_choice_nodes: usize,
+    },
+    /// Emitted once each time the finalized checkpoint changes, with enough information to load
+    /// the finalized state directly.
+    BeaconFinalizedCheckpoint {
+        checkpoint: Checkpoint,
+        /// The state root of the finalized block.
+        state_root: Hash256,
+        /// The slot of the finalized block.
+        slot: Slot,
     },
     BeaconBlockImported {
         block_root: Hash256,
@@ -55,6 +238,15 @@ pub enum EventKind<T: EthSpec> {
         reason: String,
         block: Box<SignedBeaconBlock<T>>,
     },
+    /// A block was rejected because its slot is later than the present slot, which indicates
+    /// that the clock of its sender (or of this node) is skewed.
+    BeaconBlockFromFutureSlot {
+        block_root: Hash256,
+        block_slot: Slot,
+        present_slot: Slot,
+        /// The number of slots by which the block is ahead, i.e., `block_slot - present_slot`.
+        skew: u64,
+    },
     BeaconAttestationImported {
         attestation: Box<Attestation<T>>,
     },
@@ -62,4 +254,160 @@ pub enum EventKind<T: EthSpec> {
         reason: String,
         attestation: Box<Attestation<T>>,
     },
+    /// A valid voluntary exit was added to the operation pool.
+    VoluntaryExitReceived {
+        exit: SignedVoluntaryExit,
+    },
+    /// A valid proposer slashing was added to the operation pool.
+    ProposerSlashingReceived {
+        proposer_slashing: Box<ProposerSlashing>,
+    },
+    /// A valid attester slashing was added to the operation pool.
+    AttesterSlashingReceived {
+        attester_slashing: Box<AttesterSlashing<T>>,
+    },
+    /// Sent to a single subscriber of a `ChannelEventHandler` in place of the `missed` events
+    /// that it was not keeping up with.
+    Lagged {
+        missed: u64,
+    },
+}
+
+/// A category of `EventKind`, used to filter an event stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTopic {
+    Block,
+    Attestation,
+    Head,
+    Finalization,
+    /// Head changes that are re-orgs.
+    Reorg,
+    VoluntaryExit,
+    /// Proposer and attester slashings.
+    Slashing,
+}
+
+impl EventTopic {
+    /// Returns all topics.
+    pub fn all() -> HashSet<EventTopic> {
+        vec![
+            EventTopic::Block,
+            EventTopic::Attestation,
+            EventTopic::Head,
+            EventTopic::Finalization,
+            EventTopic::Reorg,
+            EventTopic::VoluntaryExit,
+            EventTopic::Slashing,
+        ]
+        .into_iter()
+        .collect()
+    }
+}
+
+impl FromStr for EventTopic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(EventTopic::Block),
+            "attestation" => Ok(EventTopic::Attestation),
+            "head" => Ok(EventTopic::Head),
+            "finalization" => Ok(EventTopic::Finalization),
+            "reorg" => Ok(EventTopic::Reorg),
+            "voluntary_exit" => Ok(EventTopic::VoluntaryExit),
+            "slashing" => Ok(EventTopic::Slashing),
+            other => Err(format!("Unknown event topic: {}", other)),
+        }
+    }
+}
+
+impl<T: EthSpec> EventKind<T> {
+    /// Returns `true` if `self` should be sent to a subscriber of `topics`.
+    ///
+    /// A head change that is a re-org belongs to both the `Head` and `Reorg` topics, whilst
+    /// `Lagged` notices are sent to all subscribers.
+    pub fn matches_any(&self, topics: &HashSet<EventTopic>) -> bool {
+        match self {
+            EventKind::BeaconHeadChanged { reorg, .. } => {
+                topics.contains(&EventTopic::Head)
+                    || (*reorg && topics.contains(&EventTopic::Reorg))
+            }
+            EventKind::BeaconFinalization { .. } | EventKind::BeaconFinalizedCheckpoint { .. } => {
+                topics.contains(&EventTopic::Finalization)
+            }
+            EventKind::BeaconBlockImported { .. }
+            | EventKind::BeaconBlockRejected { .. }
+            | EventKind::BeaconBlockFromFutureSlot { .. } => topics.contains(&EventTopic::Block),
+            EventKind::BeaconAttestationImported { .. }
+            | EventKind::BeaconAttestationRejected { .. } => {
+                topics.contains(&EventTopic::Attestation)
+            }
+            EventKind::VoluntaryExitReceived { .. } => topics.contains(&EventTopic::VoluntaryExit),
+            EventKind::ProposerSlashingReceived { .. }
+            | EventKind::AttesterSlashingReceived { .. } => topics.contains(&EventTopic::Slashing),
+            EventKind::Lagged { .. } => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    #[test]
+    fn null_handler_wants_nothing() {
+        let handler = NullEventHandler::<E>::default();
+
+        assert!(EventTopic::all()
+            .into_iter()
+            .all(|topic| !handler.wants(topic)));
+    }
+
+    #[test]
+    fn channel_handler_wants_events_whilst_subscribed() {
+        let handler = ChannelEventHandler::<E>::default();
+
+        assert!(
+            !handler.wants(EventTopic::Block),
+            "should not want events without subscribers"
+        );
+
+        let receiver = handler.subscribe().expect("should subscribe");
+
+        assert!(
+            handler.wants(EventTopic::Block),
+            "should want events with a subscriber"
+        );
+
+        drop(receiver);
+        handler
+            .register(EventKind::Lagged { missed: 0 })
+            .expect("should register event");
+
+        assert!(
+            !handler.wants(EventTopic::Block),
+            "should not want events once the subscriber has hung up"
+        );
+    }
+
+    #[test]
+    fn tee_handler_wants_events_for_active_websocket() {
+        let handler = TeeEventHandler::<E>::new(WebSocketSender::dummy());
+
+        assert!(
+            !handler.wants(EventTopic::Attestation),
+            "should not want events without a server or subscribers"
+        );
+
+        let _receiver = handler.subscribe().expect("should subscribe");
+
+        assert!(
+            handler.wants(EventTopic::Attestation),
+            "should want events with a subscriber"
+        );
+    }
 }
@@ -0,0 +1,29 @@
+use crate::beacon_chain::FinalizationInfo;
+use lru::LruCache;
+use types::Hash256;
+
+/// The number of blocks for which the `FinalizationInfo` is cached.
+const CACHE_SIZE: usize = 256;
+
+/// Provides an LRU cache for the `FinalizationInfo` of blocks, keyed by block root.
+///
+/// Only finalized blocks should be inserted, since their `FinalizationInfo` never changes.
+pub struct FinalizationInfoCache {
+    cache: LruCache<Hash256, FinalizationInfo>,
+}
+
+impl FinalizationInfoCache {
+    pub fn new() -> Self {
+        Self {
+            cache: LruCache::new(CACHE_SIZE),
+        }
+    }
+
+    pub fn get(&mut self, block_root: &Hash256) -> Option<FinalizationInfo> {
+        self.cache.get(block_root).copied()
+    }
+
+    pub fn insert(&mut self, block_root: Hash256, info: FinalizationInfo) {
+        self.cache.put(block_root, info);
+    }
+}
@@ -1,15 +1,20 @@
 mod checkpoint_manager;
 
 use crate::{errors::BeaconChainError, metrics, BeaconChain, BeaconChainTypes};
-use checkpoint_manager::{get_effective_balances, CheckpointManager, CheckpointWithBalances};
+use checkpoint_manager::{
+    get_effective_balances, CheckpointManager, CheckpointWithBalances, FFGCheckpoints,
+};
 use parking_lot::{RwLock, RwLockReadGuard};
-use proto_array_fork_choice::{core::ProtoArray, ProtoArrayForkChoice};
+use proto_array_fork_choice::{core::ProtoArray, ForkChoiceNode, ProtoArrayForkChoice};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use state_processing::common::get_indexed_attestation;
 use std::marker::PhantomData;
 use store::{DBColumn, Error as StoreError, SimpleStoreItem};
-use types::{BeaconBlock, BeaconState, BeaconStateError, Epoch, Hash256, IndexedAttestation, Slot};
+use types::{
+    BeaconBlock, BeaconState, BeaconStateError, Checkpoint, Epoch, Hash256, IndexedAttestation,
+    Slot,
+};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -72,6 +77,37 @@ impl<T: BeaconChainTypes> ForkChoice<T> {
         }
     }
 
+    /// Instantiate a fork chooser which starts from a trusted `anchor_state`, the post-state of
+    /// the block `anchor_block_root`, rather than from genesis.
+    ///
+    /// The justified and finalized checkpoints of `anchor_state` are not known to fork choice, so
+    /// the anchor block stands in for both. Their epochs are retained so that the blocks which
+    /// descend from the anchor are viable for the head.
+    pub fn from_anchor(
+        backend: ProtoArrayForkChoice,
+        anchor_block_root: Hash256,
+        anchor_state: &BeaconState<T::EthSpec>,
+    ) -> Self {
+        let ffg_checkpoints = FFGCheckpoints {
+            justified: CheckpointWithBalances {
+                epoch: anchor_state.current_justified_checkpoint.epoch,
+                root: anchor_block_root,
+                balances: get_effective_balances(anchor_state),
+            },
+            finalized: Checkpoint {
+                epoch: anchor_state.finalized_checkpoint.epoch,
+                root: anchor_block_root,
+            },
+        };
+
+        Self {
+            backend,
+            genesis_block_root: anchor_block_root,
+            checkpoint_manager: RwLock::new(CheckpointManager::from_checkpoints(ffg_checkpoints)),
+            _phantom: PhantomData,
+        }
+    }
+
     /// Run the fork choice rule to determine the head.
     pub fn find_head(&self, chain: &BeaconChain<T>) -> Result<Hash256> {
         let timer = metrics::start_timer(&metrics::FORK_CHOICE_FIND_HEAD_TIMES);
@@ -87,6 +123,12 @@ impl<T: BeaconChainTypes> ForkChoice<T> {
         let mut manager = self.checkpoint_manager.write();
         manager.maybe_update(chain.slot()?, chain)?;
 
+        // Applying the score changes visits every block known to fork choice.
+        metrics::observe(
+            &metrics::FORK_CHOICE_FIND_HEAD_NODES,
+            self.backend.len() as f64,
+        );
+
         let result = self
             .backend
             .find_head(
@@ -112,6 +154,29 @@ impl<T: BeaconChainTypes> ForkChoice<T> {
         self.backend.block_slot_and_state_root(block_root)
     }
 
+    /// Adds the effective balances of `state` to the balances cache, so that they need not be read
+    /// from the database if the first block in the epoch of `state` becomes justified.
+    ///
+    /// `block_root` must be the root of the latest block in `state`. Does nothing unless it is the
+    /// first block in the epoch of `state`, since the cache only holds a few entries.
+    pub fn warm_balances_cache(
+        &self,
+        block_root: Hash256,
+        state: &BeaconState<T::EthSpec>,
+    ) -> Result<()> {
+        self.checkpoint_manager
+            .write()
+            .warm_balances_cache(block_root, state)
+    }
+
+    /// Returns `true` if the balances of the given block are in the balances cache, i.e., they will
+    /// not be read from the database if the block becomes justified.
+    pub fn balances_cache_contains(&self, block_root: &Hash256) -> bool {
+        self.checkpoint_manager
+            .read()
+            .balances_cache_contains(*block_root)
+    }
+
     /// Process all attestations in the given `block`.
     ///
     /// Assumes the block (and therefore its attestations) are valid. It is a logic error to
@@ -213,11 +278,64 @@ impl<T: BeaconChainTypes> ForkChoice<T> {
         self.backend.latest_message(validator_index)
     }
 
+    /// Returns the weight of the block with `block_root`, as of the last run of fork choice.
+    ///
+    /// Returns `None` if the block is unknown to fork choice.
+    pub fn block_weight(&self, block_root: &Hash256) -> Option<u64> {
+        self.backend.block_weight(block_root)
+    }
+
+    /// Returns the current `(justified, finalized)` checkpoints, as used by `Self::find_head`.
+    ///
+    /// The genesis checkpoints use the genesis block root, rather than the zero root used in the
+    /// `BeaconState`.
+    pub fn checkpoints(&self) -> (Checkpoint, Checkpoint) {
+        let manager = self.checkpoint_manager.read();
+
+        (
+            manager.current.justified.clone().into(),
+            manager.current.finalized.clone(),
+        )
+    }
+
+    /// Returns a summary of every block known to fork choice, with parents before children.
+    pub fn fork_choice_nodes(&self) -> Vec<ForkChoiceNode> {
+        self.backend.fork_choice_nodes()
+    }
+
+    /// Returns the latest messages of the validators with indices in `start..start + count`, as
+    /// `(validator_index, block_root, target_epoch)`.
+    pub fn latest_messages(&self, start: usize, count: usize) -> Vec<(usize, Hash256, Epoch)> {
+        self.backend.latest_messages(start, count)
+    }
+
+    /// Returns the number of entries in the latest messages table.
+    pub fn latest_messages_len(&self) -> usize {
+        self.backend.latest_messages_len()
+    }
+
     /// Trigger a prune on the underlying fork choice backend.
-    pub fn prune(&self) -> Result<()> {
+    ///
+    /// Returns the number of nodes that were pruned.
+    pub fn prune(&self) -> Result<usize> {
         let finalized_root = self.checkpoint_manager.read().current.finalized.root;
 
-        self.backend.maybe_prune(finalized_root).map_err(Into::into)
+        let len_before = self.backend.len();
+        self.backend.maybe_prune(finalized_root)?;
+
+        Ok(len_before.saturating_sub(self.backend.len()))
+    }
+
+    /// Sets the minimum number of pre-finalization blocks required before `Self::prune` removes
+    /// them from the backend.
+    pub fn set_prune_threshold(&self, prune_threshold: usize) {
+        self.backend.set_prune_threshold(prune_threshold)
+    }
+
+    /// Checks the structural invariants of the backend (e.g., that each block is indexed by its
+    /// root).
+    pub fn verify_invariants(&self) -> Result<()> {
+        self.backend.verify_invariants().map_err(Into::into)
     }
 
     /// Returns a read-lock to the core `ProtoArray` struct.
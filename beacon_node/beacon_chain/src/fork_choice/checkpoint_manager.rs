@@ -39,6 +39,19 @@ impl BalancesCache {
             return Ok(());
         }
 
+        self.insert(block_root, state)
+    }
+
+    /// Add the balances of the given `state` to the cache, keyed by the root of the block at the
+    /// first slot of `state.current_epoch`, where `block_root` is the root of the latest block in
+    /// `state`.
+    ///
+    /// Does nothing if there is already some entry for that block root.
+    pub fn insert<E: EthSpec>(
+        &mut self,
+        block_root: Hash256,
+        state: &BeaconState<E>,
+    ) -> Result<(), Error> {
         let epoch_boundary_slot = state.current_epoch().start_slot(E::slots_per_epoch());
         let epoch_boundary_root = if epoch_boundary_slot == state.slot {
             block_root
@@ -89,6 +102,11 @@ impl BalancesCache {
         Ok(!prior_block_found)
     }
 
+    /// Returns `true` if there are balances for the given `block_root` in the cache.
+    pub fn contains(&self, block_root: Hash256) -> bool {
+        self.position(block_root).is_some()
+    }
+
     fn position(&self, block_root: Hash256) -> Option<usize> {
         self.items
             .iter()
@@ -175,10 +193,15 @@ pub struct CheckpointManager {
 impl CheckpointManager {
     /// Create a new checkpoint cache from `genesis_checkpoint` derived from the genesis block.
     pub fn new(genesis_checkpoint: CheckpointWithBalances) -> Self {
-        let ffg_checkpoint = FFGCheckpoints {
+        Self::from_checkpoints(FFGCheckpoints {
             justified: genesis_checkpoint.clone(),
             finalized: genesis_checkpoint.into(),
-        };
+        })
+    }
+
+    /// Create a new checkpoint cache which starts with `ffg_checkpoint` as both the current and
+    /// the best checkpoints.
+    pub fn from_checkpoints(ffg_checkpoint: FFGCheckpoints) -> Self {
         Self {
             current: ffg_checkpoint.clone(),
             best: ffg_checkpoint,
@@ -335,9 +358,57 @@ impl CheckpointManager {
         }
     }
 
+    /// Adds the balances of `state` to the balances cache if `block_root` is the first block in
+    /// the epoch of `state`, where `block_root` is the root of the latest block in `state`.
+    ///
+    /// Unlike `process_state`, the balances are added regardless of whether `state` has a better
+    /// justified checkpoint.
+    pub fn warm_balances_cache<E: EthSpec>(
+        &mut self,
+        block_root: Hash256,
+        state: &BeaconState<E>,
+    ) -> Result<(), Error> {
+        self.balances_cache.process_state(block_root, state)
+    }
+
+    /// Returns `true` if the balances cache holds the balances for `block_root`.
+    pub fn balances_cache_contains(&self, block_root: Hash256) -> bool {
+        self.balances_cache.contains(block_root)
+    }
+
     /// Calculate how far `slot` lies from the start of its epoch.
     fn compute_slots_since_epoch_start<T: BeaconChainTypes>(slot: Slot) -> u64 {
         let slots_per_epoch = T::EthSpec::slots_per_epoch();
         (slot - slot.epoch(slots_per_epoch).start_slot(slots_per_epoch)).as_u64()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{test_utils::TestingBeaconStateBuilder, MinimalEthSpec};
+
+    #[test]
+    fn inserted_balances_are_taken_once() {
+        let spec = MinimalEthSpec::default_spec();
+        let (state, _) =
+            TestingBeaconStateBuilder::<MinimalEthSpec>::from_deterministic_keypairs(8, &spec)
+                .build();
+        let block_root = Hash256::from_low_u64_be(42);
+
+        let mut cache = BalancesCache::default();
+        cache
+            .insert(block_root, &state)
+            .expect("should insert balances");
+        cache
+            .insert(block_root, &state)
+            .expect("should ignore duplicate balances");
+
+        assert_eq!(
+            cache.get(block_root),
+            Some(get_effective_balances(&state)),
+            "should hit the cache"
+        );
+        assert_eq!(cache.get(block_root), None, "should remove the balances");
+    }
+}
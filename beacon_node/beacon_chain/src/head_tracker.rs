@@ -30,6 +30,16 @@ impl HeadTracker {
         map.insert(block_root, block.slot);
     }
 
+    /// Removes all heads at or prior to `finalized_slot`, except the finalized block itself.
+    ///
+    /// Such heads cannot descend from the finalized block, so they will never again become the
+    /// head of the chain. `finalized_slot` must be the slot of the block at `finalized_root`.
+    pub fn prune_below(&self, finalized_slot: Slot, finalized_root: Hash256) {
+        self.0
+            .write()
+            .retain(|root, slot| *slot > finalized_slot || *root == finalized_root);
+    }
+
     /// Returns the list of heads in the chain.
     pub fn heads(&self) -> Vec<(Hash256, Slot)> {
         self.0
@@ -149,6 +159,60 @@ mod test {
         );
     }
 
+    #[test]
+    fn prune_below() {
+        let spec = &E::default_spec();
+
+        let head_tracker = HeadTracker::default();
+
+        let register = |root: u64, parent_root: u64, slot: u64| {
+            let mut block = BeaconBlock::empty(spec);
+            block.slot = Slot::new(slot);
+            block.parent_root = Hash256::from_low_u64_be(parent_root);
+            head_tracker.register_block::<E>(Hash256::from_low_u64_be(root), &block);
+        };
+
+        // A chain 1 -> 2 -> 3 -> 4, with competing heads at slots 2 and 3 and a competing chain
+        // 5 -> 6 which reaches beyond the finalized slot.
+        register(1, 0, 1);
+        register(2, 1, 2);
+        register(3, 2, 3);
+        register(4, 3, 4);
+        register(10, 1, 2);
+        register(11, 2, 3);
+        register(5, 1, 2);
+        register(6, 5, 5);
+
+        assert_eq!(head_tracker.heads().len(), 4, "should have four heads");
+
+        head_tracker.prune_below(Slot::new(3), Hash256::from_low_u64_be(3));
+
+        let mut heads = head_tracker.heads();
+        heads.sort();
+
+        assert_eq!(
+            heads,
+            vec![
+                (Hash256::from_low_u64_be(4), Slot::new(4)),
+                (Hash256::from_low_u64_be(6), Slot::new(5)),
+            ],
+            "should only drop heads at or below the finalized slot"
+        );
+
+        let head_tracker = HeadTracker::default();
+        head_tracker
+            .0
+            .write()
+            .insert(Hash256::from_low_u64_be(3), Slot::new(3));
+        head_tracker.prune_below(Slot::new(3), Hash256::from_low_u64_be(3));
+
+        assert_eq!(
+            head_tracker.heads(),
+            vec![(Hash256::from_low_u64_be(3), Slot::new(3))],
+            "should not drop the finalized block"
+        );
+    }
+
     #[test]
     fn empty_round_trip() {
         let non_empty = HeadTracker::default();
@@ -0,0 +1,160 @@
+use ssz::Encode;
+use std::fs;
+use std::path::{Path, PathBuf};
+use types::{BeaconState, EthSpec, Hash256, SignedBeaconBlock};
+
+/// The default maximum number of files that may be present in the dump directory before no more
+/// dumps are written.
+pub const DEFAULT_MAX_INVALID_BLOCK_DUMP_FILES: usize = 32;
+
+/// The files written by `InvalidBlockDump::dump`.
+#[derive(Debug, PartialEq)]
+pub struct DumpedFiles {
+    pub block: PathBuf,
+    pub pre_state: PathBuf,
+}
+
+/// Writes blocks which fail processing, along with the state to which they were applied, to a
+/// directory so that consensus failures may be debugged after the fact.
+///
+/// Each dump consists of two SSZ files, named after the slot and root of the block (the name of the
+/// block file also includes the parent root):
+///
+/// - The `SignedBeaconBlock`.
+/// - The pre-state, which has been advanced to the slot of the block.
+///
+/// No dumps are written once the directory contains `max_files` files, to avoid filling the disk.
+pub struct InvalidBlockDump {
+    dir: PathBuf,
+    max_files: usize,
+}
+
+impl InvalidBlockDump {
+    /// Creates a dump which writes to `dir`, creating it if required.
+    pub fn new(dir: PathBuf, max_files: usize) -> Self {
+        Self { dir, max_files }
+    }
+
+    /// Returns the directory to which dumps are written.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes `block` (with root `block_root`) and its `pre_state` to the dump directory.
+    ///
+    /// Returns `Ok(None)` without writing any files if the block has already been dumped, or if
+    /// the directory contains too many files.
+    pub fn dump<E: EthSpec>(
+        &self,
+        block: &SignedBeaconBlock<E>,
+        block_root: Hash256,
+        pre_state: &BeaconState<E>,
+    ) -> Result<Option<DumpedFiles>, String> {
+        let slot = block.slot();
+        let files = DumpedFiles {
+            block: self.dir.join(format!(
+                "block_slot_{}_root_{:?}_parent_{:?}.ssz",
+                slot,
+                block_root,
+                block.parent_root()
+            )),
+            pre_state: self.dir.join(format!(
+                "pre_state_slot_{}_block_{:?}.ssz",
+                slot, block_root
+            )),
+        };
+
+        if files.block.exists() {
+            return Ok(None);
+        }
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Unable to create {:?}: {:?}", self.dir, e))?;
+
+        let file_count = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Unable to read {:?}: {:?}", self.dir, e))?
+            .count();
+        if file_count + 2 > self.max_files {
+            return Ok(None);
+        }
+
+        // The block is written last, since its presence indicates a complete dump.
+        write_file(&files.pre_state, &pre_state.as_ssz_bytes())?;
+        write_file(&files.block, &block.as_ssz_bytes())?;
+
+        Ok(Some(files))
+    }
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|e| format!("Unable to write {:?}: {:?}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use types::{
+        test_utils::{SeedableRng, TestRandom, XorShiftRng},
+        MinimalEthSpec, Slot,
+    };
+
+    type E = MinimalEthSpec;
+
+    fn block(slot: u64) -> SignedBeaconBlock<E> {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut block = SignedBeaconBlock::random_for_test(&mut rng);
+        block.message.slot = Slot::new(slot);
+        block
+    }
+
+    fn state() -> BeaconState<E> {
+        BeaconState::new(0, <_>::default(), &E::default_spec())
+    }
+
+    #[test]
+    fn writes_block_and_pre_state() {
+        let dir = tempdir().unwrap();
+        let dump = InvalidBlockDump::new(dir.path().join("dump"), 8);
+        let block = block(3);
+        let block_root = block.canonical_root();
+        let state = state();
+
+        let files = dump
+            .dump(&block, block_root, &state)
+            .expect("should dump")
+            .expect("should write files");
+
+        assert_eq!(
+            fs::read(&files.block).expect("should read block"),
+            block.as_ssz_bytes()
+        );
+        assert_eq!(
+            fs::read(&files.pre_state).expect("should read pre-state"),
+            state.as_ssz_bytes()
+        );
+        assert_eq!(
+            dump.dump(&block, block_root, &state),
+            Ok(None),
+            "a block should only be dumped once"
+        );
+    }
+
+    #[test]
+    fn stops_at_max_files() {
+        let dir = tempdir().unwrap();
+        let dump = InvalidBlockDump::new(dir.path().to_path_buf(), 5);
+        let state = state();
+
+        let written = (0..4)
+            .map(block)
+            .filter_map(|block| {
+                dump.dump(&block, block.canonical_root(), &state)
+                    .expect("should dump")
+            })
+            .count();
+
+        assert_eq!(written, 2, "only two dumps fit within five files");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 4);
+    }
+}
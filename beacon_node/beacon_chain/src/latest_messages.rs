@@ -0,0 +1,94 @@
+use crate::{BeaconChain, BeaconChainTypes};
+use serde_derive::{Deserialize, Serialize};
+use std::io::{self, Write};
+use types::{Epoch, Hash256};
+
+/// The default number of validators read from fork choice at a time when iterating the latest
+/// messages table.
+pub const DEFAULT_LATEST_MESSAGES_CHUNK_SIZE: usize = 8_192;
+
+/// The most recent vote of a validator, as known to fork choice.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LatestMessage {
+    pub validator_index: u64,
+    pub epoch: Epoch,
+    pub root: Hash256,
+}
+
+impl LatestMessage {
+    /// The header row of the CSV produced by `write_csv`.
+    pub const CSV_HEADER: &'static str = "validator_index,epoch,root";
+
+    /// Writes `messages` to `writer` as CSV, including the header row.
+    pub fn write_csv<W: Write>(
+        writer: &mut W,
+        messages: impl IntoIterator<Item = LatestMessage>,
+    ) -> io::Result<()> {
+        writeln!(writer, "{}", Self::CSV_HEADER)?;
+
+        for message in messages {
+            writeln!(
+                writer,
+                "{},{},{:?}",
+                message.validator_index, message.epoch, message.root
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<(usize, Hash256, Epoch)> for LatestMessage {
+    fn from((validator_index, root, epoch): (usize, Hash256, Epoch)) -> Self {
+        Self {
+            validator_index: validator_index as u64,
+            epoch,
+            root,
+        }
+    }
+}
+
+/// Iterates the latest messages of all validators, in order of validator index.
+///
+/// Only `chunk_size` validators are read from fork choice at a time, bounding memory usage for
+/// large validator counts. The fork choice lock is not held between chunks, so the returned
+/// messages are not guaranteed to be from a single point in time.
+pub struct LatestMessagesIter<'a, T: BeaconChainTypes> {
+    chain: &'a BeaconChain<T>,
+    next_index: usize,
+    chunk_size: usize,
+    chunk: std::vec::IntoIter<LatestMessage>,
+}
+
+impl<'a, T: BeaconChainTypes> LatestMessagesIter<'a, T> {
+    pub fn new(chain: &'a BeaconChain<T>, chunk_size: usize) -> Self {
+        Self {
+            chain,
+            next_index: 0,
+            chunk_size: std::cmp::max(chunk_size, 1),
+            chunk: vec![].into_iter(),
+        }
+    }
+}
+
+impl<'a, T: BeaconChainTypes> Iterator for LatestMessagesIter<'a, T> {
+    type Item = LatestMessage;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(message) = self.chunk.next() {
+                return Some(message);
+            }
+
+            if self.next_index >= self.chain.fork_choice.latest_messages_len() {
+                return None;
+            }
+
+            self.chunk = self
+                .chain
+                .latest_messages(self.next_index, self.chunk_size)
+                .into_iter();
+            self.next_index += self.chunk_size;
+        }
+    }
+}
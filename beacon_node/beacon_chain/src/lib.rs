@@ -2,36 +2,70 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod admin_history;
 mod beacon_chain;
+mod block_verification;
 pub mod builder;
+mod chain_snapshot;
+mod chain_stats;
 mod checkpoint;
 mod errors;
 pub mod eth1_chain;
 pub mod events;
+mod finalization_info_cache;
 mod fork_choice;
 mod head_tracker;
+mod invalid_block_dump;
+pub mod latest_messages;
+mod local_attestations;
 mod metrics;
+mod observed_attestations;
+mod observed_attester_votes;
+mod observed_block_proposers;
+mod op_pool_persistence;
 mod persisted_beacon_chain;
+mod produced_block_cache;
+mod readiness;
+pub mod schema_change;
 mod shuffling_cache;
+mod state_skip_cache;
 pub mod test_utils;
 mod timeout_rw_lock;
+mod validator_monitor;
 mod validator_pubkey_cache;
 
+pub use self::admin_history::{AdminEvent, AdminHistory};
 pub use self::beacon_chain::{
-    AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, BlockProcessingOutcome,
-    StateSkipConfig,
+    AggregateProcessingOutcome, AttestationProcessingOutcome, AttestationRewardEstimate,
+    AttesterDuties, BeaconChain, BeaconChainMode, BeaconChainTypes, BlockProcessingOutcome,
+    BlockRange, FinalizationInfo, HeadDetail, ProposerDuties, StateSkipConfig,
+    DEFAULT_MAX_BLOCK_RANGE, MAX_BALANCE_HISTORY_EPOCHS,
 };
+pub use self::block_verification::{
+    GossipBlockError, GossipVerifiedBlock, IntoBlockForImport, MAXIMUM_GOSSIP_CLOCK_DISPARITY,
+};
+pub use self::chain_snapshot::SnapshotRecord;
+pub use self::chain_stats::ChainStatsSnapshot;
 pub use self::checkpoint::CheckPoint;
-pub use self::errors::{BeaconChainError, BlockProductionError};
+pub use self::errors::{
+    BeaconChainError, BlockProductionError, ErrorCategory, OperationImportError,
+};
 pub use eth1_chain::{Eth1Chain, Eth1ChainBackend};
 pub use events::EventHandler;
 pub use fork_choice::ForkChoice;
+pub use invalid_block_dump::DEFAULT_MAX_INVALID_BLOCK_DUMP_FILES;
+pub use local_attestations::LocalAttestationCorrectness;
 pub use metrics::scrape_for_metrics;
+pub use op_pool_persistence::OP_POOL_DELTAS_PER_SNAPSHOT;
 pub use parking_lot;
+pub use proto_array_fork_choice::ForkChoiceNode;
+pub use readiness::{DegradedReason, Readiness, ReadinessConfig, SyncStatus};
 pub use slot_clock;
 pub use state_processing::per_block_processing::errors::{
     AttestationValidationError, AttesterSlashingValidationError, DepositValidationError,
     ExitValidationError, ProposerSlashingValidationError,
 };
 pub use store;
+pub use timeout_rw_lock::LockTimeouts;
 pub use types;
+pub use validator_monitor::{ValidatorMonitor, SUMMARY_DELAY_EPOCHS};
@@ -0,0 +1,109 @@
+use parking_lot::Mutex;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use types::{AttestationData, BeaconState, Epoch, EthSpec};
+
+/// The number of evaluated epochs to retain.
+const MAX_RETAINED_EPOCHS: usize = 64;
+
+/// The correctness of the attestations produced by this node during a single epoch, as judged by
+/// the canonical chain once the epoch has settled.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LocalAttestationCorrectness {
+    pub epoch: Epoch,
+    /// The number of distinct `AttestationData` produced during the epoch.
+    pub produced: u64,
+    /// Produced attestations whose target matched the canonical target.
+    pub correct_target: u64,
+    /// Produced attestations whose head matched the canonical block at their slot.
+    pub correct_head: u64,
+}
+
+/// Records the `AttestationData` produced by this node and, once the canonical chain has progressed
+/// past the end of the following epoch, determines whether each attestation voted for the canonical
+/// target and head.
+///
+/// Since all committees at a slot share the same data (aside from the index), attestations are
+/// deduplicated by their data with an index of `0`. This prevents the number of validators attached
+/// to the node from skewing the results.
+#[derive(Default)]
+pub struct LocalAttestations {
+    produced: Mutex<BTreeMap<Epoch, HashSet<AttestationData>>>,
+    evaluated: Mutex<VecDeque<LocalAttestationCorrectness>>,
+}
+
+impl LocalAttestations {
+    /// Records that an attestation with the given `data` was produced.
+    pub fn observe_production<E: EthSpec>(&self, data: &AttestationData) {
+        let data = AttestationData {
+            index: 0,
+            ..data.clone()
+        };
+
+        self.produced
+            .lock()
+            .entry(data.slot.epoch(E::slots_per_epoch()))
+            .or_insert_with(HashSet::new)
+            .insert(data);
+    }
+
+    /// Evaluates all produced attestations from epochs that are at least two epochs prior to the
+    /// epoch of `head_state` (the state of the canonical head).
+    ///
+    /// Returns the results of any newly evaluated epochs. Attestations that are too old to be
+    /// evaluated against `head_state` are discarded.
+    pub fn evaluate<E: EthSpec>(
+        &self,
+        head_state: &BeaconState<E>,
+    ) -> Vec<LocalAttestationCorrectness> {
+        let current_epoch = head_state.current_epoch();
+
+        let settled = {
+            let mut produced = self.produced.lock();
+            let unsettled = produced.split_off(&current_epoch.saturating_sub(1u64));
+            std::mem::replace(&mut *produced, unsettled)
+        };
+
+        let results = settled
+            .into_iter()
+            .filter_map(|(epoch, attestations)| {
+                let target_slot = epoch.start_slot(E::slots_per_epoch());
+                let target_root = *head_state.get_block_root(target_slot).ok()?;
+
+                let mut result = LocalAttestationCorrectness {
+                    epoch,
+                    produced: 0,
+                    correct_target: 0,
+                    correct_head: 0,
+                };
+
+                for data in attestations {
+                    let head_root = *head_state.get_block_root(data.slot).ok()?;
+
+                    result.produced += 1;
+                    if data.target.root == target_root {
+                        result.correct_target += 1;
+                    }
+                    if data.beacon_block_root == head_root {
+                        result.correct_head += 1;
+                    }
+                }
+
+                Some(result)
+            })
+            .collect::<Vec<_>>();
+
+        let mut evaluated = self.evaluated.lock();
+        evaluated.extend(results.iter().cloned());
+        while evaluated.len() > MAX_RETAINED_EPOCHS {
+            evaluated.pop_front();
+        }
+
+        results
+    }
+
+    /// Returns the results of the most recently evaluated epochs, oldest first.
+    pub fn evaluated(&self) -> Vec<LocalAttestationCorrectness> {
+        self.evaluated.lock().iter().cloned().collect()
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{BeaconChain, BeaconChainTypes};
 pub use lighthouse_metrics::*;
-use types::{BeaconState, Epoch, Hash256, Slot};
+use store::Migrate;
+use types::{BeaconState, ChainSpec, Epoch, Hash256, Slot};
 
 lazy_static! {
     /*
@@ -14,6 +15,10 @@ lazy_static! {
         "beacon_block_processing_successes_total",
         "Count of blocks processed without error"
     );
+    pub static ref BLOCK_PROCESSING_FUTURE_SLOT: Result<IntCounter> = try_create_int_counter(
+        "beacon_block_processing_future_slot_total",
+        "Count of blocks rejected because their slot is later than the present slot"
+    );
     pub static ref BLOCK_PROCESSING_TIMES: Result<Histogram> =
         try_create_histogram("beacon_block_processing_seconds", "Full runtime of block processing");
     pub static ref BLOCK_PROCESSING_BLOCK_ROOT: Result<Histogram> = try_create_histogram(
@@ -48,6 +53,26 @@ lazy_static! {
         "beacon_block_processing_fork_choice_register_seconds",
         "Time spent registering the new block with fork choice (but not finding head)"
     );
+    pub static ref INVALID_BLOCK_DUMPS: Result<IntCounter> = try_create_int_counter(
+        "beacon_invalid_block_dumps_total",
+        "Count of invalid blocks written to the dump directory"
+    );
+
+    /*
+     * Block Gossip Verification
+     */
+    pub static ref BLOCK_GOSSIP_VERIFICATION_REQUESTS: Result<IntCounter> = try_create_int_counter(
+        "beacon_block_gossip_verification_requests_total",
+        "Count of blocks submitted for gossip verification"
+    );
+    pub static ref BLOCK_GOSSIP_VERIFICATION_SUCCESSES: Result<IntCounter> = try_create_int_counter(
+        "beacon_block_gossip_verification_successes_total",
+        "Count of blocks that passed gossip verification"
+    );
+    pub static ref BLOCK_GOSSIP_VERIFICATION_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_block_gossip_verification_seconds",
+        "Full runtime of block gossip verification"
+    );
 
     /*
      * Block Production
@@ -60,8 +85,36 @@ lazy_static! {
         "beacon_block_production_successes_total",
         "Count of blocks successfully produced."
     );
+    pub static ref BLOCK_PRODUCTION_INVALID_DEPOSITS: Result<IntCounter> = try_create_int_counter(
+        "beacon_block_production_invalid_deposits_total",
+        "Count of times the deposits from the eth1 cache failed verification during block production"
+    );
     pub static ref BLOCK_PRODUCTION_TIMES: Result<Histogram> =
         try_create_histogram("beacon_block_production_seconds", "Full runtime of block production");
+    pub static ref BLOCK_PRODUCTION_STATE_ADVANCE: Result<Histogram> = try_create_histogram(
+        "beacon_block_production_state_advance_seconds",
+        "Time spent skipping slots on a state before producing a block."
+    );
+    pub static ref BLOCK_PRODUCTION_ETH1_DATA: Result<Histogram> = try_create_histogram(
+        "beacon_block_production_eth1_data_seconds",
+        "Time spent selecting the eth1 data and deposits for a produced block."
+    );
+    pub static ref BLOCK_PRODUCTION_ATTESTATIONS: Result<Histogram> = try_create_histogram(
+        "beacon_block_production_attestations_seconds",
+        "Time spent packing attestations from the op pool into a produced block."
+    );
+    pub static ref BLOCK_PRODUCTION_SLASHINGS_AND_EXITS: Result<Histogram> = try_create_histogram(
+        "beacon_block_production_slashings_and_exits_seconds",
+        "Time spent packing slashings and voluntary exits from the op pool into a produced block."
+    );
+    pub static ref BLOCK_PRODUCTION_CORE: Result<Histogram> = try_create_histogram(
+        "beacon_block_production_core_seconds",
+        "Time spent doing the core per_block_processing state processing on a produced block."
+    );
+    pub static ref BLOCK_PRODUCTION_STATE_ROOT: Result<Histogram> = try_create_histogram(
+        "beacon_block_production_state_root_seconds",
+        "Time spent calculating the state root of a produced block."
+    );
 
     /*
      * Block Statistics
@@ -71,6 +124,22 @@ lazy_static! {
         "Number of attestations in a block"
     );
 
+    /*
+     * Tree Hash Cache
+     */
+    pub static ref TREE_HASH_CACHE_UPDATE_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_tree_hash_cache_update_seconds",
+        "Time spent updating the tree hash cache of a state to obtain its root"
+    );
+    pub static ref TREE_HASH_CACHE_HITS: Result<IntCounter> = try_create_int_counter(
+        "beacon_tree_hash_cache_hits_total",
+        "Count of state root computations that reused an existing tree hash cache"
+    );
+    pub static ref TREE_HASH_CACHE_MISSES: Result<IntCounter> = try_create_int_counter(
+        "beacon_tree_hash_cache_misses_total",
+        "Count of state root computations that had to build a tree hash cache from scratch"
+    );
+
     /*
      * Attestation Processing
      */
@@ -138,6 +207,14 @@ lazy_static! {
         "beacon_attestation_production_seconds",
         "Full runtime of attestation production"
     );
+    pub static ref ATTESTATION_PRODUCTION_SOURCE_FROM_HEAD: Result<IntCounter> = try_create_int_counter(
+        "beacon_attestation_production_source_from_head_total",
+        "Count of attestations where the lagging state source was replaced by the head justified checkpoint"
+    );
+    pub static ref ATTESTATION_PRODUCTION_SOURCE_MISMATCH: Result<IntCounter> = try_create_int_counter(
+        "beacon_attestation_production_source_mismatch_total",
+        "Count of attestations produced with a source that differs from the head justified checkpoint"
+    );
 }
 
 // Second lazy-static block is used to account for macro recursion limit.
@@ -165,6 +242,11 @@ lazy_static! {
         try_create_histogram("beacon_fork_choice_seconds", "Full runtime of fork choice");
     pub static ref FORK_CHOICE_FIND_HEAD_TIMES: Result<Histogram> =
         try_create_histogram("beacon_fork_choice_find_head_seconds", "Full runtime of fork choice find_head function");
+    pub static ref FORK_CHOICE_FIND_HEAD_NODES: Result<Histogram> = try_create_histogram_with_buckets(
+        "beacon_fork_choice_find_head_nodes",
+        "Number of blocks in fork choice (each of which is visited) when finding the head",
+        vec![16.0, 64.0, 256.0, 1_024.0, 4_096.0, 16_384.0, 65_536.0]
+    );
     pub static ref FORK_CHOICE_PROCESS_BLOCK_TIMES: Result<Histogram> = try_create_histogram(
         "beacon_fork_choice_process_block_seconds",
         "Time taken to add a block and all attestations to fork choice"
@@ -189,18 +271,140 @@ lazy_static! {
         try_create_histogram("beacon_persist_eth1_cache", "Time taken to persist the eth1 caches");
     pub static ref PERSIST_FORK_CHOICE: Result<Histogram> =
         try_create_histogram("beacon_persist_fork_choice", "Time taken to persist the fork choice struct");
+    pub static ref FORK_CHOICE_PERSISTED_BYTES: Result<IntGauge> = try_create_int_gauge(
+        "beacon_fork_choice_persisted_bytes",
+        "Size of the fork choice struct when it was last persisted"
+    );
+    pub static ref PERSISTED_CONTAINER_DECODE_FAILURES: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_persisted_container_decode_failures_total",
+            "Count of persisted containers that failed to decode at startup and were discarded",
+            &["container"]
+        );
 
     /*
      * Eth1
      */
+    pub static ref MAJORITY_ETH1_VOTES: Result<IntCounter> =
+        try_create_int_counter("beacon_eth1_majority_votes", "Count of times we have voted for the candidate eth1 data with the most votes");
+    pub static ref NEW_CANDIDATE_ETH1_VOTES: Result<IntCounter> =
+        try_create_int_counter("beacon_eth1_new_candidate_votes", "Count of times we have voted for the latest candidate eth1 data, as no candidate had any votes");
     pub static ref DEFAULT_ETH1_VOTES: Result<IntCounter> =
         try_create_int_counter("beacon_eth1_default_votes", "Count of times we have voted default value for eth1 data");
 
+    /*
+     * Local Attestation Correctness
+     */
+    pub static ref LOCAL_ATTESTATIONS_EVALUATED: Result<IntCounter> = try_create_int_counter(
+        "beacon_local_attestations_evaluated_total",
+        "Count of distinct attestations produced by this node that have been compared to the canonical chain"
+    );
+    pub static ref LOCAL_ATTESTATIONS_CORRECT_TARGET: Result<IntCounter> = try_create_int_counter(
+        "beacon_local_attestations_correct_target_total",
+        "Count of evaluated attestations produced by this node with the canonical target"
+    );
+    pub static ref LOCAL_ATTESTATIONS_CORRECT_HEAD: Result<IntCounter> = try_create_int_counter(
+        "beacon_local_attestations_correct_head_total",
+        "Count of evaluated attestations produced by this node with the canonical head"
+    );
+
+    /*
+     * Slashing Detection
+     */
+    pub static ref ATTESTER_SLASHINGS_DETECTED: Result<IntCounter> = try_create_int_counter(
+        "beacon_attester_slashings_detected_total",
+        "Count of attester slashings constructed from conflicting attestations seen by this node"
+    );
+    pub static ref PROPOSER_SLASHINGS_DETECTED: Result<IntCounter> = try_create_int_counter(
+        "beacon_proposer_slashings_detected_total",
+        "Count of proposer slashings constructed from conflicting blocks seen by this node"
+    );
+
+    /*
+     * Validator Monitor
+     */
+    pub static ref VALIDATOR_MONITOR_BEACON_BLOCKS_TOTAL: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_validator_monitor_beacon_blocks_total",
+            "Count of imported blocks proposed by each monitored validator",
+            &["validator"]
+        );
+    pub static ref VALIDATOR_MONITOR_ATTESTATIONS_INCLUDED_TOTAL: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_validator_monitor_attestations_included_total",
+            "Count of epochs for which an attestation from each monitored validator was included in an imported block",
+            &["validator"]
+        );
+    pub static ref VALIDATOR_MONITOR_ATTESTATION_INCLUSION_DISTANCE: Result<IntGaugeVec> =
+        try_create_int_gauge_vec(
+            "beacon_validator_monitor_attestation_inclusion_distance",
+            "Inclusion distance of the most recently included attestation from each monitored validator",
+            &["validator"]
+        );
+    pub static ref VALIDATOR_MONITOR_ATTESTATIONS_MISSED_TOTAL: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_validator_monitor_attestations_missed_total",
+            "Count of epochs for which no attestation from each active monitored validator was included",
+            &["validator"]
+        );
+    pub static ref VALIDATOR_MONITOR_ATTESTATION_CORRECT_TARGET_TOTAL: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_validator_monitor_attestation_correct_target_total",
+            "Count of included attestations from each monitored validator with the correct target",
+            &["validator"]
+        );
+    pub static ref VALIDATOR_MONITOR_ATTESTATION_CORRECT_HEAD_TOTAL: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_validator_monitor_attestation_correct_head_total",
+            "Count of included attestations from each monitored validator with the correct head",
+            &["validator"]
+        );
+
+    /*
+     * Events
+     */
+    pub static ref EVENT_STREAM_LAGGED: Result<IntCounter> = try_create_int_counter(
+        "beacon_event_stream_lagged_total",
+        "Count of events dropped because an event stream subscriber was not keeping up"
+    );
+
     /*
      * Chain Head
      */
     pub static ref UPDATE_HEAD_TIMES: Result<Histogram> =
         try_create_histogram("beacon_update_head_seconds", "Time taken to update the canonical head");
+    pub static ref HEAD_IN_FUTURE_SLOTS: Result<IntGauge> =
+        try_create_int_gauge("beacon_head_in_future_slots", "Number of slots by which the head is ahead of the slot clock");
+    pub static ref OLDEST_HOT_STATE_SLOT: Result<IntGauge> =
+        try_create_int_gauge("beacon_oldest_hot_state_slot", "Slot of the oldest state stored in the hot database");
+
+    /*
+     * Locks
+     */
+    pub static ref LOCK_WAIT_TIMES: Result<HistogramVec> = try_create_histogram_vec(
+        "beacon_lock_wait_seconds",
+        "Time spent waiting to take each lock, including waits that timed out",
+        &["lock"]
+    );
+    pub static ref LOCK_TIMEOUTS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "beacon_lock_timeouts_total",
+        "Count of times each lock timed out",
+        &["lock"]
+    );
+    // The following counters are superseded by `LOCK_TIMEOUTS`, but are kept for existing
+    // dashboards.
+    pub static ref HEAD_LOCK_TIMEOUTS: Result<IntCounter> = try_create_int_counter(
+        "beacon_head_lock_timeouts_total",
+        "Count of times a lock on the canonical head timed out"
+    );
+    pub static ref ATTESTATION_CACHE_LOCK_TIMEOUTS: Result<IntCounter> = try_create_int_counter(
+        "beacon_attestation_cache_lock_timeouts_total",
+        "Count of times a lock on the attestation shuffling cache timed out"
+    );
+    pub static ref VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUTS: Result<IntCounter> = try_create_int_counter(
+        "beacon_validator_pubkey_cache_lock_timeouts_total",
+        "Count of times a lock on the validator pubkey cache timed out"
+    );
     pub static ref HEAD_STATE_SLOT: Result<IntGauge> =
         try_create_int_gauge("beacon_head_state_slot", "Slot of the block at the head of the chain");
     pub static ref HEAD_STATE_ROOT: Result<IntGauge> =
@@ -231,6 +435,12 @@ lazy_static! {
         try_create_int_gauge("beacon_head_state_withdrawn_validators_total", "Sum of all validator balances at the head of the chain");
     pub static ref HEAD_STATE_ETH1_DEPOSIT_INDEX: Result<IntGauge> =
         try_create_int_gauge("beacon_head_state_eth1_deposit_index", "Eth1 deposit index at the head of the chain");
+    pub static ref HEAD_STATE_ACTIVATION_QUEUE_VALIDATORS: Result<IntGauge> =
+        try_create_int_gauge("beacon_head_state_activation_queue_validators_total", "Count of validators eligible for activation but not yet active at the head of the chain");
+    pub static ref HEAD_STATE_EXIT_QUEUE_VALIDATORS: Result<IntGauge> =
+        try_create_int_gauge("beacon_head_state_exit_queue_validators_total", "Count of validators with a scheduled exit that have not yet exited at the head of the chain");
+    pub static ref HEAD_STATE_CHURN_LIMIT: Result<IntGauge> =
+        try_create_int_gauge("beacon_head_state_churn_limit", "Maximum number of validators that may be activated or exited per epoch at the head of the chain");
 
     /*
      * Operation Pool
@@ -243,13 +453,41 @@ lazy_static! {
         try_create_int_gauge("beacon_op_pool_proposer_slashings_total", "Count of proposer slashings in the op pool");
     pub static ref OP_POOL_NUM_VOLUNTARY_EXITS: Result<IntGauge> =
         try_create_int_gauge("beacon_op_pool_voluntary_exits_total", "Count of voluntary exits in the op pool");
+    pub static ref OP_POOL_PRUNED_ATTESTATIONS: Result<IntGauge> =
+        try_create_int_gauge("beacon_op_pool_pruned_attestations", "Count of attestations removed from the op pool by the most recent per-epoch pruning pass");
+    pub static ref OP_POOL_PRUNED_VOLUNTARY_EXITS: Result<IntGauge> =
+        try_create_int_gauge("beacon_op_pool_pruned_voluntary_exits", "Count of voluntary exits removed from the op pool by the most recent per-epoch pruning pass");
+    pub static ref OP_POOL_PRUNED_SLASHINGS: Result<IntGauge> =
+        try_create_int_gauge("beacon_op_pool_pruned_slashings", "Count of slashings removed from the op pool by the most recent per-epoch pruning pass");
+    pub static ref OP_POOL_ATTESTATION_INSERTIONS: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "beacon_op_pool_attestation_insertions_total",
+            "Count of attestations inserted into the op pool, by their effect on the pool",
+            &["observation"]
+        );
+    pub static ref OP_POOL_ATTESTATIONS_BEFORE_INSERTION: Result<Histogram> =
+        try_create_histogram("beacon_op_pool_attestations_before_insertion", "Count of attestations in the op pool before an attestation is inserted");
+    pub static ref OP_POOL_ATTESTATIONS_AFTER_INSERTION: Result<Histogram> =
+        try_create_histogram("beacon_op_pool_attestations_after_insertion", "Count of attestations in the op pool after an attestation is inserted");
 }
 
 /// Scrape the `beacon_chain` for metrics that are not constantly updated (e.g., the present slot,
 /// head state info, etc) and update the Prometheus `DEFAULT_REGISTRY`.
 pub fn scrape_for_metrics<T: BeaconChainTypes>(beacon_chain: &BeaconChain<T>) {
     if let Ok(head) = beacon_chain.head() {
-        scrape_head_state::<T>(&head.beacon_state, head.beacon_state_root)
+        scrape_head_state::<T>(
+            &head.beacon_state,
+            head.beacon_state_root,
+            &beacon_chain.spec,
+        )
+    }
+
+    if let Ok(slots) = beacon_chain.head_in_future_slots() {
+        set_gauge_by_u64(&HEAD_IN_FUTURE_SLOTS, slots);
+    }
+
+    if let Some(slot) = beacon_chain.store_migrator.oldest_hot_state_slot() {
+        set_gauge_by_slot(&OLDEST_HOT_STATE_SLOT, slot);
     }
 
     set_gauge_by_usize(
@@ -271,7 +509,18 @@ pub fn scrape_for_metrics<T: BeaconChainTypes>(beacon_chain: &BeaconChain<T>) {
 }
 
 /// Scrape the given `state` assuming it's the head state, updating the `DEFAULT_REGISTRY`.
-fn scrape_head_state<T: BeaconChainTypes>(state: &BeaconState<T::EthSpec>, state_root: Hash256) {
+fn scrape_head_state<T: BeaconChainTypes>(
+    state: &BeaconState<T::EthSpec>,
+    state_root: Hash256,
+    spec: &ChainSpec,
+) {
+    let current_epoch = state.current_epoch();
+    let active_validators = state
+        .validators
+        .iter()
+        .filter(|v| v.is_active_at(current_epoch))
+        .count();
+
     set_gauge_by_slot(&HEAD_STATE_SLOT, state.slot);
     set_gauge_by_hash(&HEAD_STATE_ROOT, state_root);
     set_gauge_by_slot(
@@ -301,27 +550,48 @@ fn scrape_head_state<T: BeaconChainTypes>(state: &BeaconState<T::EthSpec>, state
     );
     set_gauge_by_usize(&HEAD_STATE_TOTAL_VALIDATORS, state.validators.len());
     set_gauge_by_u64(&HEAD_STATE_VALIDATOR_BALANCES, state.balances.iter().sum());
+    set_gauge_by_usize(&HEAD_STATE_ACTIVE_VALIDATORS, active_validators);
+    set_gauge_by_usize(
+        &HEAD_STATE_SLASHED_VALIDATORS,
+        state.validators.iter().filter(|v| v.slashed).count(),
+    );
     set_gauge_by_usize(
-        &HEAD_STATE_ACTIVE_VALIDATORS,
+        &HEAD_STATE_WITHDRAWN_VALIDATORS,
         state
             .validators
             .iter()
-            .filter(|v| v.is_active_at(state.current_epoch()))
+            .filter(|v| v.is_withdrawable_at(current_epoch))
             .count(),
     );
+    set_gauge_by_u64(&HEAD_STATE_ETH1_DEPOSIT_INDEX, state.eth1_deposit_index);
     set_gauge_by_usize(
-        &HEAD_STATE_SLASHED_VALIDATORS,
-        state.validators.iter().filter(|v| v.slashed).count(),
+        &HEAD_STATE_ACTIVATION_QUEUE_VALIDATORS,
+        state
+            .validators
+            .iter()
+            .filter(|v| {
+                v.activation_eligibility_epoch != spec.far_future_epoch
+                    && v.activation_epoch > current_epoch
+            })
+            .count(),
     );
     set_gauge_by_usize(
-        &HEAD_STATE_WITHDRAWN_VALIDATORS,
+        &HEAD_STATE_EXIT_QUEUE_VALIDATORS,
         state
             .validators
             .iter()
-            .filter(|v| v.is_withdrawable_at(state.current_epoch()))
+            .filter(|v| v.exit_epoch != spec.far_future_epoch && v.exit_epoch > current_epoch)
             .count(),
     );
-    set_gauge_by_u64(&HEAD_STATE_ETH1_DEPOSIT_INDEX, state.eth1_deposit_index);
+    // Computed from the active validators rather than `BeaconState::get_churn_limit`, which
+    // requires the committee cache.
+    set_gauge_by_u64(
+        &HEAD_STATE_CHURN_LIMIT,
+        std::cmp::max(
+            spec.min_per_epoch_churn_limit,
+            active_validators as u64 / spec.churn_limit_quotient,
+        ),
+    );
 }
 
 fn set_gauge_by_slot(gauge: &Result<IntGauge>, value: Slot) {
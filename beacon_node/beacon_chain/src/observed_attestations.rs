@@ -0,0 +1,155 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use types::{Attestation, BitList, Epoch, EthSpec, Hash256};
+
+/// The maximum number of aggregation bitfields stored for a single epoch.
+///
+/// Once reached, further attestations are not recorded until the epoch changes, so they are
+/// verified as usual rather than being dropped as known.
+pub const DEFAULT_MAX_OBSERVED_PER_EPOCH: usize = 1 << 16;
+
+/// The aggregation bits of the attestations that have been processed, keyed by the root of their
+/// `AttestationData`.
+struct EpochAttestations<E: EthSpec> {
+    epoch: Epoch,
+    items: HashMap<Hash256, Vec<BitList<E::MaxValidatorsPerCommittee>>>,
+    /// The total number of bitfields in `items`.
+    len: usize,
+}
+
+/// Stores the attestations that have been successfully processed during the current epoch, so
+/// that an attestation which provides no new information may be dropped before its signature is
+/// verified.
+///
+/// All attestations are forgotten when the current epoch changes. At most `max_per_epoch`
+/// bitfields are stored for an epoch.
+pub struct ObservedAttestations<E: EthSpec> {
+    attestations: Mutex<EpochAttestations<E>>,
+    max_per_epoch: usize,
+}
+
+impl<E: EthSpec> Default for ObservedAttestations<E> {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_OBSERVED_PER_EPOCH)
+    }
+}
+
+impl<E: EthSpec> ObservedAttestations<E> {
+    /// Creates an empty store which records at most `max_per_epoch` bitfields per epoch.
+    pub fn new(max_per_epoch: usize) -> Self {
+        Self {
+            attestations: Mutex::new(EpochAttestations {
+                epoch: Epoch::new(0),
+                items: HashMap::new(),
+                len: 0,
+            }),
+            max_per_epoch,
+        }
+    }
+
+    /// Returns `true` if an attestation with the same `data_root` and a superset of the
+    /// aggregation bits of `attestation` has been observed during `current_epoch`.
+    pub fn is_known(
+        &self,
+        attestation: &Attestation<E>,
+        data_root: Hash256,
+        current_epoch: Epoch,
+    ) -> bool {
+        let mut attestations = self.attestations.lock();
+        attestations.prune(current_epoch);
+
+        attestations
+            .items
+            .get(&data_root)
+            .map_or(false, |observed| {
+                observed.iter().any(|bits| {
+                    bits.len() == attestation.aggregation_bits.len()
+                        && bits.intersection(&attestation.aggregation_bits)
+                            == attestation.aggregation_bits
+                })
+            })
+    }
+
+    /// Records that `attestation`, with the given `data_root`, has been processed during
+    /// `current_epoch`.
+    ///
+    /// Any previously observed attestation whose aggregation bits are a subset of those of
+    /// `attestation` is dropped, since it is now redundant. Nothing is recorded if the limit of
+    /// bitfields for the epoch has been reached.
+    pub fn observe(&self, attestation: &Attestation<E>, data_root: Hash256, current_epoch: Epoch) {
+        let mut attestations = self.attestations.lock();
+        attestations.prune(current_epoch);
+
+        let new_bits = &attestation.aggregation_bits;
+        let EpochAttestations { items, len, .. } = &mut *attestations;
+        let observed = items.entry(data_root).or_insert_with(Vec::new);
+
+        let prev_len = observed.len();
+        observed
+            .retain(|bits| bits.len() != new_bits.len() || bits.intersection(new_bits) != *bits);
+        *len -= prev_len - observed.len();
+
+        if *len < self.max_per_epoch {
+            observed.push(new_bits.clone());
+            *len += 1;
+        }
+    }
+}
+
+impl<E: EthSpec> EpochAttestations<E> {
+    /// Forgets all observed attestations if `current_epoch` differs from the epoch in which they
+    /// were observed.
+    fn prune(&mut self, current_epoch: Epoch) {
+        if self.epoch != current_epoch {
+            self.epoch = current_epoch;
+            self.items.clear();
+            self.len = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{
+        test_utils::{SeedableRng, TestRandom, XorShiftRng},
+        MinimalEthSpec,
+    };
+
+    type E = MinimalEthSpec;
+
+    fn attestation(rng: &mut XorShiftRng, bit: usize) -> Attestation<E> {
+        let mut attestation = Attestation::random_for_test(rng);
+        attestation.aggregation_bits = BitList::with_capacity(8).unwrap();
+        attestation.aggregation_bits.set(bit, true).unwrap();
+        attestation
+    }
+
+    #[test]
+    fn stops_observing_at_limit_until_epoch_changes() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let observed = ObservedAttestations::new(2);
+        let epoch = Epoch::new(1);
+        let data_root = Hash256::from_low_u64_be(1);
+
+        let attestations = (0..3)
+            .map(|bit| attestation(&mut rng, bit))
+            .collect::<Vec<_>>();
+        for attestation in &attestations {
+            observed.observe(attestation, data_root, epoch);
+        }
+
+        assert!(observed.is_known(&attestations[0], data_root, epoch));
+        assert!(observed.is_known(&attestations[1], data_root, epoch));
+        assert!(
+            !observed.is_known(&attestations[2], data_root, epoch),
+            "should not record beyond the limit"
+        );
+
+        observed.observe(&attestations[2], data_root, epoch + 1);
+        assert!(
+            observed.is_known(&attestations[2], data_root, epoch + 1),
+            "should record again in a new epoch"
+        );
+    }
+}
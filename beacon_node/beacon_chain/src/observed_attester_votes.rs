@@ -0,0 +1,136 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use types::{AttestationData, AttesterSlashing, Epoch, EthSpec, IndexedAttestation};
+
+/// The number of epochs for which the votes of each validator are retained.
+///
+/// Attestations are only processed during their own epoch and the next, so this is sufficient to
+/// detect any double vote between processed attestations.
+pub const VOTE_HISTORY_EPOCHS: u64 = 2;
+
+/// The recent votes of each validator, keyed by validator index.
+struct EpochVotes<E: EthSpec> {
+    epoch: Epoch,
+    items: HashMap<u64, Vec<Arc<IndexedAttestation<E>>>>,
+}
+
+/// Stores the votes of each validator that have been observed in attestations with a valid
+/// signature, so that double votes and surround votes may be detected.
+///
+/// Only the first vote for each target epoch is stored and votes are forgotten once their target
+/// is `VOTE_HISTORY_EPOCHS` or more before the current epoch, so at most `VOTE_HISTORY_EPOCHS`
+/// votes are stored for each validator.
+pub struct ObservedAttesterVotes<E: EthSpec> {
+    votes: Mutex<EpochVotes<E>>,
+}
+
+impl<E: EthSpec> Default for ObservedAttesterVotes<E> {
+    fn default() -> Self {
+        Self {
+            votes: Mutex::new(EpochVotes {
+                epoch: Epoch::new(0),
+                items: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl<E: EthSpec> ObservedAttesterVotes<E> {
+    /// Records the votes of the validators in `indexed_attestation`, returning a slashing for
+    /// each previously observed attestation that conflicts with it.
+    ///
+    /// A vote that conflicts with a previous vote is not stored, so the history of each
+    /// validator remains free of slashable votes.
+    pub fn observe(
+        &self,
+        indexed_attestation: &IndexedAttestation<E>,
+        current_epoch: Epoch,
+    ) -> Vec<AttesterSlashing<E>> {
+        let mut votes = self.votes.lock();
+        votes.prune(current_epoch);
+
+        let new_vote = Arc::new(indexed_attestation.clone());
+        let mut conflicts: Vec<Arc<IndexedAttestation<E>>> = vec![];
+
+        for validator_index in new_vote.attesting_indices.iter() {
+            let validator_votes = votes.items.entry(*validator_index).or_insert_with(Vec::new);
+
+            let conflict = validator_votes
+                .iter()
+                .find(|previous_vote| is_slashable(&previous_vote.data, &new_vote.data))
+                .cloned();
+
+            if let Some(previous_vote) = conflict {
+                if !conflicts
+                    .iter()
+                    .any(|conflict| Arc::ptr_eq(conflict, &previous_vote))
+                {
+                    conflicts.push(previous_vote);
+                }
+            } else if validator_votes
+                .iter()
+                .all(|previous_vote| previous_vote.data.target.epoch != new_vote.data.target.epoch)
+            {
+                validator_votes.push(new_vote.clone());
+            }
+        }
+
+        conflicts
+            .into_iter()
+            .map(|previous_vote| {
+                // The spec requires that the first attestation is the one which surrounds the
+                // other, if either does.
+                let (attestation_1, attestation_2) = if new_vote.is_surround_vote(&previous_vote) {
+                    (new_vote.as_ref(), previous_vote.as_ref())
+                } else {
+                    (previous_vote.as_ref(), new_vote.as_ref())
+                };
+
+                AttesterSlashing {
+                    attestation_1: attestation_1.clone(),
+                    attestation_2: attestation_2.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns `true` if a vote for `data` by `validator_index` would conflict with any stored
+    /// vote of that validator. No votes are stored or pruned.
+    pub fn is_slashable(&self, validator_index: u64, data: &AttestationData) -> bool {
+        self.votes
+            .lock()
+            .items
+            .get(&validator_index)
+            .map_or(false, |validator_votes| {
+                validator_votes
+                    .iter()
+                    .any(|previous_vote| is_slashable(&previous_vote.data, data))
+            })
+    }
+}
+
+impl<E: EthSpec> EpochVotes<E> {
+    /// Forgets all votes with a target that is too old to be stored, if `current_epoch` differs
+    /// from the epoch of the last prune.
+    fn prune(&mut self, current_epoch: Epoch) {
+        if self.epoch != current_epoch {
+            self.epoch = current_epoch;
+            self.items.retain(|_, validator_votes| {
+                validator_votes
+                    .retain(|vote| vote.data.target.epoch + VOTE_HISTORY_EPOCHS > current_epoch);
+                !validator_votes.is_empty()
+            });
+        }
+    }
+}
+
+/// Returns `true` if `a` and `b` are a double vote or if either surrounds the other.
+fn is_slashable(a: &AttestationData, b: &AttestationData) -> bool {
+    let is_double_vote = a.target.epoch == b.target.epoch && a != b;
+    let is_surround_vote = |a: &AttestationData, b: &AttestationData| {
+        a.source.epoch < b.source.epoch && b.target.epoch < a.target.epoch
+    };
+
+    is_double_vote || is_surround_vote(a, b) || is_surround_vote(b, a)
+}
@@ -0,0 +1,52 @@
+use parking_lot::Mutex;
+use std::collections::hash_map::{Entry, HashMap};
+use types::{ProposerSlashing, SignedBeaconBlockHeader, Slot};
+
+/// Stores the header of the first block observed from each proposer at each slot, so that
+/// proposers who sign two different blocks for the same slot may be detected.
+///
+/// Headers are forgotten once their slot is finalized, since blocks from those slots are no longer
+/// imported.
+#[derive(Default)]
+pub struct ObservedBlockProposers {
+    headers: Mutex<HashMap<(u64, Slot), SignedBeaconBlockHeader>>,
+}
+
+impl ObservedBlockProposers {
+    /// Records `header`, which must carry a valid signature from `proposer_index`, returning a
+    /// slashing if the proposer has already signed a different header for the same slot.
+    pub fn observe(
+        &self,
+        proposer_index: u64,
+        header: SignedBeaconBlockHeader,
+    ) -> Option<ProposerSlashing> {
+        match self
+            .headers
+            .lock()
+            .entry((proposer_index, header.message.slot))
+        {
+            Entry::Occupied(entry) => {
+                if entry.get().message != header.message {
+                    Some(ProposerSlashing {
+                        proposer_index,
+                        signed_header_1: entry.get().clone(),
+                        signed_header_2: header,
+                    })
+                } else {
+                    None
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(header);
+                None
+            }
+        }
+    }
+
+    /// Forgets all headers with a slot that is equal to or prior to `finalized_slot`.
+    pub fn prune(&self, finalized_slot: Slot) {
+        self.headers
+            .lock()
+            .retain(|(_, slot), _| *slot > finalized_slot);
+    }
+}
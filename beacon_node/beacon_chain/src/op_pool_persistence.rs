@@ -0,0 +1,105 @@
+use crate::beacon_chain::OP_POOL_DB_KEY;
+use operation_pool::{OperationPool, PersistedOperationPool, PersistedOperationPoolDelta};
+use parking_lot::Mutex;
+use store::{Error as StoreError, Store};
+use types::{EthSpec, Hash256};
+
+/// The maximum number of deltas written after a snapshot of the op pool before the next snapshot
+/// is written in their place.
+pub const OP_POOL_DELTAS_PER_SNAPSHOT: u64 = 32;
+
+/// The key of the `index`th delta written since the last snapshot.
+pub fn op_pool_delta_key(index: u64) -> Hash256 {
+    Hash256::from_low_u64_be(index)
+}
+
+/// Persists an `OperationPool` as a full snapshot, followed by deltas which contain only the
+/// operations that changed since the previous persist.
+///
+/// The snapshot is stored in the `OpPool` column under `OP_POOL_DB_KEY` and the deltas in the
+/// `OpPoolDeltas` column (see `op_pool_delta_key`). Writing a snapshot removes all deltas, so the
+/// deltas in the database always apply to the snapshot that precedes them.
+pub struct OpPoolPersistence {
+    /// The number of deltas written since the last snapshot, or `None` if no snapshot has been
+    /// written by this instance.
+    num_deltas: Mutex<Option<u64>>,
+}
+
+/// Writes a snapshot upon the first persist, even if a snapshot and deltas already exist in the
+/// database, since they may not have been written by this instance.
+impl Default for OpPoolPersistence {
+    fn default() -> Self {
+        Self {
+            num_deltas: Mutex::new(None),
+        }
+    }
+}
+
+impl OpPoolPersistence {
+    /// Persists the changes to `op_pool` since the last persist as a delta, or a snapshot of the
+    /// whole pool if there is no snapshot or `OP_POOL_DELTAS_PER_SNAPSHOT` deltas follow it.
+    ///
+    /// No delta is written if the pool is unchanged.
+    pub fn persist<E: EthSpec, S: Store<E>>(
+        &self,
+        store: &S,
+        op_pool: &OperationPool<E>,
+    ) -> Result<(), StoreError> {
+        let mut num_deltas = self.num_deltas.lock();
+
+        match *num_deltas {
+            Some(index) if index < OP_POOL_DELTAS_PER_SNAPSHOT => {
+                let delta = PersistedOperationPoolDelta::from_operation_pool(op_pool);
+
+                if !delta.is_empty() {
+                    if let Err(e) = store.put(&op_pool_delta_key(index), &delta) {
+                        // The changes in the delta are lost, so only a snapshot may follow.
+                        *num_deltas = None;
+                        return Err(e);
+                    }
+                    *num_deltas = Some(index + 1);
+                }
+
+                Ok(())
+            }
+            _ => self.write_snapshot(&mut num_deltas, store, op_pool),
+        }
+    }
+
+    /// Persists a snapshot of the whole of `op_pool`, removing all deltas.
+    pub fn persist_snapshot<E: EthSpec, S: Store<E>>(
+        &self,
+        store: &S,
+        op_pool: &OperationPool<E>,
+    ) -> Result<(), StoreError> {
+        self.write_snapshot(&mut self.num_deltas.lock(), store, op_pool)
+    }
+
+    fn write_snapshot<E: EthSpec, S: Store<E>>(
+        &self,
+        num_deltas: &mut Option<u64>,
+        store: &S,
+        op_pool: &OperationPool<E>,
+    ) -> Result<(), StoreError> {
+        *num_deltas = None;
+
+        // Clear the changes first, so that any change which happens whilst the snapshot is being
+        // created is included in the next delta.
+        op_pool.clear_changes();
+        store.put(
+            &Hash256::from_slice(&OP_POOL_DB_KEY),
+            &PersistedOperationPool::from_operation_pool(op_pool),
+        )?;
+
+        // The deltas are removed after the snapshot is written, so that an interruption cannot
+        // leave the deltas without the snapshot to which they apply. Applying stale deltas to the
+        // newer snapshot may restore pruned operations, which are simply pruned again.
+        for index in 0..OP_POOL_DELTAS_PER_SNAPSHOT {
+            store.delete::<PersistedOperationPoolDelta<E>>(&op_pool_delta_key(index))?;
+        }
+
+        *num_deltas = Some(0);
+
+        Ok(())
+    }
+}
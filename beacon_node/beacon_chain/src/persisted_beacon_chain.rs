@@ -2,7 +2,7 @@ use crate::head_tracker::SszHeadTracker;
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
 use store::{DBColumn, Error as StoreError, SimpleStoreItem};
-use types::Hash256;
+use types::{Hash256, Slot};
 
 #[derive(Clone, Encode, Decode)]
 pub struct PersistedBeaconChain {
@@ -24,3 +24,28 @@ impl SimpleStoreItem for PersistedBeaconChain {
         Self::from_ssz_bytes(bytes).map_err(Into::into)
     }
 }
+
+/// The trusted checkpoint from which a chain was started by
+/// `BeaconChainBuilder::weak_subjectivity_state`, rather than from genesis.
+#[derive(Clone, Copy, Debug, PartialEq, Encode, Decode)]
+pub struct PersistedAnchor {
+    /// The slot of the anchor block. No block at or prior to this slot may be imported.
+    pub anchor_slot: Slot,
+    /// The root of the validators of the genesis state, which cannot be read from the anchor
+    /// state.
+    pub genesis_validators_root: Hash256,
+}
+
+impl SimpleStoreItem for PersistedAnchor {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconAnchor
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Self::from_ssz_bytes(bytes).map_err(Into::into)
+    }
+}
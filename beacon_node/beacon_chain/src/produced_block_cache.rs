@@ -0,0 +1,56 @@
+use parking_lot::Mutex;
+use types::{BeaconState, EthSpec, Hash256, Slot};
+
+/// A block produced by this node, along with the state that results from applying it to its
+/// parent.
+struct ProducedBlock<E: EthSpec> {
+    block_root: Hash256,
+    slot: Slot,
+    state: BeaconState<E>,
+}
+
+/// Stores the post-state of the most recent block produced by this node, so that the block can be
+/// imported without re-processing it once it has been signed and published (and returned to us
+/// via gossip or the HTTP API).
+///
+/// Only a single block is stored. It expires at the end of the slot following the block's slot.
+pub struct ProducedBlockCache<E: EthSpec> {
+    block: Mutex<Option<ProducedBlock<E>>>,
+}
+
+impl<E: EthSpec> Default for ProducedBlockCache<E> {
+    fn default() -> Self {
+        Self {
+            block: Mutex::new(None),
+        }
+    }
+}
+
+impl<E: EthSpec> ProducedBlockCache<E> {
+    /// Stores the `state` that results from applying the block with `block_root` at `slot`,
+    /// replacing any previously produced block.
+    pub fn insert(&self, block_root: Hash256, slot: Slot, state: BeaconState<E>) {
+        *self.block.lock() = Some(ProducedBlock {
+            block_root,
+            slot,
+            state,
+        });
+    }
+
+    /// Removes and returns the post-state of the block with `block_root`, if it was produced by
+    /// this node and has not expired at `current_slot`.
+    ///
+    /// A block with any other root leaves the stored block in place, unless it has expired.
+    pub fn take(&self, block_root: Hash256, current_slot: Slot) -> Option<BeaconState<E>> {
+        let mut block = self.block.lock();
+
+        match block.take() {
+            Some(produced) if produced.slot + 1 < current_slot => None,
+            Some(produced) if produced.block_root == block_root => Some(produced.state),
+            other => {
+                *block = other;
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use serde_derive::{Deserialize, Serialize};
+use std::time::Duration;
+use types::{Epoch, Slot};
+
+/// The thresholds beyond which a node is not considered ready to serve validator duties.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct ReadinessConfig {
+    /// The maximum number of slots by which the head may lag behind the slot clock whilst the node
+    /// is still considered synced. Defaults to one epoch on mainnet.
+    pub max_sync_distance: u64,
+    /// The maximum age of the latest block in the eth1 cache.
+    ///
+    /// The cache trails the eth1 head by the eth1 follow distance, so this must comfortably
+    /// exceed the time taken to produce that many eth1 blocks.
+    pub max_eth1_block_age: Duration,
+    /// If `true`, the node is reported as degraded whilst its caches are cold.
+    pub require_warm_caches: bool,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            max_sync_distance: 32,
+            max_eth1_block_age: Duration::from_secs(6 * 60 * 60),
+            require_warm_caches: true,
+        }
+    }
+}
+
+/// A reason why a synced node with fresh eth1 data may still serve duties slowly.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum DegradedReason {
+    /// The committee shuffling for the epoch of the head has not been cached, so the first
+    /// attestations of the epoch will be verified slowly.
+    ShufflingNotCached { epoch: Epoch },
+}
+
+/// Indicates whether a node is ready to serve validator duties.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Readiness {
+    Ready,
+    /// The head is `distance` slots behind the slot clock.
+    Syncing {
+        distance: u64,
+    },
+    /// The latest block in the eth1 cache is `age` old, or the cache is empty if `age` is `None`.
+    ///
+    /// Blocks produced by this node may not include deposits or a useful eth1 vote.
+    Eth1Stale {
+        age: Option<Duration>,
+    },
+    /// The node is synced and has fresh eth1 data, but may serve duties slowly.
+    Degraded {
+        reasons: Vec<DegradedReason>,
+    },
+}
+
+impl Readiness {
+    /// Returns the verdict for a node with the given `sync_distance`, `eth1_block_age` and
+    /// `degraded_reasons`.
+    ///
+    /// When several thresholds in `config` are exceeded, the most severe verdict is returned
+    /// (i.e., syncing, then a stale eth1 cache, then degraded).
+    pub fn evaluate(
+        config: &ReadinessConfig,
+        sync_distance: u64,
+        eth1_block_age: Option<Duration>,
+        degraded_reasons: Vec<DegradedReason>,
+    ) -> Self {
+        if sync_distance > config.max_sync_distance {
+            Readiness::Syncing {
+                distance: sync_distance,
+            }
+        } else if eth1_block_age.map_or(true, |age| age > config.max_eth1_block_age) {
+            Readiness::Eth1Stale {
+                age: eth1_block_age,
+            }
+        } else if config.require_warm_caches && !degraded_reasons.is_empty() {
+            Readiness::Degraded {
+                reasons: degraded_reasons,
+            }
+        } else {
+            Readiness::Ready
+        }
+    }
+
+    /// Returns `true` if the node is ready to serve validator duties.
+    pub fn is_ready(&self) -> bool {
+        *self == Readiness::Ready
+    }
+}
+
+/// The progress of the head towards the wall-clock slot.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum SyncStatus {
+    /// The slot clock has not yet reached genesis, so there is nothing to sync.
+    PreGenesis { head_slot: Slot },
+    PostGenesis {
+        head_slot: Slot,
+        current_slot: Slot,
+        /// The number of slots by which the head lags behind `current_slot`.
+        sync_distance: u64,
+        /// `true` if `sync_distance` does not exceed the configured tolerance.
+        is_synced: bool,
+    },
+}
+
+impl SyncStatus {
+    /// Returns the status of a node with the given `head_slot`, where `current_slot` is `None`
+    /// prior to genesis.
+    pub fn new(head_slot: Slot, current_slot: Option<Slot>, max_sync_distance: u64) -> Self {
+        match current_slot {
+            None => SyncStatus::PreGenesis { head_slot },
+            Some(current_slot) => {
+                let sync_distance = current_slot.as_u64().saturating_sub(head_slot.as_u64());
+
+                SyncStatus::PostGenesis {
+                    head_slot,
+                    current_slot,
+                    sync_distance,
+                    is_synced: sync_distance <= max_sync_distance,
+                }
+            }
+        }
+    }
+
+    pub fn head_slot(&self) -> Slot {
+        match self {
+            SyncStatus::PreGenesis { head_slot } => *head_slot,
+            SyncStatus::PostGenesis { head_slot, .. } => *head_slot,
+        }
+    }
+
+    /// Returns the number of slots by which the head lags behind the slot clock (always `0` prior
+    /// to genesis).
+    pub fn sync_distance(&self) -> u64 {
+        match self {
+            SyncStatus::PreGenesis { .. } => 0,
+            SyncStatus::PostGenesis { sync_distance, .. } => *sync_distance,
+        }
+    }
+
+    /// Returns `true` if the node is synced. A node is always synced prior to genesis.
+    pub fn is_synced(&self) -> bool {
+        match self {
+            SyncStatus::PreGenesis { .. } => true,
+            SyncStatus::PostGenesis { is_synced, .. } => *is_synced,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ReadinessConfig {
+        ReadinessConfig {
+            max_sync_distance: 4,
+            max_eth1_block_age: Duration::from_secs(60),
+            require_warm_caches: true,
+        }
+    }
+
+    fn cold_shuffling() -> Vec<DegradedReason> {
+        vec![DegradedReason::ShufflingNotCached {
+            epoch: Epoch::new(1),
+        }]
+    }
+
+    #[test]
+    fn ready_within_thresholds() {
+        let verdict = Readiness::evaluate(&config(), 4, Some(Duration::from_secs(60)), vec![]);
+
+        assert_eq!(verdict, Readiness::Ready);
+        assert!(verdict.is_ready());
+    }
+
+    #[test]
+    fn syncing() {
+        let verdict = Readiness::evaluate(&config(), 5, Some(Duration::from_secs(0)), vec![]);
+
+        assert_eq!(verdict, Readiness::Syncing { distance: 5 });
+        assert!(!verdict.is_ready());
+    }
+
+    #[test]
+    fn eth1_stale() {
+        let age = Some(Duration::from_secs(61));
+
+        assert_eq!(
+            Readiness::evaluate(&config(), 0, age, vec![]),
+            Readiness::Eth1Stale { age }
+        );
+        assert_eq!(
+            Readiness::evaluate(&config(), 0, None, vec![]),
+            Readiness::Eth1Stale { age: None },
+            "an empty eth1 cache should be stale"
+        );
+    }
+
+    #[test]
+    fn degraded() {
+        let age = Some(Duration::from_secs(0));
+
+        assert_eq!(
+            Readiness::evaluate(&config(), 0, age, cold_shuffling()),
+            Readiness::Degraded {
+                reasons: cold_shuffling()
+            }
+        );
+
+        let config = ReadinessConfig {
+            require_warm_caches: false,
+            ..config()
+        };
+        assert_eq!(
+            Readiness::evaluate(&config, 0, age, cold_shuffling()),
+            Readiness::Ready,
+            "cold caches should be ignored when not required"
+        );
+    }
+
+    #[test]
+    fn most_severe_verdict_wins() {
+        assert_eq!(
+            Readiness::evaluate(&config(), 5, None, cold_shuffling()),
+            Readiness::Syncing { distance: 5 }
+        );
+        assert_eq!(
+            Readiness::evaluate(&config(), 0, None, cold_shuffling()),
+            Readiness::Eth1Stale { age: None }
+        );
+    }
+
+    #[test]
+    fn sync_status() {
+        let status = SyncStatus::new(Slot::new(3), Some(Slot::new(7)), 4);
+        assert_eq!(status.sync_distance(), 4);
+        assert!(status.is_synced());
+
+        let status = SyncStatus::new(Slot::new(3), Some(Slot::new(8)), 4);
+        assert_eq!(status.sync_distance(), 5);
+        assert!(!status.is_synced());
+
+        let status = SyncStatus::new(Slot::new(9), Some(Slot::new(8)), 4);
+        assert_eq!(
+            status.sync_distance(),
+            0,
+            "a head ahead of the clock should not underflow"
+        );
+        assert!(status.is_synced());
+    }
+
+    #[test]
+    fn sync_status_pre_genesis() {
+        let status = SyncStatus::new(Slot::new(0), None, 4);
+
+        assert_eq!(
+            status,
+            SyncStatus::PreGenesis {
+                head_slot: Slot::new(0)
+            }
+        );
+        assert_eq!(status.sync_distance(), 0);
+        assert!(status.is_synced());
+    }
+}
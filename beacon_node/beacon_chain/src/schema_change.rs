@@ -0,0 +1,56 @@
+//! Migrations between the schema versions of the database.
+//!
+//! The `SchemaVersion` stored in the database determines the layout of all of the containers
+//! persisted under fixed keys (i.e., `PersistedBeaconChain`, `PersistedOperationPool`, `SszEth1`
+//! and `SszForkChoice`). Any change to the SSZ layout of those containers must increment
+//! `store::CURRENT_SCHEMA_VERSION` and add a migration step from the prior version here.
+use crate::beacon_chain::ETH1_CACHE_DB_KEY;
+use crate::eth1_chain::SszEth1;
+use std::sync::Arc;
+use store::{Error as StoreError, SchemaVersion, Store};
+use types::{EthSpec, Hash256};
+
+/// Migrates the database from schema version `from` to schema version `to`, one version at a
+/// time, stamping the database with the new version after each step.
+///
+/// Returns an error without modifying the database if `from` is newer than `to`, since the
+/// database was written by a later version of the software.
+pub fn migrate_schema<E: EthSpec, S: Store<E>>(
+    store: Arc<S>,
+    from: SchemaVersion,
+    to: SchemaVersion,
+) -> Result<(), StoreError> {
+    match (from, to) {
+        // Migrating from a schema version to itself is a no-op.
+        (_, _) if from == to => Ok(()),
+        // Downgrades are not supported.
+        (_, _) if from > to => Err(StoreError::SchemaMigrationError(format!(
+            "database schema version {} is newer than the latest supported version {}",
+            from.as_u64(),
+            to.as_u64()
+        ))),
+        // Migrate across multiple versions by migrating one version at a time.
+        (_, _) if to.as_u64() > from.as_u64() + 1 => {
+            let next = SchemaVersion(from.as_u64() + 1);
+            migrate_schema(store.clone(), from, next)?;
+            migrate_schema(store, next, to)
+        }
+        // Version 1 adds the pruning of deposit logs to the eth1 cache, changing its layout.
+        (SchemaVersion(0), SchemaVersion(1)) => {
+            let key = Hash256::from_slice(&ETH1_CACHE_DB_KEY);
+            if let Some(ssz_eth1) = store.get::<SszEth1>(&key)? {
+                let upgraded = ssz_eth1.upgrade_from_v0().map_err(|e| {
+                    StoreError::SchemaMigrationError(format!("unable to convert eth1 cache: {}", e))
+                })?;
+                store.put(&key, &upgraded)?;
+            }
+            store.put_schema_version(to)
+        }
+        // Anything else is an error.
+        (_, _) => Err(StoreError::SchemaMigrationError(format!(
+            "no migration from database schema version {} to {}",
+            from.as_u64(),
+            to.as_u64()
+        ))),
+    }
+}
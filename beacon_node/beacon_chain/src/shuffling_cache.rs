@@ -36,6 +36,12 @@ impl ShufflingCache {
         opt
     }
 
+    /// Returns `true` if the shuffling for `epoch` and `root` is cached, without affecting the LRU
+    /// ordering or the hit/miss metrics.
+    pub fn contains(&self, epoch: Epoch, root: Hash256) -> bool {
+        self.cache.contains(&(epoch, root))
+    }
+
     pub fn insert(&mut self, epoch: Epoch, root: Hash256, committee_cache: &CommitteeCache) {
         let key = (epoch, root);
 
@@ -0,0 +1,66 @@
+use crate::beacon_chain::StateSkipConfig;
+use parking_lot::Mutex;
+use types::{BeaconState, EthSpec, Hash256, Slot};
+
+/// A state that was partially advanced through skip slots before running out of time.
+struct PartialStateSkip<E: EthSpec> {
+    start_root: Hash256,
+    config: StateSkipConfig,
+    state: BeaconState<E>,
+}
+
+/// Stores the progress of the most recent state skip that did not reach its target slot before
+/// its deadline, so that a subsequent skip from the same state may resume rather than restart.
+///
+/// Only a single partially-advanced state is stored, keyed by the root of the state that the skip
+/// started from and the slot that it reached.
+pub struct StateSkipCache<E: EthSpec> {
+    partial: Mutex<Option<PartialStateSkip<E>>>,
+}
+
+impl<E: EthSpec> Default for StateSkipCache<E> {
+    fn default() -> Self {
+        Self {
+            partial: Mutex::new(None),
+        }
+    }
+}
+
+impl<E: EthSpec> StateSkipCache<E> {
+    /// Stores `state`, which was advanced from the state with `start_root` using `config`,
+    /// replacing any previously stored state.
+    pub fn insert(&self, start_root: Hash256, config: StateSkipConfig, state: BeaconState<E>) {
+        *self.partial.lock() = Some(PartialStateSkip {
+            start_root,
+            config,
+            state,
+        });
+    }
+
+    /// Removes and returns the stored state if it was advanced from the state with `start_root`
+    /// using `config` and has not been advanced beyond `target_slot`.
+    ///
+    /// Any other state is left in place.
+    pub fn take(
+        &self,
+        start_root: Hash256,
+        config: StateSkipConfig,
+        target_slot: Slot,
+    ) -> Option<BeaconState<E>> {
+        let mut partial = self.partial.lock();
+
+        match partial.take() {
+            Some(skip)
+                if skip.start_root == start_root
+                    && skip.config == config
+                    && skip.state.slot <= target_slot =>
+            {
+                Some(skip.state)
+            }
+            other => {
+                *partial = other;
+                None
+            }
+        }
+    }
+}
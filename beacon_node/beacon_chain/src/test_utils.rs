@@ -1,13 +1,15 @@
 pub use crate::beacon_chain::{
     BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, FORK_CHOICE_DB_KEY, OP_POOL_DB_KEY,
 };
+pub use crate::op_pool_persistence::op_pool_delta_key;
 pub use crate::persisted_beacon_chain::PersistedBeaconChain;
 use crate::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::CachingEth1Backend,
-    events::NullEventHandler,
-    AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, BlockProcessingOutcome,
-    StateSkipConfig,
+    events::ChannelEventHandler,
+    AttestationProcessingOutcome, BeaconChain, BeaconChainMode, BeaconChainTypes,
+    BlockProcessingOutcome, StateSkipConfig, DEFAULT_MAX_INVALID_BLOCK_DUMP_FILES,
+    MAXIMUM_GOSSIP_CLOCK_DISPARITY,
 };
 use genesis::interop_genesis_state;
 use rayon::prelude::*;
@@ -15,6 +17,7 @@ use sloggers::{null::NullLoggerBuilder, Build};
 use slot_clock::TestingSlotClock;
 use state_processing::per_slot_processing;
 use std::borrow::Cow;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use store::{
@@ -40,7 +43,7 @@ pub type BaseHarnessType<TStore, TStoreMigrator, TEthSpec> = Witness<
     TestingSlotClock,
     CachingEth1Backend<TEthSpec, TStore>,
     TEthSpec,
-    NullEventHandler<TEthSpec>,
+    ChannelEventHandler<TEthSpec>,
 >;
 
 pub type HarnessType<E> = BaseHarnessType<MemoryStore<E>, NullMigrator, E>;
@@ -85,6 +88,89 @@ pub struct BeaconChainHarness<T: BeaconChainTypes> {
 impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn new(eth_spec_instance: E, keypairs: Vec<Keypair>) -> Self {
+        Self::new_with_mode(eth_spec_instance, keypairs, BeaconChainMode::Full)
+    }
+
+    /// Instantiate a new harness with `validator_count` initial validators, in the given `mode`.
+    ///
+    /// A harness in `BeaconChainMode::Follower` cannot produce blocks, so it must be extended
+    /// with blocks produced by another harness.
+    pub fn new_with_mode(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        mode: BeaconChainMode,
+    ) -> Self {
+        Self::new_with_options(
+            eth_spec_instance,
+            keypairs,
+            mode,
+            MAXIMUM_GOSSIP_CLOCK_DISPARITY,
+            None,
+            None,
+        )
+    }
+
+    /// Instantiate a new harness with `validator_count` initial validators, which processes blocks
+    /// from slots that start within `max_future_slot_tolerance`.
+    ///
+    /// The testing slot clock always reports that the next slot starts in one second.
+    pub fn new_with_max_future_slot_tolerance(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        max_future_slot_tolerance: Duration,
+    ) -> Self {
+        Self::new_with_options(
+            eth_spec_instance,
+            keypairs,
+            BeaconChainMode::Full,
+            max_future_slot_tolerance,
+            None,
+            None,
+        )
+    }
+
+    /// Instantiate a new harness with `validator_count` initial validators, which writes blocks
+    /// that fail processing to `invalid_block_dump_dir`.
+    pub fn new_with_invalid_block_dump(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        invalid_block_dump_dir: PathBuf,
+    ) -> Self {
+        Self::new_with_options(
+            eth_spec_instance,
+            keypairs,
+            BeaconChainMode::Full,
+            MAXIMUM_GOSSIP_CLOCK_DISPARITY,
+            Some(invalid_block_dump_dir),
+            None,
+        )
+    }
+
+    /// Instantiate a new harness with `validator_count` initial validators, which refuses to
+    /// advance a state by more than `max_skip_slots`.
+    pub fn new_with_max_skip_slots(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        max_skip_slots: u64,
+    ) -> Self {
+        Self::new_with_options(
+            eth_spec_instance,
+            keypairs,
+            BeaconChainMode::Full,
+            MAXIMUM_GOSSIP_CLOCK_DISPARITY,
+            None,
+            Some(max_skip_slots),
+        )
+    }
+
+    fn new_with_options(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        mode: BeaconChainMode,
+        max_future_slot_tolerance: Duration,
+        invalid_block_dump_dir: Option<PathBuf>,
+        max_skip_slots: Option<u64>,
+    ) -> Self {
         let data_dir = tempdir().expect("should create temporary data_dir");
         let spec = E::default_spec();
 
@@ -103,7 +189,51 @@ impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
             .expect("should build state using recent genesis")
             .dummy_eth1_backend()
             .expect("should build dummy backend")
-            .null_event_handler()
+            .event_handler(ChannelEventHandler::default())
+            .testing_slot_clock(HARNESS_SLOT_TIME)
+            .expect("should configure testing slot clock")
+            .reduced_tree_fork_choice()
+            .expect("should add fork choice to builder")
+            .mode(mode)
+            .max_future_slot_tolerance(max_future_slot_tolerance)
+            .dump_invalid_blocks(invalid_block_dump_dir, DEFAULT_MAX_INVALID_BLOCK_DUMP_FILES)
+            .max_skip_slots(max_skip_slots)
+            .build()
+            .expect("should build");
+
+        Self {
+            spec: chain.spec.clone(),
+            chain,
+            keypairs,
+            data_dir,
+        }
+    }
+
+    /// Instantiate a new harness which starts from the trusted `anchor_state` and `anchor_block`,
+    /// rather than from genesis.
+    pub fn new_from_anchor(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        anchor_state: BeaconState<E>,
+        anchor_block: SignedBeaconBlock<E>,
+        genesis_validators_root: Hash256,
+    ) -> Self {
+        let data_dir = tempdir().expect("should create temporary data_dir");
+        let spec = E::default_spec();
+
+        let log = NullLoggerBuilder.build().expect("logger should build");
+
+        let chain = BeaconChainBuilder::new(eth_spec_instance)
+            .logger(log.clone())
+            .custom_spec(spec.clone())
+            .store(Arc::new(MemoryStore::open()))
+            .store_migrator(NullMigrator)
+            .data_dir(data_dir.path().to_path_buf())
+            .weak_subjectivity_state(anchor_state, anchor_block, genesis_validators_root)
+            .expect("should build state using anchor")
+            .dummy_eth1_backend()
+            .expect("should build dummy backend")
+            .event_handler(ChannelEventHandler::default())
             .testing_slot_clock(HARNESS_SLOT_TIME)
             .expect("should configure testing slot clock")
             .reduced_tree_fork_choice()
@@ -145,7 +275,7 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
             .expect("should build state using recent genesis")
             .dummy_eth1_backend()
             .expect("should build dummy backend")
-            .null_event_handler()
+            .event_handler(ChannelEventHandler::default())
             .testing_slot_clock(HARNESS_SLOT_TIME)
             .expect("should configure testing slot clock")
             .reduced_tree_fork_choice()
@@ -182,7 +312,7 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
             .expect("should resume beacon chain from db")
             .dummy_eth1_backend()
             .expect("should build dummy backend")
-            .null_event_handler()
+            .event_handler(ChannelEventHandler::default())
             .testing_slot_clock(Duration::from_secs(1))
             .expect("should configure testing slot clock")
             .reduced_tree_fork_choice()
@@ -320,7 +450,7 @@ where
 
         let (block, state) = self
             .chain
-            .produce_block_on_state(state, slot, randao_reveal)
+            .produce_block_on_state(state, None, slot, randao_reveal)
             .expect("should produce block");
 
         let signed_block = block.sign(sk, &state.fork, &self.spec);
@@ -489,3 +619,14 @@ where
         &self.keypairs[validator_index].sk
     }
 }
+
+/// Collects the `(root, slot)` pairs yielded by a roots iterator (e.g.,
+/// `BeaconChain::rev_iter_block_roots`), panicking if the store returns an error.
+pub fn collect_roots<I>(iter: I) -> Vec<(Hash256, Slot)>
+where
+    I: IntoIterator<Item = Result<(Hash256, Slot), store::Error>>,
+{
+    iter.into_iter()
+        .collect::<Result<_, _>>()
+        .expect("should iterate roots without a store error")
+}
@@ -1,20 +1,159 @@
+use crate::metrics;
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
-use std::time::Duration;
+use serde_derive::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// The time-outs before failure during an operation to take a read/write lock on the components
+/// of a `BeaconChain`.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct LockTimeouts {
+    /// The time-out for the canonical head.
+    pub head: Duration,
+    /// The time-out for the attestation shuffling cache.
+    pub attestation_cache: Duration,
+    /// The time-out for the validator pubkey cache.
+    pub validator_pubkey_cache: Duration,
+}
+
+impl Default for LockTimeouts {
+    fn default() -> Self {
+        Self {
+            head: Duration::from_secs(1),
+            attestation_cache: Duration::from_secs(1),
+            validator_pubkey_cache: Duration::from_secs(1),
+        }
+    }
+}
+
+impl LockTimeouts {
+    /// Returns `LockTimeouts` that use the same `timeout` for all locks.
+    pub fn all(timeout: Duration) -> Self {
+        Self {
+            head: timeout,
+            attestation_cache: timeout,
+            validator_pubkey_cache: timeout,
+        }
+    }
+}
 
 /// A simple wrapper around `parking_lot::RwLock` that only permits read/write access with a
 /// time-out (i.e., no indefinitely-blocking operations).
-pub struct TimeoutRwLock<T>(RwLock<T>);
+///
+/// The time spent waiting for the lock and each time-out are recorded in metrics labelled with
+/// the `name` of the lock. Each time-out also increments the given `timeouts` counter, which
+/// predates the labelled metrics.
+pub struct TimeoutRwLock<T> {
+    lock: RwLock<T>,
+    name: &'static str,
+    timeouts: &'static metrics::Result<metrics::IntCounter>,
+    acquisitions: AtomicU64,
+}
 
 impl<T> TimeoutRwLock<T> {
-    pub fn new(inner: T) -> Self {
-        Self(RwLock::new(inner))
+    pub fn new(
+        inner: T,
+        name: &'static str,
+        timeouts: &'static metrics::Result<metrics::IntCounter>,
+    ) -> Self {
+        Self {
+            lock: RwLock::new(inner),
+            name,
+            timeouts,
+            acquisitions: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of times that the lock has been acquired (for reading or writing).
+    pub fn acquisitions(&self) -> u64 {
+        self.acquisitions.load(Ordering::Relaxed)
     }
 
     pub fn try_read_for(&self, timeout: Duration) -> Option<RwLockReadGuard<T>> {
-        self.0.try_read_for(timeout)
+        let start = Instant::now();
+        let guard = self.lock.try_read_for(timeout);
+        self.observe_wait(start, guard.is_some());
+        guard
+    }
+
+    /// Returns `None` immediately if the lock is held by a writer. This is not counted as a
+    /// time-out.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<T>> {
+        let guard = self.lock.try_read();
+        if guard.is_some() {
+            self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        }
+        guard
     }
 
     pub fn try_write_for(&self, timeout: Duration) -> Option<RwLockWriteGuard<T>> {
-        self.0.try_write_for(timeout)
+        let start = Instant::now();
+        let guard = self.lock.try_write_for(timeout);
+        self.observe_wait(start, guard.is_some());
+        guard
+    }
+
+    /// Records the time since `start` spent waiting for the lock, counting a time-out if the lock
+    /// was not `acquired`.
+    fn observe_wait(&self, start: Instant, acquired: bool) {
+        metrics::observe_vec(
+            &metrics::LOCK_WAIT_TIMES,
+            &[self.name],
+            start.elapsed().as_secs_f64(),
+        );
+        if acquired {
+            self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        } else {
+            metrics::inc_counter_vec(&metrics::LOCK_TIMEOUTS, &[self.name]);
+            metrics::inc_counter(self.timeouts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeouts(name: &str) -> i64 {
+        metrics::LOCK_TIMEOUTS
+            .as_ref()
+            .expect("should have timeouts metric")
+            .with_label_values(&[name])
+            .get()
+    }
+
+    #[test]
+    fn held_write_lock_times_out_reader() {
+        let name = "held_write_lock_times_out_reader";
+        let lock = TimeoutRwLock::new(0, name, &metrics::HEAD_LOCK_TIMEOUTS);
+        let head_lock_timeouts = metrics::HEAD_LOCK_TIMEOUTS
+            .as_ref()
+            .expect("should have head lock timeouts metric")
+            .get();
+        let timeout = Duration::from_millis(10);
+
+        assert!(lock.try_read_for(timeout).is_some());
+        assert_eq!(timeouts(name), 0, "an uncontended read should not time out");
+        assert_eq!(lock.acquisitions(), 1, "should count the read");
+
+        let _guard = lock.try_write_for(timeout).expect("should take write lock");
+        assert!(
+            lock.try_read_for(timeout).is_none(),
+            "reader should time out whilst the write lock is held"
+        );
+        assert_eq!(timeouts(name), 1, "should count the time-out");
+        assert!(
+            metrics::HEAD_LOCK_TIMEOUTS
+                .as_ref()
+                .expect("should have head lock timeouts metric")
+                .get()
+                > head_lock_timeouts,
+            "should count the time-out in the unlabelled counter"
+        );
+        assert_eq!(
+            lock.acquisitions(),
+            2,
+            "should not count the time-out as an acquisition"
+        );
     }
 }
@@ -0,0 +1,316 @@
+use crate::metrics;
+use parking_lot::Mutex;
+use slog::{debug, info, warn, Logger};
+use state_processing::common::get_attesting_indices;
+use std::collections::{HashMap, HashSet};
+use types::{
+    Attestation, BeaconBlock, BeaconState, BeaconStateError, ChainSpec, Epoch, EthSpec,
+    PublicKeyBytes,
+};
+
+/// The number of epochs after which the attestations of each monitored validator are summarized.
+///
+/// An attestation may be included up until the end of the epoch after its target epoch, so an
+/// epoch is only summarized once a block from two epochs later has been imported.
+pub const SUMMARY_DELAY_EPOCHS: u64 = 2;
+
+/// The inclusion of the attestation of a monitored validator for some epoch.
+#[derive(Debug, Clone, Copy)]
+struct AttestationInclusion {
+    inclusion_distance: u64,
+    correct_target: bool,
+    correct_head: bool,
+}
+
+#[derive(Default)]
+struct MonitoredValidator {
+    /// The first observed inclusion of an attestation from the validator, by target epoch.
+    inclusions: HashMap<Epoch, AttestationInclusion>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The monitored validators, by validator index.
+    validators: HashMap<usize, MonitoredValidator>,
+    /// The public keys of monitored validators which have not yet been found in a state.
+    unknown_pubkeys: HashSet<PublicKeyBytes>,
+    /// The number of validators in the registry which have been checked against
+    /// `unknown_pubkeys`.
+    checked_validators: usize,
+    /// The last epoch for which the attestations of monitored validators were summarized.
+    last_summarized_epoch: Option<Epoch>,
+}
+
+/// Tracks the block proposals and attestation inclusions of a set of validators chosen by the
+/// operator, reporting them in logs and in metrics labelled by validator index.
+///
+/// Inclusions are recorded from every imported block, including those which are not canonical.
+/// Only monitored validators are labelled, so the cardinality of the metrics is bounded by the
+/// number of monitored validators.
+pub struct ValidatorMonitor {
+    inner: Mutex<Inner>,
+    log: Logger,
+}
+
+impl ValidatorMonitor {
+    /// Instantiates a new monitor without any monitored validators.
+    pub fn new(log: Logger) -> Self {
+        Self {
+            inner: Mutex::new(Inner::default()),
+            log,
+        }
+    }
+
+    /// Starts monitoring the validator with the given index.
+    pub fn add_validator_index(&self, validator_index: usize) {
+        self.inner
+            .lock()
+            .validators
+            .entry(validator_index)
+            .or_insert_with(MonitoredValidator::default);
+    }
+
+    /// Starts monitoring the validator with the given public key, once it is found in the
+    /// registry of an imported block.
+    pub fn add_validator_pubkey(&self, pubkey: PublicKeyBytes) {
+        let mut inner = self.inner.lock();
+        inner.unknown_pubkeys.insert(pubkey);
+        // The validator may already be in the registry, so the whole registry must be checked.
+        inner.checked_validators = 0;
+    }
+
+    /// Returns the indices of all monitored validators, in ascending order.
+    pub fn validator_indices(&self) -> Vec<usize> {
+        let mut indices = self
+            .inner
+            .lock()
+            .validators
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        indices.sort();
+        indices
+    }
+
+    /// Records the proposal of `block` and the inclusion of any attestations from monitored
+    /// validators, summarizing the attestations of any epochs which can no longer be included.
+    ///
+    /// `state` must be the post-state of `block`.
+    pub fn process_block<E: EthSpec>(
+        &self,
+        block: &BeaconBlock<E>,
+        state: &BeaconState<E>,
+        spec: &ChainSpec,
+    ) {
+        let mut inner = self.inner.lock();
+
+        inner.find_pubkeys(state);
+
+        if inner.validators.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.process_block_internal(&mut inner, block, state, spec) {
+            debug!(
+                self.log,
+                "Unable to monitor block";
+                "block_slot" => block.slot,
+                "error" => format!("{:?}", e),
+            );
+        }
+    }
+
+    fn process_block_internal<E: EthSpec>(
+        &self,
+        inner: &mut Inner,
+        block: &BeaconBlock<E>,
+        state: &BeaconState<E>,
+        spec: &ChainSpec,
+    ) -> Result<(), BeaconStateError> {
+        let proposer_index = state.get_beacon_proposer_index(block.slot, spec)?;
+        if inner.validators.contains_key(&proposer_index) {
+            let id = proposer_index.to_string();
+            metrics::inc_counter_vec(&metrics::VALIDATOR_MONITOR_BEACON_BLOCKS_TOTAL, &[&id]);
+            info!(
+                self.log,
+                "Block from monitored validator";
+                "validator" => &id,
+                "slot" => block.slot,
+            );
+        }
+
+        for attestation in block.body.attestations.iter() {
+            self.process_included_attestation(inner, attestation, block, state)?;
+        }
+
+        self.summarize_epochs(inner, state);
+
+        Ok(())
+    }
+
+    /// Records the first inclusion of `attestation` for each monitored validator amongst its
+    /// attesters.
+    fn process_included_attestation<E: EthSpec>(
+        &self,
+        inner: &mut Inner,
+        attestation: &Attestation<E>,
+        block: &BeaconBlock<E>,
+        state: &BeaconState<E>,
+    ) -> Result<(), BeaconStateError> {
+        let data = &attestation.data;
+        let committee = state.get_beacon_committee(data.slot, data.index)?;
+        let attesting_indices =
+            get_attesting_indices::<E>(committee.committee, &attestation.aggregation_bits)?;
+
+        // The votes are only compared against the state once a monitored validator is found
+        // amongst the attesters.
+        let mut attestation_inclusion = None;
+
+        for validator_index in attesting_indices {
+            let validator = match inner.validators.get_mut(&validator_index) {
+                Some(validator) => validator,
+                None => continue,
+            };
+
+            if validator.inclusions.contains_key(&data.target.epoch) {
+                continue;
+            }
+
+            let inclusion = match attestation_inclusion {
+                Some(inclusion) => inclusion,
+                None => {
+                    let inclusion = AttestationInclusion {
+                        inclusion_distance: (block.slot - data.slot).as_u64(),
+                        correct_target: data.target.root
+                            == *state.get_block_root_at_epoch(data.target.epoch)?,
+                        correct_head: data.beacon_block_root == *state.get_block_root(data.slot)?,
+                    };
+                    attestation_inclusion = Some(inclusion);
+                    inclusion
+                }
+            };
+
+            validator.inclusions.insert(data.target.epoch, inclusion);
+
+            let id = validator_index.to_string();
+            metrics::inc_counter_vec(
+                &metrics::VALIDATOR_MONITOR_ATTESTATIONS_INCLUDED_TOTAL,
+                &[&id],
+            );
+            metrics::set_gauge_vec(
+                &metrics::VALIDATOR_MONITOR_ATTESTATION_INCLUSION_DISTANCE,
+                &[&id],
+                inclusion.inclusion_distance as i64,
+            );
+            info!(
+                self.log,
+                "Attestation from monitored validator included";
+                "validator" => &id,
+                "attestation_slot" => data.slot,
+                "block_slot" => block.slot,
+                "inclusion_distance" => inclusion.inclusion_distance,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Summarizes the attestations of each monitored validator for all epochs that are at least
+    /// `SUMMARY_DELAY_EPOCHS` prior to the epoch of `state` and have not yet been summarized.
+    fn summarize_epochs<E: EthSpec>(&self, inner: &mut Inner, state: &BeaconState<E>) {
+        let current_epoch = state.current_epoch();
+        if current_epoch < SUMMARY_DELAY_EPOCHS {
+            return;
+        }
+        let summary_epoch = current_epoch - SUMMARY_DELAY_EPOCHS;
+
+        let first_epoch = match inner.last_summarized_epoch {
+            Some(last_summarized_epoch) if last_summarized_epoch >= summary_epoch => return,
+            Some(last_summarized_epoch) => last_summarized_epoch + 1,
+            None => summary_epoch,
+        };
+        inner.last_summarized_epoch = Some(summary_epoch);
+
+        for (validator_index, validator) in inner.validators.iter_mut() {
+            let is_active_at = |epoch| {
+                state
+                    .validators
+                    .get(*validator_index)
+                    .map_or(false, |validator| validator.is_active_at(epoch))
+            };
+            let id = validator_index.to_string();
+
+            for epoch in (first_epoch.as_u64()..=summary_epoch.as_u64()).map(Epoch::new) {
+                if !is_active_at(epoch) {
+                    continue;
+                }
+
+                if let Some(inclusion) = validator.inclusions.get(&epoch) {
+                    if inclusion.correct_target {
+                        metrics::inc_counter_vec(
+                            &metrics::VALIDATOR_MONITOR_ATTESTATION_CORRECT_TARGET_TOTAL,
+                            &[&id],
+                        );
+                    }
+                    if inclusion.correct_head {
+                        metrics::inc_counter_vec(
+                            &metrics::VALIDATOR_MONITOR_ATTESTATION_CORRECT_HEAD_TOTAL,
+                            &[&id],
+                        );
+                    }
+                    info!(
+                        self.log,
+                        "Attestation summary for monitored validator";
+                        "validator" => &id,
+                        "epoch" => epoch,
+                        "inclusion_distance" => inclusion.inclusion_distance,
+                        "correct_target" => inclusion.correct_target,
+                        "correct_head" => inclusion.correct_head,
+                    );
+                } else {
+                    metrics::inc_counter_vec(
+                        &metrics::VALIDATOR_MONITOR_ATTESTATIONS_MISSED_TOTAL,
+                        &[&id],
+                    );
+                    warn!(
+                        self.log,
+                        "Attestation from monitored validator not included";
+                        "validator" => &id,
+                        "epoch" => epoch,
+                    );
+                }
+            }
+
+            validator
+                .inclusions
+                .retain(|epoch, _| *epoch > summary_epoch);
+        }
+    }
+}
+
+impl Inner {
+    /// Starts monitoring any validators in the registry of `state` whose public key was added
+    /// with `ValidatorMonitor::add_validator_pubkey`.
+    ///
+    /// Only the validators which were added to the registry since the last call are checked.
+    fn find_pubkeys<E: EthSpec>(&mut self, state: &BeaconState<E>) {
+        if self.unknown_pubkeys.is_empty() {
+            return;
+        }
+
+        for (validator_index, validator) in state
+            .validators
+            .iter()
+            .enumerate()
+            .skip(self.checked_validators)
+        {
+            if self.unknown_pubkeys.remove(&validator.pubkey) {
+                self.validators
+                    .entry(validator_index)
+                    .or_insert_with(MonitoredValidator::default);
+            }
+        }
+
+        self.checked_validators = state.validators.len();
+    }
+}
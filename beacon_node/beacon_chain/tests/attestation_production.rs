@@ -5,8 +5,9 @@ extern crate lazy_static;
 
 use beacon_chain::{
     test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy},
-    StateSkipConfig,
+    LocalAttestationCorrectness, StateSkipConfig,
 };
+use std::borrow::Cow;
 use tree_hash::TreeHash;
 use types::{AggregateSignature, EthSpec, Keypair, MainnetEthSpec, RelativeEpoch, Slot};
 
@@ -38,7 +39,8 @@ fn produces_attestations() {
 
     let chain = &harness.chain;
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
     assert_eq!(state.slot, num_blocks_produced, "head should have updated");
     assert!(
         state.finalized_checkpoint.epoch > 0,
@@ -125,3 +127,240 @@ fn produces_attestations() {
         }
     }
 }
+
+/// This test produces attestations to the head using the head state and a state from early in the
+/// chain, which has a justified checkpoint that lags behind that of the head.
+///
+/// It checks that the justified checkpoint of the given state is used as the source of each
+/// attestation, rather than that of the head.
+#[test]
+fn attestation_for_block_uses_state_justification() {
+    let num_blocks_produced = MainnetEthSpec::slots_per_epoch() * 4;
+
+    let harness = BeaconChainHarness::new(MainnetEthSpec, KEYPAIRS[..].to_vec());
+
+    // Skip past the genesis slot.
+    harness.advance_slot();
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let chain = &harness.chain;
+    let head = chain.head().expect("should get head");
+    let head_justified_checkpoint = head.beacon_state.current_justified_checkpoint.clone();
+
+    let lagging_state = chain
+        .state_at_slot(
+            Slot::from(MainnetEthSpec::slots_per_epoch()),
+            StateSkipConfig::WithStateRoots,
+        )
+        .expect("should get state");
+    let lagging_slot = lagging_state.slot;
+    let lagging_justified_checkpoint = lagging_state.current_justified_checkpoint.clone();
+
+    assert!(
+        lagging_justified_checkpoint.epoch < head_justified_checkpoint.epoch,
+        "state justification should lag the head"
+    );
+
+    let current_slot = chain.slot().expect("should get slot");
+
+    let attestation = chain
+        .produce_attestation_for_block(
+            current_slot,
+            0,
+            head.beacon_block_root,
+            Cow::Borrowed(&head.beacon_state),
+        )
+        .expect("should produce attestation");
+
+    assert_eq!(
+        attestation.data.source, head_justified_checkpoint,
+        "source should be the head justified checkpoint"
+    );
+    assert_eq!(
+        attestation.data.beacon_block_root, head.beacon_block_root,
+        "bad block root"
+    );
+
+    let attestation = chain
+        .produce_attestation_for_block(
+            lagging_slot,
+            0,
+            head.beacon_block_root,
+            Cow::Owned(lagging_state),
+        )
+        .expect("should produce attestation");
+
+    assert_eq!(
+        attestation.data.source, lagging_justified_checkpoint,
+        "source should be the justified checkpoint of the given state"
+    );
+}
+
+/// This test produces attestations for all committees at a range of slots with a single call,
+/// checking that they match those produced one committee at a time and that each call acquires
+/// the head lock only once.
+#[test]
+fn produces_attestations_for_slot() {
+    let num_blocks_produced = MainnetEthSpec::slots_per_epoch() * 4;
+
+    let harness = BeaconChainHarness::new(MainnetEthSpec, KEYPAIRS[..].to_vec());
+
+    // Skip past the genesis slot.
+    harness.advance_slot();
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let chain = &harness.chain;
+    let current_slot = chain.slot().expect("should get slot");
+
+    // Test slots prior to, at and after the head.
+    let start_slot = current_slot.as_u64() - MainnetEthSpec::slots_per_epoch();
+    let end_slot = current_slot.as_u64() + MainnetEthSpec::slots_per_epoch() * 2;
+
+    for slot in (start_slot..=end_slot).map(Slot::from) {
+        let committee_count = chain
+            .state_at_slot(slot, StateSkipConfig::WithoutStateRoots)
+            .expect("should get state")
+            .get_beacon_committees_at_slot(slot)
+            .expect("should get committees")
+            .len() as u64;
+
+        // Include an index without a committee, which should fail without affecting the others.
+        let indices = (0..=committee_count).collect::<Vec<_>>();
+
+        let acquisitions = chain.head_lock_acquisitions();
+        let attestations = chain
+            .produce_attestations_for_slot(slot, &indices)
+            .expect("should produce attestations");
+
+        assert_eq!(
+            chain.head_lock_acquisitions() - acquisitions,
+            1,
+            "should only acquire the head lock once per batch"
+        );
+        assert_eq!(
+            attestations.len(),
+            indices.len(),
+            "should produce one result per index"
+        );
+
+        for (index, attestation) in indices.into_iter().zip(attestations.into_iter()) {
+            if index < committee_count {
+                assert_eq!(
+                    attestation.expect("should produce attestation"),
+                    chain
+                        .produce_attestation(slot, index)
+                        .expect("should produce single attestation"),
+                    "batched attestation should match single attestation"
+                );
+            } else {
+                assert!(
+                    attestation.is_err(),
+                    "should not produce attestation without committee"
+                );
+            }
+        }
+    }
+}
+
+/// Produces attestations to a block which is subsequently re-orged out of the chain, then checks
+/// that they are reported as incorrect once the epoch has settled.
+#[test]
+fn evaluates_local_attestation_correctness() {
+    let slots_per_epoch = MainnetEthSpec::slots_per_epoch();
+
+    let harness = BeaconChainHarness::new(MainnetEthSpec, KEYPAIRS[..].to_vec());
+    let chain = &harness.chain;
+
+    // Skip past the genesis slot.
+    harness.advance_slot();
+
+    // Build the chain up until the last slot of epoch 1.
+    harness.extend_chain(
+        (slots_per_epoch * 2 - 1) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let fork_head = chain.head().expect("should get head");
+    let fork_slot = fork_head.beacon_block.slot();
+    let wrong_slot = fork_slot + 1;
+    let wrong_epoch = wrong_slot.epoch(slots_per_epoch);
+    assert_eq!(
+        wrong_slot,
+        wrong_epoch.start_slot(slots_per_epoch),
+        "the wrong block should be at the start of an epoch"
+    );
+
+    // Produce a block that no validator attests to, then attest to it whilst it is the head.
+    harness.advance_slot();
+    let wrong_head = harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let wrong_attestation = chain
+        .produce_attestation(wrong_slot, 0)
+        .expect("should produce attestation");
+    assert_eq!(wrong_attestation.data.beacon_block_root, wrong_head);
+    assert_eq!(wrong_attestation.data.target.root, wrong_head);
+
+    // Build a competing chain that skips the wrong block, which all validators attest to.
+    let canonical_head = harness.extend_chain(
+        slots_per_epoch as usize,
+        BlockStrategy::ForkCanonicalChainAt {
+            previous_slot: fork_slot,
+            first_slot: wrong_slot + 1,
+        },
+        AttestationStrategy::AllValidators,
+    );
+    assert_eq!(
+        chain.head().expect("should get head").beacon_block_root,
+        canonical_head,
+        "should re-org to the competing chain"
+    );
+
+    // Produce an attestation in the same epoch which agrees with the new canonical chain.
+    let correct_slot = wrong_slot + 2;
+    let correct_attestation = chain
+        .produce_attestation(correct_slot, 0)
+        .expect("should produce attestation");
+    assert_eq!(
+        correct_attestation.data.target.root, fork_head.beacon_block_root,
+        "the target should be the last block prior to the skipped epoch start"
+    );
+
+    assert!(
+        chain.local_attestation_correctness().is_empty(),
+        "should not evaluate an epoch before it has settled"
+    );
+
+    // Extend the chain until the end of the epoch following the wrong epoch.
+    harness.advance_slot();
+    harness.extend_chain(
+        slots_per_epoch as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    assert_eq!(
+        chain.local_attestation_correctness(),
+        vec![LocalAttestationCorrectness {
+            epoch: wrong_epoch,
+            produced: 2,
+            correct_target: 1,
+            correct_head: 1,
+        }],
+        "should report the re-orged attestation as incorrect"
+    );
+}
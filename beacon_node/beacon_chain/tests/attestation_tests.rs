@@ -35,22 +35,28 @@ fn attestation_validity() {
 
     // Extend the chain out a few epochs so we have some chain depth to play with.
     harness.extend_chain(
-        MainnetEthSpec::slots_per_epoch() as usize * 3 + 1,
+        MainnetEthSpec::slots_per_epoch() as usize * 3,
         BlockStrategy::OnCanonicalHead,
         AttestationStrategy::AllValidators,
     );
+    // Don't attest to the head, otherwise our attestations to it would already be known.
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
 
     let head = chain.head().expect("should get head");
     let current_slot = chain.slot().expect("should get slot");
     let current_epoch = chain.epoch().expect("should get epoch");
 
-    let valid_attestation = harness
-        .get_free_attestations(
-            &AttestationStrategy::AllValidators,
-            &head.beacon_state,
-            head.beacon_block_root,
-            head.beacon_block.slot(),
-        )
+    let valid_attestations = harness.get_free_attestations(
+        &AttestationStrategy::AllValidators,
+        &head.beacon_state,
+        head.beacon_block_root,
+        head.beacon_block.slot(),
+    );
+    let valid_attestation = valid_attestations
         .first()
         .cloned()
         .expect("should get at least one attestation");
@@ -176,7 +182,11 @@ fn attestation_validity() {
      * Should reject attestations with a bad signature.
      */
 
-    let mut bad_signature_attestation = valid_attestation.clone();
+    // Use an attestation that has not been processed, so its signature is checked.
+    let mut bad_signature_attestation = valid_attestations
+        .get(1)
+        .cloned()
+        .expect("should get a second attestation");
     let kp = generate_deterministic_keypair(0);
     let mut agg_sig = AggregateSignature::new();
     agg_sig.add(&Signature::new(&[42, 42], &kp.sk));
@@ -252,3 +262,49 @@ fn attestation_that_skips_epochs() {
         "should process attestation that skips slots"
     );
 }
+
+#[test]
+fn duplicate_attestation_is_already_known() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let head = chain.head().expect("should get head");
+
+    let attestation = harness
+        .get_free_attestations(
+            &AttestationStrategy::AllValidators,
+            &head.beacon_state,
+            head.beacon_block_root,
+            head.beacon_block.slot(),
+        )
+        .first()
+        .cloned()
+        .expect("should get at least one attestation");
+
+    assert_eq!(
+        chain.process_attestation(attestation.clone()),
+        Ok(AttestationProcessingOutcome::Processed),
+        "should process the attestation the first time"
+    );
+    assert_eq!(
+        chain.process_attestation(attestation.clone()),
+        Ok(AttestationProcessingOutcome::AlreadyKnown),
+        "should not process the same attestation twice"
+    );
+
+    // The signature is not checked, since the attestation provides no new information.
+    let mut empty_signature_attestation = attestation;
+    empty_signature_attestation.signature = AggregateSignature::new();
+
+    assert_eq!(
+        chain.process_attestation(empty_signature_attestation),
+        Ok(AttestationProcessingOutcome::AlreadyKnown),
+        "should not verify the signature of a known attestation"
+    );
+}
@@ -4,20 +4,35 @@
 extern crate lazy_static;
 
 use beacon_chain::{
-    test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy},
-    BeaconChain, BeaconChainTypes,
+    builder::BeaconChainBuilder,
+    eth1_chain::SszEth1,
+    schema_change::migrate_schema,
+    test_utils::{
+        op_pool_delta_key, AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType,
+        BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, FORK_CHOICE_DB_KEY, OP_POOL_DB_KEY,
+    },
+    AttestationProcessingOutcome, BeaconChain, BeaconChainTypes,
 };
+use eth1::{Config as Eth1Config, Service as Eth1Service, SszEth1Cache};
 use sloggers::{null::NullLoggerBuilder, Build};
+use ssz::Decode;
 use std::sync::Arc;
-use store::{DiskStore, StoreConfig};
+use store::{
+    migrate::BlockingMigrator, DBColumn, DiskStore, Migrate, SchemaVersion, Store, StoreConfig,
+    CURRENT_SCHEMA_VERSION,
+};
 use tempfile::{tempdir, TempDir};
-use types::{EthSpec, Keypair, MinimalEthSpec};
+use types::{EthSpec, Hash256, Keypair, MinimalEthSpec, DEPOSIT_TREE_DEPTH};
 
 type E = MinimalEthSpec;
 
 // Should ideally be divisible by 3.
 pub const VALIDATOR_COUNT: usize = 24;
 
+/// An `SszEth1` holding three deposits and four eth1 blocks, encoded in the layout of schema
+/// version zero.
+const ETH1_CACHE_V0: &[u8] = include_bytes!("fixtures/eth1_cache_v0.ssz");
+
 lazy_static! {
     /// A cached set of keys.
     static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
@@ -111,11 +126,8 @@ fn finalizes_after_resuming_from_db() {
         AttestationStrategy::AllValidators,
     );
 
-    let state = &resumed_harness
-        .chain
-        .head()
-        .expect("should read head")
-        .beacon_state;
+    let head = resumed_harness.chain.head().expect("should read head");
+    let state = &head.beacon_state;
     assert_eq!(
         state.slot, num_blocks_produced,
         "head should be at the current slot"
@@ -137,6 +149,604 @@ fn finalizes_after_resuming_from_db() {
     );
 }
 
+#[test]
+fn resumes_from_persist_all() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+    );
+
+    harness.advance_slot();
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let persisted_op_pool = || {
+        store
+            .get_bytes(DBColumn::OpPool.into(), &OP_POOL_DB_KEY)
+            .expect("should read op pool")
+    };
+    assert!(
+        persisted_op_pool().is_none(),
+        "the op pool should not be persisted yet"
+    );
+
+    harness
+        .chain
+        .persist_all()
+        .expect("should persist all components");
+
+    assert!(
+        persisted_op_pool().is_some(),
+        "the op pool should be persisted"
+    );
+
+    // Resume whilst the original chain is still alive, so that its `Drop` cannot persist on its
+    // behalf.
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        harness.data_dir,
+    );
+
+    assert_chains_pretty_much_the_same(&harness.chain, &resumed_harness.chain);
+}
+
+#[test]
+fn persists_op_pool_changes_incrementally() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+    );
+
+    harness.advance_slot();
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        // Don't attest to the head, so that attestations to it are not already in the pool.
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let persisted_op_pool = || {
+        store
+            .get_bytes(DBColumn::OpPool.into(), &OP_POOL_DB_KEY)
+            .expect("should read op pool")
+    };
+    let delta_exists = |index| {
+        store
+            .key_exists(
+                DBColumn::OpPoolDeltas.into(),
+                op_pool_delta_key(index).as_bytes(),
+            )
+            .expect("should read op pool delta")
+    };
+
+    // The first persist writes the whole pool.
+    harness
+        .chain
+        .persist_op_pool()
+        .expect("should persist the op pool");
+    let snapshot = persisted_op_pool().expect("the op pool should be persisted");
+    assert!(!delta_exists(0), "no delta should be written");
+
+    let head = harness.chain.head().expect("should get head");
+    let attestation = harness
+        .get_free_attestations(
+            &AttestationStrategy::AllValidators,
+            &head.beacon_state,
+            head.beacon_block_root,
+            head.beacon_block.slot(),
+        )
+        .first()
+        .cloned()
+        .expect("should get an attestation");
+    assert_eq!(
+        harness.chain.process_attestation(attestation),
+        Ok(AttestationProcessingOutcome::Processed),
+        "should process attestation"
+    );
+
+    harness
+        .chain
+        .persist_op_pool()
+        .expect("should persist the op pool");
+    assert_eq!(
+        persisted_op_pool(),
+        Some(snapshot),
+        "the snapshot should not be rewritten"
+    );
+    assert!(delta_exists(0), "a delta should be written");
+    assert!(!delta_exists(1), "only one delta should be written");
+
+    // Resume whilst the original chain is still alive, so that its `Drop` cannot persist on its
+    // behalf.
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        harness.data_dir,
+    );
+
+    assert_eq!(
+        resumed_harness.chain.op_pool, harness.chain.op_pool,
+        "the op pool should be restored from the snapshot and delta"
+    );
+}
+
+/// Builds a chain on `store`, persisting all of its components. Returns the harness' data dir.
+fn build_and_persist_chain(store: Arc<DiskStore<E>>) -> TempDir {
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+    );
+
+    harness.advance_slot();
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    harness
+        .chain
+        .persist_head_and_fork_choice()
+        .expect("should persist the head and fork choice");
+    harness
+        .chain
+        .persist_op_pool()
+        .expect("should persist the op pool");
+    harness
+        .chain
+        .persist_eth1_cache()
+        .expect("should persist the eth1 cache");
+
+    harness.data_dir
+}
+
+/// Overwrites the item at `key` in `column` with bytes that are not valid SSZ for any container.
+fn corrupt_container(store: &DiskStore<E>, column: DBColumn, key: &[u8]) {
+    store
+        .put_bytes(column.into(), key, &[0xff; 3])
+        .expect("should overwrite persisted container");
+}
+
+fn resume_builder(
+    store: Arc<DiskStore<E>>,
+    data_dir: &TempDir,
+) -> BeaconChainBuilder<DiskHarnessType<E>> {
+    let log = NullLoggerBuilder.build().expect("logger should build");
+
+    BeaconChainBuilder::new(MinimalEthSpec)
+        .logger(log)
+        .custom_spec(E::default_spec())
+        .store(store.clone())
+        .store_migrator(<BlockingMigrator<_> as Migrate<_, E>>::new(store))
+        .data_dir(data_dir.path().to_path_buf())
+}
+
+#[test]
+fn resumes_with_empty_op_pool_after_decode_failure() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let data_dir = build_and_persist_chain(store.clone());
+
+    corrupt_container(&store, DBColumn::OpPool, &OP_POOL_DB_KEY);
+
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        data_dir,
+    );
+
+    assert_eq!(
+        resumed_harness.chain.op_pool.num_attestations(),
+        0,
+        "the op pool should be empty"
+    );
+    assert_eq!(
+        resumed_harness
+            .chain
+            .head()
+            .expect("should read head")
+            .beacon_state
+            .slot,
+        MinimalEthSpec::slots_per_epoch() * 2,
+        "the head should be restored"
+    );
+}
+
+#[test]
+fn ignores_eth1_cache_after_decode_failure() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let data_dir = build_and_persist_chain(store.clone());
+
+    assert!(
+        resume_builder(store.clone(), &data_dir)
+            .get_persisted_eth1_backend()
+            .expect("should read eth1 cache")
+            .is_some(),
+        "the eth1 cache should be persisted"
+    );
+
+    corrupt_container(&store, DBColumn::Eth1Cache, &ETH1_CACHE_DB_KEY);
+
+    assert!(
+        resume_builder(store, &data_dir)
+            .get_persisted_eth1_backend()
+            .expect("should not error on undecodable eth1 cache")
+            .is_none(),
+        "the undecodable eth1 cache should be discarded"
+    );
+}
+
+#[test]
+fn fails_to_resume_after_fork_choice_decode_failure() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let data_dir = build_and_persist_chain(store.clone());
+
+    corrupt_container(&store, DBColumn::ForkChoice, &FORK_CHOICE_DB_KEY);
+
+    let error = resume_builder(store, &data_dir)
+        .resume_from_db()
+        .expect("should resume beacon chain from db")
+        .reduced_tree_fork_choice()
+        .err()
+        .expect("should fail to restore fork choice");
+
+    assert!(
+        error.contains("Unable to decode the persisted fork choice"),
+        "error should name the fork choice: {}",
+        error
+    );
+}
+
+#[test]
+fn fails_to_resume_after_beacon_chain_decode_failure() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let data_dir = build_and_persist_chain(store.clone());
+
+    corrupt_container(&store, DBColumn::BeaconChain, &BEACON_CHAIN_DB_KEY);
+
+    let error = resume_builder(store, &data_dir)
+        .resume_from_db()
+        .err()
+        .expect("should fail to restore the beacon chain");
+
+    assert!(
+        error.contains("Unable to decode the persisted beacon chain"),
+        "error should name the beacon chain: {}",
+        error
+    );
+}
+
+#[test]
+fn migrates_unversioned_database_in_place() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    // The harness does not stamp a schema version, so it writes a version zero database.
+    let data_dir = build_and_persist_chain(store.clone());
+
+    assert_eq!(
+        store.get_schema_version(),
+        Ok(SchemaVersion(0)),
+        "an unversioned database should be at version zero"
+    );
+
+    migrate_schema(store.clone(), SchemaVersion(0), CURRENT_SCHEMA_VERSION)
+        .expect("should migrate database");
+
+    assert_eq!(
+        store.get_schema_version(),
+        Ok(CURRENT_SCHEMA_VERSION),
+        "the database should be stamped with the current version"
+    );
+    assert_eq!(
+        migrate_schema(
+            store.clone(),
+            CURRENT_SCHEMA_VERSION,
+            CURRENT_SCHEMA_VERSION
+        ),
+        Ok(()),
+        "migrating to the same version should be a no-op"
+    );
+
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        data_dir,
+    );
+
+    assert_eq!(
+        resumed_harness
+            .chain
+            .head()
+            .expect("should read head")
+            .beacon_state
+            .slot,
+        MinimalEthSpec::slots_per_epoch() * 2,
+        "the migrated database should resume"
+    );
+}
+
+#[test]
+fn migrates_v0_eth1_cache() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let key = Hash256::from_slice(&ETH1_CACHE_DB_KEY);
+
+    store
+        .put_bytes(DBColumn::Eth1Cache.into(), key.as_bytes(), ETH1_CACHE_V0)
+        .expect("should store the v0 eth1 cache");
+
+    migrate_schema(store.clone(), SchemaVersion(0), SchemaVersion(1))
+        .expect("should migrate database");
+
+    let ssz_eth1 = store
+        .get::<SszEth1>(&key)
+        .expect("should read the eth1 cache")
+        .expect("the eth1 cache should exist");
+    assert!(
+        SszEth1Cache::from_ssz_bytes(ssz_eth1.backend_bytes()).is_ok(),
+        "the eth1 cache should be stored in the current layout"
+    );
+
+    let log = NullLoggerBuilder.build().expect("logger should build");
+    let service = Eth1Service::from_bytes(ssz_eth1.backend_bytes(), Eth1Config::default(), log)
+        .expect("should load the migrated eth1 cache");
+
+    assert_eq!(service.deposit_cache_len(), 3);
+    assert_eq!(service.block_cache_len(), 4);
+    assert_eq!(service.deposits().read().last_processed_block, Some(12));
+
+    let latest_block = service
+        .blocks()
+        .read()
+        .iter()
+        .last()
+        .cloned()
+        .expect("should have blocks");
+    let (deposit_root, deposits) = service
+        .deposits()
+        .read()
+        .cache
+        .get_deposits(0, 3, 3, DEPOSIT_TREE_DEPTH)
+        .expect("should get all deposits");
+
+    assert_eq!(deposits.len(), 3);
+    assert_eq!(
+        Some(deposit_root),
+        latest_block.deposit_root,
+        "the rebuilt deposit tree should match the root of the eth1 block"
+    );
+}
+
+#[test]
+fn refuses_to_migrate_newer_database() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let newer_version = SchemaVersion(CURRENT_SCHEMA_VERSION.as_u64() + 1);
+
+    store
+        .put_schema_version(newer_version)
+        .expect("should store schema version");
+
+    assert!(
+        migrate_schema(store.clone(), newer_version, CURRENT_SCHEMA_VERSION).is_err(),
+        "should refuse to open a database from a newer version"
+    );
+    assert_eq!(
+        store.get_schema_version(),
+        Ok(newer_version),
+        "the database should not be modified"
+    );
+}
+
+#[test]
+fn admin_history_survives_restart() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    // Build a finalized chain from which to export a snapshot.
+    let source_harness =
+        BeaconChainHarness::new(MinimalEthSpec, KEYPAIRS[0..VALIDATOR_COUNT].to_vec());
+    source_harness.advance_slot();
+    source_harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 5,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let mut snapshot = vec![];
+    source_harness
+        .chain
+        .export_snapshot(&mut snapshot)
+        .expect("should export snapshot");
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+    );
+    while harness.chain.slot().expect("should get slot")
+        < source_harness.chain.slot().expect("should get slot")
+    {
+        harness.advance_slot();
+    }
+
+    let imported = harness
+        .chain
+        .import_snapshot(&mut snapshot.as_slice())
+        .expect("should import snapshot");
+    assert!(imported > 0, "should import blocks");
+    assert_eq!(
+        harness.chain.import_snapshot(&mut snapshot.as_slice()),
+        Ok(0),
+        "should skip known blocks"
+    );
+
+    harness
+        .chain
+        .persist_head_and_fork_choice()
+        .expect("should persist the head and fork choice");
+
+    let data_dir = harness.data_dir;
+    drop(harness.chain);
+
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        data_dir,
+    );
+    let chain = &resumed_harness.chain;
+
+    let history = chain.admin_history(10).expect("should read admin history");
+    let actions = history
+        .iter()
+        .map(|event| event.action.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(actions, vec!["import_snapshot", "import_snapshot"]);
+    assert!(
+        history[0]
+            .parameters
+            .starts_with(&format!("imported_blocks: {},", imported)),
+        "parameters should be recorded"
+    );
+    assert!(
+        history[1].parameters.starts_with("imported_blocks: 0,"),
+        "parameters should be recorded"
+    );
+    assert_eq!(
+        chain.stats().admin_events,
+        2,
+        "stats should count the events"
+    );
+    assert_eq!(
+        chain
+            .admin_history(1)
+            .expect("should read admin history")
+            .into_iter()
+            .map(|event| event.parameters)
+            .collect::<Vec<_>>(),
+        vec![history[1].parameters.clone()],
+        "should return the latest events"
+    );
+}
+
+#[test]
+fn restores_fork_choice_votes_from_op_pool() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    let harness = BeaconChainHarness::new_with_disk_store(
+        MinimalEthSpec,
+        store.clone(),
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+    );
+
+    harness.advance_slot();
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    // Persist fork choice before it has seen any votes, simulating a node which is shut down
+    // without persisting fork choice after receiving the attestations.
+    harness
+        .chain
+        .persist_head_and_fork_choice()
+        .expect("should persist the head and fork choice");
+    let fork_choice_bytes = store
+        .get_bytes(DBColumn::ForkChoice.into(), &FORK_CHOICE_DB_KEY)
+        .expect("should read fork choice")
+        .expect("fork choice should be persisted");
+
+    let head = harness.chain.head().expect("should read head");
+    let head_block_root = head.beacon_block_root;
+
+    for attestation in harness.get_free_attestations(
+        &AttestationStrategy::AllValidators,
+        &head.beacon_state,
+        head_block_root,
+        head.beacon_block.slot(),
+    ) {
+        assert_eq!(
+            harness.chain.process_attestation(attestation),
+            Ok(AttestationProcessingOutcome::Processed),
+            "should process attestation"
+        );
+    }
+
+    harness.chain.fork_choice().expect("should run fork choice");
+
+    let latest_messages = (0..VALIDATOR_COUNT)
+        .map(|i| harness.chain.fork_choice.latest_message(i))
+        .collect::<Vec<_>>();
+    let head_weight = harness.chain.fork_choice.block_weight(&head_block_root);
+
+    assert!(
+        latest_messages.iter().all(Option::is_some),
+        "all validators should have voted"
+    );
+
+    let data_dir = harness.data_dir;
+    drop(harness.chain);
+
+    store
+        .put_bytes(
+            DBColumn::ForkChoice.into(),
+            &FORK_CHOICE_DB_KEY,
+            &fork_choice_bytes,
+        )
+        .expect("should restore the vote-less fork choice");
+
+    let resumed_harness = BeaconChainHarness::resume_from_disk_store(
+        MinimalEthSpec,
+        store,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        data_dir,
+    );
+    let chain = &resumed_harness.chain;
+
+    assert_eq!(
+        (0..VALIDATOR_COUNT)
+            .map(|i| chain.fork_choice.latest_message(i))
+            .collect::<Vec<_>>(),
+        latest_messages,
+        "latest messages should be restored"
+    );
+
+    chain.fork_choice().expect("should run fork choice");
+
+    assert_eq!(
+        chain.fork_choice.block_weight(&head_block_root),
+        head_weight,
+        "the weight of the head should be restored"
+    );
+}
+
 /// Checks that two chains are the same, for the purpose of this tests.
 ///
 /// Several fields that are hard/impossible to check are ignored (e.g., the store).
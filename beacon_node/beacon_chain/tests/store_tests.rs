@@ -4,7 +4,7 @@
 extern crate lazy_static;
 
 use beacon_chain::test_utils::{
-    AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType,
+    collect_roots, AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType,
 };
 use beacon_chain::AttestationProcessingOutcome;
 use rand::Rng;
@@ -100,7 +100,8 @@ fn randomised_skips() {
         }
     }
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     assert_eq!(state.slot, num_slots, "head should be at the current slot");
 
@@ -382,9 +383,11 @@ fn delete_blocks_and_states() {
         .expect("no db error")
         .expect("faulty head state exists");
 
-    let states_to_delete = StateRootsIterator::new(store.clone(), &faulty_head_state)
-        .take_while(|(_, slot)| *slot > unforked_blocks)
-        .collect::<Vec<_>>();
+    let states_to_delete =
+        collect_roots(StateRootsIterator::new(store.clone(), &faulty_head_state))
+            .into_iter()
+            .take_while(|(_, slot)| *slot > unforked_blocks)
+            .collect::<Vec<_>>();
 
     // Delete faulty fork
     // Attempting to load those states should find them unavailable
@@ -399,10 +402,12 @@ fn delete_blocks_and_states() {
     }
 
     // Deleting the blocks from the fork should remove them completely
-    let blocks_to_delete = BlockRootsIterator::new(store.clone(), &faulty_head_state)
-        // Extra +1 here accounts for the skipped slot that started this fork
-        .take_while(|(_, slot)| *slot > unforked_blocks + 1)
-        .collect::<Vec<_>>();
+    let blocks_to_delete =
+        collect_roots(BlockRootsIterator::new(store.clone(), &faulty_head_state))
+            .into_iter()
+            // Extra +1 here accounts for the skipped slot that started this fork
+            .take_while(|(_, slot)| *slot > unforked_blocks + 1)
+            .collect::<Vec<_>>();
 
     for (block_root, _) in blocks_to_delete {
         assert_eq!(store.delete_block(&block_root), Ok(()));
@@ -411,11 +416,10 @@ fn delete_blocks_and_states() {
 
     // Deleting frozen states should do nothing
     let split_slot = store.get_split_slot();
-    let finalized_states = harness
-        .chain
-        .rev_iter_state_roots()
-        .expect("rev iter ok")
-        .filter(|(_, slot)| *slot < split_slot);
+    let finalized_states =
+        collect_roots(harness.chain.rev_iter_state_roots().expect("rev iter ok"))
+            .into_iter()
+            .filter(|(_, slot)| *slot < split_slot);
 
     for (state_root, slot) in finalized_states {
         assert_eq!(store.delete_state(&state_root, slot), Ok(()));
@@ -427,7 +431,8 @@ fn delete_blocks_and_states() {
 
 /// Check that the head state's slot matches `expected_slot`.
 fn check_slot(harness: &TestHarness, expected_slot: u64) {
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     assert_eq!(
         state.slot, expected_slot,
@@ -437,7 +442,8 @@ fn check_slot(harness: &TestHarness, expected_slot: u64) {
 
 /// Check that the chain has finalized under best-case assumptions, and check the head slot.
 fn check_finalization(harness: &TestHarness, expected_slot: u64) {
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     check_slot(harness, expected_slot);
 
@@ -505,14 +511,16 @@ fn check_chain_dump(harness: &TestHarness, expected_len: u64) {
         .collect::<Vec<_>>();
 
     let head = harness.chain.head().expect("should get head");
-    let mut forward_block_roots = Store::forwards_block_roots_iterator(
-        harness.chain.store.clone(),
-        Slot::new(0),
-        head.beacon_state,
-        head.beacon_block_root,
-        &harness.spec,
-    )
-    .collect::<Vec<_>>();
+    let mut forward_block_roots = collect_roots(
+        Store::forwards_block_roots_iterator(
+            harness.chain.store.clone(),
+            Slot::new(0),
+            head.beacon_state.clone(),
+            head.beacon_block_root,
+            &harness.spec,
+        )
+        .expect("should get forwards iter"),
+    );
 
     // Drop the block roots for skipped slots.
     forward_block_roots.dedup_by_key(|(block_root, _)| *block_root);
@@ -530,21 +538,25 @@ fn check_chain_dump(harness: &TestHarness, expected_len: u64) {
 /// Check that state and block root iterators can reach genesis
 fn check_iterators(harness: &TestHarness) {
     assert_eq!(
-        harness
-            .chain
-            .rev_iter_state_roots()
-            .expect("should get iter")
-            .last()
-            .map(|(_, slot)| slot),
+        collect_roots(
+            harness
+                .chain
+                .rev_iter_state_roots()
+                .expect("should get iter")
+        )
+        .last()
+        .map(|(_, slot)| *slot),
         Some(Slot::new(0))
     );
     assert_eq!(
-        harness
-            .chain
-            .rev_iter_block_roots()
-            .expect("should get iter")
-            .last()
-            .map(|(_, slot)| slot),
+        collect_roots(
+            harness
+                .chain
+                .rev_iter_block_roots()
+                .expect("should get iter")
+        )
+        .last()
+        .map(|(_, slot)| *slot),
         Some(Slot::new(0))
     );
 }
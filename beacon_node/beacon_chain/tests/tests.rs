@@ -3,19 +3,39 @@
 #[macro_use]
 extern crate lazy_static;
 
-use beacon_chain::AttestationProcessingOutcome;
 use beacon_chain::{
+    events::{EventHandler, EventKind, EventTopic},
+    latest_messages::LatestMessage,
     test_utils::{
-        AttestationStrategy, BeaconChainHarness, BlockStrategy, HarnessType, OP_POOL_DB_KEY,
+        collect_roots, AttestationStrategy, BeaconChainHarness, BlockStrategy, HarnessType,
+        OP_POOL_DB_KEY,
     },
-    BlockProcessingOutcome,
+    BeaconChainError, BeaconChainMode, BlockProcessingOutcome, BlockProductionError, ErrorCategory,
+    OperationImportError, SnapshotRecord, StateSkipConfig, MAX_BALANCE_HISTORY_EPOCHS,
+    SUMMARY_DELAY_EPOCHS,
 };
+use beacon_chain::{
+    AggregateProcessingOutcome, AttestationProcessingOutcome, DegradedReason, GossipBlockError,
+    Readiness, ReadinessConfig, SyncStatus,
+};
+use futures::{future, Async, Future, Stream};
 use operation_pool::PersistedOperationPool;
+use ssz::{Decode, Encode};
 use state_processing::{
-    per_slot_processing, per_slot_processing::Error as SlotProcessingError, EpochProcessingError,
+    per_block_processing, per_slot_processing, per_slot_processing::Error as SlotProcessingError,
+    BlockSignatureStrategy, EpochProcessingError,
 };
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use store::Store;
-use types::{BeaconStateError, EthSpec, Hash256, Keypair, MinimalEthSpec, RelativeEpoch, Slot};
+use types::test_utils::{
+    build_proposer_slashing, ProposerSlashingTestTask, TestingVoluntaryExitBuilder,
+};
+use types::{
+    AggregateAndProof, AggregateSignature, Attestation, AttestationData, BeaconStateError,
+    Checkpoint, Domain, Epoch, EthSpec, Hash256, Keypair, MinimalEthSpec, PublicKeyBytes,
+    RelativeEpoch, SelectionProof, Signature, SignedBeaconBlock, SignedRoot, Slot,
+};
 
 // Should ideally be divisible by 3.
 pub const VALIDATOR_COUNT: usize = 24;
@@ -25,6 +45,23 @@ lazy_static! {
     static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
 }
 
+/// Returns all events that are immediately available from `receiver`, without blocking.
+fn drain_events<E, S>(receiver: &mut S) -> Vec<EventKind<E>>
+where
+    E: EthSpec,
+    S: Stream<Item = EventKind<E>, Error = ()>,
+{
+    future::lazy(|| {
+        let mut events = vec![];
+        while let Ok(Async::Ready(Some(event))) = receiver.poll() {
+            events.push(event);
+        }
+        Ok::<_, ()>(events)
+    })
+    .wait()
+    .expect("should drain events")
+}
+
 fn get_harness(validator_count: usize) -> BeaconChainHarness<HarnessType<MinimalEthSpec>> {
     let harness = BeaconChainHarness::new(MinimalEthSpec, KEYPAIRS[0..validator_count].to_vec());
 
@@ -37,7 +74,12 @@ fn get_harness(validator_count: usize) -> BeaconChainHarness<HarnessType<Minimal
 fn massive_skips() {
     let harness = get_harness(8);
     let spec = &MinimalEthSpec::default_spec();
-    let mut state = harness.chain.head().expect("should get head").beacon_state;
+    let mut state = harness
+        .chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .clone();
 
     // Run per_slot_processing until it returns an error.
     let error = loop {
@@ -70,16 +112,18 @@ fn iterators() {
         AttestationStrategy::SomeValidators(vec![]),
     );
 
-    let block_roots: Vec<(Hash256, Slot)> = harness
-        .chain
-        .rev_iter_block_roots()
-        .expect("should get iter")
-        .collect();
-    let state_roots: Vec<(Hash256, Slot)> = harness
-        .chain
-        .rev_iter_state_roots()
-        .expect("should get iter")
-        .collect();
+    let block_roots = collect_roots(
+        harness
+            .chain
+            .rev_iter_block_roots()
+            .expect("should get iter"),
+    );
+    let state_roots = collect_roots(
+        harness
+            .chain
+            .rev_iter_state_roots()
+            .expect("should get iter"),
+    );
 
     assert_eq!(
         block_roots.len(),
@@ -162,7 +206,8 @@ fn chooses_fork() {
 
     assert!(honest_head != faulty_head, "forks should be distinct");
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     assert_eq!(
         state.slot,
@@ -181,6 +226,324 @@ fn chooses_fork() {
     );
 }
 
+#[test]
+fn heads_detailed_describes_each_fork() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let two_thirds = (VALIDATOR_COUNT / 3) * 2;
+    let delay = MinimalEthSpec::default_spec().min_attestation_inclusion_delay as usize;
+
+    let honest_validators: Vec<usize> = (0..two_thirds).collect();
+    let faulty_validators: Vec<usize> = (two_thirds..VALIDATOR_COUNT).collect();
+
+    harness.extend_chain(
+        delay + 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let (honest_head, faulty_head) = harness.generate_two_forks_by_skipping_a_block(
+        &honest_validators,
+        &faulty_validators,
+        delay + 1,
+        delay + 2,
+    );
+
+    let details = harness.chain.heads_detailed();
+
+    assert_eq!(
+        details.len(),
+        harness.chain.heads().len(),
+        "should describe every head"
+    );
+
+    for head in &[honest_head, faulty_head] {
+        let detail = details
+            .iter()
+            .find(|detail| detail.block_root == *head)
+            .expect("should describe each fork");
+        let state = harness
+            .chain
+            .get_state(&detail.state_root, Some(detail.slot))
+            .expect("should read state")
+            .expect("should find the state of the head");
+
+        assert_eq!(state.slot, detail.slot, "should have the head slot");
+        assert_eq!(
+            detail.current_justified_checkpoint, state.current_justified_checkpoint,
+            "should have the justified checkpoint of the head state"
+        );
+        assert_eq!(
+            detail.finalized_checkpoint, state.finalized_checkpoint,
+            "should have the finalized checkpoint of the head state"
+        );
+    }
+}
+
+#[test]
+fn fork_choice_weights_sum_across_forks() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let two_thirds = (VALIDATOR_COUNT / 3) * 2;
+    let delay = MinimalEthSpec::default_spec().min_attestation_inclusion_delay as usize;
+
+    let honest_validators: Vec<usize> = (0..two_thirds).collect();
+    let faulty_validators: Vec<usize> = (two_thirds..VALIDATOR_COUNT).collect();
+
+    harness.extend_chain(
+        delay + 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let (honest_head, faulty_head) = harness.generate_two_forks_by_skipping_a_block(
+        &honest_validators,
+        &faulty_validators,
+        delay + 1,
+        delay + 2,
+    );
+
+    // Apply the latest messages to the weights.
+    harness.chain.fork_choice().expect("should run fork choice");
+
+    let balance = harness.spec.max_effective_balance;
+    let nodes = harness.chain.fork_choice_nodes();
+
+    for node in &nodes {
+        let children_weight: u64 = nodes
+            .iter()
+            .filter(|child| child.parent_root == Some(node.root))
+            .map(|child| child.weight)
+            .sum();
+        let direct_votes = (0..VALIDATOR_COUNT)
+            .filter_map(|validator_index| harness.chain.latest_message(validator_index))
+            .filter(|message| message.root == node.root)
+            .count() as u64;
+
+        assert_eq!(
+            node.weight,
+            children_weight + direct_votes * balance,
+            "the weight of a block should be its own votes plus the weight of its children"
+        );
+        assert_eq!(
+            harness.chain.block_weight(&node.root),
+            Some(node.weight),
+            "should report the weight of each block"
+        );
+    }
+
+    let voters = (0..VALIDATOR_COUNT)
+        .filter(|validator_index| harness.chain.latest_message(*validator_index).is_some())
+        .count() as u64;
+    assert_eq!(
+        nodes[0].weight,
+        voters * balance,
+        "every vote should count towards the root"
+    );
+    assert!(
+        nodes.iter().any(|node| node.root == honest_head)
+            && nodes.iter().any(|node| node.root == faulty_head),
+        "both forks should be reported"
+    );
+    assert_eq!(
+        nodes[0].best_descendant_root,
+        Some(
+            harness
+                .chain
+                .head_info()
+                .expect("should get head")
+                .block_root
+        ),
+        "the head should be the best descendant of the root"
+    );
+}
+
+#[test]
+fn is_canonical_distinguishes_orphaned_blocks() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let two_thirds = (VALIDATOR_COUNT / 3) * 2;
+    let delay = MinimalEthSpec::default_spec().min_attestation_inclusion_delay as usize;
+
+    let honest_validators: Vec<usize> = (0..two_thirds).collect();
+    let faulty_validators: Vec<usize> = (two_thirds..VALIDATOR_COUNT).collect();
+
+    harness.extend_chain(
+        delay + 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let (honest_head, faulty_head) = harness.generate_two_forks_by_skipping_a_block(
+        &honest_validators,
+        &faulty_validators,
+        delay + 1,
+        delay + 2,
+    );
+
+    let is_canonical = |block_root: &Hash256| {
+        harness
+            .chain
+            .is_canonical(block_root)
+            .expect("should check block root")
+    };
+
+    assert!(is_canonical(&honest_head), "the head should be canonical");
+    assert!(
+        !is_canonical(&faulty_head),
+        "the minority fork should not be canonical"
+    );
+
+    // Extend the chain until the forks are finalized and no longer in the block roots of the
+    // head state.
+    harness.advance_slot();
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_historical_root() + MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_info = harness.chain.head_info().expect("should get head info");
+    assert!(
+        harness
+            .chain
+            .get_block(&faulty_head)
+            .expect("should read block")
+            .expect("should find orphaned block")
+            .slot()
+            < head_info
+                .finalized_checkpoint
+                .epoch
+                .start_slot(MinimalEthSpec::slots_per_epoch()),
+        "the forks should be finalized"
+    );
+
+    assert!(
+        is_canonical(&head_info.block_root),
+        "the head should be canonical"
+    );
+    assert!(
+        is_canonical(&honest_head),
+        "a finalized block on the canonical chain should be canonical"
+    );
+    assert!(
+        is_canonical(&harness.chain.genesis_block_root),
+        "the genesis block should be canonical"
+    );
+    assert!(
+        !is_canonical(&faulty_head),
+        "a finalized but orphaned block should not be canonical"
+    );
+    assert!(
+        !is_canonical(&Hash256::repeat_byte(42)),
+        "an unknown block should not be canonical"
+    );
+}
+
+#[test]
+fn produces_attestation_at_non_canonical_head() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let two_thirds = (VALIDATOR_COUNT / 3) * 2;
+    let delay = MinimalEthSpec::default_spec().min_attestation_inclusion_delay as usize;
+
+    let honest_validators: Vec<usize> = (0..two_thirds).collect();
+    let faulty_validators: Vec<usize> = (two_thirds..VALIDATOR_COUNT).collect();
+
+    harness.extend_chain(
+        delay + 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let (honest_head, faulty_head) = harness.generate_two_forks_by_skipping_a_block(
+        &honest_validators,
+        &faulty_validators,
+        delay + 1,
+        delay + 2,
+    );
+
+    let slot = harness.chain.slot().expect("should get slot");
+
+    let canonical = harness
+        .chain
+        .produce_attestation(slot, 0)
+        .expect("should produce attestation on the canonical head");
+    assert_eq!(canonical.data.beacon_block_root, honest_head);
+
+    let minority = harness
+        .chain
+        .produce_attestation_at_head(slot, 0, faulty_head)
+        .expect("should produce attestation on the faulty head");
+    assert_eq!(minority.data.beacon_block_root, faulty_head);
+    assert_eq!(minority.data.slot, slot);
+    assert_eq!(minority.data.source, canonical.data.source);
+
+    let unknown_root = Hash256::from_low_u64_be(42);
+    assert_eq!(
+        harness
+            .chain
+            .produce_attestation_at_head(slot, 0, unknown_root),
+        Err(BeaconChainError::UnknownHeadBlock(unknown_root)),
+        "should not attest to a block unknown to fork choice"
+    );
+}
+
+#[test]
+fn target_checkpoint_with_skip_slot_at_epoch_boundary() {
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    // Build blocks up to the last slot of the first epoch, then skip the first slot of the next.
+    let last_block_root = harness.extend_chain(
+        slots_per_epoch as usize - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+    harness.advance_slot();
+    let head_root = harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    assert_eq!(
+        harness
+            .chain
+            .head_info()
+            .expect("should get head info")
+            .slot,
+        Slot::new(slots_per_epoch + 1),
+        "should skip the first slot of the second epoch"
+    );
+
+    assert_eq!(
+        harness.chain.target_checkpoint(Epoch::new(0)),
+        Ok(Checkpoint {
+            epoch: Epoch::new(0),
+            root: harness.chain.genesis_block_root,
+        }),
+        "the genesis block should be the first target"
+    );
+    assert_eq!(
+        harness.chain.target_checkpoint(Epoch::new(1)),
+        Ok(Checkpoint {
+            epoch: Epoch::new(1),
+            root: last_block_root,
+        }),
+        "a skipped start slot should use the most recent prior block"
+    );
+    assert_eq!(
+        harness.chain.target_checkpoint(Epoch::new(2)),
+        Ok(Checkpoint {
+            epoch: Epoch::new(2),
+            root: head_root,
+        }),
+        "a future epoch should use the head block"
+    );
+}
+
 #[test]
 fn finalizes_with_full_participation() {
     let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
@@ -193,7 +556,8 @@ fn finalizes_with_full_participation() {
         AttestationStrategy::AllValidators,
     );
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     assert_eq!(
         state.slot, num_blocks_produced,
@@ -216,6 +580,85 @@ fn finalizes_with_full_participation() {
     );
 }
 
+#[test]
+fn estimates_attestation_reward_with_full_participation() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 3 + 2;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let slot = harness.chain.slot().expect("should get slot");
+    let estimate = harness
+        .chain
+        .estimate_attestation_reward(0, slot)
+        .expect("should estimate reward");
+
+    // Every validator has an effective balance of 32 ETH, for a total active balance of 768 ETH,
+    // so the base reward is `32e9 * 64 / integer_sqrt(768e9) / 4`.
+    let base_reward = 584_237;
+    assert_eq!(estimate.base_reward, base_reward, "base reward");
+
+    // Every validator attested correctly in the previous epoch, so the rewards are not scaled.
+    assert_eq!(estimate.source_reward, base_reward, "source reward");
+    assert_eq!(estimate.target_reward, base_reward, "target reward");
+    assert_eq!(estimate.head_reward, base_reward, "head reward");
+    assert_eq!(
+        estimate.inclusion_delay_reward,
+        base_reward - base_reward / 8,
+        "inclusion delay reward should exclude the proposer reward"
+    );
+    assert_eq!(
+        estimate.total(),
+        base_reward * 3 + base_reward - base_reward / 8,
+        "total reward"
+    );
+
+    assert_eq!(
+        harness
+            .chain
+            .estimate_attestation_reward(VALIDATOR_COUNT, slot),
+        Err(BeaconChainError::ValidatorNotActive {
+            validator_index: VALIDATOR_COUNT,
+            epoch: slot.epoch(MinimalEthSpec::slots_per_epoch()),
+        }),
+        "should not estimate a reward for an unknown validator"
+    );
+}
+
+#[test]
+fn fork_choice_histograms_are_recorded() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let families = lighthouse_metrics::gather();
+    let sample_count = |name: &str| {
+        families
+            .iter()
+            .find(|family| family.get_name() == name)
+            .and_then(|family| family.get_metric().first())
+            .map_or(0, |metric| metric.get_histogram().get_sample_count())
+    };
+
+    for name in &[
+        "beacon_fork_choice_find_head_seconds",
+        "beacon_fork_choice_find_head_nodes",
+        "beacon_fork_choice_process_block_seconds",
+        "beacon_fork_choice_process_attestation_seconds",
+    ] {
+        assert!(sample_count(name) > 0, "{} should have samples", name);
+    }
+}
+
 #[test]
 fn finalizes_with_two_thirds_participation() {
     let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
@@ -231,7 +674,8 @@ fn finalizes_with_two_thirds_participation() {
         AttestationStrategy::SomeValidators(attesters),
     );
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     assert_eq!(
         state.slot, num_blocks_produced,
@@ -275,7 +719,8 @@ fn does_not_finalize_with_less_than_two_thirds_participation() {
         AttestationStrategy::SomeValidators(attesters),
     );
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     assert_eq!(
         state.slot, num_blocks_produced,
@@ -297,35 +742,126 @@ fn does_not_finalize_with_less_than_two_thirds_participation() {
 }
 
 #[test]
-fn does_not_finalize_without_attestation() {
+fn fork_choice_is_pruned_at_finalization() {
     let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
 
     let harness = get_harness(VALIDATOR_COUNT);
+    harness.chain.fork_choice.set_prune_threshold(0);
 
     harness.extend_chain(
         num_blocks_produced as usize,
         BlockStrategy::OnCanonicalHead,
-        AttestationStrategy::SomeValidators(vec![]),
+        AttestationStrategy::AllValidators,
     );
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
-
-    assert_eq!(
-        state.slot, num_blocks_produced,
-        "head should be at the current slot"
+    let head_info = harness.chain.head_info().expect("should get head info");
+    assert!(
+        head_info.finalized_checkpoint.epoch > 0,
+        "the chain should have finalized"
     );
-    assert_eq!(
-        state.current_epoch(),
-        num_blocks_produced / MinimalEthSpec::slots_per_epoch(),
-        "head should be at the expected epoch"
+
+    let nodes = harness.chain.fork_choice_nodes();
+    assert!(
+        nodes[0].root != harness.chain.genesis_block_root,
+        "the genesis block should have been pruned"
     );
     assert_eq!(
-        state.current_justified_checkpoint.epoch, 0,
-        "no epoch should have been justified"
+        nodes[0].parent_root, None,
+        "the oldest block should not refer to a pruned parent"
     );
     assert_eq!(
-        state.finalized_checkpoint.epoch, 0,
-        "no epoch should have been finalized"
+        nodes.len() as u64,
+        head_info.slot.as_u64() - nodes[0].slot.as_u64() + 1,
+        "only blocks from the oldest retained block onwards should be kept"
+    );
+    harness
+        .chain
+        .fork_choice
+        .verify_invariants()
+        .expect("invariants should hold after pruning");
+
+    for validator_index in 0..VALIDATOR_COUNT {
+        if let Some(message) = harness.chain.latest_message(validator_index) {
+            assert!(
+                harness.chain.fork_choice.contains_block(&message.root),
+                "no latest message should refer to a pruned block"
+            );
+        }
+    }
+}
+
+#[test]
+fn op_pool_is_pruned_without_finality() {
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+    let num_blocks_produced = slots_per_epoch * 5;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let two_thirds = (VALIDATOR_COUNT / 3) * 2;
+    let attesters: Vec<usize> = (0..two_thirds - 1).collect();
+
+    let mut max_pool_size = 0;
+    for _ in 0..num_blocks_produced {
+        harness.extend_chain(
+            1,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::SomeValidators(attesters.clone()),
+        );
+        max_pool_size = max_pool_size.max(harness.chain.op_pool.num_attestations());
+        harness.advance_slot();
+    }
+
+    assert_eq!(
+        harness
+            .chain
+            .head_info()
+            .expect("should get head info")
+            .finalized_checkpoint
+            .epoch,
+        0,
+        "no epoch should have been finalized"
+    );
+
+    // There is a single committee per slot, so each slot contributes one aggregate attestation.
+    // Without pruning, the pool would hold an attestation for every slot.
+    assert!(
+        max_pool_size <= 2 * slots_per_epoch as usize + 1,
+        "pool should only hold attestations that may still be included, not {}",
+        max_pool_size
+    );
+}
+
+#[test]
+fn does_not_finalize_without_attestation() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
+
+    assert_eq!(
+        state.slot, num_blocks_produced,
+        "head should be at the current slot"
+    );
+    assert_eq!(
+        state.current_epoch(),
+        num_blocks_produced / MinimalEthSpec::slots_per_epoch(),
+        "head should be at the expected epoch"
+    );
+    assert_eq!(
+        state.current_justified_checkpoint.epoch, 0,
+        "no epoch should have been justified"
+    );
+    assert_eq!(
+        state.finalized_checkpoint.epoch, 0,
+        "no epoch should have been finalized"
     );
 }
 
@@ -349,7 +885,12 @@ fn roundtrip_operation_pool() {
         .persist_op_pool()
         .expect("should persist op pool");
 
-    let head_state = harness.chain.head().expect("should get head").beacon_state;
+    let head_state = harness
+        .chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .clone();
 
     let key = Hash256::from_slice(&OP_POOL_DB_KEY);
     let restored_op_pool = harness
@@ -375,7 +916,8 @@ fn free_attestations_added_to_fork_choice_some_none() {
         AttestationStrategy::AllValidators,
     );
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
     let fork_choice = &harness.chain.fork_choice;
 
     let validator_slots: Vec<(usize, Slot)> = (0..VALIDATOR_COUNT)
@@ -480,7 +1022,8 @@ fn free_attestations_added_to_fork_choice_all_updated() {
         AttestationStrategy::AllValidators,
     );
 
-    let state = &harness.chain.head().expect("should get head").beacon_state;
+    let head = harness.chain.head().expect("should get head");
+    let state = &head.beacon_state;
     let fork_choice = &harness.chain.fork_choice;
 
     let validators: Vec<usize> = (0..VALIDATOR_COUNT).collect();
@@ -595,3 +1138,3340 @@ fn produces_and_processes_with_genesis_skip_slots() {
         run_skip_slot_test(i)
     }
 }
+
+#[test]
+fn attester_duties_match_committees() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let validator_indices = (0..VALIDATOR_COUNT).collect::<Vec<_>>();
+    let head_state = harness
+        .chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .clone();
+    let current_epoch = harness.chain.epoch().expect("should get epoch");
+
+    assert_eq!(
+        head_state.current_epoch(),
+        current_epoch,
+        "test requires the head to be in the current epoch"
+    );
+
+    for relative_epoch in &[RelativeEpoch::Current, RelativeEpoch::Next] {
+        let epoch = relative_epoch.into_epoch(current_epoch);
+
+        let duties = harness
+            .chain
+            .attester_duties(epoch, &validator_indices)
+            .expect("should get attester duties");
+
+        assert_eq!(
+            harness.chain.attestation_duties(
+                epoch,
+                &validator_indices
+                    .iter()
+                    .map(|i| *i as u64)
+                    .collect::<Vec<_>>()
+            ),
+            Ok(duties.clone()),
+            "should return the same duties for u64 indices"
+        );
+
+        assert_eq!(
+            duties.len(),
+            VALIDATOR_COUNT,
+            "should return a duty per validator"
+        );
+
+        let mut state = head_state.clone();
+        state
+            .build_committee_cache(*relative_epoch, &harness.spec)
+            .expect("should build committee cache");
+
+        let committees = state
+            .get_beacon_committees_at_epoch(*relative_epoch)
+            .expect("should get committees");
+
+        let mut num_duties = 0;
+        for committee in committees {
+            for (position, validator_index) in committee.committee.iter().enumerate() {
+                let duty = duties[*validator_index].expect("active validator should have duty");
+
+                assert_eq!(duty.slot, committee.slot, "slot should match");
+                assert_eq!(duty.index, committee.index, "index should match");
+                assert_eq!(duty.committee_position, position, "position should match");
+                assert_eq!(
+                    duty.committee_len,
+                    committee.committee.len(),
+                    "committee_len should match"
+                );
+
+                num_duties += 1;
+            }
+        }
+
+        assert_eq!(
+            num_duties, VALIDATOR_COUNT,
+            "all validators should have duties"
+        );
+    }
+
+    assert_eq!(
+        harness
+            .chain
+            .attester_duties(current_epoch + 2, &validator_indices),
+        Err(BeaconChainError::AttesterDutiesEpochTooFarAhead {
+            request_epoch: current_epoch + 2,
+            current_epoch,
+        }),
+        "should not return duties more than one epoch ahead"
+    );
+}
+
+#[test]
+fn chain_stats_count_operations() {
+    let num_blocks = MinimalEthSpec::slots_per_epoch() * 2;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().expect("should get head");
+
+    // Re-importing the head block should be ignored, rather than counted as an import.
+    assert_eq!(
+        harness.chain.process_block(head.beacon_block.clone()),
+        Ok(BlockProcessingOutcome::BlockIsAlreadyKnown),
+        "should not re-import block"
+    );
+
+    let stats = harness.chain.stats();
+
+    // With one committee per slot, each validator attests exactly once per epoch.
+    let expected_attestations =
+        num_blocks * VALIDATOR_COUNT as u64 / MinimalEthSpec::slots_per_epoch();
+
+    assert_eq!(stats.blocks_imported, num_blocks, "blocks imported");
+    assert_eq!(stats.blocks_ignored, 1, "blocks ignored");
+    assert_eq!(stats.blocks_invalid, 0, "blocks invalid");
+    assert_eq!(stats.block_errors, 0, "block errors");
+    assert_eq!(stats.produced_blocks_reused, 0, "produced blocks reused");
+    assert_eq!(
+        stats.attestations_processed, expected_attestations,
+        "attestations processed"
+    );
+    assert_eq!(stats.attestations_invalid, 0, "attestations invalid");
+    assert_eq!(stats.head_updates, num_blocks, "head updates");
+    assert_eq!(stats.reorgs, 0, "reorgs");
+}
+
+#[test]
+fn block_proposers_for_epoch_matches_block_proposer() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let current_epoch = harness.chain.epoch().expect("should get epoch");
+
+    let proposers = harness
+        .chain
+        .block_proposers_for_epoch(current_epoch)
+        .expect("should get proposers for epoch");
+
+    assert_eq!(
+        proposers.len() as u64,
+        MinimalEthSpec::slots_per_epoch(),
+        "should return a proposer for each slot"
+    );
+
+    for (slot, proposer_index) in proposers {
+        assert_eq!(
+            slot.epoch(MinimalEthSpec::slots_per_epoch()),
+            current_epoch,
+            "slot should be in the requested epoch"
+        );
+        assert_eq!(
+            harness
+                .chain
+                .block_proposer(slot)
+                .expect("should get block proposer"),
+            proposer_index,
+            "bulk proposer should match single proposer lookup"
+        );
+    }
+
+    assert_eq!(
+        harness.chain.block_proposers_for_epoch(current_epoch + 1),
+        Err(BeaconChainError::ProposerEpochTooFarAhead {
+            request_epoch: current_epoch + 1,
+            current_epoch,
+        }),
+        "should not return proposers for a future epoch"
+    );
+}
+
+#[test]
+fn proposer_duties_lookahead() {
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        slots_per_epoch as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let chain = &harness.chain;
+    let head = chain.head().expect("should get head");
+    let current_epoch = chain.epoch().expect("should get epoch");
+
+    let current_duties = chain
+        .proposer_duties(current_epoch)
+        .expect("should get current proposer duties");
+
+    assert_eq!(
+        current_duties.dependent_root,
+        *head
+            .beacon_state
+            .get_block_root(current_epoch.start_slot(slots_per_epoch) - 1)
+            .expect("should get block root"),
+        "current dependent root should be the last block prior to the epoch"
+    );
+    assert_eq!(
+        current_epoch
+            .slot_iter(slots_per_epoch)
+            .zip(current_duties.proposers.into_iter())
+            .collect::<Vec<_>>(),
+        chain
+            .block_proposers_for_epoch(current_epoch)
+            .expect("should get proposers for epoch"),
+        "current proposers should match block_proposers_for_epoch"
+    );
+
+    let next_epoch = current_epoch + 1;
+    let next_duties = chain
+        .proposer_duties(next_epoch)
+        .expect("should get next proposer duties");
+
+    assert_eq!(next_duties.epoch, next_epoch);
+    assert_eq!(
+        next_duties.dependent_root, head.beacon_block_root,
+        "next epoch proposers should be dependent on the head"
+    );
+
+    let mut state = chain
+        .state_at_slot(
+            next_epoch.start_slot(slots_per_epoch),
+            StateSkipConfig::WithoutStateRoots,
+        )
+        .expect("should get state");
+    state
+        .build_committee_cache(RelativeEpoch::Current, &harness.spec)
+        .expect("should build committee cache");
+
+    let expected_proposers = next_epoch
+        .slot_iter(slots_per_epoch)
+        .map(|slot| {
+            state
+                .get_beacon_proposer_index(slot, &harness.spec)
+                .expect("should get proposer index")
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        next_duties.proposers, expected_proposers,
+        "next epoch proposers should match the skipped state"
+    );
+
+    assert_eq!(
+        chain.proposer_duties(next_epoch + 1),
+        Err(BeaconChainError::ProposerEpochTooFarAhead {
+            request_epoch: next_epoch + 1,
+            current_epoch,
+        }),
+        "should not return proposers beyond the next epoch"
+    );
+}
+
+#[test]
+fn latest_messages_match_processed_attestations() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 2 - 1;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let chain = &harness.chain;
+    let head = chain.head().expect("should get head");
+    let state = &head.beacon_state;
+
+    let messages = chain.latest_messages(0, VALIDATOR_COUNT);
+
+    assert_eq!(
+        messages.len(),
+        VALIDATOR_COUNT,
+        "all validators should have a latest message"
+    );
+
+    for (validator_index, message) in messages.iter().enumerate() {
+        let slot = state
+            .get_attestation_duties(validator_index, RelativeEpoch::Current)
+            .expect("should get attester duties")
+            .unwrap()
+            .slot;
+
+        assert_eq!(
+            message.validator_index, validator_index as u64,
+            "should be ordered by validator index"
+        );
+        assert_eq!(
+            message.epoch,
+            slot.epoch(MinimalEthSpec::slots_per_epoch()),
+            "latest message epoch should be equal to attester duty"
+        );
+
+        if slot != num_blocks_produced {
+            assert_eq!(
+                message.root,
+                *state.get_block_root(slot).expect("should get block root"),
+                "latest message root should be equal to block at slot"
+            );
+        }
+    }
+
+    assert_eq!(
+        chain.latest_messages(5, 7),
+        messages[5..12].to_vec(),
+        "a page should be a slice of the table"
+    );
+    assert_eq!(
+        chain.latest_messages(VALIDATOR_COUNT, 7),
+        vec![],
+        "pages beyond the table should be empty"
+    );
+    assert_eq!(
+        chain.latest_messages_iter(5).collect::<Vec<_>>(),
+        messages,
+        "chunked iteration should return the whole table"
+    );
+
+    let mut csv = vec![];
+    LatestMessage::write_csv(&mut csv, messages).expect("should write csv");
+    let csv = String::from_utf8(csv).expect("csv should be utf8");
+
+    assert_eq!(
+        csv.lines().count(),
+        VALIDATOR_COUNT + 1,
+        "csv should have a header and a row per validator"
+    );
+    assert_eq!(csv.lines().next(), Some(LatestMessage::CSV_HEADER));
+}
+
+#[test]
+fn head_event_stream_receives_only_head_changes() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let mut stream = harness
+        .chain
+        .event_stream(vec![EventTopic::Head].into_iter().collect())
+        .expect("should subscribe to events");
+
+    let mut previous_head = harness
+        .chain
+        .head_info()
+        .expect("should get head info")
+        .block_root;
+
+    for _ in 0..MinimalEthSpec::slots_per_epoch() * 2 {
+        harness.extend_chain(
+            1,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        );
+
+        let new_head = harness
+            .chain
+            .head_info()
+            .expect("should get head info")
+            .block_root;
+
+        let events = drain_events(&mut stream);
+        assert_eq!(events.len(), 1, "should receive one event per block");
+
+        match &events[0] {
+            EventKind::BeaconHeadChanged {
+                reorg,
+                current_head_beacon_block_root,
+                previous_head_beacon_block_root,
+            } => {
+                assert!(!reorg, "should not re-org");
+                assert_eq!(*current_head_beacon_block_root, new_head);
+                assert_eq!(*previous_head_beacon_block_root, previous_head);
+            }
+            other => panic!("should only receive head changes, got {:?}", other),
+        }
+
+        previous_head = new_head;
+        harness.advance_slot();
+    }
+}
+
+#[test]
+fn finalized_checkpoint_event_fires_once_per_finalization() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let mut receiver = harness
+        .chain
+        .event_handler
+        .subscribe()
+        .expect("should subscribe to events");
+
+    let mut finalizations = 0;
+
+    for _ in 0..MinimalEthSpec::slots_per_epoch() * 5 {
+        let old_finalized_checkpoint = harness
+            .chain
+            .head_info()
+            .expect("should get head info")
+            .finalized_checkpoint;
+
+        harness.extend_chain(
+            1,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        );
+
+        let new_finalized_checkpoint = harness
+            .chain
+            .head_info()
+            .expect("should get head info")
+            .finalized_checkpoint;
+
+        let all_events = drain_events(&mut receiver);
+
+        let events = all_events
+            .iter()
+            .filter_map(|event| match event {
+                EventKind::BeaconFinalizedCheckpoint {
+                    checkpoint,
+                    state_root,
+                    slot,
+                } => Some((checkpoint.clone(), *state_root, *slot)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let finalization_events = all_events
+            .iter()
+            .filter_map(|event| match event {
+                EventKind::BeaconFinalization {
+                    epoch,
+                    root,
+                    slot,
+                    state_root,
+                    ..
+                } => Some((*epoch, *root, *slot, *state_root)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if new_finalized_checkpoint.epoch != old_finalized_checkpoint.epoch {
+            assert_eq!(events.len(), 1, "should emit exactly one event");
+            assert_eq!(
+                finalization_events.len(),
+                1,
+                "should emit exactly one finalization event"
+            );
+
+            let finalized_block = harness
+                .chain
+                .get_block(&new_finalized_checkpoint.root)
+                .expect("should read finalized block")
+                .expect("finalized block should exist");
+
+            assert_eq!(
+                events[0],
+                (
+                    new_finalized_checkpoint,
+                    finalized_block.state_root(),
+                    finalized_block.slot()
+                ),
+                "event should describe the new finalized checkpoint"
+            );
+            assert_eq!(
+                finalization_events[0],
+                (
+                    finalized_block
+                        .slot()
+                        .epoch(MinimalEthSpec::slots_per_epoch()),
+                    new_finalized_checkpoint.root,
+                    finalized_block.slot(),
+                    finalized_block.state_root()
+                ),
+                "finalization event should describe the finalized block"
+            );
+
+            finalizations += 1;
+        } else {
+            assert!(events.is_empty(), "should not emit without finalization");
+            assert!(
+                finalization_events.is_empty(),
+                "should not emit finalization without finalization"
+            );
+        }
+
+        harness.advance_slot();
+    }
+
+    assert!(finalizations > 0, "chain should have finalized");
+}
+
+#[test]
+fn proposer_slashing_event_emitted_once_valid() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let mut receiver = harness
+        .chain
+        .event_handler
+        .subscribe()
+        .expect("should subscribe to events");
+
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    drain_events(&mut receiver);
+
+    let fork = harness
+        .chain
+        .head_info()
+        .expect("should get head info")
+        .fork;
+    let validator_index = 0;
+    let build_slashing = |test_task| {
+        build_proposer_slashing::<MinimalEthSpec>(
+            test_task,
+            validator_index,
+            &KEYPAIRS[validator_index as usize].sk,
+            &fork,
+            &harness.spec,
+        )
+    };
+
+    let invalid = build_slashing(ProposerSlashingTestTask::ProposalsIdentical);
+    assert!(
+        harness.chain.process_proposer_slashing(invalid).is_err(),
+        "should reject an invalid slashing"
+    );
+    assert!(
+        drain_events(&mut receiver).is_empty(),
+        "should not emit an event for an invalid slashing"
+    );
+
+    let valid = build_slashing(ProposerSlashingTestTask::Valid);
+    harness
+        .chain
+        .process_proposer_slashing(valid.clone())
+        .expect("should accept a valid slashing");
+
+    let events = drain_events(&mut receiver);
+    assert_eq!(events.len(), 1, "should emit exactly one event");
+    match &events[0] {
+        EventKind::ProposerSlashingReceived { proposer_slashing } => {
+            assert_eq!(**proposer_slashing, valid, "should emit the slashing")
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert_eq!(
+        harness.chain.get_pooled_proposer_slashings(),
+        vec![valid],
+        "should add the slashing to the op pool"
+    );
+}
+
+#[test]
+fn detects_conflicting_blocks_from_one_proposer() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    let slot = head.beacon_block.slot();
+    let proposer_index = harness
+        .chain
+        .block_proposer(slot)
+        .expect("should get block proposer");
+
+    // Produce a second block at the slot of the head, from the same proposer but with different
+    // contents. Its state root no longer matches, but its proposal signature is valid.
+    let state = harness
+        .chain
+        .state_at_slot(slot - 1, StateSkipConfig::WithStateRoots)
+        .expect("should get state prior to the head");
+    let (mut block, _) = harness
+        .chain
+        .produce_block_on_state(
+            state,
+            None,
+            slot,
+            head.beacon_block.message.body.randao_reveal.clone(),
+        )
+        .expect("should produce block");
+    block.body.graffiti = [42; 32];
+    let conflicting_block = block.sign(
+        &KEYPAIRS[proposer_index].sk,
+        &head.beacon_state.fork,
+        &harness.spec,
+    );
+
+    match harness
+        .chain
+        .process_block(conflicting_block)
+        .expect("should process block")
+    {
+        BlockProcessingOutcome::StateRootMismatch { .. } => {}
+        other => panic!("should reject the conflicting block, got {:?}", other),
+    }
+
+    let slashings = harness.chain.get_pooled_proposer_slashings();
+    assert_eq!(slashings.len(), 1, "should add a slashing to the op pool");
+    assert_eq!(
+        slashings[0].proposer_index, proposer_index as u64,
+        "should slash the proposer"
+    );
+
+    harness.advance_slot();
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    assert_eq!(
+        head.beacon_block.message.body.proposer_slashings.to_vec(),
+        slashings,
+        "should include the slashing in the next block"
+    );
+}
+
+#[test]
+fn validator_monitor_finds_validators_by_pubkey() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let monitor = &harness.chain.validator_monitor;
+
+    monitor.add_validator_index(1);
+    monitor.add_validator_pubkey(PublicKeyBytes::from(KEYPAIRS[5].pk.clone()));
+    monitor.add_validator_pubkey(PublicKeyBytes::from(Keypair::random().pk));
+
+    assert_eq!(
+        monitor.validator_indices(),
+        vec![1],
+        "should not know the index of validators added by public key"
+    );
+
+    // Extend the chain across several epochs, so that the attestations of the monitored
+    // validators are summarized.
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 3,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    assert_eq!(
+        monitor.validator_indices(),
+        vec![1, 5],
+        "should find the index of a validator in the registry"
+    );
+}
+
+/// Returns the value of the validator monitor counter `name` for `validator_index`.
+fn validator_monitor_count(name: &str, validator_index: usize) -> u64 {
+    let id = validator_index.to_string();
+    let families = lighthouse_metrics::gather();
+
+    families
+        .iter()
+        .find(|family| family.get_name() == name)
+        .and_then(|family| {
+            family.get_metric().iter().find(|metric| {
+                metric
+                    .get_label()
+                    .iter()
+                    .any(|label| label.get_value() == id)
+            })
+        })
+        .map_or(0, |metric| metric.get_counter().get_value() as u64)
+}
+
+#[test]
+fn validator_monitor_reports_proposals_and_attestations() {
+    // These validators are not monitored by any other test, so their metrics are unaffected by
+    // tests running in parallel.
+    let attester = 2;
+    let absent = 3;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+    chain.validator_monitor.add_validator_index(attester);
+    chain.validator_monitor.add_validator_index(absent);
+
+    let epochs = 4;
+    let num_blocks = MinimalEthSpec::slots_per_epoch() * epochs;
+    harness.extend_chain(
+        num_blocks as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(
+            (0..VALIDATOR_COUNT).filter(|&i| i != absent).collect(),
+        ),
+    );
+
+    let proposals = |validator_index| {
+        (1..=num_blocks)
+            .filter(|&slot| {
+                chain
+                    .block_proposer(Slot::new(slot))
+                    .expect("should get proposer")
+                    == validator_index
+            })
+            .count() as u64
+    };
+    for &validator_index in &[attester, absent] {
+        assert_eq!(
+            validator_monitor_count(
+                "beacon_validator_monitor_beacon_blocks_total",
+                validator_index
+            ),
+            proposals(validator_index),
+            "should count each block proposed by validator {}",
+            validator_index
+        );
+    }
+
+    // The epochs up to `epochs - SUMMARY_DELAY_EPOCHS` are summarized.
+    let summarized_epochs = epochs - SUMMARY_DELAY_EPOCHS + 1;
+
+    assert_eq!(
+        validator_monitor_count(
+            "beacon_validator_monitor_attestations_included_total",
+            absent
+        ),
+        0,
+        "a validator which does not attest should have no inclusions"
+    );
+    assert_eq!(
+        validator_monitor_count("beacon_validator_monitor_attestations_missed_total", absent),
+        summarized_epochs,
+        "a validator which does not attest should miss every summarized epoch"
+    );
+
+    // The attestations of the genesis slot are never produced by the harness, so the attester
+    // may miss the first epoch.
+    let correct_target = validator_monitor_count(
+        "beacon_validator_monitor_attestation_correct_target_total",
+        attester,
+    );
+    let missed = validator_monitor_count(
+        "beacon_validator_monitor_attestations_missed_total",
+        attester,
+    );
+    assert!(missed <= 1, "the attester should only miss the first epoch");
+    assert_eq!(
+        correct_target + missed,
+        summarized_epochs,
+        "every summarized epoch should either be correct or missed"
+    );
+    assert_eq!(
+        validator_monitor_count(
+            "beacon_validator_monitor_attestation_correct_head_total",
+            attester
+        ),
+        correct_target,
+        "the attester always votes for the head"
+    );
+    assert_eq!(
+        validator_monitor_count(
+            "beacon_validator_monitor_attestations_included_total",
+            attester
+        ),
+        correct_target + 1,
+        "the inclusion in the last full epoch should be counted but not yet summarized"
+    );
+}
+
+#[test]
+fn voluntary_exit_rejected_when_invalid() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_info = harness.chain.head_info().expect("should get head info");
+    let validator_index = 0;
+    let exit = TestingVoluntaryExitBuilder::new(
+        head_info.slot.epoch(MinimalEthSpec::slots_per_epoch()),
+        validator_index,
+    )
+    .build(
+        &KEYPAIRS[validator_index as usize].sk,
+        &head_info.fork,
+        &harness.spec,
+    );
+
+    // The validator has not been active for long enough to exit.
+    match harness.chain.process_voluntary_exit(exit) {
+        Err(OperationImportError::Invalid(_)) => {}
+        other => panic!("should reject an early exit, got {:?}", other),
+    }
+    assert!(
+        harness.chain.op_pool.get_all_voluntary_exits().is_empty(),
+        "should not add the exit to the op pool"
+    );
+}
+
+/// Returns an attestation with the given `data`, signed by the validator at `committee_position`
+/// in its committee, along with the index of that validator.
+fn attestation_signed_by(
+    harness: &BeaconChainHarness<HarnessType<MinimalEthSpec>>,
+    data: AttestationData,
+    committee_position: usize,
+) -> (Attestation<MinimalEthSpec>, usize) {
+    let head_state = harness
+        .chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .clone();
+    let validator_index = head_state
+        .get_beacon_committee(data.slot, data.index)
+        .expect("should get committee")
+        .committee[committee_position];
+
+    let mut attestation = harness
+        .chain
+        .produce_attestation(data.slot, data.index)
+        .expect("should produce attestation");
+    attestation.data = data;
+    attestation
+        .sign(
+            &KEYPAIRS[validator_index].sk,
+            committee_position,
+            &head_state.fork,
+            &harness.chain.spec,
+        )
+        .expect("should sign attestation");
+
+    (attestation, validator_index)
+}
+
+#[test]
+fn detects_double_vote() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    let data = harness
+        .chain
+        .produce_attestation(head.beacon_block.slot(), 0)
+        .expect("should produce attestation")
+        .data;
+
+    let (first, validator_index) = attestation_signed_by(&harness, data.clone(), 0);
+    let (second, _) = attestation_signed_by(
+        &harness,
+        AttestationData {
+            beacon_block_root: head.beacon_block.message.parent_root,
+            ..data
+        },
+        0,
+    );
+
+    assert_eq!(
+        harness.chain.process_attestation(first.clone()),
+        Ok(AttestationProcessingOutcome::Processed)
+    );
+    assert!(harness.chain.get_pooled_attester_slashings().is_empty());
+
+    assert_eq!(
+        harness.chain.process_attestation(second.clone()),
+        Ok(AttestationProcessingOutcome::Processed)
+    );
+
+    let slashings = harness.chain.get_pooled_attester_slashings();
+    assert_eq!(slashings.len(), 1, "should detect the double vote");
+    assert_eq!(slashings[0].attestation_1.data, first.data);
+    assert_eq!(slashings[0].attestation_2.data, second.data);
+    assert_eq!(
+        slashings[0].attestation_1.attesting_indices.to_vec(),
+        vec![validator_index as u64]
+    );
+}
+
+#[test]
+fn detects_surround_vote() {
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    // Attest to all blocks prior to the current epoch, so that the chain is justified.
+    harness.extend_chain(
+        (slots_per_epoch * 5) as usize - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    let slot = head.beacon_block.slot();
+    assert_eq!(slot % slots_per_epoch, 0, "head should start an epoch");
+
+    let data = harness
+        .chain
+        .produce_attestation(slot, 0)
+        .expect("should produce attestation")
+        .data;
+    assert!(data.source.epoch > 0, "chain should be justified");
+
+    // Every validator voted during the previous epoch with a source later than genesis, so a vote
+    // with the genesis source surrounds it.
+    let (surrounding, validator_index) = attestation_signed_by(
+        &harness,
+        AttestationData {
+            source: Checkpoint {
+                epoch: Epoch::new(0),
+                root: harness.chain.genesis_block_root,
+            },
+            ..data
+        },
+        0,
+    );
+
+    assert_eq!(
+        harness.chain.process_attestation(surrounding.clone()),
+        Ok(AttestationProcessingOutcome::Processed)
+    );
+
+    let slashings = harness.chain.get_pooled_attester_slashings();
+    assert_eq!(slashings.len(), 1, "should detect the surround vote");
+    assert_eq!(
+        slashings[0].attestation_1.data, surrounding.data,
+        "the surrounding vote should be first"
+    );
+    assert_eq!(
+        slashings[0].attestation_2.data.target.epoch + 1,
+        surrounding.data.target.epoch
+    );
+    assert!(slashings[0]
+        .attestation_2
+        .attesting_indices
+        .iter()
+        .any(|i| *i == validator_index as u64));
+}
+
+#[test]
+fn is_slashable_attestation_checks_observed_votes() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    let data = harness
+        .chain
+        .produce_attestation(head.beacon_block.slot(), 0)
+        .expect("should produce attestation")
+        .data;
+    let conflicting_data = AttestationData {
+        beacon_block_root: head.beacon_block.message.parent_root,
+        ..data.clone()
+    };
+
+    let (attestation, validator_index) = attestation_signed_by(&harness, data.clone(), 0);
+    assert_eq!(
+        harness
+            .chain
+            .is_slashable_attestation(validator_index, &conflicting_data),
+        Ok(false),
+        "should not know of any vote before processing"
+    );
+
+    assert_eq!(
+        harness.chain.process_attestation(attestation),
+        Ok(AttestationProcessingOutcome::Processed)
+    );
+
+    assert_eq!(
+        harness
+            .chain
+            .is_slashable_attestation(validator_index, &data),
+        Ok(false),
+        "repeating a vote is not slashable"
+    );
+    assert_eq!(
+        harness
+            .chain
+            .is_slashable_attestation(validator_index, &conflicting_data),
+        Ok(true),
+        "should detect a double vote"
+    );
+    assert_eq!(
+        harness
+            .chain
+            .is_slashable_attestation(validator_index + 1, &conflicting_data),
+        Ok(false),
+        "should only check the votes of the given validator"
+    );
+    assert!(
+        harness.chain.get_pooled_attester_slashings().is_empty(),
+        "checking should not import a slashing"
+    );
+}
+
+#[test]
+fn dependent_roots_change_after_reorg_across_epoch_boundary() {
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+    let last_slot_of_first_epoch = Slot::new(slots_per_epoch - 1);
+
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    // Build a chain up to (but excluding) the last slot of the first epoch.
+    harness.extend_chain(
+        slots_per_epoch as usize - 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let fork_slot = last_slot_of_first_epoch - 1;
+    let fork_root = harness
+        .chain
+        .head()
+        .expect("should get head")
+        .beacon_block_root;
+
+    // Add a block at the last slot of the first epoch, without any attestations.
+    harness.advance_slot();
+    let original_root = harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let next_epoch = Epoch::new(1);
+
+    assert_eq!(
+        harness.chain.proposer_dependent_root(next_epoch),
+        Ok(original_root),
+        "proposers should depend on the last block of the prior epoch"
+    );
+    assert_eq!(
+        harness.chain.shuffling_dependent_root(next_epoch + 1),
+        Ok(original_root),
+        "shuffling should depend on the last block of the epoch prior to the prior epoch"
+    );
+
+    // Build a heavier fork which skips the last slot of the first epoch.
+    let new_head = harness.extend_chain(
+        3,
+        BlockStrategy::ForkCanonicalChainAt {
+            previous_slot: fork_slot,
+            first_slot: last_slot_of_first_epoch + 1,
+        },
+        AttestationStrategy::AllValidators,
+    );
+
+    assert_eq!(
+        harness
+            .chain
+            .head()
+            .expect("should get head")
+            .beacon_block_root,
+        new_head,
+        "the fork should become the head"
+    );
+
+    assert_eq!(
+        harness.chain.proposer_dependent_root(next_epoch),
+        Ok(fork_root),
+        "proposers should depend on the block prior to the skipped slot"
+    );
+    assert_eq!(
+        harness.chain.shuffling_dependent_root(next_epoch + 1),
+        Ok(fork_root),
+        "shuffling should depend on the block prior to the skipped slot"
+    );
+    assert_eq!(
+        harness
+            .chain
+            .proposer_duties(next_epoch)
+            .expect("should get proposer duties")
+            .dependent_root,
+        fork_root,
+        "proposer duties should report the new dependent root"
+    );
+}
+
+#[test]
+fn production_disabled_whilst_head_in_future() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let chain = &harness.chain;
+    let head_slot = chain.head_info().expect("should get head").slot;
+
+    // Simulate a restart after the system clock has been corrected backwards.
+    let current_slot = head_slot - 2;
+    chain.slot_clock.set_slot(current_slot.as_u64());
+
+    assert_eq!(
+        chain.head_in_future_slots(),
+        Ok(2),
+        "head should be two slots in the future"
+    );
+    assert_eq!(
+        chain.produce_attestation(current_slot, 0),
+        Err(BeaconChainError::HeadInFuture {
+            head_slot,
+            current_slot
+        }),
+        "should not produce attestations whilst the head is in the future"
+    );
+    assert_eq!(
+        chain
+            .produce_block(Signature::empty_signature(), current_slot)
+            .err(),
+        Some(BlockProductionError::BeaconChainError(
+            BeaconChainError::HeadInFuture {
+                head_slot,
+                current_slot
+            }
+        )),
+        "should not produce blocks whilst the head is in the future"
+    );
+
+    // The conservative mode should clear once the clock catches up with the head.
+    chain.slot_clock.set_slot(head_slot.as_u64());
+
+    assert_eq!(
+        chain.head_in_future_slots(),
+        Ok(0),
+        "head should not be in the future"
+    );
+    assert!(
+        chain.produce_attestation(head_slot, 0).is_ok(),
+        "should produce attestations once the clock has caught up"
+    );
+}
+
+#[test]
+fn replays_state_to_unstored_skip_slot() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let skipped_slot = harness.chain.head_info().expect("should get head").slot + 1;
+
+    for _ in 0..3 {
+        harness.advance_slot();
+    }
+
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let chain = &harness.chain;
+    let head = chain.head().expect("should get head");
+    let skipped_state_root = *head
+        .beacon_state
+        .get_state_root(skipped_slot)
+        .expect("should get state root of skipped slot");
+
+    // Remove the skipped state from the database, as if it was never stored.
+    chain
+        .store
+        .delete_state(&skipped_state_root, skipped_slot)
+        .expect("should delete state");
+
+    assert_eq!(
+        chain
+            .state_at_slot(skipped_slot, StateSkipConfig::WithStateRoots)
+            .err(),
+        Some(BeaconChainError::NoStateForSlot(skipped_slot)),
+        "state should not be stored"
+    );
+
+    let state = chain
+        .replay_state_to_slot(skipped_slot, head.beacon_state_root)
+        .expect("should replay state");
+
+    assert_eq!(state.slot, skipped_slot);
+    assert_eq!(
+        state.canonical_root(),
+        skipped_state_root,
+        "replayed state should match the canonical chain"
+    );
+}
+
+#[test]
+fn skips_state_four_epochs_ahead_across_two_calls() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = chain.head().expect("should get head");
+    let target_slot = head.beacon_state.slot + MinimalEthSpec::slots_per_epoch() * 4;
+
+    let mut expected = head.beacon_state.clone();
+    while expected.slot < target_slot {
+        per_slot_processing(&mut expected, None, &chain.spec).expect("should process slot");
+    }
+
+    match chain.state_at_slot_with_deadline(
+        target_slot,
+        StateSkipConfig::WithStateRoots,
+        Instant::now(),
+    ) {
+        Err(e @ BeaconChainError::StateSkipTooLarge { .. }) => assert_eq!(
+            e.kind(),
+            ErrorCategory::ServiceUnavailable,
+            "a skip which runs out of time may be retried"
+        ),
+        other => panic!("first skip should run out of time, got {:?}", other),
+    }
+
+    let state = chain
+        .state_at_slot(target_slot, StateSkipConfig::WithStateRoots)
+        .expect("second skip should reach the target slot");
+
+    assert_eq!(state.slot, target_slot);
+    assert_eq!(
+        state.canonical_root(),
+        expected.canonical_root(),
+        "resumed skip should produce the same state as an uninterrupted skip"
+    );
+}
+
+#[test]
+fn state_skip_resumes_from_partial_progress() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+    let epochs = 4;
+
+    let head_slot = chain.head_info().expect("should get head info").slot;
+    let target_slot = head_slot + MinimalEthSpec::slots_per_epoch() * epochs;
+
+    // Each call makes at least one epoch of progress before noticing that its deadline has
+    // passed, so the target is reached as long as each call resumes from the last.
+    let attempts = (0..=epochs)
+        .position(|_| {
+            chain
+                .state_at_slot_with_deadline(
+                    target_slot,
+                    StateSkipConfig::WithoutStateRoots,
+                    Instant::now(),
+                )
+                .is_ok()
+        })
+        .expect("skip should eventually reach the target slot");
+
+    assert!(
+        attempts > 0,
+        "a single call should not reach the target slot"
+    );
+}
+
+#[test]
+fn refuses_to_skip_more_than_max_skip_slots() {
+    let max_skip_slots = MinimalEthSpec::slots_per_epoch();
+    let harness = BeaconChainHarness::new_with_max_skip_slots(
+        MinimalEthSpec,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        max_skip_slots,
+    );
+    let chain = &harness.chain;
+
+    let head_slot = chain.head_info().expect("should get head info").slot;
+    let allowed_slot = head_slot + max_skip_slots;
+    let skipped_slot = head_slot + max_skip_slots * 3;
+
+    let state = chain
+        .state_at_slot(allowed_slot, StateSkipConfig::WithoutStateRoots)
+        .expect("should skip up to max_skip_slots");
+    assert_eq!(state.slot, allowed_slot);
+
+    match chain.state_at_slot(skipped_slot, StateSkipConfig::WithoutStateRoots) {
+        Err(BeaconChainError::TooManySkipSlots { requested, max }) => {
+            assert_eq!(requested, (skipped_slot - head_slot).as_u64());
+            assert_eq!(max, max_skip_slots);
+        }
+        other => panic!("state skip should be refused, got {:?}", other),
+    }
+
+    // Attestation production only advances the head state to the epoch prior to the attestation
+    // slot, which is still further than the cap.
+    match chain.produce_attestation(skipped_slot, 0) {
+        Err(BeaconChainError::TooManySkipSlots { max, .. }) => assert_eq!(max, max_skip_slots),
+        other => panic!("attestation production should be refused, got {:?}", other),
+    }
+}
+
+#[test]
+fn reads_head_info_without_waiting() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    assert_eq!(
+        chain.head_info_cached(),
+        chain.head_info().expect("should get head"),
+        "should have a snapshot before fork choice changes the head"
+    );
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_info = chain.head_info().expect("should get head");
+
+    assert_eq!(
+        chain.try_head_info_now(),
+        Some(head_info.clone()),
+        "should read the uncontended head"
+    );
+    assert_eq!(
+        chain.head_info_cached(),
+        head_info,
+        "snapshot should match the head"
+    );
+}
+
+#[test]
+fn head_is_shared_until_it_changes() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    let previous_head = chain.head().expect("should get head");
+
+    assert!(
+        Arc::ptr_eq(&previous_head, &chain.head().expect("should get head")),
+        "the head should not be cloned"
+    );
+
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = chain.head().expect("should get head");
+
+    assert!(
+        !Arc::ptr_eq(&previous_head, &head),
+        "the head should be replaced when it changes"
+    );
+    assert_eq!(
+        head.beacon_block.parent_root(),
+        previous_head.beacon_block_root,
+        "the previous head should remain readable"
+    );
+}
+
+#[test]
+fn fork_choice_takes_head_lock_at_most_twice() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let slot = chain.slot().expect("should get slot");
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+    assert!(
+        slot.epoch(slots_per_epoch).start_slot(slots_per_epoch) != slot,
+        "the new head should not start an epoch"
+    );
+
+    let proposer = chain.block_proposer(slot).expect("should get proposer");
+    let block = produce_signed_block(&harness, proposer);
+    let block_root = match chain.process_block(block) {
+        Ok(BlockProcessingOutcome::Processed { block_root }) => block_root,
+        other => panic!("should process block: {:?}", other),
+    };
+
+    let acquisitions = chain.head_lock_acquisitions();
+    chain.fork_choice().expect("should run fork choice");
+    let head_change_acquisitions = chain.head_lock_acquisitions() - acquisitions;
+
+    assert_eq!(
+        chain.head_info().expect("should get head").block_root,
+        block_root,
+        "fork choice should change the head"
+    );
+    assert!(
+        head_change_acquisitions <= 2,
+        "should read and write the head lock at most once each, acquired {} times",
+        head_change_acquisitions
+    );
+
+    let acquisitions = chain.head_lock_acquisitions();
+    chain.fork_choice().expect("should run fork choice");
+
+    assert_eq!(
+        chain.head_lock_acquisitions() - acquisitions,
+        1,
+        "should only read the head lock if the head is unchanged"
+    );
+}
+
+#[test]
+fn rejects_objects_from_unsupported_forks() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = chain.head().expect("should get head");
+    let head_info = chain.head_info().expect("should get head info");
+    let known_version = head_info.fork.current_version;
+    let unknown_version = [0xff; 4];
+    let expected_epoch = head
+        .beacon_block
+        .slot()
+        .epoch(MinimalEthSpec::slots_per_epoch());
+
+    let num_pooled_attestations = chain.op_pool.num_attestations();
+    let block_bytes = head.beacon_block.as_ssz_bytes();
+
+    assert_eq!(
+        chain.decode_block_for_fork(&block_bytes, unknown_version),
+        Err(BeaconChainError::UnsupportedFork {
+            fork_version: unknown_version,
+            epoch_hint: Some(expected_epoch),
+        }),
+        "should reject a block from an unknown fork"
+    );
+    assert_eq!(
+        chain.decode_block_for_fork(&block_bytes, known_version),
+        Ok(head.beacon_block.clone()),
+        "should decode a block from the current fork"
+    );
+
+    let attestation = chain
+        .produce_attestation(head.beacon_block.slot(), 0)
+        .expect("should produce attestation");
+    let attestation_bytes = attestation.as_ssz_bytes();
+
+    assert_eq!(
+        chain.decode_attestation_for_fork(&attestation_bytes, unknown_version),
+        Err(BeaconChainError::UnsupportedFork {
+            fork_version: unknown_version,
+            epoch_hint: Some(expected_epoch),
+        }),
+        "should reject an attestation from an unknown fork"
+    );
+    assert_eq!(
+        chain.decode_attestation_for_fork(&attestation_bytes, known_version),
+        Ok(attestation),
+        "should decode an attestation from the current fork"
+    );
+
+    assert_eq!(
+        chain.head_info().expect("should get head info"),
+        head_info,
+        "rejecting objects should not change the head"
+    );
+    assert_eq!(
+        chain.op_pool.num_attestations(),
+        num_pooled_attestations,
+        "decoding should not import attestations"
+    );
+}
+
+#[test]
+fn processes_aggregate_and_proof() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+    let spec = &harness.spec;
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let mut state = chain.head().expect("should get head").beacon_state.clone();
+    state
+        .build_committee_cache(RelativeEpoch::Current, spec)
+        .expect("should build committee cache");
+    let slot = state.slot;
+    let fork = state.fork.clone();
+
+    let aggregate = chain
+        .get_aggregated_attestation(slot, 0)
+        .expect("should have an aggregate for the head slot");
+    let committee = state
+        .get_beacon_committee(slot, 0)
+        .expect("should get committee")
+        .committee
+        .to_vec();
+    let aggregator = committee[0];
+    let non_member = (0..VALIDATOR_COUNT)
+        .find(|i| !committee.contains(i))
+        .expect("should have a validator outside the committee");
+
+    // Committees in this test are small enough that every member is an aggregator.
+    let sign = |aggregator_index: usize, signer: usize| {
+        let secret_key = &harness.keypairs[signer].sk;
+        AggregateAndProof {
+            aggregator_index: aggregator_index as u64,
+            aggregate: aggregate.clone(),
+            selection_proof: SelectionProof::new::<MinimalEthSpec>(slot, secret_key, &fork, spec)
+                .into(),
+        }
+        .sign(secret_key, &fork, spec)
+    };
+
+    assert_eq!(
+        chain.process_aggregate_and_proof(&sign(non_member, non_member)),
+        Ok(AggregateProcessingOutcome::AggregatorNotInCommittee {
+            aggregator_index: non_member as u64
+        }),
+        "should reject an aggregator outside the committee"
+    );
+    assert_eq!(
+        chain.process_aggregate_and_proof(&sign(aggregator, non_member)),
+        Ok(AggregateProcessingOutcome::InvalidSelectionProof {
+            aggregator_index: aggregator as u64
+        }),
+        "should reject a selection proof from another validator"
+    );
+
+    let mut wrongly_signed = sign(aggregator, aggregator);
+    wrongly_signed.signature = sign(aggregator, non_member).signature;
+
+    assert_eq!(
+        chain.process_aggregate_and_proof(&wrongly_signed),
+        Ok(AggregateProcessingOutcome::InvalidSignature {
+            aggregator_index: aggregator as u64
+        }),
+        "should reject an aggregate signed by another validator"
+    );
+
+    // None of the rejected aggregates were imported, otherwise this one would already be known.
+    assert_eq!(
+        chain.process_aggregate_and_proof(&sign(aggregator, aggregator)),
+        Ok(AggregateProcessingOutcome::Processed),
+        "should accept an aggregate from a committee member"
+    );
+    assert_eq!(
+        chain.process_aggregate_and_proof(&sign(aggregator, aggregator)),
+        Ok(AggregateProcessingOutcome::InvalidAggregate(
+            AttestationProcessingOutcome::AlreadyKnown
+        )),
+        "should not import the same aggregate twice"
+    );
+}
+
+#[test]
+fn readiness_reflects_sync_distance_and_cold_caches() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    assert_eq!(
+        chain.readiness().expect("should get readiness"),
+        Readiness::Degraded {
+            reasons: vec![DegradedReason::ShufflingNotCached {
+                epoch: Epoch::new(0)
+            }]
+        },
+        "should be degraded before the head shuffling is cached"
+    );
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize + 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    assert_eq!(
+        chain.readiness().expect("should get readiness"),
+        Readiness::Ready,
+        "should be ready once a block in the head epoch has been imported"
+    );
+
+    let max_sync_distance = ReadinessConfig::default().max_sync_distance;
+
+    for _ in 0..max_sync_distance {
+        harness.advance_slot();
+    }
+
+    assert_eq!(
+        chain.readiness().expect("should get readiness"),
+        Readiness::Ready,
+        "should be ready at the maximum sync distance"
+    );
+
+    harness.advance_slot();
+
+    assert_eq!(
+        chain.readiness().expect("should get readiness"),
+        Readiness::Syncing {
+            distance: max_sync_distance + 1
+        },
+        "should be syncing beyond the maximum sync distance"
+    );
+}
+
+#[test]
+fn sync_status_tracks_slot_clock() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_slot = chain.head_info().expect("should get head info").slot;
+
+    assert_eq!(
+        chain.sync_status().expect("should get sync status"),
+        SyncStatus::PostGenesis {
+            head_slot,
+            current_slot: head_slot,
+            sync_distance: 0,
+            is_synced: true,
+        },
+        "should be synced whilst the head is at the current slot"
+    );
+
+    let max_sync_distance = ReadinessConfig::default().max_sync_distance;
+
+    for _ in 0..=max_sync_distance {
+        harness.advance_slot();
+    }
+
+    assert_eq!(
+        chain.sync_status().expect("should get sync status"),
+        SyncStatus::PostGenesis {
+            head_slot,
+            current_slot: head_slot + max_sync_distance + 1,
+            sync_distance: max_sync_distance + 1,
+            is_synced: false,
+        },
+        "should be unsynced beyond the maximum sync distance"
+    );
+}
+
+/// Produces a block at the present slot using `BeaconChain::produce_block` and signs it with the
+/// key of `signer`.
+fn produce_signed_block(
+    harness: &BeaconChainHarness<HarnessType<MinimalEthSpec>>,
+    signer: usize,
+) -> SignedBeaconBlock<MinimalEthSpec> {
+    let chain = &harness.chain;
+    let slot = chain.slot().expect("should get slot");
+    let proposer = chain.block_proposer(slot).expect("should get proposer");
+    let fork = chain.head_info().expect("should get head info").fork;
+
+    let randao_reveal = {
+        let epoch = slot.epoch(MinimalEthSpec::slots_per_epoch());
+        let domain = harness.spec.get_domain(epoch, Domain::Randao, &fork);
+        let message = epoch.signing_root(domain);
+        Signature::new(message.as_bytes(), &harness.keypairs[proposer].sk)
+    };
+
+    let (block, _) = chain
+        .produce_block(randao_reveal, slot)
+        .expect("should produce block");
+
+    block.sign(&harness.keypairs[signer].sk, &fork, &harness.spec)
+}
+
+#[test]
+fn produce_block_checked_verifies_randao_reveal() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let chain = &harness.chain;
+    let slot = chain.slot().expect("should get slot");
+    let proposer = chain.block_proposer(slot).expect("should get proposer");
+    let fork = chain.head_info().expect("should get head info").fork;
+
+    let randao_reveal = |signer: usize| {
+        let epoch = slot.epoch(MinimalEthSpec::slots_per_epoch());
+        let domain = harness.spec.get_domain(epoch, Domain::Randao, &fork);
+        let message = epoch.signing_root(domain);
+        Signature::new(message.as_bytes(), &harness.keypairs[signer].sk)
+    };
+
+    let wrong_signer = (proposer + 1) % VALIDATOR_COUNT;
+    assert_eq!(
+        chain
+            .produce_block_checked(randao_reveal(wrong_signer), slot)
+            .map(|_| ()),
+        Err(BlockProductionError::InvalidRandaoReveal {
+            proposer_index: proposer
+        }),
+        "should reject a reveal from another validator"
+    );
+
+    let (block, _) = chain
+        .produce_block_checked(randao_reveal(proposer), slot)
+        .expect("should produce block with a valid reveal");
+    assert_eq!(block.slot, slot, "should produce a block at the slot");
+}
+
+#[test]
+fn imports_self_produced_block_without_reprocessing() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let reference = get_harness(VALIDATOR_COUNT);
+
+    let slot = harness.chain.slot().expect("should get slot");
+    let proposer = harness
+        .chain
+        .block_proposer(slot)
+        .expect("should get proposer");
+    let block = produce_signed_block(&harness, proposer);
+    let block_root = block.canonical_root();
+
+    assert_eq!(
+        harness.chain.process_block(block.clone()),
+        Ok(BlockProcessingOutcome::Processed { block_root }),
+        "should import the produced block"
+    );
+    assert_eq!(
+        harness.chain.stats().produced_blocks_reused,
+        1,
+        "should reuse the state computed during production"
+    );
+
+    assert_eq!(
+        reference.chain.process_block(block),
+        Ok(BlockProcessingOutcome::Processed { block_root }),
+        "should import the block on another chain"
+    );
+    assert_eq!(
+        reference.chain.stats().produced_blocks_reused,
+        0,
+        "should fully process a block produced elsewhere"
+    );
+
+    harness.chain.fork_choice().expect("should run fork choice");
+    reference
+        .chain
+        .fork_choice()
+        .expect("should run fork choice");
+
+    let head = harness.chain.head().expect("should get head");
+    let reference_head = reference.chain.head().expect("should get head");
+
+    assert_eq!(head.beacon_block_root, block_root, "should be the head");
+    assert_eq!(
+        head.beacon_state.as_ssz_bytes(),
+        reference_head.beacon_state.as_ssz_bytes(),
+        "head state should match a full import"
+    );
+}
+
+#[test]
+fn does_not_reuse_state_for_badly_signed_produced_block() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let slot = harness.chain.slot().expect("should get slot");
+    let proposer = harness
+        .chain
+        .block_proposer(slot)
+        .expect("should get proposer");
+    let block = produce_signed_block(&harness, (proposer + 1) % VALIDATOR_COUNT);
+
+    match harness.chain.process_block(block) {
+        Ok(BlockProcessingOutcome::PerBlockProcessingError(_)) => {}
+        other => panic!("should reject the block, got {:?}", other),
+    }
+    assert_eq!(
+        harness.chain.stats().produced_blocks_reused,
+        0,
+        "should not reuse the produced state"
+    );
+}
+
+#[test]
+fn validate_block_does_not_import() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let slot = harness.chain.slot().expect("should get slot");
+    let proposer = harness
+        .chain
+        .block_proposer(slot)
+        .expect("should get proposer");
+    let block = produce_signed_block(&harness, proposer);
+    let block_root = block.canonical_root();
+
+    assert_eq!(
+        harness.chain.validate_block(&block),
+        Ok(BlockProcessingOutcome::Processed { block_root }),
+        "should find the block valid"
+    );
+    assert!(
+        harness
+            .chain
+            .get_block(&block_root)
+            .expect("should read the store")
+            .is_none(),
+        "should not store the block"
+    );
+    assert!(
+        !harness.chain.fork_choice.contains_block(&block_root),
+        "should not add the block to fork choice"
+    );
+
+    let bad_block = produce_signed_block(&harness, (proposer + 1) % VALIDATOR_COUNT);
+    match harness.chain.validate_block(&bad_block) {
+        Ok(BlockProcessingOutcome::PerBlockProcessingError(_)) => {}
+        other => panic!("should reject the badly signed block, got {:?}", other),
+    }
+
+    assert_eq!(
+        harness.chain.process_block(block.clone()),
+        Ok(BlockProcessingOutcome::Processed { block_root }),
+        "should import the block after validating it"
+    );
+    assert_eq!(
+        harness.chain.validate_block(&block),
+        Ok(BlockProcessingOutcome::BlockIsAlreadyKnown),
+        "should report the imported block as known"
+    );
+}
+
+#[test]
+fn verifies_block_for_gossip() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let slot = harness.chain.slot().expect("should get slot");
+    let proposer = harness
+        .chain
+        .block_proposer(slot)
+        .expect("should get proposer");
+    let block = produce_signed_block(&harness, proposer);
+    let block_root = block.canonical_root();
+
+    let verified = harness
+        .chain
+        .verify_block_for_gossip(block)
+        .expect("should verify the block");
+
+    assert_eq!(verified.block_root(), block_root);
+    assert_eq!(verified.proposer_index(), proposer);
+    assert_eq!(
+        harness.chain.process_block(verified),
+        Ok(BlockProcessingOutcome::Processed { block_root }),
+        "should import the verified block"
+    );
+}
+
+#[test]
+fn gossip_rejects_block_from_wrong_proposer() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let slot = harness.chain.slot().expect("should get slot");
+    let proposer = harness
+        .chain
+        .block_proposer(slot)
+        .expect("should get proposer");
+    let block = produce_signed_block(&harness, (proposer + 1) % VALIDATOR_COUNT);
+
+    assert_eq!(
+        harness.chain.verify_block_for_gossip(block).err(),
+        Some(GossipBlockError::ProposalSignatureInvalid {
+            proposer_index: proposer
+        }),
+        "should reject a block signed by another validator"
+    );
+}
+
+#[test]
+fn gossip_rejects_bad_proposer_signature() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    let slot = harness.chain.slot().expect("should get slot");
+    let proposer = harness
+        .chain
+        .block_proposer(slot)
+        .expect("should get proposer");
+    let mut block = produce_signed_block(&harness, proposer);
+    block.signature = Signature::new(&[42; 32], &harness.keypairs[proposer].sk);
+
+    assert_eq!(
+        harness.chain.verify_block_for_gossip(block).err(),
+        Some(GossipBlockError::ProposalSignatureInvalid {
+            proposer_index: proposer
+        }),
+        "should reject a block with a signature over another message"
+    );
+}
+
+#[test]
+fn imports_exported_snapshot() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let finalized_checkpoint = harness
+        .chain
+        .head_info()
+        .expect("should get head info")
+        .finalized_checkpoint;
+    assert!(
+        finalized_checkpoint.epoch > 0,
+        "the chain should have finalized"
+    );
+
+    let mut bytes = vec![];
+    harness
+        .chain
+        .export_snapshot(&mut bytes)
+        .expect("should export snapshot");
+
+    let other_harness = get_harness(VALIDATOR_COUNT);
+    while other_harness.chain.slot().expect("should get slot")
+        < harness.chain.slot().expect("should get slot")
+    {
+        other_harness.advance_slot();
+    }
+
+    let imported = other_harness
+        .chain
+        .import_snapshot(&mut bytes.as_slice())
+        .expect("should import snapshot");
+
+    let finalized_slot = harness
+        .chain
+        .get_block(&finalized_checkpoint.root)
+        .expect("should read block")
+        .expect("should find finalized block")
+        .slot();
+    assert_eq!(
+        imported as u64,
+        finalized_slot.as_u64(),
+        "should import every block up to the finalized block"
+    );
+    assert_eq!(
+        other_harness
+            .chain
+            .head_info()
+            .expect("should get head info")
+            .block_root,
+        finalized_checkpoint.root,
+        "the finalized block should become the head"
+    );
+    assert_eq!(
+        other_harness.chain.import_snapshot(&mut bytes.as_slice()),
+        Ok(0),
+        "should skip known blocks"
+    );
+}
+
+/// Returns the records of a snapshot of `harness`, alongside a harness with the same genesis and
+/// slot into which the snapshot may be imported.
+fn export_snapshot_records(
+    harness: &BeaconChainHarness<HarnessType<MinimalEthSpec>>,
+) -> (
+    Vec<SnapshotRecord<MinimalEthSpec>>,
+    BeaconChainHarness<HarnessType<MinimalEthSpec>>,
+) {
+    let mut bytes = vec![];
+    harness
+        .chain
+        .export_snapshot(&mut bytes)
+        .expect("should export snapshot");
+
+    let mut reader = bytes.as_slice();
+    let mut records = vec![];
+    while let Some(record) = SnapshotRecord::read(&mut reader).expect("should read record") {
+        records.push(record);
+    }
+
+    let other_harness = get_harness(VALIDATOR_COUNT);
+    while other_harness.chain.slot().expect("should get slot")
+        < harness.chain.slot().expect("should get slot")
+    {
+        other_harness.advance_slot();
+    }
+
+    (records, other_harness)
+}
+
+/// Returns the concatenated encodings of `records`.
+fn snapshot_bytes(records: &[SnapshotRecord<MinimalEthSpec>]) -> Vec<u8> {
+    let mut bytes = vec![];
+    for record in records {
+        record.write(&mut bytes).expect("should write record");
+    }
+    bytes
+}
+
+#[test]
+fn rejects_snapshot_with_unchained_blocks() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let (mut records, other_harness) = export_snapshot_records(&harness);
+
+    let block_positions = records
+        .iter()
+        .enumerate()
+        .filter(|(_, record)| match record {
+            SnapshotRecord::Block(_) => true,
+            SnapshotRecord::State(_) => false,
+        })
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let block_root = |i: usize| match &records[block_positions[i]] {
+        SnapshotRecord::Block(block) => block.canonical_root(),
+        SnapshotRecord::State(_) => panic!("should be a block"),
+    };
+    let expected_error = BeaconChainError::SnapshotBlocksNotChained {
+        block_root: block_root(2),
+        parent_root: block_root(1),
+        expected_parent_root: block_root(0),
+    };
+    records.remove(block_positions[1]);
+
+    assert_eq!(
+        other_harness
+            .chain
+            .import_snapshot(&mut snapshot_bytes(&records).as_slice()),
+        Err(expected_error),
+        "should reject blocks which do not chain together"
+    );
+    assert_eq!(
+        other_harness
+            .chain
+            .head_info()
+            .expect("should get head info")
+            .slot,
+        Slot::new(0),
+        "the head should not be updated"
+    );
+}
+
+#[test]
+fn rejects_snapshot_with_invalid_state() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let (mut records, other_harness) = export_snapshot_records(&harness);
+
+    let state_slots = records
+        .iter()
+        .filter_map(|record| match record {
+            SnapshotRecord::State(state) => Some(state.slot.as_u64()),
+            SnapshotRecord::Block(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let finalized_epoch = harness
+        .chain
+        .head_info()
+        .expect("should get head info")
+        .finalized_checkpoint
+        .epoch
+        .as_u64();
+    assert_eq!(
+        state_slots,
+        (0..=finalized_epoch)
+            .map(|epoch| epoch * MinimalEthSpec::slots_per_epoch())
+            .collect::<Vec<_>>(),
+        "should export the state at the start of each finalized epoch"
+    );
+
+    let (slot, local_state_root) = records
+        .iter_mut()
+        .filter_map(|record| match record {
+            SnapshotRecord::State(state) if state.slot > Slot::new(0) => Some(state),
+            _ => None,
+        })
+        .map(|state| {
+            let local_state_root = state.canonical_root();
+            state.genesis_time += 1;
+            (state.slot, local_state_root)
+        })
+        .next()
+        .expect("should have a state after genesis");
+    let snapshot_state_root = records
+        .iter()
+        .find_map(|record| match record {
+            SnapshotRecord::State(state) if state.slot == slot => Some(state.canonical_root()),
+            _ => None,
+        })
+        .expect("should find the modified state");
+
+    assert_eq!(
+        other_harness
+            .chain
+            .import_snapshot(&mut snapshot_bytes(&records).as_slice()),
+        Err(BeaconChainError::SnapshotStateMismatch {
+            slot,
+            snapshot_state_root,
+            local_state_root,
+        }),
+        "should reject a state which does not match its blocks"
+    );
+}
+
+#[test]
+fn follower_imports_blocks_without_production() {
+    let num_blocks_produced = MinimalEthSpec::slots_per_epoch() * 5;
+
+    let harness = get_harness(VALIDATOR_COUNT);
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let follower = BeaconChainHarness::new_with_mode(
+        MinimalEthSpec,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        BeaconChainMode::Follower,
+    );
+    let current_slot = harness.chain.slot().expect("should get slot");
+    follower.chain.slot_clock.set_slot(current_slot.as_u64());
+
+    assert_eq!(follower.chain.mode(), BeaconChainMode::Follower);
+
+    for checkpoint in harness
+        .chain
+        .chain_dump()
+        .expect("should dump chain")
+        .into_iter()
+        .skip(1)
+    {
+        assert_eq!(
+            follower.chain.process_block(checkpoint.beacon_block),
+            Ok(BlockProcessingOutcome::Processed {
+                block_root: checkpoint.beacon_block_root
+            }),
+            "should import every block"
+        );
+    }
+    follower
+        .chain
+        .fork_choice()
+        .expect("should run fork choice");
+
+    let head_info = harness.chain.head_info().expect("should get head info");
+    let follower_head_info = follower.chain.head_info().expect("should get head info");
+    assert_eq!(
+        follower_head_info.block_root, head_info.block_root,
+        "should have the same head"
+    );
+    assert_eq!(
+        follower_head_info.finalized_checkpoint, head_info.finalized_checkpoint,
+        "should have the same finalized checkpoint"
+    );
+    assert!(
+        follower_head_info.finalized_checkpoint.epoch > 0,
+        "the chain should have finalized"
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    for attestation in harness.get_free_attestations(
+        &AttestationStrategy::AllValidators,
+        &head.beacon_state,
+        head.beacon_block_root,
+        head.beacon_block.slot(),
+    ) {
+        assert_eq!(
+            follower.chain.process_attestation(attestation),
+            Ok(AttestationProcessingOutcome::Processed),
+            "should process attestations"
+        );
+    }
+    assert_eq!(
+        follower.chain.op_pool.num_attestations(),
+        0,
+        "should not add attestations to the op pool"
+    );
+
+    let exit = TestingVoluntaryExitBuilder::new(Epoch::new(0), 0).build(
+        &KEYPAIRS[0].sk,
+        &head_info.fork,
+        &follower.spec,
+    );
+    assert_eq!(
+        follower.chain.process_voluntary_exit(exit),
+        Err(OperationImportError::FollowerMode),
+        "should refuse to queue an exit"
+    );
+    assert!(
+        follower.chain.op_pool.get_all_voluntary_exits().is_empty(),
+        "should not add the exit to the op pool"
+    );
+
+    assert_eq!(
+        follower
+            .chain
+            .produce_block(Signature::empty_signature(), current_slot)
+            .err(),
+        Some(BlockProductionError::ProductionDisabledInFollowerMode),
+        "should not produce blocks"
+    );
+}
+
+#[test]
+fn get_beacon_committee_matches_state() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 3,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let chain = &harness.chain;
+    let mut head_state = chain.head().expect("should get head").beacon_state.clone();
+    head_state
+        .build_committee_cache(RelativeEpoch::Previous, &harness.spec)
+        .expect("should build previous committee cache");
+    head_state
+        .build_committee_cache(RelativeEpoch::Current, &harness.spec)
+        .expect("should build current committee cache");
+
+    let previous_epoch_start = head_state
+        .previous_epoch()
+        .start_slot(MinimalEthSpec::slots_per_epoch());
+    for slot in (previous_epoch_start.as_u64()..=head_state.slot.as_u64()).map(Slot::new) {
+        for committee in head_state
+            .get_beacon_committees_at_slot(slot)
+            .expect("should get committees")
+        {
+            // Request each committee twice, so that the second is read from the shuffling cache.
+            for _ in 0..2 {
+                assert_eq!(
+                    chain.get_beacon_committee(slot, committee.index),
+                    Ok(committee.committee.to_vec()),
+                    "should match the committee of the head state"
+                );
+            }
+        }
+    }
+
+    let committee_count = head_state
+        .get_committee_count_at_slot(head_state.slot)
+        .expect("should get committee count");
+    assert_eq!(
+        chain.get_beacon_committee(head_state.slot, committee_count),
+        Err(BeaconChainError::NoCommitteeForSlotAndIndex {
+            slot: head_state.slot,
+            index: committee_count
+        }),
+        "should not find a committee with an unknown index"
+    );
+}
+
+#[test]
+fn get_beacon_committees_at_slot_covers_assigned_validators() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let chain = &harness.chain;
+    let mut head_state = chain.head().expect("should get head").beacon_state.clone();
+    head_state
+        .build_committee_cache(RelativeEpoch::Current, &harness.spec)
+        .expect("should build committee cache");
+
+    let epoch = head_state.current_epoch();
+    let active_validators = head_state.get_active_validator_indices(epoch);
+    let committees_per_slot =
+        MinimalEthSpec::get_committee_count_per_slot(active_validators.len(), &harness.spec);
+
+    let mut total_members = 0;
+    for slot in epoch.slot_iter(MinimalEthSpec::slots_per_epoch()) {
+        let committees = chain
+            .get_beacon_committees_at_slot(slot)
+            .expect("should get committees");
+
+        assert_eq!(
+            committees.len(),
+            committees_per_slot,
+            "should return every committee at the slot"
+        );
+
+        let assigned_validators = active_validators
+            .iter()
+            .filter(|validator_index| {
+                head_state
+                    .get_attestation_duties(**validator_index, RelativeEpoch::Current)
+                    .expect("should get duties")
+                    .map_or(false, |duty| duty.slot == slot)
+            })
+            .count();
+        let members = committees
+            .iter()
+            .map(|committee| committee.committee.len())
+            .sum::<usize>();
+
+        assert_eq!(
+            members, assigned_validators,
+            "the committees should contain each validator assigned to the slot"
+        );
+        for (index, committee) in committees.iter().enumerate() {
+            assert_eq!(committee.slot, slot, "committee should be at the slot");
+            assert_eq!(
+                committee.index, index as u64,
+                "committees should be in order of index"
+            );
+        }
+
+        total_members += members;
+    }
+
+    assert_eq!(
+        total_members,
+        active_validators.len(),
+        "every active validator should be in one committee per epoch"
+    );
+}
+
+#[test]
+fn imports_blocks_on_top_of_trusted_checkpoint() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch() as usize;
+    harness.extend_chain(
+        slots_per_epoch * 4,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let anchor = harness.chain.head().expect("should get head");
+    let anchor_slot = anchor.beacon_block.slot();
+    assert_eq!(
+        anchor_slot,
+        Slot::from(slots_per_epoch * 4),
+        "anchor should be at the start of an epoch"
+    );
+
+    harness.advance_slot();
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let next = harness.chain.head().expect("should get head");
+
+    let checkpoint_harness = BeaconChainHarness::new_from_anchor(
+        MinimalEthSpec,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        anchor.beacon_state.clone(),
+        anchor.beacon_block.clone(),
+        harness.chain.genesis_validators_root(),
+    );
+    let chain = &checkpoint_harness.chain;
+    chain
+        .slot_clock
+        .set_slot(harness.chain.slot().expect("should get slot").as_u64());
+
+    assert_eq!(chain.anchor_slot(), anchor_slot, "should record the anchor");
+    assert_eq!(
+        chain.genesis_block_root, anchor.beacon_block_root,
+        "the anchor should stand in for genesis"
+    );
+    assert_eq!(
+        chain
+            .admin_history(10)
+            .expect("should read admin history")
+            .into_iter()
+            .map(|event| event.action)
+            .collect::<Vec<_>>(),
+        vec!["checkpoint_start".to_string()],
+        "should record the checkpoint start"
+    );
+    assert_eq!(
+        chain.head_info().expect("should get head info").block_root,
+        anchor.beacon_block_root,
+        "should start with the anchor as head"
+    );
+
+    assert_eq!(
+        chain.process_block(next.beacon_block.clone()),
+        Ok(BlockProcessingOutcome::Processed {
+            block_root: next.beacon_block_root
+        }),
+        "should import the block after the anchor"
+    );
+    chain.fork_choice().expect("should run fork choice");
+    assert_eq!(
+        chain.head_info().expect("should get head info").block_root,
+        next.beacon_block_root,
+        "should update the head"
+    );
+
+    let pre_anchor_block = harness
+        .chain
+        .chain_dump()
+        .expect("should dump chain")
+        .into_iter()
+        .map(|checkpoint| checkpoint.beacon_block)
+        .find(|block| block.slot() == anchor_slot - 1)
+        .expect("should find block prior to anchor");
+    assert_eq!(
+        chain.process_block(pre_anchor_block),
+        Ok(BlockProcessingOutcome::WouldRevertFinalizedSlot {
+            block_slot: anchor_slot - 1,
+            finalized_slot: anchor_slot,
+        }),
+        "should reject blocks prior to the anchor"
+    );
+}
+
+#[test]
+fn lookups_prior_to_trusted_checkpoint_are_not_found() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+    // Start from an anchor more than `SLOTS_PER_HISTORICAL_ROOT` slots after genesis, so that
+    // iterating back to the earliest slots would require states prior to the anchor.
+    let anchor_epoch = MinimalEthSpec::slots_per_historical_root() as u64 / slots_per_epoch + 2;
+    harness.extend_chain(
+        (anchor_epoch * slots_per_epoch) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let anchor = harness.chain.head().expect("should get head");
+    let anchor_slot = anchor.beacon_block.slot();
+    assert_eq!(
+        anchor_slot,
+        Epoch::new(anchor_epoch).start_slot(slots_per_epoch),
+        "anchor should be at the start of an epoch"
+    );
+
+    harness.advance_slot();
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let next = harness.chain.head().expect("should get head");
+
+    let checkpoint_harness = BeaconChainHarness::new_from_anchor(
+        MinimalEthSpec,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        anchor.beacon_state.clone(),
+        anchor.beacon_block.clone(),
+        harness.chain.genesis_validators_root(),
+    );
+    let chain = &checkpoint_harness.chain;
+    chain
+        .slot_clock
+        .set_slot(harness.chain.slot().expect("should get slot").as_u64());
+    chain
+        .process_block(next.beacon_block.clone())
+        .expect("should import the block after the anchor");
+    chain.fork_choice().expect("should run fork choice");
+
+    let pre_anchor_slot = Slot::new(1);
+    assert_eq!(chain.root_at_slot(pre_anchor_slot), Ok(None));
+    assert_eq!(chain.state_root_at_slot(pre_anchor_slot), Ok(None));
+    assert_eq!(chain.block_at_slot(pre_anchor_slot), Ok(None));
+    assert_eq!(
+        chain.get_ancestor_block_root(next.beacon_block_root, pre_anchor_slot),
+        Ok(None)
+    );
+    match chain.state_at_slot(pre_anchor_slot, StateSkipConfig::WithStateRoots) {
+        Err(BeaconChainError::NoStateForSlot(slot)) if slot == pre_anchor_slot => {}
+        other => panic!(
+            "should not find a state prior to the anchor, got {:?}",
+            other
+        ),
+    }
+
+    assert_eq!(
+        chain.root_at_slot(anchor_slot),
+        Ok(Some(anchor.beacon_block_root)),
+        "should find the anchor block"
+    );
+    assert_eq!(
+        chain
+            .validator_balance_history(0, Epoch::new(0), Epoch::new(anchor_epoch))
+            .expect("should get balance history")
+            .into_iter()
+            .map(|(epoch, _balance)| epoch)
+            .collect::<Vec<_>>(),
+        vec![Epoch::new(anchor_epoch)],
+        "should omit the epochs prior to the anchor"
+    );
+}
+
+#[test]
+fn warm_balances_cache_is_hit_when_checkpoint_is_justified() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch() as usize;
+
+    // Without attestations nothing is justified, so the balances of the first block of epoch 2
+    // are not cached when it is imported.
+    harness.extend_chain(
+        slots_per_epoch * 2 - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    // The head is not the first block of epoch 1, so its balances should not be cached.
+    let head = harness.chain.head().expect("should get head");
+    let epoch_boundary_root = *head
+        .beacon_state
+        .get_block_root(Slot::from(slots_per_epoch))
+        .expect("should get epoch boundary root");
+    harness
+        .chain
+        .warm_balances_cache(&head.beacon_state)
+        .expect("should warm balances cache");
+    assert!(
+        !harness
+            .chain
+            .fork_choice
+            .balances_cache_contains(&epoch_boundary_root),
+        "should only warm the cache at the first block of an epoch"
+    );
+
+    harness.advance_slot();
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    assert_eq!(
+        head.beacon_state.slot,
+        Slot::from(slots_per_epoch * 2),
+        "head should be the first block of epoch 2"
+    );
+    harness
+        .chain
+        .warm_balances_cache(&head.beacon_state)
+        .expect("should warm balances cache");
+    assert!(
+        harness
+            .chain
+            .fork_choice
+            .balances_cache_contains(&head.beacon_block_root),
+        "should cache the balances of the first block of epoch 2"
+    );
+
+    // Justifying epoch 2 requires the balances of its first block.
+    harness.advance_slot();
+    harness.extend_chain(
+        slots_per_epoch,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let justified_checkpoint = harness
+        .chain
+        .head_info()
+        .expect("should get head info")
+        .current_justified_checkpoint;
+    assert_eq!(
+        justified_checkpoint,
+        Checkpoint {
+            epoch: Epoch::new(2),
+            root: head.beacon_block_root,
+        },
+        "the first block of epoch 2 should be justified"
+    );
+    // Balances are removed from the cache when they are read.
+    assert!(
+        !harness
+            .chain
+            .fork_choice
+            .balances_cache_contains(&head.beacon_block_root),
+        "should take the justified balances from the cache"
+    );
+}
+
+#[test]
+fn future_slot_block_is_reported() {
+    let harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        3,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = harness.chain.head().expect("should get head");
+    let head_slot = head.beacon_block.slot();
+    let block_root = head.beacon_block_root;
+
+    // Simulate a node whose clock is two slots behind the sender of the block.
+    let present_slot = head_slot - 2;
+    harness.chain.slot_clock.set_slot(present_slot.as_u64());
+
+    let mut stream = harness
+        .chain
+        .event_stream(vec![EventTopic::Block].into_iter().collect())
+        .expect("should subscribe to events");
+
+    assert_eq!(
+        harness.chain.process_block(head.beacon_block.clone()),
+        Ok(BlockProcessingOutcome::FutureSlot {
+            present_slot,
+            block_slot: head_slot,
+        }),
+        "should reject block from future slot"
+    );
+
+    let events = drain_events(&mut stream);
+    assert_eq!(
+        events.len(),
+        2,
+        "should emit a future slot and a rejected event"
+    );
+
+    match &events[0] {
+        EventKind::BeaconBlockFromFutureSlot {
+            block_root: event_block_root,
+            block_slot,
+            present_slot: event_present_slot,
+            skew,
+        } => {
+            assert_eq!(*event_block_root, block_root);
+            assert_eq!(*block_slot, head_slot);
+            assert_eq!(*event_present_slot, present_slot);
+            assert_eq!(*skew, 2, "should report the skew in slots");
+        }
+        other => panic!("should report the future slot, got {:?}", other),
+    }
+    match &events[1] {
+        EventKind::BeaconBlockRejected { .. } => {}
+        other => panic!("should still reject the block, got {:?}", other),
+    }
+}
+
+#[test]
+fn accepts_block_from_next_slot_within_tolerance() {
+    let producer = get_harness(VALIDATOR_COUNT);
+    // The receiver's clock is one slot behind the producer's, but the next slot starts within the
+    // tolerance.
+    let receiver = BeaconChainHarness::new_with_max_future_slot_tolerance(
+        MinimalEthSpec,
+        KEYPAIRS[0..VALIDATOR_COUNT].to_vec(),
+        Duration::from_secs(1),
+    );
+
+    producer.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let head = producer.chain.head().expect("should get head");
+    assert_eq!(receiver.chain.slot(), Ok(head.beacon_block.slot() - 1));
+
+    assert_eq!(
+        receiver.chain.process_block(head.beacon_block.clone()),
+        Ok(BlockProcessingOutcome::Processed {
+            block_root: head.beacon_block_root
+        }),
+        "should accept a block from the next slot"
+    );
+
+    producer.advance_slot();
+    producer.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let head = producer.chain.head().expect("should get head");
+
+    assert_eq!(
+        receiver.chain.process_block(head.beacon_block.clone()),
+        Ok(BlockProcessingOutcome::FutureSlot {
+            present_slot: head.beacon_block.slot() - 2,
+            block_slot: head.beacon_block.slot(),
+        }),
+        "should reject a block from beyond the next slot"
+    );
+}
+
+#[test]
+fn fork_choice_checkpoints_reflect_fork_choice_not_head_state() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+
+    // Build up to the last slot before the justified checkpoint first changes.
+    harness.extend_chain(
+        (slots_per_epoch * 3 - 1) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_info = chain.head_info().expect("should get head info");
+    let (justified, _) = chain.fork_choice_checkpoints();
+    assert_eq!(
+        justified.epoch, head_info.current_justified_checkpoint.epoch,
+        "fork choice should agree with the head state"
+    );
+
+    // Import the first block of the next epoch, without running fork choice.
+    harness.advance_slot();
+    let proposer = chain
+        .block_proposer(chain.slot().expect("should get slot"))
+        .expect("should get proposer");
+    let block = produce_signed_block(&harness, proposer);
+    match chain.process_block(block) {
+        Ok(BlockProcessingOutcome::Processed { .. }) => (),
+        other => panic!("should process block, got {:?}", other),
+    }
+
+    let head_info = chain.head_info().expect("should get head info");
+    let (justified, finalized) = chain.fork_choice_checkpoints();
+    assert!(
+        justified.epoch > head_info.current_justified_checkpoint.epoch,
+        "fork choice should use the justified checkpoint of the new block before it is the head"
+    );
+
+    chain.fork_choice().expect("should run fork choice");
+
+    let head_info = chain.head_info().expect("should get head info");
+    assert_eq!(
+        chain.fork_choice_checkpoints(),
+        (
+            head_info.current_justified_checkpoint,
+            head_info.finalized_checkpoint
+        ),
+        "fork choice should agree with the new head state"
+    );
+    assert_eq!(justified, chain.fork_choice_checkpoints().0);
+    assert_eq!(finalized, chain.fork_choice_checkpoints().1);
+}
+
+#[test]
+fn attestation_signing_root_matches_local_signature() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+
+    let head = chain.head().expect("should get head");
+    let slot = head.beacon_block.slot();
+    let validator_index = head
+        .beacon_state
+        .get_beacon_committee(slot, 0)
+        .expect("should get committee")
+        .committee[0];
+    let secret_key = &KEYPAIRS[validator_index].sk;
+
+    let mut attestation = chain
+        .produce_attestation(slot, 0)
+        .expect("should produce attestation");
+    attestation
+        .sign(secret_key, 0, &head.beacon_state.fork, &chain.spec)
+        .expect("should sign attestation");
+
+    let root = chain
+        .attestation_signing_root(&attestation.data)
+        .expect("should get signing root");
+    let mut expected = AggregateSignature::new();
+    expected.add(&Signature::new(root.as_bytes(), secret_key));
+
+    assert_eq!(
+        attestation.signature, expected,
+        "a signature over the signing root should match the locally-signed attestation"
+    );
+}
+
+#[test]
+fn block_signing_root_produces_valid_signature() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let slot = chain.slot().expect("should get slot");
+    let proposer = chain.block_proposer(slot).expect("should get proposer");
+    let block = produce_signed_block(&harness, proposer).message;
+
+    let root = chain
+        .block_signing_root(&block)
+        .expect("should get signing root");
+    let signed_block = SignedBeaconBlock {
+        message: block,
+        signature: Signature::new(root.as_bytes(), &KEYPAIRS[proposer].sk),
+    };
+
+    let mut state = chain
+        .state_at_slot(slot, StateSkipConfig::WithStateRoots)
+        .expect("should get pre-state");
+    state
+        .build_committee_cache(RelativeEpoch::Current, &chain.spec)
+        .expect("should build committee cache");
+
+    per_block_processing(
+        &mut state,
+        &signed_block,
+        None,
+        BlockSignatureStrategy::VerifyIndividual,
+        &chain.spec,
+    )
+    .expect("block signed over the signing root should pass signature verification");
+}
+
+#[test]
+fn block_production_uses_known_state_root() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let head = chain.head().expect("should get head");
+    let head_slot = head.beacon_state.slot;
+    let slot = chain.slot().expect("should get slot");
+    assert_eq!(
+        head_slot + 1,
+        slot,
+        "should produce in the slot after the head"
+    );
+
+    let randao_reveal = head.beacon_block.message.body.randao_reveal.clone();
+
+    let (_, state) = chain
+        .produce_block(randao_reveal.clone(), slot)
+        .expect("should produce block");
+    assert_eq!(
+        *state
+            .get_state_root(head_slot)
+            .expect("should get state root"),
+        head.beacon_state_root,
+        "the head state root should be used for the catch-up slot"
+    );
+
+    let known_root = Hash256::repeat_byte(42);
+    let (_, state) = chain
+        .produce_block_on_state(
+            head.beacon_state.clone(),
+            Some(known_root),
+            slot,
+            randao_reveal,
+        )
+        .expect("should produce block");
+    assert_eq!(
+        *state
+            .get_state_root(head_slot)
+            .expect("should get state root"),
+        known_root,
+        "a supplied state root should be used rather than hashing the state"
+    );
+}
+
+#[test]
+fn dumps_block_with_state_root_mismatch() {
+    let dump_dir = tempfile::tempdir().expect("should create dump dir");
+    let harness = BeaconChainHarness::new_with_invalid_block_dump(
+        MinimalEthSpec,
+        KEYPAIRS[..].to_vec(),
+        dump_dir.path().to_path_buf(),
+    );
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let proposer = chain
+        .block_proposer(chain.slot().expect("should get slot"))
+        .expect("should get proposer");
+    let mut block = produce_signed_block(&harness, proposer).message;
+    block.state_root = Hash256::repeat_byte(42);
+    let fork = chain.head_info().expect("should get head info").fork;
+    let block = block.sign(&KEYPAIRS[proposer].sk, &fork, &chain.spec);
+
+    match chain.process_block(block.clone()) {
+        Ok(BlockProcessingOutcome::StateRootMismatch { .. }) => (),
+        other => panic!("should reject block, got {:?}", other),
+    }
+
+    let mut files = std::fs::read_dir(dump_dir.path())
+        .expect("should read dump dir")
+        .map(|entry| entry.expect("should read entry").path())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(files.len(), 2, "the block and pre-state should be dumped");
+
+    let dumped_block = SignedBeaconBlock::<MinimalEthSpec>::from_ssz_bytes(
+        &std::fs::read(&files[0]).expect("should read block file"),
+    )
+    .expect("should decode block");
+    assert_eq!(dumped_block, block);
+}
+
+#[test]
+fn rev_iter_checkpoints_is_bounded_and_tolerates_missing_states() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let dump = chain.chain_dump().expect("should dump chain");
+    let limited = chain
+        .rev_iter_checkpoints(Some(3))
+        .collect::<Result<Vec<_>, _>>()
+        .expect("should iterate checkpoints");
+    assert_eq!(
+        limited
+            .iter()
+            .map(|c| c.beacon_block_root)
+            .collect::<Vec<_>>(),
+        dump.iter()
+            .rev()
+            .take(3)
+            .map(|c| c.beacon_block_root)
+            .collect::<Vec<_>>(),
+        "should yield the most recent checkpoints, from the head"
+    );
+
+    let pruned = &dump[2];
+    chain
+        .store
+        .delete_state(&pruned.beacon_state_root, pruned.beacon_state.slot)
+        .expect("should delete state");
+
+    let results = chain.rev_iter_checkpoints(None).collect::<Vec<_>>();
+    assert_eq!(results.len(), dump.len(), "should iterate to genesis");
+    for (result, checkpoint) in results.iter().rev().zip(&dump) {
+        if checkpoint.beacon_block_root == pruned.beacon_block_root {
+            assert!(result.is_err(), "the missing state should be an error");
+        } else {
+            assert_eq!(
+                result
+                    .as_ref()
+                    .expect("should load checkpoint")
+                    .beacon_block_root,
+                checkpoint.beacon_block_root
+            );
+        }
+    }
+    assert!(
+        chain.chain_dump().is_err(),
+        "chain_dump should fail on a missing state"
+    );
+}
+
+#[test]
+fn finalization_info_for_finalized_and_head_blocks() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+
+    harness.extend_chain(
+        slots_per_epoch as usize * 5,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head = chain.head().expect("should get head");
+    assert!(
+        head.beacon_state.finalized_checkpoint.epoch > 1,
+        "the chain should have finalized"
+    );
+
+    let block_slot = Slot::new(slots_per_epoch + 1);
+    let block_root = chain
+        .root_at_slot(block_slot)
+        .expect("should read root")
+        .expect("should have a block at the slot");
+    let info = chain
+        .finalization_info_for_block(block_root)
+        .expect("should get finalization info")
+        .expect("the block should be finalized");
+
+    assert_eq!(info.block_slot, block_slot);
+    assert!(info.finalized_checkpoint.epoch.start_slot(slots_per_epoch) >= block_slot);
+    assert!(info.finalized_checkpoint.epoch <= head.beacon_state.finalized_checkpoint.epoch);
+    assert_eq!(
+        chain
+            .state_at_slot(
+                info.finalized_at_epoch.start_slot(slots_per_epoch),
+                StateSkipConfig::WithStateRoots
+            )
+            .expect("should get state")
+            .finalized_checkpoint,
+        info.finalized_checkpoint,
+        "the checkpoint should be finalized at the start of the epoch"
+    );
+    let prior_finalized_checkpoint = chain
+        .state_at_slot(
+            (info.finalized_at_epoch - 1).start_slot(slots_per_epoch),
+            StateSkipConfig::WithStateRoots,
+        )
+        .expect("should get state")
+        .finalized_checkpoint;
+    assert!(
+        prior_finalized_checkpoint.epoch.start_slot(slots_per_epoch) < block_slot,
+        "the block should not be finalized at the start of the prior epoch"
+    );
+
+    assert_eq!(
+        chain.finalization_info_for_block(block_root),
+        Ok(Some(info)),
+        "the cached info should be returned"
+    );
+    assert_eq!(
+        chain.finalization_info_for_block(head.beacon_block_root),
+        Ok(None),
+        "the head should not be finalized"
+    );
+}
+
+#[test]
+fn block_roots_in_range_skips_repeated_roots_and_stops_at_head() {
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    // Build blocks up to the last slot of the first epoch, then skip the first slot of the next.
+    harness.extend_chain(
+        slots_per_epoch as usize - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+    harness.advance_slot();
+    let head_root = harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    let skipped_slot = Slot::new(slots_per_epoch);
+    let head_slot = skipped_slot + 1;
+
+    let slots_in_range = |start: Slot, count: u64| -> Vec<Slot> {
+        let range = chain
+            .block_roots_in_range(start, count)
+            .expect("should get block roots in range");
+        assert!(
+            !range.precedes_oldest_available,
+            "the chain should be available from genesis"
+        );
+        range
+            .blocks
+            .iter()
+            .map(|(root, slot)| {
+                let block = chain
+                    .get_block(root)
+                    .expect("should read block")
+                    .expect("block should exist");
+                assert_eq!(block.slot(), *slot, "the slot should be that of the block");
+                *slot
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        slots_in_range(Slot::new(0), 3),
+        vec![Slot::new(0), Slot::new(1), Slot::new(2)],
+        "should include the genesis block"
+    );
+    assert_eq!(
+        slots_in_range(skipped_slot - 2, 4),
+        vec![skipped_slot - 2, skipped_slot - 1, head_slot],
+        "should not repeat the block prior to the skipped slot"
+    );
+    assert_eq!(
+        slots_in_range(skipped_slot, 1),
+        vec![],
+        "should not include a block from prior to the range"
+    );
+    assert_eq!(
+        slots_in_range(head_slot, 16),
+        vec![head_slot],
+        "should stop at the head"
+    );
+    assert_eq!(
+        slots_in_range(head_slot + 1, 16),
+        vec![],
+        "should be empty after the head"
+    );
+
+    let blocks = chain
+        .blocks_in_range(skipped_slot, 2)
+        .expect("should get blocks in range")
+        .blocks;
+    assert_eq!(blocks.len(), 1, "should only include the head block");
+    assert_eq!(blocks[0].0, head_root);
+    assert_eq!(blocks[0].1.canonical_root(), head_root);
+
+    let genesis_root = chain.genesis_block_root;
+    let roots = chain
+        .get_blocks(&[head_root, genesis_root])
+        .expect("should get blocks")
+        .iter()
+        .map(|block| block.canonical_root())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roots,
+        vec![head_root, genesis_root],
+        "should return the blocks in the given order"
+    );
+
+    let unknown_root = Hash256::from_low_u64_be(42);
+    assert_eq!(
+        chain.get_blocks(&[head_root, unknown_root]),
+        Err(BeaconChainError::MissingBeaconBlock(unknown_root)),
+        "should not find an unknown block"
+    );
+}
+
+#[test]
+fn validator_balance_history_omits_unavailable_epochs() {
+    let slots_per_epoch = MinimalEthSpec::slots_per_epoch();
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+    let validator_index = 3;
+
+    harness.extend_chain(
+        slots_per_epoch as usize * 3,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    assert_eq!(
+        chain.head_info().expect("should get head info").slot,
+        Epoch::new(3).start_slot(slots_per_epoch),
+        "the head should be at the start of the third epoch"
+    );
+
+    let epoch_start_state = |epoch: u64| {
+        chain
+            .state_at_slot(
+                Epoch::new(epoch).start_slot(slots_per_epoch),
+                StateSkipConfig::WithStateRoots,
+            )
+            .expect("should get state")
+    };
+
+    let history = chain
+        .validator_balance_history(validator_index, Epoch::new(0), Epoch::new(5))
+        .expect("should get balance history");
+    assert_eq!(
+        history,
+        (0..=3)
+            .map(|epoch| (
+                Epoch::new(epoch),
+                epoch_start_state(epoch).balances[validator_index]
+            ))
+            .collect::<Vec<_>>(),
+        "should include each epoch up to the head"
+    );
+
+    let pruned = epoch_start_state(2);
+    chain
+        .store
+        .delete_state(&pruned.canonical_root(), pruned.slot)
+        .expect("should delete state");
+
+    let epochs = chain
+        .validator_balance_history(validator_index, Epoch::new(1), Epoch::new(3))
+        .expect("should get balance history")
+        .into_iter()
+        .map(|(epoch, _)| epoch)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        epochs,
+        vec![Epoch::new(1), Epoch::new(3)],
+        "should omit the epoch with the missing state"
+    );
+
+    assert_eq!(
+        chain.validator_balance_history(VALIDATOR_COUNT, Epoch::new(0), Epoch::new(3)),
+        Ok(vec![]),
+        "an unknown validator should have no history"
+    );
+    assert_eq!(
+        chain.validator_balance_history(
+            validator_index,
+            Epoch::new(0),
+            Epoch::new(MAX_BALANCE_HISTORY_EPOCHS)
+        ),
+        Err(BeaconChainError::BalanceHistoryRangeTooLarge {
+            start_epoch: Epoch::new(0),
+            end_epoch: Epoch::new(MAX_BALANCE_HISTORY_EPOCHS),
+            max_epochs: MAX_BALANCE_HISTORY_EPOCHS,
+        }),
+        "should reject a range which is too wide"
+    );
+}
+
+#[test]
+fn root_at_slot_returns_error_for_missing_historical_state() {
+    let slots_per_historical_root = MinimalEthSpec::slots_per_historical_root() as u64;
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        (slots_per_historical_root + MinimalEthSpec::slots_per_epoch()) as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    // The head state only has the roots of the most recent `slots_per_historical_root` slots, so
+    // iterating further back requires the state at the prior multiple of that length.
+    let historical_slot = Slot::new(slots_per_historical_root);
+    let historical_state_root = chain
+        .state_root_at_slot(historical_slot)
+        .expect("should iterate to historical slot")
+        .expect("should find historical state root");
+    let recent_slot = Slot::new(slots_per_historical_root / 2);
+    let old_slot = Slot::new(1);
+
+    let old_block_root = chain
+        .root_at_slot(old_slot)
+        .expect("should iterate to old slot")
+        .expect("should find old block root");
+    assert!(old_block_root != Hash256::zero());
+
+    chain
+        .store
+        .delete_state(&historical_state_root, historical_slot)
+        .expect("should delete state");
+
+    assert!(
+        chain
+            .root_at_slot(recent_slot)
+            .expect("should not need the deleted state")
+            .is_some(),
+        "should find roots in the head state"
+    );
+
+    let expected_error = || {
+        BeaconChainError::DBError(store::Error::MissingHistoricalState {
+            state_root: historical_state_root,
+            slot: historical_slot,
+        })
+    };
+    assert_eq!(
+        chain.root_at_slot(old_slot),
+        Err(expected_error()),
+        "should not mistake a missing state for a missing root"
+    );
+    assert_eq!(
+        chain.get_ancestor_block_root(chain.head_info().unwrap().block_root, old_slot),
+        Err(expected_error())
+    );
+    assert_eq!(
+        chain
+            .state_at_slot(old_slot, StateSkipConfig::WithStateRoots)
+            .map(|state| state.slot),
+        Err(expected_error())
+    );
+}
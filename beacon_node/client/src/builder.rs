@@ -4,12 +4,15 @@ use crate::Client;
 use beacon_chain::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::{CachingEth1Backend, Eth1Chain},
+    events::TeeEventHandler,
+    schema_change::migrate_schema,
     slot_clock::{SlotClock, SystemTimeSlotClock},
     store::{
         migrate::{BackgroundMigrator, Migrate, NullMigrator},
-        DiskStore, MemoryStore, SimpleDiskStore, Store, StoreConfig,
+        DiskStore, MemoryStore, SimpleDiskStore, Store, StoreConfig, CURRENT_SCHEMA_VERSION,
     },
-    BeaconChain, BeaconChainTypes, Eth1ChainBackend, EventHandler,
+    BeaconChain, BeaconChainMode, BeaconChainTypes, Eth1ChainBackend, EventHandler,
+    DEFAULT_MAX_INVALID_BLOCK_DUMP_FILES,
 };
 use environment::RuntimeContext;
 use eth1::{Config as Eth1Config, Service as Eth1Service};
@@ -21,7 +24,7 @@ use genesis::{
 };
 use lighthouse_bootstrap::Bootstrapper;
 use network::{NetworkConfig, NetworkMessage, Service as NetworkService};
-use slog::info;
+use slog::{info, warn};
 use ssz::Decode;
 use std::net::SocketAddr;
 use std::path::Path;
@@ -125,6 +128,20 @@ where
         let runtime_context = self.runtime_context.clone();
         let eth_spec_instance = self.eth_spec_instance.clone();
         let data_dir = config.data_dir.clone();
+        let lock_timeouts = config.lock_timeouts;
+        let readiness_config = config.readiness;
+        let max_skip_slots = config.max_skip_slots;
+        let max_block_range = config.max_block_range;
+        let dump_invalid_blocks = config.dump_invalid_blocks.clone();
+        let max_future_slot_tolerance = config.max_future_slot_tolerance;
+        let slashing_detection = !config.disable_slashing_detection;
+        let mode = if config.follower_mode {
+            BeaconChainMode::Follower
+        } else {
+            BeaconChainMode::Full
+        };
+        let validator_monitor_indices = config.validator_monitor_indices.clone();
+        let validator_monitor_pubkeys = config.validator_monitor_pubkeys.clone();
 
         future::ok(())
             .and_then(move |()| {
@@ -141,11 +158,39 @@ where
                 let spec = chain_spec
                     .ok_or_else(|| "beacon_chain_start_method requires a chain spec".to_string())?;
 
+                // Migrate the database before any of its persisted containers are read.
+                let schema_version = store
+                    .get_schema_version()
+                    .map_err(|e| format!("Unable to read database schema version: {:?}", e))?;
+                migrate_schema::<TEthSpec, _>(
+                    store.clone(),
+                    schema_version,
+                    CURRENT_SCHEMA_VERSION,
+                )
+                .map_err(|e| format!("Unable to migrate database schema: {:?}", e))?;
+                if schema_version != CURRENT_SCHEMA_VERSION {
+                    info!(
+                        context.log,
+                        "Migrated database schema";
+                        "from" => schema_version.as_u64(),
+                        "to" => CURRENT_SCHEMA_VERSION.as_u64(),
+                    );
+                }
+
                 let builder = BeaconChainBuilder::new(eth_spec_instance)
                     .logger(context.log.clone())
                     .store(store)
                     .store_migrator(store_migrator)
                     .data_dir(data_dir)
+                    .lock_timeouts(lock_timeouts)
+                    .readiness_config(readiness_config)
+                    .max_skip_slots(max_skip_slots)
+                    .max_block_range(max_block_range)
+                    .dump_invalid_blocks(dump_invalid_blocks, DEFAULT_MAX_INVALID_BLOCK_DUMP_FILES)
+                    .max_future_slot_tolerance(max_future_slot_tolerance)
+                    .slashing_detection(slashing_detection)
+                    .mode(mode)
+                    .monitor_validators(validator_monitor_indices, validator_monitor_pubkeys)
                     .custom_spec(spec.clone());
 
                 Ok((builder, spec, context))
@@ -416,7 +461,7 @@ impl<TStore, TStoreMigrator, TSlotClock, TEth1Backend, TEthSpec>
             TSlotClock,
             TEth1Backend,
             TEthSpec,
-            WebSocketSender<TEthSpec>,
+            TeeEventHandler<TEthSpec>,
         >,
     >
 where
@@ -426,7 +471,8 @@ where
     TEth1Backend: Eth1ChainBackend<TEthSpec, TStore> + 'static,
     TEthSpec: EthSpec + 'static,
 {
-    /// Specifies that the `BeaconChain` should publish events using the WebSocket server.
+    /// Specifies that the `BeaconChain` should publish events using the WebSocket server, as well
+    /// as to any subscribers (e.g., HTTP event streams).
     pub fn websocket_event_handler(mut self, config: WebSocketConfig) -> Result<Self, String> {
         let context = self
             .runtime_context
@@ -449,7 +495,7 @@ where
         if let Some(signal) = exit_signal {
             self.exit_signals.push(signal);
         }
-        self.event_handler = Some(sender);
+        self.event_handler = Some(TeeEventHandler::new(sender));
         self.websocket_listen_addr = listening_addr;
 
         Ok(self)
@@ -631,18 +677,25 @@ where
         } else {
             beacon_chain_builder
                 .get_persisted_eth1_backend()?
-                .map(|persisted| {
+                .and_then(|persisted| {
                     Eth1Chain::from_ssz_container(
                         &persisted,
                         config.clone(),
                         store.clone(),
                         &context.log,
                     )
-                    .map(|chain| chain.into_backend())
+                    .map_err(|e| {
+                        warn!(
+                            context.log,
+                            "Discarding undecodable eth1 cache";
+                            "error" => e,
+                            "info" => "a new, empty cache will be used instead"
+                        )
+                    })
+                    .ok()
                 })
-                .unwrap_or_else(|| {
-                    Ok(CachingEth1Backend::new(config, context.log.clone(), store))
-                })?
+                .map(|chain| chain.into_backend())
+                .unwrap_or_else(|| CachingEth1Backend::new(config, context.log.clone(), store))
         };
 
         self.eth1_service = None;
@@ -1,7 +1,12 @@
+use beacon_chain::{
+    LockTimeouts, ReadinessConfig, DEFAULT_MAX_BLOCK_RANGE, MAXIMUM_GOSSIP_CLOCK_DISPARITY,
+};
 use network::NetworkConfig;
 use serde_derive::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
+use types::PublicKeyBytes;
 
 pub const DEFAULT_DATADIR: &str = ".lighthouse";
 
@@ -67,6 +72,52 @@ pub struct Config {
     pub rest_api: rest_api::Config,
     pub websocket_server: websocket_server::Config,
     pub eth1: eth1::Config,
+    /// The time-outs used when taking locks on the components of the `BeaconChain`.
+    ///
+    /// Defaulted when absent, so that configs written by earlier versions can still be read.
+    #[serde(default)]
+    pub lock_timeouts: LockTimeouts,
+    /// The thresholds used to determine whether the node is ready to serve validator duties.
+    #[serde(default)]
+    pub readiness: ReadinessConfig,
+    /// The maximum number of slots that may be skipped when advancing a state. Unlimited if
+    /// `None`.
+    #[serde(default)]
+    pub max_skip_slots: Option<u64>,
+    /// The maximum number of slots that may be covered by a single block range query (e.g., a
+    /// `BlocksByRange` request).
+    #[serde(default = "default_max_block_range")]
+    pub max_block_range: u64,
+    /// The directory to which blocks that fail processing are written, along with their
+    /// pre-states. Blocks are not written if `None`.
+    #[serde(default)]
+    pub dump_invalid_blocks: Option<PathBuf>,
+    /// How far before the start of its slot a block may be processed, to allow for clock skew.
+    #[serde(default = "default_max_future_slot_tolerance")]
+    pub max_future_slot_tolerance: Duration,
+    /// If true, attester and proposer slashings will not be detected amongst the attestations and
+    /// blocks processed by the node.
+    #[serde(default)]
+    pub disable_slashing_detection: bool,
+    /// If true, the node follows the chain without producing blocks or queuing operations for
+    /// inclusion in them.
+    #[serde(default)]
+    pub follower_mode: bool,
+    /// The indices of validators whose block proposals and attestation inclusions are reported.
+    #[serde(default)]
+    pub validator_monitor_indices: Vec<usize>,
+    /// The public keys of validators whose block proposals and attestation inclusions are
+    /// reported.
+    #[serde(default)]
+    pub validator_monitor_pubkeys: Vec<PublicKeyBytes>,
+}
+
+fn default_max_future_slot_tolerance() -> Duration {
+    MAXIMUM_GOSSIP_CLOCK_DISPARITY
+}
+
+fn default_max_block_range() -> u64 {
+    DEFAULT_MAX_BLOCK_RANGE
 }
 
 impl Default for Config {
@@ -86,6 +137,16 @@ impl Default for Config {
             dummy_eth1_backend: false,
             sync_eth1_chain: false,
             eth1: <_>::default(),
+            lock_timeouts: <_>::default(),
+            readiness: <_>::default(),
+            max_skip_slots: None,
+            max_block_range: default_max_block_range(),
+            dump_invalid_blocks: None,
+            max_future_slot_tolerance: default_max_future_slot_tolerance(),
+            disable_slashing_detection: false,
+            follower_mode: false,
+            validator_monitor_indices: vec![],
+            validator_monitor_pubkeys: vec![],
         }
     }
 }
@@ -95,6 +95,20 @@ impl BlockCache {
         }
     }
 
+    /// Drops all blocks with a number lower than `block_number`, returning the number of blocks
+    /// that were dropped.
+    pub fn prune_below(&mut self, block_number: u64) -> usize {
+        let prune_count = self
+            .blocks
+            .iter()
+            .take_while(|block| block.number < block_number)
+            .count();
+
+        self.blocks.drain(..prune_count);
+
+        prune_count
+    }
+
     /// Returns the range of block numbers stored in the block cache. All blocks in this range can
     /// be accessed.
     fn available_block_numbers(&self) -> Option<RangeInclusive<u64>> {
@@ -200,6 +214,27 @@ mod tests {
         cache.insert_root_or_child(s)
     }
 
+    #[test]
+    fn prune_below() {
+        let n = 16;
+        let blocks = get_blocks(n, 10);
+
+        let mut cache = BlockCache::default();
+
+        for block in blocks {
+            insert(&mut cache, block.clone()).expect("should add consecutive blocks");
+        }
+
+        assert_eq!(cache.prune_below(4), 4, "should prune four blocks");
+        assert_eq!(cache.prune_below(2), 0, "should not prune blocks twice");
+        assert_eq!(cache.len(), n - 4, "should retain the other blocks");
+        assert_eq!(cache.lowest_block_number(), Some(4));
+        assert!(
+            insert(&mut cache, get_block(n as u64, 10)).is_ok(),
+            "should add a child after pruning"
+        );
+    }
+
     #[test]
     fn truncate() {
         let n = 16;
@@ -25,15 +25,47 @@ pub enum Error {
     ///
     /// E.g., you cannot request deposit 10 when the deposit count is 9.
     DepositCountInvalid { deposit_count: u64, range_end: u64 },
+    /// The logs of some of the requested deposits have been pruned from the cache.
+    DepositsPruned {
+        requested_start: u64,
+        first_retained: u64,
+    },
     /// Error with the merkle tree for deposits.
     DepositTreeError(merkle_proof::MerkleTreeError),
     /// An unexpected condition was encountered.
     InternalError(String),
 }
 
+/// The layout of `SszDepositCache` prior to the pruning of deposit logs.
+///
+/// Retained so that caches persisted in this layout can be converted with `SszDepositCache::from`.
+#[derive(Encode, Decode, Clone)]
+pub struct SszDepositCacheV0 {
+    logs: Vec<DepositLog>,
+    leaves: Vec<Hash256>,
+    deposit_contract_deploy_block: u64,
+    deposit_roots: Vec<Hash256>,
+}
+
+impl From<SszDepositCacheV0> for SszDepositCache {
+    /// No logs were pruned from a cache in the prior layout.
+    fn from(cache: SszDepositCacheV0) -> Self {
+        Self {
+            logs: cache.logs,
+            first_retained_index: 0,
+            last_pruned_block_number: None,
+            leaves: cache.leaves,
+            deposit_contract_deploy_block: cache.deposit_contract_deploy_block,
+            deposit_roots: cache.deposit_roots,
+        }
+    }
+}
+
 #[derive(Encode, Decode, Clone)]
 pub struct SszDepositCache {
     logs: Vec<DepositLog>,
+    first_retained_index: u64,
+    last_pruned_block_number: Option<u64>,
     leaves: Vec<Hash256>,
     deposit_contract_deploy_block: u64,
     deposit_roots: Vec<Hash256>,
@@ -43,6 +75,8 @@ impl SszDepositCache {
     pub fn from_deposit_cache(cache: &DepositCache) -> Self {
         Self {
             logs: cache.logs.clone(),
+            first_retained_index: cache.first_retained_index,
+            last_pruned_block_number: cache.last_pruned_block_number,
             leaves: cache.leaves.clone(),
             deposit_contract_deploy_block: cache.deposit_contract_deploy_block,
             deposit_roots: cache.deposit_roots.clone(),
@@ -53,8 +87,11 @@ impl SszDepositCache {
         let deposit_tree =
             DepositDataTree::create(&self.leaves, self.leaves.len(), DEPOSIT_TREE_DEPTH);
         // Check for invalid SszDepositCache conditions
-        if self.leaves.len() != self.logs.len() {
-            return Err("Invalid SszDepositCache: logs and leaves should have equal length".into());
+        if self.leaves.len() as u64 != self.first_retained_index + self.logs.len() as u64 {
+            return Err(
+                "Invalid SszDepositCache: there should be a leaf for each pruned or retained log"
+                    .into(),
+            );
         }
         // `deposit_roots` also includes the zero root
         if self.leaves.len() + 1 != self.deposit_roots.len() {
@@ -65,6 +102,8 @@ impl SszDepositCache {
         }
         Ok(DepositCache {
             logs: self.logs.clone(),
+            first_retained_index: self.first_retained_index,
+            last_pruned_block_number: self.last_pruned_block_number,
             leaves: self.leaves.clone(),
             deposit_contract_deploy_block: self.deposit_contract_deploy_block,
             deposit_tree,
@@ -77,7 +116,12 @@ impl SszDepositCache {
 ///
 /// Provides `Deposit` objects with merkle proofs included.
 pub struct DepositCache {
+    /// The logs of all deposits with an index of at least `first_retained_index`.
     logs: Vec<DepositLog>,
+    /// The number of deposits whose logs have been pruned (see `Self::prune_logs`).
+    first_retained_index: u64,
+    /// The block number of the last deposit whose log has been pruned, if any.
+    last_pruned_block_number: Option<u64>,
     leaves: Vec<Hash256>,
     deposit_contract_deploy_block: u64,
     /// An incremental merkle tree which represents the current state of the
@@ -94,6 +138,8 @@ impl Default for DepositCache {
         let deposit_roots = vec![deposit_tree.root()];
         DepositCache {
             logs: Vec::new(),
+            first_retained_index: 0,
+            last_pruned_block_number: None,
             leaves: Vec::new(),
             deposit_contract_deploy_block: 1,
             deposit_tree,
@@ -112,29 +158,58 @@ impl DepositCache {
         }
     }
 
-    /// Returns the number of deposits available in the cache.
+    /// Returns the number of deposits known to the cache, including those whose logs have been
+    /// pruned.
     pub fn len(&self) -> usize {
-        self.logs.len()
+        self.first_retained_index as usize + self.logs.len()
     }
 
-    /// True if the cache does not store any blocks.
+    /// True if the cache does not know of any deposits.
     pub fn is_empty(&self) -> bool {
-        self.logs.is_empty()
+        self.len() == 0
+    }
+
+    /// Returns the number of deposit logs that have not been pruned.
+    pub fn retained_logs_len(&self) -> usize {
+        self.logs.len()
     }
 
     /// Returns the block number for the most recent deposit in the cache.
     pub fn latest_block_number(&self) -> Option<u64> {
-        self.logs.last().map(|log| log.block_number)
+        self.logs
+            .last()
+            .map(|log| log.block_number)
+            .or(self.last_pruned_block_number)
     }
 
-    /// Returns an iterator over all the logs in `self`.
+    /// Returns an iterator over all the logs in `self` that have not been pruned.
     pub fn iter(&self) -> impl Iterator<Item = &DepositLog> {
         self.logs.iter()
     }
 
-    /// Returns the i'th deposit log.
+    /// Returns the i'th deposit log, if it has not been pruned.
     pub fn get(&self, i: usize) -> Option<&DepositLog> {
-        self.logs.get(i)
+        self.logs
+            .get(i.checked_sub(self.first_retained_index as usize)?)
+    }
+
+    /// Drops the logs of all deposits with an index lower than `deposit_index`, returning the
+    /// number of logs that were dropped.
+    ///
+    /// The leaves and roots of the deposit tree are retained, so proofs may still be generated for
+    /// the remaining deposits. Deposits with pruned logs can no longer be obtained with
+    /// `Self::get_deposits`.
+    pub fn prune_logs(&mut self, deposit_index: u64) -> usize {
+        let prune_count = std::cmp::min(deposit_index, self.len() as u64)
+            .saturating_sub(self.first_retained_index) as usize;
+
+        if prune_count > 0 {
+            self.last_pruned_block_number = Some(self.logs[prune_count - 1].block_number);
+            self.logs.drain(..prune_count);
+            self.first_retained_index += prune_count as u64;
+        }
+
+        prune_count
     }
 
     /// Adds `log` to self.
@@ -147,7 +222,7 @@ impl DepositCache {
     /// - If a log with index `log.index - 1` is not already present in `self` (ignored when empty).
     /// - If a log with `log.index` is already known, but the given `log` is distinct to it.
     pub fn insert_log(&mut self, log: DepositLog) -> Result<(), Error> {
-        match log.index.cmp(&(self.logs.len() as u64)) {
+        match log.index.cmp(&(self.len() as u64)) {
             Ordering::Equal => {
                 let deposit = log.deposit_data.tree_hash_root();
                 self.leaves.push(deposit);
@@ -158,16 +233,14 @@ impl DepositCache {
                 self.deposit_roots.push(self.deposit_tree.root());
                 Ok(())
             }
-            Ordering::Less => {
-                if self.logs[log.index as usize] == log {
-                    Ok(())
-                } else {
-                    Err(Error::DuplicateDistinctLog(log.index))
-                }
-            }
+            Ordering::Less => match self.get(log.index as usize) {
+                Some(known_log) if *known_log != log => Err(Error::DuplicateDistinctLog(log.index)),
+                // The log is either identical to the known log, or it has been pruned.
+                _ => Ok(()),
+            },
             Ordering::Greater => Err(Error::NonConsecutive {
                 log_index: log.index,
-                expected: self.logs.len(),
+                expected: self.len(),
             }),
         }
     }
@@ -183,6 +256,7 @@ impl DepositCache {
     ///
     /// - If `deposit_count` is larger than `end`.
     /// - There are not sufficient deposits in the tree to generate the proof.
+    /// - The logs of any deposits from `start` have been pruned.
     pub fn get_deposits(
         &self,
         start: u64,
@@ -196,18 +270,23 @@ impl DepositCache {
                 deposit_count,
                 range_end: end,
             })
-        } else if end > self.logs.len() as u64 {
+        } else if end > self.len() as u64 {
             // The range of requested deposits exceeds the deposits stored locally.
             Err(Error::InsufficientDeposits {
                 requested: end,
-                known_deposits: self.logs.len(),
+                known_deposits: self.len(),
             })
         } else if deposit_count > self.leaves.len() as u64 {
             // There are not `deposit_count` known deposit roots, so we can't build the merkle tree
             // to prove into.
             Err(Error::InsufficientDeposits {
                 requested: deposit_count,
-                known_deposits: self.logs.len(),
+                known_deposits: self.len(),
+            })
+        } else if start < self.first_retained_index && start < end {
+            Err(Error::DepositsPruned {
+                requested_start: start,
+                first_retained: self.first_retained_index,
             })
         } else {
             let leaves = self
@@ -224,9 +303,13 @@ impl DepositCache {
 
             let tree = DepositDataTree::create(leaves, deposit_count as usize, tree_depth);
 
+            let first_retained_index = self.first_retained_index;
             let deposits = self
                 .logs
-                .get(start as usize..end as usize)
+                .get(
+                    start.saturating_sub(first_retained_index) as usize
+                        ..end.saturating_sub(first_retained_index) as usize,
+                )
                 .ok_or_else(|| Error::InternalError("Unable to get known log".into()))?
                 .iter()
                 .map(|deposit_log| {
@@ -257,6 +340,19 @@ impl DepositCache {
         if block_number < self.deposit_contract_deploy_block {
             return None;
         }
+        // Return the number of pruned deposits if the block number queried is after the last pruned
+        // deposit, but before the first retained deposit. If it is before the last pruned deposit,
+        // the deposit count is unknown.
+        if let Some(last_pruned_block_number) = self.last_pruned_block_number {
+            if block_number < last_pruned_block_number {
+                return None;
+            }
+            if self.logs.first().map_or(true, |first_deposit| {
+                first_deposit.block_number > block_number
+            }) {
+                return Some(self.first_retained_index);
+            }
+        }
         // Return 0 if block_num queried is before first deposit
         if let Some(first_deposit) = self.logs.first() {
             if first_deposit.block_number > block_number {
@@ -291,6 +387,7 @@ pub mod tests {
     use super::*;
     use crate::deposit_log::tests::EXAMPLE_LOG;
     use crate::http::Log;
+    use ssz::{Decode, Encode};
 
     pub const TREE_DEPTH: usize = 32;
 
@@ -439,4 +536,97 @@ pub mod tests {
         // Range higher than count.
         assert!(tree.get_deposits(0, 4, 2, TREE_DEPTH).is_err());
     }
+
+    #[test]
+    fn prune_logs() {
+        let n = 16;
+        let mut tree = DepositCache::default();
+
+        for i in 0..n {
+            let mut log = example_log();
+            log.index = i;
+            log.block_number = i * 2 + 1;
+            log.deposit_data.withdrawal_credentials = Hash256::from_low_u64_be(i);
+            tree.insert_log(log).expect("should add consecutive logs")
+        }
+
+        let (full_root, full_deposits) = tree
+            .get_deposits(8, n, n, TREE_DEPTH)
+            .expect("should get deposits before pruning");
+
+        assert_eq!(tree.prune_logs(8), 8, "should prune eight logs");
+        assert_eq!(tree.prune_logs(4), 0, "should not prune logs twice");
+        assert_eq!(tree.len(), n as usize, "should count pruned deposits");
+        assert_eq!(tree.retained_logs_len(), 8, "should retain eight logs");
+        assert!(tree.get(7).is_none(), "should not return a pruned log");
+        assert_eq!(tree.get(8).map(|log| log.index), Some(8));
+
+        assert_eq!(
+            tree.get_deposits(8, n, n, TREE_DEPTH),
+            Ok((full_root, full_deposits)),
+            "should generate the same proofs after pruning"
+        );
+        assert_eq!(
+            tree.get_deposits(7, n, n, TREE_DEPTH),
+            Err(Error::DepositsPruned {
+                requested_start: 7,
+                first_retained: 8
+            })
+        );
+
+        // The last pruned deposit is in block 15, the first retained deposit is in block 17.
+        assert_eq!(tree.get_deposit_count_from_cache(14), None);
+        assert_eq!(tree.get_deposit_count_from_cache(16), Some(8));
+        assert_eq!(tree.get_deposit_count_from_cache(17), Some(9));
+
+        let mut log = example_log();
+        log.index = 3;
+        assert!(
+            tree.insert_log(log).is_ok(),
+            "should ignore a log that has been pruned"
+        );
+
+        let decoded = SszDepositCache::from_deposit_cache(&tree)
+            .to_deposit_cache()
+            .expect("should decode a pruned cache");
+        assert_eq!(decoded.len(), tree.len());
+        assert_eq!(decoded.retained_logs_len(), tree.retained_logs_len());
+    }
+
+    #[test]
+    fn converts_v0_layout() {
+        let n = 4;
+        let mut tree = DepositCache::new(7);
+
+        for i in 0..n {
+            let mut log = example_log();
+            log.index = i;
+            log.deposit_data.withdrawal_credentials = Hash256::from_low_u64_be(i);
+            tree.insert_log(log).expect("should add consecutive logs")
+        }
+
+        let v0 = SszDepositCacheV0 {
+            logs: tree.logs.clone(),
+            leaves: tree.leaves.clone(),
+            deposit_contract_deploy_block: tree.deposit_contract_deploy_block,
+            deposit_roots: tree.deposit_roots.clone(),
+        };
+
+        assert!(
+            SszDepositCache::from_ssz_bytes(&v0.as_ssz_bytes()).is_err(),
+            "the current layout should not decode the v0 layout"
+        );
+
+        let decoded = SszDepositCache::from(v0)
+            .to_deposit_cache()
+            .expect("should convert a v0 cache");
+        assert_eq!(decoded.len(), n as usize);
+        assert_eq!(decoded.retained_logs_len(), n as usize);
+        assert_eq!(decoded.deposit_contract_deploy_block, 7);
+        assert_eq!(
+            decoded.get_deposits(0, n, n, TREE_DEPTH),
+            tree.get_deposits(0, n, n, TREE_DEPTH),
+            "should generate the same proofs after conversion"
+        );
+    }
 }
@@ -0,0 +1,213 @@
+use crate::http::{get_block, get_block_number, get_network_id};
+use futures::{
+    future::{self, Either},
+    Future,
+};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Timeout for each of the calls made when checking the health of an endpoint.
+const HEALTH_CHECK_TIMEOUT_MILLIS: u64 = 5_000;
+
+/// The result of checking whether an eth1 endpoint is suitable for following the eth1 chain.
+#[derive(Debug, PartialEq, Clone)]
+pub enum EndpointHealth {
+    /// The endpoint is on the expected network and its head is recent.
+    Healthy,
+    /// The endpoint is on the expected network, but its head is `head_age` old. It is only used if
+    /// no healthy endpoint is available.
+    Stale { head_age: Duration },
+    /// The endpoint is following a different eth1 network.
+    WrongNetwork { expected: u64, found: u64 },
+    /// The endpoint could not be contacted, or returned an invalid response.
+    Offline(String),
+}
+
+/// Tracks which of the configured endpoints is used to update the eth1 caches.
+///
+/// Endpoints are identified by their index in `Config::endpoints`, where `0` is the primary.
+#[derive(Debug, Default)]
+pub struct EndpointState {
+    /// The index of the endpoint that is currently in use.
+    pub active: usize,
+    /// Set if the most recent update using the `active` endpoint failed.
+    pub active_failed: bool,
+    /// The last time the primary endpoint was tried whilst a fallback was active.
+    pub last_primary_attempt: Option<Instant>,
+}
+
+impl EndpointState {
+    /// Returns the indices of `num_endpoints` endpoints in the order in which their health should
+    /// be checked:
+    ///
+    /// - The primary, if a fallback is active and the primary has not been tried for
+    ///   `primary_retry_interval`.
+    /// - The active endpoint, unless it failed during the last update.
+    /// - All other endpoints, in order of priority.
+    /// - The active endpoint, if it failed during the last update.
+    pub fn candidate_order(
+        &mut self,
+        num_endpoints: usize,
+        now: Instant,
+        primary_retry_interval: Duration,
+    ) -> Vec<usize> {
+        let active = if self.active < num_endpoints {
+            self.active
+        } else {
+            0
+        };
+
+        let mut order = Vec::with_capacity(num_endpoints);
+
+        if active != 0
+            && self.last_primary_attempt.map_or(true, |last_attempt| {
+                now.duration_since(last_attempt) >= primary_retry_interval
+            })
+        {
+            self.last_primary_attempt = Some(now);
+            order.push(0);
+        }
+
+        if !self.active_failed && !order.contains(&active) {
+            order.push(active);
+        }
+
+        for index in 0..num_endpoints {
+            if index != active && !order.contains(&index) {
+                order.push(index);
+            }
+        }
+
+        if !order.contains(&active) {
+            order.push(active);
+        }
+
+        order
+    }
+
+    /// Sets the endpoint at `index` as active, returning `true` if it was not already active.
+    pub fn set_active(&mut self, index: usize, now: Instant) -> bool {
+        self.active_failed = false;
+
+        if index == self.active {
+            false
+        } else {
+            // Don't retry the primary immediately after moving away from it.
+            if self.active == 0 {
+                self.last_primary_attempt = Some(now);
+            }
+
+            self.active = index;
+            true
+        }
+    }
+}
+
+/// Checks the network and the age of the head block of `endpoint`.
+///
+/// The network is only checked if a `network_id` is supplied. Likewise, the head is only
+/// considered stale if a `max_head_age` is supplied.
+pub fn check_endpoint_health(
+    endpoint: String,
+    network_id: Option<u64>,
+    max_head_age: Option<Duration>,
+) -> impl Future<Item = EndpointHealth, Error = ()> {
+    let timeout = Duration::from_millis(HEALTH_CHECK_TIMEOUT_MILLIS);
+
+    let network_future = match network_id {
+        Some(expected) => Either::A(get_network_id(&endpoint, timeout).map(move |found| {
+            if found == expected {
+                None
+            } else {
+                Some(EndpointHealth::WrongNetwork { expected, found })
+            }
+        })),
+        None => Either::B(future::ok(None)),
+    };
+
+    network_future
+        .and_then(move |wrong_network| match wrong_network {
+            Some(health) => Either::A(future::ok(health)),
+            None => Either::B(
+                get_block_number(&endpoint, timeout)
+                    .and_then(move |block_number| get_block(&endpoint, block_number, timeout))
+                    .map(move |head| {
+                        let head_age = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .ok()
+                            .and_then(|now| now.checked_sub(Duration::from_secs(head.timestamp)))
+                            .unwrap_or_else(|| Duration::from_secs(0));
+
+                        match max_head_age {
+                            Some(max_head_age) if head_age > max_head_age => {
+                                EndpointHealth::Stale { head_age }
+                            }
+                            _ => EndpointHealth::Healthy,
+                        }
+                    }),
+            ),
+        })
+        .or_else(|e| Ok(EndpointHealth::Offline(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RETRY: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn primary_first_by_default() {
+        let mut state = EndpointState::default();
+
+        assert_eq!(
+            state.candidate_order(3, Instant::now(), RETRY),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn failed_active_endpoint_is_tried_last() {
+        let mut state = EndpointState::default();
+        state.active_failed = true;
+
+        assert_eq!(
+            state.candidate_order(3, Instant::now(), RETRY),
+            vec![1, 2, 0]
+        );
+    }
+
+    #[test]
+    fn periodically_retries_primary() {
+        let now = Instant::now();
+        let mut state = EndpointState::default();
+
+        assert!(state.set_active(2, now));
+        assert!(!state.set_active(2, now));
+
+        assert_eq!(
+            state.candidate_order(3, now, RETRY),
+            vec![2, 0, 1],
+            "should not retry the primary immediately"
+        );
+
+        let later = now + RETRY;
+        assert_eq!(
+            state.candidate_order(3, later, RETRY),
+            vec![0, 2, 1],
+            "should retry the primary after the interval"
+        );
+        assert_eq!(
+            state.candidate_order(3, later, RETRY),
+            vec![2, 0, 1],
+            "should wait another interval before retrying the primary"
+        );
+    }
+
+    #[test]
+    fn ignores_removed_active_endpoint() {
+        let mut state = EndpointState::default();
+        state.set_active(2, Instant::now());
+
+        assert_eq!(state.candidate_order(1, Instant::now(), RETRY), vec![0]);
+    }
+}
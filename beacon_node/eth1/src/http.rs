@@ -56,6 +56,25 @@ pub fn get_block_number(
         .map_err(|e| format!("Failed to get block number: {}", e))
 }
 
+/// Returns the network id reported by `net_version`.
+///
+/// Uses HTTP JSON RPC at `endpoint`. E.g., `http://localhost:8545`.
+pub fn get_network_id(
+    endpoint: &str,
+    timeout: Duration,
+) -> impl Future<Item = u64, Error = String> {
+    send_rpc_request(endpoint, "net_version", json!([]), timeout)
+        .and_then(|response_body| {
+            response_result(&response_body)?
+                .ok_or_else(|| "No result field was returned for network id".to_string())?
+                .as_str()
+                .ok_or_else(|| "Data was not string")?
+                .parse::<u64>()
+                .map_err(|e| format!("Failed to parse network id: {:?}", e))
+        })
+        .map_err(|e| format!("Failed to get network id: {}", e))
+}
+
 /// Gets a block hash by block number.
 ///
 /// Uses HTTP JSON RPC at `endpoint`. E.g., `http://localhost:8545`.
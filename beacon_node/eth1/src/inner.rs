@@ -1,7 +1,8 @@
 use crate::Config;
 use crate::{
     block_cache::BlockCache,
-    deposit_cache::{DepositCache, SszDepositCache},
+    deposit_cache::{DepositCache, SszDepositCache, SszDepositCacheV0},
+    endpoint::EndpointState,
 };
 use parking_lot::RwLock;
 use ssz::{Decode, Encode};
@@ -28,9 +29,21 @@ pub struct Inner {
     pub block_cache: RwLock<BlockCache>,
     pub deposit_cache: RwLock<DepositUpdater>,
     pub config: RwLock<Config>,
+    pub endpoints: RwLock<EndpointState>,
 }
 
 impl Inner {
+    /// Returns the endpoint that should be used to update the caches.
+    pub fn active_endpoint(&self) -> String {
+        let config = self.config.read();
+
+        config
+            .endpoints()
+            .get(self.endpoints.read().active)
+            .cloned()
+            .unwrap_or_else(|| config.endpoint.clone())
+    }
+
     /// Prunes the block cache to `self.target_block_cache_len`.
     ///
     /// Is a no-op if `self.target_block_cache_len` is `None`.
@@ -48,12 +61,29 @@ impl Inner {
 
     /// Recover `Inner` given byte representation of eth1 deposit and block caches.
     pub fn from_bytes(bytes: &[u8], config: Config) -> Result<Self, String> {
-        let ssz_cache = SszEth1Cache::from_ssz_bytes(bytes)
-            .map_err(|e| format!("Ssz decoding error: {:?}", e))?;
+        let ssz_cache = SszEth1Cache::from_versioned_bytes(bytes)?;
         Ok(ssz_cache.to_inner(config)?)
     }
 }
 
+/// The layout of `SszEth1Cache` prior to the pruning of deposit logs.
+#[derive(Encode, Decode, Clone)]
+pub struct SszEth1CacheV0 {
+    block_cache: BlockCache,
+    deposit_cache: SszDepositCacheV0,
+    last_processed_block: Option<u64>,
+}
+
+impl From<SszEth1CacheV0> for SszEth1Cache {
+    fn from(cache: SszEth1CacheV0) -> Self {
+        Self {
+            block_cache: cache.block_cache,
+            deposit_cache: cache.deposit_cache.into(),
+            last_processed_block: cache.last_processed_block,
+        }
+    }
+}
+
 #[derive(Encode, Decode, Clone)]
 pub struct SszEth1Cache {
     block_cache: BlockCache,
@@ -72,6 +102,18 @@ impl SszEth1Cache {
         }
     }
 
+    /// Decodes a cache persisted in either the current or the v0 layout.
+    ///
+    /// The layouts are distinguished by the length of the fixed portion of the deposit cache, so
+    /// bytes in one layout never decode as the other.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Self::from_ssz_bytes(bytes).or_else(|e| {
+            SszEth1CacheV0::from_ssz_bytes(bytes)
+                .map(Into::into)
+                .map_err(|_| format!("Ssz decoding error: {:?}", e))
+        })
+    }
+
     pub fn to_inner(&self, config: Config) -> Result<Inner, String> {
         Ok(Inner {
             block_cache: RwLock::new(self.block_cache.clone()),
@@ -80,6 +122,7 @@ impl SszEth1Cache {
                 last_processed_block: self.last_processed_block,
             }),
             config: RwLock::new(config),
+            endpoints: RwLock::new(EndpointState::default()),
         })
     }
 }
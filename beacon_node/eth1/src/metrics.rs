@@ -8,6 +8,10 @@ lazy_static! {
         try_create_int_gauge("eth1_block_cache_len", "Count of eth1 blocks in cache");
     pub static ref LATEST_CACHED_BLOCK_TIMESTAMP: Result<IntGauge> =
         try_create_int_gauge("eth1_latest_cached_block_timestamp", "Timestamp of latest block in eth1 cache");
+    pub static ref BLOCK_CACHE_LEN_BEFORE_PRUNING: Result<IntGauge> =
+        try_create_int_gauge("eth1_block_cache_len_before_pruning", "Count of eth1 blocks in cache before the last finalization pruning");
+    pub static ref BLOCK_CACHE_LEN_AFTER_PRUNING: Result<IntGauge> =
+        try_create_int_gauge("eth1_block_cache_len_after_pruning", "Count of eth1 blocks in cache after the last finalization pruning");
 
     /*
      * Eth1 deposits
@@ -16,4 +20,19 @@ lazy_static! {
         try_create_int_gauge("eth1_deposit_cache_len", "Number of deposits in the eth1 cache");
     pub static ref HIGHEST_PROCESSED_DEPOSIT_BLOCK: Result<IntGauge> =
         try_create_int_gauge("eth1_highest_processed_deposit_block", "Number of the last block checked for deposits");
+    pub static ref DEPOSIT_LOGS_LEN_BEFORE_PRUNING: Result<IntGauge> =
+        try_create_int_gauge("eth1_deposit_logs_len_before_pruning", "Number of deposit logs in cache before the last finalization pruning");
+    pub static ref DEPOSIT_LOGS_LEN_AFTER_PRUNING: Result<IntGauge> =
+        try_create_int_gauge("eth1_deposit_logs_len_after_pruning", "Number of deposit logs in cache after the last finalization pruning");
+
+    /*
+     * Eth1 endpoints
+     */
+    pub static ref ACTIVE_ENDPOINT_INDEX: Result<IntGauge> =
+        try_create_int_gauge("eth1_active_endpoint_index", "Index of the eth1 endpoint in use, where 0 is the primary");
+    pub static ref ENDPOINT_HEALTHY: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "eth1_endpoint_healthy",
+        "Set to 1 if the eth1 endpoint passed its most recent health check, 0 otherwise",
+        &["endpoint_index"]
+    );
 }
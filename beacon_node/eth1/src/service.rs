@@ -2,22 +2,24 @@ use crate::metrics;
 use crate::{
     block_cache::{BlockCache, Error as BlockCacheError, Eth1Block},
     deposit_cache::Error as DepositCacheError,
+    endpoint::{check_endpoint_health, EndpointHealth},
     http::{get_block, get_block_number, get_deposit_logs_in_range},
     inner::{DepositUpdater, Inner},
     DepositLog,
 };
 use exit_future::Exit;
 use futures::{
-    future::{loop_fn, Loop},
+    future::{self, loop_fn, Either, Loop},
     stream, Future, Stream,
 };
 use parking_lot::{RwLock, RwLockReadGuard};
 use serde::{Deserialize, Serialize};
-use slog::{debug, error, trace, Logger};
+use slog::{debug, error, info, trace, warn, Logger};
 use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::timer::Delay;
+use types::Hash256;
 
 const STANDARD_TIMEOUT_MILLIS: u64 = 15_000;
 
@@ -28,6 +30,9 @@ const GET_BLOCK_TIMEOUT_MILLIS: u64 = STANDARD_TIMEOUT_MILLIS;
 /// Timeout when doing an eth_getLogs to read the deposit contract logs.
 const GET_DEPOSIT_LOG_TIMEOUT_MILLIS: u64 = STANDARD_TIMEOUT_MILLIS;
 
+/// Whilst a fallback endpoint is in use, the primary endpoint is tried again at this interval.
+const PRIMARY_ENDPOINT_RETRY_INTERVAL: Duration = Duration::from_secs(300);
+
 #[derive(Debug, PartialEq)]
 pub enum Error {
     /// The remote node is less synced that we expect, it is not useful until has done more
@@ -56,6 +61,9 @@ pub enum Error {
         block_range: Range<u64>,
         error: String,
     },
+    /// None of the configured endpoints were suitable for use. Contains the index and health of
+    /// each endpoint.
+    NoHealthyEndpoint(Vec<(usize, EndpointHealth)>),
     /// There was an unexpected internal error.
     Internal(String),
 }
@@ -81,6 +89,17 @@ pub enum DepositCacheUpdateOutcome {
 pub struct Config {
     /// An Eth1 node (e.g., Geth) running a HTTP JSON-RPC endpoint.
     pub endpoint: String,
+    /// Eth1 nodes to use, in order of preference, when `endpoint` is unavailable or has fallen
+    /// behind.
+    #[serde(default)]
+    pub fallback_endpoints: Vec<String>,
+    /// If `Some`, endpoints that report a different `net_version` are never used.
+    #[serde(default)]
+    pub network_id: Option<u64>,
+    /// If `Some`, an endpoint whose head block is older than this many seconds is only used when
+    /// no other endpoint is available.
+    #[serde(default)]
+    pub max_endpoint_head_age_secs: Option<u64>,
     /// The address the `BlockCache` and `DepositCache` should assume is the canonical deposit contract.
     pub deposit_contract_address: String,
     /// Defines the first block that the `DepositCache` will start searching for deposit logs.
@@ -107,10 +126,23 @@ pub struct Config {
     pub max_blocks_per_update: Option<usize>,
 }
 
+impl Config {
+    /// Returns the primary endpoint, followed by all fallback endpoints.
+    pub fn endpoints(&self) -> Vec<String> {
+        std::iter::once(&self.endpoint)
+            .chain(self.fallback_endpoints.iter())
+            .cloned()
+            .collect()
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             endpoint: "http://localhost:8545".into(),
+            fallback_endpoints: vec![],
+            network_id: None,
+            max_endpoint_head_age_secs: Some(10 * 60),
             deposit_contract_address: "0x0000000000000000000000000000000000000000".into(),
             deposit_contract_deploy_block: 1,
             lowest_cached_block_number: 1,
@@ -205,6 +237,16 @@ impl Service {
         self.deposits().read().cache.len()
     }
 
+    /// Returns the endpoint that is currently used to update the caches.
+    pub fn active_endpoint(&self) -> String {
+        self.inner.active_endpoint()
+    }
+
+    /// Returns the index of the active endpoint in `Config::endpoints`, where `0` is the primary.
+    pub fn active_endpoint_index(&self) -> usize {
+        self.inner.endpoints.read().active
+    }
+
     /// Read the service's configuration.
     pub fn config(&self) -> RwLockReadGuard<Config> {
         self.inner.config.read()
@@ -231,6 +273,79 @@ impl Service {
         }
     }
 
+    /// Drops cached data that is no longer required once the eth1 data and deposits of a beacon
+    /// state have been finalized, returning the number of blocks and deposit logs dropped.
+    ///
+    /// - Blocks are dropped if they are both prior to the block with `finalized_block_hash` and
+    ///   earlier than `earliest_required_timestamp` (i.e., they cannot be voted for). The latest
+    ///   block is never dropped.
+    /// - Deposit logs are dropped if their index is lower than `finalized_deposit_index` (i.e.,
+    ///   they have been included in the finalized chain).
+    pub fn prune_finalized(
+        &self,
+        finalized_block_hash: Hash256,
+        finalized_deposit_index: u64,
+        earliest_required_timestamp: u64,
+    ) -> (usize, usize) {
+        let pruned_blocks = {
+            let mut blocks = self.inner.block_cache.write();
+            metrics::set_gauge(
+                &metrics::BLOCK_CACHE_LEN_BEFORE_PRUNING,
+                blocks.len() as i64,
+            );
+
+            let finalized_block_number = blocks
+                .iter()
+                .find(|block| block.hash == finalized_block_hash)
+                .map(|block| block.number);
+            let earliest_required_block_number = blocks
+                .iter()
+                .find(|block| block.timestamp >= earliest_required_timestamp)
+                .map(|block| block.number);
+
+            let pruned_blocks = match (
+                finalized_block_number,
+                earliest_required_block_number,
+                blocks.highest_block_number(),
+            ) {
+                (Some(finalized), Some(earliest_required), Some(highest)) => {
+                    blocks.prune_below(finalized.min(earliest_required).min(highest))
+                }
+                // If the finalized block is unknown it may not be safe to drop any blocks.
+                _ => 0,
+            };
+
+            metrics::set_gauge(&metrics::BLOCK_CACHE_LEN_AFTER_PRUNING, blocks.len() as i64);
+            metrics::set_gauge(&metrics::BLOCK_CACHE_LEN, blocks.len() as i64);
+            pruned_blocks
+        };
+
+        let pruned_logs = {
+            let mut deposits = self.inner.deposit_cache.write();
+            metrics::set_gauge(
+                &metrics::DEPOSIT_LOGS_LEN_BEFORE_PRUNING,
+                deposits.cache.retained_logs_len() as i64,
+            );
+
+            let pruned_logs = deposits.cache.prune_logs(finalized_deposit_index);
+
+            metrics::set_gauge(
+                &metrics::DEPOSIT_LOGS_LEN_AFTER_PRUNING,
+                deposits.cache.retained_logs_len() as i64,
+            );
+            pruned_logs
+        };
+
+        debug!(
+            self.log,
+            "Pruned finalized eth1 data";
+            "pruned_blocks" => pruned_blocks,
+            "pruned_deposit_logs" => pruned_logs,
+        );
+
+        (pruned_blocks, pruned_logs)
+    }
+
     /// Set the lowest block that the block cache will store.
     ///
     /// Note: this block may not always be present if truncating is enabled.
@@ -240,6 +355,9 @@ impl Service {
 
     /// Update the deposit and block cache, returning an error if either fail.
     ///
+    /// If fallback endpoints are configured, first switches to the most preferred endpoint that is
+    /// healthy (see `Self::select_endpoint`).
+    ///
     /// ## Returns
     ///
     /// - Ok(_) if the update was successful (the cache may or may not have been modified).
@@ -249,6 +367,33 @@ impl Service {
     pub fn update(
         &self,
     ) -> impl Future<Item = (DepositCacheUpdateOutcome, BlockCacheUpdateOutcome), Error = String>
+    {
+        let service = self.clone();
+        let inner = self.inner.clone();
+
+        let select_future = if self.config().fallback_endpoints.is_empty() {
+            Either::A(future::ok(()))
+        } else {
+            Either::B(
+                self.select_endpoint()
+                    .map(|_| ())
+                    .map_err(|e| format!("Failed to select eth1 endpoint: {:?}", e)),
+            )
+        };
+
+        select_future
+            .and_then(move |()| service.update_caches())
+            .map_err(move |e| {
+                // Prefer the other endpoints during the next update.
+                inner.endpoints.write().active_failed = true;
+                e
+            })
+    }
+
+    /// Update the deposit and block cache using the active endpoint.
+    fn update_caches(
+        &self,
+    ) -> impl Future<Item = (DepositCacheUpdateOutcome, BlockCacheUpdateOutcome), Error = String>
     {
         let log_a = self.log.clone();
         let log_b = self.log.clone();
@@ -305,6 +450,87 @@ impl Service {
         deposit_future.join(block_future)
     }
 
+    /// Checks the health of the configured endpoints and activates the most preferred one that is
+    /// healthy, resolving with its index.
+    ///
+    /// Endpoints are checked in the order given by `EndpointState::candidate_order` and checking
+    /// stops at the first healthy endpoint. If no endpoint is healthy, the first stale endpoint is
+    /// used instead. Endpoints that are offline or on the wrong network are never used.
+    pub fn select_endpoint(&self) -> impl Future<Item = usize, Error = Error> {
+        let inner = self.inner.clone();
+        let log = self.log.clone();
+
+        let endpoints = self.config().endpoints();
+        let network_id = self.config().network_id;
+        let max_head_age = self
+            .config()
+            .max_endpoint_head_age_secs
+            .map(Duration::from_secs);
+        let order = self.inner.endpoints.write().candidate_order(
+            endpoints.len(),
+            Instant::now(),
+            PRIMARY_ENDPOINT_RETRY_INTERVAL,
+        );
+
+        loop_fn(
+            (order.into_iter(), None, vec![]),
+            move |(mut order, first_stale, mut rejected)| match order.next() {
+                Some(index) => Either::A(
+                    check_endpoint_health(endpoints[index].clone(), network_id, max_head_age).map(
+                        move |health| {
+                            metrics::set_gauge_vec(
+                                &metrics::ENDPOINT_HEALTHY,
+                                &[index.to_string().as_str()],
+                                (health == EndpointHealth::Healthy) as i64,
+                            );
+
+                            if health == EndpointHealth::Healthy {
+                                return Loop::Break(Ok(index));
+                            }
+
+                            let first_stale = match health {
+                                EndpointHealth::Stale { .. } => first_stale.or(Some(index)),
+                                _ => first_stale,
+                            };
+                            rejected.push((index, health));
+
+                            Loop::Continue((order, first_stale, rejected))
+                        },
+                    ),
+                ),
+                None => Either::B(future::ok(Loop::Break(
+                    first_stale.ok_or_else(|| Error::NoHealthyEndpoint(rejected)),
+                ))),
+            },
+        )
+        .map_err(|()| Error::Internal("Endpoint health check failed".into()))
+        .and_then(move |result| {
+            let index = result?;
+            let endpoint = inner.config.read().endpoints()[index].clone();
+
+            if inner.endpoints.write().set_active(index, Instant::now()) {
+                if index == 0 {
+                    info!(
+                        log,
+                        "Switched to primary eth1 endpoint";
+                        "endpoint" => endpoint,
+                    );
+                } else {
+                    warn!(
+                        log,
+                        "Switched to fallback eth1 endpoint";
+                        "endpoint" => endpoint,
+                        "index" => index,
+                    );
+                }
+            }
+
+            metrics::set_gauge(&metrics::ACTIVE_ENDPOINT_INDEX, index as i64);
+
+            Ok(index)
+        })
+    }
+
     /// A looping future that updates the cache, then waits `config.auto_update_interval` before
     /// updating it again.
     ///
@@ -394,7 +620,7 @@ impl Service {
             .unwrap_or_else(|| self.config().deposit_contract_deploy_block);
 
         get_new_block_numbers(
-            &self.config().endpoint,
+            &self.active_endpoint(),
             next_required_block,
             self.config().follow_distance,
         )
@@ -422,7 +648,7 @@ impl Service {
                         let chunk_1 = chunk.clone();
                         Some(
                             get_deposit_logs_in_range(
-                                &service_1.config().endpoint,
+                                &service_1.active_endpoint(),
                                 &service_1.config().deposit_contract_address,
                                 chunk,
                                 Duration::from_millis(GET_DEPOSIT_LOG_TIMEOUT_MILLIS),
@@ -519,7 +745,7 @@ impl Service {
             .unwrap_or_else(|| self.config().lowest_cached_block_number);
 
         get_new_block_numbers(
-            &self.config().endpoint,
+            &self.active_endpoint(),
             next_required_block,
             self.config().follow_distance,
         )
@@ -671,7 +897,7 @@ fn download_eth1_block<'a>(
         .get_deposit_count_from_cache(block_number);
     // Performs a `get_blockByNumber` call to an eth1 node.
     get_block(
-        &cache.config.read().endpoint,
+        &cache.active_endpoint(),
         block_number,
         Duration::from_millis(GET_BLOCK_TIMEOUT_MILLIS),
     )
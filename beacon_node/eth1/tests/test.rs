@@ -1,7 +1,7 @@
 #![cfg(test)]
 use environment::{Environment, EnvironmentBuilder};
 use eth1::http::{get_deposit_count, get_deposit_logs_in_range, get_deposit_root, Block, Log};
-use eth1::{Config, Service};
+use eth1::{Config, EndpointHealth, Error, Service};
 use eth1::{DepositCache, DepositLog};
 use eth1_test_rig::GanacheEth1Instance;
 use futures::Future;
@@ -719,6 +719,105 @@ mod fast {
     }
 }
 
+mod fallback {
+    use super::*;
+
+    #[test]
+    fn switches_to_fallback_endpoint() {
+        let mut env = new_env();
+        let log = env.core_context().log;
+        let runtime = env.runtime();
+
+        let eth1 = runtime
+            .block_on(GanacheEth1Instance::new())
+            .expect("should start eth1 environment");
+        let deposit_contract = &eth1.deposit_contract;
+        let web3 = eth1.web3();
+
+        let now = get_block_number(runtime, &web3);
+        let service = Service::new(
+            Config {
+                // Nothing should be listening on this port.
+                endpoint: "http://127.0.0.1:1".into(),
+                fallback_endpoints: vec![eth1.endpoint()],
+                deposit_contract_address: deposit_contract.address(),
+                deposit_contract_deploy_block: now,
+                lowest_cached_block_number: now,
+                follow_distance: 0,
+                block_cache_truncation: None,
+                ..Config::default()
+            },
+            log,
+        );
+
+        let deposit = random_deposit_data();
+        deposit_contract
+            .deposit(runtime, deposit)
+            .expect("should perform a deposit");
+
+        runtime
+            .block_on(service.update())
+            .expect("should update using the fallback endpoint");
+
+        assert_eq!(
+            service.active_endpoint_index(),
+            1,
+            "should use the fallback"
+        );
+        assert_eq!(service.active_endpoint(), eth1.endpoint());
+        assert_eq!(service.deposit_cache_len(), 1, "should import the deposit");
+    }
+
+    #[test]
+    fn rejects_endpoint_on_wrong_network() {
+        let mut env = new_env();
+        let log = env.core_context().log;
+        let runtime = env.runtime();
+
+        let eth1 = runtime
+            .block_on(GanacheEth1Instance::new())
+            .expect("should start eth1 environment");
+
+        let network_id = runtime
+            .block_on(eth1::http::get_network_id(&eth1.endpoint(), timeout()))
+            .expect("should get network id");
+
+        let service = Service::new(
+            Config {
+                endpoint: eth1.endpoint(),
+                fallback_endpoints: vec![eth1.endpoint()],
+                network_id: Some(network_id + 1),
+                deposit_contract_address: eth1.deposit_contract.address(),
+                ..Config::default()
+            },
+            log,
+        );
+
+        match runtime.block_on(service.select_endpoint()) {
+            Err(Error::NoHealthyEndpoint(rejected)) => assert_eq!(
+                rejected,
+                vec![
+                    (
+                        0,
+                        EndpointHealth::WrongNetwork {
+                            expected: network_id + 1,
+                            found: network_id
+                        }
+                    ),
+                    (
+                        1,
+                        EndpointHealth::WrongNetwork {
+                            expected: network_id + 1,
+                            found: network_id
+                        }
+                    )
+                ]
+            ),
+            other => panic!("should reject both endpoints, got {:?}", other),
+        }
+    }
+}
+
 mod persist {
     use super::*;
     #[test]
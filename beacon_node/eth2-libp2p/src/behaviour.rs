@@ -298,6 +298,9 @@ pub enum PubsubMessage {
     Block(Vec<u8>),
     /// Gossipsub message providing notification of a new attestation.
     Attestation(Vec<u8>),
+    /// Gossipsub message providing notification of a new aggregate, with proof that its
+    /// aggregator was selected to publish it.
+    AggregateAndProof(Vec<u8>),
     /// Gossipsub message providing notification of a voluntary exit.
     VoluntaryExit(Vec<u8>),
     /// Gossipsub message providing notification of a new proposer slashing.
@@ -320,6 +323,9 @@ impl PubsubMessage {
             match GossipTopic::from(topic.as_str()) {
                 GossipTopic::BeaconBlock => return PubsubMessage::Block(data),
                 GossipTopic::BeaconAttestation => return PubsubMessage::Attestation(data),
+                GossipTopic::BeaconAggregateAndProof => {
+                    return PubsubMessage::AggregateAndProof(data)
+                }
                 GossipTopic::VoluntaryExit => return PubsubMessage::VoluntaryExit(data),
                 GossipTopic::ProposerSlashing => return PubsubMessage::ProposerSlashing(data),
                 GossipTopic::AttesterSlashing => return PubsubMessage::AttesterSlashing(data),
@@ -334,6 +340,7 @@ impl PubsubMessage {
         match self {
             PubsubMessage::Block(data)
             | PubsubMessage::Attestation(data)
+            | PubsubMessage::AggregateAndProof(data)
             | PubsubMessage::VoluntaryExit(data)
             | PubsubMessage::ProposerSlashing(data)
             | PubsubMessage::AttesterSlashing(data)
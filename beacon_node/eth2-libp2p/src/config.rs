@@ -69,6 +69,7 @@ impl Default for Config {
         let topics = vec![
             GossipTopic::BeaconBlock,
             GossipTopic::BeaconAttestation,
+            GossipTopic::BeaconAggregateAndProof,
             GossipTopic::VoluntaryExit,
             GossipTopic::ProposerSlashing,
             GossipTopic::AttesterSlashing,
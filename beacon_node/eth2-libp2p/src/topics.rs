@@ -8,6 +8,7 @@ pub const TOPIC_PREFIX: &str = "eth2";
 pub const TOPIC_ENCODING_POSTFIX: &str = "ssz";
 pub const BEACON_BLOCK_TOPIC: &str = "beacon_block";
 pub const BEACON_ATTESTATION_TOPIC: &str = "beacon_attestation";
+pub const BEACON_AGGREGATE_AND_PROOF_TOPIC: &str = "beacon_aggregate_and_proof";
 pub const VOLUNTARY_EXIT_TOPIC: &str = "voluntary_exit";
 pub const PROPOSER_SLASHING_TOPIC: &str = "proposer_slashing";
 pub const ATTESTER_SLASHING_TOPIC: &str = "attester_slashing";
@@ -18,6 +19,7 @@ pub const SHARD_TOPIC_PREFIX: &str = "shard";
 pub enum GossipTopic {
     BeaconBlock,
     BeaconAttestation,
+    BeaconAggregateAndProof,
     VoluntaryExit,
     ProposerSlashing,
     AttesterSlashing,
@@ -35,6 +37,7 @@ impl From<&str> for GossipTopic {
             match topic_parts[2] {
                 BEACON_BLOCK_TOPIC => GossipTopic::BeaconBlock,
                 BEACON_ATTESTATION_TOPIC => GossipTopic::BeaconAttestation,
+                BEACON_AGGREGATE_AND_PROOF_TOPIC => GossipTopic::BeaconAggregateAndProof,
                 VOLUNTARY_EXIT_TOPIC => GossipTopic::VoluntaryExit,
                 PROPOSER_SLASHING_TOPIC => GossipTopic::ProposerSlashing,
                 ATTESTER_SLASHING_TOPIC => GossipTopic::AttesterSlashing,
@@ -57,6 +60,7 @@ impl Into<String> for GossipTopic {
         match self {
             GossipTopic::BeaconBlock => topic_builder(BEACON_BLOCK_TOPIC),
             GossipTopic::BeaconAttestation => topic_builder(BEACON_ATTESTATION_TOPIC),
+            GossipTopic::BeaconAggregateAndProof => topic_builder(BEACON_AGGREGATE_AND_PROOF_TOPIC),
             GossipTopic::VoluntaryExit => topic_builder(VOLUNTARY_EXIT_TOPIC),
             GossipTopic::ProposerSlashing => topic_builder(PROPOSER_SLASHING_TOPIC),
             GossipTopic::AttesterSlashing => topic_builder(ATTESTER_SLASHING_TOPIC),
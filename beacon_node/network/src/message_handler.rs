@@ -2,7 +2,7 @@
 use crate::error;
 use crate::service::NetworkMessage;
 use crate::MessageProcessor;
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
 use eth2_libp2p::{
     behaviour::PubsubMessage,
     rpc::{RPCError, RPCErrorResponse, RPCRequest, RPCResponse, RequestId, ResponseTermination},
@@ -14,13 +14,19 @@ use slog::{debug, o, trace, warn};
 use ssz::{Decode, DecodeError};
 use std::sync::Arc;
 use tokio::sync::mpsc;
-use types::{Attestation, AttesterSlashing, ProposerSlashing, SignedBeaconBlock, VoluntaryExit};
+use types::{
+    Attestation, AttesterSlashing, ProposerSlashing, SignedAggregateAndProof, SignedBeaconBlock,
+    VoluntaryExit,
+};
 
 /// Handles messages received from the network and client and organises syncing. This
 /// functionality of this struct is to validate an decode messages from the network before
 /// passing them to the internal message processor. The message processor spawns a syncing thread
 /// which manages which blocks need to be requested and processed.
 pub struct MessageHandler<T: BeaconChainTypes> {
+    /// A reference to the underlying beacon chain, used to decode messages for the fork of their
+    /// sender.
+    beacon_chain: Arc<BeaconChain<T>>,
     /// A channel to the network service to allow for gossip propagation.
     network_send: mpsc::UnboundedSender<NetworkMessage>,
     /// Processes validated and decoded messages from the network. Has direct access to the
@@ -59,10 +65,11 @@ impl<T: BeaconChainTypes> MessageHandler<T> {
 
         // Initialise a message instance, which itself spawns the syncing thread.
         let message_processor =
-            MessageProcessor::new(executor, beacon_chain, network_send.clone(), &log);
+            MessageProcessor::new(executor, beacon_chain.clone(), network_send.clone(), &log);
 
         // generate the Message handler
         let mut handler = MessageHandler {
+            beacon_chain,
             network_send,
             message_processor,
             log: message_handler_log,
@@ -166,7 +173,7 @@ impl<T: BeaconChainTypes> MessageHandler<T> {
                             .on_status_response(peer_id, status_message);
                     }
                     RPCResponse::BlocksByRange(response) => {
-                        match self.decode_beacon_block(response) {
+                        match self.decode_beacon_block(&peer_id, response) {
                             Ok(beacon_block) => {
                                 self.message_processor.on_blocks_by_range_response(
                                     peer_id,
@@ -181,7 +188,7 @@ impl<T: BeaconChainTypes> MessageHandler<T> {
                         }
                     }
                     RPCResponse::BlocksByRoot(response) => {
-                        match self.decode_beacon_block(response) {
+                        match self.decode_beacon_block(&peer_id, response) {
                             Ok(beacon_block) => {
                                 self.message_processor.on_blocks_by_root_response(
                                     peer_id,
@@ -222,7 +229,7 @@ impl<T: BeaconChainTypes> MessageHandler<T> {
     /// Handle RPC messages
     fn handle_gossip(&mut self, id: MessageId, peer_id: PeerId, gossip_message: PubsubMessage) {
         match gossip_message {
-            PubsubMessage::Block(message) => match self.decode_gossip_block(message) {
+            PubsubMessage::Block(message) => match self.decode_gossip_block(&peer_id, message) {
                 Ok(block) => {
                     let should_forward_on = self
                         .message_processor
@@ -236,17 +243,44 @@ impl<T: BeaconChainTypes> MessageHandler<T> {
                     debug!(self.log, "Invalid gossiped beacon block"; "peer_id" => format!("{}", peer_id), "Error" => format!("{:?}", e));
                 }
             },
-            PubsubMessage::Attestation(message) => match self.decode_gossip_attestation(message) {
-                Ok(attestation) => {
-                    // TODO: Apply more sophisticated validation and decoding logic
-                    self.propagate_message(id, peer_id.clone());
-                    self.message_processor
-                        .on_attestation_gossip(peer_id, attestation);
+            PubsubMessage::Attestation(message) => {
+                match self.decode_gossip_attestation(&peer_id, message) {
+                    Ok(attestation) => {
+                        // TODO: Apply more sophisticated validation and decoding logic
+                        self.propagate_message(id, peer_id.clone());
+                        self.message_processor
+                            .on_attestation_gossip(peer_id, attestation);
+                    }
+                    Err(e) => {
+                        debug!(
+                            self.log,
+                            "Invalid gossiped attestation";
+                            "peer_id" => format!("{}", peer_id),
+                            "Error" => format!("{:?}", e)
+                        );
+                    }
                 }
-                Err(e) => {
-                    debug!(self.log, "Invalid gossiped attestation"; "peer_id" => format!("{}", peer_id), "Error" => format!("{:?}", e));
+            }
+            PubsubMessage::AggregateAndProof(message) => {
+                match self.decode_gossip_aggregate_and_proof(message) {
+                    Ok(aggregate) => {
+                        let should_forward_on = self
+                            .message_processor
+                            .on_aggregate_and_proof_gossip(peer_id.clone(), aggregate);
+                        if should_forward_on {
+                            self.propagate_message(id, peer_id);
+                        }
+                    }
+                    Err(e) => {
+                        debug!(
+                            self.log,
+                            "Invalid gossiped aggregate";
+                            "peer_id" => format!("{}", peer_id),
+                            "Error" => format!("{:?}", e)
+                        );
+                    }
                 }
-            },
+            }
             PubsubMessage::VoluntaryExit(message) => match self.decode_gossip_exit(message) {
                 Ok(_exit) => {
                     // TODO: Apply more sophisticated validation and decoding logic
@@ -318,18 +352,29 @@ impl<T: BeaconChainTypes> MessageHandler<T> {
     // Note: These are not generics as type-specific verification will need to be applied.
     fn decode_gossip_block(
         &self,
+        peer_id: &PeerId,
         beacon_block: Vec<u8>,
-    ) -> Result<SignedBeaconBlock<T::EthSpec>, DecodeError> {
+    ) -> Result<SignedBeaconBlock<T::EthSpec>, BeaconChainError> {
         //TODO: Apply verification before decoding.
-        SignedBeaconBlock::from_ssz_bytes(&beacon_block)
+        self.beacon_chain
+            .decode_block_for_fork(&beacon_block, self.peer_fork_version(peer_id)?)
     }
 
     fn decode_gossip_attestation(
         &self,
-        beacon_block: Vec<u8>,
-    ) -> Result<Attestation<T::EthSpec>, DecodeError> {
+        peer_id: &PeerId,
+        attestation: Vec<u8>,
+    ) -> Result<Attestation<T::EthSpec>, BeaconChainError> {
         //TODO: Apply verification before decoding.
-        Attestation::from_ssz_bytes(&beacon_block)
+        self.beacon_chain
+            .decode_attestation_for_fork(&attestation, self.peer_fork_version(peer_id)?)
+    }
+
+    fn decode_gossip_aggregate_and_proof(
+        &self,
+        aggregate_and_proof: Vec<u8>,
+    ) -> Result<SignedAggregateAndProof<T::EthSpec>, DecodeError> {
+        SignedAggregateAndProof::from_ssz_bytes(&aggregate_and_proof)
     }
 
     fn decode_gossip_exit(&self, voluntary_exit: Vec<u8>) -> Result<VoluntaryExit, DecodeError> {
@@ -359,9 +404,22 @@ impl<T: BeaconChainTypes> MessageHandler<T> {
     /// stream termination.
     fn decode_beacon_block(
         &self,
+        peer_id: &PeerId,
         beacon_block: Vec<u8>,
-    ) -> Result<SignedBeaconBlock<T::EthSpec>, DecodeError> {
+    ) -> Result<SignedBeaconBlock<T::EthSpec>, BeaconChainError> {
         //TODO: Implement faster block verification before decoding entirely
-        SignedBeaconBlock::from_ssz_bytes(&beacon_block)
+        self.beacon_chain
+            .decode_block_for_fork(&beacon_block, self.peer_fork_version(peer_id)?)
+    }
+
+    /// Returns the fork version declared by `peer_id` in its status handshake.
+    ///
+    /// A peer which has not yet completed the handshake is assumed to be on the fork of the
+    /// canonical head, since the handshake will disconnect it otherwise.
+    fn peer_fork_version(&self, peer_id: &PeerId) -> Result<[u8; 4], BeaconChainError> {
+        match self.message_processor.peer_fork_version(peer_id) {
+            Some(fork_version) => Ok(fork_version),
+            None => Ok(self.beacon_chain.head_info()?.fork.current_version),
+        }
     }
 }
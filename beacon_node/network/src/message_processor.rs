@@ -1,17 +1,21 @@
 use crate::service::NetworkMessage;
 use crate::sync::SyncMessage;
 use beacon_chain::{
-    AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, BlockProcessingOutcome,
+    AggregateProcessingOutcome, AttestationProcessingOutcome, BeaconChain, BeaconChainTypes,
+    BlockProcessingOutcome,
 };
 use eth2_libp2p::rpc::methods::*;
 use eth2_libp2p::rpc::{RPCEvent, RPCRequest, RPCResponse, RequestId};
 use eth2_libp2p::PeerId;
 use slog::{debug, error, o, trace, warn};
 use ssz::Encode;
+use std::collections::HashMap;
 use std::sync::Arc;
 use store::Store;
 use tokio::sync::{mpsc, oneshot};
-use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot};
+use types::{
+    Attestation, Epoch, EthSpec, Hash256, SignedAggregateAndProof, SignedBeaconBlock, Slot,
+};
 
 //TODO: Rate limit requests
 
@@ -21,6 +25,8 @@ pub(crate) const FUTURE_SLOT_TOLERANCE: u64 = 1;
 
 const SHOULD_FORWARD_GOSSIP_BLOCK: bool = true;
 const SHOULD_NOT_FORWARD_GOSSIP_BLOCK: bool = false;
+const SHOULD_FORWARD_GOSSIP_AGGREGATE: bool = true;
+const SHOULD_NOT_FORWARD_GOSSIP_AGGREGATE: bool = false;
 
 /// Keeps track of syncing information for known connected peers.
 #[derive(Clone, Copy, Debug)]
@@ -61,6 +67,8 @@ pub struct MessageProcessor<T: BeaconChainTypes> {
     _sync_exit: oneshot::Sender<()>,
     /// A network context to return and handle RPC requests.
     network: HandlerNetworkContext,
+    /// The fork version declared by each connected peer in its status handshake.
+    peer_fork_versions: HashMap<PeerId, [u8; 4]>,
     /// The `RPCHandler` logger.
     log: slog::Logger,
 }
@@ -88,10 +96,17 @@ impl<T: BeaconChainTypes> MessageProcessor<T> {
             sync_send,
             _sync_exit,
             network: HandlerNetworkContext::new(network_send, log.clone()),
+            peer_fork_versions: HashMap::new(),
             log: log.clone(),
         }
     }
 
+    /// Returns the fork version declared by `peer_id` in its status handshake, if it has
+    /// completed one.
+    pub fn peer_fork_version(&self, peer_id: &PeerId) -> Option<[u8; 4]> {
+        self.peer_fork_versions.get(peer_id).copied()
+    }
+
     fn send_to_sync(&mut self, message: SyncMessage<T::EthSpec>) {
         self.sync_send.try_send(message).unwrap_or_else(|_| {
             warn!(
@@ -105,6 +120,7 @@ impl<T: BeaconChainTypes> MessageProcessor<T> {
     ///
     /// Removes the peer from the manager.
     pub fn on_disconnect(&mut self, peer_id: PeerId) {
+        self.peer_fork_versions.remove(&peer_id);
         self.send_to_sync(SyncMessage::Disconnect(peer_id));
     }
 
@@ -179,6 +195,9 @@ impl<T: BeaconChainTypes> MessageProcessor<T> {
     ///
     /// Disconnects the peer if required.
     fn process_status(&mut self, peer_id: PeerId, status: StatusMessage) {
+        self.peer_fork_versions
+            .insert(peer_id.clone(), status.fork_version);
+
         let remote = PeerSyncInfo::from(status);
         let local = match PeerSyncInfo::from_chain(&self.chain) {
             Some(local) => local,
@@ -355,50 +374,39 @@ impl<T: BeaconChainTypes> MessageProcessor<T> {
             return;
         }
 
-        let forwards_block_root_iter = match self
+        let start_slot = Slot::from(req.start_slot);
+        let blocks = self
             .chain
-            .forwards_iter_block_roots(Slot::from(req.start_slot))
-        {
-            Ok(iter) => iter,
-            Err(e) => {
-                return error!(
+            .block_roots_in_range(start_slot, req.count.saturating_mul(req.step))
+            .and_then(|range| {
+                // Only the blocks at every `step` slots from the start slot are requested, so
+                // avoid loading the others.
+                let roots = range
+                    .blocks
+                    .into_iter()
+                    .filter(|(_root, slot)| (*slot - start_slot).as_u64() % req.step == 0)
+                    .map(|(root, _slot)| root)
+                    .collect::<Vec<_>>();
+
+                self.chain.get_blocks(&roots)
+            })
+            .unwrap_or_else(|e| {
+                error!(
                     self.log,
-                    "Unable to obtain root iter";
+                    "Unable to obtain blocks in range";
                     "error" => format!("{:?}", e)
-                )
-            }
-        };
-
-        let mut block_roots = forwards_block_root_iter
-            .take_while(|(_root, slot)| slot.as_u64() < req.start_slot + req.count * req.step)
-            .step_by(req.step as usize)
-            .map(|(root, _slot)| root)
-            .collect::<Vec<_>>();
-
-        block_roots.dedup();
+                );
+                vec![]
+            });
 
         let mut blocks_sent = 0;
-        for root in block_roots {
-            if let Ok(Some(block)) = self.chain.store.get_block(&root) {
-                // Due to skip slots, blocks could be out of the range, we ensure they are in the
-                // range before sending
-                if block.slot() >= req.start_slot
-                    && block.slot() < req.start_slot + req.count * req.step
-                {
-                    blocks_sent += 1;
-                    self.network.send_rpc_response(
-                        peer_id.clone(),
-                        request_id,
-                        RPCResponse::BlocksByRange(block.as_ssz_bytes()),
-                    );
-                }
-            } else {
-                error!(
-                    self.log,
-                    "Block in the chain is not in the store";
-                    "request_root" => format!("{:}", root),
-                );
-            }
+        for block in blocks {
+            blocks_sent += 1;
+            self.network.send_rpc_response(
+                peer_id.clone(),
+                request_id,
+                RPCResponse::BlocksByRange(block.as_ssz_bytes()),
+            );
         }
 
         if blocks_sent < (req.count as usize) {
@@ -581,7 +589,8 @@ impl<T: BeaconChainTypes> MessageProcessor<T> {
                     // we don't know the block, get the sync manager to handle the block lookup
                     self.send_to_sync(SyncMessage::UnknownBlockHash(peer_id, beacon_block_root));
                 }
-                AttestationProcessingOutcome::FutureEpoch { .. }
+                AttestationProcessingOutcome::AlreadyKnown
+                | AttestationProcessingOutcome::FutureEpoch { .. }
                 | AttestationProcessingOutcome::PastEpoch { .. }
                 | AttestationProcessingOutcome::UnknownTargetRoot { .. }
                 | AttestationProcessingOutcome::FinalizedSlot { .. } => {} // ignore the attestation
@@ -605,6 +614,80 @@ impl<T: BeaconChainTypes> MessageProcessor<T> {
             }
         }
     }
+
+    /// Process a gossip message declaring a new aggregate.
+    ///
+    /// The aggregate is only imported if its aggregator was selected to publish it.
+    ///
+    /// Returns a `bool` which, if `true`, indicates we should forward the aggregate to our peers.
+    pub fn on_aggregate_and_proof_gossip(
+        &mut self,
+        peer_id: PeerId,
+        msg: SignedAggregateAndProof<T::EthSpec>,
+    ) -> bool {
+        match self.chain.process_aggregate_and_proof(&msg) {
+            Ok(outcome) => match outcome {
+                AggregateProcessingOutcome::Processed => {
+                    debug!(
+                        self.log,
+                        "Processed aggregate";
+                        "source" => "gossip",
+                        "peer" => format!("{:?}", peer_id),
+                        "aggregator" => msg.message.aggregator_index,
+                        "block_root" => format!("{}", msg.message.aggregate.data.beacon_block_root),
+                        "slot" => format!("{}", msg.message.aggregate.data.slot),
+                    );
+                    SHOULD_FORWARD_GOSSIP_AGGREGATE
+                }
+                AggregateProcessingOutcome::InvalidAggregate(
+                    AttestationProcessingOutcome::UnknownHeadBlock { beacon_block_root },
+                ) => {
+                    trace!(
+                        self.log,
+                        "Aggregate for unknown block";
+                        "peer_id" => format!("{:?}", peer_id),
+                        "block" => format!("{}", beacon_block_root)
+                    );
+                    // we don't know the block, get the sync manager to handle the block lookup
+                    self.send_to_sync(SyncMessage::UnknownBlockHash(peer_id, beacon_block_root));
+                    SHOULD_NOT_FORWARD_GOSSIP_AGGREGATE
+                }
+                AggregateProcessingOutcome::InvalidAggregate(
+                    AttestationProcessingOutcome::AlreadyKnown,
+                )
+                | AggregateProcessingOutcome::InvalidAggregate(
+                    AttestationProcessingOutcome::FutureEpoch { .. },
+                )
+                | AggregateProcessingOutcome::InvalidAggregate(
+                    AttestationProcessingOutcome::PastEpoch { .. },
+                )
+                | AggregateProcessingOutcome::InvalidAggregate(
+                    AttestationProcessingOutcome::UnknownTargetRoot { .. },
+                )
+                | AggregateProcessingOutcome::InvalidAggregate(
+                    AttestationProcessingOutcome::FinalizedSlot { .. },
+                ) => SHOULD_NOT_FORWARD_GOSSIP_AGGREGATE, // ignore the aggregate
+                AggregateProcessingOutcome::InvalidAggregate(_)
+                | AggregateProcessingOutcome::AggregatorNotInCommittee { .. }
+                | AggregateProcessingOutcome::InvalidSelectionProof { .. }
+                | AggregateProcessingOutcome::NotAggregator { .. }
+                | AggregateProcessingOutcome::InvalidSignature { .. } => {
+                    // the peer has sent a bad aggregate. Remove them.
+                    self.network.disconnect(peer_id, GoodbyeReason::Fault);
+                    SHOULD_NOT_FORWARD_GOSSIP_AGGREGATE
+                }
+            },
+            Err(_) => {
+                // error is logged during the processing therefore no error is logged here
+                trace!(
+                    self.log,
+                    "Erroneous gossip aggregate ssz";
+                    "ssz" => format!("0x{}", hex::encode(msg.as_ssz_bytes())),
+                );
+                SHOULD_NOT_FORWARD_GOSSIP_AGGREGATE
+            }
+        }
+    }
 }
 
 /// Build a `StatusMessage` representing the state of the given `beacon_chain`.
@@ -1,10 +1,36 @@
 use crate::response_builder::ResponseBuilder;
-use crate::ApiResult;
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use crate::url_query::UrlQuery;
+use crate::{ApiError, ApiResult};
+use beacon_chain::latest_messages::LatestMessage;
+use beacon_chain::{BeaconChain, BeaconChainTypes, ForkChoiceNode};
 use hyper::{Body, Request};
 use operation_pool::PersistedOperationPool;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// The default number of validators covered by a single page of `get_latest_messages`.
+pub const DEFAULT_LATEST_MESSAGES_PAGE_SIZE: usize = 1_024;
+/// The maximum number of validators covered by a single page of `get_latest_messages`.
+pub const MAX_LATEST_MESSAGES_PAGE_SIZE: usize = 65_536;
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct LatestMessagesPage {
+    /// The first validator index covered by this page.
+    pub start_index: u64,
+    /// The `start_index` of the next page, if there are more validators.
+    pub next_start_index: Option<u64>,
+    pub messages: Vec<LatestMessage>,
+}
+
+/// The response of `get_debug_fork_choice`.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct ForkChoiceDebug {
+    /// Every block known to fork choice, with parents before children.
+    pub nodes: Vec<ForkChoiceNode>,
+    /// The latest messages of the validators given by the `validator_index` query parameters.
+    pub latest_messages: Vec<LatestMessage>,
+}
+
 /// Returns the `proto_array` fork choice struct, encoded as JSON.
 ///
 /// Useful for debugging or advanced inspection of the chain.
@@ -15,6 +41,37 @@ pub fn get_fork_choice<T: BeaconChainTypes>(
     ResponseBuilder::new(&req)?.body_no_ssz(&*beacon_chain.fork_choice.core_proto_array())
 }
 
+/// Returns the blocks known to fork choice with their weights and best descendants, along with the
+/// latest messages of the validators given by the (repeatable) `validator_index` query parameter.
+///
+/// Unlike `get_fork_choice`, fork choice is copied before it is serialized, so a slow request does
+/// not delay the processing of attestations.
+pub fn get_debug_fork_choice<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let validator_indices = UrlQuery::from_request(&req)?
+        .all_of("validator_index")?
+        .into_iter()
+        .map(|value| {
+            value.parse::<usize>().map_err(|e| {
+                ApiError::BadRequest(format!("Unable to parse validator_index: {:?}", e))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let nodes = beacon_chain.fork_choice_nodes();
+    let latest_messages = validator_indices
+        .into_iter()
+        .filter_map(|validator_index| beacon_chain.latest_message(validator_index))
+        .collect();
+
+    ResponseBuilder::new(&req)?.body_no_ssz(&ForkChoiceDebug {
+        nodes,
+        latest_messages,
+    })
+}
+
 /// Returns the `PersistedOperationPool` struct.
 ///
 /// Useful for debugging or advanced inspection of the stored operations.
@@ -26,3 +83,74 @@ pub fn get_operation_pool<T: BeaconChainTypes>(
         &beacon_chain.op_pool,
     ))
 }
+
+/// Returns the counters of operations performed by the `BeaconChain` since it was started.
+///
+/// Useful for simple dashboards that want totals scoped to this process.
+pub fn get_chain_stats<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.body_no_ssz(&beacon_chain.stats())
+}
+
+/// Returns one page of the fork choice latest messages table.
+///
+/// The `start_index` and `count` query parameters select the range of validator indices, whilst
+/// `format=csv` returns the page as CSV instead of a `LatestMessagesPage`.
+pub fn get_latest_messages<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let parse_usize = |key: &str, default: usize| -> Result<usize, ApiError> {
+        query
+            .first_of_opt(&[key])
+            .map_or(Ok(default), |(_, value)| {
+                value
+                    .parse::<usize>()
+                    .map_err(|e| ApiError::BadRequest(format!("Unable to parse {}: {:?}", key, e)))
+            })
+    };
+
+    let start_index = parse_usize("start_index", 0)?;
+    let count = parse_usize("count", DEFAULT_LATEST_MESSAGES_PAGE_SIZE)?;
+
+    if count > MAX_LATEST_MESSAGES_PAGE_SIZE {
+        return Err(ApiError::BadRequest(format!(
+            "count must not exceed {}",
+            MAX_LATEST_MESSAGES_PAGE_SIZE
+        )));
+    }
+
+    let messages = beacon_chain.latest_messages(start_index, count);
+
+    match query.first_of_opt(&["format"]) {
+        Some((_, ref format)) if format == "csv" => {
+            let mut csv = vec![];
+            LatestMessage::write_csv(&mut csv, messages)
+                .map_err(|e| ApiError::ServerError(format!("Unable to write CSV: {:?}", e)))?;
+
+            ResponseBuilder::new(&req)?.body_text(String::from_utf8_lossy(&csv).into_owned())
+        }
+        Some((_, ref format)) if format != "json" => Err(ApiError::BadRequest(format!(
+            "Unsupported format: {}",
+            format
+        ))),
+        _ => {
+            let end_index = start_index.saturating_add(count);
+            let next_start_index = if end_index < beacon_chain.fork_choice.latest_messages_len() {
+                Some(end_index as u64)
+            } else {
+                None
+            };
+
+            ResponseBuilder::new(&req)?.body_no_ssz(&LatestMessagesPage {
+                start_index: start_index as u64,
+                next_start_index,
+                messages,
+            })
+        }
+    }
+}
@@ -1,17 +1,21 @@
 use crate::helpers::*;
 use crate::response_builder::ResponseBuilder;
 use crate::validator::get_state_for_epoch;
-use crate::{ApiError, ApiResult, BoxFut, UrlQuery};
-use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use crate::{ApiError, ApiResult, BoxFut, NetworkChannel, UrlQuery};
+use beacon_chain::{
+    events::EventTopic, BeaconChain, BeaconChainTypes, OperationImportError, StateSkipConfig,
+};
 use futures::{Future, Stream};
-use hyper::{Body, Request};
+use hyper::{Body, Request, Response, StatusCode};
 use serde::{Deserialize, Serialize};
+use slog::{info, warn, Logger};
 use ssz_derive::{Decode, Encode};
+use std::collections::HashSet;
 use std::sync::Arc;
 use store::Store;
 use types::{
     AttesterSlashing, BeaconState, CommitteeIndex, EthSpec, Hash256, ProposerSlashing,
-    PublicKeyBytes, RelativeEpoch, SignedBeaconBlock, Slot, Validator,
+    PublicKeyBytes, RelativeEpoch, SignedBeaconBlock, SignedVoluntaryExit, Slot, Validator,
 };
 
 /// Information about the block and state that are at head of the beacon chain.
@@ -87,6 +91,42 @@ pub fn get_heads<T: BeaconChainTypes>(
     ResponseBuilder::new(&req)?.body(&heads)
 }
 
+/// HTTP handler to stream events from the beacon chain as server-sent events.
+///
+/// The optional `topics` query parameter is a comma-separated list of the topics to stream (see
+/// `EventTopic`). All topics are streamed if it is absent.
+pub fn get_events<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let topics = match UrlQuery::from_request(&req)?.first_of_opt(&["topics"]) {
+        Some((_key, value)) => value
+            .split(',')
+            .map(|topic| topic.parse::<EventTopic>().map_err(ApiError::BadRequest))
+            .collect::<Result<HashSet<_>, _>>()?,
+        None => EventTopic::all(),
+    };
+
+    let events = beacon_chain.event_stream(topics).ok_or_else(|| {
+        ApiError::NotImplemented("The beacon node does not support event streams".into())
+    })?;
+
+    let body = events
+        .map_err(|()| "Event stream ended unexpectedly".to_string())
+        .and_then(|event| {
+            serde_json::to_string(&event)
+                .map(|json| format!("data: {}\n\n", json))
+                .map_err(|e| format!("Unable to serialize event: {:?}", e))
+        });
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "text/event-stream")
+        .header("cache-control", "no-cache")
+        .body(Body::wrap_stream(body))
+        .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
 #[serde(bound = "T: EthSpec")]
 pub struct BlockResponse<T: EthSpec> {
@@ -132,6 +172,47 @@ pub fn get_block<T: BeaconChainTypes>(
     ResponseBuilder::new(&req)?.body(&response)
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
+#[serde(bound = "T: EthSpec")]
+pub struct BlocksResponse<T: EthSpec> {
+    /// The canonical blocks in the range, in ascending order of slot.
+    pub blocks: Vec<BlockResponse<T>>,
+    /// `true` if the range begins prior to the oldest block available to the node (e.g., if it was
+    /// started from a checkpoint), in which case the blocks prior to it are not returned.
+    pub precedes_oldest_available: bool,
+}
+
+/// HTTP handler to return the canonical `SignedBeaconBlock`s in the `count` slots from the `start`
+/// slot.
+///
+/// Fewer blocks are returned if the range extends beyond the head or the maximum range permitted
+/// by the node.
+pub fn get_blocks<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+    let start_slot = parse_slot(&query.first_of(&["start"])?.1)?;
+    let count = query
+        .first_of(&["count"])?
+        .1
+        .parse::<u64>()
+        .map_err(|e| ApiError::BadRequest(format!("Unable to parse count: {:?}", e)))?;
+
+    let range = beacon_chain.blocks_in_range(start_slot, count)?;
+
+    let response = BlocksResponse {
+        blocks: range
+            .blocks
+            .into_iter()
+            .map(|(root, beacon_block)| BlockResponse { root, beacon_block })
+            .collect(),
+        precedes_oldest_available: range.precedes_oldest_available,
+    };
+
+    ResponseBuilder::new(&req)?.body(&response)
+}
+
 /// HTTP handler to return a `SignedBeaconBlock` root at a given `slot`.
 pub fn get_block_root<T: BeaconChainTypes>(
     req: Request<Body>,
@@ -305,7 +386,10 @@ fn get_state_from_root_opt<T: BeaconChainTypes>(
             })?
             .ok_or_else(|| ApiError::NotFound(format!("No state exists with root: {}", state_root)))
     } else {
-        Ok(beacon_chain.head()?.beacon_state)
+        Ok(beacon_chain
+            .head()?
+            .beacon_state
+            .clone_with_only_committee_caches())
     }
 }
 
@@ -420,7 +504,7 @@ pub fn get_state<T: BeaconChainTypes>(
     req: Request<Body>,
     beacon_chain: Arc<BeaconChain<T>>,
 ) -> ApiResult {
-    let head_state = beacon_chain.head()?.beacon_state;
+    let head_slot = beacon_chain.head()?.beacon_state.slot;
 
     let (key, value) = match UrlQuery::from_request(&req) {
         Ok(query) => {
@@ -430,7 +514,7 @@ pub fn get_state<T: BeaconChainTypes>(
         }
         Err(ApiError::BadRequest(_)) => {
             // No parameters provided at all, use current slot.
-            (String::from("slot"), head_state.slot.to_string())
+            (String::from("slot"), head_slot.to_string())
         }
         Err(e) => {
             return Err(e);
@@ -497,6 +581,10 @@ pub fn get_genesis_time<T: BeaconChainTypes>(
     ResponseBuilder::new(&req)?.body(&beacon_chain.head()?.beacon_state.genesis_time)
 }
 
+/// HTTP handler to queue a `ProposerSlashing` for inclusion in a block.
+///
+/// Responds with `true` if the slashing was queued, or `false` if it is valid but was not queued
+/// because this node does not produce blocks. An invalid slashing is refused with a 400 status.
 pub fn proposer_slashing<T: BeaconChainTypes>(
     req: Request<Body>,
     beacon_chain: Arc<BeaconChain<T>>,
@@ -516,29 +604,27 @@ pub fn proposer_slashing<T: BeaconChainTypes>(
             })
         })
         .and_then(move |proposer_slashing| {
-            let spec = &beacon_chain.spec;
-            let state = &beacon_chain.head().unwrap().beacon_state;
-            if beacon_chain.eth1_chain.is_some() {
-                beacon_chain
-                    .op_pool
-                    .insert_proposer_slashing(proposer_slashing, state, spec)
-                    .map_err(|e| {
-                        ApiError::BadRequest(format!(
-                            "Error while inserting proposer slashing: {:?}",
-                            e
-                        ))
-                    })
-            } else {
-                Err(ApiError::BadRequest(
-                    "Cannot insert proposer slashing on node without Eth1 connection.".to_string(),
-                ))
-            }
+            let result = match beacon_chain.process_proposer_slashing(proposer_slashing.clone()) {
+                Ok(()) => Ok(true),
+                // The slashing is refused without being validated, so validate it here.
+                Err(OperationImportError::Eth1Required)
+                | Err(OperationImportError::FollowerMode) => beacon_chain
+                    .verify_proposer_slashing(&proposer_slashing)
+                    .map(|()| false),
+                Err(e) => Err(e),
+            };
+
+            operation_import_result(result, "proposer slashing")
         })
-        .and_then(|_| response_builder?.body(&true));
+        .and_then(|queued| response_builder?.body(&queued));
 
     Box::new(future)
 }
 
+/// HTTP handler to queue an `AttesterSlashing` for inclusion in a block.
+///
+/// Responds with `true` if the slashing was queued, or `false` if it is valid but was not queued
+/// because this node does not produce blocks. An invalid slashing is refused with a 400 status.
 pub fn attester_slashing<T: BeaconChainTypes>(
     req: Request<Body>,
     beacon_chain: Arc<BeaconChain<T>>,
@@ -558,25 +644,167 @@ pub fn attester_slashing<T: BeaconChainTypes>(
             })
         })
         .and_then(move |attester_slashing| {
-            let spec = &beacon_chain.spec;
-            let state = &beacon_chain.head().unwrap().beacon_state;
-            if beacon_chain.eth1_chain.is_some() {
-                beacon_chain
-                    .op_pool
-                    .insert_attester_slashing(attester_slashing, state, spec)
-                    .map_err(|e| {
-                        ApiError::BadRequest(format!(
-                            "Error while inserting attester slashing: {:?}",
-                            e
-                        ))
-                    })
-            } else {
-                Err(ApiError::BadRequest(
-                    "Cannot insert attester slashing on node without Eth1 connection.".to_string(),
+            let result = match beacon_chain.process_attester_slashing(attester_slashing.clone()) {
+                Ok(()) => Ok(true),
+                // The slashing is refused without being validated, so validate it here.
+                Err(OperationImportError::Eth1Required)
+                | Err(OperationImportError::FollowerMode) => beacon_chain
+                    .verify_attester_slashing(&attester_slashing)
+                    .map(|()| false),
+                Err(e) => Err(e),
+            };
+
+            operation_import_result(result, "attester slashing")
+        })
+        .and_then(|queued| response_builder?.body(&queued));
+
+    Box::new(future)
+}
+
+/// Maps the `result` of importing (or verifying) some slashing into the op pool to the response
+/// of the HTTP API, which is `true` if the slashing was queued for inclusion in a block.
+fn operation_import_result<E: std::fmt::Debug>(
+    result: Result<bool, OperationImportError<E>>,
+    operation: &str,
+) -> Result<bool, ApiError> {
+    result.map_err(|e| match e {
+        OperationImportError::BeaconChainError(e) => ApiError::from_category(
+            e.kind(),
+            format!("Error while processing {}: {}", operation, e),
+        ),
+        e => ApiError::BadRequest(format!("Error while inserting {}: {:?}", operation, e)),
+    })
+}
+
+/// HTTP handler to queue a `SignedVoluntaryExit` for inclusion in a block and publish it to the
+/// network.
+///
+/// Responds with `true` if the exit was queued, or `false` if it was published without being
+/// queued because this node does not produce blocks. An exit which is not valid against the
+/// current state is neither queued nor published, and the reason is returned with a 202 status.
+pub fn publish_voluntary_exit<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel,
+    log: Logger,
+) -> BoxFut {
+    try_future!(check_content_type_for_json(&req));
+    let response_builder = ResponseBuilder::new(&req);
+
+    let future = req
+        .into_body()
+        .concat2()
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+        .and_then(|chunks| {
+            serde_json::from_slice::<SignedVoluntaryExit>(&chunks).map_err(|e| {
+                ApiError::BadRequest(format!(
+                    "Unable to parse JSON into SignedVoluntaryExit: {:?}",
+                    e
                 ))
+            })
+        })
+        .and_then(move |exit| {
+            let result = match beacon_chain.process_voluntary_exit(exit.clone()) {
+                Ok(()) => Ok(true),
+                // The exit is refused without being validated, but it may still be published.
+                Err(OperationImportError::Eth1Required)
+                | Err(OperationImportError::FollowerMode) => {
+                    beacon_chain.verify_voluntary_exit(&exit).map(|()| false)
+                }
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(queued) => {
+                    info!(
+                        log,
+                        "Voluntary exit from local validator";
+                        "validator_index" => exit.message.validator_index,
+                        "epoch" => exit.message.epoch,
+                        "queued" => queued,
+                    );
+                    publish_voluntary_exit_to_network::<T>(network_chan, exit).map(|()| queued)
+                }
+                Err(OperationImportError::BeaconChainError(e)) => Err(ApiError::from_category(
+                    e.kind(),
+                    format!("Error while processing voluntary exit: {}", e),
+                )),
+                Err(e) => {
+                    warn!(
+                        log,
+                        "Invalid voluntary exit from local validator";
+                        "outcome" => format!("{:?}", e)
+                    );
+
+                    Err(ApiError::ProcessingError(format!(
+                        "The voluntary exit could not be processed and has not been published: \
+                         {:?}",
+                        e
+                    )))
+                }
             }
         })
-        .and_then(|_| response_builder?.body(&true));
+        .and_then(|queued| response_builder?.body_no_ssz(&queued));
 
     Box::new(future)
 }
+
+/// HTTP handler to return the attestations in the operation pool.
+///
+/// The attestations may be filtered by the optional `slot` and `committee_index` query
+/// parameters.
+pub fn get_pooled_attestations<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot_opt = if let Some((_key, value)) = query.first_of_opt(&["slot"]) {
+        Some(parse_slot(&value)?)
+    } else {
+        None
+    };
+    let index_opt = if let Some((_key, value)) = query.first_of_opt(&["committee_index"]) {
+        Some(parse_committee_index(&value)?)
+    } else {
+        None
+    };
+
+    // The pool is only locked whilst the attestations are cloned, not whilst they're serialized.
+    let attestations = if let Some(slot) = slot_opt {
+        beacon_chain.get_pooled_attestations_for_slot(slot)
+    } else {
+        beacon_chain.get_pooled_attestations()
+    };
+
+    let attestations = attestations
+        .into_iter()
+        .filter(|attestation| index_opt.map_or(true, |index| attestation.data.index == index))
+        .collect::<Vec<_>>();
+
+    ResponseBuilder::new(&req)?.body(&attestations)
+}
+
+/// HTTP handler to return the voluntary exits in the operation pool.
+pub fn get_pooled_voluntary_exits<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.body(&beacon_chain.get_pooled_voluntary_exits())
+}
+
+/// HTTP handler to return the proposer slashings in the operation pool.
+pub fn get_pooled_proposer_slashings<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.body(&beacon_chain.get_pooled_proposer_slashings())
+}
+
+/// HTTP handler to return the attester slashings in the operation pool.
+pub fn get_pooled_attester_slashings<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.body(&beacon_chain.get_pooled_attester_slashings())
+}
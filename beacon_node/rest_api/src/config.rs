@@ -17,15 +17,32 @@ impl ApiEncodingFormat {
             ApiEncodingFormat::SSZ => "application/ssz",
         }
     }
+
+    /// Returns the format named by the `encoding` query parameter (e.g., `?encoding=ssz`), if any.
+    pub fn from_query_param(param: &str) -> Option<ApiEncodingFormat> {
+        match param {
+            "json" => Some(ApiEncodingFormat::JSON),
+            "yaml" => Some(ApiEncodingFormat::YAML),
+            "ssz" => Some(ApiEncodingFormat::SSZ),
+            _ => None,
+        }
+    }
 }
 
+/// Parses an `Accept` header, which may list several media types (e.g.,
+/// `application/ssz, application/json;q=0.9`). The first supported media type is used, falling
+/// back to JSON.
 impl From<&str> for ApiEncodingFormat {
     fn from(f: &str) -> ApiEncodingFormat {
-        match f {
-            "application/yaml" => ApiEncodingFormat::YAML,
-            "application/ssz" => ApiEncodingFormat::SSZ,
-            _ => ApiEncodingFormat::JSON,
-        }
+        f.split(',')
+            .filter_map(|media_type| match media_type.split(';').next()?.trim() {
+                "application/json" => Some(ApiEncodingFormat::JSON),
+                "application/yaml" => Some(ApiEncodingFormat::YAML),
+                "application/ssz" => Some(ApiEncodingFormat::SSZ),
+                _ => None,
+            })
+            .next()
+            .unwrap_or(ApiEncodingFormat::JSON)
     }
 }
 
@@ -1,7 +1,12 @@
 use crate::BoxFut;
+use beacon_chain::{BeaconChainError, BlockProductionError, ErrorCategory};
 use hyper::{Body, Response, StatusCode};
 use std::error::Error as StdError;
 
+/// The number of seconds after which a client is advised to repeat a request that failed with
+/// `ApiError::ServiceUnavailable`.
+pub const RETRY_AFTER_SECONDS: u64 = 1;
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum ApiError {
     MethodNotAllowed(String),
@@ -9,6 +14,7 @@ pub enum ApiError {
     NotImplemented(String),
     BadRequest(String),
     NotFound(String),
+    ServiceUnavailable(String), // A 503 error, for when a request may succeed if it is retried.
     UnsupportedType(String),
     ImATeapot(String),       // Just in case.
     ProcessingError(String), // A 202 error, for when a block/attestation cannot be processed, but still transmitted.
@@ -24,19 +30,40 @@ impl ApiError {
             ApiError::NotImplemented(desc) => (StatusCode::NOT_IMPLEMENTED, desc),
             ApiError::BadRequest(desc) => (StatusCode::BAD_REQUEST, desc),
             ApiError::NotFound(desc) => (StatusCode::NOT_FOUND, desc),
+            ApiError::ServiceUnavailable(desc) => (StatusCode::SERVICE_UNAVAILABLE, desc),
             ApiError::UnsupportedType(desc) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, desc),
             ApiError::ImATeapot(desc) => (StatusCode::IM_A_TEAPOT, desc),
             ApiError::ProcessingError(desc) => (StatusCode::ACCEPTED, desc),
         }
     }
+
+    /// Returns an error with the status code corresponding to `category`.
+    pub fn from_category(category: ErrorCategory, desc: String) -> Self {
+        match category {
+            ErrorCategory::NotFound => ApiError::NotFound(desc),
+            ErrorCategory::BadRequest => ApiError::BadRequest(desc),
+            ErrorCategory::ServiceUnavailable => ApiError::ServiceUnavailable(desc),
+            ErrorCategory::Internal => ApiError::ServerError(desc),
+        }
+    }
 }
 
 impl Into<Response<Body>> for ApiError {
     fn into(self) -> Response<Body> {
+        let retry_after = match self {
+            ApiError::ServiceUnavailable(_) => Some(RETRY_AFTER_SECONDS),
+            _ => None,
+        };
         let status_code = self.status_code();
-        Response::builder()
+
+        let mut builder = Response::builder();
+        builder
             .status(status_code.0)
-            .header("content-type", "text/plain; charset=utf-8")
+            .header("content-type", "text/plain; charset=utf-8");
+        if let Some(seconds) = retry_after {
+            builder.header("retry-after", seconds.to_string());
+        }
+        builder
             .body(Body::from(status_code.1))
             .expect("Response should always be created.")
     }
@@ -60,9 +87,15 @@ impl From<types::BeaconStateError> for ApiError {
     }
 }
 
-impl From<beacon_chain::BeaconChainError> for ApiError {
-    fn from(e: beacon_chain::BeaconChainError) -> ApiError {
-        ApiError::ServerError(format!("BeaconChainError error: {:?}", e))
+impl From<BeaconChainError> for ApiError {
+    fn from(e: BeaconChainError) -> ApiError {
+        ApiError::from_category(e.kind(), format!("Beacon chain error: {}", e))
+    }
+}
+
+impl From<BlockProductionError> for ApiError {
+    fn from(e: BlockProductionError) -> ApiError {
+        ApiError::from_category(e.kind(), format!("Block production error: {}", e))
     }
 }
 
@@ -90,3 +123,46 @@ impl std::fmt::Display for ApiError {
         write!(f, "{:?}: {:?}", status.0, status.1)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use types::Hash256;
+
+    #[test]
+    fn lock_timeout_is_service_unavailable() {
+        let response: Response<Body> =
+            ApiError::from(BeaconChainError::CanonicalHeadLockTimeout).into();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers().get("retry-after").map(|v| v.as_bytes()),
+            Some(RETRY_AFTER_SECONDS.to_string().as_bytes())
+        );
+    }
+
+    #[test]
+    fn chain_errors_map_to_status_codes() {
+        let status = |e: BeaconChainError| ApiError::from(e).status_code().0;
+
+        assert_eq!(
+            status(BeaconChainError::MissingBeaconBlock(Hash256::zero())),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(BeaconChainError::UnableToReadSlot),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            status(BeaconChainError::TooManySkipSlots {
+                requested: 2,
+                max: 1
+            }),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(BeaconChainError::InvariantViolated("test".into())),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
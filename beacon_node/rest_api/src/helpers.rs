@@ -10,11 +10,11 @@ use network::NetworkMessage;
 use parking_lot::RwLock;
 use ssz::{Decode, Encode};
 use std::sync::Arc;
-use store::{iter::AncestorIter, Store};
+use store::Store;
 use tokio::sync::mpsc;
 use types::{
     Attestation, BeaconState, CommitteeIndex, Epoch, EthSpec, Hash256, RelativeEpoch, Signature,
-    SignedBeaconBlock, Slot,
+    SignedAggregateAndProof, SignedBeaconBlock, SignedVoluntaryExit, Slot,
 };
 
 /// Parse a slot.
@@ -121,11 +121,7 @@ pub fn block_root_at_slot<T: BeaconChainTypes>(
     beacon_chain: &BeaconChain<T>,
     target: Slot,
 ) -> Result<Option<Hash256>, ApiError> {
-    Ok(beacon_chain
-        .rev_iter_block_roots()?
-        .take_while(|(_root, slot)| *slot >= target)
-        .find(|(_root, slot)| *slot == target)
-        .map(|(root, _slot)| root))
+    Ok(beacon_chain.root_at_slot(target)?)
 }
 
 /// Returns a `BeaconState` and it's root in the canonical chain of `beacon_chain` at the given
@@ -140,7 +136,10 @@ pub fn state_at_slot<T: BeaconChainTypes>(
     let head = beacon_chain.head()?;
 
     if head.beacon_state.slot == slot {
-        Ok((head.beacon_state_root, head.beacon_state))
+        Ok((
+            head.beacon_state_root,
+            head.beacon_state.clone_with_only_committee_caches(),
+        ))
     } else {
         let root = state_root_at_slot(beacon_chain, slot, StateSkipConfig::WithStateRoots)?;
 
@@ -163,10 +162,9 @@ pub fn state_root_at_slot<T: BeaconChainTypes>(
     slot: Slot,
     config: StateSkipConfig,
 ) -> Result<Hash256, ApiError> {
-    let head_state = &beacon_chain.head()?.beacon_state;
-    let current_slot = beacon_chain
-        .slot()
-        .map_err(|_| ApiError::ServerError("Unable to read slot clock".to_string()))?;
+    let head = beacon_chain.head()?;
+    let head_state = &head.beacon_state;
+    let current_slot = beacon_chain.slot()?;
 
     // There are four scenarios when obtaining a state for a given slot:
     //
@@ -187,24 +185,23 @@ pub fn state_root_at_slot<T: BeaconChainTypes>(
         // 2. The request slot is the same as the best block (head) slot.
         //
         // The head state root is stored in memory, return a reference.
-        Ok(beacon_chain.head()?.beacon_state_root)
+        Ok(head.beacon_state_root)
     } else if head_state.slot > slot {
         // 3. The request slot is prior to the head slot.
         //
         // Iterate through the state roots on the head state to find the root for that
-        // slot. Once the root is found, load it from the database.
-        Ok(head_state
-            .try_iter_ancestor_roots(beacon_chain.store.clone())
-            .ok_or_else(|| ApiError::ServerError("Failed to create roots iterator".to_string()))?
-            .find(|(_root, s)| *s == slot)
-            .map(|(root, _slot)| root)
-            .ok_or_else(|| ApiError::NotFound(format!("Unable to find state at slot {}", slot)))?)
+        // slot. States prior to the anchor of the chain are not found.
+        beacon_chain
+            .state_root_at_slot(slot)?
+            .ok_or_else(|| ApiError::NotFound(format!("Unable to find state at slot {}", slot)))
     } else {
         // 4. The request slot is later than the head slot.
         //
         // Use `per_slot_processing` to advance the head state to the present slot,
         // assuming that all slots do not contain a block (i.e., they are skipped slots).
-        let mut state = beacon_chain.head()?.beacon_state;
+        beacon_chain.check_skip_slots(head_state.slot, slot)?;
+
+        let mut state = head_state.clone_with_only_committee_caches();
         let spec = &T::EthSpec::default_spec();
 
         let skip_state_root = match config {
@@ -275,6 +272,50 @@ pub fn publish_attestation_to_network<T: BeaconChainTypes + 'static>(
     Ok(())
 }
 
+pub fn publish_aggregate_and_proof_to_network<T: BeaconChainTypes + 'static>(
+    chan: Arc<RwLock<mpsc::UnboundedSender<NetworkMessage>>>,
+    signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
+) -> Result<(), ApiError> {
+    // create the network topic to send on
+    let topic = GossipTopic::BeaconAggregateAndProof;
+    let message = PubsubMessage::AggregateAndProof(signed_aggregate.as_ssz_bytes());
+
+    // Publish the aggregate to the p2p network via gossipsub.
+    if let Err(e) = chan.write().try_send(NetworkMessage::Publish {
+        topics: vec![topic.into()],
+        message,
+    }) {
+        return Err(ApiError::ServerError(format!(
+            "Unable to send new aggregate to network: {:?}",
+            e
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn publish_voluntary_exit_to_network<T: BeaconChainTypes + 'static>(
+    chan: Arc<RwLock<mpsc::UnboundedSender<NetworkMessage>>>,
+    exit: SignedVoluntaryExit,
+) -> Result<(), ApiError> {
+    // create the network topic to send on
+    let topic = GossipTopic::VoluntaryExit;
+    let message = PubsubMessage::VoluntaryExit(exit.as_ssz_bytes());
+
+    // Publish the exit to the p2p network via gossipsub.
+    if let Err(e) = chan.write().try_send(NetworkMessage::Publish {
+        topics: vec![topic.into()],
+        message,
+    }) {
+        return Err(ApiError::ServerError(format!(
+            "Unable to send new voluntary exit to network: {:?}",
+            e
+        )));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -41,11 +41,12 @@ use url_query::UrlQuery;
 
 pub use crate::helpers::parse_pubkey_bytes;
 pub use beacon::{
-    BlockResponse, CanonicalHeadResponse, Committee, HeadBeaconBlock, StateResponse,
-    ValidatorRequest, ValidatorResponse,
+    BlockResponse, BlocksResponse, CanonicalHeadResponse, Committee, HeadBeaconBlock,
+    StateResponse, ValidatorRequest, ValidatorResponse,
 };
+pub use beacon_chain::SyncStatus;
 pub use config::Config;
-pub use validator::{ValidatorDutiesRequest, ValidatorDuty};
+pub use validator::{CommitteeAttestation, ValidatorDutiesRequest, ValidatorDuty};
 
 pub type BoxFut = Box<dyn Future<Item = Response<Body>, Error = ApiError> + Send>;
 pub type NetworkChannel = Arc<RwLock<mpsc::UnboundedSender<NetworkMessage>>>;
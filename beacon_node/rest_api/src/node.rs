@@ -1,9 +1,37 @@
 use crate::response_builder::ResponseBuilder;
 use crate::ApiResult;
-use hyper::{Body, Request};
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use hyper::{Body, Request, StatusCode};
+use std::sync::Arc;
 use version;
 
 /// Read the version string from the current Lighthouse build.
 pub fn get_version(req: Request<Body>) -> ApiResult {
     ResponseBuilder::new(&req)?.body_no_ssz(&version::version())
 }
+
+/// HTTP handler to return the progress of the head towards the wall-clock slot.
+pub fn get_syncing<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.body_no_ssz(&beacon_chain.sync_status()?)
+}
+
+/// HTTP handler to return whether the node is ready to serve validator duties.
+///
+/// Responds with `503 Service Unavailable` (and the reason in the body) if it is not, so that load
+/// balancers can route requests elsewhere.
+pub fn get_readiness<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let readiness = beacon_chain.readiness()?;
+    let status = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    ResponseBuilder::new(&req)?.body_no_ssz_with_status(status, &readiness)
+}
@@ -1,5 +1,6 @@
-use super::{ApiError, ApiResult};
+use super::{ApiError, ApiResult, UrlQuery};
 use crate::config::ApiEncodingFormat;
+use crate::url_query::ENCODING_KEY;
 use http::header;
 use hyper::{Body, Request, Response, StatusCode};
 use serde::Serialize;
@@ -23,8 +24,14 @@ impl ResponseBuilder {
             })
             .map(String::from)?;
 
-        // JSON is our default encoding, unless something else is requested.
-        let encoding = ApiEncodingFormat::from(accept_header.as_str());
+        // An `encoding` query parameter takes precedence over the `Accept` header, for clients
+        // which are unable to set headers.
+        let encoding = match UrlQuery::from_request(req)?.first_of_opt(&[ENCODING_KEY]) {
+            Some((_, param)) => ApiEncodingFormat::from_query_param(&param)
+                .ok_or_else(|| ApiError::BadRequest(format!("Unsupported encoding: {}", param)))?,
+            // JSON is our default encoding, unless something else is requested.
+            None => ApiEncodingFormat::from(accept_header.as_str()),
+        };
         Ok(Self { encoding })
     }
 
@@ -40,6 +47,11 @@ impl ResponseBuilder {
     }
 
     pub fn body_no_ssz<T: Serialize>(self, item: &T) -> ApiResult {
+        self.body_no_ssz_with_status(StatusCode::OK, item)
+    }
+
+    /// As per `Self::body_no_ssz`, but responds with the given `status` rather than `200 OK`.
+    pub fn body_no_ssz_with_status<T: Serialize>(self, status: StatusCode, item: &T) -> ApiResult {
         let (body, content_type) = match self.encoding {
             ApiEncodingFormat::JSON => (
                 Body::from(serde_json::to_string(&item).map_err(|e| {
@@ -67,7 +79,7 @@ impl ResponseBuilder {
         };
 
         Response::builder()
-            .status(StatusCode::OK)
+            .status(status)
             .header("content-type", content_type)
             .body(body)
             .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
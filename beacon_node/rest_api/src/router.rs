@@ -44,7 +44,10 @@ pub fn route<T: BeaconChainTypes>(
             // Methods for Client
             (&Method::GET, "/node/version") => into_boxfut(node::get_version(req)),
             (&Method::GET, "/node/syncing") => {
-                into_boxfut(helpers::implementation_pending_response(req))
+                into_boxfut(node::get_syncing::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/node/readiness") => {
+                into_boxfut(node::get_readiness::<T>(req, beacon_chain))
             }
 
             // Methods for Network
@@ -75,6 +78,12 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::GET, "/beacon/block") => {
                 into_boxfut(beacon::get_block::<T>(req, beacon_chain))
             }
+            (&Method::GET, "/beacon/blocks") => {
+                into_boxfut(beacon::get_blocks::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/beacon/events") => {
+                into_boxfut(beacon::get_events::<T>(req, beacon_chain))
+            }
             (&Method::GET, "/beacon/block_root") => {
                 into_boxfut(beacon::get_block_root::<T>(req, beacon_chain))
             }
@@ -112,6 +121,21 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::POST, "/beacon/attester_slashing") => {
                 into_boxfut(beacon::attester_slashing::<T>(req, beacon_chain))
             }
+            (&Method::POST, "/beacon/voluntary_exit") => {
+                beacon::publish_voluntary_exit::<T>(req, beacon_chain, network_channel, log)
+            }
+            (&Method::GET, "/beacon/pool/attestations") => {
+                into_boxfut(beacon::get_pooled_attestations::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/beacon/pool/voluntary_exits") => {
+                into_boxfut(beacon::get_pooled_voluntary_exits::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/beacon/pool/proposer_slashings") => into_boxfut(
+                beacon::get_pooled_proposer_slashings::<T>(req, beacon_chain),
+            ),
+            (&Method::GET, "/beacon/pool/attester_slashings") => into_boxfut(
+                beacon::get_pooled_attester_slashings::<T>(req, beacon_chain),
+            ),
 
             // Methods for Validator
             (&Method::POST, "/validator/duties") => {
@@ -144,9 +168,25 @@ pub fn route<T: BeaconChainTypes>(
                 drop(timer);
                 into_boxfut(response)
             }
+            (&Method::GET, "/validator/attestations") => {
+                let timer =
+                    metrics::start_timer(&metrics::VALIDATOR_GET_ATTESTATION_REQUEST_RESPONSE_TIME);
+                let response = validator::get_new_attestations::<T>(req, beacon_chain);
+                drop(timer);
+                into_boxfut(response)
+            }
             (&Method::POST, "/validator/attestation") => {
                 validator::publish_attestation::<T>(req, beacon_chain, network_channel, log)
             }
+            (&Method::POST, "/validator/attestations") => {
+                validator::publish_attestations::<T>(req, beacon_chain, network_channel, log)
+            }
+            (&Method::GET, "/validator/aggregate_attestation") => {
+                into_boxfut(validator::get_aggregate_attestation::<T>(req, beacon_chain))
+            }
+            (&Method::POST, "/validator/aggregate_and_proof") => {
+                validator::publish_aggregate_and_proof::<T>(req, beacon_chain, network_channel, log)
+            }
 
             (&Method::GET, "/consensus/global_votes") => {
                 into_boxfut(consensus::get_vote_count::<T>(req, beacon_chain))
@@ -174,6 +214,17 @@ pub fn route<T: BeaconChainTypes>(
             (&Method::GET, "/advanced/operation_pool") => {
                 into_boxfut(advanced::get_operation_pool::<T>(req, beacon_chain))
             }
+            (&Method::GET, "/advanced/chain_stats") => {
+                into_boxfut(advanced::get_chain_stats::<T>(req, beacon_chain))
+            }
+            (&Method::GET, "/advanced/latest_messages") => {
+                into_boxfut(advanced::get_latest_messages::<T>(req, beacon_chain))
+            }
+
+            // Methods for debugging
+            (&Method::GET, "/debug/fork_choice") => {
+                into_boxfut(advanced::get_debug_fork_choice::<T>(req, beacon_chain))
+            }
 
             (&Method::GET, "/metrics") => into_boxfut(metrics::get_prometheus::<T>(
                 req,
@@ -3,6 +3,9 @@ use crate::ApiError;
 use hyper::Request;
 use types::{CommitteeIndex, Epoch, Signature, Slot};
 
+/// The query parameter used to select the encoding of a response (see `ResponseBuilder`).
+pub const ENCODING_KEY: &str = "encoding";
+
 /// Provides handy functions for parsing the query parameters of a URL.
 
 #[derive(Clone, Copy)]
@@ -44,9 +47,12 @@ impl<'a> UrlQuery<'a> {
 
     /// Returns the value for `key`, if and only if `key` is the only key present in the query
     /// parameters.
+    ///
+    /// The `encoding` parameter applies to every response, so it is not counted.
     pub fn only_one(self, key: &str) -> Result<String, ApiError> {
         let queries: Vec<_> = self
             .0
+            .filter(|(k, _v)| k != ENCODING_KEY)
             .map(|(k, v)| (k.into_owned(), v.into_owned()))
             .collect();
 
@@ -124,6 +130,11 @@ mod test {
         assert!(get_result("http://cat.io/?a=42", "b").is_err());
         assert!(get_result("http://cat.io/?a=42&b=12", "a").is_err());
         assert!(get_result("http://cat.io/", "").is_err());
+        assert_eq!(
+            get_result("http://cat.io/?slot=4&encoding=ssz", "slot"),
+            Ok("4".to_string())
+        );
+        assert!(get_result("http://cat.io/?encoding=ssz", "encoding").is_err());
     }
 
     #[test]
@@ -1,24 +1,45 @@
 use crate::helpers::{
-    check_content_type_for_json, publish_attestation_to_network, publish_beacon_block_to_network,
+    check_content_type_for_json, parse_committee_index, publish_aggregate_and_proof_to_network,
+    publish_attestation_to_network, publish_beacon_block_to_network,
 };
 use crate::response_builder::ResponseBuilder;
 use crate::{ApiError, ApiResult, BoxFut, NetworkChannel, UrlQuery};
 use beacon_chain::{
-    AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, BlockProcessingOutcome,
-    StateSkipConfig,
+    AggregateProcessingOutcome, AttestationProcessingOutcome, BeaconChain, BeaconChainTypes,
+    BlockProcessingOutcome, StateSkipConfig,
 };
 use bls::PublicKeyBytes;
 use futures::{Future, Stream};
 use hyper::{Body, Request};
 use serde::{Deserialize, Serialize};
-use slog::{error, info, warn, Logger};
+use slog::{debug, error, info, warn, Logger};
 use ssz_derive::{Decode, Encode};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use types::beacon_state::EthSpec;
 use types::{
-    Attestation, BeaconState, CommitteeIndex, Epoch, RelativeEpoch, SignedBeaconBlock, Slot,
+    Attestation, BeaconState, CommitteeIndex, Epoch, Hash256, RelativeEpoch,
+    SignedAggregateAndProof, SignedBeaconBlock, Slot,
 };
 
+/// The number of slot durations that a duties request may spend skipping a state forward.
+///
+/// Longer than the default of `BeaconChain::state_at_slot`, since a validator client that lags
+/// behind the head by a few epochs is unable to perform any duties without a response.
+const DUTIES_STATE_SKIP_SLOTS: u32 = 4;
+
+/// The outcome of producing an attestation for one committee of a batch request.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+#[serde(bound = "T: EthSpec")]
+pub struct CommitteeAttestation<T: EthSpec> {
+    /// The index of the committee, as given in the request.
+    pub committee_index: CommitteeIndex,
+    /// The unsigned attestation, if it was produced without error.
+    pub attestation: Option<Attestation<T>>,
+    /// A description of the error encountered whilst producing the attestation, if any.
+    pub error: Option<String>,
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
 pub struct ValidatorDuty {
     /// The validator's BLS public key, uniquely identifying them. _48-bytes, hex encoded with 0x prefix, case insensitive._
@@ -31,8 +52,22 @@ pub struct ValidatorDuty {
     pub attestation_committee_index: Option<CommitteeIndex>,
     /// The position of the validator in the committee.
     pub attestation_committee_position: Option<usize>,
+    /// The number of validators in the committee.
+    #[serde(default)]
+    pub attestation_committee_length: Option<usize>,
+    /// The total number of committees in `attestation_slot`.
+    #[serde(default)]
+    pub attestation_committees_at_slot: Option<u64>,
     /// The slots in which a validator must propose a block (can be empty).
     pub block_proposal_slots: Vec<Slot>,
+    /// The root of the block upon which the attestation duty depends. If this block is re-orged
+    /// out, the duty should be fetched again.
+    #[serde(default)]
+    pub attestation_dependent_root: Option<Hash256>,
+    /// The root of the block upon which the block proposal slots depend. If this block is
+    /// re-orged out, the duty should be fetched again.
+    #[serde(default)]
+    pub block_proposal_dependent_root: Option<Hash256>,
 }
 
 #[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
@@ -126,10 +161,11 @@ pub fn get_state_for_epoch<T: BeaconChainTypes>(
     config: StateSkipConfig,
 ) -> Result<BeaconState<T::EthSpec>, ApiError> {
     let slots_per_epoch = T::EthSpec::slots_per_epoch();
-    let head_epoch = beacon_chain.head()?.beacon_state.current_epoch();
+    let head = beacon_chain.head()?;
+    let head_epoch = head.beacon_state.current_epoch();
 
     if RelativeEpoch::from_epoch(head_epoch, epoch).is_ok() {
-        Ok(beacon_chain.head()?.beacon_state)
+        Ok(head.beacon_state.clone_with_only_committee_caches())
     } else {
         let slot = if epoch > head_epoch {
             // Move to the first slot of the epoch prior to the request.
@@ -143,9 +179,18 @@ pub fn get_state_for_epoch<T: BeaconChainTypes>(
             (epoch + 2).start_slot(slots_per_epoch) - 1
         };
 
-        beacon_chain.state_at_slot(slot, config).map_err(|e| {
-            ApiError::ServerError(format!("Unable to load state for epoch {}: {:?}", epoch, e))
-        })
+        let deadline = Instant::now()
+            + Duration::from_millis(beacon_chain.spec.milliseconds_per_slot)
+                * DUTIES_STATE_SKIP_SLOTS;
+
+        beacon_chain
+            .state_at_slot_with_deadline(slot, config, deadline)
+            .map_err(|e| {
+                ApiError::from_category(
+                    e.kind(),
+                    format!("Unable to load state for epoch {}: {}", epoch, e),
+                )
+            })
     }
 }
 
@@ -155,15 +200,12 @@ fn return_validator_duties<T: BeaconChainTypes>(
     epoch: Epoch,
     validator_pubkeys: Vec<PublicKeyBytes>,
 ) -> Result<Vec<ValidatorDuty>, ApiError> {
-    let mut state = get_state_for_epoch(&beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
+    // Read the dependent root before loading the state. If the head changes in between, a client
+    // will see a different dependent root on its next request and re-fetch the duties.
+    let proposal_dependent_root = beacon_chain.proposer_dependent_root(epoch)?;
 
-    let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch)
-        .map_err(|_| ApiError::ServerError(String::from("Loaded state is in the wrong epoch")))?;
+    let mut state = get_state_for_epoch(&beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
 
-    state.update_pubkey_cache()?;
-    state
-        .build_committee_cache(relative_epoch, &beacon_chain.spec)
-        .map_err(|e| ApiError::ServerError(format!("Unable to build committee cache: {:?}", e)))?;
     state
         .update_pubkey_cache()
         .map_err(|e| ApiError::ServerError(format!("Unable to build pubkey cache: {:?}", e)))?;
@@ -171,7 +213,7 @@ fn return_validator_duties<T: BeaconChainTypes>(
     // Get a list of all validators for this epoch.
     //
     // Used for quickly determining the slot for a proposer.
-    let validator_proposers: Vec<(usize, Slot)> = epoch
+    let validator_proposers = epoch
         .slot_iter(T::EthSpec::slots_per_epoch())
         .map(|slot| {
             state
@@ -186,22 +228,40 @@ fn return_validator_duties<T: BeaconChainTypes>(
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    let validator_indices = validator_pubkeys
+        .iter()
+        .map(|validator_pubkey| {
+            state
+                .get_validator_index(validator_pubkey)
+                .map_err(|e| ApiError::ServerError(format!("Unable to read pubkey cache: {:?}", e)))
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    // Obtain the attestation duties of all known validators from a single committee cache. Duties
+    // for an epoch more than one ahead of the current epoch are refused.
+    let known_indices = validator_indices
+        .iter()
+        .filter_map(|index| *index)
+        .collect::<Vec<_>>();
+    let attester_duties = beacon_chain
+        .attester_duties_with_dependent_root(epoch, &known_indices)
+        .map_err(|e| {
+            ApiError::from_category(
+                e.kind(),
+                format!("Unable to obtain attestation duties: {}", e),
+            )
+        })?;
+    let attestation_dependent_root = attester_duties.dependent_root;
+    let mut attestation_duties = attester_duties.duties.into_iter();
+
     validator_pubkeys
         .into_iter()
-        .map(|validator_pubkey| {
-            if let Some(validator_index) =
-                state.get_validator_index(&validator_pubkey).map_err(|e| {
-                    ApiError::ServerError(format!("Unable to read pubkey cache: {:?}", e))
-                })?
-            {
-                let duties = state
-                    .get_attestation_duties(validator_index, relative_epoch)
-                    .map_err(|e| {
-                        ApiError::ServerError(format!(
-                            "Unable to obtain attestation duties: {:?}",
-                            e
-                        ))
-                    })?;
+        .zip(validator_indices.into_iter())
+        .map(|(validator_pubkey, validator_index)| {
+            if let Some(validator_index) = validator_index {
+                let duties = attestation_duties.next().ok_or_else(|| {
+                    ApiError::ServerError("Missing attestation duties for validator".to_string())
+                })?;
 
                 let block_proposal_slots = validator_proposers
                     .iter()
@@ -215,7 +275,11 @@ fn return_validator_duties<T: BeaconChainTypes>(
                     attestation_slot: duties.map(|d| d.slot),
                     attestation_committee_index: duties.map(|d| d.index),
                     attestation_committee_position: duties.map(|d| d.committee_position),
+                    attestation_committee_length: duties.map(|d| d.committee_len),
+                    attestation_committees_at_slot: duties.map(|d| d.committees_at_slot),
                     block_proposal_slots,
+                    attestation_dependent_root: Some(attestation_dependent_root),
+                    block_proposal_dependent_root: Some(proposal_dependent_root),
                 })
             } else {
                 Ok(ValidatorDuty {
@@ -224,7 +288,11 @@ fn return_validator_duties<T: BeaconChainTypes>(
                     attestation_slot: None,
                     attestation_committee_index: None,
                     attestation_committee_position: None,
+                    attestation_committee_length: None,
+                    attestation_committees_at_slot: None,
                     block_proposal_slots: vec![],
+                    attestation_dependent_root: None,
+                    block_proposal_dependent_root: None,
                 })
             }
         })
@@ -251,10 +319,10 @@ pub fn get_new_beacon_block<T: BeaconChainTypes>(
                 "error" => format!("{:?}", e)
             );
 
-            ApiError::ServerError(format!(
-                "Beacon node is not able to produce a block: {:?}",
-                e
-            ))
+            ApiError::from_category(
+                e.kind(),
+                format!("Beacon node is not able to produce a block: {}", e),
+            )
         })?;
 
     ResponseBuilder::new(&req)?.body(&new_block)
@@ -344,10 +412,10 @@ pub fn publish_beacon_block<T: BeaconChainTypes>(
                             "error" => format!("{:?}", e)
                         );
 
-                        Err(ApiError::ServerError(format!(
-                            "Error while processing block: {:?}",
-                            e
-                        )))
+                        Err(ApiError::from_category(
+                            e.kind(),
+                            format!("Error while processing block: {}", e),
+                        ))
                     }
                 }
         })
@@ -365,13 +433,58 @@ pub fn get_new_attestation<T: BeaconChainTypes>(
     let slot = query.slot()?;
     let index = query.committee_index()?;
 
-    let attestation = beacon_chain
-        .produce_attestation(slot, index)
-        .map_err(|e| ApiError::BadRequest(format!("Unable to produce attestation: {:?}", e)))?;
+    let attestation = beacon_chain.produce_attestation(slot, index).map_err(|e| {
+        ApiError::from_category(e.kind(), format!("Unable to produce attestation: {}", e))
+    })?;
 
     ResponseBuilder::new(&req)?.body(&attestation)
 }
 
+/// HTTP Handler to produce a new Attestation for each of the given committees, ready to be signed
+/// by validators.
+pub fn get_new_attestations<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot = query.slot()?;
+    let indices = query
+        .all_of("committee_index")?
+        .iter()
+        .map(|index| parse_committee_index(index))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if indices.is_empty() {
+        return Err(ApiError::BadRequest(
+            "At least one committee_index is required".to_string(),
+        ));
+    }
+
+    let attestations = beacon_chain
+        .produce_attestations_for_slot(slot, &indices)
+        .map_err(|e| {
+            ApiError::from_category(e.kind(), format!("Unable to produce attestations: {}", e))
+        })?
+        .into_iter()
+        .zip(indices.into_iter())
+        .map(|(result, committee_index)| match result {
+            Ok(attestation) => CommitteeAttestation {
+                committee_index,
+                attestation: Some(attestation),
+                error: None,
+            },
+            Err(e) => CommitteeAttestation {
+                committee_index,
+                attestation: None,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect::<Vec<CommitteeAttestation<T::EthSpec>>>();
+
+    ResponseBuilder::new(&req)?.body_no_ssz(&attestations)
+}
+
 /// HTTP Handler to publish an Attestation, which has been signed by a validator.
 pub fn publish_attestation<T: BeaconChainTypes>(
     req: Request<Body>,
@@ -409,6 +522,17 @@ pub fn publish_attestation<T: BeaconChainTypes>(
                         );
                         publish_attestation_to_network::<T>(network_chan, attestation)
                     }
+                    Ok(AttestationProcessingOutcome::AlreadyKnown) => {
+                        // The attestation has already been processed and published (e.g., it was
+                        // resubmitted or received via gossip), so there is nothing to do.
+                        debug!(
+                            log,
+                            "Attestation from local validator already known";
+                            "index" => attestation.data.index,
+                            "slot" => attestation.data.slot,
+                        );
+                        Ok(())
+                    }
                     Ok(outcome) => {
                         warn!(
                             log,
@@ -428,11 +552,200 @@ pub fn publish_attestation<T: BeaconChainTypes>(
                             "error" => format!("{:?}", e)
                         );
 
-                        Err(ApiError::ServerError(format!(
-                            "Error while processing attestation: {:?}",
-                            e
+                        Err(ApiError::from_category(
+                            e.kind(),
+                            format!("Error while processing attestation: {}", e),
+                        ))
+                    }
+                }
+            })
+            .and_then(|_| response_builder?.body_no_ssz(&())),
+    )
+}
+
+/// HTTP Handler to publish a list of Attestations, each signed by a single validator.
+///
+/// Each attestation is processed and published independently, so that an invalid attestation does
+/// not prevent the others from being published. If any attestation is not published, a
+/// `ProcessingError` describing each failure is returned.
+pub fn publish_attestations<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel,
+    log: Logger,
+) -> BoxFut {
+    try_future!(check_content_type_for_json(&req));
+    let response_builder = ResponseBuilder::new(&req);
+
+    Box::new(
+        req.into_body()
+            .concat2()
+            .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+            .and_then(|chunks| {
+                serde_json::from_slice(&chunks).map_err(|e| {
+                    ApiError::BadRequest(format!(
+                        "Unable to deserialize JSON into a list of Attestations: {:?}",
+                        e
+                    ))
+                })
+            })
+            .and_then(move |attestations: Vec<Attestation<T::EthSpec>>| {
+                let failures = attestations
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(i, attestation)| {
+                        match beacon_chain.process_attestation(attestation.clone()) {
+                            Ok(AttestationProcessingOutcome::Processed) => {
+                                info!(
+                                    log,
+                                    "Attestation from local validator";
+                                    "target" => attestation.data.target.epoch,
+                                    "source" => attestation.data.source.epoch,
+                                    "index" => attestation.data.index,
+                                    "slot" => attestation.data.slot,
+                                );
+                                publish_attestation_to_network::<T>(
+                                    network_chan.clone(),
+                                    attestation,
+                                )
+                                .err()
+                                .map(|e| format!("{}: {:?}", i, e))
+                            }
+                            Ok(AttestationProcessingOutcome::AlreadyKnown) => {
+                                debug!(
+                                    log,
+                                    "Attestation from local validator already known";
+                                    "index" => attestation.data.index,
+                                    "slot" => attestation.data.slot,
+                                );
+                                None
+                            }
+                            Ok(outcome) => {
+                                warn!(
+                                    log,
+                                    "Invalid attestation from local validator";
+                                    "outcome" => format!("{:?}", outcome)
+                                );
+                                Some(format!("{}: {:?}", i, outcome))
+                            }
+                            Err(e) => {
+                                error!(
+                                    log,
+                                    "Error whilst processing attestation";
+                                    "error" => format!("{:?}", e)
+                                );
+                                Some(format!("{}: {}", i, e))
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                if failures.is_empty() {
+                    Ok(())
+                } else {
+                    Err(ApiError::ProcessingError(format!(
+                        "{} attestation(s) could not be processed and have not been published: \
+                         [{}]",
+                        failures.len(),
+                        failures.join(", ")
+                    )))
+                }
+            })
+            .and_then(|_| response_builder?.body_no_ssz(&())),
+    )
+}
+
+/// HTTP Handler to retrieve the aggregate attestation with the most signers for the given slot and
+/// committee, so that it may be published by an aggregator.
+pub fn get_aggregate_attestation<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot = query.slot()?;
+    let index = query.committee_index()?;
+
+    let aggregate = beacon_chain
+        .get_aggregated_attestation(slot, index)
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "No attestations are known for slot {} and committee {}",
+                slot, index
+            ))
+        })?;
+
+    ResponseBuilder::new(&req)?.body(&aggregate)
+}
+
+/// HTTP Handler to publish a SignedAggregateAndProof, which has been signed by an aggregator.
+///
+/// The `SignedAggregateAndProof` is published on the aggregate-and-proof topic.
+pub fn publish_aggregate_and_proof<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel,
+    log: Logger,
+) -> BoxFut {
+    try_future!(check_content_type_for_json(&req));
+    let response_builder = ResponseBuilder::new(&req);
+
+    Box::new(
+        req.into_body()
+            .concat2()
+            .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))
+            .and_then(|chunks| {
+                serde_json::from_slice(&chunks).map_err(|e| {
+                    ApiError::BadRequest(format!(
+                        "Unable to deserialize JSON into a SignedAggregateAndProof: {:?}",
+                        e
+                    ))
+                })
+            })
+            .and_then(move |signed_aggregate: SignedAggregateAndProof<T::EthSpec>| {
+                match beacon_chain.process_aggregate_and_proof(&signed_aggregate) {
+                    // An aggregate is only found to be known once its aggregator is verified. It
+                    // is published regardless, since peers may not have seen it.
+                    Ok(AggregateProcessingOutcome::Processed)
+                    | Ok(AggregateProcessingOutcome::InvalidAggregate(
+                        AttestationProcessingOutcome::AlreadyKnown,
+                    )) => {
+                        let aggregate = &signed_aggregate.message.aggregate;
+
+                        info!(
+                            log,
+                            "Aggregate from local validator";
+                            "aggregator" => signed_aggregate.message.aggregator_index,
+                            "signatures" => aggregate.aggregation_bits.num_set_bits(),
+                            "index" => aggregate.data.index,
+                            "slot" => aggregate.data.slot,
+                        );
+                        publish_aggregate_and_proof_to_network::<T>(network_chan, signed_aggregate)
+                    }
+                    Ok(outcome) => {
+                        warn!(
+                            log,
+                            "Invalid aggregate from local validator";
+                            "outcome" => format!("{:?}", outcome)
+                        );
+
+                        Err(ApiError::ProcessingError(format!(
+                            "The SignedAggregateAndProof could not be processed and has not been published: {:?}",
+                            outcome
                         )))
                     }
+                    Err(e) => {
+                        error!(
+                            log,
+                            "Error whilst processing aggregate";
+                            "error" => format!("{:?}", e)
+                        );
+
+                        Err(ApiError::from_category(
+                            e.kind(),
+                            format!("Error while processing aggregate: {}", e),
+                        ))
+                    }
                 }
             })
             .and_then(|_| response_builder?.body_no_ssz(&())),
@@ -1,23 +1,32 @@
 #![cfg(test)]
 
-use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use beacon_chain::{
+    events::{EventKind, EventTopic},
+    test_utils::collect_roots,
+    BeaconChain, BeaconChainTypes, StateSkipConfig,
+};
+use futures::{future, Async, Future, Stream};
 use node_test_rig::{
     environment::{Environment, EnvironmentBuilder},
     testing_client_config, ClientConfig, ClientGenesis, LocalBeaconNode,
 };
 use remote_beacon_node::{
-    Committee, HeadBeaconBlock, PersistedOperationPool, PublishStatus, ValidatorDuty,
-    ValidatorResponse,
+    Committee, Error as RemoteError, ExitPublishStatus, HeadBeaconBlock, PersistedOperationPool,
+    PublishStatus, ValidatorDuty, ValidatorResponse,
 };
+use ssz::Encode;
 use std::convert::TryInto;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use store::Store;
 use types::{
     test_utils::{
         build_double_vote_attester_slashing, build_proposer_slashing,
         generate_deterministic_keypair, AttesterSlashingTestTask, ProposerSlashingTestTask,
     },
-    BeaconBlock, BeaconState, ChainSpec, Domain, Epoch, EthSpec, MinimalEthSpec, PublicKey,
-    RelativeEpoch, Signature, SignedBeaconBlock, SignedRoot, Slot, Validator,
+    AggregateAndProof, BeaconBlock, BeaconState, ChainSpec, Domain, Epoch, EthSpec, Hash256,
+    MinimalEthSpec, PublicKey, RelativeEpoch, SelectionProof, Signature, SignedBeaconBlock,
+    SignedRoot, SignedVoluntaryExit, Slot, Validator, VoluntaryExit,
 };
 use version;
 
@@ -33,6 +42,32 @@ fn build_env() -> Environment<E> {
         .expect("environment should build")
 }
 
+/// Subscribes to the slashing events of `chain`.
+fn slashing_events<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+) -> impl Stream<Item = EventKind<T::EthSpec>, Error = ()> {
+    chain
+        .event_stream(std::iter::once(EventTopic::Slashing).collect())
+        .expect("should subscribe to events")
+}
+
+/// Returns the events which are ready to be read from `receiver`, without waiting for any more.
+fn drain_events<T, S>(receiver: &mut S) -> Vec<EventKind<T>>
+where
+    T: EthSpec,
+    S: Stream<Item = EventKind<T>, Error = ()>,
+{
+    future::lazy(|| {
+        let mut events = vec![];
+        while let Ok(Async::Ready(Some(event))) = receiver.poll() {
+            events.push(event);
+        }
+        Ok::<_, ()>(events)
+    })
+    .wait()
+    .expect("should drain events")
+}
+
 fn build_node<E: EthSpec>(env: &mut Environment<E>, config: ClientConfig) -> LocalBeaconNode<E> {
     let context = env.core_context();
     env.runtime()
@@ -93,7 +128,11 @@ fn validator_produce_attestation() {
         .client
         .beacon_chain()
         .expect("client should have beacon chain");
-    let state = beacon_chain.head().expect("should get head").beacon_state;
+    let state = beacon_chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .clone();
 
     let validator_index = 0;
     let duties = state
@@ -167,13 +206,181 @@ fn validator_produce_attestation() {
             remote_node
                 .http
                 .validator()
-                .publish_attestation(attestation),
+                .publish_attestation(attestation.clone()),
         )
         .expect("should publish attestation");
     assert!(
         publish_status.is_valid(),
         "the signed published attestation should be valid"
     );
+
+    // The published attestation should now be known as an aggregate.
+    let aggregate = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .produce_aggregate_attestation(attestation.data.slot, attestation.data.index),
+        )
+        .expect("should fetch aggregate from http api");
+    assert_eq!(
+        aggregate.aggregation_bits.num_set_bits(),
+        1,
+        "aggregate should include the published attestation"
+    );
+
+    let selection_proof =
+        SelectionProof::new::<E>(aggregate.data.slot, &keypair.sk, &state.fork, spec);
+    assert!(
+        selection_proof.is_aggregator(
+            duties
+                .attestation_committee_length
+                .expect("should have committee length"),
+            spec
+        ),
+        "every member of a small committee should aggregate"
+    );
+
+    let signed_aggregate = AggregateAndProof {
+        aggregator_index: validator_index as u64,
+        aggregate,
+        selection_proof: selection_proof.into(),
+    }
+    .sign(&keypair.sk, &state.fork, spec);
+
+    let publish_status = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .publish_aggregate_and_proof(signed_aggregate),
+        )
+        .expect("should publish aggregate");
+    assert!(
+        publish_status.is_valid(),
+        "the signed published aggregate should be valid"
+    );
+}
+
+#[test]
+fn validator_publish_attestations() {
+    let mut env = build_env();
+
+    let spec = &E::default_spec();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let beacon_chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+    let state = beacon_chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .clone();
+
+    let duties = state
+        .get_attestation_duties(0, RelativeEpoch::Current)
+        .expect("should have attestation duties cache")
+        .expect("should have attestation duties");
+    let committee = state
+        .get_beacon_committee(duties.slot, duties.index)
+        .expect("should get committee")
+        .committee
+        .to_vec();
+
+    let unsigned_attestation = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .produce_attestation(duties.slot, duties.index),
+        )
+        .expect("should fetch attestation from http api");
+
+    // Sign a separate attestation for each member of the committee.
+    let signed_attestations = committee
+        .iter()
+        .enumerate()
+        .map(|(position, validator_index)| {
+            let mut attestation = unsigned_attestation.clone();
+            attestation
+                .sign(
+                    &generate_deterministic_keypair(*validator_index).sk,
+                    position,
+                    &state.fork,
+                    spec,
+                )
+                .expect("should sign attestation");
+            attestation
+        })
+        .collect::<Vec<_>>();
+
+    // An invalid attestation should be reported without preventing the others from publishing.
+    let mut attestations = vec![unsigned_attestation];
+    attestations.extend(signed_attestations.clone());
+
+    let publish_status = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .publish_attestations(attestations),
+        )
+        .expect("should publish attestations");
+    assert!(
+        !publish_status.is_valid(),
+        "the unsigned published attestation should not be valid"
+    );
+
+    let aggregate = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .produce_aggregate_attestation(duties.slot, duties.index),
+        )
+        .expect("should fetch aggregate from http api");
+    assert_eq!(
+        aggregate.aggregation_bits.num_set_bits(),
+        committee.len(),
+        "aggregate should include each signed attestation"
+    );
+
+    // Resubmitting attestations which are already known should succeed.
+    let publish_status = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .publish_attestations(signed_attestations.clone()),
+        )
+        .expect("should publish attestations");
+    assert!(
+        publish_status.is_valid(),
+        "already known attestations should be accepted"
+    );
+    let publish_status = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .publish_attestation(signed_attestations[0].clone()),
+        )
+        .expect("should publish attestation");
+    assert!(
+        publish_status.is_valid(),
+        "an already known attestation should be accepted"
+    );
 }
 
 #[test]
@@ -215,14 +422,28 @@ fn validator_duties() {
         spec,
     );
 
-    epoch += 4;
+    epoch += 1;
     let duties = env
         .runtime()
         .block_on(remote_node.http.validator().get_duties(epoch, &validators))
         .expect("should fetch duties from http api");
 
-    // 2. Check with a long skip forward.
-    check_duties(duties, epoch, validators, beacon_chain, spec);
+    // 2. Check at the next epoch.
+    check_duties(duties, epoch, validators.clone(), beacon_chain, spec);
+
+    // 3. Check that the shuffling is not guessed more than one epoch ahead.
+    assert_eq!(
+        error_status(
+            env.runtime().block_on(
+                remote_node
+                    .http
+                    .validator()
+                    .get_duties(epoch + 1, &validators)
+            )
+        ),
+        400,
+        "should refuse duties more than one epoch ahead"
+    );
 
     // TODO: test an epoch in the past. Blocked because the `LocalBeaconNode` cannot produce a
     // chain, yet.
@@ -284,6 +505,24 @@ fn check_duties<T: BeaconChainTypes>(
                 "attestation index should match"
             );
 
+            assert_eq!(
+                Some(attestation_duty.committee_position),
+                duty.attestation_committee_position,
+                "attestation committee position should match"
+            );
+
+            assert_eq!(
+                Some(attestation_duty.committee_len),
+                duty.attestation_committee_length,
+                "attestation committee length should match"
+            );
+
+            assert_eq!(
+                Some(attestation_duty.committees_at_slot),
+                duty.attestation_committees_at_slot,
+                "attestation committees at slot should match"
+            );
+
             if !duty.block_proposal_slots.is_empty() {
                 for slot in &duty.block_proposal_slots {
                     let expected_proposer = state
@@ -539,6 +778,196 @@ fn beacon_block() {
     );
 }
 
+#[test]
+fn beacon_blocks_in_range() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let response = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .get_blocks_in_range(Slot::new(0), 8),
+        )
+        .expect("should fetch blocks from http api");
+
+    let db_block = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain")
+        .block_at_slot(Slot::new(0))
+        .expect("should find block")
+        .expect("block should not be none");
+
+    assert!(
+        !response.precedes_oldest_available,
+        "the chain should be available from genesis"
+    );
+    assert_eq!(
+        response.blocks.len(),
+        1,
+        "only the genesis block should be returned"
+    );
+    assert_eq!(response.blocks[0].root, db_block.canonical_root());
+    assert_eq!(response.blocks[0].beacon_block, db_block);
+}
+
+/// Returns the status code of an unsuccessful response from the HTTP API.
+fn error_status<T: std::fmt::Debug>(result: Result<T, RemoteError>) -> u16 {
+    match result {
+        Err(RemoteError::DidNotSucceed { status, .. }) => status.as_u16(),
+        other => panic!("should not succeed, got {:?}", other),
+    }
+}
+
+#[test]
+fn beacon_block_unknown_root() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let result = env.runtime().block_on(
+        remote_node
+            .http
+            .beacon()
+            .get_block_by_root(Hash256::from_low_u64_be(42)),
+    );
+
+    assert_eq!(
+        error_status(result),
+        404,
+        "should not find an unknown block"
+    );
+}
+
+#[test]
+fn beacon_state_pre_genesis() {
+    let mut env = build_env();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("should get system time")
+        .as_secs();
+
+    let mut config = testing_client_config();
+    config.genesis = ClientGenesis::Interop {
+        validator_count: 8,
+        genesis_time: now + 3_600,
+    };
+
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let result = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_state_by_slot(Slot::new(1)));
+
+    assert_eq!(
+        error_status(result),
+        503,
+        "should be unavailable until the slot clock starts"
+    );
+}
+
+#[test]
+fn beacon_state_ssz() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let (state_by_slot, root) = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .get_ssz_state_by_slot(Slot::new(0)),
+        )
+        .expect("should fetch ssz state from http api");
+
+    let (state_by_root, root_2) = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_ssz_state_by_root(root))
+        .expect("should fetch ssz state from http api");
+
+    let db_state = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain")
+        .store
+        .get_state(&root, None)
+        .expect("should read state from store")
+        .expect("state should be in store");
+
+    assert_eq!(
+        root, root_2,
+        "the two roots returned from the api should be identical"
+    );
+    assert_eq!(
+        state_by_slot.as_ssz_bytes(),
+        db_state.as_ssz_bytes(),
+        "genesis state by slot from api should be identical to that in the store"
+    );
+    assert_eq!(
+        state_by_root.as_ssz_bytes(),
+        db_state.as_ssz_bytes(),
+        "genesis state by root from api should be identical to that in the store"
+    );
+}
+
+#[test]
+fn beacon_block_ssz() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let (block_by_slot, root) = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .get_ssz_block_by_slot(Slot::new(0)),
+        )
+        .expect("should fetch ssz block from http api");
+
+    let (block_by_root, root_2) = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_ssz_block_by_root(root))
+        .expect("should fetch ssz block from http api");
+
+    let db_block = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain")
+        .store
+        .get_block(&root)
+        .expect("should read block from store")
+        .expect("block should be in store");
+
+    assert_eq!(
+        root, root_2,
+        "the two roots returned from the api should be identical"
+    );
+    assert_eq!(
+        block_by_slot.as_ssz_bytes(),
+        db_block.as_ssz_bytes(),
+        "genesis block by slot from api should be identical to that in the store"
+    );
+    assert_eq!(
+        block_by_root.as_ssz_bytes(),
+        db_block.as_ssz_bytes(),
+        "genesis block by root from api should be identical to that in the store"
+    );
+}
+
 #[test]
 fn genesis_time() {
     let mut env = build_env();
@@ -628,6 +1057,34 @@ fn get_version() {
     assert_eq!(version::version(), version, "result should be as expected");
 }
 
+#[test]
+fn get_syncing() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let status = env
+        .runtime()
+        .block_on(remote_node.http.node().get_syncing())
+        .expect("should fetch sync status from http api");
+
+    let head_slot = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain")
+        .head_info()
+        .expect("should get head info")
+        .slot;
+
+    assert_eq!(
+        status.head_slot(),
+        head_slot,
+        "result should be as expected"
+    );
+    assert!(status.is_synced(), "a node at genesis should be synced");
+}
+
 #[test]
 fn get_genesis_state_root() {
     let mut env = build_env();
@@ -642,15 +1099,17 @@ fn get_genesis_state_root() {
         .block_on(remote_node.http.beacon().get_state_root(slot))
         .expect("should fetch from http api");
 
-    let expected = node
-        .client
-        .beacon_chain()
-        .expect("should have beacon chain")
-        .rev_iter_state_roots()
-        .expect("should get iter")
-        .find(|(_cur_root, cur_slot)| slot == *cur_slot)
-        .map(|(cur_root, _)| cur_root)
-        .expect("chain should have state root at slot");
+    let expected = collect_roots(
+        node.client
+            .beacon_chain()
+            .expect("should have beacon chain")
+            .rev_iter_state_roots()
+            .expect("should get iter"),
+    )
+    .into_iter()
+    .find(|(_cur_root, cur_slot)| slot == *cur_slot)
+    .map(|(cur_root, _)| cur_root)
+    .expect("chain should have state root at slot");
 
     assert_eq!(result, expected, "result should be as expected");
 }
@@ -669,15 +1128,17 @@ fn get_genesis_block_root() {
         .block_on(remote_node.http.beacon().get_block_root(slot))
         .expect("should fetch from http api");
 
-    let expected = node
-        .client
-        .beacon_chain()
-        .expect("should have beacon chain")
-        .rev_iter_block_roots()
-        .expect("should get iter")
-        .find(|(_cur_root, cur_slot)| slot == *cur_slot)
-        .map(|(cur_root, _)| cur_root)
-        .expect("chain should have state root at slot");
+    let expected = collect_roots(
+        node.client
+            .beacon_chain()
+            .expect("should have beacon chain")
+            .rev_iter_block_roots()
+            .expect("should get iter"),
+    )
+    .into_iter()
+    .find(|(_cur_root, cur_slot)| slot == *cur_slot)
+    .map(|(cur_root, _)| cur_root)
+    .expect("chain should have state root at slot");
 
     assert_eq!(result, expected, "result should be as expected");
 }
@@ -692,7 +1153,8 @@ fn get_validators() {
         .client
         .beacon_chain()
         .expect("node should have beacon chain");
-    let state = &chain.head().expect("should get head").beacon_state;
+    let head = chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     let validators = state.validators.iter().take(2).collect::<Vec<_>>();
     let pubkeys = validators
@@ -721,7 +1183,8 @@ fn get_all_validators() {
         .client
         .beacon_chain()
         .expect("node should have beacon chain");
-    let state = &chain.head().expect("should get head").beacon_state;
+    let head = chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     let result = env
         .runtime()
@@ -744,7 +1207,8 @@ fn get_active_validators() {
         .client
         .beacon_chain()
         .expect("node should have beacon chain");
-    let state = &chain.head().expect("should get head").beacon_state;
+    let head = chain.head().expect("should get head");
+    let state = &head.beacon_state;
 
     let result = env
         .runtime()
@@ -891,6 +1355,8 @@ fn proposer_slashing() {
     let (proposer_slashings, _attester_slashings) = chain.op_pool.get_slashings(&state, spec);
     assert_eq!(proposer_slashings.len(), 0);
 
+    let mut events = slashing_events(&chain);
+
     let slot = state.slot;
     let proposer_index = chain
         .block_proposer(slot)
@@ -922,6 +1388,13 @@ fn proposer_slashing() {
     assert_eq!(proposer_slashings.len(), 1);
     assert_eq!(proposer_slashing.clone(), proposer_slashings[0]);
 
+    match drain_events(&mut events).as_slice() {
+        [EventKind::ProposerSlashingReceived {
+            proposer_slashing: received,
+        }] => assert_eq!(**received, proposer_slashing),
+        other => panic!("should emit a single slashing event, got {:?}", other),
+    }
+
     let mut invalid_proposer_slashing = build_proposer_slashing::<E>(
         ProposerSlashingTestTask::Valid,
         proposer_index as u64,
@@ -943,6 +1416,10 @@ fn proposer_slashing() {
     let (proposer_slashings, _attester_slashings) = chain.op_pool.get_slashings(&state, spec);
     assert_eq!(proposer_slashings.len(), 1);
     assert_eq!(proposer_slashing, proposer_slashings[0]);
+    assert!(
+        drain_events(&mut events).is_empty(),
+        "should not emit an event for an invalid slashing"
+    );
 }
 
 #[test]
@@ -976,6 +1453,8 @@ fn attester_slashing() {
     let (_proposer_slashings, attester_slashings) = chain.op_pool.get_slashings(&state, spec);
     assert_eq!(attester_slashings.len(), 0);
 
+    let mut events = slashing_events(&chain);
+
     let attester_slashing = build_double_vote_attester_slashing(
         AttesterSlashingTestTask::Valid,
         &validator_indices[..],
@@ -1000,6 +1479,13 @@ fn attester_slashing() {
     assert_eq!(attester_slashings.len(), 1);
     assert_eq!(attester_slashing, attester_slashings[0]);
 
+    match drain_events(&mut events).as_slice() {
+        [EventKind::AttesterSlashingReceived {
+            attester_slashing: received,
+        }] => assert_eq!(**received, attester_slashing),
+        other => panic!("should emit a single slashing event, got {:?}", other),
+    }
+
     // Building an invalid attester slashing
     let mut invalid_attester_slashing = build_double_vote_attester_slashing(
         AttesterSlashingTestTask::Valid,
@@ -1022,4 +1508,349 @@ fn attester_slashing() {
     let (_proposer_slashings, attester_slashings) = chain.op_pool.get_slashings(&state, spec);
     assert_eq!(attester_slashings.len(), 1);
     assert_eq!(attester_slashing, attester_slashings[0]);
+    assert!(
+        drain_events(&mut events).is_empty(),
+        "should not emit an event for an invalid slashing"
+    );
+}
+
+#[test]
+fn slashings_on_follower() {
+    let mut env = build_env();
+
+    // A follower does not produce blocks, so it does not queue slashings.
+    let mut config = testing_client_config();
+    config.follower_mode = true;
+
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+    let spec = &chain.spec;
+    let fork = &state.fork;
+
+    let mut events = slashing_events(&chain);
+
+    let proposer_index = chain
+        .block_proposer(state.slot)
+        .expect("should get proposer index");
+    let keypair = generate_deterministic_keypair(proposer_index);
+
+    let proposer_slashing = build_proposer_slashing::<E>(
+        ProposerSlashingTestTask::Valid,
+        proposer_index as u64,
+        &keypair.sk,
+        fork,
+        spec,
+    );
+    let queued = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .proposer_slashing(proposer_slashing),
+        )
+        .expect("should accept a valid proposer slashing");
+    assert!(!queued, "should not queue the proposer slashing");
+
+    let attester_slashing = build_double_vote_attester_slashing(
+        AttesterSlashingTestTask::Valid,
+        &[proposer_index as u64],
+        &[&keypair.sk],
+        fork,
+        spec,
+    );
+    let queued = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .attester_slashing(attester_slashing),
+        )
+        .expect("should accept a valid attester slashing");
+    assert!(!queued, "should not queue the attester slashing");
+
+    let (proposer_slashings, attester_slashings) = chain.op_pool.get_slashings(&state, spec);
+    assert!(proposer_slashings.is_empty(), "should not queue slashings");
+    assert!(attester_slashings.is_empty(), "should not queue slashings");
+    assert!(
+        drain_events(&mut events).is_empty(),
+        "should not emit events for slashings which were not queued"
+    );
+
+    // An invalid slashing should still be rejected.
+    let mut invalid_proposer_slashing = build_proposer_slashing::<E>(
+        ProposerSlashingTestTask::Valid,
+        proposer_index as u64,
+        &keypair.sk,
+        fork,
+        spec,
+    );
+    invalid_proposer_slashing.signed_header_2 = invalid_proposer_slashing.signed_header_1.clone();
+    let result = env.runtime().block_on(
+        remote_node
+            .http
+            .beacon()
+            .proposer_slashing(invalid_proposer_slashing),
+    );
+    assert_eq!(error_status(result), 400);
+}
+
+#[test]
+fn get_pooled_attestations() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+    let slot = state.slot;
+
+    let attestation = chain
+        .produce_attestation(slot, 0)
+        .expect("should produce attestation");
+    chain
+        .op_pool
+        .insert_attestation(attestation.clone(), &state.fork, &chain.spec)
+        .expect("should insert attestation");
+
+    let mut get = |slot, committee_index| {
+        env.runtime()
+            .block_on(
+                remote_node
+                    .http
+                    .beacon()
+                    .get_pooled_attestations(slot, committee_index),
+            )
+            .expect("should fetch from http api")
+    };
+
+    assert_eq!(get(None, None), vec![attestation.clone()]);
+    assert_eq!(get(Some(slot), None), vec![attestation.clone()]);
+    assert_eq!(get(Some(slot), Some(0)), vec![attestation]);
+    assert!(get(Some(slot + 1), None).is_empty());
+    assert!(get(Some(slot), Some(1)).is_empty());
+}
+
+#[test]
+fn get_pooled_slashings_and_exits() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+    let spec = &chain.spec;
+
+    let proposer_index = chain
+        .block_proposer(state.slot)
+        .expect("should get proposer index");
+    let keypair = generate_deterministic_keypair(proposer_index);
+
+    let proposer_slashing = build_proposer_slashing::<E>(
+        ProposerSlashingTestTask::Valid,
+        proposer_index as u64,
+        &keypair.sk,
+        &state.fork,
+        spec,
+    );
+    chain
+        .op_pool
+        .insert_proposer_slashing(proposer_slashing.clone(), &state, spec)
+        .expect("should insert proposer slashing");
+
+    let attester_slashing = build_double_vote_attester_slashing(
+        AttesterSlashingTestTask::Valid,
+        &[proposer_index as u64],
+        &[&keypair.sk],
+        &state.fork,
+        spec,
+    );
+    chain
+        .op_pool
+        .insert_attester_slashing(attester_slashing.clone(), &state, spec)
+        .expect("should insert attester slashing");
+
+    let proposer_slashings = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_pooled_proposer_slashings())
+        .expect("should fetch from http api");
+    assert_eq!(proposer_slashings, vec![proposer_slashing]);
+
+    let attester_slashings = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_pooled_attester_slashings())
+        .expect("should fetch from http api");
+    assert_eq!(attester_slashings, vec![attester_slashing]);
+
+    // Validators cannot exit so soon after genesis, so the pool should be empty.
+    let voluntary_exits = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_pooled_voluntary_exits())
+        .expect("should fetch from http api");
+    assert_eq!(voluntary_exits, chain.get_pooled_voluntary_exits());
+    assert!(voluntary_exits.is_empty());
+}
+
+/// Returns an exit for the validator at `validator_index`, signed for the head state of `chain`.
+fn signed_exit<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    validator_index: usize,
+) -> SignedVoluntaryExit {
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+
+    VoluntaryExit {
+        epoch: state.current_epoch(),
+        validator_index: validator_index as u64,
+    }
+    .sign(
+        &generate_deterministic_keypair(validator_index).sk,
+        &state.fork,
+        &chain.spec,
+    )
+}
+
+#[test]
+fn publish_voluntary_exit() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let exit = signed_exit(&chain, 0);
+
+    // Validators cannot exit so soon after genesis, so the exit should be rejected.
+    let status = env
+        .runtime()
+        .block_on(remote_node.http.beacon().publish_voluntary_exit(exit))
+        .expect("should publish exit");
+    match status {
+        ExitPublishStatus::Invalid(_) => (),
+        other => panic!("should reject the exit, got {:?}", other),
+    }
+
+    assert!(
+        chain.get_pooled_voluntary_exits().is_empty(),
+        "should not queue the exit"
+    );
+}
+
+#[test]
+fn publish_voluntary_exit_queues_valid_exit() {
+    let mut env = build_env();
+    // Allow validators to exit immediately after genesis.
+    env.eth2_config.spec.persistent_committee_period = 0;
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let exit = signed_exit(&chain, 0);
+
+    let status = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .publish_voluntary_exit(exit.clone()),
+        )
+        .expect("should publish exit");
+    assert_eq!(status, ExitPublishStatus::Queued);
+
+    let pooled_exits = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_pooled_voluntary_exits())
+        .expect("should fetch from http api");
+    assert_eq!(pooled_exits, vec![exit], "should queue the exit");
+}
+
+#[test]
+fn publish_voluntary_exit_without_eth1() {
+    let mut env = build_env();
+    // Allow validators to exit immediately after genesis.
+    env.eth2_config.spec.persistent_committee_period = 0;
+
+    // Without an eth1 backend the node does not produce blocks, so it does not queue exits.
+    let mut config = testing_client_config();
+    config.dummy_eth1_backend = false;
+
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let status = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .publish_voluntary_exit(signed_exit(&chain, 0)),
+        )
+        .expect("should publish exit");
+    assert_eq!(
+        status,
+        ExitPublishStatus::NotQueued,
+        "should publish a valid exit without queuing it"
+    );
+    assert!(
+        chain.get_pooled_voluntary_exits().is_empty(),
+        "should not queue the exit"
+    );
+
+    // An invalid exit should still be rejected.
+    let mut invalid_exit = signed_exit(&chain, 1);
+    invalid_exit.message.epoch += 1;
+    let status = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .publish_voluntary_exit(invalid_exit),
+        )
+        .expect("should publish exit");
+    match status {
+        ExitPublishStatus::Invalid(_) => (),
+        other => panic!(
+            "should reject an exit with a bad signature, got {:?}",
+            other
+        ),
+    }
 }
@@ -0,0 +1,63 @@
+//! Prints the administrative overrides (e.g., checkpoint starts and snapshot imports) recorded in
+//! the database of a beacon node.
+use crate::config::{get_data_dir, CLIENT_CONFIG_FILENAME};
+use beacon_chain::AdminHistory;
+use clap::ArgMatches;
+use client::ClientConfig;
+use eth2_config::read_from_file;
+use store::SimpleDiskStore;
+use types::EthSpec;
+
+/// Runs the `admin-history` sub-command of the beacon node.
+///
+/// Reads the history directly from the hot database, so the beacon node using the database must
+/// be stopped first.
+pub fn run<E: EthSpec>(cli_args: &ArgMatches, history_args: &ArgMatches) -> Result<(), String> {
+    let count = history_args
+        .value_of("count")
+        .ok_or_else(|| "The --count flag is required".to_string())?
+        .parse::<usize>()
+        .map_err(|e| format!("Unable to parse --count: {:?}", e))?;
+
+    let data_dir = get_data_dir(cli_args);
+    let config_path = data_dir.join(CLIENT_CONFIG_FILENAME);
+    let mut client_config = if config_path.exists() {
+        read_from_file::<ClientConfig>(config_path.clone())
+            .map_err(|e| format!("Unable to parse {:?} file: {:?}", config_path, e))?
+            .ok_or_else(|| format!("{:?} file does not exist", config_path))?
+    } else {
+        ClientConfig::default()
+    };
+    client_config.data_dir = data_dir;
+
+    let db_path = client_config
+        .get_db_path()
+        .ok_or_else(|| "Unable to locate the database".to_string())?;
+    // Opening a database that does not exist would create an empty one.
+    if !db_path.exists() {
+        return Err(format!("No database found at {:?}", db_path));
+    }
+
+    let store = SimpleDiskStore::<E>::open(&db_path)
+        .map_err(|e| format!("Unable to open database: {:?}", e))?;
+    let history = AdminHistory::load::<E, _>(&store)
+        .map_err(|e| format!("Unable to load admin history: {:?}", e))?;
+    let events = history
+        .latest::<E, _>(&store, count)
+        .map_err(|e| format!("Unable to read admin history: {:?}", e))?;
+
+    println!(
+        "Showing {} of {} administrative overrides, oldest first.",
+        events.len(),
+        history.num_events()
+    );
+
+    for event in events {
+        println!(
+            "{} {} (v{}) {}",
+            event.timestamp, event.action, event.software_version, event.parameters
+        );
+    }
+
+    Ok(())
+}
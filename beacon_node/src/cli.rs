@@ -191,6 +191,21 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true)
                 .default_value("http://127.0.0.1:8545")
         )
+        .arg(
+            Arg::with_name("eth1-fallback-endpoints")
+                .long("eth1-fallback-endpoints")
+                .value_name("HTTP-ENDPOINTS")
+                .help("A comma-separated list of servers, in order of preference, to use when the \
+                       --eth1-endpoint server is unavailable or has fallen behind.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("eth1-network-id")
+                .long("eth1-network-id")
+                .value_name("NETWORK_ID")
+                .help("If present, eth1 servers that are not following this network are not used.")
+                .takes_value(true)
+        )
         .arg(
             Arg::with_name("slots-per-restore-point")
                 .long("slots-per-restore-point")
@@ -213,6 +228,114 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specifies how many states the database should cache in memory [default: 5]")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("lock-timeout")
+                .long("lock-timeout")
+                .value_name("MILLISECONDS")
+                .help("Specifies how long to wait for a lock on the head or the beacon chain caches \
+                       before failing the operation. Consider increasing if the lock time-out \
+                       metrics are non-zero. [default: 1000]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("max-skip-slots")
+                .long("max-skip-slots")
+                .value_name("SLOTS")
+                .help("Refuse to advance a state (e.g., to produce a block) across more than this \
+                       many skipped slots. Unlimited if absent.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("max-block-range")
+                .long("max-block-range")
+                .value_name("SLOTS")
+                .help("The maximum number of slots covered by a single query for a range of \
+                       blocks, from a peer or the HTTP API. Longer ranges are truncated. \
+                       Defaults to 1024.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("dump-invalid-blocks")
+                .long("dump-invalid-blocks")
+                .value_name("DIR")
+                .help("Write each block that fails processing, along with the state to which it \
+                       was applied, to this directory as SSZ. Useful for reporting consensus \
+                       failures. Stops once the directory contains 32 files.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("max-future-slot-tolerance")
+                .long("max-future-slot-tolerance")
+                .value_name("MILLISECONDS")
+                .help("Accept blocks from a slot which starts within this many milliseconds, to \
+                       allow for clock skew between the proposer and this node. Blocks from slots \
+                       further in the future are rejected. [default: 500]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("disable-slashing-detection")
+                .long("disable-slashing-detection")
+                .help("Do not look for double votes and surround votes amongst the attestations \
+                       processed by this node, or for conflicting blocks from the same proposer.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("follower")
+                .long("follower")
+                .help("Follow the chain and serve its data without producing blocks or queuing \
+                       operations for inclusion in them.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("validator-monitor")
+                .long("validator-monitor")
+                .value_name("VALIDATORS")
+                .help("A comma-separated list of validator indices and 0x-prefixed public keys. \
+                       The block proposals and attestation inclusions of these validators are \
+                       reported in the logs and metrics.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("readiness-max-sync-distance")
+                .long("readiness-max-sync-distance")
+                .value_name("SLOTS")
+                .help("The number of slots the head may lag behind the current slot before the \
+                       node is reported as not ready on the /node/readiness endpoint. [default: 32]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("readiness-max-eth1-block-age")
+                .long("readiness-max-eth1-block-age")
+                .value_name("SECONDS")
+                .help("The maximum age of the latest block in the eth1 cache before the node is \
+                       reported as not ready on the /node/readiness endpoint. [default: 21600]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("readiness-ignore-cold-caches")
+                .long("readiness-ignore-cold-caches")
+                .help("Report the node as ready on the /node/readiness endpoint even whilst its \
+                       caches are cold.")
+                .takes_value(false)
+        )
+        /*
+         * The "admin-history" sub-command.
+         *
+         * Prints the administrative overrides recorded in the database.
+         */
+        .subcommand(SubCommand::with_name("admin-history")
+            .about("Prints the administrative overrides (e.g., checkpoint starts and snapshot \
+                    imports) that have been applied to the database, then exits. The beacon \
+                    node must not be running.")
+            .arg(
+                Arg::with_name("count")
+                    .long("count")
+                    .value_name("INTEGER")
+                    .help("The maximum number of overrides to print, starting from the latest.")
+                    .takes_value(true)
+                    .default_value("32")
+            )
+        )
         /*
          * The "testnet" sub-command.
          *
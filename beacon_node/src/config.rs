@@ -1,3 +1,4 @@
+use beacon_chain::LockTimeouts;
 use clap::ArgMatches;
 use client::{config::DEFAULT_DATADIR, ClientConfig, ClientGenesis, Eth2Config};
 use eth2_config::{read_from_file, write_to_file};
@@ -11,7 +12,8 @@ use std::fs;
 use std::net::{IpAddr, Ipv4Addr};
 use std::net::{TcpListener, UdpSocket};
 use std::path::PathBuf;
-use types::EthSpec;
+use std::time::Duration;
+use types::{EthSpec, PublicKeyBytes};
 
 pub const CLIENT_CONFIG_FILENAME: &str = "beacon-node.toml";
 pub const ETH2_CONFIG_FILENAME: &str = "eth2-spec.toml";
@@ -21,6 +23,18 @@ pub const NETWORK_DIR: &str = "network";
 type Result<T> = std::result::Result<T, String>;
 type Config = (ClientConfig, Eth2Config, Logger);
 
+/// Gets the beacon node data directory from the `--datadir` flag.
+///
+/// If it's not present, try and find the home directory (`~`) and push the default data directory
+/// onto it.
+pub fn get_data_dir(cli_args: &ArgMatches) -> PathBuf {
+    cli_args
+        .value_of("datadir")
+        .map(|path| PathBuf::from(path).join(BEACON_NODE_DIR))
+        .or_else(|| dirs::home_dir().map(|home| home.join(DEFAULT_DATADIR).join(BEACON_NODE_DIR)))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
 /// Gets the fully-initialized global client and eth2 configuration objects.
 ///
 /// The top-level `clap` arguments should be provided as `cli_args`.
@@ -40,15 +54,7 @@ pub fn get_configs<E: EthSpec>(
 
     client_config.spec_constants = eth2_config.spec_constants.clone();
 
-    // Read the `--datadir` flag.
-    //
-    // If it's not present, try and find the home directory (`~`) and push the default data
-    // directory onto it.
-    client_config.data_dir = cli_args
-        .value_of("datadir")
-        .map(|path| PathBuf::from(path).join(BEACON_NODE_DIR))
-        .or_else(|| dirs::home_dir().map(|home| home.join(DEFAULT_DATADIR).join(BEACON_NODE_DIR)))
-        .unwrap_or_else(|| PathBuf::from("."));
+    client_config.data_dir = get_data_dir(cli_args);
 
     // Load the client config, if it exists .
     let path = client_config.data_dir.join(CLIENT_CONFIG_FILENAME);
@@ -219,6 +225,21 @@ pub fn get_configs<E: EthSpec>(
         client_config.eth1.endpoint = val.to_string();
     }
 
+    if let Some(val) = cli_args.value_of("eth1-fallback-endpoints") {
+        client_config.eth1.fallback_endpoints = val
+            .split(',')
+            .map(|endpoint| endpoint.trim().to_string())
+            .filter(|endpoint| !endpoint.is_empty())
+            .collect();
+    }
+
+    if let Some(val) = cli_args.value_of("eth1-network-id") {
+        client_config.eth1.network_id = Some(
+            val.parse()
+                .map_err(|e| format!("Unable to parse eth1-network-id: {:?}", e))?,
+        );
+    }
+
     match cli_args.subcommand() {
         ("testnet", Some(sub_cmd_args)) => {
             process_testnet_subcommand(&mut client_config, &mut eth2_config, sub_cmd_args)?
@@ -278,6 +299,80 @@ pub fn get_configs<E: EthSpec>(
             .map_err(|_| "block-cache-size is not a valid integer".to_string())?;
     }
 
+    if let Some(lock_timeout) = cli_args.value_of("lock-timeout") {
+        let millis = lock_timeout
+            .parse()
+            .map_err(|_| "lock-timeout is not a valid integer".to_string())?;
+        client_config.lock_timeouts = LockTimeouts::all(Duration::from_millis(millis));
+    }
+
+    if let Some(max_skip_slots) = cli_args.value_of("max-skip-slots") {
+        client_config.max_skip_slots = Some(
+            max_skip_slots
+                .parse()
+                .map_err(|_| "max-skip-slots is not a valid integer".to_string())?,
+        );
+    }
+
+    if let Some(max_block_range) = cli_args.value_of("max-block-range") {
+        client_config.max_block_range = max_block_range
+            .parse()
+            .map_err(|_| "max-block-range is not a valid integer".to_string())?;
+    }
+
+    if let Some(dir) = cli_args.value_of("dump-invalid-blocks") {
+        client_config.dump_invalid_blocks = Some(PathBuf::from(dir));
+    }
+
+    if let Some(tolerance) = cli_args.value_of("max-future-slot-tolerance") {
+        let millis = tolerance
+            .parse()
+            .map_err(|_| "max-future-slot-tolerance is not a valid integer".to_string())?;
+        client_config.max_future_slot_tolerance = Duration::from_millis(millis);
+    }
+
+    if cli_args.is_present("disable-slashing-detection") {
+        client_config.disable_slashing_detection = true;
+    }
+
+    if cli_args.is_present("follower") {
+        client_config.follower_mode = true;
+    }
+
+    if let Some(validators) = cli_args.value_of("validator-monitor") {
+        for validator in validators.split(',') {
+            if validator.starts_with("0x") {
+                let bytes = hex::decode(validator.trim_start_matches("0x"))
+                    .map_err(|e| format!("Invalid validator-monitor public key: {:?}", e))?;
+                let pubkey = PublicKeyBytes::from_bytes(&bytes)
+                    .map_err(|e| format!("Invalid validator-monitor public key: {:?}", e))?;
+                client_config.validator_monitor_pubkeys.push(pubkey);
+            } else {
+                let index = validator
+                    .parse()
+                    .map_err(|_| format!("Invalid validator-monitor index: {}", validator))?;
+                client_config.validator_monitor_indices.push(index);
+            }
+        }
+    }
+
+    if let Some(distance) = cli_args.value_of("readiness-max-sync-distance") {
+        client_config.readiness.max_sync_distance = distance
+            .parse()
+            .map_err(|_| "readiness-max-sync-distance is not a valid integer".to_string())?;
+    }
+
+    if let Some(age) = cli_args.value_of("readiness-max-eth1-block-age") {
+        let seconds = age
+            .parse()
+            .map_err(|_| "readiness-max-eth1-block-age is not a valid integer".to_string())?;
+        client_config.readiness.max_eth1_block_age = Duration::from_secs(seconds);
+    }
+
+    if cli_args.is_present("readiness-ignore-cold-caches") {
+        client_config.readiness.require_warm_caches = false;
+    }
+
     if eth2_config.spec_constants != client_config.spec_constants {
         crit!(log, "Specification constants do not match.";
               "client_config" => client_config.spec_constants.to_string(),
@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate clap;
 
+pub mod admin_history;
 mod cli;
 mod config;
 
@@ -10,7 +11,7 @@ pub use client::{Client, ClientBuilder, ClientConfig, ClientGenesis};
 pub use eth2_config::Eth2Config;
 
 use beacon_chain::{
-    builder::Witness, eth1_chain::CachingEth1Backend, events::WebSocketSender,
+    builder::Witness, eth1_chain::CachingEth1Backend, events::TeeEventHandler,
     slot_clock::SystemTimeSlotClock,
 };
 use clap::ArgMatches;
@@ -30,7 +31,7 @@ pub type ProductionClient<E> = Client<
         SystemTimeSlotClock,
         CachingEth1Backend<E, DiskStore<E>>,
         E,
-        WebSocketSender<E>,
+        TeeEventHandler<E>,
     >,
 >;
 
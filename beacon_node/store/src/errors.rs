@@ -1,7 +1,7 @@
 use crate::chunked_vector::ChunkError;
 use crate::hot_cold_store::HotColdDBError;
 use ssz::DecodeError;
-use types::BeaconStateError;
+use types::{BeaconStateError, Hash256, Slot};
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
@@ -10,8 +10,20 @@ pub enum Error {
     BeaconStateError(BeaconStateError),
     PartialBeaconStateError,
     HotColdDBError(HotColdDBError),
-    DBError { message: String },
+    DBError {
+        message: String,
+    },
     RlpError(String),
+    SchemaMigrationError(String),
+    /// A state required to iterate back beyond the roots of a descendant state is not stored.
+    MissingHistoricalState {
+        state_root: Hash256,
+        slot: Slot,
+    },
+    /// A block with a root returned by a roots iterator is not stored.
+    MissingBlock(Hash256),
+    /// The hybrid forwards iterator has no state from which to continue after the freezer DB.
+    NoContinuationData,
 }
 
 impl From<DecodeError> for Error {
@@ -1,8 +1,7 @@
 use crate::chunked_iter::ChunkedVectorIter;
 use crate::chunked_vector::BlockRoots;
 use crate::iter::{BlockRootsIterator, ReverseBlockRootIterator};
-use crate::{DiskStore, Store};
-use slog::error;
+use crate::{DiskStore, Error, Store};
 use std::sync::Arc;
 use types::{BeaconState, ChainSpec, EthSpec, Hash256, Slot};
 
@@ -48,12 +47,12 @@ impl<E: EthSpec> FrozenForwardsBlockRootsIterator<E> {
 }
 
 impl<E: EthSpec> Iterator for FrozenForwardsBlockRootsIterator<E> {
-    type Item = (Hash256, Slot);
+    type Item = Result<(Hash256, Slot), Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.inner
             .next()
-            .map(|(slot, block_hash)| (block_hash, Slot::from(slot)))
+            .map(|(slot, block_hash)| Ok((block_hash, Slot::from(slot))))
     }
 }
 
@@ -63,25 +62,28 @@ impl SimpleForwardsBlockRootsIterator {
         start_slot: Slot,
         end_state: BeaconState<E>,
         end_block_root: Hash256,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         // Iterate backwards from the end state, stopping at the start slot.
-        Self {
-            values: ReverseBlockRootIterator::new(
-                (end_block_root, end_state.slot),
-                BlockRootsIterator::owned(store, end_state),
-            )
-            .take_while(|(_, slot)| *slot >= start_slot)
-            .collect(),
-        }
+        let values = ReverseBlockRootIterator::new(
+            (end_block_root, end_state.slot),
+            BlockRootsIterator::owned(store, end_state),
+        )
+        .take_while(|result| match result {
+            Ok((_, slot)) => *slot >= start_slot,
+            Err(_) => true,
+        })
+        .collect::<Result<_, _>>()?;
+
+        Ok(Self { values })
     }
 }
 
 impl Iterator for SimpleForwardsBlockRootsIterator {
-    type Item = (Hash256, Slot);
+    type Item = Result<(Hash256, Slot), Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         // Pop from the end of the vector to get the block roots in slot-ascending order.
-        self.values.pop()
+        self.values.pop().map(Ok)
     }
 }
 
@@ -92,12 +94,12 @@ impl<E: EthSpec> HybridForwardsBlockRootsIterator<E> {
         end_state: BeaconState<E>,
         end_block_root: Hash256,
         spec: &ChainSpec,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         use HybridForwardsBlockRootsIterator::*;
 
         let latest_restore_point_slot = store.get_latest_restore_point_slot();
 
-        if start_slot < latest_restore_point_slot {
+        let result = if start_slot < latest_restore_point_slot {
             PreFinalization {
                 iter: Box::new(FrozenForwardsBlockRootsIterator::new(
                     store,
@@ -114,16 +116,14 @@ impl<E: EthSpec> HybridForwardsBlockRootsIterator<E> {
                     start_slot,
                     end_state,
                     end_block_root,
-                ),
+                )?,
             }
-        }
-    }
-}
+        };
 
-impl<E: EthSpec> Iterator for HybridForwardsBlockRootsIterator<E> {
-    type Item = (Hash256, Slot);
+        Ok(result)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    fn do_next(&mut self) -> Result<Option<(Hash256, Slot)>, Error> {
         use HybridForwardsBlockRootsIterator::*;
 
         match self {
@@ -132,19 +132,14 @@ impl<E: EthSpec> Iterator for HybridForwardsBlockRootsIterator<E> {
                 continuation_data,
             } => {
                 match iter.next() {
-                    Some(x) => Some(x),
+                    Some(x) => x.map(Some),
                     // Once the pre-finalization iterator is consumed, transition
                     // to a post-finalization iterator beginning from the last slot
                     // of the pre iterator.
                     None => {
-                        let (end_state, end_block_root) =
-                            continuation_data.take().or_else(|| {
-                                error!(
-                                    iter.inner.store.log,
-                                    "HybridForwardsBlockRootsIterator: logic error"
-                                );
-                                None
-                            })?;
+                        let (end_state, end_block_root) = continuation_data
+                            .take()
+                            .ok_or_else(|| Error::NoContinuationData)?;
 
                         *self = PostFinalization {
                             iter: SimpleForwardsBlockRootsIterator::new(
@@ -152,13 +147,21 @@ impl<E: EthSpec> Iterator for HybridForwardsBlockRootsIterator<E> {
                                 Slot::from(iter.inner.end_vindex),
                                 end_state,
                                 end_block_root,
-                            ),
+                            )?,
                         };
-                        self.next()
+                        self.do_next()
                     }
                 }
             }
-            PostFinalization { iter } => iter.next(),
+            PostFinalization { iter } => iter.next().transpose(),
         }
     }
 }
+
+impl<E: EthSpec> Iterator for HybridForwardsBlockRootsIterator<E> {
+    type Item = Result<(Hash256, Slot), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.do_next().transpose()
+    }
+}
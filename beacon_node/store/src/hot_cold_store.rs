@@ -138,6 +138,37 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
         }
     }
 
+    /// Fetch a batch of blocks from the store, taking the cache lock only once.
+    ///
+    /// Blocks which are read from disk are not added to the cache, so that reading a long range of
+    /// old blocks (e.g., to serve a syncing peer) does not evict the recent blocks from it.
+    fn get_blocks(
+        &self,
+        block_roots: &[Hash256],
+    ) -> Result<Vec<Option<SignedBeaconBlock<E>>>, Error> {
+        metrics::inc_counter_by(&metrics::BEACON_BLOCK_GET_COUNT, block_roots.len() as i64);
+
+        let cached = {
+            let mut block_cache = self.block_cache.lock();
+            block_roots
+                .iter()
+                .map(|block_root| block_cache.get(block_root).cloned())
+                .collect::<Vec<_>>()
+        };
+
+        block_roots
+            .iter()
+            .zip(cached)
+            .map(|(block_root, cached)| match cached {
+                Some(block) => {
+                    metrics::inc_counter(&metrics::BEACON_BLOCK_CACHE_HIT_COUNT);
+                    Ok(Some(block))
+                }
+                None => self.get::<SignedBeaconBlock<E>>(block_root),
+            })
+            .collect()
+    }
+
     /// Delete a block from the store and the block cache.
     fn delete_block(&self, block_root: &Hash256) -> Result<(), Error> {
         self.block_cache.lock().pop(block_root);
@@ -244,9 +275,12 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
         let state_root_iter = StateRootsIterator::new(store.clone(), frozen_head);
 
         let mut to_delete = vec![];
-        for (state_root, slot) in
-            state_root_iter.take_while(|&(_, slot)| slot >= current_split_slot)
-        {
+        for result in state_root_iter.take_while(|result| match result {
+            Ok((_, slot)) => *slot >= current_split_slot,
+            Err(_) => true,
+        }) {
+            let (state_root, slot) = result?;
+
             if slot % store.config.slots_per_restore_point == 0 {
                 let state: BeaconState<E> = store
                     .hot_db
@@ -291,7 +325,7 @@ impl<E: EthSpec> Store<E> for HotColdDB<E> {
         end_state: BeaconState<E>,
         end_block_root: Hash256,
         spec: &ChainSpec,
-    ) -> Self::ForwardsBlockRootsIterator {
+    ) -> Result<Self::ForwardsBlockRootsIterator, Error> {
         HybridForwardsBlockRootsIterator::new(store, start_slot, end_state, end_block_root, spec)
     }
 
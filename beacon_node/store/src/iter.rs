@@ -1,4 +1,4 @@
-use crate::Store;
+use crate::{Error, Store};
 use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::sync::Arc;
@@ -11,7 +11,7 @@ use types::{
 /// ## Note
 ///
 /// It is assumed that all ancestors for this object are stored in the database. If this is not the
-/// case, the iterator will start returning `None` prior to genesis.
+/// case, the iterator will return an error prior to genesis.
 pub trait AncestorIter<U: Store<E>, E: EthSpec, I: Iterator> {
     /// Returns an iterator over the roots of the ancestors of `self`.
     fn try_iter_ancestor_roots(&self, store: Arc<U>) -> Option<I>;
@@ -76,18 +76,16 @@ impl<'a, T: EthSpec, U: Store<T>> StateRootsIterator<'a, T, U> {
     }
 }
 
-impl<'a, T: EthSpec, U: Store<T>> Iterator for StateRootsIterator<'a, T, U> {
-    type Item = (Hash256, Slot);
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl<'a, T: EthSpec, U: Store<T>> StateRootsIterator<'a, T, U> {
+    fn do_next(&mut self) -> Result<Option<(Hash256, Slot)>, Error> {
         if self.slot == 0 || self.slot > self.beacon_state.slot {
-            return None;
+            return Ok(None);
         }
 
         self.slot -= 1;
 
         match self.beacon_state.get_state_root(self.slot) {
-            Ok(root) => Some((*root, self.slot)),
+            Ok(root) => Ok(Some((*root, self.slot))),
             Err(BeaconStateError::SlotOutOfBounds) => {
                 // Read a `BeaconState` from the store that has access to prior historical roots.
                 let beacon_state =
@@ -95,15 +93,29 @@ impl<'a, T: EthSpec, U: Store<T>> Iterator for StateRootsIterator<'a, T, U> {
 
                 self.beacon_state = Cow::Owned(beacon_state);
 
-                let root = self.beacon_state.get_state_root(self.slot).ok()?;
+                let root = self.beacon_state.get_state_root(self.slot)?;
 
-                Some((*root, self.slot))
+                Ok(Some((*root, self.slot)))
             }
-            _ => None,
+            Err(e) => Err(e.into()),
         }
     }
 }
 
+impl<'a, T: EthSpec, U: Store<T>> Iterator for StateRootsIterator<'a, T, U> {
+    type Item = Result<(Hash256, Slot), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.do_next()
+            // Return `None` after an error, rather than the same error indefinitely.
+            .map_err(|e| {
+                self.slot = Slot::new(0);
+                e
+            })
+            .transpose()
+    }
+}
+
 /// Block iterator that uses the `parent_root` of each block to backtrack.
 pub struct ParentRootBlockIterator<'a, E: EthSpec, S: Store<E>> {
     store: &'a S,
@@ -161,22 +173,34 @@ impl<'a, T: EthSpec, U: Store<T>> BlockIterator<'a, T, U> {
 }
 
 impl<'a, T: EthSpec, U: Store<T>> Iterator for BlockIterator<'a, T, U> {
-    type Item = SignedBeaconBlock<T>;
+    type Item = Result<SignedBeaconBlock<T>, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (root, _slot) = self.roots.next()?;
-        self.roots.store.get_block(&root).ok()?
+        let block = self.roots.next()?.and_then(|(root, _slot)| {
+            self.roots
+                .store
+                .get_block(&root)?
+                .ok_or_else(|| Error::MissingBlock(root))
+        });
+
+        if block.is_err() {
+            // Return `None` after an error, rather than the same error indefinitely.
+            self.roots.slot = Slot::new(0);
+        }
+
+        Some(block)
     }
 }
 
-/// Iterates backwards through block roots. If any specified slot is unable to be retrieved, the
-/// iterator returns `None` indefinitely.
+/// Iterates backwards through block roots.
 ///
 /// Uses the `block_roots` field of `BeaconState` to as the source of block roots and will
 /// perform a lookup on the `Store` for a prior `BeaconState` if `block_roots` has been
 /// exhausted.
 ///
-/// Returns `None` for roots prior to genesis or when there is an error reading from `Store`.
+/// Returns `None` for roots prior to genesis. Returns an error if a prior `BeaconState` cannot be
+/// read from the `Store` (e.g., because it is missing), after which it returns `None`
+/// indefinitely.
 pub struct BlockRootsIterator<'a, T: EthSpec, U> {
     store: Arc<U>,
     beacon_state: Cow<'a, BeaconState<T>>,
@@ -213,18 +237,16 @@ impl<'a, T: EthSpec, U: Store<T>> BlockRootsIterator<'a, T, U> {
     }
 }
 
-impl<'a, T: EthSpec, U: Store<T>> Iterator for BlockRootsIterator<'a, T, U> {
-    type Item = (Hash256, Slot);
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl<'a, T: EthSpec, U: Store<T>> BlockRootsIterator<'a, T, U> {
+    fn do_next(&mut self) -> Result<Option<(Hash256, Slot)>, Error> {
         if self.slot == 0 || self.slot > self.beacon_state.slot {
-            return None;
+            return Ok(None);
         }
 
         self.slot -= 1;
 
         match self.beacon_state.get_block_root(self.slot) {
-            Ok(root) => Some((*root, self.slot)),
+            Ok(root) => Ok(Some((*root, self.slot))),
             Err(BeaconStateError::SlotOutOfBounds) => {
                 // Read a `BeaconState` from the store that has access to prior historical roots.
                 let beacon_state =
@@ -232,27 +254,48 @@ impl<'a, T: EthSpec, U: Store<T>> Iterator for BlockRootsIterator<'a, T, U> {
 
                 self.beacon_state = Cow::Owned(beacon_state);
 
-                let root = self.beacon_state.get_block_root(self.slot).ok()?;
+                let root = self.beacon_state.get_block_root(self.slot)?;
 
-                Some((*root, self.slot))
+                Ok(Some((*root, self.slot)))
             }
-            _ => None,
+            Err(e) => Err(e.into()),
         }
     }
 }
 
+impl<'a, T: EthSpec, U: Store<T>> Iterator for BlockRootsIterator<'a, T, U> {
+    type Item = Result<(Hash256, Slot), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.do_next()
+            // Return `None` after an error, rather than the same error indefinitely.
+            .map_err(|e| {
+                self.slot = Slot::new(0);
+                e
+            })
+            .transpose()
+    }
+}
+
 /// Fetch the next state to use whilst backtracking in `*RootsIterator`.
+///
+/// Returns an error if the state is not in the store.
 fn next_historical_root_backtrack_state<E: EthSpec, S: Store<E>>(
     store: &S,
     current_state: &BeaconState<E>,
-) -> Option<BeaconState<E>> {
+) -> Result<BeaconState<E>, Error> {
     // For compatibility with the freezer database's restore points, we load a state at
     // a restore point slot (thus avoiding replaying blocks). In the case where we're
     // not frozen, this just means we might not jump back by the maximum amount on
     // our first jump (i.e. at most 1 extra state load).
     let new_state_slot = slot_of_prev_restore_point::<E>(current_state.slot);
-    let new_state_root = current_state.get_state_root(new_state_slot).ok()?;
-    store.get_state(new_state_root, Some(new_state_slot)).ok()?
+    let new_state_root = current_state.get_state_root(new_state_slot)?;
+    store
+        .get_state(new_state_root, Some(new_state_slot))?
+        .ok_or_else(|| Error::MissingHistoricalState {
+            state_root: *new_state_root,
+            slot: new_state_slot,
+        })
 }
 
 /// Compute the slot of the last guaranteed restore point in the freezer database.
@@ -268,41 +311,36 @@ pub type ReverseStateRootIterator<'a, E, S> =
 
 pub type ReverseHashAndSlotIterator<I> = ReverseChainIterator<(Hash256, Slot), I>;
 
-/// Provides a wrapper for an iterator that returns a given `T` before it starts returning results of
-/// the `Iterator`.
+/// Provides a wrapper for a fallible iterator that returns a given `T` before it starts returning
+/// results of the `Iterator`.
 pub struct ReverseChainIterator<T, I> {
-    first_value_used: bool,
-    first_value: T,
+    first_value: Option<T>,
     iter: I,
 }
 
-impl<T, I> ReverseChainIterator<T, I>
+impl<T, I, E> ReverseChainIterator<T, I>
 where
     T: Sized,
-    I: Iterator<Item = T> + Sized,
+    I: Iterator<Item = Result<T, E>> + Sized,
 {
     pub fn new(first_value: T, iter: I) -> Self {
         Self {
-            first_value_used: false,
-            first_value,
+            first_value: Some(first_value),
             iter,
         }
     }
 }
 
-impl<T, I> Iterator for ReverseChainIterator<T, I>
+impl<T, I, E> Iterator for ReverseChainIterator<T, I>
 where
-    T: Clone,
-    I: Iterator<Item = T>,
+    I: Iterator<Item = Result<T, E>>,
 {
-    type Item = T;
+    type Item = Result<T, E>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.first_value_used {
-            self.iter.next()
-        } else {
-            self.first_value_used = true;
-            Some(self.first_value.clone())
+        match self.first_value.take() {
+            Some(first_value) => Some(Ok(first_value)),
+            None => self.iter.next(),
         }
     }
 }
@@ -349,12 +387,12 @@ mod test {
 
         let iter = BlockRootsIterator::new(store, &state_b);
 
+        let mut collected: Vec<(Hash256, Slot)> = iter.collect::<Result<_, _>>().unwrap();
+
         assert!(
-            iter.clone().any(|(_root, slot)| slot == 0),
+            collected.iter().any(|(_root, slot)| *slot == 0),
             "iter should contain zero slot"
         );
-
-        let mut collected: Vec<(Hash256, Slot)> = iter.collect();
         collected.reverse();
 
         let expected_len = 2 * MainnetEthSpec::slots_per_historical_root();
@@ -398,12 +436,12 @@ mod test {
 
         let iter = StateRootsIterator::new(store, &state_b);
 
+        let mut collected: Vec<(Hash256, Slot)> = iter.collect::<Result<_, _>>().unwrap();
+
         assert!(
-            iter.clone().any(|(_root, slot)| slot == 0),
+            collected.iter().any(|(_root, slot)| *slot == 0),
             "iter should contain zero slot"
         );
-
-        let mut collected: Vec<(Hash256, Slot)> = iter.collect();
         collected.reverse();
 
         let expected_len = MainnetEthSpec::slots_per_historical_root() * 2;
@@ -423,4 +461,39 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn roots_iters_return_error_for_missing_state() {
+        let store = Arc::new(MemoryStore::<MainnetEthSpec>::open());
+        let slots_per_historical_root = MainnetEthSpec::slots_per_historical_root();
+
+        let mut state: BeaconState<MainnetEthSpec> = get_state();
+        state.slot = Slot::from(slots_per_historical_root * 2);
+
+        let missing_state_root = Hash256::from_low_u64_be(42);
+        state
+            .set_state_root(Slot::from(slots_per_historical_root), missing_state_root)
+            .expect("should set state root");
+
+        let expected_error = || Error::MissingHistoricalState {
+            state_root: missing_state_root,
+            slot: Slot::from(slots_per_historical_root),
+        };
+
+        let block_roots = BlockRootsIterator::new(store.clone(), &state).collect::<Vec<_>>();
+        assert_eq!(block_roots.len(), slots_per_historical_root + 1);
+        assert_eq!(
+            block_roots.last(),
+            Some(&Err(expected_error())),
+            "should end with an error"
+        );
+
+        let state_roots = StateRootsIterator::new(store, &state).collect::<Vec<_>>();
+        assert_eq!(state_roots.len(), slots_per_historical_root + 1);
+        assert_eq!(
+            state_roots.last(),
+            Some(&Err(expected_error())),
+            "should end with an error"
+        );
+    }
 }
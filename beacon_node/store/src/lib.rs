@@ -19,6 +19,7 @@ mod hot_cold_store;
 mod impls;
 mod leveldb_store;
 mod memory_store;
+mod metadata;
 mod metrics;
 mod partial_beacon_state;
 mod state_batch;
@@ -32,6 +33,7 @@ pub use self::config::StoreConfig;
 pub use self::hot_cold_store::{HotColdDB as DiskStore, HotStateSummary};
 pub use self::leveldb_store::LevelDB as SimpleDiskStore;
 pub use self::memory_store::MemoryStore;
+pub use self::metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION};
 pub use self::migrate::Migrate;
 pub use self::partial_beacon_state::PartialBeaconState;
 pub use errors::Error;
@@ -47,7 +49,7 @@ pub use types::*;
 /// columns. A simple column implementation might involve prefixing a key with some bytes unique to
 /// each column.
 pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
-    type ForwardsBlockRootsIterator: Iterator<Item = (Hash256, Slot)>;
+    type ForwardsBlockRootsIterator: Iterator<Item = Result<(Hash256, Slot), Error>>;
 
     /// Retrieve some bytes in `column` with `key`.
     fn get_bytes(&self, column: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error>;
@@ -81,6 +83,19 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
         I::db_delete(self, key)
     }
 
+    /// Fetch the schema version of the database, which is `SchemaVersion(0)` if it predates
+    /// versioning.
+    fn get_schema_version(&self) -> Result<SchemaVersion, Error> {
+        Ok(self
+            .get(&metadata::schema_version_key())?
+            .unwrap_or_else(|| SchemaVersion(0)))
+    }
+
+    /// Store the schema version of the database.
+    fn put_schema_version(&self, schema_version: SchemaVersion) -> Result<(), Error> {
+        self.put(&metadata::schema_version_key(), &schema_version)
+    }
+
     /// Store a block in the store.
     fn put_block(&self, block_root: &Hash256, block: SignedBeaconBlock<E>) -> Result<(), Error> {
         self.put(block_root, &block)
@@ -91,6 +106,18 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
         self.get(block_root)
     }
 
+    /// Fetch a batch of blocks from the store, returning an item for each of `block_roots` in the
+    /// same order.
+    fn get_blocks(
+        &self,
+        block_roots: &[Hash256],
+    ) -> Result<Vec<Option<SignedBeaconBlock<E>>>, Error> {
+        block_roots
+            .iter()
+            .map(|block_root| self.get_block(block_root))
+            .collect()
+    }
+
     /// Delete a block from the store.
     fn delete_block(&self, block_root: &Hash256) -> Result<(), Error> {
         self.delete::<SignedBeaconBlock<E>>(block_root)
@@ -158,7 +185,7 @@ pub trait Store<E: EthSpec>: Sync + Send + Sized + 'static {
         end_state: BeaconState<E>,
         end_block_root: Hash256,
         spec: &ChainSpec,
-    ) -> Self::ForwardsBlockRootsIterator;
+    ) -> Result<Self::ForwardsBlockRootsIterator, Error>;
 
     /// Load the most recent ancestor state of `state_root` which lies on an epoch boundary.
     ///
@@ -193,7 +220,11 @@ pub enum DBColumn {
     BeaconState,
     /// For persisting in-memory state to the database.
     BeaconChain,
+    /// For the trusted checkpoint from which the chain was started, if not genesis.
+    BeaconAnchor,
     OpPool,
+    /// For the changes to the op pool persisted since its last full snapshot.
+    OpPoolDeltas,
     Eth1Cache,
     ForkChoice,
     /// For the table mapping restore point numbers to state roots.
@@ -205,6 +236,8 @@ pub enum DBColumn {
     BeaconHistoricalRoots,
     BeaconRandaoMixes,
     DhtEnrs,
+    /// For the append-only log of administrative overrides applied to the chain.
+    ChainEvents,
 }
 
 impl Into<&'static str> for DBColumn {
@@ -215,7 +248,9 @@ impl Into<&'static str> for DBColumn {
             DBColumn::BeaconBlock => "blk",
             DBColumn::BeaconState => "ste",
             DBColumn::BeaconChain => "bch",
+            DBColumn::BeaconAnchor => "anc",
             DBColumn::OpPool => "opo",
+            DBColumn::OpPoolDeltas => "opd",
             DBColumn::Eth1Cache => "etc",
             DBColumn::ForkChoice => "frk",
             DBColumn::BeaconRestorePoint => "brp",
@@ -225,6 +260,7 @@ impl Into<&'static str> for DBColumn {
             DBColumn::BeaconHistoricalRoots => "bhr",
             DBColumn::BeaconRandaoMixes => "brm",
             DBColumn::DhtEnrs => "dht",
+            DBColumn::ChainEvents => "cev",
         }
     }
 }
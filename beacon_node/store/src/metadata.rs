@@ -0,0 +1,41 @@
+use crate::{DBColumn, Error, SimpleStoreItem};
+use ssz::{Decode, Encode};
+use types::Hash256;
+
+/// The schema version of the database written by this version of Lighthouse.
+pub const CURRENT_SCHEMA_VERSION: SchemaVersion = SchemaVersion(1);
+
+/// The key of the `SchemaVersion` in the `DBColumn::BeaconMeta` column.
+pub const SCHEMA_VERSION_KEY: &str = "SCHEMAVERSIONSCHEMAVERSIONSCHEMA";
+
+/// The version of the layout of the containers persisted under fixed keys (e.g., the persisted
+/// beacon chain, op pool, eth1 cache and fork choice).
+///
+/// A database without a stored version predates versioning and is at version zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaVersion(pub u64);
+
+impl SchemaVersion {
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl SimpleStoreItem for SchemaVersion {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.0.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(SchemaVersion(u64::from_ssz_bytes(bytes)?))
+    }
+}
+
+/// Returns the key of the `SchemaVersion` in the `DBColumn::BeaconMeta` column.
+pub fn schema_version_key() -> Hash256 {
+    Hash256::from_slice(SCHEMA_VERSION_KEY.as_bytes())
+}
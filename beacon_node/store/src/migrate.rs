@@ -20,6 +20,14 @@ pub trait Migrate<S, E: EthSpec>: Send + Sync + 'static {
         _max_finality_distance: u64,
     ) {
     }
+
+    /// Returns the slot of the oldest state stored in the hot database, if the store separates
+    /// hot and cold states.
+    ///
+    /// States prior to this slot must be reconstructed by replaying blocks from the freezer.
+    fn oldest_hot_state_slot(&self) -> Option<Slot> {
+        None
+    }
 }
 
 /// Migrator that does nothing, for stores that don't need migration.
@@ -107,6 +115,10 @@ impl<E: EthSpec> Migrate<DiskStore<E>, E> for BackgroundMigrator<E> {
             let _ = tx.send(tx_err.0);
         }
     }
+
+    fn oldest_hot_state_slot(&self) -> Option<Slot> {
+        Some(self.db.get_split_slot())
+    }
 }
 
 impl<E: EthSpec> BackgroundMigrator<E> {
@@ -3,12 +3,13 @@ mod attestation_id;
 mod max_cover;
 mod persistence;
 
-pub use persistence::PersistedOperationPool;
+pub use persistence::{PersistedOperationPool, PersistedOperationPoolDelta};
 
 use attestation::AttMaxCover;
 use attestation_id::AttestationId;
 use max_cover::maximum_cover;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use persistence::ChangedOperations;
 use state_processing::per_block_processing::errors::{
     AttestationValidationError, AttesterSlashingValidationError, ExitValidationError,
     ProposerSlashingValidationError,
@@ -22,7 +23,8 @@ use std::collections::{hash_map, HashMap, HashSet};
 use std::marker::PhantomData;
 use types::{
     typenum::Unsigned, Attestation, AttesterSlashing, BeaconState, BeaconStateError, ChainSpec,
-    EthSpec, Fork, ProposerSlashing, RelativeEpoch, SignedVoluntaryExit, Validator,
+    CommitteeIndex, EthSpec, Fork, ProposerSlashing, RelativeEpoch, SignedVoluntaryExit, Slot,
+    Validator,
 };
 
 #[derive(Default, Debug)]
@@ -35,6 +37,10 @@ pub struct OperationPool<T: EthSpec + Default> {
     proposer_slashings: RwLock<HashMap<u64, ProposerSlashing>>,
     /// Map from exiting validator to their exit data.
     voluntary_exits: RwLock<HashMap<u64, SignedVoluntaryExit>>,
+    /// Keys of the operations that have changed since the pool was last persisted.
+    ///
+    /// This lock must never be held whilst acquiring one of the locks above.
+    changed: Mutex<ChangedOperations>,
     _phantom: PhantomData<T>,
 }
 
@@ -43,6 +49,25 @@ pub enum OpPoolError {
     GetAttestationsTotalBalanceError(BeaconStateError),
 }
 
+/// The effect of inserting an attestation into the pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttestationObservation {
+    /// The attestation was stored as a new entry, replacing any entries whose signers it covers.
+    New,
+    /// The attestation was aggregated into the existing entries whose signers it is disjoint from.
+    Aggregated,
+    /// The signers of the attestation are a subset of those of an existing entry, so it was
+    /// dropped.
+    AlreadyKnown,
+}
+
+impl AttestationObservation {
+    /// Returns `true` if the attestation added information to the pool.
+    pub fn is_new_information(self) -> bool {
+        self != AttestationObservation::AlreadyKnown
+    }
+}
+
 impl<T: EthSpec> OperationPool<T> {
     /// Create a new operation pool.
     pub fn new() -> Self {
@@ -51,6 +76,15 @@ impl<T: EthSpec> OperationPool<T> {
 
     /// Insert an attestation into the pool, aggregating it with existing attestations if possible.
     ///
+    /// - If the signers of `attestation` are a subset of those of an existing attestation with the
+    ///   same data, it is dropped.
+    /// - Otherwise, any existing attestations whose signers are a subset of those of `attestation`
+    ///   are dropped and `attestation` is aggregated into all existing attestations that it is
+    ///   disjoint from.
+    /// - If it is not disjoint from any existing attestation, it is stored separately. Partially
+    ///   overlapping attestations cannot be aggregated, since the signatures of the common
+    ///   signers would be counted twice.
+    ///
     /// ## Note
     ///
     /// This function assumes the given `attestation` is valid.
@@ -59,42 +93,103 @@ impl<T: EthSpec> OperationPool<T> {
         attestation: Attestation<T>,
         fork: &Fork,
         spec: &ChainSpec,
-    ) -> Result<(), AttestationValidationError> {
+    ) -> Result<AttestationObservation, AttestationValidationError> {
         let id = AttestationId::from_data(&attestation.data, fork, spec);
 
         // Take a write lock on the attestations map.
         let mut attestations = self.attestations.write();
 
-        let existing_attestations = match attestations.entry(id) {
+        let existing_attestations = match attestations.entry(id.clone()) {
             hash_map::Entry::Vacant(entry) => {
                 entry.insert(vec![attestation]);
-                return Ok(());
+                self.changed.lock().attestations.insert(id);
+                return Ok(AttestationObservation::New);
             }
             hash_map::Entry::Occupied(entry) => entry.into_mut(),
         };
 
+        let is_subset = |a: &Attestation<T>, b: &Attestation<T>| {
+            a.aggregation_bits.intersection(&b.aggregation_bits) == a.aggregation_bits
+        };
+
+        if existing_attestations
+            .iter()
+            .any(|existing| is_subset(&attestation, existing))
+        {
+            return Ok(AttestationObservation::AlreadyKnown);
+        }
+
+        existing_attestations.retain(|existing| !is_subset(existing, &attestation));
+        self.changed.lock().attestations.insert(id);
+
         let mut aggregated = false;
         for existing_attestation in existing_attestations.iter_mut() {
             if existing_attestation.signers_disjoint_from(&attestation) {
                 existing_attestation.aggregate(&attestation);
                 aggregated = true;
-            } else if *existing_attestation == attestation {
-                aggregated = true;
             }
         }
 
-        if !aggregated {
+        if aggregated {
+            Ok(AttestationObservation::Aggregated)
+        } else {
             existing_attestations.push(attestation);
+            Ok(AttestationObservation::New)
         }
-
-        Ok(())
     }
 
     /// Total number of attestations in the pool, including attestations for the same data.
+    ///
+    /// This is the number of attestations returned by `Self::get_all_attestations`.
     pub fn num_attestations(&self) -> usize {
         self.attestations.read().values().map(Vec::len).sum()
     }
 
+    /// Returns all of the attestations in the pool, without removing them.
+    ///
+    /// The attestations are returned in their aggregated form, as stored in the pool. Attestations
+    /// with the same data are only returned separately if their signers overlap.
+    pub fn get_all_attestations(&self) -> Vec<Attestation<T>> {
+        self.attestations
+            .read()
+            .values()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns all of the attestations in the pool for `slot`, without removing them.
+    ///
+    /// As per `Self::get_all_attestations`, the attestations are in their aggregated form.
+    pub fn get_attestations_for_slot(&self, slot: Slot) -> Vec<Attestation<T>> {
+        self.attestations
+            .read()
+            .values()
+            .flatten()
+            .filter(|attestation| attestation.data.slot == slot)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the attestation with the most signers amongst those for committee `index` at
+    /// `slot`, if any.
+    ///
+    /// Attestations for the same committee may vote for different blocks, in which case only the
+    /// one with the greatest participation is returned.
+    pub fn get_aggregated_attestation(
+        &self,
+        slot: Slot,
+        index: CommitteeIndex,
+    ) -> Option<Attestation<T>> {
+        self.attestations
+            .read()
+            .values()
+            .flatten()
+            .filter(|attestation| attestation.data.slot == slot && attestation.data.index == index)
+            .max_by_key(|attestation| attestation.aggregation_bits.num_set_bits())
+            .cloned()
+    }
+
     /// Get a list of attestations for inclusion in a block.
     ///
     /// NOTE: Assumes that all attestations in the operation_pool are valid.
@@ -147,15 +242,49 @@ impl<T: EthSpec> OperationPool<T> {
         // state.slot <= attestation_slot + SLOTS_PER_EPOCH
         // We approximate this check using the attestation's epoch, to avoid computing
         // the slot or relying on the committee cache of the finalized state.
-        self.attestations.write().retain(|_, attestations| {
+        let mut attestations = self.attestations.write();
+        let mut changed = self.changed.lock();
+
+        attestations.retain(|id, attestations| {
             // All the attestations in this bucket have the same data, so we only need to
             // check the first one.
-            attestations.first().map_or(false, |att| {
+            let keep = attestations.first().map_or(false, |att| {
                 finalized_state.current_epoch() <= att.data.target.epoch + 1
-            })
+            });
+            if !keep {
+                changed.attestations.insert(id.clone());
+            }
+            keep
         });
     }
 
+    /// Prune attestations that can no longer be included in a block at `current_slot` or later,
+    /// since they are more than an epoch old.
+    ///
+    /// Unlike `Self::prune_attestations`, this does not require finality, so it may be run
+    /// frequently.
+    ///
+    /// Returns the number of attestations that were pruned.
+    pub fn prune_attestations_by_slot(&self, current_slot: Slot) -> usize {
+        let mut attestations = self.attestations.write();
+        let len_before = attestations.values().map(Vec::len).sum::<usize>();
+        let mut changed = self.changed.lock();
+
+        attestations.retain(|id, attestations| {
+            // All the attestations in this bucket have the same data, so we only need to
+            // check the first one.
+            let keep = attestations.first().map_or(false, |att| {
+                current_slot <= att.data.slot + T::slots_per_epoch()
+            });
+            if !keep {
+                changed.attestations.insert(id.clone());
+            }
+            keep
+        });
+
+        len_before - attestations.values().map(Vec::len).sum::<usize>()
+    }
+
     /// Insert a proposer slashing into the pool.
     pub fn insert_proposer_slashing(
         &self,
@@ -166,9 +295,13 @@ impl<T: EthSpec> OperationPool<T> {
         // TODO: should maybe insert anyway if the proposer is unknown in the validator index,
         // because they could *become* known later
         verify_proposer_slashing(&slashing, state, VerifySignatures::True, spec)?;
-        self.proposer_slashings
-            .write()
-            .insert(slashing.proposer_index, slashing);
+        let proposer_index = slashing.proposer_index;
+        let mut proposer_slashings = self.proposer_slashings.write();
+        proposer_slashings.insert(proposer_index, slashing);
+        self.changed
+            .lock()
+            .proposer_slashings
+            .insert(proposer_index);
         Ok(())
     }
 
@@ -195,7 +328,9 @@ impl<T: EthSpec> OperationPool<T> {
     ) -> Result<(), AttesterSlashingValidationError> {
         verify_attester_slashing(state, &slashing, true, VerifySignatures::True, spec)?;
         let id = Self::attester_slashing_id(&slashing, state, spec);
-        self.attester_slashings.write().insert(id, slashing);
+        let mut attester_slashings = self.attester_slashings.write();
+        attester_slashings.insert(id.clone(), slashing);
+        self.changed.lock().attester_slashings.insert(id);
         Ok(())
     }
 
@@ -261,8 +396,10 @@ impl<T: EthSpec> OperationPool<T> {
 
     /// Prune proposer slashings for all slashed or withdrawn validators.
     pub fn prune_proposer_slashings(&self, finalized_state: &BeaconState<T>) {
+        let mut proposer_slashings = self.proposer_slashings.write();
         prune_validator_hash_map(
-            &mut self.proposer_slashings.write(),
+            &mut proposer_slashings,
+            &mut self.changed.lock().proposer_slashings,
             |validator| {
                 validator.slashed || validator.is_withdrawable_at(finalized_state.current_epoch())
             },
@@ -273,7 +410,10 @@ impl<T: EthSpec> OperationPool<T> {
     /// Prune attester slashings for all slashed or withdrawn validators, or attestations on another
     /// fork.
     pub fn prune_attester_slashings(&self, finalized_state: &BeaconState<T>, spec: &ChainSpec) {
-        self.attester_slashings.write().retain(|id, slashing| {
+        let mut attester_slashings = self.attester_slashings.write();
+        let mut changed = self.changed.lock();
+
+        attester_slashings.retain(|id, slashing| {
             let fork_ok = &Self::attester_slashing_id(slashing, finalized_state, spec) == id;
             let curr_epoch = finalized_state.current_epoch();
             let slashing_ok =
@@ -281,6 +421,9 @@ impl<T: EthSpec> OperationPool<T> {
                     validator.slashed || validator.is_withdrawable_at(curr_epoch)
                 })
                 .is_ok();
+            if !(fork_ok && slashing_ok) {
+                changed.attester_slashings.insert(id.clone());
+            }
             fork_ok && slashing_ok
         });
     }
@@ -295,6 +438,16 @@ impl<T: EthSpec> OperationPool<T> {
         self.proposer_slashings.read().len()
     }
 
+    /// Returns all of the attester slashings in the pool, without removing them.
+    pub fn get_all_attester_slashings(&self) -> Vec<AttesterSlashing<T>> {
+        self.attester_slashings.read().values().cloned().collect()
+    }
+
+    /// Returns all of the proposer slashings in the pool, without removing them.
+    pub fn get_all_proposer_slashings(&self) -> Vec<ProposerSlashing> {
+        self.proposer_slashings.read().values().cloned().collect()
+    }
+
     /// Insert a voluntary exit, validating it almost-entirely (future exits are permitted).
     pub fn insert_voluntary_exit(
         &self,
@@ -303,9 +456,10 @@ impl<T: EthSpec> OperationPool<T> {
         spec: &ChainSpec,
     ) -> Result<(), ExitValidationError> {
         verify_exit_time_independent_only(state, &exit, VerifySignatures::True, spec)?;
-        self.voluntary_exits
-            .write()
-            .insert(exit.message.validator_index, exit);
+        let validator_index = exit.message.validator_index;
+        let mut voluntary_exits = self.voluntary_exits.write();
+        voluntary_exits.insert(validator_index, exit);
+        self.changed.lock().voluntary_exits.insert(validator_index);
         Ok(())
     }
 
@@ -324,13 +478,64 @@ impl<T: EthSpec> OperationPool<T> {
 
     /// Prune if validator has already exited at the last finalized state.
     pub fn prune_voluntary_exits(&self, finalized_state: &BeaconState<T>) {
+        let mut voluntary_exits = self.voluntary_exits.write();
         prune_validator_hash_map(
-            &mut self.voluntary_exits.write(),
+            &mut voluntary_exits,
+            &mut self.changed.lock().voluntary_exits,
             |validator| validator.is_exited_at(finalized_state.current_epoch()),
             finalized_state,
         );
     }
 
+    /// Prune voluntary exits for validators that have already initiated an exit in `head_state`,
+    /// since they can never be included in a block on its chain.
+    ///
+    /// Returns the number of exits that were pruned.
+    pub fn prune_voluntary_exits_for_exited(
+        &self,
+        head_state: &BeaconState<T>,
+        spec: &ChainSpec,
+    ) -> usize {
+        let mut voluntary_exits = self.voluntary_exits.write();
+        prune_validator_hash_map(
+            &mut voluntary_exits,
+            &mut self.changed.lock().voluntary_exits,
+            |validator| validator.exit_epoch != spec.far_future_epoch,
+            head_state,
+        )
+    }
+
+    /// Prune proposer slashings for validators that are already slashed in `head_state`, and
+    /// attester slashings for which all the slashable validators are already slashed.
+    ///
+    /// Returns the number of slashings that were pruned.
+    pub fn prune_slashings_for_slashed(&self, head_state: &BeaconState<T>) -> usize {
+        let mut proposer_slashings = self.proposer_slashings.write();
+        let pruned_proposer_slashings = prune_validator_hash_map(
+            &mut proposer_slashings,
+            &mut self.changed.lock().proposer_slashings,
+            |validator| validator.slashed,
+            head_state,
+        );
+        drop(proposer_slashings);
+
+        let mut attester_slashings = self.attester_slashings.write();
+        let len_before = attester_slashings.len();
+        let mut changed = self.changed.lock();
+        attester_slashings.retain(|id, slashing| {
+            let keep = get_slashable_indices_modular(head_state, slashing, |_, validator| {
+                !validator.slashed
+            })
+            .is_ok();
+            if !keep {
+                changed.attester_slashings.insert(id.clone());
+            }
+            keep
+        });
+
+        pruned_proposer_slashings + len_before - attester_slashings.len()
+    }
+
     /// Prune all types of transactions given the latest finalized state.
     pub fn prune_all(&self, finalized_state: &BeaconState<T>, spec: &ChainSpec) {
         self.prune_attestations(finalized_state);
@@ -343,6 +548,19 @@ impl<T: EthSpec> OperationPool<T> {
     pub fn num_voluntary_exits(&self) -> usize {
         self.voluntary_exits.read().len()
     }
+
+    /// Returns all of the voluntary exits in the pool, without removing them.
+    pub fn get_all_voluntary_exits(&self) -> Vec<SignedVoluntaryExit> {
+        self.voluntary_exits.read().values().cloned().collect()
+    }
+
+    /// Forget which operations have changed since the pool was last persisted.
+    ///
+    /// Should be called prior to persisting the entire pool, after which only the operations
+    /// that change subsequently need to be persisted (see `PersistedOperationPoolDelta`).
+    pub fn clear_changes(&self) {
+        *self.changed.lock() = ChangedOperations::default();
+    }
 }
 
 /// Filter up to a maximum number of operations out of an iterator.
@@ -365,22 +583,36 @@ where
 /// The keys in the map should be validator indices, which will be looked up
 /// in the state's validator registry and then passed to `prune_if`.
 /// Entries for unknown validators will be kept.
+///
+/// The keys of the removed entries are added to `changed`.
+///
+/// Returns the number of entries that were removed.
 fn prune_validator_hash_map<T, F, E: EthSpec>(
     map: &mut HashMap<u64, T>,
+    changed: &mut HashSet<u64>,
     prune_if: F,
     finalized_state: &BeaconState<E>,
-) where
+) -> usize
+where
     F: Fn(&Validator) -> bool,
 {
+    let len_before = map.len();
     map.retain(|&validator_index, _| {
-        finalized_state
+        let keep = finalized_state
             .validators
             .get(validator_index as usize)
-            .map_or(true, |validator| !prune_if(validator))
+            .map_or(true, |validator| !prune_if(validator));
+        if !keep {
+            changed.insert(validator_index);
+        }
+        keep
     });
+    len_before - map.len()
 }
 
 /// Compare two operation pools.
+///
+/// The record of which operations have changed since the last persist is ignored.
 impl<T: EthSpec + Default> PartialEq for OperationPool<T> {
     fn eq(&self, other: &Self) -> bool {
         *self.attestations.read() == *other.attestations.read()
@@ -555,6 +787,14 @@ mod release_tests {
         assert_eq!(op_pool.attestations.read().len(), committees.len());
         assert_eq!(op_pool.num_attestations(), committees.len());
 
+        let pooled_attestations = op_pool.get_all_attestations();
+        assert_eq!(pooled_attestations.len(), op_pool.num_attestations());
+        assert_eq!(
+            pooled_attestations[0].aggregation_bits.num_set_bits(),
+            spec.target_committee_size as usize,
+            "should return the aggregated attestation"
+        );
+
         // Before the min attestation inclusion delay, get_attestations shouldn't return anything.
         state.slot -= 1;
         assert_eq!(
@@ -590,6 +830,56 @@ mod release_tests {
         assert_eq!(op_pool.num_attestations(), 0);
     }
 
+    /// Deltas should contain only the attestations inserted or pruned since the previous delta.
+    #[test]
+    fn attestation_deltas() {
+        let (ref mut state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(1);
+
+        let op_pool = OperationPool::new();
+        let persisted_pool = OperationPool::new();
+
+        let slot = state.slot - 1;
+        let committees = state
+            .get_beacon_committees_at_slot(slot)
+            .unwrap()
+            .into_iter()
+            .map(BeaconCommittee::into_owned)
+            .collect::<Vec<_>>();
+
+        for bc in &committees {
+            let att = signed_attestation(
+                &bc.committee,
+                bc.index,
+                keypairs,
+                ..,
+                slot,
+                state,
+                spec,
+                None,
+            );
+            op_pool.insert_attestation(att, &state.fork, spec).unwrap();
+        }
+
+        let delta = PersistedOperationPoolDelta::from_operation_pool(&op_pool);
+        assert!(!delta.is_empty());
+        delta.apply_to_operation_pool(&persisted_pool);
+        assert_eq!(persisted_pool, op_pool);
+
+        assert!(
+            PersistedOperationPoolDelta::from_operation_pool(&op_pool).is_empty(),
+            "changes should be cleared by the previous delta"
+        );
+
+        state.slot += 2 * MainnetEthSpec::slots_per_epoch();
+        op_pool.prune_attestations_by_slot(state.slot);
+        assert_eq!(op_pool.num_attestations(), 0);
+
+        let delta = PersistedOperationPoolDelta::from_operation_pool(&op_pool);
+        assert!(!delta.is_empty());
+        delta.apply_to_operation_pool(&persisted_pool);
+        assert_eq!(persisted_pool, op_pool);
+    }
+
     /// Adding an attestation already in the pool should not increase the size of the pool.
     #[test]
     fn attestation_duplicate() {
@@ -625,6 +915,70 @@ mod release_tests {
         assert_eq!(op_pool.num_attestations(), committees.len());
     }
 
+    /// Inserting an attestation should report whether it added information to the pool.
+    #[test]
+    fn attestation_insert_observations() {
+        let (ref state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(1);
+
+        let op_pool = OperationPool::new();
+
+        let slot = state.slot - 1;
+        let committees = state
+            .get_beacon_committees_at_slot(slot)
+            .unwrap()
+            .into_iter()
+            .map(BeaconCommittee::into_owned)
+            .collect::<Vec<_>>();
+        let bc = &committees[0];
+
+        let insert = |signing_range: std::ops::Range<usize>| {
+            let att = signed_attestation(
+                &bc.committee,
+                bc.index,
+                keypairs,
+                signing_range,
+                slot,
+                state,
+                spec,
+                None,
+            );
+            op_pool.insert_attestation(att, &state.fork, spec).unwrap()
+        };
+        let set_bits = || {
+            let mut bits = op_pool
+                .get_all_attestations()
+                .iter()
+                .map(|att| att.aggregation_bits.num_set_bits())
+                .collect::<Vec<_>>();
+            bits.sort();
+            bits
+        };
+
+        assert_eq!(insert(0..4), AttestationObservation::New);
+        assert_eq!(set_bits(), vec![4]);
+
+        // Subset.
+        assert_eq!(insert(1..3), AttestationObservation::AlreadyKnown);
+        assert_eq!(set_bits(), vec![4]);
+
+        // Superset, which replaces the existing attestation.
+        assert_eq!(insert(0..6), AttestationObservation::New);
+        assert_eq!(set_bits(), vec![6]);
+
+        // Disjoint.
+        assert_eq!(insert(6..8), AttestationObservation::Aggregated);
+        assert_eq!(set_bits(), vec![8]);
+
+        // Overlapping, which cannot be aggregated.
+        assert_eq!(insert(7..10), AttestationObservation::New);
+        assert_eq!(set_bits(), vec![3, 8]);
+
+        assert_eq!(insert(7..10), AttestationObservation::AlreadyKnown);
+        assert_eq!(set_bits(), vec![3, 8]);
+        assert!(!AttestationObservation::AlreadyKnown.is_new_information());
+        assert!(AttestationObservation::Aggregated.is_new_information());
+    }
+
     /// Adding lots of attestations that only intersect pairwise should lead to two aggregate
     /// attestations.
     #[test]
@@ -666,6 +1020,59 @@ mod release_tests {
         assert_eq!(op_pool.num_attestations(), 2 * committees.len());
     }
 
+    /// The aggregate for a committee should be the attestation with the most signers.
+    #[test]
+    fn attestation_get_aggregated() {
+        let (ref mut state, ref keypairs, ref spec) = attestation_test_state::<MainnetEthSpec>(1);
+
+        let op_pool = OperationPool::new();
+
+        let slot = state.slot - 1;
+        let committees = state
+            .get_beacon_committees_at_slot(slot)
+            .unwrap()
+            .into_iter()
+            .map(BeaconCommittee::into_owned)
+            .collect::<Vec<_>>();
+
+        let step_size = 2;
+        for bc in &committees {
+            assert_eq!(op_pool.get_aggregated_attestation(slot, bc.index), None);
+
+            // The overlapping attestations aggregate into two distinct attestations.
+            for i in (0..bc.committee.len() - step_size).step_by(step_size) {
+                let att = signed_attestation(
+                    &bc.committee,
+                    bc.index,
+                    keypairs,
+                    i..i + 2 * step_size,
+                    slot,
+                    state,
+                    spec,
+                    None,
+                );
+                op_pool.insert_attestation(att, &state.fork, spec).unwrap();
+            }
+
+            let best = op_pool
+                .attestations
+                .read()
+                .values()
+                .flatten()
+                .filter(|att| att.data.index == bc.index)
+                .map(|att| att.aggregation_bits.num_set_bits())
+                .max();
+
+            assert_eq!(
+                op_pool
+                    .get_aggregated_attestation(slot, bc.index)
+                    .map(|att| att.aggregation_bits.num_set_bits()),
+                best
+            );
+            assert_eq!(op_pool.get_aggregated_attestation(slot + 1, bc.index), None);
+        }
+    }
+
     /// Create a bunch of attestations signed by a small number of validators, and another
     /// bunch signed by a larger number, such that there are at least `max_attestations`
     /// signed by the larger number. Then, check that `get_attestations` only returns the
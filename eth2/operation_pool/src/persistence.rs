@@ -4,9 +4,21 @@ use parking_lot::RwLock;
 use serde_derive::{Deserialize, Serialize};
 use ssz::{Decode, Encode};
 use ssz_derive::{Decode, Encode};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use store::{DBColumn, Error as StoreError, SimpleStoreItem};
 use types::*;
 
+/// The keys of the operations in an `OperationPool` that have been inserted, modified or removed
+/// since the pool was last persisted.
+#[derive(Debug, Default)]
+pub(crate) struct ChangedOperations {
+    pub attestations: HashSet<AttestationId>,
+    pub attester_slashings: HashSet<(AttestationId, AttestationId)>,
+    pub proposer_slashings: HashSet<u64>,
+    pub voluntary_exits: HashSet<u64>,
+}
+
 /// SSZ-serializable version of `OperationPool`.
 ///
 /// Operations are stored in arbitrary order, so it's not a good idea to compare instances
@@ -97,6 +109,7 @@ impl<T: EthSpec> PersistedOperationPool<T> {
             attester_slashings,
             proposer_slashings,
             voluntary_exits,
+            changed: Default::default(),
             _phantom: Default::default(),
         }
     }
@@ -115,3 +128,141 @@ impl<T: EthSpec> SimpleStoreItem for PersistedOperationPool<T> {
         Self::from_ssz_bytes(bytes).map_err(Into::into)
     }
 }
+
+/// SSZ-serializable record of the changes to an `OperationPool` since it was last persisted.
+///
+/// Each changed operation is stored with its value at the time the delta was created, or listed
+/// by key if it was removed. A pool may be recovered by applying a sequence of deltas, in the order
+/// in which they were created, to the `PersistedOperationPool` that preceded them.
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub struct PersistedOperationPoolDelta<T: EthSpec> {
+    attestations: Vec<(AttestationId, Vec<Attestation<T>>)>,
+    removed_attestations: Vec<AttestationId>,
+    attester_slashings: Vec<((AttestationId, AttestationId), AttesterSlashing<T>)>,
+    removed_attester_slashings: Vec<(AttestationId, AttestationId)>,
+    proposer_slashings: Vec<(u64, ProposerSlashing)>,
+    removed_proposer_slashings: Vec<u64>,
+    voluntary_exits: Vec<(u64, SignedVoluntaryExit)>,
+    removed_voluntary_exits: Vec<u64>,
+}
+
+impl<T: EthSpec> PersistedOperationPoolDelta<T> {
+    /// Create a delta containing the changes to `operation_pool` since it was last persisted.
+    ///
+    /// The changes are cleared from `operation_pool`, so a subsequent delta will only contain the
+    /// operations that change after this one is created.
+    pub fn from_operation_pool(operation_pool: &OperationPool<T>) -> Self {
+        // Take the changes before reading the maps (and release the lock), so that a concurrent
+        // change is either included in this delta or recorded for the next.
+        let changed = std::mem::replace(
+            &mut *operation_pool.changed.lock(),
+            ChangedOperations::default(),
+        );
+
+        let (attestations, removed_attestations) =
+            split_changes(&operation_pool.attestations.read(), changed.attestations);
+        let (attester_slashings, removed_attester_slashings) = split_changes(
+            &operation_pool.attester_slashings.read(),
+            changed.attester_slashings,
+        );
+        let (proposer_slashings, removed_proposer_slashings) = split_changes(
+            &operation_pool.proposer_slashings.read(),
+            changed.proposer_slashings,
+        );
+        let (voluntary_exits, removed_voluntary_exits) = split_changes(
+            &operation_pool.voluntary_exits.read(),
+            changed.voluntary_exits,
+        );
+
+        Self {
+            attestations,
+            removed_attestations,
+            attester_slashings,
+            removed_attester_slashings,
+            proposer_slashings,
+            removed_proposer_slashings,
+            voluntary_exits,
+            removed_voluntary_exits,
+        }
+    }
+
+    /// Returns `true` if no operations changed.
+    pub fn is_empty(&self) -> bool {
+        self.attestations.is_empty()
+            && self.removed_attestations.is_empty()
+            && self.attester_slashings.is_empty()
+            && self.removed_attester_slashings.is_empty()
+            && self.proposer_slashings.is_empty()
+            && self.removed_proposer_slashings.is_empty()
+            && self.voluntary_exits.is_empty()
+            && self.removed_voluntary_exits.is_empty()
+    }
+
+    /// Apply the changes to `operation_pool`.
+    ///
+    /// The changes are not recorded as changes to `operation_pool`, since they have already been
+    /// persisted.
+    pub fn apply_to_operation_pool(self, operation_pool: &OperationPool<T>) {
+        apply_changes(
+            &mut operation_pool.attestations.write(),
+            self.attestations,
+            self.removed_attestations,
+        );
+        apply_changes(
+            &mut operation_pool.attester_slashings.write(),
+            self.attester_slashings,
+            self.removed_attester_slashings,
+        );
+        apply_changes(
+            &mut operation_pool.proposer_slashings.write(),
+            self.proposer_slashings,
+            self.removed_proposer_slashings,
+        );
+        apply_changes(
+            &mut operation_pool.voluntary_exits.write(),
+            self.voluntary_exits,
+            self.removed_voluntary_exits,
+        );
+    }
+}
+
+/// Split the `changed` keys of `map` into the entries that are present in `map`, and the keys of
+/// those that were removed.
+fn split_changes<K: Eq + Hash, V: Clone>(
+    map: &HashMap<K, V>,
+    changed: HashSet<K>,
+) -> (Vec<(K, V)>, Vec<K>) {
+    let mut present = vec![];
+    let mut removed = vec![];
+
+    for key in changed {
+        match map.get(&key) {
+            Some(value) => present.push((key, value.clone())),
+            None => removed.push(key),
+        }
+    }
+
+    (present, removed)
+}
+
+/// Insert the `present` entries into `map` and remove the `removed` keys from it.
+fn apply_changes<K: Eq + Hash, V>(map: &mut HashMap<K, V>, present: Vec<(K, V)>, removed: Vec<K>) {
+    for key in removed {
+        map.remove(&key);
+    }
+    map.extend(present);
+}
+
+impl<T: EthSpec> SimpleStoreItem for PersistedOperationPoolDelta<T> {
+    fn db_column() -> DBColumn {
+        DBColumn::OpPoolDeltas
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Self::from_ssz_bytes(bytes).map_err(Into::into)
+    }
+}
@@ -14,6 +14,14 @@ pub enum Error {
     InvalidNodeDelta(usize),
     DeltaOverflow(usize),
     IndexOverflow(&'static str),
+    InvalidIndicesLen {
+        indices: usize,
+        nodes: usize,
+    },
+    InvalidIndex {
+        root: Hash256,
+        index: usize,
+    },
     InvalidDeltaLen {
         deltas: usize,
         indices: usize,
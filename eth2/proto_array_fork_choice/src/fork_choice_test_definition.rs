@@ -157,6 +157,9 @@ impl ForkChoiceTestDefinition {
                     fork_choice
                         .maybe_prune(finalized_root)
                         .expect("update_finalized_root op at index {} returned error");
+                    fork_choice
+                        .verify_invariants()
+                        .expect("invariants should hold after pruning");
 
                     // Ensure that no pruning happened.
                     assert_eq!(
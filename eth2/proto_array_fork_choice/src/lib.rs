@@ -4,6 +4,7 @@ mod proto_array;
 mod proto_array_fork_choice;
 mod ssz_container;
 
+pub use crate::proto_array::ForkChoiceNode;
 pub use crate::proto_array_fork_choice::ProtoArrayForkChoice;
 pub use error::Error;
 
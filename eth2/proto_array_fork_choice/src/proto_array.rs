@@ -21,6 +21,24 @@ pub struct ProtoNode {
     best_descendant: Option<usize>,
 }
 
+/// A summary of a `ProtoNode`, with indices resolved to block roots.
+///
+/// Intended for inspecting the state of fork choice (e.g., via the HTTP API), not for fork choice
+/// itself.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ForkChoiceNode {
+    pub root: Hash256,
+    /// The root of the parent, if the parent has not been pruned.
+    pub parent_root: Option<Hash256>,
+    pub slot: Slot,
+    pub justified_epoch: Epoch,
+    pub finalized_epoch: Epoch,
+    /// The total balance of the validators that vote for this block or any of its descendants,
+    /// as of the last run of fork choice.
+    pub weight: u64,
+    pub best_descendant_root: Option<Hash256>,
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize)]
 pub struct ProtoArray {
     /// Do not attempt to prune the tree unless it has at least this many nodes. Small prunes
@@ -242,6 +260,7 @@ impl ProtoArray {
 
         // Drop all the nodes prior to finalization.
         self.nodes = self.nodes.split_off(finalized_index);
+        self.indices.shrink_to_fit();
 
         // Adjust the indices map.
         for (_root, index) in self.indices.iter_mut() {
@@ -276,6 +295,72 @@ impl ProtoArray {
         Ok(())
     }
 
+    /// Checks the structural invariants of `self`, returning the first violation found.
+    ///
+    /// - There is exactly one entry in `self.indices` for each node, pointing at that node.
+    /// - The parent of each node precedes it, whilst its best-child and best-descendant follow it.
+    pub fn verify_invariants(&self) -> Result<(), Error> {
+        if self.indices.len() != self.nodes.len() {
+            return Err(Error::InvalidIndicesLen {
+                indices: self.indices.len(),
+                nodes: self.nodes.len(),
+            });
+        }
+
+        for (root, index) in self.indices.iter() {
+            if self.nodes.get(*index).map(|node| node.root) != Some(*root) {
+                return Err(Error::InvalidIndex {
+                    root: *root,
+                    index: *index,
+                });
+            }
+        }
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.parent.map_or(false, |parent| parent >= index) {
+                return Err(Error::InvalidParentIndex(index));
+            }
+            if node.best_child.map_or(false, |best_child| {
+                best_child <= index || best_child >= self.nodes.len()
+            }) {
+                return Err(Error::InvalidBestChildIndex(index));
+            }
+            if node.best_descendant.map_or(false, |best_descendant| {
+                best_descendant <= index || best_descendant >= self.nodes.len()
+            }) {
+                return Err(Error::InvalidBestDescendant(index));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the weight of the block with `block_root`, if it is known.
+    pub fn block_weight(&self, block_root: &Hash256) -> Option<u64> {
+        let i = self.indices.get(block_root)?;
+        self.nodes.get(*i).map(|node| node.weight)
+    }
+
+    /// Returns a summary of every node, in the order they were inserted (i.e., parents before
+    /// children).
+    pub fn fork_choice_nodes(&self) -> Vec<ForkChoiceNode> {
+        let root_at =
+            |index: Option<usize>| index.and_then(|i| self.nodes.get(i)).map(|node| node.root);
+
+        self.nodes
+            .iter()
+            .map(|node| ForkChoiceNode {
+                root: node.root,
+                parent_root: root_at(node.parent),
+                slot: node.slot,
+                justified_epoch: node.justified_epoch,
+                finalized_epoch: node.finalized_epoch,
+                weight: node.weight,
+                best_descendant_root: root_at(node.best_descendant),
+            })
+            .collect()
+    }
+
     /// Observe the parent at `parent_index` with respect to the child at `child_index` and
     /// potentially modify the `parent.best_child` and `parent.best_descendant` values.
     ///
@@ -1,5 +1,5 @@
 use crate::error::Error;
-use crate::proto_array::ProtoArray;
+use crate::proto_array::{ForkChoiceNode, ProtoArray};
 use crate::ssz_container::SszContainer;
 use parking_lot::{RwLock, RwLockReadGuard};
 use ssz::{Decode, Encode};
@@ -162,11 +162,39 @@ impl ProtoArrayForkChoice {
             .map_err(|e| format!("find_head failed: {:?}", e))
     }
 
+    /// Prunes all nodes prior to the node with `finalized_root`, if there are at least
+    /// `prune_threshold` of them.
+    ///
+    /// The votes of validators which only reference pruned nodes are forgotten, so that no vote
+    /// refers to a block which is no longer known.
     pub fn maybe_prune(&self, finalized_root: Hash256) -> Result<(), String> {
-        self.proto_array
-            .write()
+        let mut proto_array = self.proto_array.write();
+
+        let len_before = proto_array.nodes.len();
+        proto_array
             .maybe_prune(finalized_root)
-            .map_err(|e| format!("find_head maybe_prune failed: {:?}", e))
+            .map_err(|e| format!("find_head maybe_prune failed: {:?}", e))?;
+
+        if proto_array.nodes.len() < len_before {
+            let indices = &proto_array.indices;
+            for vote in self.votes.write().iter_mut() {
+                if !indices.contains_key(&vote.current_root)
+                    && !indices.contains_key(&vote.next_root)
+                {
+                    *vote = VoteTracker::default();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the structural invariants of the underlying `ProtoArray`.
+    pub fn verify_invariants(&self) -> Result<(), String> {
+        self.proto_array
+            .read()
+            .verify_invariants()
+            .map_err(|e| format!("ProtoArray invariant violated: {:?}", e))
     }
 
     pub fn set_prune_threshold(&self, prune_threshold: usize) {
@@ -199,6 +227,19 @@ impl ProtoArrayForkChoice {
         Some((block.slot, block.state_root))
     }
 
+    /// Returns the weight of the block with `block_root`, if it is known.
+    pub fn block_weight(&self, block_root: &Hash256) -> Option<u64> {
+        self.proto_array.read().block_weight(block_root)
+    }
+
+    /// Returns a summary of every block known to fork choice, with parents before children.
+    ///
+    /// The summary is copied whilst holding the lock, so it may be serialized without blocking
+    /// fork choice.
+    pub fn fork_choice_nodes(&self) -> Vec<ForkChoiceNode> {
+        self.proto_array.read().fork_choice_nodes()
+    }
+
     pub fn latest_message(&self, validator_index: usize) -> Option<(Hash256, Epoch)> {
         let votes = self.votes.read();
 
@@ -215,6 +256,30 @@ impl ProtoArrayForkChoice {
         }
     }
 
+    /// Returns the latest messages of the validators with indices in `start..start + count`, as
+    /// `(validator_index, block_root, target_epoch)`.
+    ///
+    /// Validators without a latest message are omitted. Only the requested range is copied, so the
+    /// table may be paged through without duplicating it in memory.
+    pub fn latest_messages(&self, start: usize, count: usize) -> Vec<(usize, Hash256, Epoch)> {
+        self.votes
+            .read()
+            .0
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(count)
+            .filter(|(_, vote)| **vote != VoteTracker::default())
+            .map(|(validator_index, vote)| (validator_index, vote.next_root, vote.next_epoch))
+            .collect()
+    }
+
+    /// Returns the number of validators for which space has been allocated in the latest messages
+    /// table (i.e., one more than the highest validator index that has ever voted).
+    pub fn latest_messages_len(&self) -> usize {
+        self.votes.read().0.len()
+    }
+
     pub fn as_bytes(&self) -> Vec<u8> {
         SszContainer::from(self).as_ssz_bytes()
     }
@@ -708,3 +773,93 @@ mod test_compute_deltas {
         }
     }
 }
+
+#[cfg(test)]
+mod test_prune {
+    use super::*;
+
+    /// Gives a hash that is not the zero hash (unless i is `usize::max_value)`.
+    fn hash_from_index(i: usize) -> Hash256 {
+        Hash256::from_low_u64_be(i as u64 + 1)
+    }
+
+    /// Returns a fork choice containing a chain of `len` blocks, where each block is at the slot
+    /// of its index and the first block is finalized.
+    fn linear_chain(len: usize) -> ProtoArrayForkChoice {
+        let fork_choice = ProtoArrayForkChoice::new(
+            Slot::new(0),
+            Hash256::zero(),
+            Epoch::new(0),
+            Epoch::new(0),
+            hash_from_index(0),
+        )
+        .expect("should create fork choice");
+
+        for i in 1..len {
+            fork_choice
+                .process_block(
+                    Slot::new(i as u64),
+                    hash_from_index(i),
+                    hash_from_index(i - 1),
+                    Hash256::zero(),
+                    Epoch::new(0),
+                    Epoch::new(0),
+                )
+                .expect("should process block");
+        }
+
+        fork_choice
+    }
+
+    #[test]
+    fn prune_forgets_pruned_votes() {
+        let balances = vec![1, 1];
+        let fork_choice = linear_chain(8);
+
+        fork_choice
+            .process_attestation(0, hash_from_index(1), Epoch::new(1))
+            .expect("should process attestation");
+        fork_choice
+            .process_attestation(1, hash_from_index(6), Epoch::new(1))
+            .expect("should process attestation");
+        fork_choice
+            .find_head(Epoch::new(0), hash_from_index(0), Epoch::new(0), &balances)
+            .expect("should find head");
+
+        let encoded_len = fork_choice.as_bytes().len();
+
+        fork_choice.set_prune_threshold(0);
+        fork_choice
+            .maybe_prune(hash_from_index(4))
+            .expect("should prune");
+
+        assert_eq!(
+            fork_choice.len(),
+            4,
+            "should drop the pre-finalization nodes"
+        );
+        assert!(
+            fork_choice.as_bytes().len() < encoded_len,
+            "should shrink the encoded fork choice"
+        );
+        assert_eq!(
+            fork_choice.latest_message(0),
+            None,
+            "should forget a vote for a pruned block"
+        );
+        assert_eq!(
+            fork_choice.latest_message(1),
+            Some((hash_from_index(6), Epoch::new(1))),
+            "should keep a vote for a retained block"
+        );
+        fork_choice
+            .verify_invariants()
+            .expect("invariants should hold after pruning");
+
+        assert_eq!(
+            fork_choice.find_head(Epoch::new(0), hash_from_index(4), Epoch::new(0), &balances),
+            Ok(hash_from_index(7)),
+            "should find the head after pruning"
+        );
+    }
+}
@@ -41,6 +41,9 @@ pub enum BlockSignatureStrategy {
     VerifyIndividual,
     /// Verify all signatures in bulk at the beginning of block processing.
     VerifyBulk,
+    /// Verify all signatures in bulk at the beginning of block processing, except the block
+    /// proposal signature. Only use this if the proposal signature has already been verified.
+    VerifyBulkExceptProposal,
 }
 
 /// The strategy to be used when validating the block's signatures.
@@ -89,6 +92,18 @@ pub fn per_block_processing<T: EthSpec>(
             );
             VerifySignatures::False
         }
+        BlockSignatureStrategy::VerifyBulkExceptProposal => {
+            block_verify!(
+                BlockSignatureVerifier::verify_entire_block_except_proposal(
+                    state,
+                    signed_block,
+                    spec
+                )
+                .is_ok(),
+                BlockProcessingError::BulkSignatureVerificationFailed
+            );
+            VerifySignatures::False
+        }
         BlockSignatureStrategy::VerifyIndividual => VerifySignatures::True,
         BlockSignatureStrategy::NoVerification => VerifySignatures::False,
     };
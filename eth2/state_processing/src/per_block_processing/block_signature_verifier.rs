@@ -85,16 +85,43 @@ impl<'a, T: EthSpec> BlockSignatureVerifier<'a, T> {
         let mut verifier = Self::new(state, block, spec);
 
         verifier.include_block_proposal(block_root)?;
-        verifier.include_randao_reveal()?;
-        verifier.include_proposer_slashings()?;
-        verifier.include_attester_slashings()?;
-        verifier.include_attestations()?;
+        verifier.include_all_signatures_except_proposal()?;
+
+        verifier.verify()
+    }
+
+    /// Verify all* the signatures in the given `SignedBeaconBlock` except the block proposal
+    /// signature, returning `Ok(())` if the signatures are valid.
+    ///
+    /// This is useful when the proposal signature has already been verified (e.g., during gossip
+    /// validation).
+    ///
+    /// * : _Does not verify any signatures in `block.body.deposits`._
+    pub fn verify_entire_block_except_proposal(
+        state: &'a BeaconState<T>,
+        block: &'a SignedBeaconBlock<T>,
+        spec: &'a ChainSpec,
+    ) -> Result<()> {
+        let mut verifier = Self::new(state, block, spec);
+
+        verifier.include_all_signatures_except_proposal()?;
+
+        verifier.verify()
+    }
+
+    /// Includes all signatures in `self.block` for verification, except the block proposal
+    /// signature and the deposit signatures.
+    fn include_all_signatures_except_proposal(&mut self) -> Result<()> {
+        self.include_randao_reveal()?;
+        self.include_proposer_slashings()?;
+        self.include_attester_slashings()?;
+        self.include_attestations()?;
         /*
          * Deposits are not included because they can legally have invalid signatures.
          */
-        verifier.include_exits()?;
+        self.include_exits()?;
 
-        verifier.verify()
+        Ok(())
     }
 
     /// Verify all* the signatures that have been included in `self`, returning `Ok(())` if the
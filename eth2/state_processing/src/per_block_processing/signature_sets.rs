@@ -7,10 +7,10 @@ use std::borrow::Cow;
 use std::convert::TryInto;
 use tree_hash::TreeHash;
 use types::{
-    AggregateSignature, AttesterSlashing, BeaconBlock, BeaconState, BeaconStateError, ChainSpec,
-    DepositData, Domain, EthSpec, Fork, Hash256, IndexedAttestation, ProposerSlashing, PublicKey,
-    Signature, SignedBeaconBlock, SignedBeaconBlockHeader, SignedRoot, SignedVoluntaryExit,
-    SigningRoot,
+    AggregateSignature, AttestationData, AttesterSlashing, BeaconBlock, BeaconState,
+    BeaconStateError, ChainSpec, DepositData, Domain, EthSpec, Fork, Hash256, IndexedAttestation,
+    ProposerSlashing, PublicKey, Signature, SignedBeaconBlock, SignedBeaconBlockHeader, SignedRoot,
+    SignedVoluntaryExit, SigningRoot,
 };
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -39,23 +39,23 @@ impl From<BeaconStateError> for Error {
     }
 }
 
-/// A signature set that is valid if a block was signed by the expected block producer.
-pub fn block_proposal_signature_set<'a, T: EthSpec>(
-    state: &'a BeaconState<T>,
-    signed_block: &'a SignedBeaconBlock<T>,
+/// Returns the root that is signed by the proposer of `block` during the given `fork`.
+///
+/// The fork version is that of the epoch of the block's slot. The `block_root` is used, if known,
+/// rather than re-computing the root of `block`.
+pub fn block_signing_root<T: EthSpec>(
+    block: &BeaconBlock<T>,
     block_root: Option<Hash256>,
-    spec: &'a ChainSpec,
-) -> Result<SignatureSet<'a>> {
-    let block = &signed_block.message;
-    let proposer_index = state.get_beacon_proposer_index(block.slot, spec)?;
-
+    fork: &Fork,
+    spec: &ChainSpec,
+) -> Hash256 {
     let domain = spec.get_domain(
         block.slot.epoch(T::slots_per_epoch()),
         Domain::BeaconProposer,
-        &state.fork,
+        fork,
     );
 
-    let message = if let Some(root) = block_root {
+    if let Some(root) = block_root {
         SigningRoot {
             object_root: root,
             domain,
@@ -63,7 +63,20 @@ pub fn block_proposal_signature_set<'a, T: EthSpec>(
         .tree_hash_root()
     } else {
         block.signing_root(domain)
-    };
+    }
+}
+
+/// A signature set that is valid if a block was signed by the expected block producer.
+pub fn block_proposal_signature_set<'a, T: EthSpec>(
+    state: &'a BeaconState<T>,
+    signed_block: &'a SignedBeaconBlock<T>,
+    block_root: Option<Hash256>,
+    spec: &'a ChainSpec,
+) -> Result<SignatureSet<'a>> {
+    let block = &signed_block.message;
+    let proposer_index = state.get_beacon_proposer_index(block.slot, spec)?;
+
+    let message = block_signing_root(block, block_root, &state.fork, spec);
 
     Ok(SignatureSet::single(
         &signed_block.signature,
@@ -145,6 +158,13 @@ fn block_header_signature_set<'a, T: EthSpec>(
     ))
 }
 
+/// Returns the root that is signed by the attesters of `data` during the given `fork`.
+pub fn attestation_signing_root(data: &AttestationData, fork: &Fork, spec: &ChainSpec) -> Hash256 {
+    let domain = spec.get_domain(data.target.epoch, Domain::BeaconAttester, fork);
+
+    data.signing_root(domain)
+}
+
 /// Returns the signature set for the given `indexed_attestation`.
 pub fn indexed_attestation_signature_set<'a, 'b, T: EthSpec>(
     state: &'a BeaconState<T>,
@@ -158,13 +178,7 @@ pub fn indexed_attestation_signature_set<'a, 'b, T: EthSpec>(
         .map(|&validator_idx| Ok(validator_pubkey(state, validator_idx as usize)?))
         .collect::<Result<_>>()?;
 
-    let domain = spec.get_domain(
-        indexed_attestation.data.target.epoch,
-        Domain::BeaconAttester,
-        &state.fork,
-    );
-
-    let message = indexed_attestation.data.signing_root(domain);
+    let message = attestation_signing_root(&indexed_attestation.data, &state.fork, spec);
     let signed_message = SignedMessage::new(pubkeys, message.as_bytes().to_vec());
 
     Ok(SignatureSet::new(signature, vec![signed_message]))
@@ -184,13 +198,7 @@ pub fn indexed_attestation_signature_set_from_pubkeys<'a, 'b, T: EthSpec>(
         .map(|pubkey| Cow::Borrowed(&pubkey.as_raw().point))
         .collect();
 
-    let domain = spec.get_domain(
-        indexed_attestation.data.target.epoch,
-        Domain::BeaconAttester,
-        &fork,
-    );
-
-    let message = indexed_attestation.data.signing_root(domain);
+    let message = attestation_signing_root(&indexed_attestation.data, fork, spec);
     let signed_message = SignedMessage::new(pubkeys, message.as_bytes().to_vec());
 
     Ok(SignatureSet::new(signature, vec![signed_message]))
@@ -0,0 +1,70 @@
+use crate::{
+    test_utils::TestRandom, Attestation, ChainSpec, Domain, EthSpec, Fork, PublicKey, SecretKey,
+    SelectionProof, Signature, SignedAggregateAndProof, SignedRoot,
+};
+
+use serde_derive::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use test_random_derive::TestRandom;
+use tree_hash_derive::TreeHash;
+
+/// An aggregate attestation, along with the proof that its aggregator was selected to aggregate
+/// for the attestation's committee.
+///
+/// Spec v0.11.1
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Encode, Decode, TreeHash, TestRandom)]
+#[serde(bound = "T: EthSpec")]
+pub struct AggregateAndProof<T: EthSpec> {
+    /// The index of the validator that created the aggregate.
+    pub aggregator_index: u64,
+    pub aggregate: Attestation<T>,
+    /// A signature of `aggregate.data.slot` by the aggregator.
+    pub selection_proof: Signature,
+}
+
+impl<T: EthSpec> SignedRoot for AggregateAndProof<T> {}
+
+impl<T: EthSpec> AggregateAndProof<T> {
+    pub fn sign(
+        self,
+        secret_key: &SecretKey,
+        fork: &Fork,
+        spec: &ChainSpec,
+    ) -> SignedAggregateAndProof<T> {
+        let domain = spec.get_domain(
+            self.aggregate.data.target.epoch,
+            Domain::AggregateAndProof,
+            fork,
+        );
+        let message = self.signing_root(domain);
+        let signature = Signature::new(message.as_bytes(), secret_key);
+        SignedAggregateAndProof {
+            message: self,
+            signature,
+        }
+    }
+
+    /// Returns `true` if `self.selection_proof` is a signature of the aggregate's slot by
+    /// `aggregator_pubkey`.
+    pub fn is_valid_selection_proof(
+        &self,
+        aggregator_pubkey: &PublicKey,
+        fork: &Fork,
+        spec: &ChainSpec,
+    ) -> bool {
+        SelectionProof::from(self.selection_proof.clone()).verify::<T>(
+            self.aggregate.data.slot,
+            aggregator_pubkey,
+            fork,
+            spec,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MainnetEthSpec;
+
+    ssz_and_tree_hash_tests!(AggregateAndProof<MainnetEthSpec>);
+}
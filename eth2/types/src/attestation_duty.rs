@@ -11,4 +11,9 @@ pub struct AttestationDuty {
     pub committee_position: usize,
     /// The total number of attesters in the committee.
     pub committee_len: usize,
+    /// The total number of committees in `slot`.
+    ///
+    /// Required to compute the attestation subnet and for aggregator selection.
+    #[serde(default)]
+    pub committees_at_slot: u64,
 }
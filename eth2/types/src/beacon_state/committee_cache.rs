@@ -183,6 +183,7 @@ impl CommitteeCache {
                     index,
                     committee_position,
                     committee_len,
+                    committees_at_slot: self.committees_per_slot,
                 })
             })
     }
@@ -281,6 +281,10 @@ mod committees {
                     assert_eq!(attestation_duty.index, bc.index);
                     assert_eq!(attestation_duty.committee_position, committee_i);
                     assert_eq!(attestation_duty.committee_len, bc.committee.len());
+                    assert_eq!(
+                        attestation_duty.committees_at_slot,
+                        beacon_committees.len() as u64
+                    );
                 }
             }
         }
@@ -17,6 +17,8 @@ pub enum Domain {
     Randao,
     Deposit,
     VoluntaryExit,
+    SelectionProof,
+    AggregateAndProof,
 }
 
 /// Holds all the "constants" for a BeaconChain.
@@ -93,12 +95,19 @@ pub struct ChainSpec {
     domain_randao: u32,
     domain_deposit: u32,
     domain_voluntary_exit: u32,
+    domain_selection_proof: u32,
+    domain_aggregate_and_proof: u32,
 
     /*
      * Fork choice
      */
     pub safe_slots_to_update_justified: u64,
 
+    /*
+     * Validator
+     */
+    pub target_aggregators_per_committee: u64,
+
     /*
      * Eth1
      */
@@ -120,6 +129,8 @@ impl ChainSpec {
             Domain::Randao => self.domain_randao,
             Domain::Deposit => self.domain_deposit,
             Domain::VoluntaryExit => self.domain_voluntary_exit,
+            Domain::SelectionProof => self.domain_selection_proof,
+            Domain::AggregateAndProof => self.domain_aggregate_and_proof,
         }
     }
 
@@ -223,12 +234,19 @@ impl ChainSpec {
             domain_randao: 2,
             domain_deposit: 3,
             domain_voluntary_exit: 4,
+            domain_selection_proof: 5,
+            domain_aggregate_and_proof: 6,
 
             /*
              * Fork choice
              */
             safe_slots_to_update_justified: 8,
 
+            /*
+             * Validator
+             */
+            target_aggregators_per_committee: 16,
+
             /*
              * Eth1
              */
@@ -323,6 +341,12 @@ mod tests {
         test_domain(Domain::Randao, spec.domain_randao, &spec);
         test_domain(Domain::Deposit, spec.domain_deposit, &spec);
         test_domain(Domain::VoluntaryExit, spec.domain_voluntary_exit, &spec);
+        test_domain(Domain::SelectionProof, spec.domain_selection_proof, &spec);
+        test_domain(
+            Domain::AggregateAndProof,
+            spec.domain_aggregate_and_proof,
+            &spec,
+        );
     }
 }
 
@@ -0,0 +1,115 @@
+use crate::{ChainSpec, Domain, EthSpec, Fork, PublicKey, SecretKey, Signature, SignedRoot, Slot};
+use eth2_hashing::hash;
+use std::cmp;
+use std::convert::TryInto;
+
+/// A signature of a slot, which determines whether or not the signer is an aggregator for their
+/// committee at that slot.
+///
+/// Spec v0.11.1
+#[derive(Debug, PartialEq, Clone)]
+pub struct SelectionProof(Signature);
+
+impl SelectionProof {
+    pub fn new<T: EthSpec>(
+        slot: Slot,
+        secret_key: &SecretKey,
+        fork: &Fork,
+        spec: &ChainSpec,
+    ) -> Self {
+        let domain = spec.get_domain(
+            slot.epoch(T::slots_per_epoch()),
+            Domain::SelectionProof,
+            fork,
+        );
+        let message = slot.signing_root(domain);
+
+        Self(Signature::new(message.as_bytes(), secret_key))
+    }
+
+    /// Returns `true` if `self` selects its signer to aggregate for a committee of
+    /// `committee_len` validators.
+    ///
+    /// Spec v0.11.1
+    pub fn is_aggregator(&self, committee_len: usize, spec: &ChainSpec) -> bool {
+        let modulo = cmp::max(
+            1,
+            committee_len as u64 / spec.target_aggregators_per_committee,
+        );
+        let signature_hash = hash(&self.0.as_bytes());
+        let signature_hash_int = u64::from_le_bytes(
+            signature_hash[0..8]
+                .as_ref()
+                .try_into()
+                .expect("first 8 bytes of signature should always convert to fixed array"),
+        );
+
+        signature_hash_int % modulo == 0
+    }
+
+    /// Returns `true` if `self` is a signature of `slot` by `pubkey`.
+    pub fn verify<T: EthSpec>(
+        &self,
+        slot: Slot,
+        pubkey: &PublicKey,
+        fork: &Fork,
+        spec: &ChainSpec,
+    ) -> bool {
+        let domain = spec.get_domain(
+            slot.epoch(T::slots_per_epoch()),
+            Domain::SelectionProof,
+            fork,
+        );
+        let message = slot.signing_root(domain);
+
+        self.0.verify(message.as_bytes(), pubkey)
+    }
+}
+
+impl From<SelectionProof> for Signature {
+    fn from(from: SelectionProof) -> Signature {
+        from.0
+    }
+}
+
+impl From<Signature> for SelectionProof {
+    fn from(sig: Signature) -> Self {
+        Self(sig)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keypair, MinimalEthSpec};
+
+    #[test]
+    fn small_committees_always_aggregate() {
+        let spec = ChainSpec::minimal();
+        let keypair = Keypair::random();
+        let proof = SelectionProof::new::<MinimalEthSpec>(
+            Slot::new(1),
+            &keypair.sk,
+            &Fork::default(),
+            &spec,
+        );
+
+        assert!(proof.is_aggregator(1, &spec));
+        assert!(proof.is_aggregator(
+            spec.target_aggregators_per_committee as usize * 2 - 1,
+            &spec
+        ));
+    }
+
+    #[test]
+    fn verifies_against_signer() {
+        let spec = ChainSpec::minimal();
+        let fork = Fork::default();
+        let keypair = Keypair::random();
+        let proof = SelectionProof::new::<MinimalEthSpec>(Slot::new(1), &keypair.sk, &fork, &spec);
+
+        assert!(proof.verify::<MinimalEthSpec>(Slot::new(1), &keypair.pk, &fork, &spec));
+        assert!(!proof.verify::<MinimalEthSpec>(Slot::new(2), &keypair.pk, &fork, &spec));
+        assert!(!proof.verify::<MinimalEthSpec>(Slot::new(1), &Keypair::random().pk, &fork, &spec));
+    }
+}
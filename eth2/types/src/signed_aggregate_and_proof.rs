@@ -0,0 +1,46 @@
+use crate::{
+    test_utils::TestRandom, AggregateAndProof, ChainSpec, Domain, EthSpec, Fork, PublicKey,
+    Signature, SignedRoot,
+};
+
+use serde_derive::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use test_random_derive::TestRandom;
+use tree_hash_derive::TreeHash;
+
+/// An `AggregateAndProof` and a signature from its aggregator.
+///
+/// Spec v0.11.1
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Encode, Decode, TreeHash, TestRandom)]
+#[serde(bound = "T: EthSpec")]
+pub struct SignedAggregateAndProof<T: EthSpec> {
+    pub message: AggregateAndProof<T>,
+    pub signature: Signature,
+}
+
+impl<T: EthSpec> SignedAggregateAndProof<T> {
+    /// Returns `true` if `self.signature` is a signature of `self.message` by `aggregator_pubkey`.
+    pub fn is_valid_signature(
+        &self,
+        aggregator_pubkey: &PublicKey,
+        fork: &Fork,
+        spec: &ChainSpec,
+    ) -> bool {
+        let domain = spec.get_domain(
+            self.message.aggregate.data.target.epoch,
+            Domain::AggregateAndProof,
+            fork,
+        );
+        let message = self.message.signing_root(domain);
+
+        self.signature.verify(message.as_bytes(), aggregator_pubkey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MainnetEthSpec;
+
+    ssz_and_tree_hash_tests!(SignedAggregateAndProof<MainnetEthSpec>);
+}
@@ -1,4 +1,4 @@
-use crate::{test_utils::TestRandom, BeaconBlock, EthSpec, Hash256, Slot};
+use crate::{test_utils::TestRandom, BeaconBlock, EthSpec, Hash256, SignedBeaconBlockHeader, Slot};
 use bls::Signature;
 
 use serde_derive::{Deserialize, Serialize};
@@ -38,6 +38,19 @@ impl<E: EthSpec> SignedBeaconBlock<E> {
     pub fn canonical_root(&self) -> Hash256 {
         Hash256::from_slice(&self.message.tree_hash_root()[..])
     }
+
+    /// Returns the header of the block along with the proposer's signature.
+    ///
+    /// The signature remains valid for the header, since a block and its header share the same
+    /// root.
+    ///
+    /// Note: performs a full tree-hash of `self.message.body`.
+    pub fn signed_block_header(&self) -> SignedBeaconBlockHeader {
+        SignedBeaconBlockHeader {
+            message: self.message.block_header(),
+            signature: self.signature.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
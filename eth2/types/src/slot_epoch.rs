@@ -93,6 +93,7 @@ impl Epoch {
     }
 }
 
+impl SignedRoot for Slot {}
 impl SignedRoot for Epoch {}
 
 pub struct SlotIter<'a> {
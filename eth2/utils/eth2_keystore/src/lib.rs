@@ -0,0 +1,260 @@
+//! Decryption of BLS keystores, as defined in
+//! [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335).
+//!
+//! Only version 4 keystores are supported, with either the `scrypt` or `pbkdf2` (HMAC-SHA256) key
+//! derivation functions, the `sha256` checksum function and the `aes-128-ctr` cipher.
+use aes_ctr::stream_cipher::generic_array::GenericArray;
+use aes_ctr::stream_cipher::{NewStreamCipher, SyncStreamCipher};
+use aes_ctr::Aes128Ctr;
+use bls::{Keypair, PublicKey, SecretKey};
+use eth2_hashing::hash;
+use hmac::Hmac;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::fs;
+use std::path::Path;
+
+/// The only keystore version supported.
+pub const KEYSTORE_VERSION: u32 = 4;
+/// The length of the key derived by the key derivation function.
+pub const DKLEN: u32 = 32;
+/// The length of a secret key in a keystore.
+pub const SECRET_KEY_LEN: usize = 32;
+/// The length of a secret key expected by `SecretKey::from_bytes`, which is left-padded with zeros.
+const PADDED_SECRET_KEY_LEN: usize = 48;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    InvalidJson(String),
+    ReadError(String),
+    UnsupportedVersion(u32),
+    UnsupportedKdf(String),
+    UnsupportedChecksum(String),
+    UnsupportedCipher(String),
+    InvalidKdfParams(String),
+    InvalidCipherParams(String),
+    InvalidHex(String),
+    /// The checksum did not match, which indicates that the password is incorrect.
+    InvalidPassword,
+    InvalidSecretKey(String),
+    InvalidPublicKey(String),
+    /// The decrypted secret key does not correspond to the public key of the keystore.
+    PublicKeyMismatch,
+}
+
+/// A BLS keystore, as defined in EIP-2335.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub crypto: Crypto,
+    /// The hex-encoded public key, without a `0x` prefix.
+    pub pubkey: String,
+    /// The EIP-2334 derivation path of the key, which may be empty.
+    #[serde(default)]
+    pub path: String,
+    pub uuid: String,
+    pub version: u32,
+}
+
+/// The `crypto` section of a keystore.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Crypto {
+    pub kdf: Module,
+    pub checksum: Module,
+    pub cipher: Module,
+}
+
+/// A module of the `crypto` section, which applies `function` with `params` to `message`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Module {
+    pub function: String,
+    pub params: Value,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScryptParams {
+    dklen: u32,
+    n: u32,
+    r: u32,
+    p: u32,
+    salt: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pbkdf2Params {
+    dklen: u32,
+    c: u32,
+    prf: String,
+    salt: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CipherParams {
+    iv: String,
+}
+
+impl Keystore {
+    /// Parses a keystore from its JSON representation.
+    pub fn from_json_str(json: &str) -> Result<Self, Error> {
+        let keystore: Self =
+            serde_json::from_str(json).map_err(|e| Error::InvalidJson(format!("{}", e)))?;
+
+        if keystore.version != KEYSTORE_VERSION {
+            return Err(Error::UnsupportedVersion(keystore.version));
+        }
+
+        Ok(keystore)
+    }
+
+    /// Reads and parses a keystore from the JSON file at `path`.
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let json = fs::read_to_string(path).map_err(|e| Error::ReadError(format!("{}", e)))?;
+        Self::from_json_str(&json)
+    }
+
+    /// Returns the public key of the keystore, without decrypting it.
+    pub fn public_key(&self) -> Result<PublicKey, Error> {
+        PublicKey::from_bytes(&hex_decode(&self.pubkey)?)
+            .map_err(|e| Error::InvalidPublicKey(format!("{:?}", e)))
+    }
+
+    /// Decrypts the secret key of the keystore with `password`, returning the keypair.
+    ///
+    /// Returns `Error::InvalidPassword` if the checksum does not match and
+    /// `Error::PublicKeyMismatch` if the secret key does not correspond to the public key of the
+    /// keystore.
+    pub fn decrypt_keypair(&self, password: &[u8]) -> Result<Keypair, Error> {
+        let derived_key = derive_key(&self.crypto.kdf, password)?;
+
+        let cipher_message = hex_decode(&self.crypto.cipher.message)?;
+
+        if self.crypto.checksum.function != "sha256" {
+            return Err(Error::UnsupportedChecksum(
+                self.crypto.checksum.function.clone(),
+            ));
+        }
+        let mut checksum_preimage = derived_key[16..32].to_vec();
+        checksum_preimage.extend_from_slice(&cipher_message);
+        if hash(&checksum_preimage) != hex_decode(&self.crypto.checksum.message)? {
+            return Err(Error::InvalidPassword);
+        }
+
+        let secret = decrypt(&self.crypto.cipher, &derived_key[0..16], cipher_message)?;
+        if secret.len() != SECRET_KEY_LEN {
+            return Err(Error::InvalidSecretKey(format!(
+                "expected {} bytes, got {}",
+                SECRET_KEY_LEN,
+                secret.len()
+            )));
+        }
+
+        let mut padded_secret = vec![0; PADDED_SECRET_KEY_LEN - SECRET_KEY_LEN];
+        padded_secret.extend_from_slice(&secret);
+        let sk = SecretKey::from_bytes(&padded_secret)
+            .map_err(|e| Error::InvalidSecretKey(format!("{:?}", e)))?;
+        let pk = PublicKey::from_secret_key(&sk);
+
+        if pk != self.public_key()? {
+            return Err(Error::PublicKeyMismatch);
+        }
+
+        Ok(Keypair { sk, pk })
+    }
+}
+
+/// Derives the decryption key from `password` using the key derivation function in `kdf`.
+fn derive_key(kdf: &Module, password: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut derived_key = vec![0; DKLEN as usize];
+
+    match kdf.function.as_str() {
+        "scrypt" => {
+            let params: ScryptParams = parse_params(&kdf.params, Error::InvalidKdfParams)?;
+            check_dklen(params.dklen)?;
+
+            if !params.n.is_power_of_two() || params.n < 2 {
+                return Err(Error::InvalidKdfParams(format!(
+                    "n must be a power of two greater than one: {}",
+                    params.n
+                )));
+            }
+            let log_n = params.n.trailing_zeros() as u8;
+            let scrypt_params = scrypt::ScryptParams::new(log_n, params.r, params.p)
+                .map_err(|e| Error::InvalidKdfParams(format!("{:?}", e)))?;
+
+            scrypt::scrypt(
+                password,
+                &hex_decode(&params.salt)?,
+                &scrypt_params,
+                &mut derived_key,
+            )
+            .map_err(|e| Error::InvalidKdfParams(format!("{:?}", e)))?;
+        }
+        "pbkdf2" => {
+            let params: Pbkdf2Params = parse_params(&kdf.params, Error::InvalidKdfParams)?;
+            check_dklen(params.dklen)?;
+
+            if params.prf != "hmac-sha256" {
+                return Err(Error::InvalidKdfParams(format!(
+                    "unsupported prf: {}",
+                    params.prf
+                )));
+            }
+
+            pbkdf2::pbkdf2::<Hmac<Sha256>>(
+                password,
+                &hex_decode(&params.salt)?,
+                params.c as usize,
+                &mut derived_key,
+            );
+        }
+        other => return Err(Error::UnsupportedKdf(other.to_string())),
+    }
+
+    Ok(derived_key)
+}
+
+/// Decrypts `message` with `key`, using the cipher in `cipher`.
+fn decrypt(cipher: &Module, key: &[u8], mut message: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if cipher.function != "aes-128-ctr" {
+        return Err(Error::UnsupportedCipher(cipher.function.clone()));
+    }
+
+    let params: CipherParams = parse_params(&cipher.params, Error::InvalidCipherParams)?;
+    let iv = hex_decode(&params.iv)?;
+    if iv.len() != 16 {
+        return Err(Error::InvalidCipherParams(format!(
+            "iv must be 16 bytes, got {}",
+            iv.len()
+        )));
+    }
+
+    Aes128Ctr::new(GenericArray::from_slice(key), GenericArray::from_slice(&iv))
+        .apply_keystream(&mut message);
+
+    Ok(message)
+}
+
+fn parse_params<T, F>(params: &Value, error: F) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+    F: Fn(String) -> Error,
+{
+    serde_json::from_value(params.clone()).map_err(|e| error(format!("{}", e)))
+}
+
+fn check_dklen(dklen: u32) -> Result<(), Error> {
+    if dklen == DKLEN {
+        Ok(())
+    } else {
+        Err(Error::InvalidKdfParams(format!(
+            "dklen must be {}: {}",
+            DKLEN, dklen
+        )))
+    }
+}
+
+fn hex_decode(string: &str) -> Result<Vec<u8>, Error> {
+    let string = string.trim_start_matches("0x");
+    hex::decode(string).map_err(|e| Error::InvalidHex(format!("{}: {:?}", string, e)))
+}
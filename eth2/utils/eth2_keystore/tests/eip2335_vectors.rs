@@ -0,0 +1,123 @@
+//! Test vectors from EIP-2335.
+use bls::SecretKey;
+use eth2_keystore::{Error, Keystore};
+
+const PASSWORD: &[u8] = b"testpassword";
+const SECRET: &str = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+
+const SCRYPT_VECTOR: &str = r#"
+{
+    "crypto": {
+        "kdf": {
+            "function": "scrypt",
+            "params": {
+                "dklen": 32,
+                "n": 262144,
+                "p": 1,
+                "r": 8,
+                "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+            },
+            "message": ""
+        },
+        "checksum": {
+            "function": "sha256",
+            "params": {},
+            "message": "149aafa27b041f3523c53d7acba1905fa6b1c90f9fef137568101f44b531a3cb"
+        },
+        "cipher": {
+            "function": "aes-128-ctr",
+            "params": {
+                "iv": "264daa3f303d7259501c93d997d84fe6"
+            },
+            "message": "54ecc8863c0550351eee5720f3be6a5d4a016025aa91cd6436cfec938d6a8d30"
+        }
+    },
+    "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+    "path": "m/12381/60/3141592653/589793238",
+    "uuid": "1d85ae20-35c5-4611-98e8-aa14a633906f",
+    "version": 4
+}
+"#;
+
+const PBKDF2_VECTOR: &str = r#"
+{
+    "crypto": {
+        "kdf": {
+            "function": "pbkdf2",
+            "params": {
+                "dklen": 32,
+                "c": 262144,
+                "prf": "hmac-sha256",
+                "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+            },
+            "message": ""
+        },
+        "checksum": {
+            "function": "sha256",
+            "params": {},
+            "message": "18b148af8e52920318084560fd766f9d09587b4915258dec0676cba5b0da09d8"
+        },
+        "cipher": {
+            "function": "aes-128-ctr",
+            "params": {
+                "iv": "264daa3f303d7259501c93d997d84fe6"
+            },
+            "message": "a9249e0ca7315836356e4c7440361ff22b9fe71e2e2ed34fc1eb03976924ed48"
+        }
+    },
+    "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+    "path": "m/12381/60/0/0",
+    "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+    "version": 4
+}
+"#;
+
+fn expected_secret_key() -> SecretKey {
+    let mut bytes = vec![0; 16];
+    bytes.extend_from_slice(&hex::decode(SECRET).expect("should decode secret"));
+    SecretKey::from_bytes(&bytes).expect("should build secret key")
+}
+
+fn check_vector(json: &str) {
+    let keystore = Keystore::from_json_str(json).expect("should parse keystore");
+    let keypair = keystore
+        .decrypt_keypair(PASSWORD)
+        .expect("should decrypt keystore");
+
+    assert_eq!(
+        keypair.sk,
+        expected_secret_key(),
+        "should decrypt the secret"
+    );
+    assert_eq!(
+        Some(keypair.pk),
+        keystore.public_key().ok(),
+        "should match the public key"
+    );
+    assert_eq!(
+        keystore.decrypt_keypair(b"wrongpassword"),
+        Err(Error::InvalidPassword),
+        "should reject an incorrect password"
+    );
+}
+
+#[test]
+fn scrypt_vector() {
+    check_vector(SCRYPT_VECTOR);
+}
+
+#[test]
+fn pbkdf2_vector() {
+    check_vector(PBKDF2_VECTOR);
+}
+
+#[test]
+fn rejects_unsupported_version() {
+    let json = PBKDF2_VECTOR.replace("\"version\": 4", "\"version\": 3");
+
+    assert_eq!(
+        Keystore::from_json_str(&json),
+        Err(Error::UnsupportedVersion(3)),
+        "should reject version 3 keystores"
+    );
+}
@@ -8,6 +8,10 @@
 //! - `IncCounter`: used to represent an ideally ever-growing, never-shrinking integer (e.g.,
 //! number of block processing requests).
 //! - `IntGauge`: used to represent an varying integer (e.g., number of attestations per block).
+//! - `IntCounterVec`: an `IncCounter` partitioned by one or more labels (e.g., number of decode
+//! failures per database item).
+//! - `IntGaugeVec`: an `IntGauge` partitioned by one or more labels (e.g., health of each remote
+//! endpoint).
 //!
 //! ## Important
 //!
@@ -56,7 +60,10 @@
 
 use prometheus::{HistogramOpts, HistogramTimer, Opts};
 
-pub use prometheus::{Encoder, Histogram, IntCounter, IntGauge, Result, TextEncoder};
+pub use prometheus::{
+    Encoder, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Result,
+    TextEncoder,
+};
 
 /// Collect all the metrics for reporting.
 pub fn gather() -> Vec<prometheus::proto::MetricFamily> {
@@ -72,6 +79,19 @@ pub fn try_create_int_counter(name: &str, help: &str) -> Result<IntCounter> {
     Ok(counter)
 }
 
+/// Attempts to crate an `IntCounterVec` with the given `label_names`, returning `Err` if the
+/// registry does not accept the counter (potentially due to naming conflict).
+pub fn try_create_int_counter_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntCounterVec> {
+    let opts = Opts::new(name, help);
+    let counter_vec = IntCounterVec::new(opts, label_names)?;
+    prometheus::register(Box::new(counter_vec.clone()))?;
+    Ok(counter_vec)
+}
+
 /// Attempts to crate an `IntGauge`, returning `Err` if the registry does not accept the counter
 /// (potentially due to naming conflict).
 pub fn try_create_int_gauge(name: &str, help: &str) -> Result<IntGauge> {
@@ -81,6 +101,19 @@ pub fn try_create_int_gauge(name: &str, help: &str) -> Result<IntGauge> {
     Ok(gauge)
 }
 
+/// Attempts to crate an `IntGaugeVec` with the given `label_names`, returning `Err` if the
+/// registry does not accept the gauge (potentially due to naming conflict).
+pub fn try_create_int_gauge_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<IntGaugeVec> {
+    let opts = Opts::new(name, help);
+    let gauge_vec = IntGaugeVec::new(opts, label_names)?;
+    prometheus::register(Box::new(gauge_vec.clone()))?;
+    Ok(gauge_vec)
+}
+
 /// Attempts to crate a `Histogram`, returning `Err` if the registry does not accept the counter
 /// (potentially due to naming conflict).
 pub fn try_create_histogram(name: &str, help: &str) -> Result<Histogram> {
@@ -90,6 +123,34 @@ pub fn try_create_histogram(name: &str, help: &str) -> Result<Histogram> {
     Ok(histogram)
 }
 
+/// Attempts to crate a `Histogram` with the given `buckets`, returning `Err` if the registry does
+/// not accept the histogram (potentially due to naming conflict).
+///
+/// Useful for histograms of values other than durations, which do not suit the default buckets.
+pub fn try_create_histogram_with_buckets(
+    name: &str,
+    help: &str,
+    buckets: Vec<f64>,
+) -> Result<Histogram> {
+    let opts = HistogramOpts::new(name, help).buckets(buckets);
+    let histogram = Histogram::with_opts(opts)?;
+    prometheus::register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+/// Attempts to crate a `HistogramVec` with the given `label_names`, returning `Err` if the
+/// registry does not accept the histogram (potentially due to naming conflict).
+pub fn try_create_histogram_vec(
+    name: &str,
+    help: &str,
+    label_names: &[&str],
+) -> Result<HistogramVec> {
+    let opts = HistogramOpts::new(name, help);
+    let histogram_vec = HistogramVec::new(opts, label_names)?;
+    prometheus::register(Box::new(histogram_vec.clone()))?;
+    Ok(histogram_vec)
+}
+
 /// Starts a timer for the given `Histogram`, stopping when it gets dropped or given to `stop_timer(..)`.
 pub fn start_timer(histogram: &Result<Histogram>) -> Option<HistogramTimer> {
     if let Ok(histogram) = histogram {
@@ -118,15 +179,48 @@ pub fn inc_counter_by(counter: &Result<IntCounter>, value: i64) {
     }
 }
 
+/// Increments the counter in `counter_vec` identified by `label_values`.
+///
+/// Does nothing if the number of `label_values` does not match the labels of `counter_vec`.
+pub fn inc_counter_vec(counter_vec: &Result<IntCounterVec>, label_values: &[&str]) {
+    if let Ok(counter_vec) = counter_vec {
+        if let Ok(counter) = counter_vec.get_metric_with_label_values(label_values) {
+            counter.inc();
+        }
+    }
+}
+
 pub fn set_gauge(gauge: &Result<IntGauge>, value: i64) {
     if let Ok(gauge) = gauge {
         gauge.set(value);
     }
 }
 
+/// Sets the value of the gauge in `gauge_vec` identified by `label_values`.
+///
+/// Does nothing if the number of `label_values` does not match the labels of `gauge_vec`.
+pub fn set_gauge_vec(gauge_vec: &Result<IntGaugeVec>, label_values: &[&str], value: i64) {
+    if let Ok(gauge_vec) = gauge_vec {
+        if let Ok(gauge) = gauge_vec.get_metric_with_label_values(label_values) {
+            gauge.set(value);
+        }
+    }
+}
+
 /// Sets the value of a `Histogram` manually.
 pub fn observe(histogram: &Result<Histogram>, value: f64) {
     if let Ok(histogram) = histogram {
         histogram.observe(value);
     }
 }
+
+/// Sets the value of the histogram in `histogram_vec` identified by `label_values` manually.
+///
+/// Does nothing if the number of `label_values` does not match the labels of `histogram_vec`.
+pub fn observe_vec(histogram_vec: &Result<HistogramVec>, label_values: &[&str], value: f64) {
+    if let Ok(histogram_vec) = histogram_vec {
+        if let Ok(histogram) = histogram_vec.get_metric_with_label_values(label_values) {
+            histogram.observe(value);
+        }
+    }
+}
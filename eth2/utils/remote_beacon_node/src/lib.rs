@@ -4,25 +4,35 @@
 //! Presently, this is only used for testing but it _could_ become a user-facing library.
 
 use eth2_config::Eth2Config;
-use futures::{future, Future, IntoFuture};
+use futures::{
+    future::{self, loop_fn, Either, Loop},
+    Future, IntoFuture, Stream,
+};
+use rand::Rng;
 use reqwest::{
+    header::ACCEPT,
     r#async::{Client, ClientBuilder, Response},
     StatusCode,
 };
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use ssz::Encode;
+use serde::{de::DeserializeOwned, Serialize};
+use ssz::{Decode, Encode};
 use std::marker::PhantomData;
-use std::time::Duration;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::{Delay, Timeout};
 use types::{
     Attestation, AttesterSlashing, BeaconBlock, BeaconState, CommitteeIndex, Epoch, EthSpec, Fork,
-    Hash256, ProposerSlashing, PublicKey, Signature, SignedBeaconBlock, Slot,
+    Hash256, ProposerSlashing, PublicKey, Signature, SignedAggregateAndProof, SignedBeaconBlock,
+    SignedVoluntaryExit, Slot,
 };
 use url::Url;
 
 pub use operation_pool::PersistedOperationPool;
 pub use proto_array_fork_choice::core::ProtoArray;
 pub use rest_api::{
-    CanonicalHeadResponse, Committee, HeadBeaconBlock, ValidatorDutiesRequest, ValidatorDuty,
+    BlockResponse, BlocksResponse, CanonicalHeadResponse, Committee, CommitteeAttestation,
+    HeadBeaconBlock, StateResponse, SyncStatus, ValidatorDutiesRequest, ValidatorDuty,
     ValidatorRequest, ValidatorResponse,
 };
 
@@ -33,6 +43,83 @@ pub const REQUEST_TIMEOUT_SECONDS: u64 = 15;
 #[cfg(not(debug_assertions))]
 pub const REQUEST_TIMEOUT_SECONDS: u64 = 5;
 
+/// The time-outs for requests to the beacon node, after which the request fails with
+/// `Error::TimedOut`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Timeouts {
+    /// The time-out for inexpensive requests (e.g., reading the fork or the head).
+    pub fast: Duration,
+    /// The time-out for requests that are expensive for the beacon node to serve (e.g., producing
+    /// a block or computing duties).
+    pub heavy: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self::all(Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+    }
+}
+
+impl Timeouts {
+    /// Returns `Timeouts` that use the same `timeout` for all requests.
+    pub fn all(timeout: Duration) -> Self {
+        Self {
+            fast: timeout,
+            heavy: timeout,
+        }
+    }
+}
+
+/// Determines how failed `GET` requests are retried.
+///
+/// Other requests (e.g., publishing a block) are never retried, since they may not be idempotent.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RetryPolicy {
+    /// The number of times a request is retried after the first attempt fails.
+    pub retries: usize,
+    /// The delay before the first retry. The delay doubles for each subsequent retry, with up to
+    /// half of each delay removed at random to avoid retrying in lock-step with other clients.
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl RetryPolicy {
+    /// Returns a policy which never retries a request.
+    pub fn none() -> Self {
+        Self {
+            retries: 0,
+            base_delay: Duration::from_millis(0),
+        }
+    }
+
+    /// Returns the delay before the retry following the given (zero-indexed) failed `attempt`.
+    fn delay(&self, attempt: usize) -> Duration {
+        let delay = self.base_delay * 2_u32.saturating_pow(attempt as u32);
+        let max_jitter = delay / 2;
+        let jitter_nanos = rand::thread_rng().gen_range(0, max_jitter.as_nanos() as u64 + 1);
+
+        delay - Duration::from_nanos(jitter_nanos)
+    }
+}
+
+/// The number of requests made by a `HttpClient` that timed out or were retried.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct RequestStats {
+    pub timeouts: usize,
+    pub retries: usize,
+}
+
+#[derive(Default)]
+struct RequestCounters {
+    timeouts: AtomicUsize,
+    retries: AtomicUsize,
+}
+
 #[derive(Clone)]
 /// Connects to a remote Lighthouse (or compatible) node via HTTP.
 pub struct RemoteBeaconNode<E: EthSpec> {
@@ -45,9 +132,18 @@ impl<E: EthSpec> RemoteBeaconNode<E> {
         Self::new_with_timeout(http_endpoint, Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
     }
 
+    /// Uses the same `timeout` for all requests, which are never retried.
     pub fn new_with_timeout(http_endpoint: String, timeout: Duration) -> Result<Self, String> {
+        Self::new_with_timeouts(http_endpoint, Timeouts::all(timeout), RetryPolicy::none())
+    }
+
+    pub fn new_with_timeouts(
+        http_endpoint: String,
+        timeouts: Timeouts,
+        retry_policy: RetryPolicy,
+    ) -> Result<Self, String> {
         Ok(Self {
-            http: HttpClient::new(http_endpoint, timeout)
+            http: HttpClient::new(http_endpoint, timeouts, retry_policy)
                 .map_err(|e| format!("Unable to create http client: {:?}", e))?,
         })
     }
@@ -61,28 +157,53 @@ pub enum Error {
     ReqwestError(reqwest::Error),
     /// There was an error when encoding/decoding an object using serde.
     SerdeJsonError(serde_json::Error),
+    /// There was an error when decoding an SSZ-encoded object.
+    SszDecodeError(ssz::DecodeError),
     /// The server responded to the request, however it did not return a 200-type success code.
     DidNotSucceed { status: StatusCode, body: String },
+    /// The server did not respond to the request to `path` within `timeout`.
+    TimedOut { path: String, timeout: Duration },
+    /// The timer used to enforce a time-out or delay a retry failed.
+    TimerError(tokio::timer::Error),
+}
+
+impl Error {
+    /// Returns `true` if the error may be transient, such that the request is worth retrying.
+    fn is_retriable(&self) -> bool {
+        match self {
+            Error::ReqwestError(e) => !e.is_serialization(),
+            Error::TimedOut { .. } => true,
+            Error::DidNotSucceed { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct HttpClient<E> {
     client: Client,
     url: Url,
-    timeout: Duration,
+    timeouts: Timeouts,
+    retry_policy: RetryPolicy,
+    counters: Arc<RequestCounters>,
     _phantom: PhantomData<E>,
 }
 
 impl<E: EthSpec> HttpClient<E> {
     /// Creates a new instance (without connecting to the node).
-    pub fn new(server_url: String, timeout: Duration) -> Result<Self, Error> {
+    pub fn new(
+        server_url: String,
+        timeouts: Timeouts,
+        retry_policy: RetryPolicy,
+    ) -> Result<Self, Error> {
         Ok(Self {
             client: ClientBuilder::new()
-                .timeout(timeout)
                 .build()
                 .expect("should build from static configuration"),
             url: Url::parse(&server_url)?,
-            timeout: Duration::from_secs(15),
+            timeouts,
+            retry_policy,
+            counters: Arc::new(RequestCounters::default()),
             _phantom: PhantomData,
         })
     }
@@ -107,37 +228,169 @@ impl<E: EthSpec> HttpClient<E> {
         Advanced(self.clone())
     }
 
+    /// Returns the number of requests made by this client (and its clones) that timed out or were
+    /// retried.
+    pub fn request_stats(&self) -> RequestStats {
+        RequestStats {
+            timeouts: self.counters.timeouts.load(Ordering::Relaxed),
+            retries: self.counters.retries.load(Ordering::Relaxed),
+        }
+    }
+
     fn url(&self, path: &str) -> Result<Url, Error> {
         self.url.join(path).map_err(|e| e.into())
     }
 
+    /// Fails `future` with `Error::TimedOut` if it does not complete within `timeout`.
+    fn with_timeout<F>(
+        &self,
+        future: F,
+        path: String,
+        timeout: Duration,
+    ) -> impl Future<Item = F::Item, Error = Error>
+    where
+        F: Future<Error = Error>,
+    {
+        let counters = self.counters.clone();
+
+        Timeout::new(future, timeout).map_err(move |e| {
+            if e.is_elapsed() {
+                counters.timeouts.fetch_add(1, Ordering::Relaxed);
+                Error::TimedOut { path, timeout }
+            } else if e.is_timer() {
+                Error::TimerError(e.into_timer().expect("should be a timer error"))
+            } else {
+                e.into_inner().expect("should be an inner error")
+            }
+        })
+    }
+
+    /// Posts `body` to `url`, using the fast time-out. The request is never retried.
+    ///
+    /// Returns the status and the text of the response body.
     pub fn json_post<T: Serialize>(
         &self,
         url: Url,
         body: T,
-    ) -> impl Future<Item = Response, Error = Error> {
-        self.client
+    ) -> impl Future<Item = (StatusCode, String), Error = Error> {
+        self.json_post_with_timeout(url, body, self.timeouts.fast)
+    }
+
+    /// As per `Self::json_post`, but with the given `timeout`.
+    ///
+    /// The `timeout` applies to the whole request, including reading the response body.
+    pub fn json_post_with_timeout<T: Serialize>(
+        &self,
+        url: Url,
+        body: T,
+        timeout: Duration,
+    ) -> impl Future<Item = (StatusCode, String), Error = Error> {
+        let path = url.path().to_string();
+        let request = self
+            .client
             .post(&url.to_string())
             .json(&body)
             .send()
-            .map_err(Error::from)
+            .and_then(|mut response| {
+                let status = response.status();
+                response.text().map(move |text| (status, text))
+            })
+            .map_err(Error::from);
+
+        self.with_timeout(request, path, timeout)
     }
 
+    /// Gets the JSON object at `url`, using the fast time-out. The request is retried according
+    /// to the `RetryPolicy`.
     pub fn json_get<T: DeserializeOwned>(
         &self,
-        mut url: Url,
+        url: Url,
+        query_pairs: Vec<(String, String)>,
+    ) -> impl Future<Item = T, Error = Error> {
+        self.json_get_with_timeout(url, query_pairs, self.timeouts.fast)
+    }
+
+    /// As per `Self::json_get`, but with the given `timeout` for each attempt.
+    pub fn json_get_with_timeout<T: DeserializeOwned>(
+        &self,
+        url: Url,
+        query_pairs: Vec<(String, String)>,
+        timeout: Duration,
+    ) -> impl Future<Item = T, Error = Error> {
+        self.get_with_timeout(
+            url,
+            query_pairs,
+            timeout,
+            "application/json",
+            |mut success| success.json::<T>().map_err(Error::from),
+        )
+    }
+
+    /// As per `Self::json_get_with_timeout`, but requests the object as SSZ rather than JSON.
+    ///
+    /// SSZ is far cheaper to encode and decode than JSON for large objects, such as states.
+    pub fn ssz_get_with_timeout<T: Decode>(
+        &self,
+        url: Url,
         query_pairs: Vec<(String, String)>,
+        timeout: Duration,
     ) -> impl Future<Item = T, Error = Error> {
+        self.get_with_timeout(url, query_pairs, timeout, "application/ssz", |success| {
+            success
+                .into_body()
+                .concat2()
+                .map_err(Error::from)
+                .and_then(|bytes| T::from_ssz_bytes(&bytes).map_err(Error::SszDecodeError))
+        })
+    }
+
+    /// Gets the object at `url` in the encoding given by the `accept` media type, using `decode`
+    /// to decode a successful response. The request is retried according to the `RetryPolicy`.
+    fn get_with_timeout<T, F, R>(
+        &self,
+        mut url: Url,
+        query_pairs: Vec<(String, String)>,
+        timeout: Duration,
+        accept: &'static str,
+        decode: F,
+    ) -> impl Future<Item = T, Error = Error>
+    where
+        F: Fn(Response) -> R + Copy,
+        R: Future<Item = T, Error = Error>,
+    {
         query_pairs.into_iter().for_each(|(key, param)| {
             url.query_pairs_mut().append_pair(&key, &param);
         });
 
-        self.client
-            .get(&url.to_string())
-            .send()
-            .map_err(Error::from)
-            .and_then(|response| error_for_status(response).map_err(Error::from))
-            .and_then(|mut success| success.json::<T>().map_err(Error::from))
+        let client = self.clone();
+
+        loop_fn(0, move |attempt| {
+            let client = client.clone();
+            let request = client
+                .client
+                .get(&url.to_string())
+                .header(ACCEPT, accept)
+                .send()
+                .map_err(Error::from)
+                .and_then(|response| error_for_status(response).map_err(Error::from))
+                .and_then(decode);
+
+            client
+                .with_timeout(request, url.path().to_string(), timeout)
+                .then(move |result| match result {
+                    Err(e) if attempt < client.retry_policy.retries && e.is_retriable() => {
+                        client.counters.retries.fetch_add(1, Ordering::Relaxed);
+
+                        let delay = client.retry_policy.delay(attempt);
+                        Either::A(
+                            Delay::new(Instant::now() + delay)
+                                .map_err(Error::TimerError)
+                                .map(move |()| Loop::Continue(attempt + 1)),
+                        )
+                    }
+                    result => Either::B(future::result(result.map(Loop::Break))),
+                })
+        })
     }
 }
 
@@ -160,6 +413,25 @@ fn error_for_status(
     }
 }
 
+/// Returns an `Error` (with the `body` of the response) if `status` is a 4xx or 5xx error code.
+fn check_status(status: StatusCode, body: String) -> Result<(), Error> {
+    if status.is_client_error() || status.is_server_error() {
+        Err(Error::DidNotSucceed { status, body })
+    } else {
+        Ok(())
+    }
+}
+
+/// Decodes the JSON object in `body` if `status` is a 200-type success code, otherwise returns an
+/// `Error` with the `body` of the response.
+fn json_from_success<T: DeserializeOwned>(status: StatusCode, body: String) -> Result<T, Error> {
+    if status.is_success() {
+        serde_json::from_str(&body).map_err(Error::from)
+    } else {
+        Err(Error::DidNotSucceed { status, body })
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum PublishStatus {
     /// The object was valid and has been published to the network.
@@ -178,6 +450,21 @@ impl PublishStatus {
     }
 }
 
+/// The result of publishing a voluntary exit.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExitPublishStatus {
+    /// The exit was valid, has been queued for inclusion in a block and published to the network.
+    Queued,
+    /// The exit was valid and has been published to the network, but it was not queued because
+    /// the beacon node does not produce blocks.
+    NotQueued,
+    /// The exit was not valid and has not been published to the network.
+    Invalid(String),
+    /// The server responded with an unknown status code. The exit may or may not have been
+    /// published to the network.
+    Unknown,
+}
+
 /// Provides the functions on the `/beacon` endpoint of the node.
 #[derive(Clone)]
 pub struct Validator<E>(HttpClient<E>);
@@ -207,6 +494,26 @@ impl<E: EthSpec> Validator<E> {
             .and_then(move |url| client.json_get(url, query_params))
     }
 
+    /// Produces an unsigned attestation for each of the given committees, with a single request.
+    pub fn produce_attestations(
+        &self,
+        slot: Slot,
+        committee_indices: &[CommitteeIndex],
+    ) -> impl Future<Item = Vec<CommitteeAttestation<E>>, Error = Error> {
+        let query_params = std::iter::once(("slot".to_string(), format!("{}", slot)))
+            .chain(
+                committee_indices
+                    .iter()
+                    .map(|index| ("committee_index".to_string(), format!("{}", index))),
+            )
+            .collect::<Vec<_>>();
+
+        let client = self.0.clone();
+        self.url("attestations")
+            .into_future()
+            .and_then(move |url| client.json_get(url, query_params))
+    }
+
     /// Posts an attestation to the beacon node, expecting it to verify it and publish it to the network.
     pub fn publish_attestation(
         &self,
@@ -216,19 +523,64 @@ impl<E: EthSpec> Validator<E> {
         self.url("attestation")
             .into_future()
             .and_then(move |url| client.json_post::<_>(url, attestation))
-            .and_then(|mut response| {
-                response
-                    .text()
-                    .map(|text| (response, text))
-                    .map_err(Error::from)
+            .and_then(|(status, text)| match status {
+                StatusCode::OK => Ok(PublishStatus::Valid),
+                StatusCode::ACCEPTED => Ok(PublishStatus::Invalid(text)),
+                _ => check_status(status, text).map(|_| PublishStatus::Unknown),
             })
-            .and_then(|(response, text)| match response.status() {
+    }
+
+    /// Posts a list of attestations to the beacon node, expecting it to verify and publish each of
+    /// them to the network.
+    ///
+    /// Returns `PublishStatus::Invalid` if any of the attestations was not published.
+    pub fn publish_attestations(
+        &self,
+        attestations: Vec<Attestation<E>>,
+    ) -> impl Future<Item = PublishStatus, Error = Error> {
+        let client = self.0.clone();
+        self.url("attestations")
+            .into_future()
+            .and_then(move |url| client.json_post::<_>(url, attestations))
+            .and_then(|(status, text)| match status {
                 StatusCode::OK => Ok(PublishStatus::Valid),
                 StatusCode::ACCEPTED => Ok(PublishStatus::Invalid(text)),
-                _ => response
-                    .error_for_status()
-                    .map_err(Error::from)
-                    .map(|_| PublishStatus::Unknown),
+                _ => check_status(status, text).map(|_| PublishStatus::Unknown),
+            })
+    }
+
+    /// Returns the aggregate attestation with the most signers that the beacon node knows for the
+    /// given committee.
+    pub fn produce_aggregate_attestation(
+        &self,
+        slot: Slot,
+        committee_index: CommitteeIndex,
+    ) -> impl Future<Item = Attestation<E>, Error = Error> {
+        let query_params = vec![
+            ("slot".into(), format!("{}", slot)),
+            ("committee_index".into(), format!("{}", committee_index)),
+        ];
+
+        let client = self.0.clone();
+        self.url("aggregate_attestation")
+            .into_future()
+            .and_then(move |url| client.json_get(url, query_params))
+    }
+
+    /// Posts a signed aggregate to the beacon node, expecting it to verify it and publish it to
+    /// the network.
+    pub fn publish_aggregate_and_proof(
+        &self,
+        signed_aggregate: SignedAggregateAndProof<E>,
+    ) -> impl Future<Item = PublishStatus, Error = Error> {
+        let client = self.0.clone();
+        self.url("aggregate_and_proof")
+            .into_future()
+            .and_then(move |url| client.json_post::<_>(url, signed_aggregate))
+            .and_then(|(status, text)| match status {
+                StatusCode::OK => Ok(PublishStatus::Valid),
+                StatusCode::ACCEPTED => Ok(PublishStatus::Invalid(text)),
+                _ => check_status(status, text).map(|_| PublishStatus::Unknown),
             })
     }
 
@@ -250,9 +602,11 @@ impl<E: EthSpec> Validator<E> {
 
         self.url("duties")
             .into_future()
-            .and_then(move |url| client.json_post::<_>(url, bulk_request))
-            .and_then(|response| error_for_status(response).map_err(Error::from))
-            .and_then(|mut success| success.json().map_err(Error::from))
+            .and_then(move |url| {
+                let timeout = client.timeouts.heavy;
+                client.json_post_with_timeout::<_>(url, bulk_request, timeout)
+            })
+            .and_then(|(status, text)| json_from_success(status, text))
     }
 
     /// Posts a block to the beacon node, expecting it to verify it and publish it to the network.
@@ -263,20 +617,14 @@ impl<E: EthSpec> Validator<E> {
         let client = self.0.clone();
         self.url("block")
             .into_future()
-            .and_then(move |url| client.json_post::<_>(url, block))
-            .and_then(|mut response| {
-                response
-                    .text()
-                    .map(|text| (response, text))
-                    .map_err(Error::from)
+            .and_then(move |url| {
+                let timeout = client.timeouts.heavy;
+                client.json_post_with_timeout::<_>(url, block, timeout)
             })
-            .and_then(|(response, text)| match response.status() {
+            .and_then(|(status, text)| match status {
                 StatusCode::OK => Ok(PublishStatus::Valid),
                 StatusCode::ACCEPTED => Ok(PublishStatus::Invalid(text)),
-                _ => response
-                    .error_for_status()
-                    .map_err(Error::from)
-                    .map(|_| PublishStatus::Unknown),
+                _ => check_status(status, text).map(|_| PublishStatus::Unknown),
             })
     }
 
@@ -288,12 +636,13 @@ impl<E: EthSpec> Validator<E> {
     ) -> impl Future<Item = BeaconBlock<E>, Error = Error> {
         let client = self.0.clone();
         self.url("block").into_future().and_then(move |url| {
-            client.json_get::<BeaconBlock<E>>(
+            client.json_get_with_timeout::<BeaconBlock<E>>(
                 url,
                 vec![
                     ("slot".into(), format!("{}", slot.as_u64())),
                     ("randao_reveal".into(), signature_as_string(&randao_reveal)),
                 ],
+                client.timeouts.heavy,
             )
         })
     }
@@ -343,6 +692,24 @@ impl<E: EthSpec> Beacon<E> {
             .and_then(move |url| client.json_get(url, vec![]))
     }
 
+    /// Returns the canonical blocks (and their roots) in the `count` slots from `start_slot`.
+    pub fn get_blocks_in_range(
+        &self,
+        start_slot: Slot,
+        count: u64,
+    ) -> impl Future<Item = BlocksResponse<E>, Error = Error> {
+        let client = self.0.clone();
+        self.url("blocks").into_future().and_then(move |url| {
+            client.json_get(
+                url,
+                vec![
+                    ("start".into(), format!("{}", start_slot.as_u64())),
+                    ("count".into(), format!("{}", count)),
+                ],
+            )
+        })
+    }
+
     /// Returns the block and block root at the given slot.
     pub fn get_block_by_slot(
         &self,
@@ -359,6 +726,22 @@ impl<E: EthSpec> Beacon<E> {
         self.get_block("root".to_string(), root_as_string(root))
     }
 
+    /// As per `Self::get_block_by_slot`, but the block is transferred as SSZ rather than JSON.
+    pub fn get_ssz_block_by_slot(
+        &self,
+        slot: Slot,
+    ) -> impl Future<Item = (SignedBeaconBlock<E>, Hash256), Error = Error> {
+        self.get_ssz_block("slot".to_string(), format!("{}", slot.as_u64()))
+    }
+
+    /// As per `Self::get_block_by_root`, but the block is transferred as SSZ rather than JSON.
+    pub fn get_ssz_block_by_root(
+        &self,
+        root: Hash256,
+    ) -> impl Future<Item = (SignedBeaconBlock<E>, Hash256), Error = Error> {
+        self.get_ssz_block("root".to_string(), root_as_string(root))
+    }
+
     /// Returns the block and block root at the given slot.
     fn get_block(
         &self,
@@ -374,6 +757,26 @@ impl<E: EthSpec> Beacon<E> {
             .map(|response| (response.beacon_block, response.root))
     }
 
+    /// As per `Self::get_block`, but the block is transferred as SSZ.
+    fn get_ssz_block(
+        &self,
+        query_key: String,
+        query_param: String,
+    ) -> impl Future<Item = (SignedBeaconBlock<E>, Hash256), Error = Error> {
+        let client = self.0.clone();
+        self.url("block")
+            .into_future()
+            .and_then(move |url| {
+                let timeout = client.timeouts.fast;
+                client.ssz_get_with_timeout::<BlockResponse<E>>(
+                    url,
+                    vec![(query_key, query_param)],
+                    timeout,
+                )
+            })
+            .map(|response| (response.beacon_block, response.root))
+    }
+
     /// Returns the state and state root at the given slot.
     pub fn get_state_by_slot(
         &self,
@@ -390,6 +793,22 @@ impl<E: EthSpec> Beacon<E> {
         self.get_state("root".to_string(), root_as_string(root))
     }
 
+    /// As per `Self::get_state_by_slot`, but the state is transferred as SSZ rather than JSON.
+    pub fn get_ssz_state_by_slot(
+        &self,
+        slot: Slot,
+    ) -> impl Future<Item = (BeaconState<E>, Hash256), Error = Error> {
+        self.get_ssz_state("slot".to_string(), format!("{}", slot.as_u64()))
+    }
+
+    /// As per `Self::get_state_by_root`, but the state is transferred as SSZ rather than JSON.
+    pub fn get_ssz_state_by_root(
+        &self,
+        root: Hash256,
+    ) -> impl Future<Item = (BeaconState<E>, Hash256), Error = Error> {
+        self.get_ssz_state("root".to_string(), root_as_string(root))
+    }
+
     /// Returns the root of the state at the given slot.
     pub fn get_state_root(&self, slot: Slot) -> impl Future<Item = Hash256, Error = Error> {
         let client = self.0.clone();
@@ -416,7 +835,32 @@ impl<E: EthSpec> Beacon<E> {
         self.url("state")
             .into_future()
             .and_then(move |url| {
-                client.json_get::<StateResponse<E>>(url, vec![(query_key, query_param)])
+                let timeout = client.timeouts.heavy;
+                client.json_get_with_timeout::<StateResponse<E>>(
+                    url,
+                    vec![(query_key, query_param)],
+                    timeout,
+                )
+            })
+            .map(|response| (response.beacon_state, response.root))
+    }
+
+    /// As per `Self::get_state`, but the state is transferred as SSZ.
+    fn get_ssz_state(
+        &self,
+        query_key: String,
+        query_param: String,
+    ) -> impl Future<Item = (BeaconState<E>, Hash256), Error = Error> {
+        let client = self.0.clone();
+        self.url("state")
+            .into_future()
+            .and_then(move |url| {
+                let timeout = client.timeouts.heavy;
+                client.ssz_get_with_timeout::<StateResponse<E>>(
+                    url,
+                    vec![(query_key, query_param)],
+                    timeout,
+                )
             })
             .map(|response| (response.beacon_state, response.root))
     }
@@ -443,8 +887,7 @@ impl<E: EthSpec> Beacon<E> {
         self.url("validators")
             .into_future()
             .and_then(move |url| client.json_post::<_>(url, bulk_request))
-            .and_then(|response| error_for_status(response).map_err(Error::from))
-            .and_then(|mut success| success.json().map_err(Error::from))
+            .and_then(|(status, text)| json_from_success(status, text))
     }
 
     /// Returns all validators.
@@ -465,7 +908,10 @@ impl<E: EthSpec> Beacon<E> {
 
         self.url("validators/all")
             .into_future()
-            .and_then(move |url| client.json_get(url, query_params))
+            .and_then(move |url| {
+                let timeout = client.timeouts.heavy;
+                client.json_get_with_timeout(url, query_params, timeout)
+            })
     }
 
     /// Returns the active validators.
@@ -486,7 +932,10 @@ impl<E: EthSpec> Beacon<E> {
 
         self.url("validators/active")
             .into_future()
-            .and_then(move |url| client.json_get(url, query_params))
+            .and_then(move |url| {
+                let timeout = client.timeouts.heavy;
+                client.json_get_with_timeout(url, query_params, timeout)
+            })
     }
 
     /// Returns committees at the given epoch.
@@ -512,8 +961,7 @@ impl<E: EthSpec> Beacon<E> {
             .and_then(move |url| {
                 client
                     .json_post::<_>(url, proposer_slashing)
-                    .and_then(|response| error_for_status(response).map_err(Error::from))
-                    .and_then(|mut success| success.json().map_err(Error::from))
+                    .and_then(|(status, text)| json_from_success(status, text))
             })
     }
 
@@ -528,10 +976,84 @@ impl<E: EthSpec> Beacon<E> {
             .and_then(move |url| {
                 client
                     .json_post::<_>(url, attester_slashing)
-                    .and_then(|response| error_for_status(response).map_err(Error::from))
-                    .and_then(|mut success| success.json().map_err(Error::from))
+                    .and_then(|(status, text)| json_from_success(status, text))
+            })
+    }
+
+    /// Posts a signed voluntary exit to the beacon node, expecting it to queue it for inclusion in
+    /// a block (if it produces blocks) and publish it to the network.
+    pub fn publish_voluntary_exit(
+        &self,
+        exit: SignedVoluntaryExit,
+    ) -> impl Future<Item = ExitPublishStatus, Error = Error> {
+        let client = self.0.clone();
+        self.url("voluntary_exit")
+            .into_future()
+            .and_then(move |url| client.json_post::<_>(url, exit))
+            .and_then(|(status, text)| match status {
+                StatusCode::OK => {
+                    if serde_json::from_str::<bool>(&text)? {
+                        Ok(ExitPublishStatus::Queued)
+                    } else {
+                        Ok(ExitPublishStatus::NotQueued)
+                    }
+                }
+                StatusCode::ACCEPTED => Ok(ExitPublishStatus::Invalid(text)),
+                _ => check_status(status, text).map(|_| ExitPublishStatus::Unknown),
             })
     }
+
+    /// Returns the attestations in the operation pool, optionally filtered by `slot` and
+    /// `committee_index`.
+    pub fn get_pooled_attestations(
+        &self,
+        slot: Option<Slot>,
+        committee_index: Option<CommitteeIndex>,
+    ) -> impl Future<Item = Vec<Attestation<E>>, Error = Error> {
+        let client = self.0.clone();
+
+        let mut query_params = vec![];
+        if let Some(slot) = slot {
+            query_params.push(("slot".into(), format!("{}", slot.as_u64())));
+        }
+        if let Some(committee_index) = committee_index {
+            query_params.push(("committee_index".into(), format!("{}", committee_index)));
+        }
+
+        self.url("pool/attestations")
+            .into_future()
+            .and_then(move |url| client.json_get(url, query_params))
+    }
+
+    /// Returns the voluntary exits in the operation pool.
+    pub fn get_pooled_voluntary_exits(
+        &self,
+    ) -> impl Future<Item = Vec<SignedVoluntaryExit>, Error = Error> {
+        let client = self.0.clone();
+        self.url("pool/voluntary_exits")
+            .into_future()
+            .and_then(move |url| client.json_get(url, vec![]))
+    }
+
+    /// Returns the proposer slashings in the operation pool.
+    pub fn get_pooled_proposer_slashings(
+        &self,
+    ) -> impl Future<Item = Vec<ProposerSlashing>, Error = Error> {
+        let client = self.0.clone();
+        self.url("pool/proposer_slashings")
+            .into_future()
+            .and_then(move |url| client.json_get(url, vec![]))
+    }
+
+    /// Returns the attester slashings in the operation pool.
+    pub fn get_pooled_attester_slashings(
+        &self,
+    ) -> impl Future<Item = Vec<AttesterSlashing<E>>, Error = Error> {
+        let client = self.0.clone();
+        self.url("pool/attester_slashings")
+            .into_future()
+            .and_then(move |url| client.json_get(url, vec![]))
+    }
 }
 
 /// Provides the functions on the `/spec` endpoint of the node.
@@ -572,6 +1094,14 @@ impl<E: EthSpec> Node<E> {
             .into_future()
             .and_then(move |url| client.json_get(url, vec![]))
     }
+
+    /// Returns the progress of the node's head towards the wall-clock slot.
+    pub fn get_syncing(&self) -> impl Future<Item = SyncStatus, Error = Error> {
+        let client = self.0.clone();
+        self.url("syncing")
+            .into_future()
+            .and_then(move |url| client.json_get(url, vec![]))
+    }
 }
 
 /// Provides the functions on the `/advanced` endpoint of the node.
@@ -605,20 +1135,6 @@ impl<E: EthSpec> Advanced<E> {
     }
 }
 
-#[derive(Deserialize)]
-#[serde(bound = "T: EthSpec")]
-pub struct BlockResponse<T: EthSpec> {
-    pub beacon_block: SignedBeaconBlock<T>,
-    pub root: Hash256,
-}
-
-#[derive(Deserialize)]
-#[serde(bound = "T: EthSpec")]
-pub struct StateResponse<T: EthSpec> {
-    pub beacon_state: BeaconState<T>,
-    pub root: Hash256,
-}
-
 fn root_as_string(root: Hash256) -> String {
     format!("0x{:?}", root)
 }
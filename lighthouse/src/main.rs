@@ -155,6 +155,22 @@ fn run<E: EthSpec>(
         return Ok(());
     };
 
+    if let Some(sub_matches) = matches.subcommand_matches("validator_client") {
+        if let Some(exit_matches) = sub_matches.subcommand_matches("exit") {
+            // The exit command runs to completion, rather than starting the validator client.
+            return validator_client::exit::run(sub_matches, exit_matches, environment)
+                .map_err(|e| format!("Failed to exit validator: {}", e));
+        }
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("beacon_node") {
+        if let Some(history_matches) = sub_matches.subcommand_matches("admin-history") {
+            // The admin-history command reads the database, rather than starting the beacon node.
+            return beacon_node::admin_history::run::<E>(sub_matches, history_matches)
+                .map_err(|e| format!("Failed to print admin history: {}", e));
+        }
+    }
+
     let beacon_node = if let Some(sub_matches) = matches.subcommand_matches("beacon_node") {
         let runtime_context = environment.core_context();
 
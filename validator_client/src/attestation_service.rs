@@ -1,26 +1,55 @@
 use crate::{
     duties_service::{DutiesService, ValidatorDuty},
+    fallback_beacon_node::FallbackBeaconNode,
+    metrics,
     validator_store::ValidatorStore,
 };
 use environment::RuntimeContext;
 use exit_future::Signal;
-use futures::{Future, Stream};
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
-use slog::{crit, info, trace};
+use futures::{
+    future::{self, loop_fn, Either, Loop},
+    Future, IntoFuture, Stream,
+};
+use parking_lot::Mutex;
+use remote_beacon_node::PublishStatus;
+use slog::{crit, debug, info, trace, warn, Logger};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::timer::Interval;
-use types::{ChainSpec, CommitteeIndex, EthSpec, Slot};
+use tokio::timer::{Delay, Interval};
+use types::{Attestation, ChainSpec, CommitteeIndex, EthSpec, PublicKey, SelectionProof, Slot};
+
+/// Delay this period of time after the slot starts. This allows the slot clock to tick over.
+const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(100);
+
+/// The number of times per slot that the head is polled whilst waiting for the block at the
+/// present slot.
+const HEAD_POLLS_PER_SLOT: u32 = 24;
+
+/// The number of times an aggregate is requested before giving up, in case the beacon node has not
+/// yet received any attestations for the committee.
+const AGGREGATE_REQUEST_ATTEMPTS: usize = 3;
+
+/// The delay between requests for an aggregate.
+const AGGREGATE_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// The reason that attestation production was started.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum AttestationTrigger {
+    /// The beacon node imported a block at the present slot.
+    BlockArrived,
+    /// One-third of the slot elapsed without a block at the present slot.
+    Deadline,
+}
 
 /// Builds an `AttestationService`.
 pub struct AttestationServiceBuilder<T, E: EthSpec> {
     duties_service: Option<DutiesService<T, E>>,
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_node: Option<FallbackBeaconNode<E>>,
     context: Option<RuntimeContext<E>>,
 }
 
@@ -50,7 +79,7 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
+    pub fn beacon_node(mut self, beacon_node: FallbackBeaconNode<E>) -> Self {
         self.beacon_node = Some(beacon_node);
         self
     }
@@ -78,6 +107,7 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build AttestationService without runtime_context")?,
+                selection_proofs: Mutex::new(HashMap::new()),
             }),
         })
     }
@@ -88,15 +118,21 @@ pub struct Inner<T, E: EthSpec> {
     duties_service: DutiesService<T, E>,
     validator_store: ValidatorStore<T, E>,
     slot_clock: T,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_node: FallbackBeaconNode<E>,
     context: RuntimeContext<E>,
+    /// The selection proofs that have been signed, so that each is only signed once per slot.
+    selection_proofs: Mutex<HashMap<Slot, HashMap<PublicKey, SelectionProof>>>,
 }
 
-/// Attempts to produce attestations for all known validators 1/3rd of the way through each slot.
+/// Attempts to produce attestations for all known validators 1/3rd of the way through each slot,
+/// or as soon as the beacon node imports a block at the present slot (if that is earlier).
 ///
 /// If any validators are on the same committee, a single attestation will be downloaded and
 /// returned to the beacon node. This attestation will have a signature from each of the
 /// validators.
+///
+/// Two-thirds of the way through each slot, an aggregate is published for each committee in which
+/// one of the validators has been selected as an aggregator.
 pub struct AttestationService<T, E: EthSpec> {
     inner: Arc<Inner<T, E>>,
 }
@@ -131,13 +167,14 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         let interval = {
             let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
             Interval::new(
-                Instant::now() + duration_to_next_slot + slot_duration / 3,
+                Instant::now() + duration_to_next_slot + TIME_DELAY_FROM_SLOT,
                 slot_duration,
             )
         };
 
         let (exit_signal, exit_fut) = exit_future::signal();
         let service = self.clone();
+        let spec = spec.clone();
         let log_1 = log.clone();
         let log_2 = log.clone();
         let log_3 = log.clone();
@@ -154,17 +191,12 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
                             }
                         })
                         .for_each(move |_| {
-                            if let Err(e) = service.spawn_attestation_tasks() {
+                            if let Err(e) = service.spawn_slot_task(&spec) {
                                 crit!(
                                     log_2,
                                     "Failed to spawn attestation tasks";
                                     "error" => e
                                 )
-                            } else {
-                                trace!(
-                                    log_2,
-                                    "Spawned attestation tasks";
-                                )
                             }
 
                             Ok(())
@@ -176,16 +208,198 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         Ok(exit_signal)
     }
 
-    /// For each each required attestation, spawn a new task that downloads, signs and uploads the
-    /// attestation to the beacon node.
-    fn spawn_attestation_tasks(&self) -> Result<(), String> {
-        let service = self.clone();
-
-        let slot = service
+    /// Spawns a task which waits until it is time to attest during the present slot, then spawns
+    /// the attestation tasks. Also spawns the aggregation task, if required.
+    ///
+    /// Does nothing if no validators are required to attest during the present slot.
+    fn spawn_slot_task(&self, spec: &ChainSpec) -> Result<(), String> {
+        let slot = self
             .slot_clock
             .now()
             .ok_or_else(|| "Failed to read slot clock".to_string())?;
 
+        if self.duties_service.attesters(slot).is_empty() {
+            return Ok(());
+        }
+
+        if !self.duties_service.beacon_node_is_synced() {
+            warn!(
+                self.context.log,
+                "Skipping attestations";
+                "reason" => "beacon node is not synced",
+                "slot" => slot.as_u64(),
+            );
+            return Ok(());
+        }
+
+        let deadline = self.attestation_deadline()?;
+        let poll_interval = self.slot_clock.slot_duration() / HEAD_POLLS_PER_SLOT;
+        let service = self.clone();
+        let future = wait_for_block_or_deadline(
+            self.beacon_node.clone(),
+            slot,
+            deadline,
+            poll_interval,
+            self.context.log.clone(),
+        )
+        .map(move |trigger| service.attest_at_trigger(slot, trigger));
+
+        self.context.executor.spawn(future);
+
+        self.spawn_aggregation_task(slot, spec)
+    }
+
+    /// Spawns a task which, two-thirds of the way through the present slot, publishes an aggregate
+    /// for each committee in which one of the validators is an aggregator.
+    ///
+    /// Does nothing if none of the validators are aggregators at `slot`.
+    fn spawn_aggregation_task(&self, slot: Slot, spec: &ChainSpec) -> Result<(), String> {
+        let aggregators = self.aggregators(slot, spec);
+
+        if aggregators.is_empty() {
+            return Ok(());
+        }
+
+        let slot_duration = self.slot_clock.slot_duration();
+        let aggregation_time = self.time_into_slot(slot_duration * 2 / 3)?;
+        let service = self.clone();
+        let log = self.context.log.clone();
+
+        self.context.executor.spawn(
+            Delay::new(aggregation_time)
+                .map_err(move |e| {
+                    crit!(
+                        log,
+                        "Failed to wait for aggregation";
+                        "error" => format!("{}", e)
+                    )
+                })
+                .and_then(move |_| {
+                    future::join_all(aggregators.into_iter().map(
+                        move |(committee_index, (duty, selection_proof))| {
+                            service.do_aggregation(slot, committee_index, duty, selection_proof)
+                        },
+                    ))
+                })
+                .map(|_| ()),
+        );
+
+        Ok(())
+    }
+
+    /// Returns the duty and selection proof of a single aggregator for each committee at `slot`
+    /// in which any of the validators is an aggregator.
+    fn aggregators(
+        &self,
+        slot: Slot,
+        spec: &ChainSpec,
+    ) -> HashMap<CommitteeIndex, (ValidatorDuty, SelectionProof)> {
+        let mut aggregators = HashMap::new();
+
+        for duty in self.duties_service.attesters(slot) {
+            let (committee_index, committee_length) = match (
+                duty.attestation_committee_index,
+                duty.attestation_committee_length,
+            ) {
+                (Some(index), Some(length)) => (index, length),
+                _ => continue,
+            };
+
+            // One aggregate per committee is sufficient.
+            if aggregators.contains_key(&committee_index) {
+                continue;
+            }
+
+            if let Some(selection_proof) = self.selection_proof(slot, &duty.validator_pubkey) {
+                if selection_proof.is_aggregator(committee_length, spec) {
+                    aggregators.insert(committee_index, (duty, selection_proof));
+                }
+            }
+        }
+
+        aggregators
+    }
+
+    /// Returns the selection proof of the validator with `validator_pubkey` at `slot`, only
+    /// signing it if it has not already been signed.
+    fn selection_proof(&self, slot: Slot, validator_pubkey: &PublicKey) -> Option<SelectionProof> {
+        let mut selection_proofs = self.selection_proofs.lock();
+
+        // Proofs for earlier slots will never be required again.
+        selection_proofs.retain(|proof_slot, _| *proof_slot >= slot);
+
+        let proofs_at_slot = selection_proofs.entry(slot).or_insert_with(HashMap::new);
+
+        if let Some(selection_proof) = proofs_at_slot.get(validator_pubkey) {
+            return Some(selection_proof.clone());
+        }
+
+        let selection_proof = self
+            .validator_store
+            .produce_selection_proof(validator_pubkey, slot)?;
+        proofs_at_slot.insert(validator_pubkey.clone(), selection_proof.clone());
+
+        Some(selection_proof)
+    }
+
+    /// Records the `trigger` that started attestation production, then spawns the attestation
+    /// tasks for `slot`.
+    fn attest_at_trigger(&self, slot: Slot, trigger: AttestationTrigger) {
+        let log = &self.context.log;
+
+        match trigger {
+            AttestationTrigger::BlockArrived => {
+                metrics::inc_counter(&metrics::ATTESTATIONS_TRIGGERED_BY_BLOCK)
+            }
+            AttestationTrigger::Deadline => {
+                metrics::inc_counter(&metrics::ATTESTATIONS_TRIGGERED_BY_DEADLINE)
+            }
+        }
+
+        if let Err(e) = self.spawn_attestation_tasks(slot) {
+            crit!(
+                log,
+                "Failed to spawn attestation tasks";
+                "error" => e
+            )
+        } else {
+            trace!(
+                log,
+                "Spawned attestation tasks";
+                "trigger" => format!("{:?}", trigger),
+            )
+        }
+    }
+
+    /// Returns the instant that is one-third of the way through the present slot, according to the
+    /// slot clock.
+    fn attestation_deadline(&self) -> Result<Instant, String> {
+        self.time_into_slot(self.slot_clock.slot_duration() / 3)
+    }
+
+    /// Returns the instant that is `offset` after the start of the present slot, according to the
+    /// slot clock.
+    ///
+    /// If `offset` has already elapsed, the instant is now.
+    fn time_into_slot(&self, offset: Duration) -> Result<Instant, String> {
+        let duration_to_next_slot = self
+            .slot_clock
+            .duration_to_next_slot()
+            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+        let slot_duration = self.slot_clock.slot_duration();
+
+        let duration_to_offset = duration_to_next_slot
+            .checked_sub(slot_duration.checked_sub(offset).unwrap_or_default())
+            .unwrap_or_else(|| Duration::from_secs(0));
+
+        Ok(Instant::now() + duration_to_offset)
+    }
+
+    /// For each each required attestation, spawn a new task that downloads, signs and uploads the
+    /// attestation to the beacon node.
+    fn spawn_attestation_tasks(&self, slot: Slot) -> Result<(), String> {
+        let service = self.clone();
+
         let mut committee_indices: HashMap<CommitteeIndex, Vec<ValidatorDuty>> = HashMap::new();
 
         service
@@ -216,97 +430,309 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         Ok(())
     }
 
-    /// For a given `committee_index`, download the attestation, have it signed by all validators
-    /// in `validator_duties` then upload it.
+    /// For a given `committee_index`, download the attestation once, have a copy signed by each
+    /// validator in `validator_duties` then upload all the signed attestations together.
     fn do_attestation(
         &self,
         slot: Slot,
         committee_index: CommitteeIndex,
         validator_duties: Vec<ValidatorDuty>,
     ) -> impl Future<Item = (), Error = ()> {
-        let service_1 = self.clone();
-        let service_2 = self.clone();
+        let validator_store = self.validator_store.clone();
         let log_1 = self.context.log.clone();
         let log_2 = self.context.log.clone();
 
-        self.beacon_node
-            .http
-            .validator()
-            .produce_attestation(slot, committee_index)
-            .map_err(|e| format!("Failed to produce attestation: {:?}", e))
-            .map(move |attestation| {
-                validator_duties
-                    .iter()
-                    .fold(attestation, |mut attestation, duty| {
-                        let log = service_1.context.log.clone();
-
-                        if let Some((
-                            duty_slot,
-                            duty_committee_index,
-                            validator_committee_position,
-                        )) = attestation_duties(duty)
-                        {
-                            if duty_slot == slot && duty_committee_index == committee_index {
-                                if service_1
-                                    .validator_store
-                                    .sign_attestation(
-                                        &duty.validator_pubkey,
-                                        validator_committee_position,
-                                        &mut attestation,
-                                    )
-                                    .is_none()
-                                {
-                                    crit!(log, "Failed to sign attestation");
-                                }
-                            } else {
-                                crit!(log, "Inconsistent validator duties during signing");
-                            }
-                        } else {
-                            crit!(log, "Missing validator duties when signing");
-                        }
+        attest_for_committee(
+            self.beacon_node.clone(),
+            slot,
+            committee_index,
+            validator_duties,
+            move |validator_pubkey, validator_committee_position, attestation| {
+                validator_store.sign_attestation(
+                    validator_pubkey,
+                    validator_committee_position,
+                    attestation,
+                )
+            },
+            self.context.log.clone(),
+        )
+        .map(move |(attestations, publish_status)| {
+            let data = &attestations[0].data;
 
-                        attestation
-                    })
-            })
-            .and_then(move |attestation| {
-                service_2
-                    .beacon_node
-                    .http
-                    .validator()
-                    .publish_attestation(attestation.clone())
-                    .map(|publish_status| (attestation, publish_status))
-                    .map_err(|e| format!("Failed to publish attestation: {:?}", e))
-            })
-            .map(move |(attestation, publish_status)| match publish_status {
+            match publish_status {
                 PublishStatus::Valid => info!(
                     log_1,
-                    "Successfully published attestation";
-                    "signatures" => attestation.aggregation_bits.num_set_bits(),
-                    "head_block" => format!("{}", attestation.data.beacon_block_root),
-                    "committee_index" => attestation.data.index,
-                    "slot" => attestation.data.slot.as_u64(),
+                    "Successfully published attestations";
+                    "count" => attestations.len(),
+                    "head_block" => format!("{}", data.beacon_block_root),
+                    "committee_index" => data.index,
+                    "slot" => data.slot.as_u64(),
                 ),
                 PublishStatus::Invalid(msg) => crit!(
                     log_1,
-                    "Published attestation was invalid";
+                    "Published attestations were invalid";
                     "message" => msg,
-                    "committee_index" => attestation.data.index,
-                    "slot" => attestation.data.slot.as_u64(),
+                    "count" => attestations.len(),
+                    "committee_index" => data.index,
+                    "slot" => data.slot.as_u64(),
                 ),
                 PublishStatus::Unknown => {
-                    crit!(log_1, "Unknown condition when publishing attestation")
+                    crit!(log_1, "Unknown condition when publishing attestations")
+                }
+            }
+        })
+        .map_err(move |e| {
+            crit!(
+                log_2,
+                "Error during attestation production";
+                "error" => e
+            )
+        })
+    }
+
+    /// Downloads the aggregate for `committee_index`, has it signed by the aggregator with `duty`
+    /// and then publishes it.
+    fn do_aggregation(
+        &self,
+        slot: Slot,
+        committee_index: CommitteeIndex,
+        duty: ValidatorDuty,
+        selection_proof: SelectionProof,
+    ) -> impl Future<Item = (), Error = ()> {
+        let service_1 = self.clone();
+        let service_2 = self.clone();
+        let log_1 = self.context.log.clone();
+        let log_2 = self.context.log.clone();
+
+        duty.validator_index
+            .ok_or_else(|| "Missing validator index for aggregator".to_string())
+            .into_future()
+            .and_then(move |validator_index| {
+                get_aggregate_with_retry(
+                    service_1.beacon_node.clone(),
+                    slot,
+                    committee_index,
+                    AGGREGATE_REQUEST_ATTEMPTS,
+                    AGGREGATE_RETRY_DELAY,
+                )
+                .and_then(move |aggregate| {
+                    service_1
+                        .validator_store
+                        .sign_aggregate_and_proof(
+                            &duty.validator_pubkey,
+                            validator_index as u64,
+                            aggregate,
+                            selection_proof,
+                        )
+                        .ok_or_else(|| "Failed to sign aggregate".to_string())
+                })
+            })
+            .and_then(move |signed_aggregate| {
+                let aggregate_to_publish = signed_aggregate.clone();
+
+                service_2
+                    .beacon_node
+                    .first_success(move |node| {
+                        node.http
+                            .validator()
+                            .publish_aggregate_and_proof(aggregate_to_publish.clone())
+                    })
+                    .map(|publish_status| (signed_aggregate, publish_status))
+                    .map_err(|e| format!("Failed to publish aggregate: {}", e))
+            })
+            .map(move |(signed_aggregate, publish_status)| {
+                let aggregate = &signed_aggregate.message.aggregate;
+
+                match publish_status {
+                    PublishStatus::Valid => info!(
+                        log_1,
+                        "Successfully published aggregate";
+                        "signatures" => aggregate.aggregation_bits.num_set_bits(),
+                        "head_block" => format!("{}", aggregate.data.beacon_block_root),
+                        "committee_index" => aggregate.data.index,
+                        "slot" => aggregate.data.slot.as_u64(),
+                    ),
+                    PublishStatus::Invalid(msg) => crit!(
+                        log_1,
+                        "Published aggregate was invalid";
+                        "message" => msg,
+                        "committee_index" => aggregate.data.index,
+                        "slot" => aggregate.data.slot.as_u64(),
+                    ),
+                    PublishStatus::Unknown => {
+                        crit!(log_1, "Unknown condition when publishing aggregate")
+                    }
                 }
             })
             .map_err(move |e| {
                 crit!(
                     log_2,
-                    "Error during attestation production";
+                    "Error during aggregation";
                     "error" => e
                 )
             })
     }
 }
 
+/// Downloads a single unsigned attestation for the committee with `committee_index` at `slot`
+/// from the `beacon_node`, has a copy signed with `sign` by each validator in `validator_duties`,
+/// then publishes all of the signed attestations in a single request.
+///
+/// Returns the published attestations, which each have a single aggregation bit set.
+fn attest_for_committee<E, F>(
+    beacon_node: FallbackBeaconNode<E>,
+    slot: Slot,
+    committee_index: CommitteeIndex,
+    validator_duties: Vec<ValidatorDuty>,
+    sign: F,
+    log: Logger,
+) -> impl Future<Item = (Vec<Attestation<E>>, PublishStatus), Error = String>
+where
+    E: EthSpec,
+    F: Fn(&PublicKey, usize, &mut Attestation<E>) -> Option<()>,
+{
+    let publish_node = beacon_node.clone();
+
+    beacon_node
+        .first_success(move |node| {
+            node.http
+                .validator()
+                .produce_attestation(slot, committee_index)
+        })
+        .map_err(|e| format!("Failed to produce attestation: {}", e))
+        .and_then(move |unsigned_attestation| {
+            let attestations = validator_duties
+                .iter()
+                .filter_map(|duty| {
+                    let (duty_slot, duty_committee_index, validator_committee_position) =
+                        if let Some(duties) = attestation_duties(duty) {
+                            duties
+                        } else {
+                            crit!(log, "Missing validator duties when signing");
+                            return None;
+                        };
+
+                    if duty_slot != slot || duty_committee_index != committee_index {
+                        crit!(log, "Inconsistent validator duties during signing");
+                        return None;
+                    }
+
+                    let mut attestation = unsigned_attestation.clone();
+                    if sign(
+                        &duty.validator_pubkey,
+                        validator_committee_position,
+                        &mut attestation,
+                    )
+                    .is_none()
+                    {
+                        crit!(log, "Failed to sign attestation");
+                        return None;
+                    }
+
+                    Some(attestation)
+                })
+                .collect::<Vec<_>>();
+
+            if attestations.is_empty() {
+                Err("No attestations were signed".to_string())
+            } else {
+                Ok(attestations)
+            }
+        })
+        .and_then(move |attestations| {
+            let attestations_to_publish = attestations.clone();
+
+            publish_node
+                .first_success(move |node| {
+                    node.http
+                        .validator()
+                        .publish_attestations(attestations_to_publish.clone())
+                })
+                .map(|publish_status| (attestations, publish_status))
+                .map_err(|e| format!("Failed to publish attestations: {}", e))
+        })
+}
+
+/// Requests the aggregate for the committee with `committee_index` at `slot` from the
+/// `beacon_node`, making up to `attempts` requests separated by `retry_delay`.
+fn get_aggregate_with_retry<E: EthSpec>(
+    beacon_node: FallbackBeaconNode<E>,
+    slot: Slot,
+    committee_index: CommitteeIndex,
+    attempts: usize,
+    retry_delay: Duration,
+) -> impl Future<Item = Attestation<E>, Error = String> {
+    loop_fn(1, move |attempt| {
+        beacon_node
+            .first_success(move |node| {
+                node.http
+                    .validator()
+                    .produce_aggregate_attestation(slot, committee_index)
+            })
+            .then(move |result| match result {
+                Ok(aggregate) => Either::A(future::ok(Loop::Break(aggregate))),
+                Err(e) if attempt >= attempts => Either::A(future::err(format!(
+                    "Failed to get aggregate after {} attempts: {}",
+                    attempts, e
+                ))),
+                Err(_) => Either::B(
+                    Delay::new(Instant::now() + retry_delay)
+                        .map_err(|e| format!("Failed to wait to retry aggregate: {}", e))
+                        .map(move |_| Loop::Continue(attempt + 1)),
+                ),
+            })
+    })
+}
+
+/// Polls the head of the `beacon_node` every `poll_interval` until it has a block at `slot` or
+/// `deadline` is reached, whichever is earlier.
+///
+/// Failures to read the head are ignored, so that a misbehaving beacon node can only delay
+/// attestation until the deadline.
+fn wait_for_block_or_deadline<E: EthSpec>(
+    beacon_node: FallbackBeaconNode<E>,
+    slot: Slot,
+    deadline: Instant,
+    poll_interval: Duration,
+    log: Logger,
+) -> impl Future<Item = AttestationTrigger, Error = ()> {
+    let wait_for_block = loop_fn((), move |()| {
+        let next_poll = Instant::now() + poll_interval;
+        let log = log.clone();
+
+        beacon_node
+            .first_success(|node| node.http.beacon().get_head())
+            .then(move |result| {
+                match result {
+                    Ok(head) if head.slot >= slot => {
+                        return Either::A(future::ok(Loop::Break(AttestationTrigger::BlockArrived)))
+                    }
+                    Ok(_) => (),
+                    Err(e) => debug!(
+                        log,
+                        "Failed to poll head for attestation";
+                        "error" => e,
+                    ),
+                }
+
+                Either::B(
+                    Delay::new(next_poll)
+                        .map_err(|_| ())
+                        .map(|_| Loop::Continue(())),
+                )
+            })
+    });
+
+    wait_for_block
+        .select(
+            Delay::new(deadline)
+                .map_err(|_| ())
+                .map(|_| AttestationTrigger::Deadline),
+        )
+        .map(|(trigger, _)| trigger)
+        .map_err(|_| ())
+}
+
 fn attestation_duties(duty: &ValidatorDuty) -> Option<(Slot, CommitteeIndex, usize)> {
     Some((
         duty.attestation_slot?,
@@ -314,3 +740,267 @@ fn attestation_duties(duty: &ValidatorDuty) -> Option<(Slot, CommitteeIndex, usi
         duty.attestation_committee_position?,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{json_response, MockBeaconNode, MockRequest};
+    use environment::{Environment, EnvironmentBuilder};
+    use remote_beacon_node::{CanonicalHeadResponse, RetryPolicy, Timeouts};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use types::{
+        test_utils::generate_deterministic_keypair, AggregateSignature, AttestationData, BitList,
+        Checkpoint, Hash256, MinimalEthSpec,
+    };
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    fn environment() -> Environment<MinimalEthSpec> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .and_then(|builder| builder.single_thread_tokio_runtime())
+            .and_then(|builder| builder.build())
+            .expect("should build environment")
+    }
+
+    /// Returns a mock beacon node with a head block at `slot`.
+    fn node_with_head_at(slot: Slot) -> MockBeaconNode {
+        let head = CanonicalHeadResponse {
+            slot,
+            block_root: Hash256::zero(),
+            state_root: Hash256::zero(),
+            finalized_slot: Slot::new(0),
+            finalized_block_root: Hash256::zero(),
+            justified_slot: Slot::new(0),
+            justified_block_root: Hash256::zero(),
+            previous_justified_slot: Slot::new(0),
+            previous_justified_block_root: Hash256::zero(),
+        };
+
+        MockBeaconNode::start("127.0.0.1:0".parse().unwrap(), move || json_response(&head))
+    }
+
+    /// Waits for a block at slot `1`, returning the trigger and the time spent waiting.
+    fn wait(
+        env: &mut Environment<MinimalEthSpec>,
+        url: String,
+        deadline: Duration,
+    ) -> (AttestationTrigger, Duration) {
+        let beacon_node = FallbackBeaconNode::new(
+            vec![url],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+        let log = env.core_context().log;
+        let start = Instant::now();
+
+        let trigger = env
+            .runtime()
+            .block_on(wait_for_block_or_deadline(
+                beacon_node,
+                Slot::new(1),
+                start + deadline,
+                POLL_INTERVAL,
+                log,
+            ))
+            .expect("should wait for block or deadline");
+
+        (trigger, start.elapsed())
+    }
+
+    #[test]
+    fn attests_early_when_block_arrives() {
+        let mut env = environment();
+        let node = node_with_head_at(Slot::new(1));
+
+        let (trigger, elapsed) = wait(&mut env, node.url(), Duration::from_secs(5));
+
+        assert_eq!(trigger, AttestationTrigger::BlockArrived);
+        assert!(
+            elapsed < Duration::from_secs(5),
+            "should not wait for the deadline"
+        );
+    }
+
+    #[test]
+    fn attests_at_deadline_without_block() {
+        let mut env = environment();
+        let node = node_with_head_at(Slot::new(0));
+        let deadline = POLL_INTERVAL * 4;
+
+        let (trigger, elapsed) = wait(&mut env, node.url(), deadline);
+
+        assert_eq!(trigger, AttestationTrigger::Deadline);
+        assert!(elapsed >= deadline, "should wait until the deadline");
+    }
+
+    #[test]
+    fn attests_at_deadline_when_node_is_down() {
+        let mut env = environment();
+        let node = node_with_head_at(Slot::new(1));
+        let url = node.url();
+        drop(node);
+
+        let (trigger, _) = wait(&mut env, url, POLL_INTERVAL * 4);
+
+        assert_eq!(trigger, AttestationTrigger::Deadline);
+    }
+
+    /// Returns an empty aggregate for committee `0` at slot `1`.
+    fn aggregate() -> Attestation<MinimalEthSpec> {
+        Attestation {
+            aggregation_bits: BitList::with_capacity(4).expect("should create bitlist"),
+            data: AttestationData {
+                slot: Slot::new(1),
+                index: 0,
+                beacon_block_root: Hash256::zero(),
+                source: Checkpoint::default(),
+                target: Checkpoint::default(),
+            },
+            signature: AggregateSignature::new(),
+        }
+    }
+
+    /// Returns a mock beacon node which responds with `404` to the first `failed_requests`
+    /// requests and `aggregate()` thereafter, along with a count of the requests it has received.
+    fn node_with_aggregate_after(failed_requests: usize) -> (MockBeaconNode, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let node_requests = requests.clone();
+
+        let node = MockBeaconNode::start("127.0.0.1:0".parse().unwrap(), move || {
+            if node_requests.fetch_add(1, Ordering::Relaxed) < failed_requests {
+                (404, "\"No attestations are known\"".to_string())
+            } else {
+                json_response(&aggregate())
+            }
+        });
+
+        (node, requests)
+    }
+
+    fn get_aggregate(
+        env: &mut Environment<MinimalEthSpec>,
+        url: String,
+        attempts: usize,
+    ) -> Result<Attestation<MinimalEthSpec>, String> {
+        let beacon_node = FallbackBeaconNode::new(
+            vec![url],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+
+        env.runtime().block_on(get_aggregate_with_retry(
+            beacon_node,
+            Slot::new(1),
+            0,
+            attempts,
+            POLL_INTERVAL,
+        ))
+    }
+
+    #[test]
+    fn retries_until_aggregate_is_known() {
+        let mut env = environment();
+        let (node, requests) = node_with_aggregate_after(2);
+
+        assert_eq!(get_aggregate(&mut env, node.url(), 3), Ok(aggregate()));
+        assert_eq!(requests.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn gives_up_when_aggregate_is_unknown() {
+        let mut env = environment();
+        let (node, requests) = node_with_aggregate_after(usize::max_value());
+
+        assert!(get_aggregate(&mut env, node.url(), 2).is_err());
+        assert_eq!(
+            requests.load(Ordering::Relaxed),
+            2,
+            "should not request more than the number of attempts"
+        );
+    }
+
+    /// Returns the duty of a validator at `position` in committee `0` at slot `1`.
+    fn committee_duty(position: usize) -> ValidatorDuty {
+        ValidatorDuty {
+            validator_pubkey: generate_deterministic_keypair(position).pk,
+            validator_index: Some(position),
+            attestation_slot: Some(Slot::new(1)),
+            attestation_committee_index: Some(0),
+            attestation_committee_position: Some(position),
+            attestation_committee_length: Some(4),
+            attestation_committees_at_slot: Some(1),
+            block_proposal_slots: vec![],
+            attestation_dependent_root: None,
+            block_proposal_dependent_root: None,
+        }
+    }
+
+    #[test]
+    fn fetches_and_publishes_once_per_committee() {
+        let mut env = environment();
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let published = Arc::new(Mutex::new(vec![]));
+        let node_fetches = fetches.clone();
+        let node_published = published.clone();
+
+        let node = MockBeaconNode::start_with_requests(
+            "127.0.0.1:0".parse().unwrap(),
+            move |request: &MockRequest| match (request.method.as_str(), request.path.as_str()) {
+                ("GET", path) if path.starts_with("/validator/attestation?") => {
+                    node_fetches.fetch_add(1, Ordering::Relaxed);
+                    json_response(&aggregate())
+                }
+                ("POST", "/validator/attestations") => {
+                    let attestations: Vec<Attestation<MinimalEthSpec>> =
+                        serde_json::from_slice(&request.body).expect("should decode attestations");
+                    node_published.lock().push(attestations);
+                    json_response(&())
+                }
+                _ => (404, "\"Unexpected request\"".to_string()),
+            },
+        );
+        let beacon_node = FallbackBeaconNode::new(
+            vec![node.url()],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+        let log = env.core_context().log;
+
+        let (attestations, publish_status) = env
+            .runtime()
+            .block_on(attest_for_committee(
+                beacon_node,
+                Slot::new(1),
+                0,
+                (0..3).map(committee_duty).collect(),
+                |_, position, attestation: &mut Attestation<MinimalEthSpec>| {
+                    attestation.aggregation_bits.set(position, true).ok()
+                },
+                log,
+            ))
+            .expect("should attest for committee");
+
+        assert_eq!(publish_status, PublishStatus::Valid);
+        assert_eq!(fetches.load(Ordering::Relaxed), 1, "should fetch data once");
+
+        let published = published.lock();
+        assert_eq!(published.len(), 1, "should publish once");
+        assert_eq!(published[0], attestations);
+        assert_eq!(
+            attestations.len(),
+            3,
+            "should sign an attestation per validator"
+        );
+        for (position, attestation) in attestations.iter().enumerate() {
+            assert_eq!(attestation.aggregation_bits.num_set_bits(), 1);
+            assert_eq!(attestation.aggregation_bits.get(position), Ok(true));
+        }
+    }
+}
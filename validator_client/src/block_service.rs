@@ -1,25 +1,44 @@
-use crate::{duties_service::DutiesService, validator_store::ValidatorStore};
+use crate::{
+    duties_service::DutiesService, fallback_beacon_node::FallbackBeaconNode, metrics,
+    validator_store::ValidatorStore,
+};
 use environment::RuntimeContext;
 use exit_future::Signal;
-use futures::{stream, Future, IntoFuture, Stream};
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
-use slog::{crit, error, info, trace};
+use futures::{
+    future::{self, loop_fn, Loop},
+    stream, Future, IntoFuture, Stream,
+};
+use parking_lot::RwLock;
+use remote_beacon_node::PublishStatus;
+use slog::{crit, error, info, trace, warn};
 use slot_clock::SlotClock;
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::timer::Interval;
-use types::{ChainSpec, EthSpec};
+use tokio::timer::{Delay, Interval};
+use types::{ChainSpec, EthSpec, PublicKey, SignedBeaconBlock, Slot};
 
 /// Delay this period of time after the slot starts. This allows the node to process the new slot.
 const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(100);
 
+/// The maximum number of times we will try to publish a single signed block.
+const MAX_PUBLISH_ATTEMPTS: usize = 4;
+
+/// The delay before the first publish retry. Each subsequent retry doubles this delay.
+const PUBLISH_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Signed blocks are kept in the cache for this many slots after their own slot.
+const SIGNED_BLOCK_CACHE_DEPTH: u64 = 32;
+
+type SignedBlockCache<E> = HashMap<(Slot, PublicKey), SignedBeaconBlock<E>>;
+
 /// Builds a `BlockService`.
 pub struct BlockServiceBuilder<T, E: EthSpec> {
     duties_service: Option<DutiesService<T, E>>,
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<Arc<T>>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_node: Option<FallbackBeaconNode<E>>,
     context: Option<RuntimeContext<E>>,
 }
 
@@ -49,7 +68,7 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
+    pub fn beacon_node(mut self, beacon_node: FallbackBeaconNode<E>) -> Self {
         self.beacon_node = Some(beacon_node);
         self
     }
@@ -77,6 +96,7 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build BlockService without runtime_context")?,
+                signed_blocks: RwLock::new(HashMap::new()),
             }),
         })
     }
@@ -87,8 +107,14 @@ pub struct Inner<T, E: EthSpec> {
     duties_service: DutiesService<T, E>,
     validator_store: ValidatorStore<T, E>,
     slot_clock: Arc<T>,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_node: FallbackBeaconNode<E>,
     context: RuntimeContext<E>,
+    /// Blocks which have been signed by a local validator, keyed by slot and proposer.
+    ///
+    /// Once a block is in this cache it is the _only_ block that validator will publish for that
+    /// slot. We never request another block from the beacon node for the same slot, since signing
+    /// two different blocks would be a slashable offence.
+    signed_blocks: RwLock<SignedBlockCache<E>>,
 }
 
 /// Attempts to produce attestations for any block producer(s) at the start of the epoch.
@@ -167,9 +193,19 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
             })
             .into_future()
             .and_then(move |slot| {
-                let iter = service.duties_service.block_producers(slot).into_iter();
+                service.prune_signed_blocks(slot);
 
-                if iter.len() == 0 {
+                let mut iter = service.duties_service.block_producers(slot).into_iter();
+
+                if iter.len() > 0 && !service.duties_service.beacon_node_is_synced() {
+                    warn!(
+                        log_2,
+                        "Skipping block production";
+                        "reason" => "beacon node is not synced",
+                        "slot" => slot.as_u64(),
+                    );
+                    iter = vec![].into_iter();
+                } else if iter.len() == 0 {
                     trace!(
                         log_2,
                         "No local block proposers for this slot";
@@ -190,47 +226,11 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
                     let log_2 = service.context.log.clone();
                     let service_1 = service.clone();
                     let service_2 = service.clone();
-                    let service_3 = service.clone();
 
                     block_producers.next().map(move |validator_pubkey| {
                         service_1
-                            .validator_store
-                            .randao_reveal(&validator_pubkey, slot.epoch(E::slots_per_epoch()))
-                            .ok_or_else(|| "Unable to produce randao reveal".to_string())
-                            .into_future()
-                            .and_then(move |randao_reveal| {
-                                service_1
-                                    .beacon_node
-                                    .http
-                                    .validator()
-                                    .produce_block(slot, randao_reveal)
-                                    .map_err(|e| {
-                                        format!(
-                                            "Error from beacon node when producing block: {:?}",
-                                            e
-                                        )
-                                    })
-                            })
-                            .and_then(move |block| {
-                                service_2
-                                    .validator_store
-                                    .sign_block(&validator_pubkey, block)
-                                    .ok_or_else(|| "Unable to sign block".to_string())
-                            })
-                            .and_then(move |block| {
-                                service_3
-                                    .beacon_node
-                                    .http
-                                    .validator()
-                                    .publish_block(block.clone())
-                                    .map(|publish_status| (block, publish_status))
-                                    .map_err(|e| {
-                                        format!(
-                                            "Error from beacon node when publishing block: {:?}",
-                                            e
-                                        )
-                                    })
-                            })
+                            .get_or_produce_signed_block(slot, validator_pubkey)
+                            .and_then(move |block| service_2.publish_block_with_retry(slot, block))
                             .map(move |(block, publish_status)| match publish_status {
                                 PublishStatus::Valid => info!(
                                     log_1,
@@ -263,4 +263,457 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
                 .map(|_| ())
             })
     }
+
+    /// Returns the block that `validator_pubkey` has already signed for `slot`, if any. Otherwise,
+    /// requests a new block from the beacon node, signs it and stores it in the cache.
+    fn get_or_produce_signed_block(
+        &self,
+        slot: Slot,
+        validator_pubkey: PublicKey,
+    ) -> Box<dyn Future<Item = SignedBeaconBlock<E>, Error = String> + Send> {
+        let cache_key = (slot, validator_pubkey.clone());
+
+        if let Some(block) = self.signed_blocks.read().get(&cache_key).cloned() {
+            warn!(
+                self.context.log,
+                "Re-using previously signed block";
+                "slot" => slot.as_u64(),
+            );
+            return Box::new(future::ok(block));
+        }
+
+        let service_1 = self.clone();
+        let service_2 = self.clone();
+
+        Box::new(
+            self.validator_store
+                .randao_reveal(&validator_pubkey, slot.epoch(E::slots_per_epoch()))
+                .ok_or_else(|| "Unable to produce randao reveal".to_string())
+                .into_future()
+                .and_then(move |randao_reveal| {
+                    service_1
+                        .beacon_node
+                        .first_success(move |node| {
+                            node.http
+                                .validator()
+                                .produce_block(slot, randao_reveal.clone())
+                        })
+                        .map_err(|e| format!("Error from beacon node when producing block: {}", e))
+                })
+                .and_then(move |block| {
+                    let mut signed_blocks = service_2.signed_blocks.write();
+
+                    // Another task may have signed a block for this slot whilst we were waiting
+                    // for the beacon node. If so, discard our block and use theirs.
+                    if let Some(existing) = signed_blocks.get(&cache_key) {
+                        return Ok(existing.clone());
+                    }
+
+                    let signed_block = service_2
+                        .validator_store
+                        .sign_block(&validator_pubkey, block)
+                        .ok_or_else(|| "Unable to sign block".to_string())?;
+
+                    signed_blocks.insert(cache_key, signed_block.clone());
+
+                    Ok(signed_block)
+                }),
+        )
+    }
+
+    /// Publishes `block` to the beacon node, retrying with an exponential backoff if the request
+    /// fails. Retries cease once `MAX_PUBLISH_ATTEMPTS` is reached or `slot` has ended.
+    fn publish_block_with_retry(
+        &self,
+        slot: Slot,
+        block: SignedBeaconBlock<E>,
+    ) -> impl Future<Item = (SignedBeaconBlock<E>, PublishStatus), Error = String> {
+        let service = self.clone();
+
+        loop_fn((block, 0), move |(block, attempt)| {
+            let service = service.clone();
+            let log = service.context.log.clone();
+
+            let block_to_publish = block.clone();
+
+            service
+                .beacon_node
+                .first_success(move |node| {
+                    node.http
+                        .validator()
+                        .publish_block(block_to_publish.clone())
+                })
+                .then(move |result| {
+                    let future: Box<dyn Future<Item = Loop<_, _>, Error = String> + Send> =
+                        match result {
+                            Ok(publish_status) => {
+                                if attempt > 0 {
+                                    metrics::inc_counter(&metrics::BLOCK_PUBLISH_LATE);
+                                }
+
+                                Box::new(future::ok(Loop::Break((block, publish_status))))
+                            }
+                            Err(e) => {
+                                let still_in_slot = service.slot_clock.now() == Some(slot);
+
+                                if attempt + 1 < MAX_PUBLISH_ATTEMPTS && still_in_slot {
+                                    let delay = PUBLISH_RETRY_DELAY * 2_u32.pow(attempt as u32);
+
+                                    warn!(
+                                        log,
+                                        "Failed to publish block, retrying";
+                                        "error" => e,
+                                        "attempt" => attempt + 1,
+                                        "retry_delay_ms" => delay.as_millis() as u64,
+                                        "slot" => slot.as_u64(),
+                                    );
+
+                                    metrics::inc_counter(&metrics::BLOCK_PUBLISH_RETRIES);
+
+                                    Box::new(
+                                        Delay::new(Instant::now() + delay)
+                                            .map_err(|e| {
+                                                format!("Failed to trigger delay: {:?}", e)
+                                            })
+                                            .map(move |_| Loop::Continue((block, attempt + 1))),
+                                    )
+                                } else {
+                                    metrics::inc_counter(&metrics::BLOCK_PUBLISH_FAILURES);
+
+                                    Box::new(future::err(format!(
+                                        "Error from beacon node when publishing block \
+                                         (attempts: {}): {}",
+                                        attempt + 1,
+                                        e
+                                    )))
+                                }
+                            }
+                        };
+
+                    future
+                })
+        })
+    }
+
+    /// Removes any signed blocks that are older than `SIGNED_BLOCK_CACHE_DEPTH` slots.
+    fn prune_signed_blocks(&self, current_slot: Slot) {
+        let prune_below = current_slot.saturating_sub(SIGNED_BLOCK_CACHE_DEPTH);
+
+        self.signed_blocks
+            .write()
+            .retain(|(slot, _pubkey), _block| *slot >= prune_below);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::duties_service::DutiesServiceBuilder;
+    use crate::fork_service::{ForkService, ForkServiceBuilder};
+    use crate::test_utils::{json_response, MockBeaconNode, MockRequest};
+    use environment::{Environment, EnvironmentBuilder};
+    use parking_lot::Mutex;
+    use remote_beacon_node::{RetryPolicy, Timeouts, ValidatorDuty};
+    use slot_clock::TestingSlotClock;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempdir::TempDir;
+    use types::{
+        test_utils::generate_deterministic_keypair, BeaconBlock, Fork, MinimalEthSpec,
+        PublicKeyBytes, Signature,
+    };
+
+    type E = MinimalEthSpec;
+
+    fn environment() -> Environment<E> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .and_then(|builder| builder.single_thread_tokio_runtime())
+            .and_then(|builder| builder.build())
+            .expect("should build environment")
+    }
+
+    /// Returns a mock beacon node which responds with `500` to the first `failed_publishes`
+    /// attempts to publish a block and `200` thereafter, along with a count of the requests it
+    /// has received.
+    fn node_publishing_after(failed_publishes: usize) -> (MockBeaconNode, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let node_requests = requests.clone();
+
+        let node = MockBeaconNode::start_with_requests(
+            "127.0.0.1:0".parse().unwrap(),
+            move |request: &MockRequest| {
+                let previous_requests = node_requests.fetch_add(1, Ordering::Relaxed);
+                if request.method == "POST" && previous_requests >= failed_publishes {
+                    (200, "null".to_string())
+                } else {
+                    (500, "\"Unable to publish block\"".to_string())
+                }
+            },
+        );
+
+        (node, requests)
+    }
+
+    /// Returns a block service which uses the beacon node at `url`, with a slot clock at `slot`,
+    /// along with the fork service of its validator store (which is not started).
+    ///
+    /// The temporary directory of the validator store is returned, since it is removed once
+    /// dropped.
+    fn block_service(
+        env: &Environment<E>,
+        url: String,
+        slot: Slot,
+    ) -> (
+        BlockService<TestingSlotClock, E>,
+        ForkService<TestingSlotClock, E>,
+        TempDir,
+    ) {
+        let spec = env.eth2_config.spec.clone();
+        let dir = TempDir::new("block_service").expect("should create temp dir");
+
+        let beacon_node = FallbackBeaconNode::new(
+            vec![url],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+        let slot_clock = || {
+            TestingSlotClock::new(
+                slot,
+                Duration::from_secs(0),
+                Duration::from_millis(spec.milliseconds_per_slot),
+            )
+        };
+        let fork_service = ForkServiceBuilder::new()
+            .slot_clock(slot_clock())
+            .beacon_node(beacon_node.clone())
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build fork service");
+        let validator_store = ValidatorStore::empty(
+            dir.path().to_path_buf(),
+            spec.clone(),
+            fork_service.clone(),
+            env.core_context().log,
+        )
+        .expect("should create validator store");
+        let duties_service = DutiesServiceBuilder::new()
+            .slot_clock(slot_clock())
+            .validator_store(validator_store.clone())
+            .beacon_node(beacon_node.clone())
+            .runtime_context(env.core_context())
+            .allow_unsynced_beacon_node(true)
+            .build()
+            .expect("should build duties service");
+        let service = BlockServiceBuilder::new()
+            .duties_service(duties_service)
+            .validator_store(validator_store)
+            .slot_clock(slot_clock())
+            .beacon_node(beacon_node)
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build block service");
+
+        (service, fork_service, dir)
+    }
+
+    fn signed_block(env: &Environment<E>, slot: Slot) -> SignedBeaconBlock<E> {
+        let mut block = BeaconBlock::empty(&env.eth2_config.spec);
+        block.slot = slot;
+
+        SignedBeaconBlock {
+            message: block,
+            signature: Signature::empty_signature(),
+        }
+    }
+
+    #[test]
+    fn retries_publish_until_accepted() {
+        let mut env = environment();
+        let (node, requests) = node_publishing_after(2);
+        let slot = Slot::new(1);
+        let (service, _fork_service, _dir) = block_service(&env, node.url(), slot);
+        let block = signed_block(&env, slot);
+
+        let (published, publish_status) = env
+            .runtime()
+            .block_on(service.publish_block_with_retry(slot, block.clone()))
+            .expect("should publish block");
+
+        assert_eq!(published, block, "should publish the given block");
+        assert_eq!(publish_status, PublishStatus::Valid);
+        assert_eq!(
+            requests.load(Ordering::Relaxed),
+            3,
+            "should retry each failed publish"
+        );
+    }
+
+    #[test]
+    fn stops_retrying_publish_after_slot() {
+        let mut env = environment();
+        let (node, requests) = node_publishing_after(usize::max_value());
+        let slot = Slot::new(1);
+        let (service, _fork_service, _dir) = block_service(&env, node.url(), slot + 1);
+
+        assert!(
+            env.runtime()
+                .block_on(service.publish_block_with_retry(slot, signed_block(&env, slot)))
+                .is_err(),
+            "should fail to publish block"
+        );
+        assert_eq!(
+            requests.load(Ordering::Relaxed),
+            1,
+            "should not retry once the slot has ended"
+        );
+    }
+
+    #[test]
+    fn reuses_signed_block_for_slot() {
+        let mut env = environment();
+        let (node, requests) = node_publishing_after(0);
+        let slot = Slot::new(1);
+        let (service, _fork_service, _dir) = block_service(&env, node.url(), slot);
+        let pubkey = generate_deterministic_keypair(0).pk;
+        let block = signed_block(&env, slot);
+
+        service
+            .signed_blocks
+            .write()
+            .insert((slot, pubkey.clone()), block.clone());
+
+        assert_eq!(
+            env.runtime()
+                .block_on(service.get_or_produce_signed_block(slot, pubkey)),
+            Ok(block),
+            "should re-use the signed block"
+        );
+        assert_eq!(
+            requests.load(Ordering::Relaxed),
+            0,
+            "should not request another block from the beacon node"
+        );
+
+        service.prune_signed_blocks(slot + SIGNED_BLOCK_CACHE_DEPTH + 1);
+        assert!(
+            service.signed_blocks.read().is_empty(),
+            "should prune blocks older than the cache depth"
+        );
+    }
+    #[test]
+    fn publishes_one_signed_block_per_slot() {
+        let mut env = environment();
+        let spec = env.eth2_config.spec.clone();
+        let slot = Slot::new(1);
+        let keypair = generate_deterministic_keypair(0);
+        let block = {
+            let mut block = BeaconBlock::<E>::empty(&spec);
+            block.slot = slot;
+            block
+        };
+
+        let duties = vec![ValidatorDuty {
+            validator_pubkey: PublicKeyBytes::from(keypair.pk.clone()),
+            validator_index: Some(0),
+            attestation_slot: None,
+            attestation_committee_index: None,
+            attestation_committee_position: None,
+            attestation_committee_length: None,
+            attestation_committees_at_slot: None,
+            block_proposal_slots: vec![slot],
+            attestation_dependent_root: None,
+            block_proposal_dependent_root: None,
+        }];
+        let duties_requests = AtomicUsize::new(0);
+        let node_block = block.clone();
+        let produced = Arc::new(AtomicUsize::new(0));
+        let node_produced = produced.clone();
+        let published = Arc::new(Mutex::new(vec![]));
+        let node_published = published.clone();
+
+        // Fail every attempt of the first update to publish its block.
+        let node = MockBeaconNode::start_with_requests(
+            "127.0.0.1:0".parse().unwrap(),
+            move |request: &MockRequest| match (request.method.as_str(), request.path.as_str()) {
+                ("GET", path) if path.starts_with("/beacon/fork") => {
+                    json_response(&Fork::default())
+                }
+                // Only the duties of the current epoch are requested first.
+                ("POST", "/validator/duties") => {
+                    if duties_requests.fetch_add(1, Ordering::Relaxed) == 0 {
+                        json_response(&duties)
+                    } else {
+                        json_response(&Vec::<ValidatorDuty>::new())
+                    }
+                }
+                ("GET", path) if path.starts_with("/validator/block") => {
+                    node_produced.fetch_add(1, Ordering::Relaxed);
+                    json_response(&node_block)
+                }
+                ("POST", "/validator/block") => {
+                    let mut published = node_published.lock();
+                    published.push(request.body.clone());
+                    if published.len() > MAX_PUBLISH_ATTEMPTS {
+                        (200, "null".to_string())
+                    } else {
+                        (500, "\"Unable to publish block\"".to_string())
+                    }
+                }
+                _ => (404, "\"Not found\"".to_string()),
+            },
+        );
+        let (service, fork_service, dir) = block_service(&env, node.url(), slot);
+
+        let _exit_signal = fork_service
+            .start_update_service(&spec)
+            .expect("should start fork service");
+        assert!(service
+            .validator_store
+            .add_validator(keypair.clone(), dir.path().to_path_buf()));
+        env.runtime()
+            .block_on(
+                service
+                    .duties_service
+                    .update_validators(vec![keypair.pk.clone()]),
+            )
+            .expect("should download duties");
+        assert_eq!(
+            service.duties_service.block_producers(slot),
+            vec![keypair.pk]
+        );
+
+        env.runtime()
+            .block_on(service.clone().do_update())
+            .expect("should run first update");
+        assert_eq!(
+            published.lock().len(),
+            MAX_PUBLISH_ATTEMPTS,
+            "should fail every publish of the first update"
+        );
+
+        env.runtime()
+            .block_on(service.clone().do_update())
+            .expect("should run second update");
+
+        let published = published.lock();
+        assert_eq!(
+            published.len(),
+            MAX_PUBLISH_ATTEMPTS + 1,
+            "should publish again in the second update"
+        );
+        assert!(
+            published.iter().all(|body| *body == published[0]),
+            "should publish a single signed block"
+        );
+        let signed_block: SignedBeaconBlock<E> =
+            serde_json::from_slice(&published[0]).expect("should publish a signed block");
+        assert_eq!(signed_block.message, block);
+        assert_eq!(
+            produced.load(Ordering::Relaxed),
+            1,
+            "should request a block from the beacon node once"
+        );
+    }
 }
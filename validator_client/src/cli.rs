@@ -14,12 +14,49 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value(&DEFAULT_HTTP_SERVER)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("fallback-servers")
+                .long("fallback-servers")
+                .value_name("NETWORK_ADDRESSES")
+                .help("Comma-separated addresses of BeaconNodes to use whenever the --server \
+                       is unreachable, in order of preference.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("allow-unsynced")
                 .long("allow-unsynced")
                 .help("If present, the validator client will still poll for duties if the beacon
                       node is not synced.")
         )
+        .arg(
+            Arg::with_name("clock-drift-threshold")
+                .long("clock-drift-threshold")
+                .value_name("MILLISECONDS")
+                .help("The number of milliseconds by which the local clock may differ from that \
+                       of the beacon node before a warning is logged.")
+                .takes_value(true)
+                .default_value("500"),
+        )
+        .arg(
+            Arg::with_name("validators-dir")
+                .long("validators-dir")
+                .value_name("VALIDATORS_DIR")
+                .help("Loads the validators from the EIP-2335 `keystore-*.json` files in this \
+                       directory, instead of from the data directory. The password of each \
+                       keystore is read from a sibling `.pass` file or from --secrets-dir. \
+                       The directory is rescanned each slot, so keystores may be added or \
+                       removed without a restart.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("secrets-dir")
+                .long("secrets-dir")
+                .value_name("SECRETS_DIR")
+                .help("A directory of keystore passwords, each in a file named after the \
+                       0x-prefixed public key of its keystore.")
+                .requires("validators-dir")
+                .takes_value(true),
+        )
         /*
          * The "testnet" sub-command.
          *
@@ -49,6 +86,22 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     .help("Path to a YAML file."))
             )
         )
+        /*
+         * The "exit" sub-command.
+         *
+         * Used for voluntarily exiting a validator.
+         */
+        .subcommand(SubCommand::with_name("exit")
+            .about("Signs a voluntary exit for one of the validators of this client and \
+                    publishes it via the beacon node, after asking for confirmation. An exit \
+                    cannot be reversed.")
+            .arg(Arg::with_name("pubkey")
+                .long("pubkey")
+                .value_name("PUBKEY")
+                .required(true)
+                .takes_value(true)
+                .help("The 0x-prefixed public key of the validator to exit."))
+        )
         .subcommand(SubCommand::with_name("sign_block")
             .about("Connects to the beacon server, requests a new block (after providing reveal),\
             and prints the signed block to standard out")
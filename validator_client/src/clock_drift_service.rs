@@ -0,0 +1,399 @@
+use crate::{fallback_beacon_node::FallbackBeaconNode, metrics};
+use environment::RuntimeContext;
+use exit_future::Signal;
+use futures::{Future, Stream};
+use remote_beacon_node::SyncStatus;
+use slog::{crit, debug, info, warn};
+use slot_clock::SlotClock;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::timer::Interval;
+use types::{ChainSpec, EthSpec};
+
+/// Delay this period of time after the slot starts. This allows the node to process the new slot.
+const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(80);
+
+/// Builds a `ClockDriftService`.
+pub struct ClockDriftServiceBuilder<T, E: EthSpec> {
+    threshold: u64,
+    genesis_time: Option<u64>,
+    slot_clock: Option<T>,
+    beacon_node: Option<FallbackBeaconNode<E>>,
+    context: Option<RuntimeContext<E>>,
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> ClockDriftServiceBuilder<T, E> {
+    pub fn new() -> Self {
+        Self {
+            threshold: 0,
+            genesis_time: None,
+            slot_clock: None,
+            beacon_node: None,
+            context: None,
+        }
+    }
+
+    /// Sets the number of milliseconds by which the clocks may disagree before a warning is
+    /// logged.
+    pub fn threshold(mut self, threshold: u64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the genesis time of the chain, in seconds since the UNIX epoch.
+    pub fn genesis_time(mut self, genesis_time: u64) -> Self {
+        self.genesis_time = Some(genesis_time);
+        self
+    }
+
+    pub fn slot_clock(mut self, slot_clock: T) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn beacon_node(mut self, beacon_node: FallbackBeaconNode<E>) -> Self {
+        self.beacon_node = Some(beacon_node);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<E>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<ClockDriftService<T, E>, String> {
+        Ok(ClockDriftService {
+            inner: Arc::new(Inner {
+                threshold: self.threshold,
+                genesis_time: self
+                    .genesis_time
+                    .ok_or_else(|| "Cannot build ClockDriftService without genesis_time")?,
+                slot_clock: self
+                    .slot_clock
+                    .ok_or_else(|| "Cannot build ClockDriftService without slot_clock")?,
+                beacon_node: self
+                    .beacon_node
+                    .ok_or_else(|| "Cannot build ClockDriftService without beacon_node")?,
+                context: self
+                    .context
+                    .ok_or_else(|| "Cannot build ClockDriftService without runtime_context")?,
+            }),
+        })
+    }
+}
+
+/// Helper to minimise `Arc` usage.
+pub struct Inner<T, E: EthSpec> {
+    threshold: u64,
+    genesis_time: u64,
+    slot_clock: T,
+    beacon_node: FallbackBeaconNode<E>,
+    context: RuntimeContext<E>,
+}
+
+/// Compares the wall-clock time of the validator client with the current slot of the beacon node
+/// at the start of each epoch, warning if they disagree by more than a threshold.
+///
+/// A drifting clock (e.g., due to a broken NTP configuration) causes duties to be performed at
+/// the wrong time, which leads to missed blocks and attestations.
+pub struct ClockDriftService<T, E: EthSpec> {
+    inner: Arc<Inner<T, E>>,
+}
+
+impl<T, E: EthSpec> Clone for ClockDriftService<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, E: EthSpec> Deref for ClockDriftService<T, E> {
+    type Target = Inner<T, E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> ClockDriftService<T, E> {
+    /// Starts the service that checks the clock drift immediately, then at the start of each
+    /// epoch.
+    pub fn start_update_service(&self, spec: &ChainSpec) -> Result<Signal, String> {
+        let log = self.context.log.clone();
+        let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+
+        let duration_to_next_epoch = self
+            .slot_clock
+            .duration_to_next_epoch(E::slots_per_epoch())
+            .ok_or_else(|| "Unable to determine duration to next epoch".to_string())?;
+
+        let interval = Interval::new(
+            Instant::now() + duration_to_next_epoch + TIME_DELAY_FROM_SLOT,
+            slot_duration * E::slots_per_epoch() as u32,
+        );
+
+        let (exit_signal, exit_fut) = exit_future::signal();
+        let service = self.clone();
+        let log_1 = log.clone();
+        let log_2 = log.clone();
+
+        self.context.executor.spawn(self.check_and_log());
+        self.context.executor.spawn(
+            exit_fut
+                .until(
+                    interval
+                        .map_err(move |e| {
+                            crit! {
+                                log_1,
+                                "Timer thread failed";
+                                "error" => format!("{}", e)
+                            }
+                        })
+                        .for_each(move |_| {
+                            service.context.executor.spawn(service.check_and_log());
+
+                            Ok(())
+                        }),
+                )
+                .map(move |_| info!(log_2, "Shutdown complete")),
+        );
+
+        Ok(exit_signal)
+    }
+
+    /// Runs `Self::do_check`, logging any failure.
+    fn check_and_log(&self) -> impl Future<Item = (), Error = ()> {
+        let log = self.context.log.clone();
+
+        self.do_check().map(|_| ()).map_err(move |e| {
+            debug!(
+                log,
+                "Unable to check clock drift";
+                "error" => e
+            )
+        })
+    }
+
+    /// Compares the local wall-clock time with the slot of the beacon node, returning the drift in
+    /// milliseconds (see `clock_drift`). The drift is `None` prior to genesis.
+    fn do_check(&self) -> impl Future<Item = Option<i64>, Error = String> {
+        let service = self.clone();
+
+        self.beacon_node
+            .first_success(|node| node.http.node().get_syncing())
+            .map_err(|e| format!("Error retrieving sync status: {}", e))
+            .and_then(move |status| {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(|e| format!("Unable to read system time: {:?}", e))?;
+                let drift = clock_drift(
+                    now,
+                    Duration::from_secs(service.genesis_time),
+                    service.slot_clock.slot_duration(),
+                    &status,
+                );
+
+                if let Some(drift) = drift {
+                    metrics::set_gauge(&metrics::CLOCK_DRIFT_MILLISECONDS, drift);
+
+                    if drift.abs() as u64 > service.threshold {
+                        warn!(
+                            service.context.log,
+                            "Clock drift detected";
+                            "msg" => "check that NTP is working on this host and the beacon node",
+                            "local_slot" => service.slot_clock.now().map(|slot| slot.as_u64()),
+                            "drift_ms" => drift,
+                        );
+                    }
+                }
+
+                Ok(drift)
+            })
+    }
+}
+
+/// Returns the number of milliseconds by which `now` (the duration since the UNIX epoch) falls
+/// after the end of the wall-clock slot of the beacon node with the given `status`, or before its
+/// start (negative), or `None` if the beacon node is prior to genesis.
+///
+/// A slot starts at `genesis + slot * slot_duration`. The beacon node only reports whole slots,
+/// so any time within its slot has no drift.
+///
+/// A head from a later slot than the current slot of the beacon node also indicates that the local
+/// clock is behind.
+fn clock_drift(
+    now: Duration,
+    genesis: Duration,
+    slot_duration: Duration,
+    status: &SyncStatus,
+) -> Option<i64> {
+    match status {
+        SyncStatus::PreGenesis { .. } => None,
+        SyncStatus::PostGenesis {
+            head_slot,
+            current_slot,
+            ..
+        } => {
+            let slot = std::cmp::max(*head_slot, *current_slot).as_u64();
+            let now = now.as_millis() as i64;
+            let slot_start = (genesis + slot_duration * slot as u32).as_millis() as i64;
+            let slot_end = slot_start + slot_duration.as_millis() as i64;
+
+            if now < slot_start {
+                Some(now - slot_start)
+            } else if now >= slot_end {
+                Some(now - slot_end)
+            } else {
+                Some(0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{json_response, MockBeaconNode};
+    use environment::{Environment, EnvironmentBuilder};
+    use remote_beacon_node::{RetryPolicy, Timeouts};
+    use slot_clock::TestingSlotClock;
+    use types::{MinimalEthSpec, Slot};
+
+    fn environment() -> Environment<MinimalEthSpec> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .and_then(|builder| builder.single_thread_tokio_runtime())
+            .and_then(|builder| builder.build())
+            .expect("should build environment")
+    }
+
+    fn post_genesis(head_slot: u64, current_slot: u64) -> SyncStatus {
+        SyncStatus::PostGenesis {
+            head_slot: Slot::new(head_slot),
+            current_slot: Slot::new(current_slot),
+            sync_distance: current_slot.saturating_sub(head_slot),
+            is_synced: true,
+        }
+    }
+
+    const GENESIS: Duration = Duration::from_secs(1_000);
+    const SLOT_DURATION: Duration = Duration::from_secs(6);
+
+    /// Returns the time `millis` milliseconds after the start of `slot`.
+    fn time_in_slot(slot: u32, millis: u64) -> Duration {
+        GENESIS + SLOT_DURATION * slot + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn clock_drift_is_none_before_genesis() {
+        let status = SyncStatus::PreGenesis {
+            head_slot: Slot::new(0),
+        };
+
+        assert_eq!(
+            clock_drift(time_in_slot(3, 0), GENESIS, SLOT_DURATION, &status),
+            None
+        );
+    }
+
+    #[test]
+    fn clock_drift_is_zero_within_slot() {
+        let status = post_genesis(10, 10);
+
+        for &millis in &[0, 3_000, 5_999] {
+            assert_eq!(
+                clock_drift(time_in_slot(10, millis), GENESIS, SLOT_DURATION, &status),
+                Some(0)
+            );
+        }
+    }
+
+    #[test]
+    fn clock_drift_measures_milliseconds_outside_slot() {
+        let status = post_genesis(9, 10);
+
+        assert_eq!(
+            clock_drift(time_in_slot(11, 250), GENESIS, SLOT_DURATION, &status),
+            Some(250),
+            "less than a slot ahead should be detected"
+        );
+        assert_eq!(
+            clock_drift(time_in_slot(12, 500), GENESIS, SLOT_DURATION, &status),
+            Some(6_500)
+        );
+        assert_eq!(
+            clock_drift(
+                time_in_slot(10, 0) - Duration::from_millis(1),
+                GENESIS,
+                SLOT_DURATION,
+                &status
+            ),
+            Some(-1),
+            "less than a slot behind should be detected"
+        );
+    }
+
+    #[test]
+    fn clock_drift_accounts_for_head_from_later_slot() {
+        assert_eq!(
+            clock_drift(
+                time_in_slot(10, 0),
+                GENESIS,
+                SLOT_DURATION,
+                &post_genesis(13, 11)
+            ),
+            Some(-18_000),
+            "a head later than the local time means the local clock is behind"
+        );
+    }
+
+    #[test]
+    fn check_reports_drift_from_beacon_node() {
+        let mut env = environment();
+        let spec = env.eth2_config.spec.clone();
+        let node = MockBeaconNode::start("127.0.0.1:0".parse().unwrap(), || {
+            json_response(&post_genesis(20, 20))
+        });
+
+        let beacon_node = FallbackBeaconNode::new(
+            vec![node.url()],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+        let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+        let slot_clock = TestingSlotClock::new(Slot::new(0), Duration::from_secs(0), slot_duration);
+
+        // Place genesis such that the local clock is within the 23rd slot, two slots after the end
+        // of the current slot of the beacon node.
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("should read system time");
+        let genesis_time = now.as_secs() - (slot_duration * 23).as_secs();
+
+        let service = ClockDriftServiceBuilder::<_, MinimalEthSpec>::new()
+            .threshold(1)
+            .genesis_time(genesis_time)
+            .slot_clock(slot_clock)
+            .beacon_node(beacon_node)
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build clock drift service");
+
+        let drift = env
+            .runtime()
+            .block_on(service.do_check())
+            .expect("should check clock drift")
+            .expect("should be post genesis");
+
+        let min_drift = (slot_duration * 2).as_millis() as i64;
+        assert!(
+            drift >= min_drift && drift < min_drift + slot_duration.as_millis() as i64,
+            "drift of {}ms should be just over two slots",
+            drift
+        );
+    }
+}
@@ -4,6 +4,9 @@ use std::path::PathBuf;
 
 pub const DEFAULT_HTTP_SERVER: &str = "http://localhost:5052/";
 pub const DEFAULT_DATA_DIR: &str = ".lighthouse/validators";
+/// The default number of milliseconds by which the validator client and beacon node clocks may
+/// disagree before a warning is logged.
+pub const DEFAULT_CLOCK_DRIFT_THRESHOLD: u64 = 500;
 
 /// Specifies a method for obtaining validator keypairs.
 #[derive(Clone)]
@@ -12,6 +15,12 @@ pub enum KeySource {
     Disk,
     /// Generate the keypairs (insecure, generates predictable keys).
     InsecureKeypairs(Vec<usize>),
+    /// Decrypt the keypairs from the EIP-2335 keystores in `validators_dir`, reading passwords
+    /// from sibling `.pass` files or from `secrets_dir`.
+    Keystores {
+        validators_dir: PathBuf,
+        secrets_dir: Option<PathBuf>,
+    },
 }
 
 impl Default for KeySource {
@@ -32,9 +41,15 @@ pub struct Config {
     ///
     /// Should be similar to `http://localhost:8080`
     pub http_server: String,
+    /// The http endpoints of beacon nodes to use when `http_server` is unavailable, in order of
+    /// priority.
+    pub fallback_http_servers: Vec<String>,
     /// If true, the validator client will still poll for duties and produce blocks even if the
     /// beacon node is not synced at startup.
     pub allow_unsynced_beacon_node: bool,
+    /// The number of milliseconds by which the local clock may differ from that of the beacon
+    /// node before a warning is logged.
+    pub clock_drift_threshold: u64,
 }
 
 impl Default for Config {
@@ -47,12 +62,21 @@ impl Default for Config {
             data_dir,
             key_source: <_>::default(),
             http_server: DEFAULT_HTTP_SERVER.to_string(),
+            fallback_http_servers: vec![],
             allow_unsynced_beacon_node: false,
+            clock_drift_threshold: DEFAULT_CLOCK_DRIFT_THRESHOLD,
         }
     }
 }
 
 impl Config {
+    /// Returns the http endpoints of all beacon nodes, in order of priority.
+    pub fn http_servers(&self) -> Vec<String> {
+        std::iter::once(self.http_server.clone())
+            .chain(self.fallback_http_servers.iter().cloned())
+            .collect()
+    }
+
     /// Returns a `Default` implementation of `Self` with some parameters modified by the supplied
     /// `cli_args`.
     pub fn from_cli(cli_args: &ArgMatches) -> Result<Config, String> {
@@ -75,6 +99,15 @@ impl Config {
             config.http_server = server.to_string();
         }
 
+        if let Some(servers) = cli_args.value_of("fallback-servers") {
+            config.fallback_http_servers = servers
+                .split(',')
+                .map(str::trim)
+                .filter(|server| !server.is_empty())
+                .map(String::from)
+                .collect();
+        }
+
         let mut config = match cli_args.subcommand() {
             ("testnet", Some(sub_cli_args)) => {
                 if cli_args.is_present("eth2-config") && sub_cli_args.is_present("bootstrap") {
@@ -87,13 +120,25 @@ impl Config {
                 process_testnet_subcommand(sub_cli_args, config)?
             }
             _ => {
-                config.key_source = KeySource::Disk;
+                config.key_source = match cli_args.value_of("validators-dir") {
+                    Some(validators_dir) => KeySource::Keystores {
+                        validators_dir: PathBuf::from(validators_dir),
+                        secrets_dir: cli_args.value_of("secrets-dir").map(PathBuf::from),
+                    },
+                    None => KeySource::Disk,
+                };
                 config
             }
         };
 
         config.allow_unsynced_beacon_node = cli_args.is_present("allow-unsynced");
 
+        if let Some(threshold) = cli_args.value_of("clock-drift-threshold") {
+            config.clock_drift_threshold = threshold
+                .parse::<u64>()
+                .map_err(|e| format!("Unable to parse clock drift threshold: {:?}", e))?;
+        }
+
         Ok(config)
     }
 }
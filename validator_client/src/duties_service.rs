@@ -1,18 +1,18 @@
-use crate::validator_store::ValidatorStore;
+use crate::{fallback_beacon_node::FallbackBeaconNode, validator_store::ValidatorStore};
 use environment::RuntimeContext;
 use exit_future::Signal;
 use futures::{future, Future, IntoFuture, Stream};
 use parking_lot::RwLock;
-use remote_beacon_node::RemoteBeaconNode;
 use slog::{crit, debug, error, info, trace, warn};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::timer::Interval;
-use types::{ChainSpec, CommitteeIndex, Epoch, EthSpec, PublicKey, Slot};
+use types::{ChainSpec, CommitteeIndex, Epoch, EthSpec, Hash256, PublicKey, Slot};
 
 /// Delay this period of time after the slot starts. This allows the node to process the new slot.
 const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(100);
@@ -20,6 +20,9 @@ const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(100);
 /// Remove any duties where the `duties_epoch < current_epoch - PRUNE_DEPTH`.
 const PRUNE_DEPTH: u64 = 4;
 
+/// The number of gossip subnets across which attestations are published.
+const ATTESTATION_SUBNET_COUNT: u64 = 64;
+
 type BaseHashMap = HashMap<PublicKey, HashMap<Epoch, ValidatorDuty>>;
 
 /// Stores the duties for some validator for an epoch.
@@ -27,14 +30,41 @@ type BaseHashMap = HashMap<PublicKey, HashMap<Epoch, ValidatorDuty>>;
 pub struct ValidatorDuty {
     /// The validator's BLS public key, uniquely identifying them. _48-bytes, hex encoded with 0x prefix, case insensitive._
     pub validator_pubkey: PublicKey,
+    /// The validator's index in `state.validators`.
+    pub validator_index: Option<usize>,
     /// The slot at which the validator must attest.
     pub attestation_slot: Option<Slot>,
     /// The index of the committee within `slot` of which the validator is a member.
     pub attestation_committee_index: Option<CommitteeIndex>,
     /// The position of the validator in the committee.
     pub attestation_committee_position: Option<usize>,
+    /// The number of validators in the committee.
+    pub attestation_committee_length: Option<usize>,
+    /// The total number of committees in `attestation_slot`.
+    pub attestation_committees_at_slot: Option<u64>,
     /// The slots in which a validator must propose a block (can be empty).
     pub block_proposal_slots: Vec<Slot>,
+    /// The root of the block upon which the attestation duty depends.
+    pub attestation_dependent_root: Option<Hash256>,
+    /// The root of the block upon which the block proposal slots depend.
+    pub block_proposal_dependent_root: Option<Hash256>,
+}
+
+impl ValidatorDuty {
+    /// Returns the id of the gossip subnet on which the validator should publish its attestation.
+    ///
+    /// Returns `None` if the validator has no attestation duty, or if the beacon node did not
+    /// supply the number of committees at the attestation slot.
+    pub fn attestation_subnet_id(&self, slots_per_epoch: u64) -> Option<u64> {
+        let slot = self.attestation_slot?;
+        let committees_since_epoch_start =
+            self.attestation_committees_at_slot? * (slot.as_u64() % slots_per_epoch);
+
+        Some(
+            (committees_since_epoch_start + self.attestation_committee_index?)
+                % ATTESTATION_SUBNET_COUNT,
+        )
+    }
 }
 
 impl TryInto<ValidatorDuty> for remote_beacon_node::ValidatorDuty {
@@ -45,10 +75,15 @@ impl TryInto<ValidatorDuty> for remote_beacon_node::ValidatorDuty {
             validator_pubkey: (&self.validator_pubkey)
                 .try_into()
                 .map_err(|e| format!("Invalid pubkey bytes from server: {:?}", e))?,
+            validator_index: self.validator_index,
             attestation_slot: self.attestation_slot,
             attestation_committee_index: self.attestation_committee_index,
             attestation_committee_position: self.attestation_committee_position,
+            attestation_committee_length: self.attestation_committee_length,
+            attestation_committees_at_slot: self.attestation_committees_at_slot,
             block_proposal_slots: self.block_proposal_slots,
+            attestation_dependent_root: self.attestation_dependent_root,
+            block_proposal_dependent_root: self.block_proposal_dependent_root,
         })
     }
 }
@@ -68,9 +103,20 @@ enum InsertOutcome {
     Invalid,
 }
 
+/// The roots of the blocks upon which the duties for some epoch depend.
+///
+/// If the beacon node reports different roots for an epoch, the chain has re-orged across the
+/// block that decided the shuffling and all duties for that epoch are stale.
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct DependentRoots {
+    attestation: Option<Hash256>,
+    block_proposal: Option<Hash256>,
+}
+
 #[derive(Default)]
 pub struct DutiesStore {
     store: RwLock<BaseHashMap>,
+    dependent_roots: RwLock<HashMap<Epoch, DependentRoots>>,
 }
 
 impl DutiesStore {
@@ -175,6 +221,35 @@ impl DutiesStore {
         }
     }
 
+    /// Records the `dependent_roots` of the duties for `epoch`.
+    ///
+    /// Returns the previously known roots if they differ from `dependent_roots`.
+    fn update_dependent_roots(
+        &self,
+        epoch: Epoch,
+        dependent_roots: DependentRoots,
+    ) -> Option<DependentRoots> {
+        self.dependent_roots
+            .write()
+            .insert(epoch, dependent_roots)
+            .filter(|previous_roots| *previous_roots != dependent_roots)
+    }
+
+    /// Removes all duties of the validator with `validator_pubkey`.
+    fn remove_validator(&self, validator_pubkey: &PublicKey) {
+        self.store.write().remove(validator_pubkey);
+    }
+
+    /// Removes the duties of all validators for the given `epoch`.
+    fn remove_epoch(&self, epoch: Epoch) {
+        self.store
+            .write()
+            .retain(|_validator_pubkey, validator_map| {
+                validator_map.remove(&epoch);
+                !validator_map.is_empty()
+            });
+    }
+
     fn prune(&self, prior_to: Epoch) {
         self.store
             .write()
@@ -182,13 +257,16 @@ impl DutiesStore {
                 validator_map.retain(|epoch, _duties| *epoch >= prior_to);
                 !validator_map.is_empty()
             });
+        self.dependent_roots
+            .write()
+            .retain(|epoch, _roots| *epoch >= prior_to);
     }
 }
 
 pub struct DutiesServiceBuilder<T, E: EthSpec> {
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_node: Option<FallbackBeaconNode<E>>,
     context: Option<RuntimeContext<E>>,
     allow_unsynced_beacon_node: bool,
 }
@@ -214,7 +292,7 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
+    pub fn beacon_node(mut self, beacon_node: FallbackBeaconNode<E>) -> Self {
         self.beacon_node = Some(beacon_node);
         self
     }
@@ -247,6 +325,7 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
                     .context
                     .ok_or_else(|| "Cannot build DutiesService without runtime_context")?,
                 allow_unsynced_beacon_node: self.allow_unsynced_beacon_node,
+                beacon_node_synced: AtomicBool::new(false),
             }),
         })
     }
@@ -257,17 +336,20 @@ pub struct Inner<T, E: EthSpec> {
     store: Arc<DutiesStore>,
     validator_store: ValidatorStore<T, E>,
     pub(crate) slot_clock: T,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_node: FallbackBeaconNode<E>,
     context: RuntimeContext<E>,
-    /// If true, the duties service will poll for duties from the beacon node even if it is not
-    /// synced.
+    /// If true, the duties service will poll for duties from the beacon node (and validators will
+    /// sign) even if it is not synced.
     allow_unsynced_beacon_node: bool,
+    /// The sync status most recently reported by the beacon node.
+    beacon_node_synced: AtomicBool,
 }
 
 /// Maintains a store of the duties for all voting validators in the `validator_store`.
 ///
-/// Polls the beacon node at the start of each epoch, collecting duties for the current and next
-/// epoch.
+/// Polls the beacon node at the start of each slot, collecting duties for the current and next
+/// epoch. If the roots upon which the duties of an epoch depend change (i.e., a re-org across the
+/// shuffling decision block), all stored duties for that epoch are replaced.
 pub struct DutiesService<T, E: EthSpec> {
     inner: Arc<Inner<T, E>>,
 }
@@ -319,6 +401,42 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         self.store.attesters(slot, E::slots_per_epoch())
     }
 
+    /// Returns `true` if the beacon node reported itself as synced during the most recent update,
+    /// or if the service is configured to allow an unsynced beacon node.
+    ///
+    /// Validators should not sign blocks or attestations whilst this is `false`.
+    pub fn beacon_node_is_synced(&self) -> bool {
+        self.allow_unsynced_beacon_node || self.beacon_node_synced.load(Ordering::Relaxed)
+    }
+
+    /// Downloads the duties of the validators with `pubkeys` for the current epoch and the next,
+    /// so that newly enabled validators need not wait for the next routine update.
+    pub fn update_validators(
+        &self,
+        pubkeys: Vec<PublicKey>,
+    ) -> impl Future<Item = (), Error = String> {
+        let service = self.clone();
+
+        self.slot_clock
+            .now()
+            .ok_or_else(|| "Unable to read slot clock".to_string())
+            .into_future()
+            .and_then(move |slot| {
+                let epoch = slot.epoch(E::slots_per_epoch());
+
+                service
+                    .clone()
+                    .update_epoch_for(epoch, pubkeys.clone())
+                    .and_then(move |_| service.update_epoch_for(epoch + 1, pubkeys))
+            })
+    }
+
+    /// Forgets all duties of the validator with `pubkey`, so that it is no longer assigned any
+    /// blocks or attestations.
+    pub fn remove_validator(&self, pubkey: &PublicKey) {
+        self.store.remove_validator(pubkey)
+    }
+
     /// Start the service that periodically polls the beacon node for validator duties.
     pub fn start_update_service(&self, spec: &ChainSpec) -> Result<Signal, String> {
         let log = self.context.log.clone();
@@ -401,53 +519,54 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
 
                 service_2
                     .beacon_node
-                    .http
-                    .beacon()
-                    .get_head()
-                    .map(move |head| (epoch, head.slot.epoch(E::slots_per_epoch())))
+                    .first_success(|node| node.http.node().get_syncing())
+                    .map(move |sync_status| (epoch, sync_status))
                     .map_err(move |e| {
                         error!(
                                 log,
                                 "Failed to contact beacon node";
-                                "error" => format!("{:?}", e)
+                                "error" => e
                         )
                     })
             })
-            .and_then(move |(current_epoch, beacon_head_epoch)| {
+            .and_then(move |(current_epoch, sync_status)| {
                 let log = service_3.context.log.clone();
 
-                let future: Box<dyn Future<Item = (), Error = ()> + Send> = if beacon_head_epoch + 1
-                    < current_epoch
-                    && !service_3.allow_unsynced_beacon_node
-                {
-                    error!(
-                        log,
-                        "Beacon node is not synced";
-                        "node_head_epoch" => format!("{}", beacon_head_epoch),
-                        "current_epoch" => format!("{}", current_epoch),
-                    );
+                service_3
+                    .beacon_node_synced
+                    .store(sync_status.is_synced(), Ordering::Relaxed);
 
-                    Box::new(future::ok(()))
-                } else {
-                    Box::new(service_3.update_epoch(current_epoch).then(move |result| {
-                        if let Err(e) = result {
-                            error!(
-                                log,
-                                "Failed to get current epoch duties";
-                                "http_error" => format!("{:?}", e)
-                            );
-                        }
+                let future: Box<dyn Future<Item = (), Error = ()> + Send> =
+                    if !service_3.beacon_node_is_synced() {
+                        error!(
+                            log,
+                            "Beacon node is not synced";
+                            "node_head_slot" => sync_status.head_slot().as_u64(),
+                            "sync_distance" => sync_status.sync_distance(),
+                            "current_epoch" => format!("{}", current_epoch),
+                        );
+
+                        Box::new(future::ok(()))
+                    } else {
+                        Box::new(service_3.update_epoch(current_epoch).then(move |result| {
+                            if let Err(e) = result {
+                                error!(
+                                    log,
+                                    "Failed to get current epoch duties";
+                                    "http_error" => format!("{:?}", e)
+                                );
+                            }
 
-                        let log = service_4.context.log.clone();
-                        service_4.update_epoch(current_epoch + 1).map_err(move |e| {
-                            error!(
-                                log,
-                                "Failed to get next epoch duties";
-                                "http_error" => format!("{:?}", e)
-                            );
-                        })
-                    }))
-                };
+                            let log = service_4.context.log.clone();
+                            service_4.update_epoch(current_epoch + 1).map_err(move |e| {
+                                error!(
+                                    log,
+                                    "Failed to get next epoch duties";
+                                    "http_error" => format!("{:?}", e)
+                                );
+                            })
+                        }))
+                    };
 
                 future
             })
@@ -456,20 +575,57 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
 
     /// Attempt to download the duties of all managed validators for the given `epoch`.
     fn update_epoch(self, epoch: Epoch) -> impl Future<Item = (), Error = String> {
+        let pubkeys = self.validator_store.voting_pubkeys();
+        self.update_epoch_for(epoch, pubkeys)
+    }
+
+    /// Attempt to download the duties of the validators with `pubkeys` for the given `epoch`.
+    fn update_epoch_for(
+        self,
+        epoch: Epoch,
+        pubkeys: Vec<PublicKey>,
+    ) -> impl Future<Item = (), Error = String> {
         let service_1 = self.clone();
         let service_2 = self;
 
-        let pubkeys = service_1.validator_store.voting_pubkeys();
         service_1
             .beacon_node
-            .http
-            .validator()
-            .get_duties(epoch, pubkeys.as_slice())
+            .first_success(move |node| node.http.validator().get_duties(epoch, &pubkeys))
             .map(move |all_duties| (epoch, all_duties))
-            .map_err(move |e| format!("Failed to get duties for epoch {}: {:?}", epoch, e))
+            .map_err(move |e| format!("Failed to get duties for epoch {}: {}", epoch, e))
             .and_then(move |(epoch, all_duties)| {
                 let log = service_2.context.log.clone();
 
+                // All duties for an epoch are computed by the beacon node from the same chain, so
+                // the first known validator is representative of all.
+                let dependent_roots = all_duties
+                    .iter()
+                    .find(|duties| duties.validator_index.is_some())
+                    .map(|duties| DependentRoots {
+                        attestation: duties.attestation_dependent_root,
+                        block_proposal: duties.block_proposal_dependent_root,
+                    });
+
+                if let Some(dependent_roots) = dependent_roots {
+                    if let Some(previous_roots) = service_2
+                        .store
+                        .update_dependent_roots(epoch, dependent_roots)
+                    {
+                        // The shuffling has been re-orged. Drop all the stale duties, including
+                        // those of validators that may no longer have a duty in this epoch.
+                        service_2.store.remove_epoch(epoch);
+
+                        info!(
+                            log,
+                            "Duties dependent root changed";
+                            "info" => "Chain re-org occurred, duties have been re-fetched",
+                            "previous_roots" => format!("{:?}", previous_roots),
+                            "new_roots" => format!("{:?}", dependent_roots),
+                            "epoch" => format!("{}", epoch),
+                        );
+                    }
+                }
+
                 let mut new_validator = 0;
                 let mut new_epoch = 0;
                 let mut identical = 0;
@@ -489,6 +645,10 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
                                 "First duty assignment for validator";
                                 "proposal_slots" => format!("{:?}", &duties.block_proposal_slots),
                                 "attestation_slot" => format!("{:?}", &duties.attestation_slot),
+                                "attestation_subnet" => format!(
+                                    "{:?}",
+                                    duties.attestation_subnet_id(E::slots_per_epoch())
+                                ),
                                 "validator" => format!("{:?}", &duties.validator_pubkey)
                             );
                             new_validator += 1
@@ -544,3 +704,159 @@ fn duties_match_epoch(duties: &ValidatorDuty, epoch: Epoch, slots_per_epoch: u64
             .iter()
             .all(|slot| slot.epoch(slots_per_epoch) == epoch)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fork_service::ForkServiceBuilder;
+    use crate::test_utils::{json_response, MockBeaconNode};
+    use environment::{Environment, EnvironmentBuilder};
+    use remote_beacon_node::{RetryPolicy, Timeouts};
+    use slot_clock::TestingSlotClock;
+    use std::sync::atomic::AtomicUsize;
+    use tempdir::TempDir;
+    use types::{test_utils::generate_deterministic_keypair, MinimalEthSpec, PublicKeyBytes};
+
+    type E = MinimalEthSpec;
+
+    fn environment() -> Environment<E> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .and_then(|builder| builder.single_thread_tokio_runtime())
+            .and_then(|builder| builder.build())
+            .expect("should build environment")
+    }
+
+    /// Returns the duties of validator `i` with the given `attestation_slot`, which depend on
+    /// `dependent_root`.
+    fn remote_duty(
+        i: usize,
+        attestation_slot: Slot,
+        dependent_root: Hash256,
+    ) -> remote_beacon_node::ValidatorDuty {
+        remote_beacon_node::ValidatorDuty {
+            validator_pubkey: PublicKeyBytes::from(generate_deterministic_keypair(i).pk),
+            validator_index: Some(i),
+            attestation_slot: Some(attestation_slot),
+            attestation_committee_index: Some(0),
+            attestation_committee_position: Some(0),
+            attestation_committee_length: Some(1),
+            attestation_committees_at_slot: Some(1),
+            block_proposal_slots: vec![],
+            attestation_dependent_root: Some(dependent_root),
+            block_proposal_dependent_root: Some(dependent_root),
+        }
+    }
+
+    /// Returns a mock beacon node which responds to the `n`th request with `responses[n]`.
+    fn node_with_responses(
+        responses: Vec<Vec<remote_beacon_node::ValidatorDuty>>,
+    ) -> MockBeaconNode {
+        let requests = AtomicUsize::new(0);
+
+        MockBeaconNode::start("127.0.0.1:0".parse().unwrap(), move || {
+            json_response(&responses[requests.fetch_add(1, Ordering::Relaxed)])
+        })
+    }
+
+    /// Returns a duties service which uses the beacon node at `url`.
+    ///
+    /// The temporary directory of the validator store is returned, since it is removed once
+    /// dropped.
+    fn duties_service(
+        env: &Environment<E>,
+        url: String,
+    ) -> (DutiesService<TestingSlotClock, E>, TempDir) {
+        let spec = env.eth2_config.spec.clone();
+        let dir = TempDir::new("duties_service").expect("should create temp dir");
+
+        let beacon_node = FallbackBeaconNode::new(
+            vec![url],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+        let slot_clock = || {
+            TestingSlotClock::new(
+                Slot::new(0),
+                Duration::from_secs(0),
+                Duration::from_millis(spec.milliseconds_per_slot),
+            )
+        };
+        let fork_service = ForkServiceBuilder::new()
+            .slot_clock(slot_clock())
+            .beacon_node(beacon_node.clone())
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build fork service");
+        let validator_store = ValidatorStore::empty(
+            dir.path().to_path_buf(),
+            spec.clone(),
+            fork_service,
+            env.core_context().log,
+        )
+        .expect("should create validator store");
+        let service = DutiesServiceBuilder::new()
+            .slot_clock(slot_clock())
+            .validator_store(validator_store)
+            .beacon_node(beacon_node)
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build duties service");
+
+        (service, dir)
+    }
+
+    fn attester_indices(service: &DutiesService<TestingSlotClock, E>, slot: Slot) -> Vec<usize> {
+        let mut indices = service
+            .attesters(slot)
+            .into_iter()
+            .filter_map(|duty| duty.validator_index)
+            .collect::<Vec<_>>();
+        indices.sort();
+        indices
+    }
+
+    #[test]
+    fn replaces_all_duties_when_dependent_root_changes() {
+        let mut env = environment();
+        let epoch = Epoch::new(0);
+        let (old_root, new_root) = (Hash256::from_low_u64_be(1), Hash256::from_low_u64_be(2));
+        let node = node_with_responses(vec![
+            vec![
+                remote_duty(0, Slot::new(1), old_root),
+                remote_duty(1, Slot::new(1), old_root),
+            ],
+            vec![
+                remote_duty(0, Slot::new(1), old_root),
+                remote_duty(1, Slot::new(1), old_root),
+            ],
+            // After a re-org, the beacon node no longer reports a duty for validator 1.
+            vec![remote_duty(0, Slot::new(2), new_root)],
+        ]);
+        let (service, _dir) = duties_service(&env, node.url());
+        let pubkeys = (0..2)
+            .map(|i| generate_deterministic_keypair(i).pk)
+            .collect::<Vec<_>>();
+
+        for _ in 0..2 {
+            env.runtime()
+                .block_on(service.clone().update_epoch_for(epoch, pubkeys.clone()))
+                .expect("should update duties");
+
+            assert_eq!(attester_indices(&service, Slot::new(1)), vec![0, 1]);
+        }
+
+        env.runtime()
+            .block_on(service.clone().update_epoch_for(epoch, pubkeys))
+            .expect("should update duties");
+
+        assert_eq!(
+            attester_indices(&service, Slot::new(1)),
+            Vec::<usize>::new(),
+            "should drop the duties which depend on the old root"
+        );
+        assert_eq!(attester_indices(&service, Slot::new(2)), vec![0]);
+    }
+}
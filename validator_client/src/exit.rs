@@ -0,0 +1,509 @@
+//! Signs a voluntary exit for one of the validators of this client and publishes it via the beacon
+//! node.
+use crate::config::{Config, KeySource};
+use crate::keystores::{keystore_paths, load_keystores_from_paths};
+use crate::validator_directory::ValidatorDirectory;
+use crate::{HTTP_RETRY_POLICY, HTTP_TIMEOUTS};
+use clap::ArgMatches;
+use environment::Environment;
+use futures::{
+    future::{self, loop_fn, Loop},
+    Future,
+};
+use remote_beacon_node::{ExitPublishStatus, RemoteBeaconNode};
+use slog::{info, warn};
+use std::fs::read_dir;
+use std::io::{stdin, stdout, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+use types::{
+    test_utils::generate_deterministic_keypair, ChainSpec, Epoch, EthSpec, Fork, Keypair,
+    PublicKey, SignedVoluntaryExit, VoluntaryExit,
+};
+
+/// The phrase which must be entered to confirm the exit.
+pub const CONFIRMATION_PHRASE: &str = "Exit my validator";
+
+/// The number of times the operation pool of the beacon node is checked for the published exit.
+const POOL_POLL_ATTEMPTS: usize = 6;
+
+/// The interval between checks of the operation pool of the beacon node.
+const POOL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The outcome of an exit that was accepted by the beacon node.
+#[derive(Debug, PartialEq)]
+enum ExitOutcome {
+    /// The exit was queued and found in the operation pool of the beacon node.
+    Pooled,
+    /// The exit was queued, but was not found in the operation pool of the beacon node.
+    NotPooled,
+    /// The exit was published, but the beacon node does not produce blocks so it was not queued.
+    NotQueued,
+}
+
+/// An unsigned exit, with the information required to sign it.
+struct PendingExit {
+    exit: VoluntaryExit,
+    fork: Fork,
+    spec: ChainSpec,
+}
+
+/// Runs the `exit` sub-command of the validator client.
+///
+/// Fetches the fork and the index of the validator from the beacon node, asks the user to
+/// confirm, then signs and publishes the exit and checks that it arrived in the operation pool.
+pub fn run<T: EthSpec>(
+    cli_args: &ArgMatches,
+    exit_args: &ArgMatches,
+    mut env: Environment<T>,
+) -> Result<(), String> {
+    let context = env.core_context();
+    let log = context.log;
+    let spec_constants = context.eth2_config.spec_constants;
+    let config = Config::from_cli(cli_args)?;
+
+    let pubkey = exit_args
+        .value_of("pubkey")
+        .ok_or_else(|| "The --pubkey flag is required".to_string())
+        .and_then(parse_pubkey)?;
+    let keypair = load_keypair(&config, &pubkey)?;
+
+    let beacon_node: RemoteBeaconNode<T> = RemoteBeaconNode::new_with_timeouts(
+        config.http_server.clone(),
+        HTTP_TIMEOUTS,
+        HTTP_RETRY_POLICY,
+    )?;
+
+    let pending =
+        env.runtime()
+            .block_on(prepare_exit(beacon_node.clone(), pubkey, spec_constants))?;
+
+    let current_epoch = pending.exit.epoch;
+    let (exit_epoch, withdrawable_epoch) = earliest_exit_epochs(current_epoch, &pending.spec);
+
+    info!(
+        log,
+        "Prepared voluntary exit";
+        "validator" => keypair.pk.as_hex_string(),
+        "validator_index" => pending.exit.validator_index,
+        "current_epoch" => current_epoch,
+        "earliest_exit_epoch" => exit_epoch,
+        "earliest_withdrawable_epoch" => withdrawable_epoch,
+    );
+
+    println!(
+        "Validator {} will stop validating and become exited at epoch {}, and its balance will \
+         become withdrawable at epoch {}. These are the earliest epochs; they are later if many \
+         validators are exiting. The validator must keep performing its duties until it has \
+         exited. AN EXIT CANNOT BE REVERSED.",
+        keypair.pk.as_hex_string(),
+        exit_epoch,
+        withdrawable_epoch
+    );
+    if !confirm()? {
+        return Err("The exit was not confirmed, it has not been published".to_string());
+    }
+
+    let signed_exit = pending.exit.sign(&keypair.sk, &pending.fork, &pending.spec);
+    let validator_index = signed_exit.message.validator_index;
+
+    match env
+        .runtime()
+        .block_on(publish_exit(beacon_node, signed_exit))?
+    {
+        ExitOutcome::Pooled => info!(
+            log,
+            "Voluntary exit is in the operation pool";
+            "validator_index" => validator_index,
+        ),
+        ExitOutcome::NotPooled => warn!(
+            log,
+            "Voluntary exit not found in the operation pool";
+            "msg" => "it may already be included in a block, check the validator again later",
+            "validator_index" => validator_index,
+        ),
+        ExitOutcome::NotQueued => info!(
+            log,
+            "Published voluntary exit";
+            "msg" => "the beacon node does not produce blocks, so it has not queued the exit",
+            "validator_index" => validator_index,
+        ),
+    }
+
+    Ok(())
+}
+
+/// Publishes `signed_exit` via the beacon node and, if the beacon node queued it, checks that it
+/// arrived in the operation pool.
+fn publish_exit<T: EthSpec>(
+    beacon_node: RemoteBeaconNode<T>,
+    signed_exit: SignedVoluntaryExit,
+) -> impl Future<Item = ExitOutcome, Error = String> {
+    let validator_index = signed_exit.message.validator_index;
+
+    beacon_node
+        .http
+        .beacon()
+        .publish_voluntary_exit(signed_exit)
+        .map_err(|e| format!("Unable to publish voluntary exit: {:?}", e))
+        .and_then(
+            move |status| -> Box<dyn Future<Item = ExitOutcome, Error = String> + Send> {
+                match status {
+                    ExitPublishStatus::Queued => Box::new(
+                        wait_for_pooled_exit(beacon_node, validator_index).map(|pooled| {
+                            if pooled {
+                                ExitOutcome::Pooled
+                            } else {
+                                ExitOutcome::NotPooled
+                            }
+                        }),
+                    ),
+                    ExitPublishStatus::NotQueued => Box::new(future::ok(ExitOutcome::NotQueued)),
+                    ExitPublishStatus::Invalid(msg) => Box::new(future::err(format!(
+                        "Beacon node rejected the voluntary exit: {}",
+                        msg
+                    ))),
+                    ExitPublishStatus::Unknown => Box::new(future::err(
+                        "Unknown result when publishing voluntary exit".to_string(),
+                    )),
+                }
+            },
+        )
+}
+
+/// Reads the spec, fork, head and validator from the beacon node and builds an exit for the
+/// current epoch.
+fn prepare_exit<T: EthSpec>(
+    beacon_node: RemoteBeaconNode<T>,
+    pubkey: PublicKey,
+    spec_constants: String,
+) -> impl Future<Item = PendingExit, Error = String> {
+    let beacon = beacon_node.http.beacon();
+
+    beacon_node
+        .http
+        .spec()
+        .get_eth2_config()
+        .join3(beacon.get_fork(), beacon.get_head())
+        .map_err(|e| format!("Unable to read from beacon node: {:?}", e))
+        .and_then(move |(eth2_config, fork, head)| {
+            // Do not sign with a domain from a beacon node using different spec constants.
+            if eth2_config.spec_constants != spec_constants {
+                return Err(format!(
+                    "Beacon node is using an incompatible spec. Got {}, expected {}",
+                    eth2_config.spec_constants, spec_constants
+                ));
+            }
+
+            Ok((
+                eth2_config.spec,
+                fork,
+                head.slot.epoch(T::slots_per_epoch()),
+            ))
+        })
+        .and_then(move |(spec, fork, current_epoch)| {
+            beacon
+                .get_validators(vec![pubkey.clone()], None)
+                .map_err(|e| format!("Unable to read validator from beacon node: {:?}", e))
+                .and_then(move |mut validators| {
+                    let response = validators
+                        .pop()
+                        .ok_or_else(|| "Beacon node returned no validator".to_string())?;
+                    let (validator_index, validator) =
+                        match (response.validator_index, response.validator) {
+                            (Some(index), Some(validator)) => (index, validator),
+                            _ => {
+                                return Err(format!(
+                                    "Validator {} is unknown to the beacon node",
+                                    pubkey.as_hex_string()
+                                ))
+                            }
+                        };
+
+                    if validator.exit_epoch != spec.far_future_epoch {
+                        return Err(format!(
+                            "Validator {} has already exited or is exiting at epoch {}",
+                            validator_index, validator.exit_epoch
+                        ));
+                    }
+                    if !validator.is_active_at(current_epoch) {
+                        return Err(format!(
+                            "Validator {} is not active at epoch {}",
+                            validator_index, current_epoch
+                        ));
+                    }
+
+                    Ok(PendingExit {
+                        exit: VoluntaryExit {
+                            epoch: current_epoch,
+                            validator_index: validator_index as u64,
+                        },
+                        fork,
+                        spec,
+                    })
+                })
+        })
+}
+
+/// Polls the operation pool of the beacon node until it contains an exit for `validator_index`.
+///
+/// Returns `false` if the exit was not found after `POOL_POLL_ATTEMPTS` attempts.
+fn wait_for_pooled_exit<T: EthSpec>(
+    beacon_node: RemoteBeaconNode<T>,
+    validator_index: u64,
+) -> impl Future<Item = bool, Error = String> {
+    loop_fn(0, move |attempt| {
+        let beacon_node = beacon_node.clone();
+
+        Delay::new(Instant::now() + POOL_POLL_INTERVAL)
+            .map_err(|e| format!("Unable to create poll delay: {:?}", e))
+            .and_then(move |_| {
+                beacon_node
+                    .http
+                    .beacon()
+                    .get_pooled_voluntary_exits()
+                    .map_err(|e| format!("Unable to read operation pool: {:?}", e))
+            })
+            .map(move |exits| {
+                if exits
+                    .iter()
+                    .any(|exit| exit.message.validator_index == validator_index)
+                {
+                    Loop::Break(true)
+                } else if attempt + 1 >= POOL_POLL_ATTEMPTS {
+                    Loop::Break(false)
+                } else {
+                    Loop::Continue(attempt + 1)
+                }
+            })
+    })
+}
+
+/// Returns the earliest epochs at which a validator which exits in `current_epoch` is exited and
+/// becomes withdrawable, respectively.
+///
+/// The epochs are later if the exit churn limit is reached.
+fn earliest_exit_epochs(current_epoch: Epoch, spec: &ChainSpec) -> (Epoch, Epoch) {
+    let exit_epoch = current_epoch + 1 + spec.max_seed_lookahead;
+    (
+        exit_epoch,
+        exit_epoch + spec.min_validator_withdrawability_delay,
+    )
+}
+
+/// Prompts the user to enter the `CONFIRMATION_PHRASE`, returning `true` if they do.
+fn confirm() -> Result<bool, String> {
+    print!("Enter the phrase \"{}\" to confirm: ", CONFIRMATION_PHRASE);
+    stdout()
+        .flush()
+        .map_err(|e| format!("Unable to write to stdout: {:?}", e))?;
+
+    let mut input = String::new();
+    stdin()
+        .read_line(&mut input)
+        .map_err(|e| format!("Unable to read from stdin: {:?}", e))?;
+
+    Ok(is_confirmation(&input))
+}
+
+/// Returns `true` if `input` is the `CONFIRMATION_PHRASE`, ignoring surrounding whitespace.
+fn is_confirmation(input: &str) -> bool {
+    input.trim() == CONFIRMATION_PHRASE
+}
+
+/// Parses a `0x`-prefixed, hex-encoded public key.
+fn parse_pubkey(string: &str) -> Result<PublicKey, String> {
+    let bytes = hex::decode(string.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid public key {}: {:?}", string, e))?;
+    PublicKey::from_bytes(&bytes).map_err(|e| format!("Invalid public key {}: {:?}", string, e))
+}
+
+/// Loads the keypair for `pubkey` from the key source of `config`.
+fn load_keypair(config: &Config, pubkey: &PublicKey) -> Result<Keypair, String> {
+    let keypairs: Vec<Keypair> = match &config.key_source {
+        KeySource::Disk => read_dir(&config.data_dir)
+            .map_err(|e| {
+                format!(
+                    "Failed to read data directory {:?}: {:?}",
+                    config.data_dir, e
+                )
+            })?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.is_dir())
+            .filter_map(|path| {
+                ValidatorDirectory::load_for_signing(path)
+                    .ok()?
+                    .voting_keypair
+            })
+            .collect(),
+        KeySource::InsecureKeypairs(indices) => indices
+            .iter()
+            .map(|index| generate_deterministic_keypair(*index))
+            .collect(),
+        KeySource::Keystores {
+            validators_dir,
+            secrets_dir,
+        } => load_keystores_from_paths(
+            keystore_paths(validators_dir)?,
+            secrets_dir.as_ref().map(PathBuf::as_path),
+        )?
+        .keypairs
+        .into_iter()
+        .map(|(_, keypair)| keypair)
+        .collect(),
+    };
+
+    keypairs
+        .into_iter()
+        .find(|keypair| keypair.pk == *pubkey)
+        .ok_or_else(|| {
+            format!(
+                "No keypair for validator {} was found",
+                pubkey.as_hex_string()
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{json_response, MockBeaconNode, MockRequest};
+    use environment::{Environment, EnvironmentBuilder};
+    use parking_lot::Mutex;
+    use remote_beacon_node::{RetryPolicy, Timeouts};
+    use std::sync::Arc;
+    use types::MinimalEthSpec;
+
+    fn environment() -> Environment<MinimalEthSpec> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .and_then(|builder| builder.single_thread_tokio_runtime())
+            .and_then(|builder| builder.build())
+            .expect("should build environment")
+    }
+
+    fn signed_exit(validator_index: u64) -> SignedVoluntaryExit {
+        VoluntaryExit {
+            epoch: Epoch::new(0),
+            validator_index,
+        }
+        .sign(
+            &generate_deterministic_keypair(validator_index as usize).sk,
+            &Fork::default(),
+            &MinimalEthSpec::default_spec(),
+        )
+    }
+
+    /// Publishes an exit via a mock beacon node which answers the publication with
+    /// `publish_response` and holds the published exit in its operation pool, returning the
+    /// outcome and the paths of all requests.
+    fn publish_to_mock_node(
+        publish_response: (u16, String),
+    ) -> (Result<ExitOutcome, String>, Vec<String>) {
+        let mut env = environment();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let node_requests = requests.clone();
+        let pool = Arc::new(Mutex::new(vec![]));
+
+        let node = MockBeaconNode::start_with_requests(
+            "127.0.0.1:0".parse().unwrap(),
+            move |request: &MockRequest| {
+                node_requests.lock().push(request.path.clone());
+                match (request.method.as_str(), request.path.as_str()) {
+                    ("POST", "/beacon/voluntary_exit") => {
+                        let exit: SignedVoluntaryExit =
+                            serde_json::from_slice(&request.body).expect("should decode exit");
+                        pool.lock().push(exit);
+                        publish_response.clone()
+                    }
+                    ("GET", "/beacon/pool/voluntary_exits") => json_response(&*pool.lock()),
+                    _ => (404, "\"Unexpected request\"".to_string()),
+                }
+            },
+        );
+        let beacon_node: RemoteBeaconNode<MinimalEthSpec> = RemoteBeaconNode::new_with_timeouts(
+            node.url(),
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+        )
+        .expect("should create remote beacon node");
+
+        let outcome = env
+            .runtime()
+            .block_on(publish_exit(beacon_node, signed_exit(3)));
+        let requests = requests.lock().clone();
+
+        (outcome, requests)
+    }
+
+    #[test]
+    fn checks_pool_for_queued_exit() {
+        let (outcome, requests) = publish_to_mock_node(json_response(&true));
+
+        assert_eq!(outcome, Ok(ExitOutcome::Pooled));
+        assert_eq!(
+            requests,
+            vec!["/beacon/voluntary_exit", "/beacon/pool/voluntary_exits"],
+            "should check the pool once the exit is queued"
+        );
+    }
+
+    #[test]
+    fn does_not_check_pool_for_exit_not_queued() {
+        let (outcome, requests) = publish_to_mock_node(json_response(&false));
+
+        assert_eq!(outcome, Ok(ExitOutcome::NotQueued));
+        assert_eq!(
+            requests,
+            vec!["/beacon/voluntary_exit"],
+            "should not wait for an exit the beacon node did not queue"
+        );
+    }
+
+    #[test]
+    fn fails_for_rejected_exit() {
+        let (outcome, requests) =
+            publish_to_mock_node((202, "\"Validator has not been active long enough\"".into()));
+
+        assert!(
+            outcome
+                .expect_err("should fail for a rejected exit")
+                .contains("rejected"),
+            "should report the rejection"
+        );
+        assert_eq!(requests, vec!["/beacon/voluntary_exit"]);
+    }
+
+    #[test]
+    fn requires_exact_confirmation_phrase() {
+        assert!(is_confirmation("Exit my validator\n"));
+        assert!(!is_confirmation("exit my validator\n"));
+        assert!(!is_confirmation("y\n"));
+        assert!(!is_confirmation(""));
+    }
+
+    #[test]
+    fn computes_earliest_exit_epochs() {
+        let spec = MinimalEthSpec::default_spec();
+        let (exit_epoch, withdrawable_epoch) = earliest_exit_epochs(Epoch::new(10), &spec);
+
+        assert_eq!(exit_epoch, Epoch::new(10) + 1 + spec.max_seed_lookahead);
+        assert_eq!(
+            withdrawable_epoch,
+            exit_epoch + spec.min_validator_withdrawability_delay
+        );
+    }
+
+    #[test]
+    fn parses_pubkey() {
+        let keypair = generate_deterministic_keypair(0);
+
+        assert_eq!(
+            parse_pubkey(&keypair.pk.as_hex_string()),
+            Ok(keypair.pk),
+            "should parse 0x-prefixed pubkey"
+        );
+        assert!(parse_pubkey("0x00").is_err(), "should reject short pubkey");
+    }
+}
@@ -0,0 +1,498 @@
+use crate::metrics;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use futures::{
+    future::{self, loop_fn, Loop},
+    Future, IntoFuture, Stream,
+};
+use remote_beacon_node::{RemoteBeaconNode, RetryPolicy, Timeouts};
+use slog::{crit, debug, info, warn};
+use std::fmt::Debug;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::timer::Interval;
+use types::{ChainSpec, EthSpec};
+
+/// A single beacon node endpoint, along with its most recently observed health.
+pub struct Candidate<E: EthSpec> {
+    url: String,
+    node: RemoteBeaconNode<E>,
+    healthy: AtomicBool,
+}
+
+/// Helper to minimise `Arc` usage.
+pub struct Inner<E: EthSpec> {
+    /// The beacon nodes, in order of priority.
+    candidates: Vec<Candidate<E>>,
+    /// The index of the candidate that most recently served a request.
+    preferred: AtomicUsize,
+    context: RuntimeContext<E>,
+}
+
+/// Wraps an ordered list of beacon nodes, sending each request to the node that most recently
+/// succeeded and falling back to the others (in order of priority) when it fails.
+///
+/// Once started, the update service periodically re-probes any nodes with a higher priority than
+/// the preferred node, so that requests return to the primary node once it recovers.
+pub struct FallbackBeaconNode<E: EthSpec> {
+    inner: Arc<Inner<E>>,
+}
+
+impl<E: EthSpec> Clone for FallbackBeaconNode<E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<E: EthSpec> Deref for FallbackBeaconNode<E> {
+    type Target = Inner<E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl<E: EthSpec> FallbackBeaconNode<E> {
+    /// Creates a client for each of the `urls`, which must be supplied in order of priority.
+    ///
+    /// All nodes are assumed to be healthy until a request to them fails.
+    pub fn new(
+        urls: Vec<String>,
+        timeouts: Timeouts,
+        retry_policy: RetryPolicy,
+        context: RuntimeContext<E>,
+    ) -> Result<Self, String> {
+        if urls.is_empty() {
+            return Err("At least one beacon node must be supplied".to_string());
+        }
+
+        let candidates = urls
+            .into_iter()
+            .map(|url| {
+                let node = RemoteBeaconNode::new_with_timeouts(url.clone(), timeouts, retry_policy)
+                    .map_err(|e| {
+                        format!("Unable to init beacon node http client for {}: {}", url, e)
+                    })?;
+
+                metrics::set_gauge_vec(&metrics::BEACON_NODE_HEALTHY, &[&url], 1);
+
+                Ok(Candidate {
+                    url,
+                    node,
+                    healthy: AtomicBool::new(true),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                candidates,
+                preferred: AtomicUsize::new(0),
+                context,
+            }),
+        })
+    }
+
+    /// Returns the URL of the node that requests are presently sent to first.
+    pub fn preferred_url(&self) -> &str {
+        &self.candidates[self.preferred.load(Ordering::Relaxed)].url
+    }
+
+    /// Calls `func` with the preferred node, then with each of the other nodes (in order of
+    /// priority) until one of them returns `Ok`.
+    ///
+    /// Returns an error describing every failure if no node succeeds.
+    pub fn first_success<F, R>(&self, func: F) -> impl Future<Item = R::Item, Error = String>
+    where
+        F: Fn(&RemoteBeaconNode<E>) -> R + Send + 'static,
+        R: IntoFuture<Error = remote_beacon_node::Error>,
+        R::Future: Send + 'static,
+        R::Item: Send + 'static,
+    {
+        let fallback = self.clone();
+        let preferred = self.preferred.load(Ordering::Relaxed);
+        let order = std::iter::once(preferred)
+            .chain((0..self.candidates.len()).filter(move |i| *i != preferred))
+            .collect::<Vec<_>>();
+
+        loop_fn(
+            (0, vec![]),
+            move |(attempt, mut errors): (usize, Vec<String>)| {
+                let fallback = fallback.clone();
+                let index = order[attempt];
+                let is_last = attempt + 1 == order.len();
+
+                func(&fallback.candidates[index].node).into_future().then(
+                    move |result| match result {
+                        Ok(item) => {
+                            fallback.set_healthy(index);
+                            fallback.set_preferred(index);
+
+                            Ok(Loop::Break(item))
+                        }
+                        Err(e) => {
+                            fallback.set_unhealthy(index, &e);
+                            errors.push(format!("{}: {:?}", fallback.candidates[index].url, e));
+
+                            if is_last {
+                                Err(format!("All beacon nodes failed: {}", errors.join(", ")))
+                            } else {
+                                Ok(Loop::Continue((attempt + 1, errors)))
+                            }
+                        }
+                    },
+                )
+            },
+        )
+    }
+
+    /// Starts the service that periodically re-probes any unhealthy nodes and any nodes with a
+    /// higher priority than the preferred node.
+    pub fn start_update_service(&self, spec: &ChainSpec) -> Result<Signal, String> {
+        let log = self.context.log.clone();
+
+        let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+        let interval = Interval::new(Instant::now() + slot_duration, slot_duration);
+
+        let (exit_signal, exit_fut) = exit_future::signal();
+        let fallback = self.clone();
+        let log_1 = log.clone();
+        let log_2 = log.clone();
+
+        self.context.executor.spawn(
+            exit_fut
+                .until(
+                    interval
+                        .map_err(move |e| {
+                            crit! {
+                                log_1,
+                                "Timer thread failed";
+                                "error" => format!("{}", e)
+                            }
+                        })
+                        .for_each(move |_| fallback.probe_nodes()),
+                )
+                .map(move |_| info!(log_2, "Shutdown complete")),
+        );
+
+        Ok(exit_signal)
+    }
+
+    /// Requests the version of each unhealthy node and each node with a higher priority than the
+    /// preferred node, switching to the highest priority node that responds.
+    fn probe_nodes(&self) -> impl Future<Item = (), Error = ()> {
+        let fallback = self.clone();
+        let preferred = self.preferred.load(Ordering::Relaxed);
+
+        let probes = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter(|(index, candidate)| {
+                *index < preferred || !candidate.healthy.load(Ordering::Relaxed)
+            })
+            .map(|(index, candidate)| {
+                candidate
+                    .node
+                    .http
+                    .node()
+                    .get_version()
+                    .then(move |result| Ok::<_, ()>((index, result)))
+            })
+            .collect::<Vec<_>>();
+
+        future::join_all(probes).map(move |results| {
+            let mut highest_priority = None;
+
+            for (index, result) in results {
+                match result {
+                    Ok(_) => {
+                        fallback.set_healthy(index);
+                        // Results are in order of priority, so the first success is the highest.
+                        if highest_priority.is_none() {
+                            highest_priority = Some(index);
+                        }
+                    }
+                    Err(e) => fallback.set_unhealthy(index, &e),
+                }
+            }
+
+            if let Some(index) = highest_priority.filter(|index| *index < preferred) {
+                fallback.set_preferred(index);
+            }
+        })
+    }
+
+    /// Records that the node at `index` is healthy, logging if it was previously unhealthy.
+    fn set_healthy(&self, index: usize) {
+        let candidate = &self.candidates[index];
+
+        if !candidate.healthy.swap(true, Ordering::Relaxed) {
+            info!(
+                self.context.log,
+                "Beacon node is healthy";
+                "endpoint" => &candidate.url,
+            );
+        }
+
+        metrics::set_gauge_vec(&metrics::BEACON_NODE_HEALTHY, &[&candidate.url], 1);
+    }
+
+    /// Records that a request to the node at `index` failed, logging if it was previously healthy.
+    fn set_unhealthy<T: Debug>(&self, index: usize, error: &T) {
+        let candidate = &self.candidates[index];
+
+        if candidate.healthy.swap(false, Ordering::Relaxed) {
+            warn!(
+                self.context.log,
+                "Beacon node is unhealthy";
+                "error" => format!("{:?}", error),
+                "endpoint" => &candidate.url,
+            );
+        } else {
+            debug!(
+                self.context.log,
+                "Beacon node request failed";
+                "error" => format!("{:?}", error),
+                "endpoint" => &candidate.url,
+            );
+        }
+
+        metrics::set_gauge_vec(&metrics::BEACON_NODE_HEALTHY, &[&candidate.url], 0);
+    }
+
+    /// Sends future requests to the node at `index` first.
+    fn set_preferred(&self, index: usize) {
+        let previous = self.preferred.swap(index, Ordering::Relaxed);
+
+        if previous != index {
+            info!(
+                self.context.log,
+                "Switched beacon node";
+                "previous" => &self.candidates[previous].url,
+                "endpoint" => &self.candidates[index].url,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{json_response, MockBeaconNode};
+    use environment::{Environment, EnvironmentBuilder};
+    use remote_beacon_node::{Error, RequestStats};
+    use std::net::SocketAddr;
+    use std::thread;
+    use types::{Epoch, MinimalEthSpec};
+
+    /// Returns a mock beacon node which responds to every request with `version`.
+    fn version_node(addr: SocketAddr, version: &'static str) -> MockBeaconNode {
+        MockBeaconNode::start(addr, move || json_response(&version))
+    }
+
+    /// Returns a mock beacon node which waits for `delay` before responding to each request with
+    /// an empty list.
+    fn slow_node(delay: Duration) -> MockBeaconNode {
+        MockBeaconNode::start("127.0.0.1:0".parse().unwrap(), move || {
+            thread::sleep(delay);
+            json_response(&Vec::<u64>::new())
+        })
+    }
+
+    fn environment() -> Environment<MinimalEthSpec> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .and_then(|builder| builder.single_thread_tokio_runtime())
+            .and_then(|builder| builder.build())
+            .expect("should build environment")
+    }
+
+    fn get_version(
+        env: &mut Environment<MinimalEthSpec>,
+        fallback: &FallbackBeaconNode<MinimalEthSpec>,
+    ) -> Result<String, String> {
+        env.runtime()
+            .block_on(fallback.first_success(|node| node.http.node().get_version()))
+    }
+
+    #[test]
+    fn falls_back_and_recovers() {
+        let mut env = environment();
+        let primary = version_node("127.0.0.1:0".parse().unwrap(), "primary");
+        let secondary = version_node("127.0.0.1:0".parse().unwrap(), "secondary");
+
+        let fallback = FallbackBeaconNode::new(
+            vec![primary.url(), secondary.url()],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+
+        assert_eq!(get_version(&mut env, &fallback), Ok("primary".to_string()));
+        assert_eq!(fallback.preferred_url(), primary.url());
+
+        let primary_addr = primary.addr;
+        drop(primary);
+
+        assert_eq!(
+            get_version(&mut env, &fallback),
+            Ok("secondary".to_string()),
+            "should fall back when the primary is down"
+        );
+        assert_eq!(fallback.preferred_url(), secondary.url());
+        assert!(!fallback.candidates[0].healthy.load(Ordering::Relaxed));
+
+        env.runtime()
+            .block_on(fallback.probe_nodes())
+            .expect("should probe nodes");
+        assert_eq!(
+            fallback.preferred_url(),
+            secondary.url(),
+            "should not switch back whilst the primary is down"
+        );
+
+        let primary = version_node(primary_addr, "primary");
+
+        env.runtime()
+            .block_on(fallback.probe_nodes())
+            .expect("should probe nodes");
+        assert_eq!(
+            fallback.preferred_url(),
+            primary.url(),
+            "should switch back once the primary recovers"
+        );
+        assert!(fallback.candidates[0].healthy.load(Ordering::Relaxed));
+        assert_eq!(get_version(&mut env, &fallback), Ok("primary".to_string()));
+    }
+
+    #[test]
+    fn errors_when_all_nodes_fail() {
+        let mut env = environment();
+        let node = version_node("127.0.0.1:0".parse().unwrap(), "node");
+        let url = node.url();
+        drop(node);
+
+        let fallback = FallbackBeaconNode::new(
+            vec![url],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+
+        assert!(get_version(&mut env, &fallback).is_err());
+    }
+
+    #[test]
+    fn requires_a_node() {
+        let mut env = environment();
+        let result = FallbackBeaconNode::<MinimalEthSpec>::new(
+            vec![],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn honours_request_timeouts() {
+        let mut env = environment();
+        let node = slow_node(Duration::from_millis(500));
+
+        let remote = RemoteBeaconNode::<MinimalEthSpec>::new_with_timeouts(
+            node.url(),
+            Timeouts {
+                fast: Duration::from_millis(100),
+                heavy: Duration::from_secs(5),
+            },
+            RetryPolicy::none(),
+        )
+        .expect("should create remote beacon node");
+
+        let start = Instant::now();
+        match env.runtime().block_on(remote.http.node().get_version()) {
+            Err(Error::TimedOut { path, timeout }) => {
+                assert_eq!(path, "/node/version");
+                assert_eq!(timeout, Duration::from_millis(100));
+            }
+            other => panic!("fast request should time out, not {:?}", other),
+        }
+        assert!(
+            start.elapsed() < Duration::from_millis(400),
+            "should give up at the fast time-out"
+        );
+
+        let duties = env
+            .runtime()
+            .block_on(remote.http.validator().get_duties(Epoch::new(0), &[]))
+            .expect("heavy request should outlast the fast time-out");
+        assert!(duties.is_empty());
+
+        assert_eq!(
+            remote.http.request_stats(),
+            RequestStats {
+                timeouts: 1,
+                retries: 0
+            }
+        );
+    }
+
+    #[test]
+    fn retries_only_get_requests() {
+        let mut env = environment();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let node_requests = requests.clone();
+        let node = MockBeaconNode::start("127.0.0.1:0".parse().unwrap(), move || {
+            node_requests.fetch_add(1, Ordering::Relaxed);
+            (502, "Bad Gateway".to_string())
+        });
+
+        let remote = RemoteBeaconNode::<MinimalEthSpec>::new_with_timeouts(
+            node.url(),
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy {
+                retries: 2,
+                base_delay: Duration::from_millis(10),
+            },
+        )
+        .expect("should create remote beacon node");
+
+        assert!(env
+            .runtime()
+            .block_on(remote.http.node().get_version())
+            .is_err());
+        assert_eq!(
+            requests.load(Ordering::Relaxed),
+            3,
+            "should retry a failed GET request"
+        );
+
+        requests.store(0, Ordering::Relaxed);
+
+        assert!(env
+            .runtime()
+            .block_on(remote.http.validator().get_duties(Epoch::new(0), &[]))
+            .is_err());
+        assert_eq!(
+            requests.load(Ordering::Relaxed),
+            1,
+            "should not retry a failed POST request"
+        );
+
+        assert_eq!(
+            remote.http.request_stats(),
+            RequestStats {
+                timeouts: 0,
+                retries: 2
+            }
+        );
+    }
+}
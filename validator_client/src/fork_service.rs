@@ -1,24 +1,37 @@
+use crate::fallback_beacon_node::FallbackBeaconNode;
 use environment::RuntimeContext;
 use exit_future::Signal;
-use futures::{Future, Stream};
-use parking_lot::RwLock;
-use remote_beacon_node::RemoteBeaconNode;
-use slog::{crit, info, trace};
+use futures::{
+    future::{self, loop_fn, Loop},
+    sync::oneshot,
+    Future, Stream,
+};
+use parking_lot::{Mutex, RwLock};
+use slog::{crit, debug, info, trace, warn};
 use slot_clock::SlotClock;
+use std::cmp;
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::timer::Interval;
+use tokio::sync::watch;
+use tokio::timer::{Delay, Interval};
 use types::{ChainSpec, EthSpec, Fork};
 
 /// Delay this period of time after the slot starts. This allows the node to process the new slot.
 const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(80);
 
+/// The delay before retrying a failed update. The delay doubles after each subsequent failure, up
+/// to the slot duration.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The number of attempts to download the fork before `start_update_service` gives up.
+const STARTUP_UPDATE_ATTEMPTS: usize = 4;
+
 /// Builds a `ForkService`.
 pub struct ForkServiceBuilder<T, E: EthSpec> {
     fork: Option<Fork>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_node: Option<FallbackBeaconNode<E>>,
     context: Option<RuntimeContext<E>>,
 }
 
@@ -37,7 +50,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
+    pub fn beacon_node(mut self, beacon_node: FallbackBeaconNode<E>) -> Self {
         self.beacon_node = Some(beacon_node);
         self
     }
@@ -48,9 +61,13 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkServiceBuilder<T, E> {
     }
 
     pub fn build(self) -> Result<ForkService<T, E>, String> {
+        let (fork_sender, fork_receiver) = watch::channel(self.fork.clone());
+
         Ok(ForkService {
             inner: Arc::new(Inner {
                 fork: RwLock::new(self.fork),
+                fork_sender: Mutex::new(fork_sender),
+                fork_receiver,
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build ForkService without slot_clock")?,
@@ -68,12 +85,16 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkServiceBuilder<T, E> {
 /// Helper to minimise `Arc` usage.
 pub struct Inner<T, E: EthSpec> {
     fork: RwLock<Option<Fork>>,
-    beacon_node: RemoteBeaconNode<E>,
+    /// Notifies subscribers whenever `fork` changes.
+    fork_sender: Mutex<watch::Sender<Option<Fork>>>,
+    fork_receiver: watch::Receiver<Option<Fork>>,
+    beacon_node: FallbackBeaconNode<E>,
     context: RuntimeContext<E>,
     slot_clock: T,
 }
 
-/// Attempts to download the `Fork` struct from the beacon node at the start of each epoch.
+/// Attempts to download the `Fork` struct from the beacon node at the start of each epoch,
+/// retrying with an exponential backoff on failure.
 pub struct ForkService<T, E: EthSpec> {
     inner: Arc<Inner<T, E>>,
 }
@@ -100,31 +121,50 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkService<T, E> {
         self.fork.read().clone()
     }
 
-    /// Starts the service that periodically polls for the `Fork`.
+    /// Returns a receiver which yields the present fork, then each new fork as it changes.
+    pub fn subscribe(&self) -> watch::Receiver<Option<Fork>> {
+        self.fork_receiver.clone()
+    }
+
+    /// Downloads the `Fork` (retrying a few times on failure), then starts the service that
+    /// periodically polls for the `Fork`.
+    ///
+    /// Blocks the calling thread until the initial download completes, guaranteeing that
+    /// `Self::fork` is `Some` once this function returns `Ok`. Must not be called from within a
+    /// single-threaded runtime.
     pub fn start_update_service(&self, spec: &ChainSpec) -> Result<Signal, String> {
         let log = self.context.log.clone();
+        let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
 
         let duration_to_next_epoch = self
             .slot_clock
             .duration_to_next_epoch(E::slots_per_epoch())
             .ok_or_else(|| "Unable to determine duration to next epoch".to_string())?;
 
-        let interval = {
-            let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
-            Interval::new(
-                Instant::now() + duration_to_next_epoch + TIME_DELAY_FROM_SLOT,
-                slot_duration * E::slots_per_epoch() as u32,
-            )
-        };
+        // Run an initial update, waiting for it to complete before starting the updater service.
+        let (result_sender, result_receiver) = oneshot::channel();
+        self.context.executor.spawn(
+            self.update_with_retry(slot_duration, STARTUP_UPDATE_ATTEMPTS)
+                .then(move |result| {
+                    let _ = result_sender.send(result);
+                    Ok(())
+                }),
+        );
+        result_receiver
+            .wait()
+            .map_err(|_| "Initial fork update was cancelled".to_string())?
+            .map_err(|e| format!("Unable to download fork from beacon node: {}", e))?;
+
+        let interval = Interval::new(
+            Instant::now() + duration_to_next_epoch + TIME_DELAY_FROM_SLOT,
+            slot_duration * E::slots_per_epoch() as u32,
+        );
 
         let (exit_signal, exit_fut) = exit_future::signal();
         let service = self.clone();
         let log_1 = log.clone();
         let log_2 = log.clone();
 
-        // Run an immediate update before starting the updater service.
-        self.context.executor.spawn(service.clone().do_update());
-
         self.context.executor.spawn(
             exit_fut
                 .until(
@@ -136,7 +176,25 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkService<T, E> {
                                 "error" => format!("{}", e)
                             }
                         })
-                        .for_each(move |_| service.do_update().then(|_| Ok(()))),
+                        .for_each(move |_| {
+                            let log = service.context.log.clone();
+
+                            // Retries may last for up to an epoch, so run them in a separate task
+                            // to avoid delaying the interval.
+                            service.context.executor.spawn(
+                                service
+                                    .update_with_retry(slot_duration, E::slots_per_epoch() as usize)
+                                    .map_err(move |e| {
+                                        warn!(
+                                            log,
+                                            "Fork update failed";
+                                            "error" => e
+                                        )
+                                    }),
+                            );
+
+                            Ok(())
+                        }),
                 )
                 .map(move |_| info!(log_2, "Shutdown complete")),
         );
@@ -144,28 +202,168 @@ impl<T: SlotClock + 'static, E: EthSpec> ForkService<T, E> {
         Ok(exit_signal)
     }
 
-    /// Attempts to download the `Fork` from the server.
-    fn do_update(&self) -> impl Future<Item = (), Error = ()> {
-        let service_1 = self.clone();
-        let log_1 = service_1.context.log.clone();
-        let log_2 = service_1.context.log.clone();
+    /// Attempts to download the `Fork`, retrying up to `max_attempts` times with an exponential
+    /// backoff that is capped at `slot_duration`.
+    fn update_with_retry(
+        &self,
+        slot_duration: Duration,
+        max_attempts: usize,
+    ) -> impl Future<Item = (), Error = String> {
+        let service = self.clone();
+
+        loop_fn(
+            (1, INITIAL_RETRY_DELAY),
+            move |(attempt, delay): (usize, Duration)| {
+                let log = service.context.log.clone();
+
+                service.do_update().then(move |result| {
+                    let future: Box<dyn Future<Item = Loop<_, _>, Error = String> + Send> =
+                        match result {
+                            Ok(()) => Box::new(future::ok(Loop::Break(()))),
+                            Err(e) if attempt < max_attempts => {
+                                debug!(
+                                    log,
+                                    "Fork update failed, retrying";
+                                    "error" => e,
+                                    "attempt" => attempt,
+                                    "retry_delay_ms" => delay.as_millis() as u64,
+                                );
+
+                                let next_delay = cmp::min(delay * 2, slot_duration);
+
+                                Box::new(
+                                    Delay::new(Instant::now() + delay)
+                                        .map_err(|e| format!("Failed to trigger delay: {:?}", e))
+                                        .map(move |_| Loop::Continue((attempt + 1, next_delay))),
+                                )
+                            }
+                            Err(e) => {
+                                Box::new(future::err(format!("{} (attempts: {})", e, attempt)))
+                            }
+                        };
+
+                    future
+                })
+            },
+        )
+    }
+
+    /// Attempts to download the `Fork` from the server, notifying subscribers if it has changed.
+    fn do_update(&self) -> impl Future<Item = (), Error = String> {
+        let service = self.clone();
 
         self.inner
             .beacon_node
-            .http
-            .beacon()
-            .get_fork()
-            .map(move |fork| *(service_1.fork.write()) = Some(fork))
-            .map(move |_| trace!(log_1, "Fork update success"))
-            .map_err(move |e| {
-                trace!(
-                    log_2,
-                    "Fork update failed";
-                    "error" => format!("Error retrieving fork: {:?}", e)
-                )
+            .first_success(|node| node.http.beacon().get_fork())
+            .map(move |fork| {
+                let mut current = service.fork.write();
+
+                if current.as_ref() != Some(&fork) {
+                    *current = Some(fork.clone());
+
+                    // An error indicates there are no subscribers, which is fine.
+                    let _ = service.fork_sender.lock().broadcast(Some(fork));
+                }
+
+                trace!(service.context.log, "Fork update success");
             })
-            // Returning an error will stop the interval. This is not desired, a single failure
-            // should not stop all future attempts.
-            .then(|_| Ok(()))
+            .map_err(|e| format!("Error retrieving fork: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{json_response, MockBeaconNode};
+    use environment::{Environment, EnvironmentBuilder};
+    use remote_beacon_node::{RetryPolicy, Timeouts};
+    use slot_clock::TestingSlotClock;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use types::{Epoch, MinimalEthSpec, Slot};
+
+    /// The number of requests the flaky beacon node fails before it starts to succeed.
+    const FAILED_REQUESTS: usize = 2;
+
+    fn environment() -> Environment<MinimalEthSpec> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .and_then(|builder| builder.single_thread_tokio_runtime())
+            .and_then(|builder| builder.build())
+            .expect("should build environment")
+    }
+
+    /// Returns a beacon node which fails the first `FAILED_REQUESTS` requests, then responds with a
+    /// fork at the epoch in `fork_epoch`.
+    fn flaky_node(fork_epoch: Arc<AtomicU64>) -> MockBeaconNode {
+        let requests = AtomicUsize::new(0);
+
+        MockBeaconNode::start("127.0.0.1:0".parse().unwrap(), move || {
+            if requests.fetch_add(1, Ordering::Relaxed) < FAILED_REQUESTS {
+                (500, "flaky".to_string())
+            } else {
+                json_response(&Fork {
+                    epoch: Epoch::new(fork_epoch.load(Ordering::Relaxed)),
+                    ..Fork::default()
+                })
+            }
+        })
+    }
+
+    #[test]
+    fn retries_until_fork_is_known() {
+        let mut env = environment();
+        let fork_epoch = Arc::new(AtomicU64::new(0));
+        let node = flaky_node(fork_epoch.clone());
+        let spec = env.eth2_config.spec.clone();
+
+        let beacon_node = FallbackBeaconNode::new(
+            vec![node.url()],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+        let service = ForkServiceBuilder::new()
+            .slot_clock(TestingSlotClock::new(
+                Slot::new(0),
+                Duration::from_secs(0),
+                Duration::from_millis(spec.milliseconds_per_slot),
+            ))
+            .beacon_node(beacon_node)
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build fork service");
+        let subscriber = service.subscribe();
+
+        assert_eq!(service.fork(), None);
+
+        let start = Instant::now();
+        let _exit_signal = service
+            .start_update_service(&spec)
+            .expect("should start fork service");
+
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "should retry promptly, took {:?}",
+            start.elapsed()
+        );
+        assert_eq!(service.fork(), Some(Fork::default()));
+        assert_eq!(*subscriber.get_ref(), Some(Fork::default()));
+
+        fork_epoch.store(1, Ordering::Relaxed);
+        env.runtime()
+            .block_on(service.do_update())
+            .expect("should update fork");
+
+        let new_fork = Some(Fork {
+            epoch: Epoch::new(1),
+            ..Fork::default()
+        });
+        assert_eq!(service.fork(), new_fork);
+        assert_eq!(
+            *subscriber.get_ref(),
+            new_fork,
+            "should notify subscribers of the new fork"
+        );
     }
 }
@@ -0,0 +1,544 @@
+use crate::duties_service::DutiesService;
+use crate::keystores::{keystore_paths, load_keystores_from_paths, LoadedKeystores};
+use crate::validator_store::ValidatorStore;
+use environment::RuntimeContext;
+use exit_future::Signal;
+use futures::{future, future::Either, sync::oneshot, Future, IntoFuture, Stream};
+use parking_lot::Mutex;
+use slog::{crit, error, info};
+use slot_clock::SlotClock;
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::timer::Interval;
+use types::{ChainSpec, Epoch, EthSpec, PublicKey};
+
+/// Delay this period of time after the slot starts, so that the scan does not compete with the
+/// duties of the slot.
+const TIME_DELAY_FROM_SLOT: Duration = Duration::from_millis(500);
+
+/// Builds a `KeystoreService`.
+pub struct KeystoreServiceBuilder<T, E: EthSpec> {
+    validators_dir: Option<PathBuf>,
+    secrets_dir: Option<PathBuf>,
+    validator_store: Option<ValidatorStore<T, E>>,
+    duties_service: Option<DutiesService<T, E>>,
+    context: Option<RuntimeContext<E>>,
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> KeystoreServiceBuilder<T, E> {
+    pub fn new() -> Self {
+        Self {
+            validators_dir: None,
+            secrets_dir: None,
+            validator_store: None,
+            duties_service: None,
+            context: None,
+        }
+    }
+
+    pub fn validators_dir(mut self, validators_dir: PathBuf) -> Self {
+        self.validators_dir = Some(validators_dir);
+        self
+    }
+
+    pub fn secrets_dir(mut self, secrets_dir: Option<PathBuf>) -> Self {
+        self.secrets_dir = secrets_dir;
+        self
+    }
+
+    pub fn validator_store(mut self, store: ValidatorStore<T, E>) -> Self {
+        self.validator_store = Some(store);
+        self
+    }
+
+    pub fn duties_service(mut self, service: DutiesService<T, E>) -> Self {
+        self.duties_service = Some(service);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<E>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<KeystoreService<T, E>, String> {
+        Ok(KeystoreService {
+            inner: Arc::new(Inner {
+                validators_dir: self
+                    .validators_dir
+                    .ok_or_else(|| "Cannot build KeystoreService without validators_dir")?,
+                secrets_dir: self.secrets_dir,
+                validator_store: self
+                    .validator_store
+                    .ok_or_else(|| "Cannot build KeystoreService without validator_store")?,
+                duties_service: self
+                    .duties_service
+                    .ok_or_else(|| "Cannot build KeystoreService without duties_service")?,
+                context: self
+                    .context
+                    .ok_or_else(|| "Cannot build KeystoreService without runtime_context")?,
+                keystores: Mutex::new(Keystores::default()),
+            }),
+        })
+    }
+}
+
+/// The keystores known to the service.
+#[derive(Default)]
+struct Keystores {
+    /// The public keys of the enabled validators, by the path of their keystore.
+    enabled: HashMap<PathBuf, PublicKey>,
+    /// The validators whose keystore has been removed, with the epoch of the removal.
+    removed: HashMap<PathBuf, Epoch>,
+    /// The keystores which failed to load during `failed_epoch`.
+    failed: HashSet<PathBuf>,
+    failed_epoch: Option<Epoch>,
+}
+
+/// Helper to minimise `Arc` usage.
+pub struct Inner<T, E: EthSpec> {
+    validators_dir: PathBuf,
+    secrets_dir: Option<PathBuf>,
+    validator_store: ValidatorStore<T, E>,
+    duties_service: DutiesService<T, E>,
+    context: RuntimeContext<E>,
+    keystores: Mutex<Keystores>,
+}
+
+/// Rescans the validators directory at the start of each slot, so that validators may be added
+/// or removed without restarting the validator client.
+///
+/// The validators of new keystores are enabled immediately and their duties are downloaded
+/// straight away. The validators of removed keystores are disabled at the start of the next epoch.
+/// A keystore which fails to load is retried once per epoch.
+pub struct KeystoreService<T, E: EthSpec> {
+    inner: Arc<Inner<T, E>>,
+}
+
+impl<T, E: EthSpec> Clone for KeystoreService<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, E: EthSpec> Deref for KeystoreService<T, E> {
+    type Target = Inner<T, E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> KeystoreService<T, E> {
+    /// Start the service that periodically rescans the validators directory.
+    pub fn start_update_service(&self, spec: &ChainSpec) -> Result<Signal, String> {
+        let log = self.context.log.clone();
+
+        let duration_to_next_slot = self
+            .duties_service
+            .slot_clock
+            .duration_to_next_slot()
+            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+
+        let interval = {
+            let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+            Interval::new(
+                Instant::now() + duration_to_next_slot + TIME_DELAY_FROM_SLOT,
+                slot_duration,
+            )
+        };
+
+        let (exit_signal, exit_fut) = exit_future::signal();
+        let service = self.clone();
+        let log_1 = log.clone();
+        let log_2 = log;
+
+        self.context.executor.spawn(
+            exit_fut
+                .until(
+                    interval
+                        .map_err(move |e| {
+                            crit! {
+                                log_1,
+                                "Timer thread failed";
+                                "error" => format!("{}", e)
+                            }
+                        })
+                        .for_each(move |_| service.do_update().then(|_| Ok(()))),
+                )
+                .map(move |_| info!(log_2, "Shutdown complete")),
+        );
+
+        Ok(exit_signal)
+    }
+
+    /// Rescans the validators directory, then downloads the duties of any newly enabled
+    /// validators.
+    ///
+    /// The new keystores are decrypted on a separate thread, since decryption is deliberately
+    /// slow and would otherwise block the executor.
+    fn do_update(&self) -> impl Future<Item = (), Error = ()> {
+        let log_1 = self.context.log.clone();
+        let log_2 = self.context.log.clone();
+        let duties_service = self.duties_service.clone();
+        let service = self.clone();
+        let secrets_dir = self.secrets_dir.clone();
+
+        self.start_scan()
+            .into_future()
+            .and_then(move |new_paths| {
+                if new_paths.is_empty() {
+                    return Either::A(future::ok(vec![]));
+                }
+
+                let (result_sender, result_receiver) = oneshot::channel();
+                let paths = new_paths.clone();
+                thread::spawn(move || {
+                    let _ = result_sender.send(load_keystores_from_paths(
+                        paths,
+                        secrets_dir.as_ref().map(PathBuf::as_path),
+                    ));
+                });
+
+                Either::B(
+                    result_receiver
+                        .map_err(|_| "Keystore decryption was cancelled".to_string())
+                        .and_then(move |loaded| service.finish_scan(new_paths, loaded)),
+                )
+            })
+            .map_err(move |e| {
+                error!(
+                    log_1,
+                    "Failed to scan validators directory";
+                    "error" => e,
+                )
+            })
+            .and_then(move |new_pubkeys| {
+                let future: Box<dyn Future<Item = (), Error = ()> + Send> =
+                    if new_pubkeys.is_empty() {
+                        Box::new(future::ok(()))
+                    } else {
+                        Box::new(
+                            duties_service
+                                .update_validators(new_pubkeys)
+                                .map_err(move |e| {
+                                    error!(
+                                        log_2,
+                                        "Failed to get duties for new validators";
+                                        "error" => e,
+                                    )
+                                }),
+                        )
+                    };
+
+                future
+            })
+    }
+
+    /// Enables the validator of each new keystore in the validators directory and disables the
+    /// validator of each keystore that was removed prior to the current epoch.
+    ///
+    /// Returns the public keys of the newly enabled validators. An error is returned if the
+    /// directory cannot be read or if two new keystores share a public key.
+    ///
+    /// The new keystores are decrypted on the calling thread, without holding the lock on the
+    /// known keystores.
+    pub fn scan(&self) -> Result<Vec<PublicKey>, String> {
+        let new_paths = self.start_scan()?;
+        if new_paths.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let secrets_dir = self.secrets_dir.as_ref().map(PathBuf::as_path);
+        let loaded = load_keystores_from_paths(new_paths.clone(), secrets_dir);
+
+        self.finish_scan(new_paths, loaded)
+    }
+
+    /// Disables the validator of each keystore that was removed prior to the current epoch and
+    /// returns the paths of the keystores that should be loaded.
+    fn start_scan(&self) -> Result<Vec<PathBuf>, String> {
+        let log = &self.context.log;
+        let epoch = self
+            .duties_service
+            .slot_clock
+            .now()
+            .ok_or_else(|| "Unable to read slot clock".to_string())?
+            .epoch(E::slots_per_epoch());
+
+        let mut keystores = self.keystores.lock();
+
+        if keystores.failed_epoch != Some(epoch) {
+            keystores.failed.clear();
+            keystores.failed_epoch = Some(epoch);
+        }
+
+        let paths = keystore_paths(&self.validators_dir)?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        // Schedule the removal of the validators whose keystores have disappeared, cancelling the
+        // removal of any which have reappeared.
+        let Keystores {
+            enabled, removed, ..
+        } = &mut *keystores;
+        for (path, pubkey) in enabled.iter() {
+            if paths.contains(path) {
+                removed.remove(path);
+            } else if !removed.contains_key(path) {
+                info!(
+                    log,
+                    "Validator keystore removed";
+                    "info" => "the validator will be disabled at the next epoch",
+                    "validator" => format!("{:?}", pubkey),
+                    "path" => path.to_str(),
+                );
+                removed.insert(path.clone(), epoch);
+            }
+        }
+
+        let due = keystores
+            .removed
+            .iter()
+            .filter(|(_, removal_epoch)| **removal_epoch < epoch)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        for path in due {
+            keystores.removed.remove(&path);
+            if let Some(pubkey) = keystores.enabled.remove(&path) {
+                self.validator_store.remove_validator(&pubkey);
+                self.duties_service.remove_validator(&pubkey);
+                info!(
+                    log,
+                    "Disabled validator";
+                    "validator" => format!("{:?}", pubkey),
+                    "path" => path.to_str(),
+                );
+            }
+        }
+
+        Ok(paths
+            .into_iter()
+            .filter(|path| {
+                !keystores.enabled.contains_key(path) && !keystores.failed.contains(path)
+            })
+            .collect())
+    }
+
+    /// Enables the validators of the keystores `loaded` from `new_paths`, returning their public
+    /// keys.
+    fn finish_scan(
+        &self,
+        new_paths: Vec<PathBuf>,
+        loaded: Result<LoadedKeystores, String>,
+    ) -> Result<Vec<PublicKey>, String> {
+        let log = &self.context.log;
+        let mut keystores = self.keystores.lock();
+
+        let loaded = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                keystores.failed.extend(new_paths);
+                return Err(e);
+            }
+        };
+
+        for (path, e) in loaded.failures {
+            error!(
+                log,
+                "Failed to load a validator keystore";
+                "error" => e,
+                "path" => path.to_str(),
+            );
+            keystores.failed.insert(path);
+        }
+
+        let mut new_pubkeys = vec![];
+        for (path, keypair) in loaded.keypairs {
+            let pubkey = keypair.pk.clone();
+
+            // The keystore may have been enabled by another scan whilst it was being decrypted.
+            if keystores.enabled.contains_key(&path) {
+                continue;
+            }
+
+            if self.validator_store.add_validator(keypair, path.clone()) {
+                info!(
+                    log,
+                    "Enabled validator";
+                    "validator" => format!("{:?}", pubkey),
+                    "path" => path.to_str(),
+                );
+                keystores.enabled.insert(path, pubkey.clone());
+                new_pubkeys.push(pubkey);
+            } else {
+                error!(
+                    log,
+                    "Failed to load a validator keystore";
+                    "error" => "a validator with the same public key is already enabled",
+                    "path" => path.to_str(),
+                );
+                keystores.failed.insert(path);
+            }
+        }
+
+        Ok(new_pubkeys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::duties_service::DutiesServiceBuilder;
+    use crate::fallback_beacon_node::FallbackBeaconNode;
+    use crate::fork_service::ForkServiceBuilder;
+    use crate::test_utils::{
+        json_response, MockBeaconNode, KEYSTORE, KEYSTORE_PASSWORD, KEYSTORE_PUBKEY,
+    };
+    use environment::{Environment, EnvironmentBuilder};
+    use remote_beacon_node::{RetryPolicy, Timeouts, ValidatorDuty};
+    use slot_clock::TestingSlotClock;
+    use std::fs;
+    use tempdir::TempDir;
+    use types::{MinimalEthSpec, Slot};
+
+    fn environment() -> Environment<MinimalEthSpec> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .and_then(|builder| builder.single_thread_tokio_runtime())
+            .and_then(|builder| builder.build())
+            .expect("should build environment")
+    }
+
+    /// Returns a beacon node which assigns the validator of `KEYSTORE` to attest at slot 1.
+    fn duties_node() -> MockBeaconNode {
+        let pubkey =
+            PublicKey::from_bytes(&hex::decode(KEYSTORE_PUBKEY).expect("should decode public key"))
+                .expect("should parse public key");
+        let duties = vec![ValidatorDuty {
+            validator_pubkey: pubkey.into(),
+            validator_index: Some(0),
+            attestation_slot: Some(Slot::new(1)),
+            attestation_committee_index: Some(0),
+            attestation_committee_position: Some(0),
+            attestation_committee_length: Some(1),
+            attestation_committees_at_slot: Some(1),
+            block_proposal_slots: vec![],
+            attestation_dependent_root: None,
+            block_proposal_dependent_root: None,
+        }];
+
+        MockBeaconNode::start("127.0.0.1:0".parse().unwrap(), move || {
+            json_response(&duties)
+        })
+    }
+
+    #[test]
+    fn enables_new_keystores_and_disables_removed_keystores() {
+        let mut env = environment();
+        let spec = env.eth2_config.spec.clone();
+        let dir = TempDir::new("keystores").expect("should create temp dir");
+        let node = duties_node();
+
+        let beacon_node = FallbackBeaconNode::new(
+            vec![node.url()],
+            Timeouts::all(Duration::from_secs(1)),
+            RetryPolicy::none(),
+            env.core_context(),
+        )
+        .expect("should create fallback beacon node");
+        let slot_clock = || {
+            TestingSlotClock::new(
+                Slot::new(0),
+                Duration::from_secs(0),
+                Duration::from_millis(spec.milliseconds_per_slot),
+            )
+        };
+        let fork_service = ForkServiceBuilder::new()
+            .slot_clock(slot_clock())
+            .beacon_node(beacon_node.clone())
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build fork service");
+        let validator_store = ValidatorStore::empty(
+            dir.path().to_path_buf(),
+            spec.clone(),
+            fork_service,
+            env.core_context().log,
+        )
+        .expect("should create validator store");
+        let duties_service = DutiesServiceBuilder::new()
+            .slot_clock(slot_clock())
+            .validator_store(validator_store.clone())
+            .beacon_node(beacon_node)
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build duties service");
+        let service = KeystoreServiceBuilder::new()
+            .validators_dir(dir.path().to_path_buf())
+            .validator_store(validator_store.clone())
+            .duties_service(duties_service.clone())
+            .runtime_context(env.core_context())
+            .build()
+            .expect("should build keystore service");
+
+        env.runtime()
+            .block_on(service.do_update())
+            .expect("should scan empty directory");
+        assert_eq!(validator_store.num_voting_validators(), 0);
+
+        // Drop a new keystore into the directory whilst the service is running.
+        let keystore_path = dir.path().join("keystore-0.json");
+        fs::write(&keystore_path, KEYSTORE).expect("should write keystore");
+        fs::write(dir.path().join("keystore-0.pass"), KEYSTORE_PASSWORD)
+            .expect("should write password");
+
+        env.runtime()
+            .block_on(service.do_update())
+            .expect("should load new keystore");
+        assert_eq!(
+            validator_store.num_voting_validators(),
+            1,
+            "should enable the new validator"
+        );
+        assert_eq!(
+            duties_service.attester_count(Epoch::new(0)),
+            1,
+            "should download the duties of the new validator"
+        );
+
+        fs::remove_file(&keystore_path).expect("should remove keystore");
+
+        env.runtime()
+            .block_on(service.do_update())
+            .expect("should scan directory");
+        assert_eq!(
+            validator_store.num_voting_validators(),
+            1,
+            "should not disable the validator until the next epoch"
+        );
+
+        duties_service
+            .slot_clock
+            .set_slot(MinimalEthSpec::slots_per_epoch());
+        env.runtime()
+            .block_on(service.do_update())
+            .expect("should scan directory");
+        assert_eq!(
+            validator_store.num_voting_validators(),
+            0,
+            "should disable the validator at the next epoch"
+        );
+        assert_eq!(
+            duties_service.attester_count(Epoch::new(0)),
+            0,
+            "should forget the duties of the removed validator"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Loading of validator keypairs from EIP-2335 keystores.
+//!
+//! Each keystore is a `keystore-*.json` file in the validators directory. Its password is read
+//! from a sibling file with the `.pass` extension or, failing that, from a file in the secrets
+//! directory named after the `0x`-prefixed public key of the keystore.
+use eth2_keystore::Keystore;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use types::Keypair;
+
+/// The prefix of the filename of each keystore.
+pub const KEYSTORE_PREFIX: &str = "keystore-";
+/// The extension of the filename of each keystore.
+pub const KEYSTORE_EXTENSION: &str = "json";
+/// The extension of a password file which is a sibling of a keystore.
+pub const PASSWORD_EXTENSION: &str = "pass";
+
+/// The result of loading all the keystores in a directory.
+#[derive(Debug, Default)]
+pub struct LoadedKeystores {
+    /// The keypairs which were decrypted, with the path of their keystore.
+    pub keypairs: Vec<(PathBuf, Keypair)>,
+    /// The keystores which could not be loaded, with a description of the failure.
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+/// Returns the paths of all keystores in `validators_dir`, in ascending order.
+pub fn keystore_paths(validators_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut paths = fs::read_dir(validators_dir)
+        .map_err(|e| {
+            format!(
+                "Failed to read validators directory {:?}: {:?}",
+                validators_dir, e
+            )
+        })?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| is_keystore_path(path))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Loads and decrypts the keystores at `paths`.
+///
+/// A keystore which cannot be read or decrypted is recorded in `LoadedKeystores::failures`
+/// without preventing the others from loading. However, an error is returned if two keystores
+/// share a public key.
+pub fn load_keystores_from_paths(
+    paths: Vec<PathBuf>,
+    secrets_dir: Option<&Path>,
+) -> Result<LoadedKeystores, String> {
+    let mut loaded = LoadedKeystores::default();
+    let mut keystores = vec![];
+    let mut pubkeys: HashMap<String, PathBuf> = HashMap::new();
+
+    for path in paths {
+        let keystore = match Keystore::from_json_file(&path) {
+            Ok(keystore) => keystore,
+            Err(e) => {
+                loaded
+                    .failures
+                    .push((path, format!("Unable to parse keystore: {:?}", e)));
+                continue;
+            }
+        };
+
+        let pubkey = keystore.pubkey.trim_start_matches("0x").to_lowercase();
+        if let Some(other) = pubkeys.insert(pubkey.clone(), path.clone()) {
+            return Err(format!(
+                "Duplicate public key 0x{} in keystores {:?} and {:?}",
+                pubkey, other, path
+            ));
+        }
+
+        keystores.push((path, pubkey, keystore));
+    }
+
+    let results = keystores
+        .into_par_iter()
+        .map(|(path, pubkey, keystore)| {
+            let result = read_password(&path, &pubkey, secrets_dir).and_then(|password| {
+                keystore
+                    .decrypt_keypair(&password)
+                    .map_err(|e| format!("Unable to decrypt keystore: {:?}", e))
+            });
+            (path, result)
+        })
+        .collect::<Vec<_>>();
+
+    for (path, result) in results {
+        match result {
+            Ok(keypair) => loaded.keypairs.push((path, keypair)),
+            Err(e) => loaded.failures.push((path, e)),
+        }
+    }
+
+    Ok(loaded)
+}
+
+/// Returns `true` if the filename of `path` matches `keystore-*.json`.
+fn is_keystore_path(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().and_then(|ext| ext.to_str()) == Some(KEYSTORE_EXTENSION)
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with(KEYSTORE_PREFIX))
+}
+
+/// Reads the password of the keystore at `keystore_path`, stripping any trailing newline.
+fn read_password(
+    keystore_path: &Path,
+    pubkey: &str,
+    secrets_dir: Option<&Path>,
+) -> Result<Vec<u8>, String> {
+    let sibling = keystore_path.with_extension(PASSWORD_EXTENSION);
+    let password_path = if sibling.exists() {
+        sibling
+    } else if let Some(secrets_dir) = secrets_dir {
+        secrets_dir.join(format!("0x{}", pubkey))
+    } else {
+        return Err(format!("No password file found at {:?}", sibling));
+    };
+
+    let mut password = fs::read(&password_path)
+        .map_err(|e| format!("Unable to read password file {:?}: {:?}", password_path, e))?;
+    while password
+        .last()
+        .map_or(false, |b| *b == b'\n' || *b == b'\r')
+    {
+        password.pop();
+    }
+
+    Ok(password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{KEYSTORE, KEYSTORE_PASSWORD, KEYSTORE_PUBKEY};
+    use tempdir::TempDir;
+
+    fn load_keystores(
+        validators_dir: &Path,
+        secrets_dir: Option<&Path>,
+    ) -> Result<LoadedKeystores, String> {
+        load_keystores_from_paths(keystore_paths(validators_dir)?, secrets_dir)
+    }
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, contents).expect("should write file");
+        path
+    }
+
+    #[test]
+    fn loads_with_sibling_password_file() {
+        let dir = TempDir::new("keystores").expect("should create temp dir");
+        let keystore_path = write(dir.path(), "keystore-0.json", KEYSTORE);
+        write(
+            dir.path(),
+            "keystore-0.pass",
+            &format!("{}\n", KEYSTORE_PASSWORD),
+        );
+
+        let loaded = load_keystores(dir.path(), None).expect("should load keystores");
+
+        assert!(loaded.failures.is_empty(), "should not fail");
+        assert_eq!(loaded.keypairs.len(), 1, "should load one keypair");
+        assert_eq!(
+            loaded.keypairs[0].0, keystore_path,
+            "should record the path"
+        );
+        assert_eq!(
+            loaded.keypairs[0].1.pk.as_hex_string(),
+            format!("0x{}", KEYSTORE_PUBKEY),
+            "should decrypt the keypair"
+        );
+    }
+
+    #[test]
+    fn loads_with_secrets_dir() {
+        let dir = TempDir::new("keystores").expect("should create temp dir");
+        let secrets = TempDir::new("secrets").expect("should create temp dir");
+        write(dir.path(), "keystore-0.json", KEYSTORE);
+        write(
+            secrets.path(),
+            &format!("0x{}", KEYSTORE_PUBKEY),
+            KEYSTORE_PASSWORD,
+        );
+
+        let loaded =
+            load_keystores(dir.path(), Some(secrets.path())).expect("should load keystores");
+
+        assert!(loaded.failures.is_empty(), "should not fail");
+        assert_eq!(loaded.keypairs.len(), 1, "should load one keypair");
+    }
+
+    #[test]
+    fn reports_failures_without_aborting() {
+        let dir = TempDir::new("keystores").expect("should create temp dir");
+        write(dir.path(), "keystore-0.json", KEYSTORE);
+        write(dir.path(), "keystore-0.pass", "wrongpassword");
+        let invalid_path = write(dir.path(), "keystore-1.json", "{}");
+        let missing_path = write(
+            dir.path(),
+            "keystore-2.json",
+            &KEYSTORE.replace(KEYSTORE_PUBKEY, &KEYSTORE_PUBKEY.replace("96", "97")),
+        );
+        write(dir.path(), "unrelated.json", "{}");
+
+        let loaded = load_keystores(dir.path(), None).expect("should load keystores");
+        let failed_paths = loaded
+            .failures
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+
+        assert!(loaded.keypairs.is_empty(), "should not load any keypair");
+        assert_eq!(failed_paths.len(), 3, "should report each keystore");
+        assert!(
+            failed_paths.contains(&invalid_path),
+            "should report invalid"
+        );
+        assert!(
+            failed_paths.contains(&missing_path),
+            "should report missing password"
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_pubkeys() {
+        let dir = TempDir::new("keystores").expect("should create temp dir");
+        write(dir.path(), "keystore-0.json", KEYSTORE);
+        write(dir.path(), "keystore-1.json", KEYSTORE);
+
+        assert!(
+            load_keystores(dir.path(), None).is_err(),
+            "should reject duplicate public keys"
+        );
+    }
+}
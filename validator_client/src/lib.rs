@@ -1,10 +1,22 @@
+#[macro_use]
+extern crate lazy_static;
+
 mod attestation_service;
 mod block_service;
 mod cli;
+mod clock_drift_service;
 mod config;
 mod duties_service;
+pub mod exit;
+mod fallback_beacon_node;
 mod fork_service;
+mod keystore_service;
+mod keystores;
+mod metrics;
 mod notifier;
+mod slashing_protection;
+#[cfg(test)]
+mod test_utils;
 mod validator_store;
 
 pub mod validator_directory;
@@ -15,16 +27,19 @@ pub use config::{Config, KeySource};
 use attestation_service::{AttestationService, AttestationServiceBuilder};
 use block_service::{BlockService, BlockServiceBuilder};
 use clap::ArgMatches;
+use clock_drift_service::{ClockDriftService, ClockDriftServiceBuilder};
 use duties_service::{DutiesService, DutiesServiceBuilder};
 use environment::RuntimeContext;
 use exit_future::Signal;
+use fallback_beacon_node::FallbackBeaconNode;
 use fork_service::{ForkService, ForkServiceBuilder};
 use futures::{
     future::{self, loop_fn, Loop},
     Future, IntoFuture,
 };
+use keystore_service::{KeystoreService, KeystoreServiceBuilder};
 use notifier::spawn_notifier;
-use remote_beacon_node::RemoteBeaconNode;
+use remote_beacon_node::{RetryPolicy, Timeouts};
 use slog::{error, info, Logger};
 use slot_clock::SlotClock;
 use slot_clock::SystemTimeSlotClock;
@@ -37,15 +52,28 @@ use validator_store::ValidatorStore;
 /// The interval between attempts to contact the beacon node during startup.
 const RETRY_DELAY: Duration = Duration::from_secs(2);
 
-/// The global timeout for HTTP requests to the beacon node.
-const HTTP_TIMEOUT: Duration = Duration::from_secs(12);
+/// The time-outs for HTTP requests to the beacon node.
+const HTTP_TIMEOUTS: Timeouts = Timeouts {
+    fast: Duration::from_secs(4),
+    heavy: Duration::from_secs(12),
+};
+
+/// The policy for retrying failed HTTP `GET` requests to the beacon node, before falling back to
+/// another node.
+const HTTP_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    retries: 1,
+    base_delay: Duration::from_millis(250),
+};
 
 pub struct ProductionValidatorClient<T: EthSpec> {
     context: RuntimeContext<T>,
+    beacon_node: FallbackBeaconNode<T>,
     duties_service: DutiesService<SystemTimeSlotClock, T>,
     fork_service: ForkService<SystemTimeSlotClock, T>,
+    clock_drift_service: ClockDriftService<SystemTimeSlotClock, T>,
     block_service: BlockService<SystemTimeSlotClock, T>,
     attestation_service: AttestationService<SystemTimeSlotClock, T>,
+    keystore_service: Option<KeystoreService<SystemTimeSlotClock, T>>,
     exit_signals: Vec<Signal>,
 }
 
@@ -77,28 +105,33 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             log_1,
             "Starting validator client";
             "beacon_node" => &config.http_server,
+            "fallback_beacon_nodes" => config.fallback_http_servers.join(", "),
             "datadir" => format!("{:?}", config.data_dir),
         );
 
-        RemoteBeaconNode::new_with_timeout(config.http_server.clone(), HTTP_TIMEOUT)
-            .map_err(|e| format!("Unable to init beacon node http client: {}", e))
+        let beacon_node_context = context.service_context("beacon_node".into());
+
+        let beacon_node = FallbackBeaconNode::new(
+            config.http_servers(),
+            HTTP_TIMEOUTS,
+            HTTP_RETRY_POLICY,
+            beacon_node_context,
+        );
+
+        beacon_node
             .into_future()
             .and_then(move |beacon_node| wait_for_node(beacon_node, log_2))
             .and_then(|beacon_node| {
                 beacon_node
-                    .http
-                    .spec()
-                    .get_eth2_config()
+                    .first_success(|node| node.http.spec().get_eth2_config())
                     .map(|eth2_config| (beacon_node, eth2_config))
-                    .map_err(|e| format!("Unable to read eth2 config from beacon node: {:?}", e))
+                    .map_err(|e| format!("Unable to read eth2 config from beacon node: {}", e))
             })
             .and_then(|(beacon_node, eth2_config)| {
                 beacon_node
-                    .http
-                    .beacon()
-                    .get_genesis_time()
+                    .first_success(|node| node.http.beacon().get_genesis_time())
                     .map(|genesis_time| (beacon_node, eth2_config, genesis_time))
-                    .map_err(|e| format!("Unable to read genesis time from beacon node: {:?}", e))
+                    .map_err(|e| format!("Unable to read genesis time from beacon node: {}", e))
             })
             .and_then(move |(beacon_node, remote_eth2_config, genesis_time)| {
                 SystemTime::now()
@@ -175,6 +208,14 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                     .runtime_context(context.service_context("fork".into()))
                     .build()?;
 
+                let clock_drift_service = ClockDriftServiceBuilder::new()
+                    .threshold(config.clock_drift_threshold)
+                    .genesis_time(genesis_time)
+                    .slot_clock(slot_clock.clone())
+                    .beacon_node(beacon_node.clone())
+                    .runtime_context(context.service_context("clock_drift".into()))
+                    .build()?;
+
                 let validator_store: ValidatorStore<SystemTimeSlotClock, T> =
                     match &config.key_source {
                         // Load pre-existing validators from the data dir.
@@ -197,14 +238,15 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                                 log.clone(),
                             )?
                         }
+                        // The keystores are loaded by the keystore service, once it is built.
+                        KeySource::Keystores { validators_dir, .. } => ValidatorStore::empty(
+                            validators_dir.clone(),
+                            context.eth2_config.spec.clone(),
+                            fork_service.clone(),
+                            log.clone(),
+                        )?,
                     };
 
-                info!(
-                    log,
-                    "Loaded validator keypair store";
-                    "voting_validators" => validator_store.num_voting_validators()
-                );
-
                 let duties_service = DutiesServiceBuilder::new()
                     .slot_clock(slot_clock.clone())
                     .validator_store(validator_store.clone())
@@ -213,6 +255,34 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                     .allow_unsynced_beacon_node(config.allow_unsynced_beacon_node)
                     .build()?;
 
+                let keystore_service = match &config.key_source {
+                    KeySource::Keystores {
+                        validators_dir,
+                        secrets_dir,
+                    } => {
+                        let keystore_service = KeystoreServiceBuilder::new()
+                            .validators_dir(validators_dir.clone())
+                            .secrets_dir(secrets_dir.clone())
+                            .validator_store(validator_store.clone())
+                            .duties_service(duties_service.clone())
+                            .runtime_context(context.service_context("keystore".into()))
+                            .build()?;
+                        keystore_service.scan()?;
+                        Some(keystore_service)
+                    }
+                    _ => None,
+                };
+
+                info!(
+                    log,
+                    "Loaded validator keypair store";
+                    "voting_validators" => validator_store.num_voting_validators()
+                );
+                metrics::set_gauge(
+                    &metrics::ENABLED_VALIDATORS,
+                    validator_store.num_voting_validators() as i64,
+                );
+
                 let block_service = BlockServiceBuilder::new()
                     .duties_service(duties_service.clone())
                     .slot_clock(slot_clock.clone())
@@ -225,32 +295,47 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
                     .duties_service(duties_service.clone())
                     .slot_clock(slot_clock)
                     .validator_store(validator_store)
-                    .beacon_node(beacon_node)
+                    .beacon_node(beacon_node.clone())
                     .runtime_context(context.service_context("attestation".into()))
                     .build()?;
 
                 Ok(Self {
                     context,
+                    beacon_node,
                     duties_service,
                     fork_service,
+                    clock_drift_service,
                     block_service,
                     attestation_service,
+                    keystore_service,
                     exit_signals: vec![],
                 })
             })
     }
 
     pub fn start_service(&mut self) -> Result<(), String> {
-        let duties_exit = self
-            .duties_service
+        let beacon_node_exit = self
+            .beacon_node
             .start_update_service(&self.context.eth2_config.spec)
-            .map_err(|e| format!("Unable to start duties service: {}", e))?;
+            .map_err(|e| format!("Unable to start beacon node fallback service: {}", e))?;
 
+        // The fork service blocks until the fork is known, ensuring it is available to the other
+        // services before they start.
         let fork_exit = self
             .fork_service
             .start_update_service(&self.context.eth2_config.spec)
             .map_err(|e| format!("Unable to start fork service: {}", e))?;
 
+        let clock_drift_exit = self
+            .clock_drift_service
+            .start_update_service(&self.context.eth2_config.spec)
+            .map_err(|e| format!("Unable to start clock drift service: {}", e))?;
+
+        let duties_exit = self
+            .duties_service
+            .start_update_service(&self.context.eth2_config.spec)
+            .map_err(|e| format!("Unable to start duties service: {}", e))?;
+
         let block_exit = self
             .block_service
             .start_update_service(&self.context.eth2_config.spec)
@@ -265,32 +350,38 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             spawn_notifier(self).map_err(|e| format!("Failed to start notifier: {}", e))?;
 
         self.exit_signals = vec![
-            duties_exit,
+            beacon_node_exit,
             fork_exit,
+            clock_drift_exit,
+            duties_exit,
             block_exit,
             attestation_exit,
             notifier_exit,
         ];
 
+        if let Some(keystore_service) = &self.keystore_service {
+            let keystore_exit = keystore_service
+                .start_update_service(&self.context.eth2_config.spec)
+                .map_err(|e| format!("Unable to start keystore service: {}", e))?;
+            self.exit_signals.push(keystore_exit);
+        }
+
         Ok(())
     }
 }
 
-/// Request the version from the node, looping back and trying again on failure. Exit once the node
-/// has been contacted.
+/// Request the version from the nodes, looping back and trying again on failure. Exit once any
+/// node has been contacted.
 fn wait_for_node<E: EthSpec>(
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_node: FallbackBeaconNode<E>,
     log: Logger,
-) -> impl Future<Item = RemoteBeaconNode<E>, Error = String> {
-    // Try to get the version string from the node, looping until success is returned.
+) -> impl Future<Item = FallbackBeaconNode<E>, Error = String> {
+    // Try to get the version string from the nodes, looping until success is returned.
     loop_fn(beacon_node.clone(), move |beacon_node| {
         let log = log.clone();
         beacon_node
             .clone()
-            .http
-            .node()
-            .get_version()
-            .map_err(|e| format!("{:?}", e))
+            .first_success(|node| node.http.node().get_version())
             .then(move |result| {
                 let future: Box<dyn Future<Item = Loop<_, _>, Error = String> + Send> = match result
                 {
@@ -299,6 +390,7 @@ fn wait_for_node<E: EthSpec>(
                             log,
                             "Connected to beacon node";
                             "version" => version,
+                            "endpoint" => beacon_node.preferred_url(),
                         );
 
                         Box::new(future::ok(Loop::Break(beacon_node)))
@@ -307,7 +399,7 @@ fn wait_for_node<E: EthSpec>(
                         error!(
                             log,
                             "Unable to connect to beacon node";
-                            "error" => format!("{:?}", e),
+                            "error" => e,
                         );
 
                         Box::new(
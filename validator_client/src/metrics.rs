@@ -0,0 +1,68 @@
+pub use lighthouse_metrics::*;
+
+lazy_static! {
+    /*
+     * Block publication
+     */
+    pub static ref BLOCK_PUBLISH_RETRIES: Result<IntCounter> = try_create_int_counter(
+        "vc_block_publish_retries_total",
+        "Count of times a signed block publish was retried after a failed request"
+    );
+    pub static ref BLOCK_PUBLISH_LATE: Result<IntCounter> = try_create_int_counter(
+        "vc_block_publish_late_total",
+        "Count of signed blocks that were only published after one or more retries"
+    );
+    pub static ref BLOCK_PUBLISH_FAILURES: Result<IntCounter> = try_create_int_counter(
+        "vc_block_publish_failures_total",
+        "Count of signed blocks that could not be published before retries were exhausted"
+    );
+
+    /*
+     * Slashing protection
+     */
+    pub static ref SLASHING_PROTECTION_REFUSED_BLOCKS: Result<IntCounter> = try_create_int_counter(
+        "vc_slashing_protection_refused_blocks_total",
+        "Count of blocks that were not signed because they could result in a slashing"
+    );
+    pub static ref SLASHING_PROTECTION_REFUSED_ATTESTATIONS: Result<IntCounter> = try_create_int_counter(
+        "vc_slashing_protection_refused_attestations_total",
+        "Count of attestations that were not signed because they could result in a slashing"
+    );
+
+    /*
+     * Attestation timing
+     */
+    pub static ref ATTESTATIONS_TRIGGERED_BY_BLOCK: Result<IntCounter> = try_create_int_counter(
+        "vc_attestations_triggered_by_block_total",
+        "Count of slots where attestation started early because the block for the slot arrived"
+    );
+    pub static ref ATTESTATIONS_TRIGGERED_BY_DEADLINE: Result<IntCounter> = try_create_int_counter(
+        "vc_attestations_triggered_by_deadline_total",
+        "Count of slots where attestation started at one-third of the slot without a block for the slot"
+    );
+
+    /*
+     * Validators
+     */
+    pub static ref ENABLED_VALIDATORS: Result<IntGauge> = try_create_int_gauge(
+        "vc_enabled_validators_count",
+        "Number of validators which are enabled for signing"
+    );
+
+    /*
+     * Beacon node fallback
+     */
+    pub static ref BEACON_NODE_HEALTHY: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "vc_beacon_node_healthy",
+        "Set to 1 if the most recent request to the beacon node endpoint succeeded, otherwise 0",
+        &["endpoint"]
+    );
+
+    /*
+     * Clock drift
+     */
+    pub static ref CLOCK_DRIFT_MILLISECONDS: Result<IntGauge> = try_create_int_gauge(
+        "vc_clock_drift_milliseconds",
+        "Milliseconds by which the local clock is ahead of the beacon node (negative if behind)"
+    );
+}
@@ -0,0 +1,755 @@
+use parking_lot::{Mutex, RwLock};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{prelude::*, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tree_hash::TreeHash;
+use types::{AttestationData, Epoch, Hash256, PublicKey, Slot};
+
+/// The name of the file in the validator data directory that stores the signing history.
+pub const SLASHING_PROTECTION_FILENAME: &str = "slashing_protection.ssz";
+
+/// The number of records per validator that may be appended to the file before it is compacted.
+const COMPACTION_RECORDS_PER_VALIDATOR: usize = 8;
+
+/// The length of the little-endian length prefix of each record in the file.
+const RECORD_LENGTH_BYTES: usize = 4;
+
+/// The reason that a message was deemed unsafe to sign.
+#[derive(Debug, PartialEq)]
+pub enum NotSafe {
+    /// A block has already been signed at or after this slot.
+    PreviousBlockSlot { previous: Slot, slot: Slot },
+    /// A different attestation has already been signed with the same target epoch.
+    DoubleVote { target: Epoch },
+    /// The attestation surrounds a previously signed attestation.
+    SurroundingVote { previous: SignedAttestation },
+    /// The attestation is surrounded by a previously signed attestation.
+    SurroundedVote { previous: SignedAttestation },
+    /// The attestation is not later than the attestations pruned from the history, so it cannot be
+    /// checked against them.
+    PrunedVote { pruned: PrunedEpochs },
+    /// The signing history could not be persisted, so signing is not safe.
+    PersistError(String),
+}
+
+/// The source and target of an attestation which has been signed by a validator.
+#[derive(Debug, PartialEq, Clone, Encode, Decode)]
+pub struct SignedAttestation {
+    pub source_epoch: Epoch,
+    pub target_epoch: Epoch,
+    /// The tree hash root of the `AttestationData`, allowing an identical attestation to be
+    /// signed again.
+    pub data_root: Hash256,
+}
+
+impl SignedAttestation {
+    fn from_attestation_data(data: &AttestationData) -> Self {
+        Self {
+            source_epoch: data.source.epoch,
+            target_epoch: data.target.epoch,
+            data_root: data.tree_hash_root(),
+        }
+    }
+
+    /// Returns `true` if `self` surrounds `other`.
+    fn surrounds(&self, other: &Self) -> bool {
+        self.source_epoch < other.source_epoch && other.target_epoch < self.target_epoch
+    }
+}
+
+/// The highest source and target epochs of the attestations that have been pruned from a
+/// validator's history.
+#[derive(Debug, PartialEq, Clone, Copy, Encode, Decode)]
+pub struct PrunedEpochs {
+    pub source_epoch: Epoch,
+    pub target_epoch: Epoch,
+}
+
+/// The messages signed by a single validator.
+#[derive(Debug, PartialEq, Clone, Default, Encode, Decode)]
+struct SigningHistory {
+    last_block_slot: Option<Slot>,
+    attestations: Vec<SignedAttestation>,
+    pruned: Option<PrunedEpochs>,
+}
+
+impl SigningHistory {
+    /// Returns `Ok` if it is safe to sign a block at `slot`.
+    fn check_block(&self, slot: Slot) -> Result<(), NotSafe> {
+        match self.last_block_slot {
+            Some(previous) if slot <= previous => {
+                Err(NotSafe::PreviousBlockSlot { previous, slot })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `Ok(true)` if it is safe to sign `attestation`, or `Ok(false)` if it is safe
+    /// because the very same attestation has already been signed.
+    fn check_attestation(&self, attestation: &SignedAttestation) -> Result<bool, NotSafe> {
+        for previous in &self.attestations {
+            if previous.target_epoch == attestation.target_epoch {
+                if previous.data_root == attestation.data_root {
+                    return Ok(false);
+                } else {
+                    return Err(NotSafe::DoubleVote {
+                        target: attestation.target_epoch,
+                    });
+                }
+            } else if attestation.surrounds(previous) {
+                return Err(NotSafe::SurroundingVote {
+                    previous: previous.clone(),
+                });
+            } else if previous.surrounds(attestation) {
+                return Err(NotSafe::SurroundedVote {
+                    previous: previous.clone(),
+                });
+            }
+        }
+
+        // Any attestation that would conflict with a pruned attestation has either a source epoch
+        // lower than the highest pruned source or a target epoch no higher than the highest pruned
+        // target.
+        if let Some(pruned) = self.pruned {
+            if attestation.source_epoch < pruned.source_epoch
+                || attestation.target_epoch <= pruned.target_epoch
+            {
+                return Err(NotSafe::PrunedVote { pruned });
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Adds the messages recorded in `other` to `self`, then prunes the attestations that are no
+    /// longer needed.
+    fn merge(&mut self, other: SigningHistory) {
+        self.last_block_slot = self.last_block_slot.max(other.last_block_slot);
+        for attestation in other.attestations {
+            if !self.attestations.contains(&attestation) {
+                self.attestations.push(attestation);
+            }
+        }
+        self.pruned = match (self.pruned, other.pruned) {
+            (Some(a), Some(b)) => Some(PrunedEpochs {
+                source_epoch: a.source_epoch.max(b.source_epoch),
+                target_epoch: a.target_epoch.max(b.target_epoch),
+            }),
+            (a, b) => a.or(b),
+        };
+        self.prune();
+    }
+
+    /// Removes the attestations with a target epoch prior to the highest source epoch that has
+    /// been signed.
+    ///
+    /// A validator's source epoch only moves past epochs that have been justified, so the
+    /// attestations prior to it are not needed to sign the attestations that follow. The highest
+    /// source and target of the pruned attestations are kept so that any attestation conflicting
+    /// with them is still refused.
+    fn prune(&mut self) {
+        let horizon = match self.attestations.iter().map(|a| a.source_epoch).max() {
+            Some(horizon) => horizon,
+            None => return,
+        };
+
+        let mut pruned = self.pruned;
+        self.attestations.retain(|attestation| {
+            if attestation.target_epoch < horizon {
+                pruned = Some(match pruned {
+                    Some(pruned) => PrunedEpochs {
+                        source_epoch: pruned.source_epoch.max(attestation.source_epoch),
+                        target_epoch: pruned.target_epoch.max(attestation.target_epoch),
+                    },
+                    None => PrunedEpochs {
+                        source_epoch: attestation.source_epoch,
+                        target_epoch: attestation.target_epoch,
+                    },
+                });
+                false
+            } else {
+                true
+            }
+        });
+        self.pruned = pruned;
+    }
+}
+
+/// A record in the slashing protection file.
+///
+/// The file is a sequence of these records, each prefixed by its length as a little-endian `u32`.
+/// A validator's history is the merge of all of its records.
+#[derive(Encode, Decode)]
+struct PersistedSigningHistory {
+    pubkey: PublicKey,
+    history: SigningHistory,
+}
+
+impl PersistedSigningHistory {
+    /// Returns the length-prefixed SSZ encoding of `self`.
+    fn as_record_bytes(&self) -> Vec<u8> {
+        let bytes = self.as_ssz_bytes();
+        let mut record = Vec::with_capacity(RECORD_LENGTH_BYTES + bytes.len());
+        record.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        record.extend_from_slice(&bytes);
+        record
+    }
+
+    /// Decodes the records in `bytes`.
+    ///
+    /// An incomplete record at the end of `bytes` is ignored, since it was never acknowledged as
+    /// written (e.g., the process stopped part-way through writing it).
+    fn from_records_bytes(mut bytes: &[u8]) -> Result<Vec<Self>, String> {
+        let mut records = vec![];
+
+        while bytes.len() >= RECORD_LENGTH_BYTES {
+            let mut length = [0; RECORD_LENGTH_BYTES];
+            length.copy_from_slice(&bytes[0..RECORD_LENGTH_BYTES]);
+            let end = RECORD_LENGTH_BYTES + u32::from_le_bytes(length) as usize;
+
+            if bytes.len() < end {
+                break;
+            }
+
+            records.push(
+                Self::from_ssz_bytes(&bytes[RECORD_LENGTH_BYTES..end])
+                    .map_err(|e| format!("Unable to decode slashing protection file: {:?}", e))?,
+            );
+            bytes = &bytes[end..];
+        }
+
+        Ok(records)
+    }
+}
+
+/// The slashing protection file, opened for appending.
+struct Journal {
+    file: File,
+    /// The length of the file after the last successful write.
+    len: u64,
+    /// The number of records in the file.
+    records: usize,
+}
+
+impl Journal {
+    /// Appends `record` to the file, syncing it to disk before returning.
+    fn append(&mut self, record: &PersistedSigningHistory) -> Result<(), String> {
+        let bytes = record.as_record_bytes();
+
+        match self
+            .file
+            .write_all(&bytes)
+            .and_then(|_| self.file.sync_data())
+        {
+            Ok(()) => {
+                self.len += bytes.len() as u64;
+                self.records += 1;
+                Ok(())
+            }
+            Err(e) => {
+                // Drop any partially written record, so that later records may still be read.
+                let _ = self
+                    .file
+                    .set_len(self.len)
+                    .and_then(|_| self.file.seek(SeekFrom::Start(self.len)));
+                Err(format!("Unable to write slashing protection file: {:?}", e))
+            }
+        }
+    }
+}
+
+/// Records the blocks and attestations signed by each validator, refusing to sign any message that
+/// could result in the validator being slashed.
+///
+/// Each message is appended to the file on disk before each check returns `Ok`, so that it
+/// survives restarts of the validator client. The file is compacted (via an atomic rename) when it
+/// is opened and whenever it has grown to several records per validator.
+///
+/// Each validator's history has its own lock, so that the validators may sign concurrently. The
+/// locks are always taken in the order: `histories`, a single validator's history, `journal`.
+pub struct SlashingProtection {
+    path: PathBuf,
+    histories: RwLock<HashMap<PublicKey, Arc<Mutex<SigningHistory>>>>,
+    journal: Mutex<Journal>,
+}
+
+impl SlashingProtection {
+    /// Loads the signing history from `path`, or creates an empty history if the file does not
+    /// exist.
+    pub fn open_or_create(path: PathBuf) -> Result<Self, String> {
+        let mut histories: HashMap<PublicKey, SigningHistory> = HashMap::new();
+
+        if path.exists() {
+            let mut bytes = vec![];
+            File::open(&path)
+                .and_then(|mut file| file.read_to_end(&mut bytes))
+                .map_err(|e| format!("Unable to read slashing protection file: {:?}", e))?;
+
+            for record in PersistedSigningHistory::from_records_bytes(&bytes)? {
+                histories
+                    .entry(record.pubkey)
+                    .or_default()
+                    .merge(record.history);
+            }
+        }
+
+        let journal = Self::write_compacted(&path, histories.iter())?;
+
+        Ok(Self {
+            path,
+            histories: RwLock::new(
+                histories
+                    .into_iter()
+                    .map(|(pubkey, history)| (pubkey, Arc::new(Mutex::new(history))))
+                    .collect(),
+            ),
+            journal: Mutex::new(journal),
+        })
+    }
+
+    /// Returns `Ok` if it is safe for `pubkey` to sign a block at `slot`, recording the block as
+    /// signed.
+    pub fn check_and_insert_block(&self, pubkey: &PublicKey, slot: Slot) -> Result<(), NotSafe> {
+        let history = self.history(pubkey);
+        let mut history = history.lock();
+
+        history.check_block(slot)?;
+
+        self.journal
+            .lock()
+            .append(&PersistedSigningHistory {
+                pubkey: pubkey.clone(),
+                history: SigningHistory {
+                    last_block_slot: Some(slot),
+                    ..SigningHistory::default()
+                },
+            })
+            .map_err(NotSafe::PersistError)?;
+
+        history.last_block_slot = Some(slot);
+        drop(history);
+
+        self.compact_if_needed().map_err(NotSafe::PersistError)
+    }
+
+    /// Returns `Ok` if it is safe for `pubkey` to sign an attestation with `data`, recording the
+    /// attestation as signed.
+    pub fn check_and_insert_attestation(
+        &self,
+        pubkey: &PublicKey,
+        data: &AttestationData,
+    ) -> Result<(), NotSafe> {
+        let history = self.history(pubkey);
+        let mut history = history.lock();
+        let attestation = SignedAttestation::from_attestation_data(data);
+
+        if !history.check_attestation(&attestation)? {
+            // Signing the same message again is safe and the history is unchanged.
+            return Ok(());
+        }
+
+        self.journal
+            .lock()
+            .append(&PersistedSigningHistory {
+                pubkey: pubkey.clone(),
+                history: SigningHistory {
+                    attestations: vec![attestation.clone()],
+                    ..SigningHistory::default()
+                },
+            })
+            .map_err(NotSafe::PersistError)?;
+
+        history.attestations.push(attestation);
+        history.prune();
+        drop(history);
+
+        self.compact_if_needed().map_err(NotSafe::PersistError)
+    }
+
+    /// Returns the history of `pubkey`, creating an empty one if it is not yet known.
+    fn history(&self, pubkey: &PublicKey) -> Arc<Mutex<SigningHistory>> {
+        if let Some(history) = self.histories.read().get(pubkey) {
+            return history.clone();
+        }
+
+        self.histories
+            .write()
+            .entry(pubkey.clone())
+            .or_default()
+            .clone()
+    }
+
+    /// Rewrites the file with a single record per validator if it has grown to more than
+    /// `COMPACTION_RECORDS_PER_VALIDATOR` records per validator.
+    fn compact_if_needed(&self) -> Result<(), String> {
+        let limit = |validators: usize| COMPACTION_RECORDS_PER_VALIDATOR * validators.max(1);
+
+        let validators = self.histories.read().len();
+        if self.journal.lock().records <= limit(validators) {
+            return Ok(());
+        }
+
+        let histories = self.histories.write();
+
+        // Another thread may have compacted the file whilst the lock was released.
+        if self.journal.lock().records <= limit(histories.len()) {
+            return Ok(());
+        }
+
+        // Lock every history before the journal, so that no record can be appended that is not
+        // included in the compacted file.
+        let locked = histories
+            .iter()
+            .map(|(pubkey, history)| (pubkey, history.lock()))
+            .collect::<Vec<_>>();
+        let mut journal = self.journal.lock();
+
+        *journal = Self::write_compacted(
+            &self.path,
+            locked.iter().map(|(pubkey, history)| (*pubkey, &**history)),
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes a single record for each of `histories` to a temporary file, then renames it over
+    /// `path`, returning the new file opened for appending.
+    fn write_compacted<'a>(
+        path: &Path,
+        histories: impl Iterator<Item = (&'a PublicKey, &'a SigningHistory)>,
+    ) -> Result<Journal, String> {
+        let mut bytes = vec![];
+        let mut records = 0;
+        for (pubkey, history) in histories {
+            bytes.extend_from_slice(
+                &PersistedSigningHistory {
+                    pubkey: pubkey.clone(),
+                    history: history.clone(),
+                }
+                .as_record_bytes(),
+            );
+            records += 1;
+        }
+
+        let temp_path = path.with_extension("ssz.tmp");
+
+        // The handle used to write the temporary file remains valid after the rename, so it is
+        // used for all further appends.
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(&bytes)?;
+                file.sync_all()?;
+                fs::rename(&temp_path, path)?;
+                Ok(Journal {
+                    file,
+                    len: bytes.len() as u64,
+                    records,
+                })
+            })
+            .map_err(|e| format!("Unable to write slashing protection file: {:?}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+    use types::{test_utils::generate_deterministic_keypair, Checkpoint};
+
+    fn attestation_data(source: u64, target: u64) -> AttestationData {
+        AttestationData {
+            slot: Slot::new(0),
+            index: 0,
+            beacon_block_root: Hash256::zero(),
+            source: Checkpoint {
+                epoch: Epoch::new(source),
+                root: Hash256::zero(),
+            },
+            target: Checkpoint {
+                epoch: Epoch::new(target),
+                root: Hash256::zero(),
+            },
+        }
+    }
+
+    fn slashing_protection(temp_dir: &TempDir) -> SlashingProtection {
+        SlashingProtection::open_or_create(temp_dir.path().join(SLASHING_PROTECTION_FILENAME))
+            .expect("should open slashing protection")
+    }
+
+    #[test]
+    fn blocks() {
+        let temp_dir = TempDir::new("slashing_protection").expect("should create test dir");
+        let protection = slashing_protection(&temp_dir);
+        let pubkey = generate_deterministic_keypair(0).pk;
+
+        assert_eq!(
+            protection.check_and_insert_block(&pubkey, Slot::new(2)),
+            Ok(())
+        );
+        assert_eq!(
+            protection.check_and_insert_block(&pubkey, Slot::new(2)),
+            Err(NotSafe::PreviousBlockSlot {
+                previous: Slot::new(2),
+                slot: Slot::new(2)
+            }),
+            "should not sign at the same slot"
+        );
+        assert!(
+            protection
+                .check_and_insert_block(&pubkey, Slot::new(1))
+                .is_err(),
+            "should not sign at an earlier slot"
+        );
+        assert_eq!(
+            protection.check_and_insert_block(&pubkey, Slot::new(3)),
+            Ok(())
+        );
+        assert_eq!(
+            protection.check_and_insert_block(&generate_deterministic_keypair(1).pk, Slot::new(1)),
+            Ok(()),
+            "other validators should be unaffected"
+        );
+    }
+
+    #[test]
+    fn double_votes() {
+        let temp_dir = TempDir::new("slashing_protection").expect("should create test dir");
+        let protection = slashing_protection(&temp_dir);
+        let pubkey = generate_deterministic_keypair(0).pk;
+
+        let data = attestation_data(1, 2);
+        let mut conflicting = data.clone();
+        conflicting.beacon_block_root = Hash256::repeat_byte(1);
+
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &data),
+            Ok(())
+        );
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &data),
+            Ok(()),
+            "should sign the same attestation again"
+        );
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &conflicting),
+            Err(NotSafe::DoubleVote {
+                target: Epoch::new(2)
+            })
+        );
+    }
+
+    #[test]
+    fn surrounding_votes() {
+        let temp_dir = TempDir::new("slashing_protection").expect("should create test dir");
+        let protection = slashing_protection(&temp_dir);
+        let pubkey = generate_deterministic_keypair(0).pk;
+
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &attestation_data(2, 3)),
+            Ok(())
+        );
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &attestation_data(1, 4)),
+            Err(NotSafe::SurroundingVote {
+                previous: SignedAttestation::from_attestation_data(&attestation_data(2, 3))
+            })
+        );
+    }
+
+    #[test]
+    fn surrounded_votes() {
+        let temp_dir = TempDir::new("slashing_protection").expect("should create test dir");
+        let protection = slashing_protection(&temp_dir);
+        let pubkey = generate_deterministic_keypair(0).pk;
+
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &attestation_data(1, 4)),
+            Ok(())
+        );
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &attestation_data(2, 3)),
+            Err(NotSafe::SurroundedVote {
+                previous: SignedAttestation::from_attestation_data(&attestation_data(1, 4))
+            })
+        );
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &attestation_data(4, 5)),
+            Ok(()),
+            "should sign a subsequent, non-surrounding vote"
+        );
+    }
+
+    #[test]
+    fn history_survives_restart() {
+        let temp_dir = TempDir::new("slashing_protection").expect("should create test dir");
+        let pubkey = generate_deterministic_keypair(0).pk;
+
+        {
+            let protection = slashing_protection(&temp_dir);
+            assert_eq!(
+                protection.check_and_insert_block(&pubkey, Slot::new(5)),
+                Ok(())
+            );
+            assert_eq!(
+                protection.check_and_insert_attestation(&pubkey, &attestation_data(2, 3)),
+                Ok(())
+            );
+        }
+
+        let protection = slashing_protection(&temp_dir);
+
+        assert!(
+            protection
+                .check_and_insert_block(&pubkey, Slot::new(5))
+                .is_err(),
+            "should remember the signed block"
+        );
+        assert!(
+            protection
+                .check_and_insert_attestation(&pubkey, &attestation_data(1, 4))
+                .is_err(),
+            "should remember the signed attestation"
+        );
+    }
+
+    #[test]
+    fn prunes_attestations_prior_to_source() {
+        let temp_dir = TempDir::new("slashing_protection").expect("should create test dir");
+        let pubkey = generate_deterministic_keypair(0).pk;
+
+        {
+            let protection = slashing_protection(&temp_dir);
+            for epoch in 0..4 {
+                assert_eq!(
+                    protection
+                        .check_and_insert_attestation(&pubkey, &attestation_data(epoch, epoch + 1)),
+                    Ok(())
+                );
+            }
+            assert_eq!(
+                protection.history(&pubkey).lock().attestations.len(),
+                2,
+                "should prune the attestations with a target prior to epoch 3"
+            );
+        }
+
+        let protection = slashing_protection(&temp_dir);
+        let pruned = PrunedEpochs {
+            source_epoch: Epoch::new(1),
+            target_epoch: Epoch::new(2),
+        };
+
+        assert_eq!(protection.history(&pubkey).lock().pruned, Some(pruned));
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &attestation_data(2, 2)),
+            Err(NotSafe::PrunedVote { pruned }),
+            "should not sign a vote that may double a pruned vote"
+        );
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &attestation_data(1, 2)),
+            Err(NotSafe::PrunedVote { pruned }),
+            "should not sign a pruned vote again"
+        );
+        assert_eq!(
+            protection.check_and_insert_attestation(&pubkey, &attestation_data(4, 5)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn appends_and_compacts_file() {
+        let temp_dir = TempDir::new("slashing_protection").expect("should create test dir");
+        let path = temp_dir.path().join(SLASHING_PROTECTION_FILENAME);
+        let protection = slashing_protection(&temp_dir);
+        let pubkey = generate_deterministic_keypair(0).pk;
+        let file_len = || fs::metadata(&path).expect("should read metadata").len();
+
+        assert_eq!(
+            protection.check_and_insert_block(&pubkey, Slot::new(1)),
+            Ok(())
+        );
+        let record_len = file_len();
+        assert!(record_len > 0, "should append the block");
+
+        for slot in 2..=COMPACTION_RECORDS_PER_VALIDATOR as u64 {
+            assert_eq!(
+                protection.check_and_insert_block(&pubkey, Slot::new(slot)),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            file_len(),
+            record_len * COMPACTION_RECORDS_PER_VALIDATOR as u64,
+            "should append a record for each block"
+        );
+
+        assert_eq!(
+            protection.check_and_insert_block(&pubkey, Slot::new(100)),
+            Ok(())
+        );
+        assert_eq!(file_len(), record_len, "should compact to a single record");
+        assert_eq!(protection.journal.lock().records, 1);
+
+        assert_eq!(
+            protection.check_and_insert_block(&pubkey, Slot::new(101)),
+            Ok(())
+        );
+        drop(protection);
+
+        assert_eq!(
+            slashing_protection(&temp_dir)
+                .history(&pubkey)
+                .lock()
+                .last_block_slot,
+            Some(Slot::new(101)),
+            "should read the records appended after compaction"
+        );
+    }
+
+    #[test]
+    fn ignores_incomplete_record() {
+        let temp_dir = TempDir::new("slashing_protection").expect("should create test dir");
+        let path = temp_dir.path().join(SLASHING_PROTECTION_FILENAME);
+        let pubkey = generate_deterministic_keypair(0).pk;
+
+        assert_eq!(
+            slashing_protection(&temp_dir).check_and_insert_block(&pubkey, Slot::new(5)),
+            Ok(())
+        );
+
+        let record = PersistedSigningHistory {
+            pubkey: pubkey.clone(),
+            history: SigningHistory {
+                last_block_slot: Some(Slot::new(6)),
+                ..SigningHistory::default()
+            },
+        }
+        .as_record_bytes();
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&record[..record.len() - 1]))
+            .expect("should write incomplete record");
+
+        let protection = slashing_protection(&temp_dir);
+
+        assert_eq!(
+            protection.history(&pubkey).lock().last_block_slot,
+            Some(Slot::new(5))
+        );
+        assert_eq!(
+            protection.check_and_insert_block(&pubkey, Slot::new(6)),
+            Ok(()),
+            "should sign the block that was not acknowledged"
+        );
+    }
+}
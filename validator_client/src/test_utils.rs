@@ -0,0 +1,184 @@
+//! Provides a mock beacon node HTTP server and a keystore for testing the validator client
+//! services.
+
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// The password of `KEYSTORE`.
+pub const KEYSTORE_PASSWORD: &str = "testpassword";
+/// The public key of `KEYSTORE`, hex-encoded without a `0x` prefix.
+pub const KEYSTORE_PUBKEY: &str = "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07";
+
+/// The pbkdf2 test vector from EIP-2335.
+pub const KEYSTORE: &str = r#"
+{
+    "crypto": {
+        "kdf": {
+            "function": "pbkdf2",
+            "params": {
+                "dklen": 32,
+                "c": 262144,
+                "prf": "hmac-sha256",
+                "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+            },
+            "message": ""
+        },
+        "checksum": {
+            "function": "sha256",
+            "params": {},
+            "message": "18b148af8e52920318084560fd766f9d09587b4915258dec0676cba5b0da09d8"
+        },
+        "cipher": {
+            "function": "aes-128-ctr",
+            "params": {
+                "iv": "264daa3f303d7259501c93d997d84fe6"
+            },
+            "message": "a9249e0ca7315836356e4c7440361ff22b9fe71e2e2ed34fc1eb03976924ed48"
+        }
+    },
+    "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+    "path": "m/12381/60/0/0",
+    "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+    "version": 4
+}
+"#;
+
+/// A request received by a `MockBeaconNode`.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// The HTTP method, e.g., `GET`.
+    pub method: String,
+    /// The path of the request, including any query string.
+    pub path: String,
+    /// The body of the request, which is empty unless a `Content-Length` was given.
+    pub body: Vec<u8>,
+}
+
+/// A HTTP server which answers every request with the status code and body returned by a
+/// `responder` function. The server is stopped when dropped.
+pub struct MockBeaconNode {
+    pub addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockBeaconNode {
+    /// Starts a server listening on `addr`. Use port `0` to select any free port.
+    pub fn start<F>(addr: SocketAddr, mut responder: F) -> Self
+    where
+        F: FnMut() -> (u16, String) + Send + 'static,
+    {
+        Self::start_with_requests(addr, move |_| responder())
+    }
+
+    /// As per `start`, except that `responder` is given each request that the server receives.
+    pub fn start_with_requests<F>(addr: SocketAddr, mut responder: F) -> Self
+    where
+        F: FnMut(&MockRequest) -> (u16, String) + Send + 'static,
+    {
+        let listener = TcpListener::bind(addr).expect("should bind mock beacon node");
+        let addr = listener.local_addr().expect("should have local addr");
+        listener
+            .set_nonblocking(true)
+            .expect("should set non-blocking");
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let handle = thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => respond(stream, &mut responder),
+                    Err(_) => thread::sleep(Duration::from_millis(10)),
+                }
+            }
+        });
+
+        Self {
+            addr,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Returns the URL of the server, suitable for a `RemoteBeaconNode`.
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+}
+
+impl Drop for MockBeaconNode {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Returns a successful response containing `value`, encoded as JSON.
+pub fn json_response<T: Serialize>(value: &T) -> (u16, String) {
+    (
+        200,
+        serde_json::to_string(value).expect("should encode response"),
+    )
+}
+
+/// Reads a single request from `stream` and writes the response from `responder`.
+fn respond<F: FnMut(&MockRequest) -> (u16, String)>(mut stream: TcpStream, responder: &mut F) {
+    let _ = stream.set_nonblocking(false);
+
+    let mut request = vec![];
+    let mut buf = [0; 1024];
+    let header_len = loop {
+        if let Some(i) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break i + 4;
+        }
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => request.extend_from_slice(&buf[0..n]),
+        }
+    };
+
+    let head = String::from_utf8_lossy(&request[0..header_len]).to_string();
+    let mut request_line = head.lines().next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("").to_string();
+    let path = request_line.next().unwrap_or("").to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, ':');
+            let name = parts.next()?;
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                parts.next()?.trim().parse::<usize>().ok()
+            } else {
+                None
+            }
+        })
+        .next()
+        .unwrap_or(0);
+
+    let mut body = request.split_off(header_len);
+    while body.len() < content_length {
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => body.extend_from_slice(&buf[0..n]),
+        }
+    }
+
+    let (status, response) = responder(&MockRequest { method, path, body });
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        response.len(),
+        response
+    );
+}
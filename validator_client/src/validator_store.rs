@@ -1,8 +1,10 @@
 use crate::fork_service::ForkService;
+use crate::metrics;
+use crate::slashing_protection::{SlashingProtection, SLASHING_PROTECTION_FILENAME};
 use crate::validator_directory::{ValidatorDirectory, ValidatorDirectoryBuilder};
 use parking_lot::RwLock;
 use rayon::prelude::*;
-use slog::{error, Logger};
+use slog::{crit, error, Logger};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
 use std::fs::read_dir;
@@ -12,13 +14,15 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tempdir::TempDir;
 use types::{
-    Attestation, BeaconBlock, ChainSpec, Domain, Epoch, EthSpec, Fork, PublicKey, Signature,
-    SignedBeaconBlock, SignedRoot,
+    AggregateAndProof, Attestation, BeaconBlock, ChainSpec, Domain, Epoch, EthSpec, Fork, Keypair,
+    PublicKey, SelectionProof, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot,
+    Slot,
 };
 
 #[derive(Clone)]
 pub struct ValidatorStore<T, E: EthSpec> {
     validators: Arc<RwLock<HashMap<PublicKey, ValidatorDirectory>>>,
+    slashing_protection: Arc<SlashingProtection>,
     spec: Arc<ChainSpec>,
     log: Logger,
     temp_dir: Option<Arc<TempDir>>,
@@ -33,6 +37,9 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         fork_service: ForkService<T, E>,
         log: Logger,
     ) -> Result<Self, String> {
+        let slashing_protection =
+            SlashingProtection::open_or_create(base_dir.join(SLASHING_PROTECTION_FILENAME))?;
+
         let validator_key_values = read_dir(&base_dir)
             .map_err(|e| format!("Failed to read base directory {:?}: {:?}", base_dir, e))?
             .collect::<Vec<_>>()
@@ -66,6 +73,30 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
 
         Ok(Self {
             validators: Arc::new(RwLock::new(HashMap::from_par_iter(validator_key_values))),
+            slashing_protection: Arc::new(slashing_protection),
+            spec: Arc::new(spec),
+            log,
+            temp_dir: None,
+            fork_service,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Creates a store without any validators, which may be added later with `add_validator`.
+    ///
+    /// The slashing protection database is stored in `base_dir`.
+    pub fn empty(
+        base_dir: PathBuf,
+        spec: ChainSpec,
+        fork_service: ForkService<T, E>,
+        log: Logger,
+    ) -> Result<Self, String> {
+        let slashing_protection =
+            SlashingProtection::open_or_create(base_dir.join(SLASHING_PROTECTION_FILENAME))?;
+
+        Ok(Self {
+            validators: Arc::new(RwLock::new(HashMap::new())),
+            slashing_protection: Arc::new(slashing_protection),
             spec: Arc::new(spec),
             log,
             temp_dir: None,
@@ -83,6 +114,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         let temp_dir = TempDir::new("insecure_validator")
             .map_err(|e| format!("Unable to create temp dir: {:?}", e))?;
         let data_dir = PathBuf::from(temp_dir.path());
+        let slashing_protection =
+            SlashingProtection::open_or_create(data_dir.join(SLASHING_PROTECTION_FILENAME))?;
 
         let validators = validator_indices
             .par_iter()
@@ -107,6 +140,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
 
         Ok(Self {
             validators: Arc::new(RwLock::new(HashMap::from_iter(validators))),
+            slashing_protection: Arc::new(slashing_protection),
             spec: Arc::new(spec),
             log,
             temp_dir: Some(Arc::new(temp_dir)),
@@ -127,6 +161,41 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         self.validators.read().len()
     }
 
+    /// Starts signing with `voting_keypair`, which was loaded from `directory`.
+    ///
+    /// Returns `false` if a validator with the same public key is already enabled, in which case
+    /// the store is unchanged.
+    pub fn add_validator(&self, voting_keypair: Keypair, directory: PathBuf) -> bool {
+        let mut validators = self.validators.write();
+        if validators.contains_key(&voting_keypair.pk) {
+            return false;
+        }
+
+        validators.insert(
+            voting_keypair.pk.clone(),
+            ValidatorDirectory {
+                directory,
+                voting_keypair: Some(voting_keypair),
+                withdrawal_keypair: None,
+                deposit_data: None,
+            },
+        );
+        metrics::set_gauge(&metrics::ENABLED_VALIDATORS, validators.len() as i64);
+
+        true
+    }
+
+    /// Stops signing with the validator with `voting_pubkey`.
+    ///
+    /// Returns `false` if the validator was not enabled.
+    pub fn remove_validator(&self, voting_pubkey: &PublicKey) -> bool {
+        let mut validators = self.validators.write();
+        let removed = validators.remove(voting_pubkey).is_some();
+        metrics::set_gauge(&metrics::ENABLED_VALIDATORS, validators.len() as i64);
+
+        removed
+    }
+
     fn fork(&self) -> Option<Fork> {
         if self.fork_service.fork().is_none() {
             error!(
@@ -156,13 +225,29 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_pubkey: &PublicKey,
         block: BeaconBlock<E>,
     ) -> Option<SignedBeaconBlock<E>> {
-        // TODO: check for slashing.
         self.validators
             .read()
             .get(validator_pubkey)
             .and_then(|validator_dir| {
                 let voting_keypair = validator_dir.voting_keypair.as_ref()?;
-                Some(block.sign(&voting_keypair.sk, &self.fork()?, &self.spec))
+                let fork = self.fork()?;
+
+                if let Err(e) = self
+                    .slashing_protection
+                    .check_and_insert_block(validator_pubkey, block.slot)
+                {
+                    metrics::inc_counter(&metrics::SLASHING_PROTECTION_REFUSED_BLOCKS);
+                    crit!(
+                        self.log,
+                        "Refusing to sign slashable block";
+                        "reason" => format!("{:?}", e),
+                        "slot" => block.slot.as_u64(),
+                        "validator" => format!("{:?}", validator_pubkey),
+                    );
+                    return None;
+                }
+
+                Some(block.sign(&voting_keypair.sk, &fork, &self.spec))
             })
     }
 
@@ -172,18 +257,34 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         validator_committee_position: usize,
         attestation: &mut Attestation<E>,
     ) -> Option<()> {
-        // TODO: check for slashing.
         self.validators
             .read()
             .get(validator_pubkey)
             .and_then(|validator_dir| {
                 let voting_keypair = validator_dir.voting_keypair.as_ref()?;
+                let fork = self.fork()?;
+
+                if let Err(e) = self
+                    .slashing_protection
+                    .check_and_insert_attestation(validator_pubkey, &attestation.data)
+                {
+                    metrics::inc_counter(&metrics::SLASHING_PROTECTION_REFUSED_ATTESTATIONS);
+                    crit!(
+                        self.log,
+                        "Refusing to sign slashable attestation";
+                        "reason" => format!("{:?}", e),
+                        "source_epoch" => attestation.data.source.epoch.as_u64(),
+                        "target_epoch" => attestation.data.target.epoch.as_u64(),
+                        "validator" => format!("{:?}", validator_pubkey),
+                    );
+                    return None;
+                }
 
                 attestation
                     .sign(
                         &voting_keypair.sk,
                         validator_committee_position,
-                        &self.fork()?,
+                        &fork,
                         &self.spec,
                     )
                     .map_err(|e| {
@@ -198,4 +299,53 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                 Some(())
             })
     }
+
+    /// Signs `slot`, producing the proof which determines whether the validator is an aggregator
+    /// at that slot.
+    pub fn produce_selection_proof(
+        &self,
+        validator_pubkey: &PublicKey,
+        slot: Slot,
+    ) -> Option<SelectionProof> {
+        self.validators
+            .read()
+            .get(validator_pubkey)
+            .and_then(|validator_dir| {
+                let voting_keypair = validator_dir.voting_keypair.as_ref()?;
+
+                Some(SelectionProof::new::<E>(
+                    slot,
+                    &voting_keypair.sk,
+                    &self.fork()?,
+                    &self.spec,
+                ))
+            })
+    }
+
+    /// Signs `aggregate` on behalf of the aggregator with `validator_index`.
+    ///
+    /// The aggregate is already signed by its attesters, so there is no slashing risk.
+    pub fn sign_aggregate_and_proof(
+        &self,
+        validator_pubkey: &PublicKey,
+        validator_index: u64,
+        aggregate: Attestation<E>,
+        selection_proof: SelectionProof,
+    ) -> Option<SignedAggregateAndProof<E>> {
+        self.validators
+            .read()
+            .get(validator_pubkey)
+            .and_then(|validator_dir| {
+                let voting_keypair = validator_dir.voting_keypair.as_ref()?;
+
+                Some(
+                    AggregateAndProof {
+                        aggregator_index: validator_index,
+                        aggregate,
+                        selection_proof: selection_proof.into(),
+                    }
+                    .sign(&voting_keypair.sk, &self.fork()?, &self.spec),
+                )
+            })
+    }
 }